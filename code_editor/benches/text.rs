@@ -0,0 +1,118 @@
+// Measures the cost of single edits on a very large file, both on the bare `Text` and through a
+// `Session`, which also updates tokens, indent state, wrap data and the layout.
+//
+// Run with `cargo bench -p makepad-code-editor --bench text`.
+
+use {
+    makepad_code_editor::{
+        decoration::DecorationSet,
+        selection::Affinity,
+        session::SelectionMode,
+        text::{Change, Length, Position, Text},
+        Document, Session,
+    },
+    std::{
+        hint::black_box,
+        time::Instant,
+    },
+};
+
+const LINE_COUNT: usize = 100_000;
+const ITERATION_COUNT: usize = 1000;
+
+fn source() -> String {
+    let mut source = String::new();
+    for index in 0..LINE_COUNT / 5 {
+        source.push_str(&format!("fn function_{}(value: usize) -> usize {{\n", index));
+        source.push_str("    // Returns the value, doubled.\n");
+        source.push_str("    let doubled = value * 2;\n");
+        source.push_str("    doubled\n");
+        source.push_str("}\n");
+    }
+    source
+}
+
+fn bench(name: &str, mut f: impl FnMut(usize)) {
+    let start = Instant::now();
+    for index in 0..ITERATION_COUNT {
+        f(index);
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<40} {:>10.2?} per iteration",
+        name,
+        elapsed / ITERATION_COUNT as u32
+    );
+}
+
+fn start_of_line(line_index: usize) -> Position {
+    Position {
+        line_index,
+        byte_index: 0,
+    }
+}
+
+fn main() {
+    let source = source();
+
+    let start = Instant::now();
+    let text = black_box(Text::from(source.as_str()));
+    println!("{:<40} {:>10.2?}", "load 100k lines", start.elapsed());
+
+    let mut text = text;
+    bench("text: insert char", |index| {
+        let position = start_of_line(index * 97 % LINE_COUNT);
+        text.apply_change(Change::Insert(position, Text::from('x')));
+    });
+    bench("text: insert and delete line", |index| {
+        let position = start_of_line(index * 97 % LINE_COUNT);
+        let inserted = Text::from("let a = 1;\n");
+        let length = inserted.length();
+        text.apply_change(Change::Insert(position, inserted));
+        text.apply_change(Change::Delete(position, length));
+    });
+    bench("text: delete and restore 100 lines", |index| {
+        let position = start_of_line(index * 97 % (LINE_COUNT - 100));
+        let length = Length {
+            line_count: 100,
+            byte_count: 0,
+        };
+        let deleted = text.slice(position, length);
+        text.apply_change(Change::Delete(position, length));
+        text.apply_change(Change::Insert(position, deleted));
+    });
+
+    let start = Instant::now();
+    let mut session = Session::new(Document::new(
+        source.as_str().into(),
+        DecorationSet::new(),
+        "rs",
+    ));
+    println!("{:<40} {:>10.2?}", "session: open 100k lines", start.elapsed());
+
+    let edit = |session: &mut Session, index: usize, f: &dyn Fn(&Session)| {
+        session.set_selection(
+            start_of_line(index * 97 % (LINE_COUNT - 100)),
+            Affinity::Before,
+            SelectionMode::Simple,
+        );
+        f(session);
+        session.handle_changes();
+    };
+    bench("session: type char", |index| {
+        edit(&mut session, index, &|session| session.insert(Text::from('x')));
+    });
+    bench("session: enter", |index| {
+        edit(&mut session, index, &|session| session.enter());
+    });
+    bench("session: backspace", |index| {
+        edit(&mut session, index, &|session| session.backspace());
+    });
+    bench("session: open block comment", |index| {
+        edit(&mut session, index, &|session| session.insert(Text::from("/*")));
+    });
+    bench("session: undo", |_| {
+        session.undo();
+        session.handle_changes();
+    });
+}
@@ -1,6 +1,7 @@
 use {
     crate::{
         decoration::{Decoration, DecorationType},
+        diff::{Hunk, HunkKind},
         layout::{BlockElement, WrappedElement},
         selection::Affinity,
         session::{SelectionMode, Session},
@@ -37,6 +38,12 @@ live_design! {
         delimiter_highlight: #f,
         error_decoration: #f00,
         warning_decoration: #0f0,
+        search_match_decoration: #fc0,
+        remote_cursor_decoration: #c678dd,
+        added_line_marker: #587c0c,
+        modified_line_marker: #0c7d9d,
+        removed_line_marker: #94151b,
+        line_annotation: #6a6a6a,
     }
 
     DrawIndentGuide = {{DrawIndentGuide}} {
@@ -51,6 +58,9 @@ live_design! {
 
     DrawDecoration = {{DrawDecoration}} {
         fn pixel(self) -> vec4 {
+            if self.fill > 0.0 {
+                return vec4(self.color.rgb * self.color.a * self.fill, self.color.a * self.fill);
+            }
             let transformed_pos = vec2(self.pos.x, self.pos.y + 0.03 * sin(self.pos.x * self.rect_size.x));
             let cx = Sdf2d::viewport(transformed_pos * self.rect_size);
             cx.move_to(0.0, self.rect_size.y - 1.0);
@@ -142,6 +152,9 @@ live_design! {
            // draw_depth: 1.0,
             color: #5,
         }
+        draw_line_marker: {
+            draw_depth: 1.0,
+        }
         draw_decoration: {
           //  draw_depth: 2.0,
         }
@@ -169,6 +182,22 @@ live_design! {
             }
         }
 
+        draw_popup_bg: {
+            draw_depth: 5.0,
+            color: #2B2B2B,
+        }
+
+        draw_popup_selection: {
+            draw_depth: 5.5,
+            color: #264F78,
+        }
+
+        draw_popup_text: {
+            draw_depth: 6.0,
+            text_style: <THEME_FONT_CODE> {},
+            color: #C0C0C0,
+        }
+
         animator: {
             blink = {
                 default: off
@@ -226,11 +255,15 @@ pub struct CodeEditor {
     #[live] draw_text: DrawCodeText,
     #[live] token_colors: TokenColors,
     #[live] draw_indent_guide: DrawIndentGuide,
+    #[live] draw_line_marker: DrawColor,
     #[live] draw_decoration: DrawDecoration,
     #[live] draw_selection: DrawSelection,
     #[live] draw_cursor: DrawColor,
     #[live] draw_cursor_bg: DrawColor,
     #[live] draw_bg: DrawColor,
+    #[live] draw_popup_bg: DrawColor,
+    #[live] draw_popup_selection: DrawColor,
+    #[live] draw_popup_text: DrawText,
     #[rust(KeepCursorInView::Off)] keep_cursor_in_view: KeepCursorInView,
     #[rust] last_cursor_screen_pos: Option<DVec2>,
 
@@ -248,6 +281,33 @@ pub struct CodeEditor {
     #[animator] animator: Animator,
 
     #[rust] blink_timer: Timer,
+
+    #[live(0.6)] hover_delay: f64,
+
+    #[rust] hover_timer: Timer,
+    #[rust] hover_abs: DVec2,
+    #[rust] popup: Option<Popup>,
+    // a line index and a text to show after the end of that line, such as who changed it last
+    #[rust] line_annotation: Option<(usize, String)>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CompletionItem {
+    pub label: String,
+    pub detail: String,
+    pub insert_text: String,
+}
+
+enum Popup {
+    Hover {
+        position: Position,
+        text: String,
+    },
+    Completion {
+        position: Position,
+        items: Vec<CompletionItem>,
+        selected_index: usize,
+    },
 }
 
 enum KeepCursorInView {
@@ -276,7 +336,32 @@ impl KeepCursorInView {
     }
 }
 impl LiveHook for CodeEditor {
+    fn after_new_from_doc(&mut self, cx: &mut Cx) {
+        cx.clear_key_bindings(live_id!(code_editor));
+        for (command, name, keys) in COMMANDS {
+            cx.register_command(*command, name);
+            if let Err(e) = cx.bind_command(live_id!(code_editor), *command, keys, Some(live_id!(code_editor))) {
+                error!("{}", e);
+            }
+        }
+    }
 }
+
+// the editor commands and their default keys, they are bound in the code_editor scope so a
+// keymap can move them without touching the rest of the application
+const COMMANDS: &[(LiveId, &str, &str)] = &[
+    (live_id!(decrease_font_size), "Decrease Font Size", "Primary+Minus"),
+    (live_id!(reset_font_size), "Reset Font Size", "Primary+0"),
+    (live_id!(increase_font_size), "Increase Font Size", "Primary+Equals"),
+    (live_id!(toggle_word_wrap), "Toggle Word Wrap", "Primary+W"),
+    (live_id!(add_next_occurrence), "Add Next Occurrence", "Primary+D"),
+    (live_id!(select_all_occurrences), "Select All Occurrences", "Primary+Shift+L"),
+    (live_id!(find_next), "Find Next", "Primary+G"),
+    (live_id!(find_prev), "Find Previous", "Primary+Shift+G"),
+    (live_id!(go_to_definition), "Go to Definition", "F12"),
+    (live_id!(undo), "Undo", "Primary+Z"),
+    (live_id!(redo), "Redo", "Primary+Shift+Z"),
+];
 /*
 impl LiveHook for CodeEditor {
     fn before_live_design(cx: &mut Cx) {
@@ -477,6 +562,7 @@ impl CodeEditor {
         self.draw_indent_guide_layer(cx, session);
         self.draw_decoration_layer(cx, session);
         self.draw_selection_layer(cx, session);
+        self.draw_popup(cx, session);
 
         // Get the last added selection.
         // Get the normalized cursor position. To go from normalized to screen position, multiply by
@@ -544,6 +630,177 @@ impl CodeEditor {
         self.blink_timer = cx.start_timeout(self.blink_speed)
     }
 
+    pub fn set_line_annotation(&mut self, cx: &mut Cx, annotation: Option<(usize, String)>) {
+        if self.line_annotation != annotation {
+            self.line_annotation = annotation;
+            self.redraw(cx);
+        }
+    }
+
+    pub fn show_hover(&mut self, cx: &mut Cx, position: Position, text: String) {
+        self.popup = if text.trim().is_empty() {
+            None
+        } else {
+            Some(Popup::Hover { position, text })
+        };
+        self.redraw(cx);
+    }
+
+    pub fn show_completions(
+        &mut self,
+        cx: &mut Cx,
+        session: &Session,
+        items: Vec<CompletionItem>,
+    ) {
+        let (position, _) = word_before_cursor(session);
+        self.popup = if items.is_empty() {
+            None
+        } else {
+            Some(Popup::Completion {
+                position,
+                items,
+                selected_index: 0,
+            })
+        };
+        self.redraw(cx);
+    }
+
+    pub fn close_popup(&mut self, cx: &mut Cx) {
+        if self.popup.take().is_some() {
+            self.redraw(cx);
+        }
+    }
+
+    fn matching_completions(&self, session: &Session) -> Vec<&CompletionItem> {
+        let Some(Popup::Completion { items, .. }) = &self.popup else {
+            return Vec::new();
+        };
+        let (_, prefix) = word_before_cursor(session);
+        items
+            .iter()
+            .filter(|item| item.label.starts_with(&prefix))
+            .collect()
+    }
+
+    fn accept_completion(&mut self, session: &mut Session) -> bool {
+        let Some(Popup::Completion { selected_index, .. }) = &self.popup else {
+            return false;
+        };
+        let selected_index = *selected_index;
+        let Some(item) = self.matching_completions(session).get(selected_index).cloned().cloned()
+        else {
+            return false;
+        };
+        self.popup = None;
+        let (start, _) = word_before_cursor(session);
+        let cursor = cursor_position(session);
+        session.set_selection(start, Affinity::Before, SelectionMode::Simple);
+        session.move_to(cursor, Affinity::After);
+        session.insert(item.insert_text.as_str().into());
+        true
+    }
+
+    // Keys that go to the completion popup instead of the text while it is open.
+    fn handle_popup_key(
+        &mut self,
+        cx: &mut Cx,
+        hit: &Hit,
+        session: &mut Session,
+        actions: &mut Vec<CodeEditorAction>,
+    ) -> bool {
+        let Some(Popup::Completion { .. }) = &self.popup else {
+            return false;
+        };
+        let Hit::KeyDown(KeyEvent { key_code, .. }) = hit else {
+            return false;
+        };
+        let item_count = self.matching_completions(session).len();
+        match key_code {
+            KeyCode::Escape => {
+                self.close_popup(cx);
+            }
+            KeyCode::ArrowUp | KeyCode::ArrowDown if item_count > 0 => {
+                if let Some(Popup::Completion { selected_index, .. }) = &mut self.popup {
+                    *selected_index = if *key_code == KeyCode::ArrowUp {
+                        (*selected_index + item_count - 1) % item_count
+                    } else {
+                        (*selected_index + 1) % item_count
+                    };
+                }
+                self.redraw(cx);
+            }
+            KeyCode::ReturnKey | KeyCode::Tab if item_count > 0 => {
+                if self.accept_completion(session) {
+                    actions.push(CodeEditorAction::TextDidChange);
+                    self.keep_cursor_in_view = KeepCursorInView::Once;
+                    self.reset_cursor_blinker(cx);
+                }
+                self.redraw(cx);
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    // returns whether the command moved the cursor
+    fn handle_command(
+        &mut self,
+        cx: &mut Cx,
+        command: LiveId,
+        session: &mut Session,
+        actions: &mut Vec<CodeEditorAction>,
+    ) -> bool {
+        match command {
+            live_id!(decrease_font_size) => {
+                self.decrease_font_size();
+                self.redraw(cx);
+            }
+            live_id!(reset_font_size) => {
+                self.reset_font_size();
+                self.redraw(cx);
+            }
+            live_id!(increase_font_size) => {
+                self.increase_font_size();
+                self.redraw(cx);
+            }
+            live_id!(toggle_word_wrap) => {
+                self.word_wrap = !self.word_wrap;
+                self.redraw(cx);
+            }
+            live_id!(add_next_occurrence) => if session.add_next_occurrence() {
+                self.redraw(cx);
+                return true
+            }
+            live_id!(select_all_occurrences) => if session.select_all_occurrences() {
+                self.redraw(cx);
+                return true
+            }
+            live_id!(find_next) => if session.find_next() {
+                self.redraw(cx);
+                return true
+            }
+            live_id!(find_prev) => if session.find_prev() {
+                self.redraw(cx);
+                return true
+            }
+            live_id!(go_to_definition) => {
+                actions.push(CodeEditorAction::DefinitionRequested(cursor_position(session)));
+            }
+            live_id!(undo) => if session.undo() {
+                cx.redraw_all();
+                actions.push(CodeEditorAction::TextDidChange);
+                return true
+            }
+            live_id!(redo) => if session.redo() {
+                self.redraw(cx);
+                actions.push(CodeEditorAction::TextDidChange);
+                return true
+            }
+            _ => ()
+        }
+        false
+    }
+    
     pub fn handle_event(
         &mut self,
         cx: &mut Cx,
@@ -568,12 +825,30 @@ impl CodeEditor {
             }
             self.blink_timer = cx.start_timeout(self.blink_speed)
         }
+        if self.hover_timer.is_event(event).is_some() {
+            let ((position, _), is_in_gutter) = self.pick(session, self.hover_abs);
+            if !is_in_gutter {
+                actions.push(CodeEditorAction::HoverRequested(position));
+            }
+        }
         let mut keyboard_moved_cursor = false;
-        match event.hits(cx, self.scroll_bars.area()) {
+        if let Event::Command(command) = event {
+            if cx.has_key_focus(self.scroll_bars.area()) {
+                keyboard_moved_cursor = self.handle_command(cx, *command, session, &mut actions);
+            }
+        }
+        let hit = event.hits(cx, self.scroll_bars.area());
+        let hit = if self.handle_popup_key(cx, &hit, session, &mut actions) {
+            Hit::Nothing
+        } else {
+            hit
+        };
+        match hit {
             Hit::KeyFocusLost(_) => {
                 self.animator_play(cx, id!(focus.off));
             }
             Hit::KeyFocus(_) => {
+                cx.set_command_scope(Some(live_id!(code_editor)));
                 self.animator_play(cx, id!(focus.on));
             }
             Hit::KeyDown(KeyEvent {
@@ -598,54 +873,11 @@ impl CodeEditor {
                 self.redraw(cx);
             }
             Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::Minus,
-                modifiers: KeyModifiers { control, logo, .. },
-                ..
-            }) => {
-                if control || logo {
-                    self.decrease_font_size();
-                    self.redraw(cx);
-                }
-            }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::Key0,
-                modifiers: KeyModifiers { control, logo, .. },
-                ..
-            }) => {
-                if control || logo {
-                    self.reset_font_size();
-                    self.redraw(cx);
-                }
-            }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::Equals,
-                modifiers: KeyModifiers { control, logo, .. },
-                ..
-            }) => {
-                if control || logo {
-                    self.increase_font_size();
-                    self.redraw(cx);
-                }
-            }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::KeyW,
-                modifiers: KeyModifiers { control, logo, .. },
-                ..
-            }) => {
-                if control || logo {
-                    self.word_wrap = !self.word_wrap;
-                    self.redraw(cx);
-                }
-            }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::KeyA,
-                modifiers: KeyModifiers {control, logo, ..},
+                key_code: KeyCode::Space,
+                modifiers: KeyModifiers { control: true, .. },
                 ..
             }) => {
-                if control || logo {
-                    //session.select_all();
-                    self.redraw(cx);
-                }
+                actions.push(CodeEditorAction::CompletionRequested(cursor_position(session)));
             }
             Hit::KeyDown(KeyEvent {
                 key_code: KeyCode::ArrowLeft,
@@ -753,6 +985,9 @@ impl CodeEditor {
                 self.redraw(cx);
                 keyboard_moved_cursor = true;
                 actions.push(CodeEditorAction::TextDidChange);
+                if input == "." {
+                    actions.push(CodeEditorAction::CompletionRequested(cursor_position(session)));
+                }
             }
             Hit::TextInput(TextInputEvent {
                 ref input,
@@ -821,38 +1056,6 @@ impl CodeEditor {
                 keyboard_moved_cursor = true;
                 self.redraw(cx);
             }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::KeyZ,
-                modifiers:
-                    KeyModifiers {
-                        logo: true,
-                        shift: false,
-                        ..
-                    },
-                ..
-            }) => {
-                if session.undo() {
-                    cx.redraw_all();
-                    actions.push(CodeEditorAction::TextDidChange);
-                    keyboard_moved_cursor = true;
-                }
-            }
-            Hit::KeyDown(KeyEvent {
-                key_code: KeyCode::KeyZ,
-                modifiers:
-                    KeyModifiers {
-                        logo: true,
-                        shift: true,
-                        ..
-                    },
-                ..
-            }) => {
-                if session.redo() {
-                    self.redraw(cx);
-                    actions.push(CodeEditorAction::TextDidChange);
-                    keyboard_moved_cursor = true;
-                }
-            }
             Hit::FingerDown(FingerDownEvent {
                 abs,
                 tap_count,
@@ -860,13 +1063,19 @@ impl CodeEditor {
                     KeyModifiers {
                         alt: false,
                         shift: false,
+                        control,
+                        logo,
                         ..
                     },
                 ..
             }) => {
                 self.animator_play(cx, id!(focus.on));
                 cx.set_key_focus(self.scroll_bars.area());
+                self.close_popup(cx);
                 let ((cursor, affinity), is_in_gutter) = self.pick(session, abs);
+                if (control || logo) && !is_in_gutter {
+                    actions.push(CodeEditorAction::DefinitionRequested(cursor));
+                }
                 session.set_selection(
                     cursor,
                     affinity,
@@ -921,8 +1130,21 @@ impl CodeEditor {
                 self.reset_cursor_blinker(cx);
                 self.keep_cursor_in_view = KeepCursorInView::Off;
             }
-            Hit::FingerHoverIn(_) | Hit::FingerHoverOver(_) => {
+            Hit::FingerHoverIn(FingerHoverEvent { abs, .. })
+            | Hit::FingerHoverOver(FingerHoverEvent { abs, .. }) => {
                 cx.set_cursor(MouseCursor::Text);
+                if let Some(Popup::Hover { .. }) = self.popup {
+                    self.close_popup(cx);
+                }
+                self.hover_abs = abs;
+                cx.stop_timer(self.hover_timer);
+                self.hover_timer = cx.start_timeout(self.hover_delay);
+            }
+            Hit::FingerHoverOut(_) => {
+                cx.stop_timer(self.hover_timer);
+                if let Some(Popup::Hover { .. }) = self.popup {
+                    self.close_popup(cx);
+                }
             }
             Hit::FingerDown(FingerDownEvent {
                 abs,
@@ -945,6 +1167,17 @@ impl CodeEditor {
         if keyboard_moved_cursor {
             self.keep_cursor_in_view = KeepCursorInView::Once;
             self.reset_cursor_blinker(cx);
+            match self.popup {
+                Some(Popup::Hover { .. }) => self.close_popup(cx),
+                Some(Popup::Completion { position, .. }) => {
+                    // Close the completions once the cursor leaves the word they were requested for.
+                    let (start, _) = word_before_cursor(session);
+                    if start != position {
+                        self.close_popup(cx);
+                    }
+                }
+                None => {}
+            }
         }
         if let KeepCursorInView::Always(abs, next) = &mut self.keep_cursor_in_view {
             if next.is_event(event).is_some() {
@@ -958,7 +1191,94 @@ impl CodeEditor {
         actions
     }
 
+    fn draw_popup(&mut self, cx: &mut Cx2d, session: &Session) {
+        const MAX_ROW_COUNT: usize = 12;
+        const MAX_COLUMN_COUNT: usize = 80;
+
+        let (position, rows, selected_row_index) = match &self.popup {
+            Some(Popup::Hover { position, text }) => (
+                *position,
+                text.lines()
+                    .take(MAX_ROW_COUNT)
+                    .map(|line| line.to_string())
+                    .collect::<Vec<_>>(),
+                None,
+            ),
+            Some(Popup::Completion {
+                position,
+                selected_index,
+                ..
+            }) => {
+                let items = self.matching_completions(session);
+                let label_width = items
+                    .iter()
+                    .map(|item| item.label.column_count())
+                    .max()
+                    .unwrap_or(0);
+                let first_index = selected_index.saturating_sub(MAX_ROW_COUNT - 1);
+                (
+                    *position,
+                    items
+                        .iter()
+                        .skip(first_index)
+                        .take(MAX_ROW_COUNT)
+                        .map(|item| format!("{:<2$}  {}", item.label, item.detail, label_width))
+                        .collect::<Vec<_>>(),
+                    Some(selected_index - first_index),
+                )
+            }
+            None => return,
+        };
+        let text = session.document().as_text();
+        let lines = text.as_lines();
+        if rows.is_empty() || position.line_index >= lines.len() {
+            return;
+        }
+        let position = Position {
+            line_index: position.line_index,
+            byte_index: position.byte_index.min(lines[position.line_index].len()),
+        };
+        let rows = rows
+            .into_iter()
+            .map(|row| row.chars().take(MAX_COLUMN_COUNT).collect::<String>())
+            .collect::<Vec<_>>();
+        let column_count = rows.iter().map(|row| row.column_count()).max().unwrap_or(0);
+
+        let (x, y) = session
+            .layout()
+            .logical_to_normalized_position(position, Affinity::Before);
+        let padding = dvec2(self.cell_size.x, self.cell_size.y * 0.25);
+        let origin = dvec2(x, y + 1.0) * self.cell_size + self.viewport_rect.pos;
+        self.draw_popup_bg.draw_abs(
+            cx,
+            Rect {
+                pos: origin,
+                size: dvec2(column_count as f64, rows.len() as f64) * self.cell_size
+                    + padding * 2.0,
+            },
+        );
+        self.draw_popup_text.text_style.font_size = self.draw_text.text_style.font_size;
+        for (row_index, row) in rows.iter().enumerate() {
+            let row_origin = origin + padding + dvec2(0.0, row_index as f64 * self.cell_size.y);
+            if selected_row_index == Some(row_index) {
+                self.draw_popup_selection.draw_abs(
+                    cx,
+                    Rect {
+                        pos: row_origin - dvec2(padding.x, 0.0),
+                        size: dvec2(
+                            column_count as f64 * self.cell_size.x + padding.x * 2.0,
+                            self.cell_size.y,
+                        ),
+                    },
+                );
+            }
+            self.draw_popup_text.draw_abs(cx, row_origin, row);
+        }
+    }
+
     fn draw_gutter(&mut self, cx: &mut Cx2d, session: &Session) {
+        let hunks = session.document().diff_hunks();
+        let line_count = session.document().as_text().as_lines().len();
         let mut line_index = self.line_start;
         let mut origin_y = session.layout().line(self.line_start).y();
         let mut buf = String::new();
@@ -985,6 +1305,7 @@ impl CodeEditor {
                             ),
                         &buf,
                     );
+                    self.draw_line_markers(cx, &hunks, line_index, line_count, origin_y, line.height());
                     line_index += 1;
                     origin_y += line.height();
                 }
@@ -995,6 +1316,60 @@ impl CodeEditor {
         }
     }
 
+    // marks the lines that differ from the diff base of the document, to the left of the line
+    // numbers. lines that were removed are marked between the lines around them
+    fn draw_line_markers(
+        &mut self,
+        cx: &mut Cx2d,
+        hunks: &[Hunk],
+        line_index: usize,
+        line_count: usize,
+        origin_y: f64,
+        height: f64,
+    ) {
+        let x = self.gutter_rect.pos.x - 7.0;
+        let y = origin_y * self.cell_size.y + self.gutter_rect.pos.y;
+        let index = hunks.partition_point(|hunk| hunk.new.end < line_index);
+        for hunk in hunks[index..]
+            .iter()
+            .take_while(|hunk| hunk.new.start <= line_index + 1)
+        {
+            let removed_y = match hunk.kind() {
+                HunkKind::Added | HunkKind::Modified => {
+                    if !hunk.new.contains(&line_index) {
+                        continue;
+                    }
+                    self.draw_line_marker.color = if hunk.kind() == HunkKind::Added {
+                        self.token_colors.added_line_marker
+                    } else {
+                        self.token_colors.modified_line_marker
+                    };
+                    self.draw_line_marker.draw_abs(
+                        cx,
+                        Rect {
+                            pos: dvec2(x, y),
+                            size: dvec2(3.0, height * self.cell_size.y),
+                        },
+                    );
+                    continue;
+                }
+                HunkKind::Removed if hunk.new.start == line_index => y,
+                HunkKind::Removed if hunk.new.start == line_count && line_index + 1 == line_count => {
+                    y + height * self.cell_size.y
+                }
+                HunkKind::Removed => continue,
+            };
+            self.draw_line_marker.color = self.token_colors.removed_line_marker;
+            self.draw_line_marker.draw_abs(
+                cx,
+                Rect {
+                    pos: dvec2(x, removed_y - 1.5),
+                    size: dvec2(6.0, 3.0),
+                },
+            );
+        }
+    }
+
     fn draw_text_layer(&mut self, cx: &mut Cx2d, session: &Session) {
         let highlighted_delimiter_positions = session.highlighted_delimiter_positions();
         let mut line_index = self.line_start;
@@ -1107,6 +1482,20 @@ impl CodeEditor {
                             }
                         }
                     }
+                    if let Some((_, annotation)) = self
+                        .line_annotation
+                        .as_ref()
+                        .filter(|(annotation_line_index, _)| *annotation_line_index == line_index)
+                    {
+                        let (x, y) = line.grid_to_normalized_position(row_index, column_index + 4);
+                        self.draw_text.color = self.token_colors.line_annotation;
+                        self.draw_text.outline = 0.0;
+                        self.draw_text.draw_abs(
+                            cx,
+                            DVec2 { x, y: origin_y + y } * self.cell_size + self.viewport_rect.pos,
+                            annotation,
+                        );
+                    }
                     line_index += 1;
                     origin_y += line.height();
                 }
@@ -1416,9 +1805,38 @@ impl CodeEditor {
     }
 }
 
+fn cursor_position(session: &Session) -> Position {
+    session.selections()[session.last_added_selection_index().unwrap()]
+        .cursor
+        .position
+}
+
+// Returns where the identifier that ends at the cursor starts, together with its text.
+fn word_before_cursor(session: &Session) -> (Position, String) {
+    let cursor = cursor_position(session);
+    let text = session.document().as_text();
+    let line = &text.as_lines()[cursor.line_index][..cursor.byte_index];
+    let start = line
+        .char_indices()
+        .rev()
+        .take_while(|(_, char)| char.is_alphanumeric() || *char == '_')
+        .last()
+        .map_or(cursor.byte_index, |(index, _)| index);
+    (
+        Position {
+            line_index: cursor.line_index,
+            byte_index: start,
+        },
+        line[start..].to_string(),
+    )
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, DefaultNone)]
 pub enum CodeEditorAction {
     TextDidChange,
+    HoverRequested(Position),
+    DefinitionRequested(Position),
+    CompletionRequested(Position),
     None
 }
 
@@ -1568,6 +1986,9 @@ impl<'a> DrawDecorationLayer<'a> {
             })
         {
             let decoration = *self.decorations.next().unwrap();
+            if decoration.is_empty() && decoration.ty == DecorationType::RemoteCursor {
+                self.draw_remote_cursor(cx, line, origin_y, row_index, column_index);
+            }
             if !decoration.is_empty() {
                 let (start_x, _) = line.grid_to_normalized_position(row_index, column_index);
                 self.active_decoration = Some(ActiveDecoration {
@@ -1578,6 +1999,34 @@ impl<'a> DrawDecorationLayer<'a> {
         }
     }
 
+    fn draw_remote_cursor(
+        &mut self,
+        cx: &mut Cx2d,
+        line: Line<'_>,
+        origin_y: f64,
+        row_index: usize,
+        column_index: usize,
+    ) {
+        let (x, y) = line.grid_to_normalized_position(row_index, column_index);
+        self.code_editor.draw_decoration.color =
+            self.code_editor.token_colors.remote_cursor_decoration;
+        self.code_editor.draw_decoration.fill = 1.0;
+        self.code_editor.draw_decoration.draw_abs(
+            cx,
+            Rect {
+                pos: DVec2 {
+                    x,
+                    y: origin_y + y,
+                } * self.code_editor.cell_size
+                    + self.code_editor.viewport_rect.pos,
+                size: DVec2 {
+                    x: 2.0,
+                    y: line.scale() * self.code_editor.cell_size.y,
+                },
+            },
+        );
+    }
+
     fn draw_decoration(
         &mut self,
         cx: &mut Cx2d,
@@ -1588,11 +2037,20 @@ impl<'a> DrawDecorationLayer<'a> {
     ) {
         let start_x = mem::take(&mut self.active_decoration.as_mut().unwrap().start_x);
         let (x, y) = line.grid_to_normalized_position(row_index, column_index);
-        self.code_editor.draw_decoration.color =
-            match self.active_decoration.as_mut().unwrap().decoration.ty {
-                DecorationType::Warning => self.code_editor.token_colors.warning_decoration,
-                DecorationType::Error => self.code_editor.token_colors.error_decoration,
-            };
+        let ty = self.active_decoration.as_mut().unwrap().decoration.ty;
+        self.code_editor.draw_decoration.color = match ty {
+            DecorationType::Warning => self.code_editor.token_colors.warning_decoration,
+            DecorationType::Error => self.code_editor.token_colors.error_decoration,
+            DecorationType::SearchMatch => self.code_editor.token_colors.search_match_decoration,
+            DecorationType::RemoteCursor => {
+                self.code_editor.token_colors.remote_cursor_decoration
+            }
+        };
+        self.code_editor.draw_decoration.fill = if ty == DecorationType::RemoteCursor {
+            0.3
+        } else {
+            0.0
+        };
 
         self.code_editor.draw_decoration.draw_abs(
             cx,
@@ -1902,6 +2360,18 @@ struct TokenColors {
     error_decoration: Vec4,
     #[live]
     warning_decoration: Vec4,
+    #[live]
+    search_match_decoration: Vec4,
+    #[live]
+    remote_cursor_decoration: Vec4,
+    #[live]
+    added_line_marker: Vec4,
+    #[live]
+    modified_line_marker: Vec4,
+    #[live]
+    removed_line_marker: Vec4,
+    #[live]
+    line_annotation: Vec4,
 }
 
 #[derive(Live, LiveHook, LiveRegister)]
@@ -1914,11 +2384,15 @@ pub struct DrawIndentGuide {
 }
 
 #[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
 struct DrawDecoration {
     #[deref]
     draw_super: DrawQuad,
     #[live]
     color: Vec4,
+    // Decorations with a fill are drawn as a box with this opacity instead of an underline.
+    #[live]
+    fill: f32,
 }
 
 #[derive(Live, LiveHook, LiveRegister)]
@@ -1,12 +1,16 @@
 use {
     crate::text::{Edit, Length, Position},
-    std::{ops::Deref, slice::Iter},
+    std::{mem, ops::Deref, slice::Iter},
 };
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum DecorationType {
     Error,
     Warning,
+    SearchMatch,
+    // The selection of another participant of a collaboration session. Empty ones are drawn as
+    // a cursor.
+    RemoteCursor,
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -94,6 +98,56 @@ impl DecorationSet {
         self.decorations.clear();
     }
 
+    // Replaces all decorations of type `ty` with `decorations`, which have to be sorted. New
+    // decorations that overlap with a decoration of another type are dropped.
+    pub fn replace_decorations(
+        &mut self,
+        ty: DecorationType,
+        decorations: impl IntoIterator<Item = Decoration>,
+    ) {
+        self.replace_decorations_where(|decoration| decoration.ty == ty, decorations);
+    }
+
+    // Like `replace_decorations`, but replaces the decorations with the given `id` instead, so
+    // that decorations from different sources can be updated independently.
+    pub fn replace_decorations_with_id(
+        &mut self,
+        id: usize,
+        decorations: impl IntoIterator<Item = Decoration>,
+    ) {
+        self.replace_decorations_where(|decoration| decoration.id == id, decorations);
+    }
+
+    fn replace_decorations_where(
+        &mut self,
+        is_replaced: impl Fn(&Decoration) -> bool,
+        decorations: impl IntoIterator<Item = Decoration>,
+    ) {
+        let mut old_decorations = mem::take(&mut self.decorations)
+            .into_iter()
+            .filter(|decoration| !is_replaced(decoration))
+            .peekable();
+        for decoration in decorations {
+            while let Some(old_decoration) = old_decorations
+                .next_if(|old_decoration| old_decoration.start() <= decoration.start())
+            {
+                self.decorations.push(old_decoration);
+            }
+            if self
+                .decorations
+                .last()
+                .is_some_and(|prev_decoration| prev_decoration.overlaps_with(decoration))
+                || old_decorations
+                    .peek()
+                    .is_some_and(|next_decoration| decoration.overlaps_with(*next_decoration))
+            {
+                continue;
+            }
+            self.decorations.push(decoration);
+        }
+        self.decorations.extend(old_decorations);
+    }
+
     pub fn apply_edit(&mut self, edit: &Edit) {
         for decoration in &mut self.decorations {
             *decoration = decoration.apply_edit(edit);
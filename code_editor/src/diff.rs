@@ -0,0 +1,141 @@
+use std::ops::Range;
+
+// Finding the shortest diff takes time and memory that grow with the square of the number of
+// changes, so texts that differ in more lines than this are treated as one big change.
+const MAX_EDIT_COUNT: usize = 1000;
+
+// A run of lines that differ between two versions of a text. The lines in `old` were replaced
+// by the lines in `new`, one of which can be empty.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Hunk {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+impl Hunk {
+    pub fn kind(&self) -> HunkKind {
+        if self.old.is_empty() {
+            HunkKind::Added
+        } else if self.new.is_empty() {
+            HunkKind::Removed
+        } else {
+            HunkKind::Modified
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum HunkKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+// Returns the hunks in which `new` differs from `old`, in order, using the algorithm from "An
+// O(ND) Difference Algorithm and Its Variations" by Eugene W. Myers.
+pub fn diff<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Hunk> {
+    let prefix_len = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix_len = old[prefix_len..]
+        .iter()
+        .rev()
+        .zip(new[prefix_len..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_middle = &old[prefix_len..old.len() - suffix_len];
+    let new_middle = &new[prefix_len..new.len() - suffix_len];
+    let matches = find_matches(old_middle, new_middle).unwrap_or_default();
+    let mut hunks = Vec::new();
+    let mut old_start = 0;
+    let mut new_start = 0;
+    for (old_index, new_index) in matches
+        .into_iter()
+        .chain([(old_middle.len(), new_middle.len())])
+    {
+        if old_index > old_start || new_index > new_start {
+            hunks.push(Hunk {
+                old: prefix_len + old_start..prefix_len + old_index,
+                new: prefix_len + new_start..prefix_len + new_index,
+            });
+        }
+        old_start = old_index + 1;
+        new_start = new_index + 1;
+    }
+    hunks
+}
+
+// Returns the pairs of indices of the elements that `old` and `new` have in common in a
+// shortest diff, or `None` if that diff has more than `MAX_EDIT_COUNT` edits.
+fn find_matches<T: PartialEq>(old: &[T], new: &[T]) -> Option<Vec<(usize, usize)>> {
+    let old_len = old.len() as isize;
+    let new_len = new.len() as isize;
+    let max_edit_count = (old.len() + new.len()).min(MAX_EDIT_COUNT) as isize;
+    let offset = max_edit_count + 1;
+    // The furthest index into `old` that can be reached on each diagonal, which is the
+    // difference between the index into `old` and the index into `new`.
+    let mut furthest = vec![0isize; 2 * offset as usize + 1];
+    let mut trace = Vec::new();
+    let mut edit_count = 0;
+    'outer: loop {
+        if edit_count > max_edit_count {
+            return None;
+        }
+        trace.push(furthest.clone());
+        for diagonal in (-edit_count..=edit_count).step_by(2) {
+            let index = (diagonal + offset) as usize;
+            let mut old_index = if diagonal == -edit_count
+                || diagonal != edit_count && furthest[index - 1] < furthest[index + 1]
+            {
+                furthest[index + 1]
+            } else {
+                furthest[index - 1] + 1
+            };
+            let mut new_index = old_index - diagonal;
+            while old_index < old_len
+                && new_index < new_len
+                && old[old_index as usize] == new[new_index as usize]
+            {
+                old_index += 1;
+                new_index += 1;
+            }
+            furthest[index] = old_index;
+            if old_index >= old_len && new_index >= new_len {
+                break 'outer;
+            }
+        }
+        edit_count += 1;
+    }
+    let mut matches = Vec::new();
+    let mut old_index = old_len;
+    let mut new_index = new_len;
+    for (edit_count, furthest) in trace.iter().enumerate().rev() {
+        let edit_count = edit_count as isize;
+        let diagonal = old_index - new_index;
+        let index = (diagonal + offset) as usize;
+        let prev_diagonal = if diagonal == -edit_count
+            || diagonal != edit_count && furthest[index - 1] < furthest[index + 1]
+        {
+            diagonal + 1
+        } else {
+            diagonal - 1
+        };
+        let (prev_old_index, prev_new_index) = if edit_count == 0 {
+            (0, 0)
+        } else {
+            let prev_old_index = furthest[(prev_diagonal + offset) as usize];
+            (prev_old_index, prev_old_index - prev_diagonal)
+        };
+        while old_index > prev_old_index && new_index > prev_new_index {
+            old_index -= 1;
+            new_index -= 1;
+            matches.push((old_index as usize, new_index as usize));
+        }
+        old_index = prev_old_index;
+        new_index = prev_new_index;
+    }
+    matches.reverse();
+    Some(matches)
+}
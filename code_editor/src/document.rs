@@ -1,7 +1,8 @@
 use {
     crate::{
         char::CharExt,
-        decoration::{Decoration, DecorationSet},
+        decoration::{Decoration, DecorationSet, DecorationType},
+        diff::{self, Hunk},
         history::{EditKind, History},
         inlays::{BlockInlay, InlineInlay},
         iter::IteratorExt,
@@ -17,10 +18,10 @@ use {
         cell::{Ref, RefCell},
         cmp::Ordering,
         collections::HashMap,
-        iter,
+        iter, mem,
         ops::Range,
         rc::Rc,
-        sync::mpsc::Sender,
+        sync::mpsc::{self, Receiver, Sender},
     },
 };
 
@@ -28,7 +29,7 @@ use {
 pub struct Document(Rc<DocumentInner>);
 
 impl Document {
-    pub fn new(text: Text, decorations: DecorationSet) -> Self {
+    pub fn new(text: Text, decorations: DecorationSet, extension: &str) -> Self {
         let line_count = text.as_lines().len();
         let tokens: Vec<_> = (0..line_count)
             .map(|line| tokenize(&text.as_lines()[line]).collect::<Vec<_>>())
@@ -41,9 +42,13 @@ impl Document {
                 inline_inlays: (0..line_count).map(|_| Vec::new()).collect(),
                 block_inlays: Vec::new(),
             }),
-            tokenizer: RefCell::new(Tokenizer::new(line_count)),
+            tokenizer: RefCell::new(Tokenizer::for_extension(extension, line_count)),
+            dirty_indent_lines: RefCell::new(0..line_count),
             decorations: RefCell::new(decorations),
             edit_senders: RefCell::new(HashMap::new()),
+            edit_observers: RefCell::new(Vec::new()),
+            diff_base: RefCell::new(None),
+            diff_hunks: RefCell::new(Some(Vec::new())),
         }));
         inner.update_indent_state();
         inner.0.tokenizer.borrow_mut().update(
@@ -135,7 +140,7 @@ impl Document {
         }
         drop(history);
         self.autoindent(&line_ranges, settings.tab_column_count, &mut edits);
-        self.update_after_edit(session_id, None, &edits);
+        self.update_after_edit(Some(session_id), None, &edits);
     }
 
     pub fn edit_linewise(
@@ -171,7 +176,81 @@ impl Document {
             }
         }
         drop(history);
-        self.update_after_edit(origin_id, None, &edits);
+        self.update_after_edit(Some(origin_id), None, &edits);
+    }
+
+    // Replaces the text with `text` as a single undoable edit. Only the lines from the first to
+    // the last line that differ are replaced, so selections outside of them stay where they are.
+    pub fn replace_text(&self, origin_id: SessionId, selections: &SelectionSet, text: Text) {
+        let mut history = self.0.history.borrow_mut();
+        if *history.as_text() == text {
+            return;
+        }
+        let old_lines = history.as_text().as_lines();
+        let new_lines = text.as_lines();
+        let max_common_line_count = old_lines.len().min(new_lines.len()) - 1;
+        let prefix_line_count = old_lines
+            .iter()
+            .zip(new_lines.iter())
+            .take(max_common_line_count)
+            .take_while(|(old_line, new_line)| old_line == new_line)
+            .count();
+        let suffix_line_count = old_lines
+            .iter()
+            .rev()
+            .zip(new_lines.iter().rev())
+            .take(max_common_line_count - prefix_line_count)
+            .take_while(|(old_line, new_line)| old_line == new_line)
+            .count();
+        let start = Position {
+            line_index: prefix_line_count,
+            byte_index: 0,
+        };
+        let last_line_index = old_lines.len() - 1 - suffix_line_count;
+        let length = Length {
+            line_count: last_line_index - prefix_line_count,
+            byte_count: old_lines[last_line_index].len(),
+        };
+        let replacement = new_lines
+            .range(prefix_line_count..new_lines.len() - suffix_line_count)
+            .map(|line| line.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        history.force_new_group();
+        history.push_or_extend_group(origin_id, EditKind::Other, selections);
+        let mut edits = Vec::new();
+        if length != Length::zero() {
+            edits.push(Edit {
+                change: Change::Delete(start, length),
+                drift: Drift::Before,
+            });
+        }
+        if !replacement.is_empty() {
+            edits.push(Edit {
+                change: Change::Insert(start, replacement.into()),
+                drift: Drift::Before,
+            });
+        }
+        for edit in &edits {
+            history.apply_edit(edit.clone());
+        }
+        history.force_new_group();
+        drop(history);
+        self.update_after_edit(Some(origin_id), None, &edits);
+    }
+
+    // Applies edits that were made to the same text somewhere else, such as by another participant
+    // of a collaboration session. They are not undoable, and none of the sessions made them.
+    pub fn apply_remote_edits(&self, edits: Vec<Edit>) {
+        if edits.is_empty() {
+            return;
+        }
+        let mut history = self.0.history.borrow_mut();
+        for edit in &edits {
+            history.apply_remote_edit(edit.clone());
+        }
+        drop(history);
+        self.update_after_edit(None, None, &edits);
     }
 
     pub fn add_decoration(&mut self, decoration: Decoration) {
@@ -182,6 +261,122 @@ impl Document {
         self.0.decorations.borrow_mut().clear()
     }
 
+    pub fn replace_decorations(
+        &self,
+        ty: DecorationType,
+        decorations: impl IntoIterator<Item = Decoration>,
+    ) {
+        self.0
+            .decorations
+            .borrow_mut()
+            .replace_decorations(ty, decorations);
+    }
+
+    pub fn replace_decorations_with_id(
+        &self,
+        id: usize,
+        decorations: impl IntoIterator<Item = Decoration>,
+    ) {
+        self.0
+            .decorations
+            .borrow_mut()
+            .replace_decorations_with_id(id, decorations);
+    }
+
+    // Replaces all inline inlays with `inlays`, which have to be sorted by position. Inlays
+    // that do not point into the text are dropped.
+    pub fn set_inline_inlays(&self, inlays: impl IntoIterator<Item = (Position, InlineInlay)>) {
+        let history = self.0.history.borrow();
+        let lines = history.as_text().as_lines();
+        let mut layout = self.0.layout.borrow_mut();
+        let mut new_inline_inlays = vec![Vec::new(); layout.inline_inlays.len()];
+        for (position, inlay) in inlays {
+            if lines
+                .get(position.line_index)
+                .is_some_and(|line| line.is_char_boundary(position.byte_index))
+            {
+                new_inline_inlays[position.line_index].push((position.byte_index, inlay));
+            }
+        }
+        let mut changed_lines = Vec::new();
+        for (line_index, (inline_inlays, new_inline_inlays)) in layout
+            .inline_inlays
+            .iter_mut()
+            .zip(new_inline_inlays)
+            .enumerate()
+        {
+            if *inline_inlays != new_inline_inlays {
+                *inline_inlays = new_inline_inlays;
+                changed_lines.push(line_index);
+            }
+        }
+        drop(layout);
+        drop(history);
+        if changed_lines.is_empty() {
+            return;
+        }
+        // Sessions lay out lines again when they are edited, so an empty insertion on every
+        // changed line makes them pick up the new inlays without touching the text.
+        let edits: Vec<_> = changed_lines
+            .into_iter()
+            .map(|line_index| Edit {
+                change: Change::Insert(
+                    Position {
+                        line_index,
+                        byte_index: 0,
+                    },
+                    Text::new(),
+                ),
+                drift: Drift::Before,
+            })
+            .collect();
+        for edit_sender in self.0.edit_senders.borrow().values() {
+            edit_sender.send((None, edits.clone())).unwrap();
+        }
+    }
+
+    // Sets the text that the text of this document is compared to, such as the version of the
+    // file in the last commit.
+    pub fn set_diff_base(&self, text: Option<Text>) {
+        *self.0.diff_base.borrow_mut() = text;
+        *self.0.diff_hunks.borrow_mut() = None;
+    }
+
+    pub fn diff_base(&self) -> Ref<'_, Option<Text>> {
+        self.0.diff_base.borrow()
+    }
+
+    // Returns the hunks in which the text differs from the diff base, which are empty if there is
+    // no diff base. They are only computed again when they are asked for after an edit.
+    pub fn diff_hunks(&self) -> Ref<'_, [Hunk]> {
+        if self.0.diff_hunks.borrow().is_none() {
+            let hunks = match &*self.0.diff_base.borrow() {
+                Some(diff_base) => {
+                    let history = self.0.history.borrow();
+                    let old_lines: Vec<_> = diff_base.as_lines().iter().map(String::as_str).collect();
+                    let new_lines: Vec<_> = history
+                        .as_text()
+                        .as_lines()
+                        .iter()
+                        .map(String::as_str)
+                        .collect();
+                    diff::diff(&old_lines, &new_lines)
+                }
+                None => Vec::new(),
+            };
+            *self.0.diff_hunks.borrow_mut() = Some(hunks);
+        }
+        Ref::map(self.0.diff_hunks.borrow(), |hunks| hunks.as_deref().unwrap())
+    }
+
+    // Returns a receiver for the edits made to this document, in the order in which they were
+    // applied. Observers that drop their receiver are removed on the next edit.
+    pub fn observe_edits(&self) -> Receiver<Vec<Edit>> {
+        let (edit_sender, edit_receiver) = mpsc::channel();
+        self.0.edit_observers.borrow_mut().push(edit_sender);
+        edit_receiver
+    }
+
     pub fn add_session(
         &mut self,
         session_id: SessionId,
@@ -239,9 +434,10 @@ impl Document {
                 }
             })
         {
-            let mut desired_indentation_column_count = self.as_text().as_lines()
-                [..line_range.start]
-                .iter()
+            let mut desired_indentation_column_count = self
+                .as_text()
+                .as_lines()
+                .range(0..line_range.start)
                 .rev()
                 .find_map(|line| next_line_indent_column_count(line, indent_column_count))
                 .unwrap_or(0);
@@ -259,7 +455,8 @@ impl Document {
                     })
                     .unwrap_or(false)
                 {
-                    desired_indentation_column_count -= 4;
+                    desired_indentation_column_count =
+                        desired_indentation_column_count.saturating_sub(4);
                 }
                 self.edit_lines_internal(line, edits, |line| {
                     crate::session::reindent(line, |_| desired_indentation_column_count)
@@ -323,7 +520,7 @@ impl Document {
         let mut changes = Vec::new();
         let selections = self.0.history.borrow_mut().undo(selections, &mut changes);
         if let Some(selections) = selections {
-            self.update_after_edit(origin_id, Some(selections), &changes);
+            self.update_after_edit(Some(origin_id), Some(selections), &changes);
             true
         } else {
             false
@@ -334,7 +531,7 @@ impl Document {
         let mut changes = Vec::new();
         let selections = self.0.history.borrow_mut().redo(selections, &mut changes);
         if let Some(selections) = selections {
-            self.update_after_edit(origin_id, Some(selections), &changes);
+            self.update_after_edit(Some(origin_id), Some(selections), &changes);
             true
         } else {
             false
@@ -343,7 +540,7 @@ impl Document {
 
     fn update_after_edit(
         &self,
-        origin_id: SessionId,
+        origin_id: Option<SessionId>,
         selections: Option<SelectionSet>,
         edits: &[Edit],
     ) {
@@ -375,6 +572,8 @@ impl Document {
             self.apply_change_to_tokens(&edit.change);
             self.apply_change_to_inline_inlays(&edit.change, edit.drift);
             self.0.tokenizer.borrow_mut().apply_change(&edit.change);
+            edit.change
+                .extend_dirty_line_range(&mut self.0.dirty_indent_lines.borrow_mut());
         }
         self.update_indent_state();
         self.0.tokenizer.borrow_mut().update(
@@ -386,8 +585,11 @@ impl Document {
             decorations.apply_edit(edit);
         }
         drop(decorations);
+        if self.0.diff_base.borrow().is_some() {
+            *self.0.diff_hunks.borrow_mut() = None;
+        }
         for (&session_id, edit_sender) in &*self.0.edit_senders.borrow() {
-            if session_id == origin_id {
+            if Some(session_id) == origin_id {
                 edit_sender
                     .send((selections.clone(), edits.to_vec()))
                     .unwrap();
@@ -407,6 +609,10 @@ impl Document {
                     .unwrap();
             }
         }
+        self.0
+            .edit_observers
+            .borrow_mut()
+            .retain(|edit_sender| edit_sender.send(edits.to_vec()).is_ok());
     }
 
     fn apply_change_to_tokens(&self, change: &Change) {
@@ -609,18 +815,34 @@ impl Document {
         let indent_state = &mut layout.indent_state;
         let history = self.0.history.borrow();
         let lines = history.as_text().as_lines();
-        let mut current_indent_column_count = 0;
-        for line_index in 0..lines.len() {
+        // Only the dirty lines, and the empty lines that directly follow them, can change.
+        let dirty_lines = mem::replace(&mut *self.0.dirty_indent_lines.borrow_mut(), 0..0);
+        if dirty_lines.is_empty() {
+            return;
+        }
+        let mut current_indent_column_count = match dirty_lines.start {
+            0 => 0,
+            line_index => match indent_state[line_index - 1].unwrap() {
+                IndentState::Empty(indent_column_count) => indent_column_count,
+                IndentState::NonEmpty(_, next_indent_column_count) => next_indent_column_count,
+            },
+        };
+        for (line_index, line) in lines
+            .range(dirty_lines.start..lines.len())
+            .enumerate()
+            .map(|(index, line)| (dirty_lines.start + index, line))
+        {
             match indent_state[line_index] {
+                Some(IndentState::NonEmpty(..)) if line_index >= dirty_lines.end => break,
                 Some(IndentState::NonEmpty(_, next_indent_column_count)) => {
                     current_indent_column_count = next_indent_column_count;
                 }
                 _ => {
-                    indent_state[line_index] = Some(match lines[line_index].indent() {
+                    indent_state[line_index] = Some(match line.indent() {
                         Some(indent) => {
                             let indent_column_count = indent.column_count();
                             let mut next_indent_column_count = indent_column_count;
-                            if lines[line_index]
+                            if line
                                 .chars()
                                 .rev()
                                 .find_map(|char| {
@@ -683,8 +905,13 @@ struct DocumentInner {
     history: RefCell<History>,
     layout: RefCell<DocumentLayout>,
     tokenizer: RefCell<Tokenizer>,
+    dirty_indent_lines: RefCell<Range<usize>>,
     decorations: RefCell<DecorationSet>,
     edit_senders: RefCell<HashMap<SessionId, Sender<(Option<SelectionSet>, Vec<Edit>)>>>,
+    edit_observers: RefCell<Vec<Sender<Vec<Edit>>>>,
+    diff_base: RefCell<Option<Text>>,
+    // `None` when the hunks have to be computed again.
+    diff_hunks: RefCell<Option<Vec<Hunk>>>,
 }
 
 fn tokenize(text: &str) -> impl Iterator<Item = Token> + '_ {
@@ -51,6 +51,15 @@ impl History {
         self.redo_stack.clear();
     }
 
+    // Applies an edit that was made somewhere else. Undoing edits made before it is no longer
+    // possible, since they might overlap with it.
+    pub fn apply_remote_edit(&mut self, edit: Edit) {
+        self.text.apply_change(edit.change);
+        self.current_desc = None;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
     pub fn undo(
         &mut self,
         selections: &SelectionSet,
@@ -5,7 +5,7 @@ use {
         selection::Affinity,
         session::SessionLayout,
         str::StrExt,
-        text::{Position, Text},
+        text::{LinesIter, Position, Text},
         widgets::{BlockWidget, InlineWidget},
         wrap::WrapData,
         Token,
@@ -89,7 +89,7 @@ impl<'a> Layout<'a> {
             column_count: self.session_layout.column_count[start..end].iter(),
             fold: self.session_layout.fold_column[start..end].iter(),
             scale: self.session_layout.scale[start..end].iter(),
-            text: self.text.as_lines()[start..end].iter(),
+            text: self.text.as_lines().range(start..end),
             indent_state: self.document_layout.indent_state[start..end].iter(),
             tokens: self.document_layout.tokens[start..end].iter(),
             inline_inlays: self.document_layout.inline_inlays[start..end].iter(),
@@ -120,7 +120,7 @@ pub struct Lines<'a> {
     column_count: Iter<'a, Option<usize>>,
     fold: Iter<'a, usize>,
     scale: Iter<'a, f64>,
-    text: Iter<'a, String>,
+    text: LinesIter<'a>,
     indent_state: Iter<'a, Option<IndentState>>,
     tokens: Iter<'a, Vec<Token>>,
     inline_inlays: Iter<'a, Vec<(usize, InlineInlay)>>,
@@ -1,9 +1,11 @@
 pub use makepad_widgets;
+pub use makepad_text_search::{regex, search, text};
 use makepad_widgets::*;
 
 pub mod char;
 pub mod code_editor;
 pub mod decoration;
+pub mod diff;
 pub mod document;
 pub mod history;
 pub mod inlays;
@@ -13,14 +15,13 @@ pub mod selection;
 pub mod session;
 pub mod settings;
 pub mod str;
-pub mod text;
 pub mod token;
 pub mod tokenizer;
 pub mod widgets;
 pub mod wrap;
 
 pub use self::{
-    code_editor::CodeEditor, document::Document, history::History, layout::Line,
+    code_editor::CodeEditor, document::Document, history::History, layout::Line, search::Search,
     selection::Selection, session::Session, settings::Settings, token::Token, tokenizer::Tokenizer,
 };
 
@@ -2,7 +2,7 @@ use {
     crate::{
         layout::Layout,
         str::StrExt,
-        text::{Edit, Length, Position},
+        text::{Edit, Length, Lines, Position},
     },
     std::{ops, ops::Deref, slice::Iter},
 };
@@ -265,7 +265,7 @@ impl Cursor {
         self.position.byte_index == 0
     }
 
-    pub fn is_at_end_of_line(self, lines: &[String]) -> bool {
+    pub fn is_at_end_of_line(self, lines: &Lines) -> bool {
         self.position.byte_index == lines[self.position.line_index].len()
     }
 
@@ -282,7 +282,7 @@ impl Cursor {
         row == line.row_count() - 1
     }
 
-    pub fn move_left(self, lines: &[String]) -> Self {
+    pub fn move_left(self, lines: &Lines) -> Self {
         if !self.is_at_start_of_line() {
             return self.move_to_prev_grapheme(lines);
         }
@@ -292,7 +292,7 @@ impl Cursor {
         self
     }
 
-    pub fn move_right(self, lines: &[String]) -> Self {
+    pub fn move_right(self, lines: &Lines) -> Self {
         if !self.is_at_end_of_line(lines) {
             return self.move_to_next_grapheme(lines);
         }
@@ -322,7 +322,7 @@ impl Cursor {
         self.move_to_end_of_line(layout.as_text().as_lines())
     }
 
-    pub fn home(self, lines: &[String]) -> Self {
+    pub fn home(self, lines: &Lines) -> Self {
         if !self.is_at_start_of_line() {
             let indent_len = lines[self.position.line_index].indent().unwrap_or("").len();
             if self.position.byte_index <= indent_len {
@@ -341,7 +341,7 @@ impl Cursor {
         self
     }
 
-    pub fn end(self, lines: &[String]) -> Self {
+    pub fn end(self, lines: &Lines) -> Self {
         if !self.is_at_end_of_line(lines) {
             let indent_len = lines[self.position.line_index].indent().unwrap_or("").len();
             if self.position.byte_index >= indent_len {
@@ -360,7 +360,7 @@ impl Cursor {
         self
     }
 
-    pub fn move_to_end_of_line(self, lines: &[String]) -> Self {
+    pub fn move_to_end_of_line(self, lines: &Lines) -> Self {
         let mut me = self.clone();
         while !me.is_at_end_of_line(lines) {
             me = me.move_to_next_grapheme(lines);
@@ -390,7 +390,7 @@ impl Cursor {
         }
     }
 
-    pub fn move_to_file_end(self, lines: &[String]) -> Self {
+    pub fn move_to_file_end(self, lines: &Lines) -> Self {
         Self {
             position: Position {
                 line_index: lines.len() - 1,
@@ -401,7 +401,7 @@ impl Cursor {
         }
     }
 
-    pub fn move_to_prev_grapheme(self, lines: &[String]) -> Self {
+    pub fn move_to_prev_grapheme(self, lines: &Lines) -> Self {
         Self {
             position: Position {
                 line_index: self.position.line_index,
@@ -416,7 +416,7 @@ impl Cursor {
         }
     }
 
-    pub fn move_to_next_grapheme(self, lines: &[String]) -> Self {
+    pub fn move_to_next_grapheme(self, lines: &Lines) -> Self {
         let line = &lines[self.position.line_index];
         Self {
             position: Position {
@@ -432,7 +432,7 @@ impl Cursor {
         }
     }
 
-    pub fn move_to_end_of_prev_line(self, lines: &[String]) -> Self {
+    pub fn move_to_end_of_prev_line(self, lines: &Lines) -> Self {
         let prev_line_index = self.position.line_index - 1;
         Self {
             position: Position {
@@ -1,12 +1,14 @@
 use {
     crate::{
         char::CharExt,
+        decoration::{Decoration, DecorationType},
         document::Document,
         history::EditKind,
         layout::{BlockElement, Layout, WrappedElement},
+        search::{Search, SearchMatch, SearchOptions},
         selection::{Affinity, Cursor, SelectionSet},
         str::StrExt,
-        text::{Change, Drift, Edit, Length, Position, Text},
+        text::{Change, Drift, Edit, Length, Lines, Position, Text},
         wrap,
         wrap::WrapData,
         Selection, Settings,
@@ -30,6 +32,7 @@ pub struct Session {
     selection_state: RefCell<SelectionState>,
     wrap_column: Cell<Option<usize>>,
     fold_state: RefCell<FoldState>,
+    search_state: RefCell<SearchState>,
     edit_receiver: Receiver<(Option<SelectionSet>, Vec<Edit>)>,
 }
 
@@ -63,6 +66,10 @@ impl Session {
                 folded_lines: HashSet::new(),
                 unfolding_lines: HashSet::new(),
             }),
+            search_state: RefCell::new(SearchState {
+                search: None,
+                matches: Vec::new(),
+            }),
             edit_receiver,
         };
         for line in 0..line_count {
@@ -113,6 +120,16 @@ impl Session {
         })
     }
 
+    pub fn search(&self) -> Ref<'_, Option<Search>> {
+        Ref::map(self.search_state.borrow(), |search_state| &search_state.search)
+    }
+
+    pub fn search_matches(&self) -> Ref<'_, [SearchMatch]> {
+        Ref::map(self.search_state.borrow(), |search_state| {
+            search_state.matches.as_slice()
+        })
+    }
+
     pub fn set_wrap_column(&self, wrap_column: Option<usize>) {
         if self.wrap_column.get() == wrap_column {
             return;
@@ -761,6 +778,11 @@ impl Session {
         string
     }
 
+    pub fn replace_text(&self, text: Text) {
+        self.document
+            .replace_text(self.id, &self.selection_state.borrow().selections, text);
+    }
+
     pub fn undo(&self) -> bool {
         self.selection_state
             .borrow_mut()
@@ -779,6 +801,169 @@ impl Session {
             .redo(self.id, &self.selection_state.borrow().selections)
     }
 
+    pub fn set_search(&self, search: Option<Search>) {
+        self.search_state.borrow_mut().search = search;
+        self.update_search_matches();
+    }
+
+    pub fn find_next(&self) -> bool {
+        let position = self.last_added_selection().end();
+        let search_match = {
+            let search_state = self.search_state.borrow();
+            search_state
+                .matches
+                .iter()
+                .find(|search_match| search_match.start >= position)
+                .or(search_state.matches.first())
+                .copied()
+        };
+        match search_match {
+            Some(search_match) => {
+                self.select_search_match(search_match);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn find_prev(&self) -> bool {
+        let position = self.last_added_selection().start();
+        let search_match = {
+            let search_state = self.search_state.borrow();
+            search_state
+                .matches
+                .iter()
+                .rev()
+                .find(|search_match| search_match.end <= position)
+                .or(search_state.matches.last())
+                .copied()
+        };
+        match search_match {
+            Some(search_match) => {
+                self.select_search_match(search_match);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Replaces the match that is currently selected, if any, and selects the next one.
+    pub fn replace(&self, replacement: &str) -> bool {
+        let selection = self.last_added_selection();
+        let search_state = self.search_state.borrow();
+        let index = match search_state.matches.iter().position(|search_match| {
+            search_match.start == selection.start() && search_match.end == selection.end()
+        }) {
+            Some(index) => index,
+            None => {
+                drop(search_state);
+                return self.find_next();
+            }
+        };
+        let search_match = search_state.matches[index];
+        let next_search_match = search_state
+            .matches
+            .get(index + 1)
+            .or(search_state.matches.first())
+            .copied()
+            .filter(|&next_search_match| next_search_match != search_match);
+        drop(search_state);
+        self.replace_search_matches(&[search_match], replacement);
+        // The selection is in terms of the text before the edit, and is moved along with it
+        // when the edit is handled.
+        if let Some(next_search_match) = next_search_match {
+            self.select_search_match(next_search_match);
+        }
+        true
+    }
+
+    // Replaces all matches as a single undo group, and returns how many there were.
+    pub fn replace_all(&self, replacement: &str) -> usize {
+        let search_matches = self.search_state.borrow().matches.clone();
+        self.replace_search_matches(&search_matches, replacement);
+        search_matches.len()
+    }
+
+    // Adds a selection for the next occurrence of the text in the last added selection, or
+    // selects the word under the cursor if that selection is empty.
+    pub fn add_next_occurrence(&self) -> bool {
+        let selection = self.last_added_selection();
+        if selection.is_empty() {
+            return self.select_word_under_cursor();
+        }
+        let search_matches = match self.occurrences_of(selection) {
+            Some(search_matches) => search_matches,
+            None => return false,
+        };
+        let selections = self.selections();
+        let is_selected = |search_match: &SearchMatch| {
+            selections.iter().any(|selection| {
+                selection.start() == search_match.start && selection.end() == search_match.end
+            })
+        };
+        let search_match = search_matches
+            .iter()
+            .filter(|search_match| !is_selected(search_match))
+            .find(|search_match| search_match.start >= selection.end())
+            .or_else(|| {
+                search_matches
+                    .iter()
+                    .find(|search_match| !is_selected(search_match))
+            })
+            .copied();
+        drop(selections);
+        let search_match = match search_match {
+            Some(search_match) => search_match,
+            None => return false,
+        };
+        let mut selection_state = self.selection_state.borrow_mut();
+        selection_state.last_added_selection_index = Some(
+            selection_state
+                .selections
+                .add_selection(selection_from_search_match(search_match)),
+        );
+        selection_state.injected_char_stack.clear();
+        drop(selection_state);
+        self.update_highlighted_delimiter_positions();
+        self.document().force_new_group();
+        true
+    }
+
+    // Selects every occurrence of the text in the last added selection, or of the word under
+    // the cursor if that selection is empty.
+    pub fn select_all_occurrences(&self) -> bool {
+        if self.last_added_selection().is_empty() && !self.select_word_under_cursor() {
+            return false;
+        }
+        let selection = self.last_added_selection();
+        let search_matches = match self.occurrences_of(selection) {
+            Some(search_matches) => search_matches,
+            None => return false,
+        };
+        let mut selection_state = self.selection_state.borrow_mut();
+        let mut last_added_selection_index = None;
+        for (index, &search_match) in search_matches.iter().enumerate() {
+            let search_match_selection = selection_from_search_match(search_match);
+            let selection_index = if index == 0 {
+                selection_state.selections.set_selection(search_match_selection);
+                0
+            } else {
+                selection_state
+                    .selections
+                    .add_selection(search_match_selection)
+            };
+            if search_match.start == selection.start() {
+                last_added_selection_index = Some(selection_index);
+            }
+        }
+        selection_state.last_added_selection_index = last_added_selection_index.or(Some(0));
+        selection_state.injected_char_stack.clear();
+        drop(selection_state);
+        self.update_highlighted_delimiter_positions();
+        self.document().force_new_group();
+        true
+    }
+
     pub fn handle_changes(&mut self) {
         while let Ok((selections, edits)) = self.edit_receiver.try_recv() {
             self.update_after_edit(selections, &edits);
@@ -814,7 +999,9 @@ impl Session {
     }
 
     fn update_after_edit(&self, selections: Option<SelectionSet>, edits: &[Edit]) {
+        let mut dirty_lines = 0..0;
         for edit in edits {
+            edit.change.extend_dirty_line_range(&mut dirty_lines);
             match edit.change {
                 Change::Insert(point, ref text) => {
                     self.layout.borrow_mut().column_count[point.line_index] = None;
@@ -866,8 +1053,7 @@ impl Session {
                 }
             }
         }
-        let line_count = self.document.as_text().as_lines().len();
-        for line in 0..line_count {
+        for line in dirty_lines {
             if self.layout.borrow().wrap_data[line].is_none() {
                 self.update_wrap_data(line);
             }
@@ -886,6 +1072,139 @@ impl Session {
         }
         drop(selection_state);
         self.update_highlighted_delimiter_positions();
+        self.update_search_matches();
+    }
+
+    fn last_added_selection(&self) -> Selection {
+        let selection_state = self.selection_state.borrow();
+        selection_state.selections[selection_state.last_added_selection_index.unwrap_or(0)]
+    }
+
+    fn select_search_match(&self, search_match: SearchMatch) {
+        let mut selection_state = self.selection_state.borrow_mut();
+        selection_state
+            .selections
+            .set_selection(selection_from_search_match(search_match));
+        selection_state.last_added_selection_index = Some(0);
+        selection_state.injected_char_stack.clear();
+        drop(selection_state);
+        self.update_highlighted_delimiter_positions();
+        self.document().force_new_group();
+    }
+
+    fn select_word_under_cursor(&self) -> bool {
+        let mut selection_state = self.selection_state.borrow_mut();
+        let index = selection_state.last_added_selection_index.unwrap_or(0);
+        let index = selection_state.selections.update_selection(index, |selection| {
+            grow_selection(
+                selection,
+                self.document.as_text().as_lines(),
+                SelectionMode::Word,
+                &self.settings.word_separators,
+            )
+        });
+        selection_state.last_added_selection_index = Some(index);
+        let is_empty = selection_state.selections[index].is_empty();
+        drop(selection_state);
+        self.update_highlighted_delimiter_positions();
+        !is_empty
+    }
+
+    // The occurrences of the text in `selection`, found with the active search if the
+    // selection is one of its matches, and as literal text otherwise.
+    fn occurrences_of(&self, selection: Selection) -> Option<Vec<SearchMatch>> {
+        if selection.start().line_index != selection.end().line_index {
+            return None;
+        }
+        let search_state = self.search_state.borrow();
+        if search_state.matches.iter().any(|search_match| {
+            search_match.start == selection.start() && search_match.end == selection.end()
+        }) {
+            return Some(search_state.matches.clone());
+        }
+        let text = self.document.as_text();
+        let line = &text.as_lines()[selection.start().line_index];
+        let search = Search::new(
+            &line[selection.start().byte_index..selection.end().byte_index],
+            SearchOptions {
+                case_sensitive: true,
+                ..SearchOptions::default()
+            },
+        )
+        .unwrap();
+        Some(search.find_all(text.as_lines(), &self.settings.word_separators))
+    }
+
+    fn replace_search_matches(&self, search_matches: &[SearchMatch], replacement: &str) {
+        let search = match &self.search_state.borrow().search {
+            Some(search) => search.clone(),
+            None => return,
+        };
+        if search_matches.is_empty() {
+            return;
+        }
+        let replacements: Vec<_> = {
+            let text = self.document.as_text();
+            search_matches
+                .iter()
+                .map(|&search_match| {
+                    Text::from(search.expand_replacement(text.as_lines(), search_match, replacement))
+                })
+                .collect()
+        };
+        let mut selections = SelectionSet::new();
+        for (index, &search_match) in search_matches.iter().enumerate() {
+            if index == 0 {
+                selections.set_selection(selection_from_search_match(search_match));
+            } else {
+                selections.add_selection(selection_from_search_match(search_match));
+            }
+        }
+        let mut replacements = replacements.into_iter();
+        self.document.edit_selections(
+            self.id,
+            EditKind::Other,
+            &selections,
+            &self.settings,
+            |mut editor, position, length| {
+                let replacement = replacements.next().unwrap();
+                editor.apply_edit(Edit {
+                    change: Change::Delete(position, length),
+                    drift: Drift::Before,
+                });
+                if !replacement.is_empty() {
+                    editor.apply_edit(Edit {
+                        change: Change::Insert(position, replacement),
+                        drift: Drift::Before,
+                    });
+                }
+            },
+        );
+    }
+
+    fn update_search_matches(&self) {
+        let mut search_state = self.search_state.borrow_mut();
+        if search_state.search.is_none() && search_state.matches.is_empty() {
+            return;
+        }
+        search_state.matches = match &search_state.search {
+            Some(search) => search.find_all(
+                self.document.as_text().as_lines(),
+                &self.settings.word_separators,
+            ),
+            None => Vec::new(),
+        };
+        self.document.replace_decorations(
+            DecorationType::SearchMatch,
+            search_state.matches.iter().map(|search_match| {
+                Decoration::new(
+                    0,
+                    search_match.start,
+                    search_match.end,
+                    DecorationType::SearchMatch,
+                )
+            }),
+        );
     }
 
     fn update_y(&self) {
@@ -981,6 +1300,7 @@ impl Session {
 
 impl Drop for Session {
     fn drop(&mut self) {
+        self.set_search(None);
         self.document.remove_session(self.id);
     }
 }
@@ -1014,6 +1334,12 @@ struct SelectionState {
     highlighted_delimiter_positions: HashSet<Position>,
 }
 
+#[derive(Debug)]
+struct SearchState {
+    search: Option<Search>,
+    matches: Vec<SearchMatch>,
+}
+
 #[derive(Debug)]
 struct FoldState {
     folding_lines: HashSet<usize>,
@@ -1036,7 +1362,7 @@ pub fn reindent(string: &str, f: impl FnOnce(usize) -> usize) -> (usize, usize,
 
 fn grow_selection(
     selection: Selection,
-    lines: &[String],
+    lines: &Lines,
     mode: SelectionMode,
     word_separators: &[char],
 ) -> Selection {
@@ -1155,12 +1481,23 @@ fn grow_selection(
     }
 }
 
+fn selection_from_search_match(search_match: SearchMatch) -> Selection {
+    Selection {
+        cursor: Cursor {
+            position: search_match.end,
+            affinity: Affinity::Before,
+            preferred_column_index: None,
+        },
+        anchor: search_match.start,
+    }
+}
+
 fn new_indentation(column_count: usize) -> String {
     iter::repeat(' ').take(column_count).collect()
 }
 
 fn find_highlighted_delimiter_pair(
-    lines: &[String],
+    lines: &Lines,
     position: Position,
 ) -> Option<(Position, Position)> {
     // Cursor is before an opening delimiter
@@ -1234,7 +1571,7 @@ fn find_highlighted_delimiter_pair(
 }
 
 fn find_opening_delimiter(
-    lines: &[String],
+    lines: &Lines,
     position: Position,
     closing_delimiter: char,
 ) -> Option<Position> {
@@ -1268,7 +1605,7 @@ fn find_opening_delimiter(
 }
 
 fn find_closing_delimiter(
-    lines: &[String],
+    lines: &Lines,
     position: Position,
     opening_delimiter: char,
 ) -> Option<Position> {
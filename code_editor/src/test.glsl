@@ -0,0 +1,42 @@
+#version 100
+#extension GL_OES_standard_derivatives : enable
+precision highp float;
+
+/* A block comment
+   that spans lines */
+uniform float pass_table[36];
+uniform sampler2D ds_tex;
+attribute vec2 packed_geometry_0;
+varying vec4 packed_varying_0;
+
+const float PI = 3.141592653589793;
+const int MAX_STEPS = 0x20;
+
+struct Ray {
+    vec3 origin;
+    vec3 dir;
+};
+
+vec4 sample2d(sampler2D sampler, vec2 pos) {
+    return texture2D(sampler, vec2(pos.x, 1.0 - pos.y));
+}
+
+float sdf_circle(vec2 p, float r) {
+    return length(p) - r; // signed distance
+}
+
+void main() {
+    vec2 uv = packed_varying_0.xy;
+    float d = 1e10;
+    for (int i = 0; i < MAX_STEPS; i++) {
+        if (d < .001) {
+            break;
+        }
+        d = min(d, sdf_circle(uv * 2.0, 0.5));
+    }
+    if (d > 1.0) {
+        discard;
+    }
+    uv.x += fwidth(d) * 0.5f;
+    gl_FragColor = mix(sample2d(ds_tex, uv), vec4(1.0, 0.0, 0.0, 1.0), step(0.0, d));
+}
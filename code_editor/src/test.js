@@ -0,0 +1,42 @@
+// A sample script that exercises the JavaScript tokenizer
+import { readFile } from "fs/promises";
+
+/* A block comment
+   that spans lines */
+const MAX_RETRIES = 3;
+let $element = null, _private = undefined;
+
+class WasmBridge extends EventTarget {
+    static instances = 0;
+
+    constructor(url) {
+        super();
+        this.url = url;
+        this.memory = new WebAssembly.Memory({ initial: 0x100, maximum: 1_000 });
+        WasmBridge.instances++;
+    }
+
+    async load() {
+        for (let attempt = 0; attempt < MAX_RETRIES; attempt += 1) {
+            try {
+                const response = await fetch(this.url);
+                return await WebAssembly.instantiateStreaming(response, {});
+            } catch (error) {
+                console.error(`attempt ${attempt} failed: ${error.message}
+continuing on the next line`);
+            }
+        }
+        throw new Error('could not load ' + this.url);
+    }
+}
+
+const big = 9007199254740993n;
+const ratio = .5e-3;
+const escaped = "a \"quoted\" string";
+const maybe = $element?.value ?? NaN;
+const spread = [...[1, 2, 3]].map((x) => x ** 2);
+if (typeof window !== "undefined" && spread.length === 3) {
+    window.bridge = new WasmBridge("app.wasm");
+} else {
+    readFile("app.wasm").then((bytes) => console.log(bytes.length >>> 0));
+}
@@ -0,0 +1,49 @@
+enable f16;
+
+/* A block comment /* that nests */
+   and spans lines */
+struct VertexInput {
+    @location(0) pos: vec2<f32>,
+    @builtin(instance_index) instance: u32,
+};
+
+struct Varyings {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2f,
+};
+
+@group(0) @binding(0) var<uniform> pass_table: array<vec4<f32>, 9>;
+@group(1) @binding(0) var ds_tex: texture_2d<f32>;
+@group(1) @binding(1) var ds_tex_sampler: sampler;
+
+const MAX_STEPS: i32 = 0x20;
+override scale: f32 = 1.0;
+
+fn sdf_circle(p: vec2<f32>, r: f32) -> f32 {
+    return length(p) - r; // signed distance
+}
+
+@vertex
+fn vertex_main(input: VertexInput) -> Varyings {
+    var out: Varyings;
+    out.position = vec4<f32>(input.pos * scale, 0.0, 1.0);
+    out.uv = input.pos;
+    return out;
+}
+
+@fragment
+fn pixel_main(in: Varyings) -> @location(0) vec4<f32> {
+    var d = 1e10f;
+    var i = 0i;
+    loop {
+        if i >= MAX_STEPS || d < .001 {
+            break;
+        }
+        d = min(d, sdf_circle(in.uv * 2.0, 0.5h));
+        continuing {
+            i += 1;
+        }
+    }
+    let color = textureSample(ds_tex, ds_tex_sampler, in.uv);
+    return select(color, vec4<f32>(1.0, 0.0, 0.0, 1.0), d > 1.0);
+}
@@ -0,0 +1,169 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{classify_identifier, CharExt, Cursor, LanguageTokenizer},
+};
+
+// GLSL and WGSL share their C-like lexical structure, so both tokenizers are the
+// same state machine with different keywords.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct GlslTokenizer;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct WgslTokenizer;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ShaderState {
+    #[default]
+    Initial,
+    // WGSL block comments nest, GLSL ones never get past depth 0.
+    BlockCommentTail { depth: usize },
+}
+
+impl LanguageTokenizer for GlslTokenizer {
+    type State = ShaderState;
+
+    fn next(&self, state: ShaderState, cursor: &mut Cursor) -> (ShaderState, TokenKind) {
+        match state {
+            ShaderState::Initial => initial(cursor, Dialect::Glsl),
+            ShaderState::BlockCommentTail { depth } => block_comment_tail(cursor, depth, Dialect::Glsl),
+        }
+    }
+}
+
+impl LanguageTokenizer for WgslTokenizer {
+    type State = ShaderState;
+
+    fn next(&self, state: ShaderState, cursor: &mut Cursor) -> (ShaderState, TokenKind) {
+        match state {
+            ShaderState::Initial => initial(cursor, Dialect::Wgsl),
+            ShaderState::BlockCommentTail { depth } => block_comment_tail(cursor, depth, Dialect::Wgsl),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Dialect {
+    Glsl,
+    Wgsl,
+}
+
+const PUNCTUATORS: &[&str] = &[
+    "<<=", ">>=", "++", "--", "+=", "-=", "*=", "/=", "%=", "&=", "|=", "^=", "==", "!=", "<=",
+    ">=", "&&", "||", "^^", "<<", ">>", "->", "+", "-", "*", "/", "%", "&", "|", "^", "!", "~",
+    "=", "<", ">", "?", ":", ";", ",", ".",
+];
+
+fn initial(cursor: &mut Cursor, dialect: Dialect) -> (ShaderState, TokenKind) {
+    let kind = match (cursor.peek(0), cursor.peek(1)) {
+        ('/', '/') => {
+            cursor.skip_to_end();
+            TokenKind::Comment
+        }
+        ('/', '*') => {
+            cursor.skip(2);
+            return block_comment_tail(cursor, 0, dialect);
+        }
+        // preprocessor directives and attributes
+        ('#', _) if dialect == Dialect::Glsl => {
+            cursor.skip(1);
+            cursor.skip_while(|char| char.is_whitespace());
+            cursor.skip_suffix();
+            TokenKind::OtherKeyword
+        }
+        ('@', _) if dialect == Dialect::Wgsl => {
+            cursor.skip(1);
+            cursor.skip_suffix();
+            TokenKind::OtherKeyword
+        }
+        ('(', _) | (')', _) | ('[', _) | (']', _) | ('{', _) | ('}', _) => {
+            cursor.skip(1);
+            TokenKind::Delimiter
+        }
+        ('.', char) | (char, _) if char.is_ascii_digit() => {
+            let kind = cursor.skip_number();
+            cursor.skip_suffix();
+            kind
+        }
+        (char, _) if char.is_identifier_start() => {
+            let string = cursor.skip_identifier();
+            let kind = match dialect {
+                Dialect::Glsl => glsl_keyword(string),
+                Dialect::Wgsl => wgsl_keyword(string),
+            };
+            kind.unwrap_or_else(|| classify_identifier(string, cursor))
+        }
+        (char, _) if char.is_whitespace() => cursor.skip_whitespace(),
+        _ => {
+            if !cursor.skip_longest(PUNCTUATORS) {
+                cursor.skip(1);
+                return (ShaderState::Initial, TokenKind::Unknown);
+            }
+            TokenKind::Punctuator
+        }
+    };
+    (ShaderState::Initial, kind)
+}
+
+fn block_comment_tail(cursor: &mut Cursor, mut depth: usize, dialect: Dialect) -> (ShaderState, TokenKind) {
+    loop {
+        match (cursor.peek(0), cursor.peek(1)) {
+            ('/', '*') if dialect == Dialect::Wgsl => {
+                cursor.skip(2);
+                depth += 1;
+            }
+            ('*', '/') => {
+                cursor.skip(2);
+                if depth == 0 {
+                    break (ShaderState::Initial, TokenKind::Comment);
+                }
+                depth -= 1;
+            }
+            ('\0', _) => break (ShaderState::BlockCommentTail { depth }, TokenKind::Comment),
+            _ => cursor.skip(1),
+        }
+    }
+}
+
+fn glsl_keyword(string: &str) -> Option<TokenKind> {
+    Some(match string {
+        "if" | "else" | "switch" | "case" | "default" | "return" | "discard" => {
+            TokenKind::BranchKeyword
+        }
+        "for" | "while" | "do" | "break" | "continue" => TokenKind::LoopKeyword,
+        "attribute" | "const" | "uniform" | "varying" | "buffer" | "shared" | "layout"
+        | "centroid" | "flat" | "smooth" | "noperspective" | "patch" | "sample" | "in"
+        | "out" | "inout" | "invariant" | "precise" | "precision" | "lowp" | "mediump"
+        | "highp" | "struct" | "coherent" | "volatile" | "restrict" | "readonly"
+        | "writeonly" => TokenKind::OtherKeyword,
+        "true" | "false" => TokenKind::Constant,
+        "void" | "bool" | "int" | "uint" | "float" | "double" | "vec2" | "vec3" | "vec4"
+        | "ivec2" | "ivec3" | "ivec4" | "uvec2" | "uvec3" | "uvec4" | "bvec2" | "bvec3"
+        | "bvec4" | "dvec2" | "dvec3" | "dvec4" | "mat2" | "mat3" | "mat4" | "mat2x2"
+        | "mat2x3" | "mat2x4" | "mat3x2" | "mat3x3" | "mat3x4" | "mat4x2" | "mat4x3"
+        | "mat4x4" | "sampler2D" | "sampler3D" | "samplerCube" | "sampler2DShadow"
+        | "sampler2DArray" | "samplerExternalOES" | "texture2D" | "texture3D"
+        | "textureCube" | "sampler" | "image2D" => TokenKind::Typename,
+        _ => return None,
+    })
+}
+
+fn wgsl_keyword(string: &str) -> Option<TokenKind> {
+    Some(match string {
+        "if" | "else" | "switch" | "case" | "default" | "return" | "discard" => {
+            TokenKind::BranchKeyword
+        }
+        "for" | "while" | "loop" | "break" | "continue" | "continuing" => TokenKind::LoopKeyword,
+        "fn" | "let" | "var" | "const" | "override" | "struct" | "alias" | "enable"
+        | "requires" | "diagnostic" | "const_assert" | "function" | "private" | "workgroup"
+        | "uniform" | "storage" | "read" | "write" | "read_write" => TokenKind::OtherKeyword,
+        "true" | "false" => TokenKind::Constant,
+        "bool" | "i32" | "u32" | "f32" | "f16" | "vec2" | "vec3" | "vec4" | "vec2f"
+        | "vec3f" | "vec4f" | "vec2i" | "vec3i" | "vec4i" | "vec2u" | "vec3u" | "vec4u"
+        | "mat2x2" | "mat2x3" | "mat2x4" | "mat3x2" | "mat3x3" | "mat3x4" | "mat4x2"
+        | "mat4x3" | "mat4x4" | "mat2x2f" | "mat3x3f" | "mat4x4f" | "array" | "atomic"
+        | "ptr" | "sampler" | "sampler_comparison" | "texture_2d" | "texture_3d"
+        | "texture_cube" | "texture_depth_2d" | "texture_external"
+        | "texture_storage_2d" => TokenKind::Typename,
+        _ => return None,
+    })
+}
@@ -0,0 +1,111 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{classify_identifier, CharExt, Cursor, LanguageTokenizer},
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct JavascriptTokenizer;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum JavascriptState {
+    #[default]
+    Initial,
+    BlockCommentTail,
+    // Template literals can span lines, their `${}` substitutions are highlighted as
+    // part of the string.
+    TemplateStringTail,
+}
+
+impl LanguageTokenizer for JavascriptTokenizer {
+    type State = JavascriptState;
+
+    fn next(&self, state: JavascriptState, cursor: &mut Cursor) -> (JavascriptState, TokenKind) {
+        match state {
+            JavascriptState::Initial => initial(cursor),
+            JavascriptState::BlockCommentTail => block_comment_tail(cursor),
+            JavascriptState::TemplateStringTail => template_string_tail(cursor),
+        }
+    }
+}
+
+const PUNCTUATORS: &[&str] = &[
+    ">>>=", "===", "!==", "**=", "...", ">>>", "&&=", "||=", "??=", "<<=", ">>=", "=>", "==",
+    "!=", "<=", ">=", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=", "/=", "%=", "**",
+    "<<", ">>", "&=", "|=", "^=", "+", "-", "*", "/", "%", "&", "|", "^", "!", "~", "=", "<",
+    ">", "?", ":", ";", ",", ".",
+];
+
+fn initial(cursor: &mut Cursor) -> (JavascriptState, TokenKind) {
+    let kind = match (cursor.peek(0), cursor.peek(1)) {
+        ('/', '/') => {
+            cursor.skip_to_end();
+            TokenKind::Comment
+        }
+        ('/', '*') => {
+            cursor.skip(2);
+            return block_comment_tail(cursor);
+        }
+        ('`', _) => {
+            cursor.skip(1);
+            return template_string_tail(cursor);
+        }
+        (quote @ '"', _) | (quote @ '\'', _) => {
+            cursor.skip(1);
+            cursor.skip_quoted_tail(quote);
+            TokenKind::String
+        }
+        ('(', _) | (')', _) | ('[', _) | (']', _) | ('{', _) | ('}', _) => {
+            cursor.skip(1);
+            TokenKind::Delimiter
+        }
+        ('.', char) | (char, _) if char.is_ascii_digit() => {
+            let kind = cursor.skip_number();
+            // BigInt literals end in n
+            cursor.skip_if(|char| char == 'n');
+            kind
+        }
+        (char, _) if char.is_identifier_start() || char == '$' => {
+            let start = cursor.index();
+            cursor.skip_while(|char| char.is_identifier_continue() || char == '$');
+            let string = cursor.slice_from(start);
+            match string {
+                "if" | "else" | "switch" | "case" | "default" | "return" | "throw" | "try"
+                | "catch" | "finally" => TokenKind::BranchKeyword,
+                "for" | "while" | "do" | "break" | "continue" | "of" => TokenKind::LoopKeyword,
+                "var" | "let" | "const" | "function" | "class" | "extends" | "new" | "delete"
+                | "typeof" | "instanceof" | "in" | "this" | "super" | "import" | "export"
+                | "from" | "as" | "async" | "await" | "yield" | "static" | "get" | "set"
+                | "void" | "with" | "debugger" => TokenKind::OtherKeyword,
+                "true" | "false" | "null" | "undefined" | "NaN" | "Infinity" => {
+                    TokenKind::Constant
+                }
+                _ => classify_identifier(string, cursor),
+            }
+        }
+        (char, _) if char.is_whitespace() => cursor.skip_whitespace(),
+        _ => {
+            if !cursor.skip_longest(PUNCTUATORS) {
+                cursor.skip(1);
+                return (JavascriptState::Initial, TokenKind::Unknown);
+            }
+            TokenKind::Punctuator
+        }
+    };
+    (JavascriptState::Initial, kind)
+}
+
+fn block_comment_tail(cursor: &mut Cursor) -> (JavascriptState, TokenKind) {
+    if cursor.skip_past("*/") {
+        (JavascriptState::Initial, TokenKind::Comment)
+    } else {
+        (JavascriptState::BlockCommentTail, TokenKind::Comment)
+    }
+}
+
+fn template_string_tail(cursor: &mut Cursor) -> (JavascriptState, TokenKind) {
+    if cursor.skip_quoted_tail('`') {
+        (JavascriptState::Initial, TokenKind::String)
+    } else {
+        (JavascriptState::TemplateStringTail, TokenKind::String)
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{CharExt, Cursor, LanguageTokenizer},
+};
+
+// JSON has no tokens that span lines, so it needs no state. Object keys are
+// tokenized as identifiers to tell them apart from string values.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct JsonTokenizer;
+
+impl LanguageTokenizer for JsonTokenizer {
+    type State = ();
+
+    fn next(&self, _state: (), cursor: &mut Cursor) -> ((), TokenKind) {
+        let kind = match (cursor.peek(0), cursor.peek(1)) {
+            ('"', _) => {
+                cursor.skip(1);
+                cursor.skip_quoted_tail('"');
+                if cursor.rest().trim_start().starts_with(':') {
+                    TokenKind::Identifier
+                } else {
+                    TokenKind::String
+                }
+            }
+            ('-', char) | (char, _) if char.is_ascii_digit() => {
+                cursor.skip_if(|char| char == '-');
+                cursor.skip_number()
+            }
+            ('{', _) | ('}', _) | ('[', _) | (']', _) => {
+                cursor.skip(1);
+                TokenKind::Delimiter
+            }
+            (',', _) | (':', _) => {
+                cursor.skip(1);
+                TokenKind::Punctuator
+            }
+            (char, _) if char.is_identifier_start() => match cursor.skip_identifier() {
+                "true" | "false" | "null" => TokenKind::Constant,
+                _ => TokenKind::Unknown,
+            },
+            (char, _) if char.is_whitespace() => cursor.skip_whitespace(),
+            _ => {
+                cursor.skip(1);
+                TokenKind::Unknown
+            }
+        };
+        ((), kind)
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{Cursor, LanguageTokenizer},
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct MarkdownTokenizer;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum MarkdownState {
+    #[default]
+    Initial,
+    // Inside a fenced code block, which ends at a line starting with the same fence char.
+    FencedCode(char),
+    HtmlCommentTail,
+}
+
+impl LanguageTokenizer for MarkdownTokenizer {
+    type State = MarkdownState;
+
+    fn next(&self, state: MarkdownState, cursor: &mut Cursor) -> (MarkdownState, TokenKind) {
+        match state {
+            MarkdownState::Initial => initial(cursor),
+            MarkdownState::FencedCode(fence) => fenced_code(cursor, fence),
+            MarkdownState::HtmlCommentTail => html_comment_tail(cursor),
+        }
+    }
+}
+
+fn initial(cursor: &mut Cursor) -> (MarkdownState, TokenKind) {
+    let at_line_start = cursor.preceding().trim().is_empty();
+    let kind = match (cursor.peek(0), cursor.peek(1), cursor.peek(2)) {
+        (fence @ '`', '`', '`') | (fence @ '~', '~', '~') if at_line_start => {
+            cursor.skip_to_end();
+            return (MarkdownState::FencedCode(fence), TokenKind::Punctuator);
+        }
+        ('<', '!', '-') if cursor.rest().starts_with("<!--") => {
+            cursor.skip(4);
+            return html_comment_tail(cursor);
+        }
+        ('#', _, _) if at_line_start && is_heading(cursor.rest()) => {
+            cursor.skip_to_end();
+            TokenKind::Typename
+        }
+        ('>', _, _) if at_line_start => {
+            cursor.skip(1);
+            TokenKind::Punctuator
+        }
+        // list items and horizontal rules
+        ('-', ' ', _) | ('*', ' ', _) | ('+', ' ', _) if at_line_start => {
+            cursor.skip(1);
+            TokenKind::Punctuator
+        }
+        ('-', '-', '-') | ('*', '*', '*') | ('_', '_', '_') if at_line_start && is_rule(cursor.rest()) => {
+            cursor.skip_to_end();
+            TokenKind::Punctuator
+        }
+        (char, _, _) if char.is_ascii_digit() && at_line_start && is_ordered_list_item(cursor.rest()) => {
+            cursor.skip_while(|char| char.is_ascii_digit());
+            cursor.skip(1);
+            TokenKind::Punctuator
+        }
+        ('`', _, _) => {
+            // a code span ends at a run of as many backticks as it started with
+            let start = cursor.index();
+            cursor.skip_while(|char| char == '`');
+            let fence = cursor.slice_from(start);
+            cursor.skip_past(fence);
+            TokenKind::String
+        }
+        ('*', _, _) | ('_', _, _) if !cursor.preceding().ends_with(char::is_alphanumeric) => {
+            let emphasis = cursor.peek(0);
+            cursor.skip_while(|char| char == emphasis);
+            TokenKind::Punctuator
+        }
+        ('!', '[', _) => {
+            cursor.skip(1);
+            TokenKind::Punctuator
+        }
+        ('[', _, _) | (']', _, _) => {
+            cursor.skip(1);
+            TokenKind::Delimiter
+        }
+        // the destination of a link
+        ('(', _, _) if cursor.preceding().ends_with(']') => {
+            cursor.skip_past(")");
+            TokenKind::String
+        }
+        ('\\', _, _) => {
+            cursor.skip(2);
+            TokenKind::Constant
+        }
+        (char, _, _) if char.is_whitespace() => cursor.skip_whitespace(),
+        _ => {
+            cursor.skip(1);
+            cursor.skip_while(|char| !char.is_whitespace() && !is_inline_marker(char));
+            TokenKind::Identifier
+        }
+    };
+    (MarkdownState::Initial, kind)
+}
+
+fn fenced_code(cursor: &mut Cursor, fence: char) -> (MarkdownState, TokenKind) {
+    let is_closing_fence = cursor.preceding().trim().is_empty() && {
+        let rest = cursor.rest().trim_start();
+        rest.chars().take(3).filter(|char| *char == fence).count() == 3
+    };
+    cursor.skip_to_end();
+    if is_closing_fence {
+        (MarkdownState::Initial, TokenKind::Punctuator)
+    } else {
+        (MarkdownState::FencedCode(fence), TokenKind::String)
+    }
+}
+
+fn html_comment_tail(cursor: &mut Cursor) -> (MarkdownState, TokenKind) {
+    if cursor.skip_past("-->") {
+        (MarkdownState::Initial, TokenKind::Comment)
+    } else {
+        (MarkdownState::HtmlCommentTail, TokenKind::Comment)
+    }
+}
+
+fn is_heading(rest: &str) -> bool {
+    let level = rest.chars().take_while(|char| *char == '#').count();
+    level <= 6 && matches!(rest[level..].chars().next(), None | Some(' ' | '\t'))
+}
+
+fn is_rule(rest: &str) -> bool {
+    let mut chars = rest.chars().filter(|char| !char.is_whitespace());
+    let first = chars.next().unwrap();
+    chars.all(|char| char == first)
+}
+
+fn is_ordered_list_item(rest: &str) -> bool {
+    let digits = rest.chars().take_while(|char| char.is_ascii_digit()).count();
+    let mut after = rest[digits..].chars();
+    matches!(after.next(), Some('.') | Some(')')) && matches!(after.next(), None | Some(' '))
+}
+
+fn is_inline_marker(char: char) -> bool {
+    matches!(char, '`' | '*' | '_' | '[' | ']' | '!' | '\\' | '<')
+}
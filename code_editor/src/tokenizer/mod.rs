@@ -0,0 +1,402 @@
+pub mod glsl;
+pub mod javascript;
+pub mod json;
+pub mod markdown;
+pub mod rust;
+pub mod toml;
+
+pub use self::{
+    glsl::{GlslTokenizer, WgslTokenizer},
+    javascript::JavascriptTokenizer,
+    json::JsonTokenizer,
+    markdown::MarkdownTokenizer,
+    rust::RustTokenizer,
+    toml::TomlTokenizer,
+};
+
+use {
+    crate::{
+        text::{Change, Text},
+        token::TokenKind,
+        Token,
+    },
+    std::{fmt, mem, ops::Range},
+};
+
+// A state machine that splits a single line into tokens. Tokens can span lines through
+// `State`, which is carried from the end of one line to the start of the next, so the
+// `Tokenizer` only has to retokenize a line when the state it starts in changes.
+pub trait LanguageTokenizer: fmt::Debug {
+    type State: Clone + Copy + fmt::Debug + Default + Eq;
+
+    // Consumes at least one char from `cursor`, which is never at the end of the line.
+    fn next(&self, state: Self::State, cursor: &mut Cursor) -> (Self::State, TokenKind);
+}
+
+#[derive(Debug)]
+pub struct Tokenizer {
+    lines: Box<dyn LineStates>,
+}
+
+impl Tokenizer {
+    pub fn new<L: LanguageTokenizer + 'static>(language: L, line_count: usize) -> Self {
+        Self {
+            lines: Box::new(LanguageLineStates {
+                language,
+                state: (0..line_count).map(|_| None).collect(),
+                dirty_lines: 0..line_count,
+            }),
+        }
+    }
+
+    // Picks the language from a file extension, anything unknown is tokenized as Rust.
+    pub fn for_extension(extension: &str, line_count: usize) -> Self {
+        match extension {
+            "toml" => Self::new(TomlTokenizer, line_count),
+            "md" | "markdown" => Self::new(MarkdownTokenizer, line_count),
+            "json" => Self::new(JsonTokenizer, line_count),
+            "glsl" | "vert" | "frag" | "comp" => Self::new(GlslTokenizer, line_count),
+            "wgsl" => Self::new(WgslTokenizer, line_count),
+            "js" | "mjs" | "cjs" => Self::new(JavascriptTokenizer, line_count),
+            _ => Self::new(RustTokenizer, line_count),
+        }
+    }
+
+    pub fn apply_change(&mut self, change: &Change) {
+        self.lines.apply_change(change);
+    }
+
+    pub fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]) {
+        self.lines.update(text, tokens);
+    }
+}
+
+trait LineStates: fmt::Debug {
+    fn apply_change(&mut self, change: &Change);
+
+    fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]);
+}
+
+#[derive(Debug)]
+struct LanguageLineStates<L: LanguageTokenizer> {
+    language: L,
+    state: Vec<Option<(L::State, L::State)>>,
+    dirty_lines: Range<usize>,
+}
+
+impl<L: LanguageTokenizer> LineStates for LanguageLineStates<L> {
+    fn apply_change(&mut self, change: &Change) {
+        match *change {
+            Change::Insert(point, ref text) => {
+                self.state[point.line_index] = None;
+                let line_count = text.length().line_count;
+                if line_count > 0 {
+                    let line = point.line_index + 1;
+                    self.state.splice(line..line, (0..line_count).map(|_| None));
+                }
+            }
+            Change::Delete(start, length) => {
+                self.state[start.line_index] = None;
+                let line_count = length.line_count;
+                if line_count > 0 {
+                    let start_line = start.line_index + 1;
+                    let end_line = start_line + line_count;
+                    self.state.drain(start_line..end_line);
+                }
+            }
+        }
+        change.extend_dirty_line_range(&mut self.dirty_lines);
+    }
+
+    // Only retokenizes from the first dirty line on, and stops at the first line after the
+    // dirty lines that starts in the same state as before, since all lines after it are then
+    // unaffected.
+    fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]) {
+        let dirty_lines = mem::replace(&mut self.dirty_lines, 0..0);
+        if dirty_lines.is_empty() {
+            return;
+        }
+        let mut state = match dirty_lines.start {
+            0 => L::State::default(),
+            line => self.state[line - 1].unwrap().1,
+        };
+        let lines = text.as_lines();
+        for (line, string) in lines.range(dirty_lines.start..lines.len()).enumerate() {
+            let line = dirty_lines.start + line;
+            match self.state[line] {
+                Some((start_state, _)) if state == start_state && line >= dirty_lines.end => {
+                    break;
+                }
+                Some((start_state, end_state)) if state == start_state => {
+                    state = end_state;
+                }
+                _ => {
+                    let start_state = state;
+                    let mut new_tokens = Vec::new();
+                    let mut cursor = Cursor::new(string);
+                    loop {
+                        let (next_state, token) = next_token(&self.language, state, &mut cursor);
+                        state = next_state;
+                        match token {
+                            Some(token) => new_tokens.push(token),
+                            None => break,
+                        }
+                    }
+                    self.state[line] = Some((start_state, state));
+                    tokens[line] = new_tokens;
+                }
+            }
+        }
+    }
+}
+
+fn next_token<L: LanguageTokenizer>(
+    language: &L,
+    state: L::State,
+    cursor: &mut Cursor,
+) -> (L::State, Option<Token>) {
+    if cursor.peek(0) == '\0' {
+        return (state, None);
+    }
+    let start = cursor.index;
+    let (next_state, kind) = language.next(state, cursor);
+    let end = cursor.index;
+    assert!(start < end);
+    (
+        next_state,
+        Some(Token {
+            len: end - start,
+            kind,
+        }),
+    )
+}
+
+#[derive(Debug)]
+pub struct Cursor<'a> {
+    string: &'a str,
+    index: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(string: &'a str) -> Self {
+        Cursor { string, index: 0 }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    // The text from `start` up to the cursor.
+    pub fn slice_from(&self, start: usize) -> &'a str {
+        &self.string[start..self.index]
+    }
+
+    // The text of the line before the cursor.
+    pub fn preceding(&self) -> &'a str {
+        &self.string[..self.index]
+    }
+
+    // The text of the line from the cursor on.
+    pub fn rest(&self) -> &'a str {
+        &self.string[self.index..]
+    }
+
+    pub fn peek(&self, index: usize) -> char {
+        self.string[self.index..].chars().nth(index).unwrap_or('\0')
+    }
+
+    pub fn skip(&mut self, count: usize) {
+        self.index = self.string[self.index..]
+            .char_indices()
+            .nth(count)
+            .map_or(self.string.len(), |(index, _)| self.index + index);
+    }
+
+    pub fn skip_if<P>(&mut self, predicate: P) -> bool
+    where
+        P: FnOnce(char) -> bool,
+    {
+        if predicate(self.peek(0)) {
+            self.skip(1);
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn skip_while<P>(&mut self, mut predicate: P)
+    where
+        P: FnMut(char) -> bool,
+    {
+        while self.peek(0) != '\0' && self.skip_if(&mut predicate) {}
+    }
+
+    pub fn skip_exponent(&mut self) -> bool {
+        debug_assert!(self.peek(0) == 'E' || self.peek(0) == 'e');
+        self.skip(1);
+        if self.peek(0) == '+' || self.peek(0) == '-' {
+            self.skip(1);
+        }
+        self.skip_digits(10)
+    }
+
+    pub fn skip_digits(&mut self, radix: u32) -> bool {
+        let mut has_skip_digits = false;
+        loop {
+            match self.peek(0) {
+                '_' => {
+                    self.skip(1);
+                }
+                char if char.is_digit(radix) => {
+                    self.skip(1);
+                    has_skip_digits = true;
+                }
+                _ => break,
+            }
+        }
+        has_skip_digits
+    }
+
+    pub fn skip_suffix(&mut self) -> bool {
+        if self.peek(0).is_identifier_start() {
+            self.skip(1);
+            while self.skip_if(|char| char.is_identifier_continue()) {}
+            return true;
+        }
+        false
+    }
+
+    // Skips an identifier and returns it, the cursor has to be at an identifier start.
+    pub fn skip_identifier(&mut self) -> &'a str {
+        debug_assert!(self.peek(0).is_identifier_start());
+        let start = self.index;
+        self.skip(1);
+        while self.skip_if(|char| char.is_identifier_continue()) {}
+        self.slice_from(start)
+    }
+
+    pub fn skip_whitespace(&mut self) -> TokenKind {
+        debug_assert!(self.peek(0).is_whitespace());
+        self.skip(1);
+        while self.skip_if(|char| char.is_whitespace()) {}
+        TokenKind::Whitespace
+    }
+
+    // Skips a number with an optional fraction and exponent, or a 0x, 0o or 0b literal.
+    pub fn skip_number(&mut self) -> TokenKind {
+        match (self.peek(0), self.peek(1)) {
+            ('0', 'b') | ('0', 'o') | ('0', 'x') => {
+                let radix = match self.peek(1) {
+                    'b' => 2,
+                    'o' => 8,
+                    _ => 16,
+                };
+                self.skip(2);
+                if !self.skip_digits(radix) {
+                    return TokenKind::Unknown;
+                }
+                TokenKind::Number
+            }
+            _ => {
+                self.skip_digits(10);
+                if self.peek(0) == '.' && self.peek(1) != '.' {
+                    self.skip(1);
+                    self.skip_digits(10);
+                }
+                if self.peek(0) == 'E' || self.peek(0) == 'e' {
+                    if !self.skip_exponent() {
+                        return TokenKind::Unknown;
+                    }
+                }
+                TokenKind::Number
+            }
+        }
+    }
+
+    // Skips up to and including the closing `quote`, returns false if the line ends first.
+    // A backslash escapes the char that follows it.
+    pub fn skip_quoted_tail(&mut self, quote: char) -> bool {
+        loop {
+            match self.peek(0) {
+                '\0' => return false,
+                '\\' => self.skip(2),
+                char if char == quote => {
+                    self.skip(1);
+                    return true;
+                }
+                _ => self.skip(1),
+            }
+        }
+    }
+
+    // Skips up to and including `end`, returns false if the line ends first.
+    pub fn skip_past(&mut self, end: &str) -> bool {
+        match self.string[self.index..].find(end) {
+            Some(index) => {
+                self.index += index + end.len();
+                true
+            }
+            None => {
+                self.index = self.string.len();
+                false
+            }
+        }
+    }
+
+    pub fn skip_to_end(&mut self) {
+        self.index = self.string.len();
+    }
+
+    // Skips the longest of `candidates` that the rest of the line starts with, if any.
+    pub fn skip_longest(&mut self, candidates: &[&str]) -> bool {
+        let rest = self.rest();
+        match candidates
+            .iter()
+            .filter(|candidate| rest.starts_with(*candidate))
+            .max_by_key(|candidate| candidate.len())
+        {
+            Some(candidate) => {
+                self.index += candidate.len();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+pub trait CharExt {
+    fn is_identifier_start(self) -> bool;
+    fn is_identifier_continue(self) -> bool;
+}
+
+impl CharExt for char {
+    fn is_identifier_start(self) -> bool {
+        match self {
+            'A'..='Z' | '_' | 'a'..='z' => true,
+            _ => false,
+        }
+    }
+
+    fn is_identifier_continue(self) -> bool {
+        match self {
+            '0'..='9' | 'A'..='Z' | '_' | 'a'..='z' => true,
+            _ => false,
+        }
+    }
+}
+
+// Classifies an identifier the way the Rust tokenizer does for names that aren't keywords:
+// SHOUTING_CASE is a constant, CamelCase a type, and a name followed by `(` a function.
+pub fn classify_identifier(string: &str, cursor: &Cursor) -> TokenKind {
+    let mut chars = string.chars();
+    if chars.next().unwrap().is_uppercase() {
+        match chars.next() {
+            Some(char) if char.is_uppercase() => TokenKind::Constant,
+            _ => TokenKind::Typename,
+        }
+    } else if cursor.peek(0) == '(' {
+        TokenKind::Function
+    } else {
+        TokenKind::Identifier
+    }
+}
@@ -1,70 +1,8 @@
 use crate::{
-    text::{Change, Text},
     token::TokenKind,
-    Token,
+    tokenizer::{classify_identifier, CharExt, Cursor, LanguageTokenizer},
 };
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub struct Tokenizer {
-    state: Vec<Option<(State, State)>>,
-}
-
-impl Tokenizer {
-    pub fn new(line_count: usize) -> Self {
-        Self {
-            state: (0..line_count).map(|_| None).collect(),
-        }
-    }
-
-    pub fn apply_change(&mut self, change: &Change) {
-        match *change {
-            Change::Insert(point, ref text) => {
-                self.state[point.line_index] = None;
-                let line_count = text.length().line_count;
-                if line_count > 0 {
-                    let line = point.line_index + 1;
-                    self.state.splice(line..line, (0..line_count).map(|_| None));
-                }
-            }
-            Change::Delete(start, length) => {
-                self.state[start.line_index] = None;
-                let line_count = length.line_count;
-                if line_count > 0 {
-                    let start_line = start.line_index + 1;
-                    let end_line = start_line + line_count;
-                    self.state.drain(start_line..end_line);
-                }
-            }
-        }
-    }
-
-    pub fn update(&mut self, text: &Text, tokens: &mut [Vec<Token>]) {
-        let mut state = State::default();
-        for line in 0..text.as_lines().len() {
-            match self.state[line] {
-                Some((start_state, end_state)) if state == start_state => {
-                    state = end_state;
-                }
-                _ => {
-                    let start_state = state;
-                    let mut new_tokens = Vec::new();
-                    let mut cursor = Cursor::new(&text.as_lines()[line]);
-                    loop {
-                        let (next_state, token) = state.next(&mut cursor);
-                        state = next_state;
-                        match token {
-                            Some(token) => new_tokens.push(token),
-                            None => break,
-                        }
-                    }
-                    self.state[line] = Some((start_state, state));
-                    tokens[line] = new_tokens;
-                }
-            }
-        }
-    }
-}
-
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum State {
     Initial(InitialState),
@@ -79,27 +17,19 @@ impl Default for State {
     }
 }
 
-impl State {
-    pub fn next(self, cursor: &mut Cursor) -> (State, Option<Token>) {
-        if cursor.peek(0) == '\0' {
-            return (self, None);
-        }
-        let start = cursor.index;
-        let (next_state, kind) = match self {
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RustTokenizer;
+
+impl LanguageTokenizer for RustTokenizer {
+    type State = State;
+
+    fn next(&self, state: State, cursor: &mut Cursor) -> (State, TokenKind) {
+        match state {
             State::Initial(state) => state.next(cursor),
             State::BlockCommentTail(state) => state.next(cursor),
             State::DoubleQuotedStringTail(state) => state.next(cursor),
             State::RawDoubleQuotedStringTail(state) => state.next(cursor),
-        };
-        let end = cursor.index;
-        assert!(start < end);
-        (
-            next_state,
-            Some(Token {
-                len: end - start,
-                kind,
-            }),
-        )
+        }
     }
 }
 
@@ -214,11 +144,7 @@ impl InitialState {
 
     fn identifier_or_keyword(self, cursor: &mut Cursor) -> (State, TokenKind) {
         debug_assert!(cursor.peek(0).is_identifier_start());
-        let start = cursor.index;
-        cursor.skip(1);
-        while cursor.skip_if(|char| char.is_identifier_continue()) {}
-        let end = cursor.index;
-        let string = &cursor.string[start..end];
+        let string = cursor.skip_identifier();
         (
             State::Initial(InitialState),
             match string {
@@ -230,19 +156,7 @@ impl InitialState {
                 | "type" | "unsafe" | "use" | "where" | "usize" | "isize" | "u8" | "u16"
                 | "u32" | "u64" | "i8" | "i16" | "i32" | "i64" | "vec2" | "vec3" | "vec4"
                 | "bool" | "f32" | "f64" => TokenKind::OtherKeyword,
-                _ => {
-                    let mut chars = string.chars();
-                    if chars.next().unwrap().is_uppercase() {
-                        match chars.next() {
-                            Some(char) if char.is_uppercase() => TokenKind::Constant,
-                            _ => TokenKind::Typename,
-                        }
-                    } else if cursor.peek(0) == '(' {
-                        TokenKind::Function
-                    } else {
-                        TokenKind::Identifier
-                    }
-                }
+                _ => classify_identifier(string, cursor),
             },
         )
     }
@@ -379,10 +293,7 @@ impl InitialState {
     }
 
     fn whitespace(self, cursor: &mut Cursor) -> (State, TokenKind) {
-        debug_assert!(cursor.peek(0).is_whitespace());
-        cursor.skip(1);
-        while cursor.skip_if(|char| char.is_whitespace()) {}
-        (State::Initial(InitialState), TokenKind::Whitespace)
+        (State::Initial(InitialState), cursor.skip_whitespace())
     }
 }
 
@@ -469,94 +380,3 @@ impl RawDoubleQuotedStringTailState {
         }
     }
 }
-
-#[derive(Debug)]
-pub struct Cursor<'a> {
-    string: &'a str,
-    index: usize,
-}
-
-impl<'a> Cursor<'a> {
-    pub fn new(string: &'a str) -> Self {
-        Cursor { string, index: 0 }
-    }
-
-    fn peek(&self, index: usize) -> char {
-        self.string[self.index..].chars().nth(index).unwrap_or('\0')
-    }
-
-    fn skip(&mut self, count: usize) {
-        self.index = self.string[self.index..]
-            .char_indices()
-            .nth(count)
-            .map_or(self.string.len(), |(index, _)| self.index + index);
-    }
-
-    fn skip_if<P>(&mut self, predicate: P) -> bool
-    where
-        P: FnOnce(char) -> bool,
-    {
-        if predicate(self.peek(0)) {
-            self.skip(1);
-            true
-        } else {
-            false
-        }
-    }
-
-    fn skip_exponent(&mut self) -> bool {
-        debug_assert!(self.peek(0) == 'E' || self.peek(0) == 'e');
-        self.skip(1);
-        if self.peek(0) == '+' || self.peek(0) == '-' {
-            self.skip(1);
-        }
-        self.skip_digits(10)
-    }
-
-    fn skip_digits(&mut self, radix: u32) -> bool {
-        let mut has_skip_digits = false;
-        loop {
-            match self.peek(0) {
-                '_' => {
-                    self.skip(1);
-                }
-                char if char.is_digit(radix) => {
-                    self.skip(1);
-                    has_skip_digits = true;
-                }
-                _ => break,
-            }
-        }
-        has_skip_digits
-    }
-
-    fn skip_suffix(&mut self) -> bool {
-        if self.peek(0).is_identifier_start() {
-            self.skip(1);
-            while self.skip_if(|char| char.is_identifier_continue()) {}
-            return true;
-        }
-        false
-    }
-}
-
-pub trait CharExt {
-    fn is_identifier_start(self) -> bool;
-    fn is_identifier_continue(self) -> bool;
-}
-
-impl CharExt for char {
-    fn is_identifier_start(self) -> bool {
-        match self {
-            'A'..='Z' | '_' | 'a'..='z' => true,
-            _ => false,
-        }
-    }
-
-    fn is_identifier_continue(self) -> bool {
-        match self {
-            '0'..='9' | 'A'..='Z' | '_' | 'a'..='z' => true,
-            _ => false,
-        }
-    }
-}
@@ -0,0 +1,134 @@
+use crate::{
+    token::TokenKind,
+    tokenizer::{Cursor, LanguageTokenizer},
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct TomlTokenizer;
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum TomlState {
+    #[default]
+    Initial,
+    MultilineBasicStringTail,
+    MultilineLiteralStringTail,
+}
+
+impl LanguageTokenizer for TomlTokenizer {
+    type State = TomlState;
+
+    fn next(&self, state: TomlState, cursor: &mut Cursor) -> (TomlState, TokenKind) {
+        match state {
+            TomlState::Initial => initial(cursor),
+            TomlState::MultilineBasicStringTail => multiline_basic_string_tail(cursor),
+            TomlState::MultilineLiteralStringTail => multiline_literal_string_tail(cursor),
+        }
+    }
+}
+
+fn initial(cursor: &mut Cursor) -> (TomlState, TokenKind) {
+    let kind = match (cursor.peek(0), cursor.peek(1), cursor.peek(2)) {
+        ('"', '"', '"') => {
+            cursor.skip(3);
+            return multiline_basic_string_tail(cursor);
+        }
+        ('\'', '\'', '\'') => {
+            cursor.skip(3);
+            return multiline_literal_string_tail(cursor);
+        }
+        ('#', _, _) => {
+            cursor.skip_to_end();
+            TokenKind::Comment
+        }
+        // a [table] or [[array.of.tables]] header is the only thing on its line
+        ('[', _, _) if cursor.preceding().trim().is_empty() => {
+            cursor.skip_past("]");
+            cursor.skip_if(|char| char == ']');
+            TokenKind::Typename
+        }
+        ('"', _, _) => {
+            cursor.skip(1);
+            cursor.skip_quoted_tail('"');
+            key_or(cursor, TokenKind::String)
+        }
+        ('\'', _, _) => {
+            cursor.skip(1);
+            cursor.skip_past("'");
+            key_or(cursor, TokenKind::String)
+        }
+        ('[', _, _) | (']', _, _) | ('{', _, _) | ('}', _, _) => {
+            cursor.skip(1);
+            TokenKind::Delimiter
+        }
+        ('=', _, _) | (',', _, _) | ('.', _, _) => {
+            cursor.skip(1);
+            TokenKind::Punctuator
+        }
+        // numbers, dates and times are all runs of digits and separators
+        ('+', char, _) | ('-', char, _) | (char, _, _) if char.is_ascii_digit() => {
+            cursor.skip(1);
+            cursor.skip_while(is_value_char);
+            key_or(cursor, TokenKind::Number)
+        }
+        (char, _, _) if is_bare_key_char(char) => {
+            let start = cursor.index();
+            cursor.skip_while(is_bare_key_char);
+            match cursor.slice_from(start) {
+                "true" | "false" => key_or(cursor, TokenKind::Constant),
+                "inf" | "nan" => key_or(cursor, TokenKind::Number),
+                _ => TokenKind::Identifier,
+            }
+        }
+        (char, _, _) if char.is_whitespace() => cursor.skip_whitespace(),
+        _ => {
+            cursor.skip(1);
+            TokenKind::Unknown
+        }
+    };
+    (TomlState::Initial, kind)
+}
+
+// keys can be quoted or look like a value, what follows them tells them apart
+fn key_or(cursor: &Cursor, kind: TokenKind) -> TokenKind {
+    let rest = cursor.rest().trim_start();
+    if rest.starts_with('=') || rest.starts_with('.') {
+        TokenKind::Identifier
+    } else {
+        kind
+    }
+}
+
+fn multiline_basic_string_tail(cursor: &mut Cursor) -> (TomlState, TokenKind) {
+    loop {
+        match (cursor.peek(0), cursor.peek(1), cursor.peek(2)) {
+            ('"', '"', '"') => {
+                cursor.skip(3);
+                // up to two quotes are allowed right before the closing delimiter
+                cursor.skip_if(|char| char == '"');
+                cursor.skip_if(|char| char == '"');
+                break (TomlState::Initial, TokenKind::String);
+            }
+            ('\0', _, _) => break (TomlState::MultilineBasicStringTail, TokenKind::String),
+            ('\\', _, _) => cursor.skip(2),
+            _ => cursor.skip(1),
+        }
+    }
+}
+
+fn multiline_literal_string_tail(cursor: &mut Cursor) -> (TomlState, TokenKind) {
+    if cursor.skip_past("'''") {
+        cursor.skip_if(|char| char == '\'');
+        cursor.skip_if(|char| char == '\'');
+        (TomlState::Initial, TokenKind::String)
+    } else {
+        (TomlState::MultilineLiteralStringTail, TokenKind::String)
+    }
+}
+
+fn is_bare_key_char(char: char) -> bool {
+    char.is_ascii_alphanumeric() || char == '_' || char == '-'
+}
+
+fn is_value_char(char: char) -> bool {
+    char.is_ascii_alphanumeric() || matches!(char, '_' | '.' | '-' | '+' | ':')
+}
@@ -0,0 +1,83 @@
+use makepad_code_editor::{
+    decoration::DecorationSet,
+    diff::{diff, Hunk, HunkKind},
+    Document,
+};
+
+fn hunk(old: std::ops::Range<usize>, new: std::ops::Range<usize>) -> Hunk {
+    Hunk { old, new }
+}
+
+fn apply(old: &[&str], new: &[&str], hunks: &[Hunk]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut old_index = 0;
+    for hunk in hunks {
+        lines.extend(old[old_index..hunk.old.start].iter().map(|line| line.to_string()));
+        lines.extend(new[hunk.new.clone()].iter().map(|line| line.to_string()));
+        old_index = hunk.old.end;
+    }
+    lines.extend(old[old_index..].iter().map(|line| line.to_string()));
+    lines
+}
+
+#[test]
+fn finds_added_modified_and_removed_lines() {
+    let old = ["a", "b", "c", "d", "e", "f"];
+    let new = ["a", "x", "c", "d", "y", "z", "f", "g"];
+    let hunks = diff(&old, &new);
+    assert_eq!(
+        hunks,
+        [hunk(1..2, 1..2), hunk(4..5, 4..6), hunk(6..6, 7..8)]
+    );
+    assert_eq!(
+        hunks.iter().map(Hunk::kind).collect::<Vec<_>>(),
+        [HunkKind::Modified, HunkKind::Modified, HunkKind::Added]
+    );
+    assert_eq!(diff(&["a", "b", "c"], &["a", "c"]), [hunk(1..2, 1..1)]);
+    assert_eq!(diff(&["a", "b", "c"], &["a", "c"])[0].kind(), HunkKind::Removed);
+    assert!(diff(&["a", "b"], &["a", "b"]).is_empty());
+    assert_eq!(diff::<&str>(&[], &["a"]), [hunk(0..0, 0..1)]);
+}
+
+#[test]
+fn finds_shortest_diffs() {
+    let cases: [(&[&str], &[&str]); 4] = [
+        (&["a", "b", "c", "a", "b", "b", "a"], &["c", "b", "a", "b", "a", "c"]),
+        (&["x", "a", "y", "a", "z"], &["a", "a", "a"]),
+        (&["a", "b", "a", "b"], &["b", "a", "b", "a"]),
+        (&["a", "b"], &["c", "d", "e"]),
+    ];
+    for (old, new) in cases {
+        let hunks = diff(old, new);
+        assert_eq!(apply(old, new, &hunks), new);
+        for hunks in hunks.windows(2) {
+            assert!(hunks[0].old.end < hunks[1].old.start);
+        }
+    }
+    // The example from the paper has 5 edits.
+    let hunks = diff(cases[0].0, cases[0].1);
+    let edit_count: usize = hunks.iter().map(|hunk| hunk.old.len() + hunk.new.len()).sum();
+    assert_eq!(edit_count, 5);
+}
+
+#[test]
+fn treats_texts_that_differ_too_much_as_one_change() {
+    let old: Vec<_> = (0..3000).map(|index| index.to_string()).collect();
+    let new: Vec<_> = (0..3000).map(|index| format!("{}!", index)).collect();
+    assert_eq!(diff(&old, &new), [hunk(0..3000, 0..3000)]);
+}
+
+#[test]
+fn keeps_hunks_of_document_up_to_date() {
+    let document = Document::new("a\nb\nc".into(), DecorationSet::new(), "rs");
+    assert!(document.diff_hunks().is_empty());
+    document.set_diff_base(Some("a\nc\nd".into()));
+    assert_eq!(&*document.diff_hunks(), [hunk(1..1, 1..2), hunk(2..3, 3..3)]);
+    let mut session = makepad_code_editor::Session::new(document.clone());
+    session.replace_text("a\nc\nd".into());
+    session.handle_changes();
+    assert!(document.diff_hunks().is_empty());
+    document.set_diff_base(None);
+    session.replace_text("x".into());
+    assert!(document.diff_hunks().is_empty());
+}
@@ -0,0 +1,71 @@
+use makepad_code_editor::{
+    decoration::DecorationSet,
+    selection::Affinity,
+    session::SelectionMode,
+    text::{Change, Drift, Edit, Position},
+    Document, Session,
+};
+
+fn session(text: &str) -> Session {
+    Session::new(Document::new(text.into(), DecorationSet::new(), "rs"))
+}
+
+fn position(line_index: usize, byte_index: usize) -> Position {
+    Position {
+        line_index,
+        byte_index,
+    }
+}
+
+fn cursor(session: &Session) -> Position {
+    session.selections()[0].cursor.position
+}
+
+#[test]
+fn replaces_only_the_lines_that_differ() {
+    let mut session = session("a\nb\nc\nd");
+    session.set_selection(position(3, 1), Affinity::Before, SelectionMode::Simple);
+    session.replace_text("a\nx\ny\nc\nd".into());
+    session.handle_changes();
+    assert_eq!(session.document().as_text().to_string(), "a\nx\ny\nc\nd");
+    assert_eq!(cursor(&session), position(4, 1));
+
+    // Replacing the text with itself does nothing, so undo goes back to before the first edit.
+    session.replace_text("a\nx\ny\nc\nd".into());
+    assert!(session.undo());
+    session.handle_changes();
+    assert_eq!(session.document().as_text().to_string(), "a\nb\nc\nd");
+    assert_eq!(cursor(&session), position(3, 1));
+    assert!(!session.undo());
+}
+
+#[test]
+fn replaces_all_of_the_text() {
+    let mut session = session("abc\ndef");
+    session.replace_text("".into());
+    session.handle_changes();
+    assert_eq!(session.document().as_text().to_string(), "");
+    session.replace_text("uvw\nxyz\n".into());
+    session.handle_changes();
+    assert_eq!(session.document().as_text().to_string(), "uvw\nxyz\n");
+    session.replace_text("uvw\nxyz".into());
+    session.handle_changes();
+    assert_eq!(session.document().as_text().to_string(), "uvw\nxyz");
+}
+
+#[test]
+fn applies_remote_edits_without_undo() {
+    let mut session = session("abc\ndef");
+    session.set_selection(position(1, 2), Affinity::Before, SelectionMode::Simple);
+    session.insert("x".into());
+    session.handle_changes();
+    session.document().apply_remote_edits(vec![Edit {
+        change: Change::Insert(position(0, 0), "// remote\n".into()),
+        drift: Drift::Before,
+    }]);
+    session.handle_changes();
+    assert_eq!(session.document().as_text().to_string(), "// remote\nabc\ndexf");
+    assert_eq!(cursor(&session), position(2, 3));
+    // Local edits from before the remote edit can no longer be undone.
+    assert!(!session.undo());
+}
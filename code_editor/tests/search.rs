@@ -0,0 +1,250 @@
+use makepad_code_editor::{
+    decoration::{Decoration, DecorationSet, DecorationType},
+    search::{SearchMatch, SearchOptions},
+    selection::Affinity,
+    session::SelectionMode,
+    text::{Position, Text},
+    Document, Search, Session, Settings,
+};
+
+fn find(pattern: &str, options: SearchOptions, text: &str) -> Vec<(usize, usize, usize)> {
+    let text = Text::from(text);
+    Search::new(pattern, options)
+        .unwrap()
+        .find_all(text.as_lines(), &Settings::default().word_separators)
+        .into_iter()
+        .map(|search_match| {
+            assert_eq!(search_match.start.line_index, search_match.end.line_index);
+            (
+                search_match.start.line_index,
+                search_match.start.byte_index,
+                search_match.end.byte_index,
+            )
+        })
+        .collect()
+}
+
+fn session(text: &str) -> Session {
+    Session::new(Document::new(text.into(), DecorationSet::new(), "rs"))
+}
+
+fn text_of(session: &Session) -> String {
+    session.document().as_text().to_string()
+}
+
+fn selected_ranges(session: &Session) -> Vec<(Position, Position)> {
+    session
+        .selections()
+        .iter()
+        .map(|selection| (selection.start(), selection.end()))
+        .collect()
+}
+
+fn position(line_index: usize, byte_index: usize) -> Position {
+    Position {
+        line_index,
+        byte_index,
+    }
+}
+
+#[test]
+fn search_finds_literal_matches() {
+    let text = "let foo = Foo::new();\nfoofoo food\n";
+    assert_eq!(
+        find("foo", SearchOptions::default(), text),
+        vec![(0, 4, 7), (0, 10, 13), (1, 0, 3), (1, 3, 6), (1, 7, 10)]
+    );
+    let case_sensitive = SearchOptions {
+        case_sensitive: true,
+        ..SearchOptions::default()
+    };
+    assert_eq!(
+        find("foo", case_sensitive, text),
+        vec![(0, 4, 7), (1, 0, 3), (1, 3, 6), (1, 7, 10)]
+    );
+    let whole_word = SearchOptions {
+        whole_word: true,
+        ..SearchOptions::default()
+    };
+    assert_eq!(find("foo", whole_word, text), vec![(0, 4, 7), (0, 10, 13)]);
+    assert_eq!(find("", SearchOptions::default(), text), vec![]);
+}
+
+#[test]
+fn search_finds_regex_matches() {
+    let regex = SearchOptions {
+        regex: true,
+        ..SearchOptions::default()
+    };
+    let text = "x1 = 10;\ny22 = 200;\n";
+    assert_eq!(find("\\d+", regex, text), vec![(0, 1, 2), (0, 5, 7), (1, 1, 3), (1, 6, 9)]);
+    // Empty matches are skipped instead of highlighting every position.
+    assert_eq!(find("\\d*", regex, text).len(), 4);
+    let whole_word_regex = SearchOptions {
+        whole_word: true,
+        ..regex
+    };
+    assert_eq!(find("\\d+", whole_word_regex, text), vec![(0, 5, 7), (1, 6, 9)]);
+}
+
+#[test]
+fn search_stops_after_the_given_number_of_matches() {
+    let text = Text::from("a a\na\na a a\n");
+    let search = Search::new("a", SearchOptions::default()).unwrap();
+    let positions = |max_count| {
+        search
+            .find_up_to(text.as_lines(), &[], max_count)
+            .into_iter()
+            .map(|search_match| (search_match.start.line_index, search_match.start.byte_index))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(positions(0), vec![]);
+    assert_eq!(positions(2), vec![(0, 0), (0, 2)]);
+    assert_eq!(positions(4), vec![(0, 0), (0, 2), (1, 0), (2, 0)]);
+    assert_eq!(positions(10).len(), 6);
+}
+
+#[test]
+fn search_expands_capture_groups_in_replacements() {
+    let search = Search::new(
+        "(\\w+): (\\w+)",
+        SearchOptions {
+            regex: true,
+            ..SearchOptions::default()
+        },
+    )
+    .unwrap();
+    let text = Text::from("let pair = (name: String);");
+    let search_match = search.find_all(text.as_lines(), &[])[0];
+    let expand = |replacement| search.expand_replacement(text.as_lines(), search_match, replacement);
+    assert_eq!(expand("$2 $1"), "String name");
+    assert_eq!(expand("${1}_id: $$$2"), "name_id: $String");
+    assert_eq!(expand("$9$"), "$");
+    let literal = Search::new("(\\w+)", SearchOptions::default()).unwrap();
+    assert_eq!(
+        literal.expand_replacement(
+            text.as_lines(),
+            SearchMatch {
+                start: position(0, 0),
+                end: position(0, 3),
+            },
+            "$1"
+        ),
+        "$1"
+    );
+}
+
+#[test]
+fn session_highlights_and_navigates_matches() {
+    let mut session = session("let a = 1;\nlet b = a;\n");
+    session.document().clone().add_decoration(Decoration::new(
+        0,
+        position(1, 8),
+        position(1, 9),
+        DecorationType::Error,
+    ));
+    session.set_search(Some(Search::new("a", SearchOptions::default()).unwrap()));
+    assert_eq!(session.search_matches().len(), 2);
+    // The match that overlaps with the error is not highlighted, the error is kept.
+    let decorations: Vec<_> = session
+        .document()
+        .decorations()
+        .iter()
+        .map(|decoration| (decoration.ty, decoration.start()))
+        .collect();
+    assert_eq!(
+        decorations,
+        vec![
+            (DecorationType::SearchMatch, position(0, 4)),
+            (DecorationType::Error, position(1, 8)),
+        ]
+    );
+
+    assert!(session.find_next());
+    assert_eq!(selected_ranges(&session), vec![(position(0, 4), position(0, 5))]);
+    assert!(session.find_next());
+    assert_eq!(selected_ranges(&session), vec![(position(1, 8), position(1, 9))]);
+    assert!(session.find_next());
+    assert_eq!(selected_ranges(&session), vec![(position(0, 4), position(0, 5))]);
+    assert!(session.find_prev());
+    assert_eq!(selected_ranges(&session), vec![(position(1, 8), position(1, 9))]);
+
+    session.insert("aa".into());
+    session.handle_changes();
+    assert_eq!(session.search_matches().len(), 3);
+
+    session.set_search(None);
+    assert!(session.search_matches().is_empty());
+    assert_eq!(session.document().decorations().len(), 1);
+}
+
+#[test]
+fn session_replaces_matches_as_one_undo_group() {
+    let mut session = session("foo bar foo\nfoo\n");
+    session.set_search(Some(
+        Search::new(
+            "f(o+)",
+            SearchOptions {
+                regex: true,
+                ..SearchOptions::default()
+            },
+        )
+        .unwrap(),
+    ));
+
+    // Replacing without a selected match only selects the next one.
+    assert!(session.replace("b$1"));
+    session.handle_changes();
+    assert_eq!(text_of(&session), "foo bar foo\nfoo\n");
+    assert_eq!(selected_ranges(&session), vec![(position(0, 0), position(0, 3))]);
+
+    assert!(session.replace("b$1"));
+    session.handle_changes();
+    assert_eq!(text_of(&session), "boo bar foo\nfoo\n");
+    assert_eq!(selected_ranges(&session), vec![(position(0, 8), position(0, 11))]);
+
+    assert_eq!(session.replace_all("x"), 2);
+    session.handle_changes();
+    assert_eq!(text_of(&session), "boo bar x\nx\n");
+    assert!(session.search_matches().is_empty());
+
+    assert!(session.undo());
+    session.handle_changes();
+    assert_eq!(text_of(&session), "boo bar foo\nfoo\n");
+    assert!(session.undo());
+    session.handle_changes();
+    assert_eq!(text_of(&session), "foo bar foo\nfoo\n");
+    assert_eq!(session.search_matches().len(), 3);
+}
+
+#[test]
+fn session_selects_occurrences() {
+    let session = session("item items item\nitem\n");
+    session.set_selection(position(0, 1), Affinity::Before, SelectionMode::Simple);
+
+    // The first invocation selects the word under the cursor.
+    assert!(session.add_next_occurrence());
+    assert_eq!(selected_ranges(&session), vec![(position(0, 0), position(0, 4))]);
+    assert!(session.add_next_occurrence());
+    assert_eq!(
+        selected_ranges(&session),
+        vec![(position(0, 0), position(0, 4)), (position(0, 5), position(0, 9))]
+    );
+    assert!(session.add_next_occurrence());
+    assert!(session.add_next_occurrence());
+    assert_eq!(session.selections().len(), 4);
+    assert!(!session.add_next_occurrence());
+
+    session.set_selection(position(1, 2), Affinity::Before, SelectionMode::Simple);
+    assert!(session.select_all_occurrences());
+    assert_eq!(
+        selected_ranges(&session),
+        vec![
+            (position(0, 0), position(0, 4)),
+            (position(0, 5), position(0, 9)),
+            (position(0, 11), position(0, 15)),
+            (position(1, 0), position(1, 4)),
+        ]
+    );
+    assert_eq!(session.last_added_selection_index(), Some(3));
+}
@@ -0,0 +1,123 @@
+use makepad_code_editor::text::{Change, Length, Position, Text};
+
+// A small deterministic generator, so that failures can be reproduced.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        ((self.0 >> 33) as usize) % bound
+    }
+}
+
+fn random_position(rng: &mut Rng, lines: &[String]) -> Position {
+    let line_index = rng.next(lines.len());
+    let byte_index = rng.next(lines[line_index].len() + 1);
+    Position {
+        line_index,
+        byte_index,
+    }
+}
+
+fn random_text(rng: &mut Rng) -> String {
+    let line_count = match rng.next(4) {
+        0 => rng.next(1200),
+        _ => rng.next(3),
+    };
+    (0..=line_count)
+        .map(|index| format!("{}", index).repeat(rng.next(3)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn offset(lines: &[String], position: Position) -> usize {
+    lines[..position.line_index]
+        .iter()
+        .map(|line| line.len() + 1)
+        .sum::<usize>()
+        + position.byte_index
+}
+
+#[test]
+fn edits_match_a_string_model() {
+    let mut rng = Rng(7);
+    let initial = (0..5000)
+        .map(|index| format!("line {}", index))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut text = Text::from(initial.as_str());
+    let mut model = initial;
+    for _ in 0..2000 {
+        let lines = text.as_lines().iter().cloned().collect::<Vec<_>>();
+        let start = random_position(&mut rng, &lines);
+        let change = if rng.next(2) == 0 {
+            let inserted = random_text(&mut rng);
+            model.insert_str(offset(&lines, start), &inserted);
+            Change::Insert(start, Text::from(inserted))
+        } else {
+            let mut end = random_position(&mut rng, &lines);
+            if end.line_index > start.line_index + 1500 {
+                end = Position {
+                    line_index: start.line_index,
+                    byte_index: lines[start.line_index].len(),
+                };
+            }
+            let (start, end) = (start.min(end), start.max(end));
+            model.replace_range(offset(&lines, start)..offset(&lines, end), "");
+            Change::Delete(start, end - start)
+        };
+        text.apply_change(change);
+        assert_eq!(text.to_string(), model);
+    }
+
+    let lines = model.split('\n').collect::<Vec<_>>();
+    assert_eq!(text.as_lines().len(), lines.len());
+    assert_eq!(text.as_lines().iter().len(), lines.len());
+    for (index, line) in lines.iter().enumerate() {
+        assert_eq!(text.as_lines()[index], *line);
+    }
+    for _ in 0..100 {
+        let start = rng.next(lines.len() + 1);
+        let end = start + rng.next(lines.len() + 1 - start);
+        let range = text.as_lines().range(start..end);
+        assert_eq!(range.len(), end - start);
+        assert!(range.clone().eq(lines[start..end].iter()));
+        assert!(range.rev().eq(lines[start..end].iter().rev()));
+    }
+}
+
+#[test]
+fn slices_and_lengths_span_chunks() {
+    let source = (0..3000)
+        .map(|index| index.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let text = Text::from(source.as_str());
+    assert_eq!(
+        text.length(),
+        Length {
+            line_count: 2999,
+            byte_count: 4,
+        }
+    );
+    let start = Position {
+        line_index: 10,
+        byte_index: 1,
+    };
+    let slice = text.slice(
+        start,
+        Length {
+            line_count: 2000,
+            byte_count: 2,
+        },
+    );
+    let expected = (10..=2010)
+        .map(|index| index.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_eq!(slice.to_string(), &expected[1..expected.len() - 2]);
+    assert_eq!(Text::from(slice.to_string()), slice);
+}
@@ -0,0 +1,185 @@
+use makepad_code_editor::{
+    text::{Change, Length, Position, Text},
+    token::TokenKind,
+    Token, Tokenizer,
+};
+
+const FIXTURES: &[(&str, &str)] = &[
+    ("rs", include_str!("../src/test.rs")),
+    ("toml", include_str!("../src/test.toml")),
+    ("md", include_str!("../src/test.md")),
+    ("json", include_str!("../src/test.json")),
+    ("glsl", include_str!("../src/test.glsl")),
+    ("wgsl", include_str!("../src/test.wgsl")),
+    ("js", include_str!("../src/test.js")),
+];
+
+fn tokenize(tokenizer: &mut Tokenizer, text: &Text, tokens: &mut Vec<Vec<Token>>) {
+    tokens.resize(text.as_lines().len(), Vec::new());
+    tokenizer.update(text, tokens);
+}
+
+fn fresh_tokens(extension: &str, text: &Text) -> Vec<Vec<Token>> {
+    let mut tokenizer = Tokenizer::for_extension(extension, text.as_lines().len());
+    let mut tokens = Vec::new();
+    tokenize(&mut tokenizer, text, &mut tokens);
+    tokens
+}
+
+fn line_kinds<'a>(line: &'a str, tokens: &[Token]) -> Vec<(&'a str, TokenKind)> {
+    let mut start = 0;
+    tokens
+        .iter()
+        .map(|token| {
+            let slice = &line[start..start + token.len];
+            start += token.len;
+            (slice, token.kind)
+        })
+        .collect()
+}
+
+fn kind_of(extension: &str, source: &str, needle: &str) -> TokenKind {
+    let text = Text::from(source);
+    let tokens = fresh_tokens(extension, &text);
+    text.as_lines()
+        .iter()
+        .zip(&tokens)
+        .flat_map(|(line, tokens)| line_kinds(line, tokens))
+        .find(|(slice, _)| *slice == needle)
+        .unwrap_or_else(|| panic!("no `{}` token in the .{} fixture", needle, extension))
+        .1
+}
+
+#[test]
+fn tokens_cover_every_line() {
+    for (extension, source) in FIXTURES {
+        let text = Text::from(*source);
+        let tokens = fresh_tokens(extension, &text);
+        for (index, (line, tokens)) in text.as_lines().iter().zip(&tokens).enumerate() {
+            let len: usize = tokens.iter().map(|token| token.len).sum();
+            assert_eq!(len, line.len(), "line {} of the .{} fixture", index + 1, extension);
+            if *extension != "rs" {
+                assert!(
+                    tokens.iter().all(|token| token.kind != TokenKind::Unknown),
+                    "unknown token on line {} of the .{} fixture: {:?}",
+                    index + 1,
+                    extension,
+                    line_kinds(line, tokens)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn tokens_are_classified_per_language() {
+    let fixture = |extension| FIXTURES.iter().find(|(ext, _)| *ext == extension).unwrap().1;
+
+    let toml = fixture("toml");
+    assert_eq!(kind_of("toml", toml, "[package]"), TokenKind::Typename);
+    assert_eq!(kind_of("toml", toml, "[[bin]]"), TokenKind::Typename);
+    assert_eq!(kind_of("toml", toml, "\"quoted key\""), TokenKind::Identifier);
+    assert_eq!(kind_of("toml", toml, "0xDEADBEEF"), TokenKind::Number);
+    assert_eq!(kind_of("toml", toml, "1979-05-27T07:32:00Z"), TokenKind::Number);
+    assert_eq!(kind_of("toml", toml, "with an escaped \\\"\"\" quote"), TokenKind::String);
+
+    let json = fixture("json");
+    assert_eq!(kind_of("json", json, "\"name\""), TokenKind::Identifier);
+    assert_eq!(kind_of("json", json, "\"makepad-example\""), TokenKind::String);
+    assert_eq!(kind_of("json", json, "null"), TokenKind::Constant);
+    assert_eq!(kind_of("json", json, "-1.5E-10"), TokenKind::Number);
+
+    let md = fixture("md");
+    assert_eq!(kind_of("md", md, "## Lists"), TokenKind::Typename);
+    assert_eq!(kind_of("md", md, "`inline code`"), TokenKind::String);
+    assert_eq!(kind_of("md", md, "    // this is code, not markdown: # not a heading"), TokenKind::String);
+    assert_eq!(kind_of("md", md, "still inside"), TokenKind::String);
+    assert_eq!(kind_of("md", md, "that spans lines -->"), TokenKind::Comment);
+
+    let glsl = fixture("glsl");
+    assert_eq!(kind_of("glsl", glsl, "#version"), TokenKind::OtherKeyword);
+    assert_eq!(kind_of("glsl", glsl, "vec4"), TokenKind::Typename);
+    assert_eq!(kind_of("glsl", glsl, "discard"), TokenKind::BranchKeyword);
+    assert_eq!(kind_of("glsl", glsl, "   that spans lines */"), TokenKind::Comment);
+
+    let wgsl = fixture("wgsl");
+    assert_eq!(kind_of("wgsl", wgsl, "@location"), TokenKind::OtherKeyword);
+    assert_eq!(kind_of("wgsl", wgsl, "loop"), TokenKind::LoopKeyword);
+    assert_eq!(kind_of("wgsl", wgsl, "0.5h"), TokenKind::Number);
+    assert_eq!(kind_of("wgsl", wgsl, "   and spans lines */"), TokenKind::Comment);
+
+    let js = fixture("js");
+    assert_eq!(kind_of("js", js, "async"), TokenKind::OtherKeyword);
+    assert_eq!(kind_of("js", js, "WasmBridge"), TokenKind::Typename);
+    assert_eq!(kind_of("js", js, "9007199254740993n"), TokenKind::Number);
+    assert_eq!(kind_of("js", js, "continuing on the next line`"), TokenKind::String);
+    assert_eq!(kind_of("js", js, ">>>"), TokenKind::Punctuator);
+}
+
+#[test]
+fn retokenizing_after_an_edit_matches_a_fresh_tokenization() {
+    let openers = [
+        ("rs", "/*"),
+        ("toml", "\"\"\""),
+        ("md", "```"),
+        ("json", "\""),
+        ("glsl", "/*"),
+        ("wgsl", "/*"),
+        ("js", "`"),
+    ];
+    for (extension, source) in FIXTURES {
+        let opener = openers.iter().find(|(ext, _)| ext == extension).unwrap().1;
+        let mut text = Text::from(*source);
+        let line_count = text.as_lines().len().min(60);
+        let mut tokenizer = Tokenizer::for_extension(extension, text.as_lines().len());
+        let mut tokens = Vec::new();
+        tokenize(&mut tokenizer, &text, &mut tokens);
+        for line_index in (0..line_count).step_by(3) {
+            let position = Position {
+                line_index,
+                byte_index: 0,
+            };
+            let inserted = Text::from(format!("{}\nx", opener));
+            let length = inserted.length();
+            for change in [
+                Change::Insert(position, inserted),
+                Change::Delete(position, length),
+            ] {
+                apply_change(&mut text, &mut tokenizer, &mut tokens, change);
+                tokenize(&mut tokenizer, &text, &mut tokens);
+                assert_eq!(
+                    tokens,
+                    fresh_tokens(extension, &text),
+                    "after an edit on line {} of the .{} fixture",
+                    line_index + 1,
+                    extension
+                );
+            }
+        }
+    }
+}
+
+fn apply_change(
+    text: &mut Text,
+    tokenizer: &mut Tokenizer,
+    tokens: &mut Vec<Vec<Token>>,
+    change: Change,
+) {
+    let Length { line_count, .. } = match &change {
+        Change::Insert(_, text) => text.length(),
+        Change::Delete(_, length) => *length,
+    };
+    let line_index = match &change {
+        Change::Insert(position, _) | Change::Delete(position, _) => position.line_index,
+    };
+    match &change {
+        Change::Insert(..) => {
+            tokens.splice(line_index + 1..line_index + 1, (0..line_count).map(|_| Vec::new()));
+        }
+        Change::Delete(..) => {
+            tokens.drain(line_index + 1..line_index + 1 + line_count);
+        }
+    }
+    tokenizer.apply_change(&change);
+    text.apply_change(change);
+}
@@ -0,0 +1,310 @@
+//! Glyphs that are drawn in color rather than as a single-colored outline, such as emoji. These
+//! come either from layers of outlines that are each filled with a color from a palette (the
+//! `COLR` and `CPAL` tables), or from embedded PNG images (the `CBDT` and `sbix` tables).
+
+use {
+    std::{
+        collections::HashMap,
+        rc::Rc,
+    },
+    crate::{
+        makepad_vector::geometry::{Point, Rectangle},
+        owned_font_face::OwnedFace,
+    },
+    makepad_zune_png::PngDecoder,
+    rustybuzz::ttf_parser::{self, colr, GlyphId, RasterImageFormat},
+};
+
+pub struct CxColorGlyph {
+    // The bounds of the glyph in font units.
+    pub bounds: Rectangle,
+    pub kind: CxColorGlyphKind,
+}
+
+pub enum CxColorGlyphKind {
+    // The layers from bottom to top, each filled with its color, or with the color of the text if
+    // it has none.
+    Layers(Vec<CxColorGlyphLayer>),
+    // BGRA pixels with straight alpha, top row first, that cover the bounds of the glyph.
+    Image {
+        width: usize,
+        height: usize,
+        pixels: Vec<u32>,
+    },
+}
+
+pub struct CxColorGlyphLayer {
+    pub glyph_id: usize,
+    pub color: Option<[u8; 4]>,
+}
+
+#[derive(Default)]
+pub struct ColorGlyphCache {
+    glyphs: HashMap<usize, Option<Rc<CxColorGlyph >>>,
+}
+
+impl ColorGlyphCache {
+    // Returns the color glyph for the given glyph index, or `None` if the font only has an outline
+    // for it. Note that owned_font_face should be the same as the CxFont to which this cache
+    // belongs.
+    pub fn get(&mut self, owned_font_face: &OwnedFace, glyph_id: usize) -> Option<Rc<CxColorGlyph >> {
+        self.glyphs
+            .entry(glyph_id)
+            .or_insert_with(|| owned_font_face.with_ref(|face| load_color_glyph(face, glyph_id)).map(Rc::new))
+            .clone()
+    }
+}
+
+fn load_color_glyph(face: &ttf_parser::Face<'_>, glyph_id: usize) -> Option<CxColorGlyph> {
+    let id = GlyphId(u16::try_from(glyph_id).ok()?);
+    if face.is_color_glyph(id) {
+        let mut painter = LayerPainter {
+            face,
+            outline: None,
+            bounds: None,
+            layers: Vec::new(),
+        };
+        face.paint_color_glyph(id, 0, &mut painter)?;
+        return Some(CxColorGlyph {
+            bounds: painter.bounds?,
+            kind: CxColorGlyphKind::Layers(painter.layers),
+        })
+    }
+    // Ask for the largest strike, it gets scaled down to the font size when it is rasterized.
+    let image = face.glyph_raster_image(id, u16::MAX)?;
+    if image.format != RasterImageFormat::PNG || image.pixels_per_em == 0 {
+        return None
+    }
+    let mut decoder = PngDecoder::new(image.data);
+    let data = decoder.decode().ok()?.u8()?;
+    let (width, height) = decoder.get_dimensions()?;
+    let pixels = png_to_bgra(&data, width, height)?;
+    let (image_width, image_height) = if image.width > 0 && image.height > 0 {
+        (image.width as f64, image.height as f64)
+    }
+    else {
+        (width as f64, height as f64)
+    };
+    let scale = face.units_per_em() as f64 / image.pixels_per_em as f64;
+    Some(CxColorGlyph {
+        bounds: Rectangle::new(
+            Point::new(image.x as f64 * scale, image.y as f64 * scale),
+            Point::new((image.x as f64 + image_width) * scale, (image.y as f64 + image_height) * scale),
+        ),
+        kind: CxColorGlyphKind::Image {width, height, pixels},
+    })
+}
+
+pub(crate) fn png_to_bgra(data: &[u8], width: usize, height: usize) -> Option<Vec<u32>> {
+    let pixels = width * height;
+    if pixels == 0 {
+        return None
+    }
+    let pack = |r: u8, g: u8, b: u8, a: u8| ((a as u32) << 24) | ((r as u32) << 16) | ((g as u32) << 8) | (b as u32);
+    let channels = data.len() / pixels;
+    let data = &data[..pixels * channels];
+    Some(match channels {
+        4 => data.chunks_exact(4).map(|p| pack(p[0], p[1], p[2], p[3])).collect(),
+        3 => data.chunks_exact(3).map(|p| pack(p[0], p[1], p[2], 0xff)).collect(),
+        2 => data.chunks_exact(2).map(|p| pack(p[0], p[0], p[0], p[1])).collect(),
+        1 => data.iter().map(|&p| pack(p, p, p, 0xff)).collect(),
+        _ => return None,
+    })
+}
+
+// Collects the layers of a `COLR` glyph. Every layer first outlines a glyph and then paints it.
+struct LayerPainter<'a, 'b> {
+    face: &'a ttf_parser::Face<'b>,
+    outline: Option<GlyphId>,
+    bounds: Option<Rectangle>,
+    layers: Vec<CxColorGlyphLayer>,
+}
+
+impl LayerPainter<'_, '_> {
+    fn paint(&mut self, color: Option<[u8; 4]>) {
+        let Some(id) = self.outline.take() else {
+            return
+        };
+        let Some(rect) = self.face.glyph_bounding_box(id) else {
+            return
+        };
+        let rect = Rectangle::new(
+            Point::new(rect.x_min as f64, rect.y_min as f64),
+            Point::new(rect.x_max as f64, rect.y_max as f64),
+        );
+        self.bounds = Some(match self.bounds {
+            Some(bounds) => Rectangle::new(
+                Point::new(bounds.p_min.x.min(rect.p_min.x), bounds.p_min.y.min(rect.p_min.y)),
+                Point::new(bounds.p_max.x.max(rect.p_max.x), bounds.p_max.y.max(rect.p_max.y)),
+            ),
+            None => rect,
+        });
+        self.layers.push(CxColorGlyphLayer {
+            glyph_id: id.0 as usize,
+            color,
+        });
+    }
+}
+
+impl colr::Painter for LayerPainter<'_, '_> {
+    fn outline(&mut self, glyph_id: GlyphId) {
+        self.outline = Some(glyph_id);
+    }
+
+    fn paint_foreground(&mut self) {
+        self.paint(None);
+    }
+
+    fn paint_color(&mut self, color: ttf_parser::RgbaColor) {
+        self.paint(Some([color.red, color.green, color.blue, color.alpha]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // A 2x1 PNG with an opaque red pixel and a half transparent blue one.
+    const PNG: &[u8] = &[
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+        0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0xf4, 0x22, 0x7f,
+        0x8a, 0x00, 0x00, 0x00, 0x0e, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9c, 0x63, 0xf8, 0xcf, 0xc0, 0x00,
+        0x42, 0x0d, 0x00, 0x0f, 0x7a, 0x03, 0x7e, 0x77, 0xe9, 0x7f, 0x97, 0x00, 0x00, 0x00, 0x00, 0x49,
+        0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+    const PNG_PIXELS: [u32; 2] = [0xffff0000, 0x800000ff];
+    
+    fn be16(values: &[i32]) -> Vec<u8> {
+        values.iter().flat_map(|&value| (value as u16).to_be_bytes()).collect()
+    }
+    
+    fn be32(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|value| value.to_be_bytes()).collect()
+    }
+    
+    // Builds a font with 4 glyphs and 1000 units per em out of the given tables, the required
+    // `head`, `hhea` and `maxp` tables are added to them.
+    fn font(tables: Vec<(&'static [u8; 4], Vec<u8>)>) -> Vec<u8> {
+        let mut head = vec![0; 54];
+        head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+        let mut tables = tables;
+        tables.push((b"head", head));
+        tables.push((b"hhea", vec![0; 36]));
+        tables.push((b"maxp", [be32(&[0x5000]), be16(&[4])].concat()));
+        tables.sort_by_key(|(tag, _)| **tag);
+        
+        let mut data = [be32(&[0x10000]), be16(&[tables.len() as i32, 0, 0, 0])].concat();
+        let mut offset = 12 + 16 * tables.len();
+        for (tag, table) in &tables {
+            data.extend_from_slice(*tag);
+            data.extend(be32(&[0, offset as u32, table.len() as u32]));
+            offset += (table.len() + 3) / 4 * 4;
+        }
+        for (_, table) in &tables {
+            data.extend(table);
+            data.resize((data.len() + 3) / 4 * 4, 0);
+        }
+        data
+    }
+    
+    // A simple glyph outline with a single rectangular contour.
+    fn rectangle(x0: i32, y0: i32, x1: i32, y1: i32) -> Vec<u8> {
+        [
+            be16(&[1, x0, y0, x1, y1, 3, 0]),
+            vec![1; 4],
+            be16(&[x0, x1 - x0, 0, x0 - x1]),
+            be16(&[y0, 0, y1 - y0, 0]),
+        ].concat()
+    }
+    
+    // Glyph 0 and 3 are empty, 1 and 2 are rectangles.
+    fn outlines() -> Vec<(&'static [u8; 4], Vec<u8>)> {
+        let glyphs = [vec![], rectangle(0, 0, 100, 100), rectangle(50, -50, 150, 50), vec![]];
+        let mut loca = vec![0];
+        for glyph in &glyphs {
+            loca.push(loca.last().unwrap() + glyph.len() as i32 / 2);
+        }
+        vec![(b"glyf", glyphs.concat()), (b"loca", be16(&loca))]
+    }
+    
+    fn load(data: &[u8], glyph_id: usize) -> Option<CxColorGlyph> {
+        load_color_glyph(&ttf_parser::Face::parse(data, 0).unwrap(), glyph_id)
+    }
+    
+    fn assert_bounds(glyph: &CxColorGlyph, [x0, y0, x1, y1]: [f64; 4]) {
+        let bounds = glyph.bounds;
+        assert_eq!([bounds.p_min.x, bounds.p_min.y, bounds.p_max.x, bounds.p_max.y], [x0, y0, x1, y1]);
+    }
+    
+    fn assert_image(glyph: &CxColorGlyph) {
+        let CxColorGlyphKind::Image {width, height, pixels} = &glyph.kind else {
+            panic!("expected an image")
+        };
+        assert_eq!((*width, *height), (2, 1));
+        assert_eq!(pixels, &PNG_PIXELS);
+    }
+    
+    #[test]
+    fn loads_colr_layers_with_cpal_colors() {
+        let mut tables = outlines();
+        // Glyph 3 is glyph 1 in red, below glyph 2 in the color of the text.
+        tables.push((b"COLR", [be16(&[0, 1]), be32(&[14, 20]), be16(&[2, 3, 0, 2, 1, 0, 2, 0xffff])].concat()));
+        tables.push((b"CPAL", [be16(&[0, 1, 1, 1]), be32(&[14]), be16(&[0]), vec![0x00, 0x00, 0xff, 0xff]].concat()));
+        let data = font(tables);
+        
+        let glyph = load(&data, 3).unwrap();
+        assert_bounds(&glyph, [0.0, -50.0, 150.0, 100.0]);
+        let CxColorGlyphKind::Layers(layers) = &glyph.kind else {
+            panic!("expected layers")
+        };
+        let layers: Vec<_> = layers.iter().map(|layer| (layer.glyph_id, layer.color)).collect();
+        assert_eq!(layers, [(1, Some([0xff, 0x00, 0x00, 0xff])), (2, None)]);
+        // The layers themselves are plain outlines.
+        assert!(load(&data, 1).is_none());
+    }
+    
+    #[test]
+    fn loads_cbdt_images() {
+        let mut tables = outlines();
+        // One strike of 20 pixels per em, with a PNG for glyph 1.
+        tables.push((b"CBLC", [
+            be32(&[0x30000, 1, 56, 16, 1, 0]),
+            vec![0; 24],
+            be16(&[1, 1]),
+            vec![20, 20, 32, 1],
+            be16(&[1, 1]),
+            be32(&[8]),
+            be16(&[1, 17]),
+            be32(&[4, 0, 5 + 4 + PNG.len() as u32]),
+        ].concat()));
+        tables.push((b"CBDT", [be32(&[0x30000]), vec![1, 2, 0, 1, 2], be32(&[PNG.len() as u32]), PNG.to_vec()].concat()));
+        let data = font(tables);
+        
+        let glyph = load(&data, 1).unwrap();
+        assert_bounds(&glyph, [0.0, 0.0, 100.0, 50.0]);
+        assert_image(&glyph);
+        assert!(load(&data, 2).is_none());
+    }
+    
+    #[test]
+    fn loads_sbix_images() {
+        let mut tables = outlines();
+        // One strike of 40 pixels per em, with a PNG for glyph 1 at (2, -1) pixels.
+        let end = 24 + 8 + PNG.len() as u32;
+        tables.push((b"sbix", [
+            be16(&[1, 1]),
+            be32(&[1, 12]),
+            be16(&[40, 72]),
+            be32(&[24, 24, end, end, end]),
+            be16(&[2, -1]),
+            b"png ".to_vec(),
+            PNG.to_vec(),
+        ].concat()));
+        let data = font(tables);
+        
+        let glyph = load(&data, 1).unwrap();
+        assert_bounds(&glyph, [50.0, -25.0, 100.0, 0.0]);
+        assert_image(&glyph);
+        assert!(load(&data, 2).is_none());
+    }
+}
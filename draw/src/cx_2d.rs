@@ -54,6 +54,8 @@ impl<'a> Drop for Cx2d<'a> {
     fn drop(&mut self) {
         self.draw_font_atlas();
         self.draw_icon_atlas();
+        let font_atlas = self.fonts_atlas_rc.0.borrow().texture.clone();
+        self.cx.send_studio_draw_samples(&font_atlas);
     }
 }
 
@@ -103,6 +105,10 @@ impl<'a> Cx2d<'a> {
         self.pass_stack.len()>0
     }
     
+    pub fn is_pass_on_stack(&self, pass_id: PassId) -> bool {
+        self.pass_stack.iter().any(|item| item.pass_id == pass_id)
+    }
+    
     pub fn make_child_pass(&mut self, pass: &Pass) {
         let pass_id = self.pass_stack.last().unwrap().pass_id;
         let cxpass = &mut self.passes[pass.pass_id()];
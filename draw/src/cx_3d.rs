@@ -1 +1,268 @@
+use {
+    crate::{
+        makepad_platform::*,
+        cx_2d::Cx2d,
+        draw_list_2d::DrawList2d,
+        geometry::GeometryGen,
+        gltf::{parse_glb, GltfDocument, GltfError},
+        shader::draw_mesh::DrawMesh3d,
+    },
+};
 
+// an orbit camera that looks at a target from a distance, yaw and pitch are in degrees
+#[derive(Clone, Debug)]
+pub struct Camera3d {
+    pub target: Vec3,
+    pub distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov_y: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Default for Camera3d {
+    fn default() -> Self {
+        Self {
+            target: vec3(0.0, 0.0, 0.0),
+            distance: 5.0,
+            yaw: 30.0,
+            pitch: 20.0,
+            fov_y: 45.0,
+            near: 0.1,
+            far: 1000.0,
+        }
+    }
+}
+
+impl Camera3d {
+    pub fn eye(&self) -> Vec3 {
+        let yaw = self.yaw.to_radians();
+        let pitch = self.pitch.clamp(-89.0, 89.0).to_radians();
+        self.target + vec3(
+            pitch.cos() * yaw.sin(),
+            pitch.sin(),
+            pitch.cos() * yaw.cos()
+        ) * self.distance
+    }
+
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::look_at(self.eye(), self.target, vec3(0.0, 1.0, 0.0))
+    }
+
+    // points the camera at the center of a bounding box from far enough to see all of it
+    pub fn frame_bounds(&mut self, min: Vec3, max: Vec3) {
+        let size = max - min;
+        let radius = 0.5 * (size.x * size.x + size.y * size.y + size.z * size.z).sqrt();
+        self.target = (min + max) * 0.5;
+        self.distance = (radius / (self.fov_y.to_radians() * 0.5).sin()).max(self.near * 2.0);
+        self.far = self.far.max(self.distance + radius * 2.0);
+    }
+}
+
+// an indexed triangle mesh in a geometry buffer with the pos, id, normal, uv layout
+pub struct Mesh3d {
+    pub geometry: Geometry,
+    pub bounds_min: Vec3,
+    pub bounds_max: Vec3,
+}
+
+impl Mesh3d {
+    pub fn new(cx: &mut Cx, positions: &[Vec3], normals: &[Vec3], uvs: &[Vec2], indices: &[u32]) -> Self {
+        let geometry = Geometry::new(cx);
+        GeometryGen::from_mesh_3d(positions, normals, uvs, indices).to_geometry(cx, &geometry);
+        let mut bounds_min = vec3(f32::MAX, f32::MAX, f32::MAX);
+        let mut bounds_max = vec3(f32::MIN, f32::MIN, f32::MIN);
+        for p in positions {
+            bounds_min = vec3(bounds_min.x.min(p.x), bounds_min.y.min(p.y), bounds_min.z.min(p.z));
+            bounds_max = vec3(bounds_max.x.max(p.x), bounds_max.y.max(p.y), bounds_max.z.max(p.z));
+        }
+        Self {geometry, bounds_min, bounds_max}
+    }
+
+    pub fn cube(cx: &mut Cx, width: f32, height: f32, depth: f32) -> Self {
+        let geometry = Geometry::new(cx);
+        GeometryGen::from_cube_3d(width, height, depth, 1, 1, 1).to_geometry(cx, &geometry);
+        Self {
+            geometry,
+            bounds_min: vec3(-width, -height, -depth) * 0.5,
+            bounds_max: vec3(width, height, depth) * 0.5,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Material3d {
+    pub base_color: Vec4,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub base_color_texture: Option<Texture>,
+    pub unlit: bool,
+}
+
+impl Default for Material3d {
+    fn default() -> Self {
+        Self {
+            base_color: vec4(1.0, 1.0, 1.0, 1.0),
+            metallic: 0.0,
+            roughness: 0.5,
+            base_color_texture: None,
+            unlit: false,
+        }
+    }
+}
+
+// the meshes of a model with their materials, placed by the nodes they were loaded from
+pub struct Model3d {
+    pub meshes: Vec<(Mesh3d, Material3d)>,
+    pub textures: Vec<Option<Texture>>,
+    pub instances: Vec<(usize, Mat4)>,
+}
+
+impl Model3d {
+    pub fn from_glb(cx: &mut Cx, data: &[u8]) -> Result<Self, GltfError> {
+        Ok(Self::from_gltf(cx, parse_glb(data)?))
+    }
+
+    pub fn from_gltf(cx: &mut Cx, doc: GltfDocument) -> Self {
+        let textures: Vec<Option<Texture>> = doc.images.into_iter().map( | image | {
+            image.map( | image | Texture::new_with_format(cx, TextureFormat::VecBGRAu8_32 {
+                width: image.width,
+                height: image.height,
+                data: image.data
+            }))
+        }).collect();
+        let materials: Vec<Material3d> = doc.materials.iter().map( | material | Material3d {
+            base_color: material.base_color,
+            metallic: material.metallic,
+            roughness: material.roughness,
+            base_color_texture: material.base_color_image.and_then( | image | textures.get(image).cloned().flatten()),
+            unlit: material.unlit,
+        }).collect();
+        // every primitive becomes its own mesh, a gltf mesh maps to a range of them
+        let mut meshes = Vec::new();
+        let mut ranges = Vec::new();
+        for mesh in &doc.meshes {
+            let start = meshes.len();
+            for primitive in &mesh.primitives {
+                let material = primitive.material
+                    .and_then( | material | materials.get(material).cloned())
+                    .unwrap_or_default();
+                meshes.push((
+                    Mesh3d::new(cx, &primitive.positions, &primitive.normals, &primitive.uvs, &primitive.indices),
+                    material
+                ));
+            }
+            ranges.push(start..meshes.len());
+        }
+        let mut instances = Vec::new();
+        for instance in &doc.instances {
+            for mesh in ranges[instance.mesh].clone() {
+                instances.push((mesh, instance.transform));
+            }
+        }
+        Self {meshes, textures, instances}
+    }
+
+    // the bounding box of all placed meshes, in model space
+    pub fn bounds(&self) -> Option<(Vec3, Vec3)> {
+        let mut bounds: Option<(Vec3, Vec3)> = None;
+        for (mesh, transform) in &self.instances {
+            let (min, max) = (self.meshes[*mesh].0.bounds_min, self.meshes[*mesh].0.bounds_max);
+            if min.x > max.x {
+                continue;
+            }
+            for i in 0..8 {
+                let corner = vec4(
+                    if i & 1 == 0 {min.x} else {max.x},
+                    if i & 2 == 0 {min.y} else {max.y},
+                    if i & 4 == 0 {min.z} else {max.z},
+                    1.0
+                );
+                let p = transform.transform_vec4(corner);
+                let (lo, hi) = bounds.get_or_insert((p.to_vec3(), p.to_vec3()));
+                *lo = vec3(lo.x.min(p.x), lo.y.min(p.y), lo.z.min(p.z));
+                *hi = vec3(hi.x.max(p.x), hi.y.max(p.y), hi.z.max(p.z));
+            }
+        }
+        bounds
+    }
+
+    pub fn draw(&self, cx: &mut Cx2d, draw_mesh: &mut DrawMesh3d, transform: &Mat4) {
+        for (mesh, node_transform) in &self.instances {
+            let (mesh, material) = &self.meshes[*mesh];
+            draw_mesh.set_material(material);
+            draw_mesh.draw_mesh(cx, mesh, &Mat4::mul(node_transform, transform));
+        }
+    }
+}
+
+// a depth tested scene that renders into its own texture, which is then drawn into the 2D layout
+pub struct Cx3d {
+    pub camera: Camera3d,
+    pub clear_color: Vec4,
+    pass: Pass,
+    color_texture: Texture,
+    depth_texture: Texture,
+    draw_list: DrawList2d,
+}
+
+impl Cx3d {
+    pub fn new(cx: &mut Cx) -> Self {
+        let pass = Pass::new_with_name(cx, "Cx3d");
+        let color_texture = Texture::new_with_format(cx, TextureFormat::RenderBGRAu8 {
+            size: TextureSize::Auto
+        });
+        let depth_texture = Texture::new_with_format(cx, TextureFormat::DepthD32 {
+            size: TextureSize::Auto
+        });
+        let clear_color = vec4(0.0, 0.0, 0.0, 0.0);
+        pass.add_color_texture(cx, &color_texture, PassClearColor::ClearWith(clear_color));
+        pass.set_depth_texture(cx, &depth_texture, PassClearDepth::ClearWith(1.0));
+        Self {
+            camera: Camera3d::default(),
+            clear_color,
+            pass,
+            color_texture,
+            depth_texture,
+            draw_list: DrawList2d::new(cx),
+        }
+    }
+
+    pub fn color_texture(&self) -> &Texture {
+        &self.color_texture
+    }
+
+    pub fn depth_texture(&self) -> &Texture {
+        &self.depth_texture
+    }
+
+    // starts the pass, everything drawn until end goes into the scene
+    pub fn begin(&mut self, cx: &mut Cx2d) {
+        self.pass.clear_color_textures(cx);
+        self.pass.add_color_texture(cx, &self.color_texture, PassClearColor::ClearWith(self.clear_color));
+        self.pass.set_matrix_mode(cx, PassMatrixMode::Projection {
+            fov_y: self.camera.fov_y,
+            near: self.camera.near,
+            far: self.camera.far,
+            cam: self.camera.view_matrix(),
+        });
+        cx.make_child_pass(&self.pass);
+        cx.begin_pass(&self.pass, None);
+        self.draw_list.begin_always(cx);
+    }
+
+    pub fn end(&mut self, cx: &mut Cx2d) {
+        self.draw_list.end(cx);
+        cx.end_pass(&self.pass);
+    }
+
+    // sizes the pass to the area the color texture is drawn in, call it after end
+    pub fn set_area(&self, cx: &mut Cx2d, area: Area) {
+        cx.set_pass_area(&self.pass, area);
+    }
+
+    pub fn redraw(&self, cx: &mut Cx) {
+        self.draw_list.redraw(cx);
+    }
+}
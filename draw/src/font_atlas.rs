@@ -14,6 +14,9 @@ pub use {
     crate::{
         makepad_platform::*,
         cx_2d::Cx2d,
+        color_glyph::{ColorGlyphCache, CxColorGlyphKind},
+        shader::draw_text::TextStyle,
+        system_fonts::SystemFonts,
         turtle::{Walk, Layout},
         draw_list_2d::{ManyInstances, DrawList2d, RedrawingApi},
         geometry::GeometryQuad2D,
@@ -28,13 +31,24 @@ pub use {
 
 pub(crate) const ATLAS_WIDTH: usize = 4096;
 pub(crate) const ATLAS_HEIGHT: usize = 4096;
+pub(crate) const COLOR_ATLAS_WIDTH: usize = 2048;
+pub(crate) const COLOR_ATLAS_HEIGHT: usize = 2048;
 
 pub struct CxFontsAtlas {
     pub fonts: Vec<Option<CxFont >>,
     pub path_to_font_id: HashMap<String, usize>,
     pub texture: Texture,
     pub clear_buffer: bool,
-    pub alloc: CxFontsAtlasAlloc
+    pub alloc: CxFontsAtlasAlloc,
+    // Color glyphs (emoji) are kept in their own RGBA atlas, next to the SDF atlas.
+    pub color_texture: Texture,
+    // For every pixel of the color atlas, how much of it is in the color of the text.
+    pub color_mask_texture: Texture,
+    pub color_alloc: CxFontsAtlasAlloc,
+    system_fonts: SystemFonts,
+    // Set when a character was drawn without its system font because the system fonts were
+    // still being scanned.
+    redraw_when_system_fonts_ready: bool,
 }
 
 #[derive(Default)]
@@ -53,7 +67,7 @@ pub struct CxFontsAtlasSdfConfig {
 }
 
 impl CxFontsAtlas {
-    pub fn new(texture: Texture) -> Self {
+    pub fn new(texture: Texture, color_texture: Texture, color_mask_texture: Texture) -> Self {
         Self {
             fonts: Vec::new(),
             path_to_font_id: HashMap::new(),
@@ -79,6 +93,19 @@ impl CxFontsAtlas {
                     },
                 })
             },
+            color_texture,
+            color_mask_texture,
+            color_alloc: CxFontsAtlasAlloc {
+                texture_size: DVec2 {
+                    x: COLOR_ATLAS_WIDTH as f64,
+                    y: COLOR_ATLAS_HEIGHT as f64
+                },
+                // Color glyphs are stored as plain pixels, an SDF can't hold their colors.
+                sdf: None,
+                ..Default::default()
+            },
+            system_fonts: SystemFonts::default(),
+            redraw_when_system_fonts_ready: false,
         }
     }
 }
@@ -190,17 +217,67 @@ impl CxFontsAtlas {
         font_id
     }
     
+    // Returns the first font of the text style that has a glyph for the character, trying the
+    // system fonts after its fallback fonts if the style allows that. If no font has the glyph,
+    // the main font is returned, so that its missing glyph is drawn.
+    pub fn font_id_for_char(&mut self, text_style: &TextStyle, c: char) -> Option<usize> {
+        let fonts = &self.fonts;
+        let main_font_id = match first_font_with_glyph(text_style.font_ids(), |font_id| {
+            Some(fonts.get(font_id)?.as_ref()?.has_glyph(c))
+        }) {
+            Ok(font_id) => return Some(font_id),
+            Err(main_font_id) => main_font_id,
+        };
+        if text_style.system_font_fallback && !c.is_whitespace() && !c.is_control() {
+            if let Some(font_id) = self.system_font_id_for_char(c) {
+                return Some(font_id)
+            }
+            if self.system_fonts.is_scanning() {
+                self.redraw_when_system_fonts_ready = true;
+            }
+        }
+        main_font_id
+    }
+    
+    fn system_font_id_for_char(&mut self, c: char) -> Option<usize> {
+        let path = self.system_fonts.path_for_char(c)?.to_string_lossy().to_string();
+        if let Some(font_id) = self.path_to_font_id.get(&path) {
+            return self.fonts[*font_id].as_ref().map(|_| *font_id)
+        }
+        let font_id = self.fonts.len();
+        let font = match std::fs::read(&path) {
+            Ok(data) => CxFont::load_from_ttf_bytes(Rc::new(data)).ok(),
+            Err(err) => {
+                error!("system_font_id_for_char - {} {}", path, err);
+                None
+            }
+        };
+        self.fonts.push(font);
+        self.path_to_font_id.insert(path, font_id);
+        self.fonts[font_id].as_ref().map(|_| font_id)
+    }
+    
+    // Returns the advance of the character in ems, in the font that is used to draw it.
+    pub fn glyph_advance_em(&mut self, text_style: &TextStyle, c: char) -> Option<f64> {
+        let font_id = self.font_id_for_char(text_style, c)?;
+        let font = self.fonts[font_id].as_mut()?;
+        let units_per_em = font.ttf_font.units_per_em;
+        Some(font.get_glyph(c)?.horizontal_metrics.advance_width / units_per_em)
+    }
+    
     pub fn reset_fonts_atlas(&mut self) {
         for cxfont in &mut self.fonts {
             if let Some(cxfont) = cxfont {
                 cxfont.atlas_pages.clear();
             }
         }
-        self.alloc.todo.clear();
-        self.alloc.full = false;
-        self.alloc.xpos = 0;
-        self.alloc.ypos = 0;
-        self.alloc.hmax = 0;
+        for alloc in [&mut self.alloc, &mut self.color_alloc] {
+            alloc.todo.clear();
+            alloc.full = false;
+            alloc.xpos = 0;
+            alloc.ypos = 0;
+            alloc.hmax = 0;
+        }
         self.clear_buffer = true;
     }
     
@@ -209,6 +286,25 @@ impl CxFontsAtlas {
     }
 }
 
+// Walks the fonts in order and returns the first one that has the glyph, `has_glyph` returns
+// `None` for fonts that failed to load. Without a match, the first loaded font is returned as
+// the error, or `None` if there is none.
+fn first_font_with_glyph(
+    font_ids: impl Iterator<Item = usize>,
+    mut has_glyph: impl FnMut(usize) -> Option<bool>,
+) -> Result<usize, Option<usize>> {
+    let mut main_font_id = None;
+    for font_id in font_ids {
+        if let Some(has_glyph) = has_glyph(font_id) {
+            main_font_id.get_or_insert(font_id);
+            if has_glyph {
+                return Ok(font_id)
+            }
+        }
+    }
+    Err(main_font_id)
+}
+
 impl<'a> Cx2d<'a> {
     pub fn lazy_construct_font_atlas(cx: &mut Cx){
         // ok lets fetch/instance our CxFontsAtlasRc
@@ -221,7 +317,20 @@ impl<'a> Cx2d<'a> {
                 unpack_row_length: None
             });
             
-            let fonts_atlas = CxFontsAtlas::new(texture);
+            let color_texture = Texture::new_with_format(cx, TextureFormat::VecBGRAu8_32 {
+                width: COLOR_ATLAS_WIDTH,
+                height: COLOR_ATLAS_HEIGHT,
+                data: vec![],
+            });
+            
+            let color_mask_texture = Texture::new_with_format(cx, TextureFormat::VecRu8 {
+                width: COLOR_ATLAS_WIDTH,
+                height: COLOR_ATLAS_HEIGHT,
+                data: vec![],
+                unpack_row_length: None
+            });
+            
+            let fonts_atlas = CxFontsAtlas::new(texture, color_texture, color_mask_texture);
             cx.set_global(CxFontsAtlasRc(Rc::new(RefCell::new(fonts_atlas))));
         }
     }
@@ -238,7 +347,7 @@ impl<'a> Cx2d<'a> {
         let mut fonts_atlas = fonts_atlas_rc.0.borrow_mut();
         let fonts_atlas = &mut*fonts_atlas;
 
-        if fonts_atlas.alloc.full {
+        if fonts_atlas.alloc.full || fonts_atlas.color_alloc.full {
             fonts_atlas.reset_fonts_atlas();
         }
 
@@ -248,6 +357,15 @@ impl<'a> Cx2d<'a> {
         for todo in std::mem::take(&mut fonts_atlas.alloc.todo) {
             self.swrast_atlas_todo(fonts_atlas, todo, &mut reuse_sdfer_bufs);
         }
+        for todo in std::mem::take(&mut fonts_atlas.color_alloc.todo) {
+            self.swrast_color_atlas_todo(fonts_atlas, todo);
+        }
+
+        // Keep redrawing while the system fonts are scanned, so that the characters that are
+        // waiting for them show up once they are there.
+        if std::mem::take(&mut fonts_atlas.redraw_when_system_fonts_ready) {
+            self.cx.redraw_all();
+        }
     }
 
     fn swrast_atlas_todo(
@@ -312,40 +430,7 @@ impl<'a> Cx2d<'a> {
             atlas_alloc_wh.y.ceil() as usize,
         );
 
-        let mut cur = ab_glyph_rasterizer::point(0.0, 0.0);
-        let to_ab = |p: makepad_vector::geometry::Point| ab_glyph_rasterizer::point(p.x as f32, p.y as f32);
-        commands
-        .fold(ab_glyph_rasterizer::Rasterizer::new(
-            glyph_rast.width(),
-            glyph_rast.height()
-        ), |mut rasterizer, cmd| match cmd {
-            makepad_vector::path::PathCommand::MoveTo(p) => {
-                cur = to_ab(p);
-                rasterizer
-            }
-            makepad_vector::path::PathCommand::LineTo(p1) => {
-                let (p0, p1) = (cur, to_ab(p1));
-                rasterizer.draw_line(p0, p1);
-                cur = p1;
-                rasterizer
-            }
-            makepad_vector::path::PathCommand::ArcTo(..) => {
-                unreachable!("font glyphs should not use arcs");
-            }
-            makepad_vector::path::PathCommand::QuadraticTo(p1, p2) => {
-                let (p0, p1, p2) = (cur, to_ab(p1), to_ab(p2));
-                rasterizer.draw_quad(p0, p1, p2);
-                cur = p2;
-                rasterizer
-            }
-            makepad_vector::path::PathCommand::CubicTo(p1, p2, p3) => {
-                let (p0, p1, p2, p3) = (cur, to_ab(p1), to_ab(p2), to_ab(p3));
-                rasterizer.draw_cubic(p0, p1, p2, p3);
-                cur = p3;
-                rasterizer
-            }
-            makepad_vector::path::PathCommand::Close => rasterizer
-        })
+        rasterize_outline(commands, glyph_rast.width(), glyph_rast.height())
         .for_each_pixel_2d(|x, y, a| {
             glyph_rast[(x as usize, y as usize)] = sdfer::Unorm8::encode(a);
         });
@@ -385,6 +470,200 @@ impl<'a> Cx2d<'a> {
         }
         fonts_atlas.texture.swap_vec_u8(self.cx, &mut atlas_data);
     }
+
+    fn swrast_color_atlas_todo(&mut self, fonts_atlas: &mut CxFontsAtlas, todo: CxFontsAtlasTodo) {
+        let cxfont = fonts_atlas.fonts[todo.font_id].as_mut().unwrap();
+        let Some(color_glyph) = cxfont.color_glyphs.get(&cxfont.owned_font_face, todo.glyph_id) else {
+            return
+        };
+        let units_per_em = cxfont.ttf_font.units_per_em;
+        let atlas_page = &cxfont.atlas_pages[todo.atlas_page_id];
+        let glyphtc = *atlas_page.atlas_glyphs.get(&todo.glyph_id).unwrap();
+        let font_scale_pixels = atlas_page.font_size * 96.0 / (72.0 * units_per_em) * atlas_page.dpi_factor;
+        let bounds = color_glyph.bounds;
+        let texture_size = fonts_atlas.color_alloc.texture_size;
+
+        // These mirror `swrast_atlas_todo` above, with the bounds of the color glyph.
+        let render_pad_dpx = 2.0;
+        let render_wh = dvec2(
+            ((bounds.p_max.x - bounds.p_min.x) * font_scale_pixels).ceil() + render_pad_dpx * 2.0,
+            ((bounds.p_max.y - bounds.p_min.y) * font_scale_pixels).ceil() + render_pad_dpx * 2.0,
+        );
+        let atlas_alloc_wh = dvec2(
+            (glyphtc.t2.x - glyphtc.t1.x) as f64 * texture_size.x + 1.0,
+            (glyphtc.t2.y - glyphtc.t1.y) as f64 * texture_size.y + 1.0,
+        );
+        let atlas_scaling = atlas_alloc_wh / render_wh;
+        let (width, height) = (atlas_alloc_wh.x.ceil() as usize, atlas_alloc_wh.y.ceil() as usize);
+
+        // Premultiplied RGBA, with the bottom row first like the rasterized outlines. Layers in the
+        // color of the text add to the mask instead, as the atlas is shared by text of every color.
+        let mut glyph_rgba = vec![[0.0f32; 4]; width * height];
+        let mut glyph_mask = vec![0.0f32; width * height];
+        match &color_glyph.kind {
+            CxColorGlyphKind::Layers(layers) => {
+                let transform = AffineTransformation::identity()
+                    .translate(Vector::new(-bounds.p_min.x, -bounds.p_min.y))
+                    .uniform_scale(font_scale_pixels)
+                    .translate(Vector::new(render_pad_dpx, render_pad_dpx))
+                    .scale(Vector::new(atlas_scaling.x, atlas_scaling.y));
+                for layer in layers {
+                    let Ok(glyph) = cxfont.get_glyph_by_id(layer.glyph_id) else {
+                        continue
+                    };
+                    let is_foreground = layer.color.is_none();
+                    let [r, g, b, a] = layer.color.unwrap_or([0, 0, 0, 0xff]).map(|c| c as f32 / 255.0);
+                    let commands = glyph.outline.iter().map(|command| command.transform(&transform));
+                    rasterize_outline(commands, width, height).for_each_pixel_2d(|x, y, coverage| {
+                        let alpha = coverage.min(1.0) * a;
+                        let index = y as usize * width + x as usize;
+                        let dst = &mut glyph_rgba[index];
+                        *dst = [
+                            r * alpha + dst[0] * (1.0 - alpha),
+                            g * alpha + dst[1] * (1.0 - alpha),
+                            b * alpha + dst[2] * (1.0 - alpha),
+                            alpha + dst[3] * (1.0 - alpha),
+                        ];
+                        let mask = &mut glyph_mask[index];
+                        *mask = (if is_foreground {alpha} else {0.0}) + *mask * (1.0 - alpha);
+                    });
+                }
+            }
+            CxColorGlyphKind::Image {width: image_width, height: image_height, pixels} => {
+                // The area of the raster that the image covers, which gets the average of the
+                // image pixels that fall inside each of its pixels.
+                let x0 = render_pad_dpx * atlas_scaling.x;
+                let y0 = render_pad_dpx * atlas_scaling.y;
+                let w = (bounds.p_max.x - bounds.p_min.x) * font_scale_pixels * atlas_scaling.x;
+                let h = (bounds.p_max.y - bounds.p_min.y) * font_scale_pixels * atlas_scaling.y;
+                let (image_w, image_h) = (*image_width as f64, *image_height as f64);
+                for y in 0..height {
+                    for x in 0..width {
+                        let u0 = (x as f64 - x0) / w * image_w;
+                        let u1 = (x as f64 + 1.0 - x0) / w * image_w;
+                        // The image is stored top row first.
+                        let v0 = (y0 + h - y as f64 - 1.0) / h * image_h;
+                        let v1 = (y0 + h - y as f64) / h * image_h;
+                        glyph_rgba[y * width + x] = average_image_pixels(pixels, *image_width, *image_height, u0..u1, v0..v1);
+                    }
+                }
+            }
+        }
+
+        let mut atlas_data = vec![];
+        fonts_atlas.color_texture.swap_vec_u32(self.cx, &mut atlas_data);
+        let (atlas_w, atlas_h) = fonts_atlas.color_texture.get_format(self.cx).vec_width_height().unwrap();
+        if atlas_data.is_empty() {
+            atlas_data = vec![0; atlas_w*atlas_h];
+        } else {
+            assert_eq!(atlas_data.len(), atlas_w*atlas_h);
+        }
+
+        let atlas_x0 = (glyphtc.t1.x as f64 * texture_size.x) as usize;
+        let atlas_y0 = (glyphtc.t1.y as f64 * texture_size.y) as usize;
+        let to_u8 = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u32;
+        for y in 0..height {
+            let dst = &mut atlas_data[(atlas_h - atlas_y0 - 1 - y) * atlas_w..][..atlas_w][atlas_x0..][..width];
+            for (dst, [r, g, b, a]) in dst.iter_mut().zip(&glyph_rgba[y * width..][..width]) {
+                *dst = (to_u8(*a) << 24) | (to_u8(*r) << 16) | (to_u8(*g) << 8) | to_u8(*b);
+            }
+        }
+        fonts_atlas.color_texture.swap_vec_u32(self.cx, &mut atlas_data);
+
+        // Image glyphs write their (empty) mask too, the atlas is reused once it fills up.
+        let mut mask_data = vec![];
+        fonts_atlas.color_mask_texture.swap_vec_u8(self.cx, &mut mask_data);
+        if mask_data.is_empty() {
+            mask_data = vec![0; atlas_w*atlas_h];
+        } else {
+            assert_eq!(mask_data.len(), atlas_w*atlas_h);
+        }
+        for y in 0..height {
+            let dst = &mut mask_data[(atlas_h - atlas_y0 - 1 - y) * atlas_w..][..atlas_w][atlas_x0..][..width];
+            for (dst, mask) in dst.iter_mut().zip(&glyph_mask[y * width..][..width]) {
+                *dst = to_u8(*mask) as u8;
+            }
+        }
+        fonts_atlas.color_mask_texture.swap_vec_u8(self.cx, &mut mask_data);
+    }
+}
+
+// Returns the premultiplied average of the BGRA pixels of an image in the given ranges of columns
+// and rows, which are clamped to the image.
+fn average_image_pixels(
+    pixels: &[u32],
+    width: usize,
+    height: usize,
+    columns: std::ops::Range<f64>,
+    rows: std::ops::Range<f64>,
+) -> [f32; 4] {
+    let clamp = |range: std::ops::Range<f64>, size: usize| {
+        if range.end <= 0.0 || range.start >= size as f64 {
+            return 0..0
+        }
+        let start = (range.start.max(0.0) as usize).min(size - 1);
+        let end = (range.end.ceil() as usize).clamp(start + 1, size);
+        start..end
+    };
+    let (columns, rows) = (clamp(columns, width), clamp(rows, height));
+    let mut sum = [0.0f32; 4];
+    let mut count = 0.0;
+    for y in rows {
+        for x in columns.clone() {
+            let pixel = pixels[y * width + x];
+            let a = (pixel >> 24) as f32 / 255.0;
+            sum[0] += ((pixel >> 16) & 0xff) as f32 / 255.0 * a;
+            sum[1] += ((pixel >> 8) & 0xff) as f32 / 255.0 * a;
+            sum[2] += (pixel & 0xff) as f32 / 255.0 * a;
+            sum[3] += a;
+            count += 1.0;
+        }
+    }
+    if count > 0.0 {
+        sum.map(|c| c / count)
+    } else {
+        sum
+    }
+}
+
+// Rasterizes a glyph outline that has already been transformed into pixels, into a coverage
+// mask of the given size.
+fn rasterize_outline(
+    commands: impl Iterator<Item = makepad_vector::path::PathCommand>,
+    width: usize,
+    height: usize,
+) -> ab_glyph_rasterizer::Rasterizer {
+    let mut cur = ab_glyph_rasterizer::point(0.0, 0.0);
+    let to_ab = |p: makepad_vector::geometry::Point| ab_glyph_rasterizer::point(p.x as f32, p.y as f32);
+    commands
+    .fold(ab_glyph_rasterizer::Rasterizer::new(width, height), |mut rasterizer, cmd| match cmd {
+        makepad_vector::path::PathCommand::MoveTo(p) => {
+            cur = to_ab(p);
+            rasterizer
+        }
+        makepad_vector::path::PathCommand::LineTo(p1) => {
+            let (p0, p1) = (cur, to_ab(p1));
+            rasterizer.draw_line(p0, p1);
+            cur = p1;
+            rasterizer
+        }
+        makepad_vector::path::PathCommand::ArcTo(..) => {
+            unreachable!("font glyphs should not use arcs");
+        }
+        makepad_vector::path::PathCommand::QuadraticTo(p1, p2) => {
+            let (p0, p1, p2) = (cur, to_ab(p1), to_ab(p2));
+            rasterizer.draw_quad(p0, p1, p2);
+            cur = p2;
+            rasterizer
+        }
+        makepad_vector::path::PathCommand::CubicTo(p1, p2, p3) => {
+            let (p0, p1, p2, p3) = (cur, to_ab(p1), to_ab(p2), to_ab(p3));
+            rasterizer.draw_cubic(p0, p1, p2, p3);
+            cur = p3;
+            rasterizer
+        }
+        makepad_vector::path::PathCommand::Close => rasterizer
+    })
 }
 
 pub struct CxFont {
@@ -392,6 +671,7 @@ pub struct CxFont {
     pub owned_font_face: crate::owned_font_face::OwnedFace,
     pub atlas_pages: Vec<CxFontAtlasPage>,
     pub shape_cache: ShapeCache,
+    pub color_glyphs: ColorGlyphCache,
 }
 
 pub struct ShapeCache {
@@ -548,6 +828,7 @@ impl CxFont {
             owned_font_face,
             atlas_pages: Vec::new(),
             shape_cache: ShapeCache::new(),
+            color_glyphs: ColorGlyphCache::default(),
         })
     }
     
@@ -566,15 +847,48 @@ impl CxFont {
         self.atlas_pages.len() - 1
     }
 
+    pub fn has_glyph(&self, c: char) -> bool {
+        self.owned_font_face.with_ref(|face| face.glyph_index(c).is_some())
+    }
+
     pub fn get_glyph(&mut self, c:char)->Option<&Glyph>{
-        if c < '\u{10000}' {
-            Some(self.get_glyph_by_id(self.owned_font_face.with_ref(|face| face.glyph_index(c))?.0 as usize).unwrap())
-        } else {
-            None
-        }
+        Some(self.get_glyph_by_id(self.owned_font_face.with_ref(|face| face.glyph_index(c))?.0 as usize).unwrap())
     }
 
     pub fn get_glyph_by_id(&mut self, id: usize) -> makepad_vector::ttf_parser::Result<&Glyph> {
         self.owned_font_face.with_ref(|face| self.ttf_font.get_glyph_by_id(face, id))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Font 1 failed to load, the others have glyphs for the given characters.
+    fn has_glyph(c: char) -> impl FnMut(usize) -> Option<bool> {
+        move |font_id| match font_id {
+            0 => Some(c == 'a'),
+            1 => None,
+            2 => Some(c == 'a' || c == 'b'),
+            3 => Some(c == 'b' || c == 'c'),
+            _ => Some(false),
+        }
+    }
+    
+    #[test]
+    fn walks_the_fallback_chain_in_order() {
+        let chain = [0, 1, 2, 3];
+        assert_eq!(first_font_with_glyph(chain.into_iter(), has_glyph('a')), Ok(0));
+        assert_eq!(first_font_with_glyph(chain.into_iter(), has_glyph('b')), Ok(2));
+        assert_eq!(first_font_with_glyph(chain.into_iter(), has_glyph('c')), Ok(3));
+        assert_eq!(first_font_with_glyph([3, 2].into_iter(), has_glyph('b')), Ok(3));
+    }
+    
+    #[test]
+    fn falls_back_to_the_first_loaded_font() {
+        assert_eq!(first_font_with_glyph([0, 1, 2, 3].into_iter(), has_glyph('d')), Err(Some(0)));
+        assert_eq!(first_font_with_glyph([1, 3].into_iter(), has_glyph('a')), Err(Some(3)));
+        assert_eq!(first_font_with_glyph([1].into_iter(), has_glyph('a')), Err(None));
+        assert_eq!(first_font_with_glyph([].into_iter(), has_glyph('a')), Err(None));
+    }
+}
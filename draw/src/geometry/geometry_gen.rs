@@ -12,6 +12,12 @@ live_design!{
         x2: 1.0;
         y2: 1.0;
     }
+    
+    GeometryCube3D = {{GeometryCube3D}} {
+        width: 1.0;
+        height: 1.0;
+        depth: 1.0;
+    }
 }
 
 impl LiveHook for GeometryQuad2D {
@@ -61,6 +67,50 @@ pub struct GeometryQuad2D {
     #[live(1.0)] pub y2: f32,
 }
 
+impl LiveHook for GeometryCube3D {
+    fn after_apply(&mut self, cx: &mut Cx, _apply:&mut Apply, _index:usize, _nodes:&[LiveNode]) {
+        let mut fp = GeometryFingerprint::new(LiveType::of::<Self>());
+        fp.push(self.width);
+        fp.push(self.height);
+        fp.push(self.depth);
+        self.geometry_ref = Some(cx.get_geometry_ref(fp));
+        GeometryGen::from_cube_3d(
+            self.width,
+            self.height,
+            self.depth,
+            1,
+            1,
+            1
+        ).to_geometry(cx, &self.geometry_ref.as_ref().unwrap().0);
+    }
+}
+
+impl GeometryFields for GeometryCube3D {
+    fn geometry_fields(&self, fields: &mut Vec<GeometryField>) {
+        fields.push(GeometryField {id: live_id!(geom_pos), ty: ShaderTy::Vec3});
+        fields.push(GeometryField {id: live_id!(geom_id), ty: ShaderTy::Float});
+        fields.push(GeometryField {id: live_id!(geom_normal), ty: ShaderTy::Vec3});
+        fields.push(GeometryField {id: live_id!(geom_uv), ty: ShaderTy::Vec2});
+    }
+    
+    fn get_geometry_id(&self) -> Option<GeometryId> {
+        self.geometry_ref.as_ref().map( | gr | gr.0.geometry_id())
+    }
+    
+    fn live_type_check(&self) -> LiveType {
+        LiveType::of::<Self>()
+    }
+}
+
+// the vertex layout of this geometry is shared by every 3D mesh
+#[derive(Live, LiveRegister)]
+pub struct GeometryCube3D {
+    #[rust] pub geometry_ref: Option<GeometryRef>,
+    #[live(1.0)] pub width: f32,
+    #[live(1.0)] pub height: f32,
+    #[live(1.0)] pub depth: f32,
+}
+
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct GeometryGen {
     pub vertices: Vec<f32>, // vec4 pos, vec3 normal, vec2 uv
@@ -99,6 +149,12 @@ impl GeometryGen {
         g
     }
     
+    pub fn from_mesh_3d(positions: &[Vec3], normals: &[Vec3], uvs: &[Vec2], indices: &[u32]) -> GeometryGen {
+        let mut g = Self::default();
+        g.add_mesh_3d(positions, normals, uvs, indices);
+        g
+    }
+    
     // requires pos:vec2 normalized layout
     pub fn add_quad_2d(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
         let vertex_offset = self.vertices.len() as u32;
@@ -137,6 +193,41 @@ impl GeometryGen {
     }
     
     
+    // requires pos:vec3, id:float, normal:vec3, uv:vec2 layout
+    // missing normals are computed by averaging the face normals, missing uvs are zero
+    pub fn add_mesh_3d(&mut self, positions: &[Vec3], normals: &[Vec3], uvs: &[Vec2], indices: &[u32]) {
+        let vertex_offset = (self.vertices.len() / 9) as u32;
+        let mut smooth_normals = Vec::new();
+        let normals = if normals.len() == positions.len() {
+            normals
+        }
+        else {
+            smooth_normals.resize(positions.len(), Vec3::default());
+            for tri in indices.chunks_exact(3) {
+                let (a, b, c) = (tri[0] as usize, tri[1] as usize, tri[2] as usize);
+                if a >= positions.len() || b >= positions.len() || c >= positions.len() {
+                    continue;
+                }
+                let n = Vec3::cross(positions[b] - positions[a], positions[c] - positions[a]);
+                smooth_normals[a] += n;
+                smooth_normals[b] += n;
+                smooth_normals[c] += n;
+            }
+            for n in &mut smooth_normals {
+                *n = n.normalize();
+            }
+            &smooth_normals
+        };
+        for (i, pos) in positions.iter().enumerate() {
+            let normal = normals[i];
+            let uv = uvs.get(i).cloned().unwrap_or_default();
+            self.vertices.extend_from_slice(&[pos.x, pos.y, pos.z, 0.0, normal.x, normal.y, normal.z, uv.x, uv.y]);
+        }
+        for index in indices {
+            self.indices.push(vertex_offset + index);
+        }
+    }
+    
     // requires pos:vec3, id:float, normal:vec3, uv:vec2 layout
     pub fn add_plane_3d(
         &mut self,
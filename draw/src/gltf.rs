@@ -0,0 +1,583 @@
+//! Reads binary glTF 2.0 (`.glb`) files: triangle meshes with positions, normals and texture
+//! coordinates, the node hierarchy of the default scene, metallic-roughness materials and the
+//! PNG or JPEG images they reference. Buffers and images must live in the binary chunk.
+
+use {
+    std::fmt,
+    crate::{
+        makepad_platform::*,
+        makepad_platform::makepad_micro_serde::{DeJson, JsonValue},
+        color_glyph::png_to_bgra,
+    },
+    makepad_zune_png::PngDecoder,
+    makepad_zune_jpeg::JpegDecoder,
+};
+
+const GLB_MAGIC: u32 = 0x46546C67;
+const CHUNK_JSON: u32 = 0x4E4F534A;
+const CHUNK_BIN: u32 = 0x004E4942;
+// the most values an accessor without a buffer view is filled with, as its count isn't bounded
+// by the size of the file
+const MAX_ZEROED_VALUES: usize = 1 << 24;
+
+#[derive(Clone, Debug, Default)]
+pub struct GltfDocument {
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<GltfMaterial>,
+    // indexed like the images of the file, images that failed to decode are None
+    pub images: Vec<Option<GltfImage>>,
+    // every placement of a mesh in the default scene, with the transforms of its parents applied
+    pub instances: Vec<GltfInstance>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GltfPrimitive {
+    pub positions: Vec<Vec3>,
+    // empty when the file has no normals
+    pub normals: Vec<Vec3>,
+    // empty when the file has no texture coordinates
+    pub uvs: Vec<Vec2>,
+    pub indices: Vec<u32>,
+    pub material: Option<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfMaterial {
+    pub base_color: Vec4,
+    pub metallic: f32,
+    pub roughness: f32,
+    // an index into the images of the document
+    pub base_color_image: Option<usize>,
+    // set by the KHR_materials_unlit extension
+    pub unlit: bool,
+}
+
+impl Default for GltfMaterial {
+    fn default() -> Self {
+        Self {
+            base_color: vec4(1.0, 1.0, 1.0, 1.0),
+            metallic: 1.0,
+            roughness: 1.0,
+            base_color_image: None,
+            unlit: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfImage {
+    pub width: usize,
+    pub height: usize,
+    // packed like the VecBGRAu8_32 texture format
+    pub data: Vec<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct GltfInstance {
+    pub mesh: usize,
+    pub transform: Mat4,
+}
+
+/// The possible errors that can occur when reading a glTF file.
+#[derive(Debug)]
+pub enum GltfError {
+    /// The data does not start with a binary glTF header, or the header is not version 2.
+    NotGlb,
+    /// A chunk or a buffer view points outside of the data.
+    Truncated,
+    /// The JSON chunk could not be parsed.
+    Json(String),
+    /// A required property is missing or has the wrong type.
+    Invalid(&'static str),
+    /// A buffer refers to an external file or a data URI, only the binary chunk is supported.
+    ExternalBuffer,
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+pub fn parse_glb(data: &[u8]) -> Result<GltfDocument, GltfError> {
+    let read_u32 = |offset: usize| -> Result<u32, GltfError> {
+        let bytes = data.get(offset..offset + 4).ok_or(GltfError::Truncated)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+    if data.len() < 12 || read_u32(0)? != GLB_MAGIC || read_u32(4)? != 2 {
+        return Err(GltfError::NotGlb)
+    }
+    let length = (read_u32(8)? as usize).min(data.len());
+    let mut json = None;
+    let mut bin: &[u8] = &[];
+    let mut offset = 12;
+    while offset + 8 <= length {
+        let chunk_length = read_u32(offset)? as usize;
+        let chunk_type = read_u32(offset + 4)?;
+        let chunk = data.get(offset + 8..offset + 8 + chunk_length).ok_or(GltfError::Truncated)?;
+        match chunk_type {
+            CHUNK_JSON if json.is_none() => json = Some(chunk),
+            CHUNK_BIN if bin.is_empty() => bin = chunk,
+            _ => ()
+        }
+        // chunks are padded to 4 bytes
+        offset += 8 + ((chunk_length + 3) & !3);
+    }
+    let json = json.ok_or(GltfError::Invalid("JSON chunk"))?;
+    let json = std::str::from_utf8(json).map_err( | _ | GltfError::Json("JSON chunk is not UTF-8".to_string()))?;
+    let root = JsonValue::deserialize_json(json).map_err( | e | GltfError::Json(format!("{:?}", e)))?;
+    GltfReader {root: &root, bin}.read()
+}
+
+struct GltfReader<'a> {
+    root: &'a JsonValue,
+    bin: &'a [u8],
+}
+
+impl<'a> GltfReader<'a> {
+    fn read(&self) -> Result<GltfDocument, GltfError> {
+        if let Some(buffers) = array(self.root, "buffers") {
+            if buffers.iter().any( | buffer | get(buffer, "uri").is_some()) {
+                return Err(GltfError::ExternalBuffer)
+            }
+        }
+        let mut doc = GltfDocument::default();
+        for mesh in array(self.root, "meshes").unwrap_or(&[]) {
+            doc.meshes.push(self.read_mesh(mesh)?);
+        }
+        for material in array(self.root, "materials").unwrap_or(&[]) {
+            doc.materials.push(self.read_material(material));
+        }
+        for image in array(self.root, "images").unwrap_or(&[]) {
+            doc.images.push(self.read_image(image));
+        }
+
+        let nodes = array(self.root, "nodes").unwrap_or(&[]);
+        let scene = usize_of(self.root, "scene").unwrap_or(0);
+        let roots: Vec<usize> = if let Some(scene) = array(self.root, "scenes").and_then( | scenes | scenes.get(scene)) {
+            indices(scene, "nodes")
+        }
+        else {
+            // without scenes every node that is not a child is a root
+            let mut is_child = vec![false; nodes.len()];
+            for node in nodes {
+                for child in indices(node, "children") {
+                    if let Some(flag) = is_child.get_mut(child) {*flag = true}
+                }
+            }
+            (0..nodes.len()).filter( | i | !is_child[*i]).collect()
+        };
+        let mut stack: Vec<(usize, Mat4, usize)> = roots.into_iter().map( | node | (node, Mat4::identity(), 0)).collect();
+        while let Some((index, parent, depth)) = stack.pop() {
+            let node = nodes.get(index).ok_or(GltfError::Invalid("node index"))?;
+            // guards against cycles in broken files
+            if depth > 64 {
+                return Err(GltfError::Invalid("node hierarchy"))
+            }
+            let transform = Mat4::mul(&node_transform(node), &parent);
+            if let Some(mesh) = usize_of(node, "mesh") {
+                if mesh < doc.meshes.len() {
+                    doc.instances.push(GltfInstance {mesh, transform});
+                }
+            }
+            for child in indices(node, "children") {
+                stack.push((child, transform, depth + 1));
+            }
+        }
+        Ok(doc)
+    }
+
+    fn read_mesh(&self, mesh: &JsonValue) -> Result<GltfMesh, GltfError> {
+        let mut out = GltfMesh {
+            name: get(mesh, "name").and_then(string),
+            primitives: Vec::new()
+        };
+        for primitive in array(mesh, "primitives").ok_or(GltfError::Invalid("mesh primitives"))? {
+            // only triangle lists are supported
+            if usize_of(primitive, "mode").unwrap_or(4) != 4 {
+                continue;
+            }
+            let attributes = get(primitive, "attributes").ok_or(GltfError::Invalid("primitive attributes"))?;
+            let position = usize_of(attributes, "POSITION").ok_or(GltfError::Invalid("POSITION attribute"))?;
+            let (positions, _) = self.read_accessor(position)?;
+            let positions: Vec<Vec3> = positions.chunks_exact(3).map( | v | vec3(v[0] as f32, v[1] as f32, v[2] as f32)).collect();
+            let normals = if let Some(normal) = usize_of(attributes, "NORMAL") {
+                let (normals, _) = self.read_accessor(normal)?;
+                normals.chunks_exact(3).map( | v | vec3(v[0] as f32, v[1] as f32, v[2] as f32)).collect()
+            }
+            else {
+                Vec::new()
+            };
+            let uvs = if let Some(uv) = usize_of(attributes, "TEXCOORD_0") {
+                let (uvs, _) = self.read_accessor(uv)?;
+                uvs.chunks_exact(2).map( | v | vec2(v[0] as f32, v[1] as f32)).collect()
+            }
+            else {
+                Vec::new()
+            };
+            let indices = if let Some(indices) = usize_of(primitive, "indices") {
+                let (indices, _) = self.read_accessor(indices)?;
+                let indices: Vec<u32> = indices.into_iter().map( | i | i as u32).collect();
+                if indices.iter().any( | i | *i as usize >= positions.len()) {
+                    return Err(GltfError::Invalid("vertex index"))
+                }
+                indices
+            }
+            else {
+                (0..positions.len() as u32).collect()
+            };
+            out.primitives.push(GltfPrimitive {
+                positions,
+                normals,
+                uvs,
+                indices,
+                material: usize_of(primitive, "material"),
+            });
+        }
+        Ok(out)
+    }
+
+    // returns the components of all elements one after the other, and the components per element
+    fn read_accessor(&self, index: usize) -> Result<(Vec<f64>, usize), GltfError> {
+        let accessor = array(self.root, "accessors")
+            .and_then( | accessors | accessors.get(index))
+            .ok_or(GltfError::Invalid("accessor index"))?;
+        let count = usize_of(accessor, "count").ok_or(GltfError::Invalid("accessor count"))?;
+        let components = match get(accessor, "type").and_then(string).as_deref() {
+            Some("SCALAR") => 1,
+            Some("VEC2") => 2,
+            Some("VEC3") => 3,
+            Some("VEC4") => 4,
+            Some("MAT2") => 4,
+            Some("MAT3") => 9,
+            Some("MAT4") => 16,
+            _ => return Err(GltfError::Invalid("accessor type"))
+        };
+        let component_type = usize_of(accessor, "componentType").ok_or(GltfError::Invalid("accessor componentType"))?;
+        let size = match component_type {
+            5120 | 5121 => 1,
+            5122 | 5123 => 2,
+            5125 | 5126 => 4,
+            _ => return Err(GltfError::Invalid("accessor componentType"))
+        };
+        let normalized = matches!(get(accessor, "normalized"), Some(JsonValue::Bool(true)));
+        let values = count.checked_mul(components).ok_or(GltfError::Invalid("accessor count"))?;
+        // accessors without a buffer view (sparse ones, which aren't supported) are all zeros
+        let view_index = match usize_of(accessor, "bufferView") {
+            Some(view_index) => view_index,
+            None => {
+                if values > MAX_ZEROED_VALUES {
+                    return Err(GltfError::Invalid("accessor count"))
+                }
+                return Ok((vec![0.0; values], components))
+            }
+        };
+        let (view, stride) = self.buffer_view(view_index)?;
+        let stride = stride.unwrap_or(size * components);
+        let offset = usize_of(accessor, "byteOffset").unwrap_or(0);
+        // the last element has to fit in the view before anything is allocated for the count
+        if count > 0 {
+            let end = (count - 1).checked_mul(stride)
+                .and_then( | last | last.checked_add(offset)?.checked_add(components * size))
+                .ok_or(GltfError::Truncated)?;
+            if end > view.len() {
+                return Err(GltfError::Truncated)
+            }
+        }
+        let mut out = Vec::with_capacity(values);
+        for i in 0..count {
+            for c in 0..components {
+                let at = offset + i * stride + c * size;
+                let bytes = view.get(at..at + size).ok_or(GltfError::Truncated)?;
+                let value = match component_type {
+                    5120 => {
+                        let v = bytes[0] as i8 as f64;
+                        if normalized {(v / 127.0).max(-1.0)} else {v}
+                    }
+                    5121 => {
+                        let v = bytes[0] as f64;
+                        if normalized {v / 255.0} else {v}
+                    }
+                    5122 => {
+                        let v = i16::from_le_bytes([bytes[0], bytes[1]]) as f64;
+                        if normalized {(v / 32767.0).max(-1.0)} else {v}
+                    }
+                    5123 => {
+                        let v = u16::from_le_bytes([bytes[0], bytes[1]]) as f64;
+                        if normalized {v / 65535.0} else {v}
+                    }
+                    5125 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+                    _ => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+                };
+                out.push(value);
+            }
+        }
+        Ok((out, components))
+    }
+
+    fn buffer_view(&self, index: usize) -> Result<(&'a [u8], Option<usize>), GltfError> {
+        let view = array(self.root, "bufferViews")
+            .and_then( | views | views.get(index))
+            .ok_or(GltfError::Invalid("bufferView index"))?;
+        if usize_of(view, "buffer").unwrap_or(0) != 0 {
+            return Err(GltfError::ExternalBuffer)
+        }
+        let offset = usize_of(view, "byteOffset").unwrap_or(0);
+        let length = usize_of(view, "byteLength").ok_or(GltfError::Invalid("bufferView byteLength"))?;
+        let end = offset.checked_add(length).ok_or(GltfError::Truncated)?;
+        let data = self.bin.get(offset..end).ok_or(GltfError::Truncated)?;
+        Ok((data, usize_of(view, "byteStride")))
+    }
+
+    fn read_material(&self, material: &JsonValue) -> GltfMaterial {
+        let mut out = GltfMaterial::default();
+        if let Some(pbr) = get(material, "pbrMetallicRoughness") {
+            let factor = numbers(pbr, "baseColorFactor");
+            if factor.len() == 4 {
+                out.base_color = vec4(factor[0] as f32, factor[1] as f32, factor[2] as f32, factor[3] as f32);
+            }
+            if let Some(metallic) = get(pbr, "metallicFactor").and_then(number) {
+                out.metallic = metallic as f32;
+            }
+            if let Some(roughness) = get(pbr, "roughnessFactor").and_then(number) {
+                out.roughness = roughness as f32;
+            }
+            out.base_color_image = get(pbr, "baseColorTexture")
+                .and_then( | texture | usize_of(texture, "index"))
+                .and_then( | texture | array(self.root, "textures")?.get(texture))
+                .and_then( | texture | usize_of(texture, "source"));
+        }
+        out.unlit = get(material, "extensions")
+            .and_then( | extensions | get(extensions, "KHR_materials_unlit"))
+            .is_some();
+        out
+    }
+
+    fn read_image(&self, image: &JsonValue) -> Option<GltfImage> {
+        let view = match usize_of(image, "bufferView") {
+            Some(view) => view,
+            None => {
+                error!("glTF image is not stored in the binary chunk");
+                return None
+            }
+        };
+        let (data, _) = self.buffer_view(view).ok()?;
+        let mime_type = get(image, "mimeType").and_then(string).unwrap_or_default();
+        let decoded = if mime_type == "image/png" || data.starts_with(&[0x89, b'P', b'N', b'G']) {
+            let mut decoder = PngDecoder::new(data);
+            decoder.decode().ok().and_then( | image | image.u8()).and_then( | pixels | {
+                let (width, height) = decoder.get_dimensions()?;
+                Some((pixels, width, height))
+            })
+        }
+        else if mime_type == "image/jpeg" || data.starts_with(&[0xff, 0xd8]) {
+            let mut decoder = JpegDecoder::new(data);
+            decoder.decode().ok().and_then( | pixels | {
+                let info = decoder.info()?;
+                Some((pixels, info.width as usize, info.height as usize))
+            })
+        }
+        else {
+            None
+        };
+        let image = decoded.and_then( | (pixels, width, height) | {
+            Some(GltfImage {width, height, data: png_to_bgra(&pixels, width, height)?})
+        });
+        if image.is_none() {
+            error!("Cannot decode glTF image of type {}", mime_type);
+        }
+        image
+    }
+}
+
+// the local transform of a node, either a matrix or translation, rotation and scale
+fn node_transform(node: &JsonValue) -> Mat4 {
+    let matrix = numbers(node, "matrix");
+    if matrix.len() == 16 {
+        let mut v = [0.0; 16];
+        for (i, value) in matrix.iter().enumerate() {
+            v[i] = *value as f32;
+        }
+        return Mat4 {v}
+    }
+    let t = numbers(node, "translation");
+    let r = numbers(node, "rotation");
+    let s = numbers(node, "scale");
+    let mut transform = Mat4::identity();
+    if s.len() == 3 {
+        transform.v[0] = s[0] as f32;
+        transform.v[5] = s[1] as f32;
+        transform.v[10] = s[2] as f32;
+    }
+    if r.len() == 4 {
+        let (x, y, z, w) = (r[0] as f32, r[1] as f32, r[2] as f32, r[3] as f32);
+        let rotation = Mat4 {v: [
+            1.0 - 2.0 * (y * y + z * z),
+            2.0 * (x * y + z * w),
+            2.0 * (x * z - y * w),
+            0.0,
+            2.0 * (x * y - z * w),
+            1.0 - 2.0 * (x * x + z * z),
+            2.0 * (y * z + x * w),
+            0.0,
+            2.0 * (x * z + y * w),
+            2.0 * (y * z - x * w),
+            1.0 - 2.0 * (x * x + y * y),
+            0.0,
+            0.0,
+            0.0,
+            0.0,
+            1.0
+        ]};
+        transform = Mat4::mul(&transform, &rotation);
+    }
+    if t.len() == 3 {
+        transform = Mat4::mul(&transform, &Mat4::translation(t[0] as f32, t[1] as f32, t[2] as f32));
+    }
+    transform
+}
+
+fn get<'a>(value: &'a JsonValue, key: &str) -> Option<&'a JsonValue> {
+    match value {
+        JsonValue::Object(object) => object.get(key),
+        _ => None
+    }
+}
+
+fn array<'a>(value: &'a JsonValue, key: &str) -> Option<&'a [JsonValue]> {
+    match get(value, key) {
+        Some(JsonValue::Array(array)) => Some(array),
+        _ => None
+    }
+}
+
+fn number(value: &JsonValue) -> Option<f64> {
+    match value {
+        JsonValue::U64(v) => Some(*v as f64),
+        JsonValue::I64(v) => Some(*v as f64),
+        JsonValue::F64(v) => Some(*v),
+        _ => None
+    }
+}
+
+fn string(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::String(s) => Some(s.clone()),
+        _ => None
+    }
+}
+
+fn usize_of(value: &JsonValue, key: &str) -> Option<usize> {
+    match get(value, key) {
+        Some(JsonValue::U64(v)) => usize::try_from(*v).ok(),
+        _ => None
+    }
+}
+
+fn numbers(value: &JsonValue, key: &str) -> Vec<f64> {
+    array(value, key).map( | array | array.iter().filter_map(number).collect()).unwrap_or_default()
+}
+
+fn indices(value: &JsonValue, key: &str) -> Vec<usize> {
+    array(value, key).map( | array | array.iter().filter_map( | v | match v {
+        JsonValue::U64(v) => Some(*v as usize),
+        _ => None
+    }).collect()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn glb(json: &str, bin: &[u8]) -> Vec<u8> {
+        let mut json = json.as_bytes().to_vec();
+        json.resize((json.len() + 3) & !3, b' ');
+        let mut bin = bin.to_vec();
+        bin.resize((bin.len() + 3) & !3, 0);
+        let length = 12 + 8 + json.len() + 8 + bin.len();
+        let mut data = Vec::new();
+        for value in [GLB_MAGIC, 2, length as u32, json.len() as u32, CHUNK_JSON] {
+            data.extend(value.to_le_bytes());
+        }
+        data.extend(json);
+        data.extend((bin.len() as u32).to_le_bytes());
+        data.extend(CHUNK_BIN.to_le_bytes());
+        data.extend(bin);
+        data
+    }
+    
+    // A single triangle, with the given count for its position accessor and the given indices.
+    fn triangle(position_count: u64, indices: [u16; 3]) -> Vec<u8> {
+        let mut bin = Vec::new();
+        for v in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+            bin.extend(v.to_le_bytes());
+        }
+        for i in indices {
+            bin.extend(i.to_le_bytes());
+        }
+        let json = format!(r#"{{
+            "asset": {{"version": "2.0"}},
+            "scene": 0,
+            "scenes": [{{"nodes": [0]}}],
+            "nodes": [{{"mesh": 0, "translation": [1, 2, 3]}}],
+            "meshes": [{{"name": "triangle", "primitives": [{{"attributes": {{"POSITION": 0}}, "indices": 1}}]}}],
+            "accessors": [
+                {{"bufferView": 0, "componentType": 5126, "count": {position_count}, "type": "VEC3"}},
+                {{"bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR"}}
+            ],
+            "bufferViews": [
+                {{"buffer": 0, "byteOffset": 0, "byteLength": 36}},
+                {{"buffer": 0, "byteOffset": 36, "byteLength": 6}}
+            ],
+            "buffers": [{{"byteLength": 44}}]
+        }}"#);
+        glb(&json, &bin)
+    }
+    
+    #[test]
+    fn reads_a_triangle() {
+        let doc = parse_glb(&triangle(3, [0, 1, 2])).unwrap();
+        assert_eq!(doc.meshes.len(), 1);
+        assert_eq!(doc.meshes[0].name.as_deref(), Some("triangle"));
+        let primitive = &doc.meshes[0].primitives[0];
+        assert_eq!(primitive.positions, [vec3(0.0, 0.0, 0.0), vec3(1.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)]);
+        assert_eq!(primitive.indices, [0, 1, 2]);
+        assert!(primitive.normals.is_empty() && primitive.uvs.is_empty());
+        assert_eq!(doc.instances.len(), 1);
+        assert_eq!(doc.instances[0].mesh, 0);
+        assert_eq!(doc.instances[0].transform.transform_vec4(vec4(0.0, 0.0, 0.0, 1.0)), vec4(1.0, 2.0, 3.0, 1.0));
+    }
+    
+    #[test]
+    fn rejects_a_truncated_chunk() {
+        let data = triangle(3, [0, 1, 2]);
+        assert!(matches!(parse_glb(&data[..data.len() - 8]), Err(GltfError::Truncated)));
+        assert!(matches!(parse_glb(&data[..8]), Err(GltfError::NotGlb)));
+    }
+    
+    #[test]
+    fn rejects_an_out_of_range_index() {
+        assert!(matches!(parse_glb(&triangle(3, [0, 1, 3])), Err(GltfError::Invalid("vertex index"))));
+    }
+    
+    #[test]
+    fn rejects_an_oversized_count() {
+        // more elements than fit in the buffer view
+        assert!(matches!(parse_glb(&triangle(4, [0, 1, 2])), Err(GltfError::Truncated)));
+        assert!(matches!(parse_glb(&triangle(1 << 40, [0, 1, 2])), Err(GltfError::Truncated)));
+        // so many components that counting them overflows
+        assert!(matches!(parse_glb(&triangle(u64::MAX / 2, [0, 1, 2])), Err(GltfError::Invalid("accessor count"))));
+        // and a count without a buffer view to bound it
+        let json = r#"{
+            "meshes": [{"primitives": [{"attributes": {"POSITION": 0}}]}],
+            "accessors": [{"componentType": 5126, "count": 1000000000, "type": "VEC3"}]
+        }"#;
+        assert!(matches!(parse_glb(&glb(json, &[])), Err(GltfError::Invalid("accessor count"))));
+    }
+}
@@ -16,9 +16,11 @@ pub use {
         draw_list_2d::{ManyInstances, DrawList2d, RedrawingApi},
         geometry::GeometryQuad2D,
         makepad_vector::trapezoidator::Trapezoidator,
-        makepad_vector::geometry::{AffineTransformation, Transform, Vector, Point},
+        makepad_vector::geometry::{AffineTransformation, Transform, Transformation, Vector, Point, Trapezoid},
         makepad_vector::internal_iter::*,
-        makepad_vector::path::{PathIterator, PathCommand},
+        makepad_vector::path::{LinePath, PathIterator, PathCommand},
+        makepad_vector::trapezoidator::FillRule,
+        svg::{parse_svg, parse_svg_path, concat_transforms, transform_scale, SvgShape, SvgPaint, SvgGradientKind},
     }
 };
 
@@ -37,24 +39,9 @@ pub struct CxIconEntry {
     args: CxIconArgs,
 }
 
-struct CxIconPathCommands {
+struct CxIconPath {
     bounds: Rect,
-    path: Vec<PathCommand>
-}
-
-impl<'a> InternalIterator for &CxIconPathCommands {
-    type Item = PathCommand;
-    fn for_each<F>(self, f: &mut F) -> bool
-    where
-    F: FnMut(PathCommand) -> bool,
-    {
-        for item in &self.path {
-            if !f(item.clone()) {
-                return false
-            }
-        }
-        true
-    }
+    shapes: Vec<SvgShape>
 }
 
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
@@ -67,7 +54,7 @@ pub struct CxIconAtlas {
     pub texture: Texture,
     pub clear_buffer: bool,
     svg_deps: HashMap<String, CxIconPathHash>,
-    paths: HashMap<CxIconPathHash, Vec<CxIconPathCommands>>,
+    paths: HashMap<CxIconPathHash, CxIconPath>,
     entries: HashMap<CxIconEntryHash, CxIconEntry>,
     alloc: CxIconAtlasAlloc
 }
@@ -88,6 +75,8 @@ pub struct CxIconArgs {
     pub translate: DVec2,
     pub subpixel: DVec2,
     pub scale: f64,
+    // draw the shapes with their own paint instead of as a single coverage mask
+    pub multicolor: bool,
 }
 
 impl CxIconArgs {
@@ -101,6 +90,7 @@ impl CxIconArgs {
             .bytes_append(&self.scale.to_be_bytes())
             .bytes_append(&self.size.x.to_be_bytes())
             .bytes_append(&self.size.y.to_be_bytes())
+            .bytes_append(&[self.multicolor as u8])
     }
 }
 
@@ -125,51 +115,12 @@ impl CxIconAtlas {
     pub fn parse_and_cache_path(&mut self, path_hash: CxIconPathHash, path: &[u8]) -> Option<(CxIconPathHash, Rect)> {
         match parse_svg_path(path) {
             Ok(path) => {
-                let mut min = dvec2(f64::INFINITY, f64::INFINITY);
-                let mut max = dvec2(-f64::INFINITY, -f64::INFINITY);
-                fn bound(p: &Point, min: &mut DVec2, max: &mut DVec2) {
-                    if p.x < min.x {min.x = p.x}
-                    if p.y < min.y {min.y = p.y}
-                    if p.x > max.x {max.x = p.x}
-                    if p.y > max.y {max.y = p.y}
-                }
-                for cmd in &path {
-                    match cmd {
-                        PathCommand::MoveTo(p) => {bound(p, &mut min, &mut max)},
-                        PathCommand::LineTo(p) => {bound(p, &mut min, &mut max)},
-                        PathCommand::ArcTo(e, r, _, _, _) => {
-                            // TODO: this is pretty rough
-                            bound(&Point{x: e.x + r.x, y: e.y + r.y}, &mut min, &mut max);
-                            bound(&Point{x: e.x - r.x, y: e.y - r.y}, &mut min, &mut max);
-                        },
-                        PathCommand::QuadraticTo(p1, p) => {
-                            bound(p1, &mut min, &mut max);
-                            bound(p, &mut min, &mut max);
-                        },
-                        PathCommand::CubicTo(p1, p2, p) => {
-                            bound(p1, &mut min, &mut max);
-                            bound(p2, &mut min, &mut max);
-                            bound(p, &mut min, &mut max);
-                        },
-                        PathCommand::Close => ()
-                    }
-                }
-                let bounds = Rect {pos: min, size: max - min};
-                if let Some( foundpath) = self.paths.get_mut(&path_hash) {
-                    foundpath.push(CxIconPathCommands {
-                        bounds,
-                        path
-                    })
-                }
-                else
-                {
-
-
-                    self.paths.insert(path_hash,vec![ CxIconPathCommands {
-                        bounds,
-                        path
-                    }]);
-                }
+                let shape = SvgShape::new_filled(path, vec4(1.0, 1.0, 1.0, 1.0));
+                let bounds = shape.bounds()?;
+                self.paths.insert(path_hash, CxIconPath {
+                    bounds,
+                    shapes: vec![shape]
+                });
                 return Some((path_hash, bounds));
             }
             Err(e) => {
@@ -185,67 +136,32 @@ impl CxIconAtlas {
             // alright so. lets see if we have a path hash
             if let Some(path_hash) = self.svg_deps.get(svg_dep.as_str()) {
                 if let Some(path) = self.paths.get(&path_hash) {
-                    let mut bounds:Rect = path[0].bounds;
-                    for i in 1..path.len(){
-                        bounds = bounds.hull(path[i].bounds);
-                    }
-                    return Some((*path_hash, bounds))
+                    return Some((*path_hash, path.bounds))
                 }
                 return None
             }
             let path_hash = CxIconPathHash(LiveId(self.svg_deps.len() as u64));
             self.svg_deps.insert(svg_dep.as_str().to_string(), path_hash);
-            // lets parse the path range out of the svg file
+            // lets parse the shapes out of the svg file
             match cx.get_dependency(svg_dep.as_str()) {
                 Ok(data)=>{        
-
-                    let mut errors = Some(Vec::new());
                     let svg_string = std::str::from_utf8(&data).unwrap();
-                    let  doc = parse_html(svg_string, &mut errors);
-
-                    if errors.as_ref().unwrap().len()>0{
-                        log!("SVG parser returned errors {:?}", errors)
-                    }
-                    let mut node = doc.new_walker();
+                    let doc = parse_svg(svg_string);
                     
-                    while !node.done(){
-                        match node.open_tag_lc() 
-                        {
-                            some_id!(g)=>{
-                                // do something with clipping/transform groups here.
-                            }                            
-                            some_id!(path)=>{
-                                self.parse_and_cache_path(path_hash, node.find_attr_lc(live_id!(d)).unwrap().as_bytes());   
-                                                        }         
-                                                            
-                            _=>()
-                        }
-                        match node.close_tag_lc() 
-                        {
-                            some_id!(g)=>
-                            {
-                                
-                            }
-                            _=>()
-                        }
-                        node.walk();
-                    }
-                    
-                   
-                    if let Some(path) = self.paths.get(&path_hash) {
-                        let mut bounds:Rect = path[0].bounds;             
-                        for i in 1..path.len(){
-                            bounds = bounds.hull(path[i].bounds);
-                        }
+                    if let Some(bounds) = doc.bounds().filter(|_| !doc.shapes.is_empty()) {
+                        self.paths.insert(path_hash, CxIconPath {
+                            bounds,
+                            shapes: doc.shapes
+                        });
                         return Some((path_hash, bounds));              
                     }
 
-                    println!("No SVG path tag found in svg file {}",path_str);
+                    error!("No SVG shapes found in svg file {}", path_str);
                     return None
                     
                 }
-                Err(_err)=>{
-                    println!("Error in SVG file {}: {}",path_str, _err);
+                Err(err)=>{
+                    error!("Error in SVG file {}: {}", path_str, err);
                     return None
                 }
             }
@@ -255,11 +171,7 @@ impl CxIconAtlas {
         }
         let path_hash = CxIconPathHash(LiveId(Rc::as_ptr(path_str) as u64));
         if let Some(path) = self.paths.get(&path_hash) {
-            let mut bounds:Rect = path[0].bounds;
-            for i in 1..path.len(){
-                bounds = bounds.hull(path[i].bounds);
-            }
-            return Some((path_hash,bounds))
+            return Some((path_hash, path.bounds))
         }
         self.parse_and_cache_path(path_hash, path_str.as_str().as_bytes())
     }
@@ -335,38 +247,76 @@ impl CxIconAtlas {
 }
 
 
+// the number of gradient stops the vector shader interpolates between
+const MAX_GRADIENT_STOPS: usize = 4;
+
 impl DrawTrapezoidVector {
     // atlas drawing function used by CxAfterDraw
-    fn draw_vector(&mut self, entry: &CxIconEntry, path: &CxIconPathCommands, many: &mut ManyInstances) {
-        let trapezoids = {
-            let mut trapezoids = Vec::new();
-            //log_str(&format!("Serializing char {} {} {} {}", glyphtc.tx1 , cx.fonts_atlas.texture_size.x ,todo.subpixel_x_fract ,atlas_page.dpi_factor));
-            let trapezoidate = self.trapezoidator.trapezoidate(
-                path.map({
-                    //log!("{:?} {:?}", entry.args, entry.pos);
-                    move | cmd | {
-                        let cmd = cmd.transform(
-                            &AffineTransformation::identity()
-                                .translate(Vector::new(entry.args.translate.x, entry.args.translate.y))
-                                .uniform_scale(entry.args.scale)
-                                .translate(Vector::new(entry.pos.x + entry.args.subpixel.x, entry.pos.y + entry.args.subpixel.y))
-                        );
-                        cmd
+    fn draw_vector(&mut self, entry: &CxIconEntry, shape: &SvgShape, many: &mut ManyInstances) {
+        let transform = concat_transforms(
+            &shape.transform,
+            &AffineTransformation::identity()
+                .translate(Vector::new(entry.args.translate.x, entry.args.translate.y))
+                .uniform_scale(entry.args.scale)
+                .translate(Vector::new(entry.pos.x + entry.args.subpixel.x, entry.pos.y + entry.args.subpixel.y))
+        );
+        // curves are flattened in the user space of the shape, so scale the tolerance with it
+        let epsilon = entry.args.linearize / transform_scale(&transform).max(1e-6);
+        
+        if let Some(fill) = &shape.fill {
+            let line_path: LinePath = shape.path.iter().copied().linearize(epsilon).collect();
+            self.draw_line_path(&line_path.transform(&transform), shape.fill_rule, fill, &transform, entry.args.multicolor, many);
+        }
+        if let Some((stroke, style)) = &shape.stroke {
+            let line_path = self.stroker.stroke(shape.path.iter().copied().linearize(epsilon), style, epsilon);
+            self.draw_line_path(&line_path.transform(&transform), FillRule::NonZero, stroke, &transform, entry.args.multicolor, many);
+        }
+    }
+    
+    fn draw_line_path(&mut self, line_path: &LinePath, fill_rule: FillRule, paint: &SvgPaint, transform: &AffineTransformation, multicolor: bool, many: &mut ManyInstances) {
+        self.trapezoidator.set_fill_rule(fill_rule);
+        let mut trapezoids: Vec<Trapezoid> = Vec::new();
+        if let Some(trapezoidate) = self.trapezoidator.trapezoidate(line_path.commands()) {
+            trapezoids.extend_from_internal_iter(trapezoidate);
+        }
+        
+        // single color icons only keep the coverage, which the icon shader tints
+        let color = |c: Vec4| if multicolor {c} else {vec4(1.0, 1.0, 1.0, c.w)};
+        match paint {
+            SvgPaint::Color(c) => {
+                self.color = color(*c);
+                self.gradient_kind = 0.0;
+            }
+            SvgPaint::Gradient(gradient) => {
+                let stops = gradient.fixed_stops(MAX_GRADIENT_STOPS);
+                self.color = color(stops[0].1);
+                self.gradient_color = color(stops[1].1);
+                self.gradient_color2 = color(stops[2].1);
+                self.gradient_color3 = color(stops[3].1);
+                self.gradient_offsets = vec4(stops[0].0 as f32, stops[1].0 as f32, stops[2].0 as f32, stops[3].0 as f32);
+                let to_atlas = |p: DVec2| {
+                    let p = transform.transform_point(Point::new(p.x, p.y));
+                    vec2(p.x as f32, p.y as f32)
+                };
+                match gradient.kind {
+                    SvgGradientKind::Linear {start, end} => {
+                        let (start, end) = (to_atlas(start), to_atlas(end));
+                        self.gradient = vec4(start.x, start.y, end.x, end.y);
+                        self.gradient_kind = 1.0;
                     }
-                }).linearize(entry.args.linearize)
-            );
-            if let Some(trapezoidate) = trapezoidate {
-                trapezoids.extend_from_internal_iter(
-                    trapezoidate
-                );
+                    SvgGradientKind::Radial {center, radius} => {
+                        let center = to_atlas(center);
+                        self.gradient = vec4(center.x, center.y, (radius * transform_scale(transform)) as f32, 0.0);
+                        self.gradient_kind = 2.0;
+                    }
+                }
             }
-            trapezoids
-        };
+        }
         
         for trapezoid in trapezoids {
             self.a_xs = Vec2 {x: trapezoid.xs[0], y: trapezoid.xs[1]};
             self.a_ys = Vec4 {x: trapezoid.ys[0], y: trapezoid.ys[1], z: trapezoid.ys[2], w: trapezoid.ys[3]};
-            self.chan = 0.0 as f32;
+            self.chan = 4.0;
             many.instances.extend_from_slice(self.draw_vars.as_slice());
         }
     }
@@ -455,8 +405,8 @@ impl<'a> Cx2d<'a> {
                 for todo in atlas_todo {
                     let entry = atlas.entries.get(&todo).unwrap();
                     let path = atlas.paths.get(&entry.path_hash).unwrap();
-                    for i in 0..path.len(){                        
-                        draw_atlas.draw_trapezoid.draw_vector(entry, &path[i], &mut many);
+                    for shape in &path.shapes {
+                        draw_atlas.draw_trapezoid.draw_vector(entry, shape, &mut many);
                     }
                     
                 }
@@ -470,279 +420,3 @@ impl<'a> Cx2d<'a> {
     
     
 }
-
-fn parse_svg_path(path: &[u8]) -> Result<Vec<PathCommand>, String> {
-    #[derive(Debug)]
-    enum Cmd {
-        Unknown,
-        Move(bool),
-        Hor(bool),
-        Vert(bool),
-        Line(bool),
-        Arc(bool),
-        Cubic(bool),
-        Quadratic(bool),
-        Close
-    }
-    impl Default for Cmd {fn default() -> Self {Self::Unknown}}
-    
-    #[derive(Default)]
-    struct ParseState {
-        cmd: Cmd,
-        expect_nums: usize,
-        chain: bool,
-        nums: [f64; 7],
-        num_count: usize,
-        last_pt: Point,
-        first_pt: Point,
-        out: Vec<PathCommand>,
-        num_state: Option<NumState>
-    }
-    
-    #[derive(Debug)]
-    struct NumState {
-        num: f64,
-        mul: f64,
-        has_dot: bool,
-    }
-    
-    impl NumState {
-        fn new_pos(v: f64) -> Self {Self {num: v, mul: 1.0, has_dot: false}}
-        fn new_min() -> Self {Self {num: 0.0, mul: -1.0, has_dot: false}}
-        fn finalize(self) -> f64 {self.num * self.mul}
-        fn add_digit(&mut self, digit: f64) {
-            self.num *= 10.0;
-            self.num += digit;
-            if self.has_dot {
-                self.mul *= 0.1;
-            }
-        }
-    }
-    
-    impl ParseState {
-        fn next_cmd(&mut self, cmd: Cmd) -> Result<(), String> {
-            self.finalize_cmd() ?;
-            self.chain = false;
-            self.expect_nums = match cmd {
-                Cmd::Unknown => panic!(),
-                Cmd::Move(_) => 2,
-                Cmd::Hor(_) => 1,
-                Cmd::Vert(_) => 1,
-                Cmd::Line(_) => 2,
-                Cmd::Cubic(_) => 6,
-                Cmd::Arc(_) => 7,
-                Cmd::Quadratic(_) => 4,
-                Cmd::Close => 0
-            };
-            self.cmd = cmd;
-            Ok(())
-        }
-        
-        fn add_min(&mut self) -> Result<(), String> {
-            if self.num_state.is_some() {
-                self.finalize_num();
-            }
-            if self.expect_nums == self.num_count {
-                self.finalize_cmd() ?;
-            }
-            if self.expect_nums == 0 {
-                return Err(format!("Unexpected minus"));
-            }
-            self.num_state = Some(NumState::new_min());
-            Ok(())
-        }
-        
-        fn add_digit(&mut self, digit: f64) -> Result<(), String> {
-            if let Some(num_state) = &mut self.num_state {
-                num_state.add_digit(digit);
-            }
-            else {
-                if self.expect_nums == self.num_count {
-                    self.finalize_cmd() ?;
-                }
-                if self.expect_nums == 0 {
-                    return Err(format!("Unexpected digit"));
-                }
-                self.num_state = Some(NumState::new_pos(digit))
-            }
-            Ok(())
-        }
-        
-        fn add_dot(&mut self) -> Result<(), String> {
-            if let Some(num_state) = &mut self.num_state {
-                if num_state.has_dot {
-                    self.finalize_num();
-                    self.add_digit(0.0) ?;
-                    self.add_dot() ?;
-                    return Ok(());
-                }
-                num_state.has_dot = true;
-            }
-            else {
-                self.add_digit(0.0) ?;
-                self.add_dot() ?;
-            }
-            Ok(())
-        }
-        
-        fn finalize_num(&mut self) {
-            if let Some(num_state) = self.num_state.take() {
-                self.nums[self.num_count] = num_state.finalize();
-                self.num_count += 1;
-            }
-        }
-        
-        fn whitespace(&mut self) -> Result<(), String> {
-            self.finalize_num();
-            if self.expect_nums == self.num_count {
-                self.finalize_cmd() ?;
-            }
-            Ok(())
-        }
-        
-        fn finalize_cmd(&mut self) -> Result<(), String> {
-            self.finalize_num();
-            if self.chain && self.num_count == 0 {
-                return Ok(())
-            }
-            if self.expect_nums != self.num_count {
-                return Err(format!("SVG Path command {:?} expected {} points, got {}", self.cmd, self.expect_nums, self.num_count));
-            }
-            match self.cmd {
-                Cmd::Unknown => (),
-                Cmd::Move(abs) => {
-                    
-                    if abs {
-                        self.last_pt = Point {x: self.nums[0], y: self.nums[1]};
-                    }
-                    else {
-                        self.last_pt += Vector {x: self.nums[0], y: self.nums[1]};
-                    }
-                    self.first_pt = self.last_pt;
-                    self.out.push(PathCommand::MoveTo(self.last_pt));
-                },
-                Cmd::Hor(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[0], y: self.last_pt.y};
-                    }
-                    else {
-                        self.last_pt += Vector {x: self.nums[0], y: 0.0};
-                    }
-                    self.out.push(PathCommand::LineTo(self.last_pt));
-                }
-                Cmd::Vert(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.last_pt.x, y: self.nums[0]};
-                    }
-                    else {
-                        self.last_pt += Vector {x: 0.0, y: self.nums[0]};
-                    }
-                    self.out.push(PathCommand::LineTo(self.last_pt));
-                }
-                Cmd::Line(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[0], y: self.nums[1]};
-                    }
-                    else {
-                        self.last_pt += Vector {x: self.nums[0], y: self.nums[1]};
-                    }
-                    self.out.push(PathCommand::LineTo(self.last_pt));
-                },
-                Cmd::Cubic(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[4], y: self.nums[5]};
-                        self.out.push(PathCommand::CubicTo(
-                            Point {x: self.nums[0], y: self.nums[1]},
-                            Point {x: self.nums[2], y: self.nums[3]},
-                            self.last_pt,
-                        ));
-                    } else {
-                        self.out.push(PathCommand::CubicTo(
-                            self.last_pt + Vector {x: self.nums[0], y: self.nums[1]},
-                            self.last_pt + Vector {x: self.nums[2], y: self.nums[3]},
-                            self.last_pt + Vector {x: self.nums[4], y: self.nums[5]},
-                        ));
-                        self.last_pt += Vector {x: self.nums[4], y: self.nums[5]};
-                    }
-                },
-                Cmd::Arc(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[5], y: self.nums[6]};
-                        self.out.push(PathCommand::ArcTo(
-                            self.last_pt,
-                            Point {x: self.nums[0], y: self.nums[1]},
-                            self.nums[2],
-                            self.nums[3] != 0.0,
-                            self.nums[4] != 0.0,
-                        ));
-                    }
-                    else {
-                        self.out.push(PathCommand::ArcTo(
-                            self.last_pt + Vector {x: self.nums[5], y: self.nums[6]},
-                            Point {x: self.nums[0], y: self.nums[1]},
-                            self.nums[2],
-                            self.nums[3] != 0.0,
-                            self.nums[4] != 0.0,
-                        ));
-                        self.last_pt += Vector {x: self.nums[5], y: self.nums[6]};
-                    }
-                },
-                Cmd::Quadratic(abs) => {
-                    if abs {
-                        self.last_pt = Point {x: self.nums[2], y: self.nums[3]};
-                        self.out.push(PathCommand::QuadraticTo(
-                            Point {x: self.nums[0], y: self.nums[1]},
-                            self.last_pt
-                        ));
-                    }
-                    else {
-                        self.out.push(PathCommand::QuadraticTo(
-                            self.last_pt + Vector {x: self.nums[0], y: self.nums[1]},
-                            self.last_pt + Vector {x: self.nums[2], y: self.nums[3]},
-                        ));
-                        self.last_pt += Vector {x: self.nums[2], y: self.nums[3]};
-                    }
-                }
-                Cmd::Close => {
-                    self.last_pt = self.first_pt;
-                    self.out.push(PathCommand::Close);
-                }
-            }
-            self.num_count = 0;
-            self.chain = true;
-            Ok(())
-        }
-    }
-    
-    let mut state = ParseState::default();
-    
-    for i in 0..path.len() {
-        match path[i] {
-            b'M' => state.next_cmd(Cmd::Move(true)) ?,
-            b'm' => state.next_cmd(Cmd::Move(false)) ?,
-            b'Q' => state.next_cmd(Cmd::Quadratic(true)) ?,
-            b'q' => state.next_cmd(Cmd::Quadratic(false)) ?,
-            b'C' => state.next_cmd(Cmd::Cubic(true)) ?,
-            b'c' => state.next_cmd(Cmd::Cubic(false)) ?,
-            b'H' => state.next_cmd(Cmd::Hor(true)) ?,
-            b'h' => state.next_cmd(Cmd::Hor(false)) ?,
-            b'V' => state.next_cmd(Cmd::Vert(true)) ?,
-            b'v' => state.next_cmd(Cmd::Vert(false)) ?,
-            b'L' => state.next_cmd(Cmd::Line(true)) ?,
-            b'l' => state.next_cmd(Cmd::Line(false)) ?,
-            b'A' => state.next_cmd(Cmd::Arc(true)) ?,
-            b'a' => state.next_cmd(Cmd::Arc(false)) ?,
-            b'Z' | b'z' => state.next_cmd(Cmd::Close) ?,
-            b'-' => state.add_min() ?,
-            b'0'..=b'9' => state.add_digit((path[i] - b'0') as f64) ?,
-            b'.' => state.add_dot() ?,
-            b',' | b' ' | b'\r' | b'\n' | b'\t' => state.whitespace() ?,
-            x => {
-                return Err(format!("Unexpected character {} - {}", x, x as char))
-            }
-        }
-    }
-    state.finalize_cmd() ?;
-    
-    Ok(state.out)
-}
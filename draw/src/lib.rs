@@ -5,6 +5,7 @@ pub use makepad_vector;
 pub mod match_event;
 pub mod overlay;
 pub mod cx_2d;
+pub mod cx_3d;
 pub mod draw_list_2d;
 pub mod shader;
 pub mod turtle;
@@ -12,7 +13,11 @@ pub mod font_atlas;
 pub mod geometry;
 pub mod nav;
 pub mod icon_atlas;
+pub mod svg;
+pub mod gltf;
 mod owned_font_face;
+mod color_glyph;
+mod system_fonts;
  
 pub use crate::{
     match_event::MatchEvent, 
@@ -35,7 +40,8 @@ pub use crate::{
         NavOrder,
         NavStop,
         NavItem,
-        NavScrollIndex
+        NavScrollIndex,
+        NavGroupIndex
     },
     draw_list_2d::{
         DrawList2d,
@@ -46,6 +52,13 @@ pub use crate::{
     cx_2d::{
         Cx2d
     },
+    cx_3d::{
+        Cx3d,
+        Camera3d,
+        Mesh3d,
+        Material3d,
+        Model3d,
+    },
     shader::{
         //draw_shape::{DrawShape, Shape, Fill},
         draw_icon::DrawIcon,
@@ -53,10 +66,13 @@ pub use crate::{
         draw_line::DrawLine,
         draw_text::DrawText,
         draw_color::DrawColor,
+        draw_path::DrawPath,
+        draw_mesh::DrawMesh3d,
     },
     geometry::{
         GeometryGen,
         GeometryQuad2D,
+        GeometryCube3D,
     },
 };
 
@@ -69,4 +85,6 @@ pub fn live_design(cx: &mut Cx) {
     crate::geometry::geometry_gen::live_design(cx);
     crate::shader::std::live_design(cx);
     crate::shader::draw_trapezoid::live_design(cx);
+    crate::shader::draw_path::live_design(cx);
+    crate::shader::draw_mesh::live_design(cx);
 }
\ No newline at end of file
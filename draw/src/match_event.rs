@@ -47,6 +47,7 @@ pub trait MatchEvent{
     fn handle_draw_2d(&mut self, _cx: &mut Cx2d){}
     fn handle_key_down(&mut self, _cx: &mut Cx, _e:&KeyEvent){}
     fn handle_key_up(&mut self, _cx: &mut Cx, _e:&KeyEvent){}
+    fn handle_command(&mut self, _cx: &mut Cx, _command:LiveId){}
     fn handle_back_pressed(&mut self, _cx: &mut Cx){}
 
     fn match_event(&mut self, cx:&mut Cx, event:&Event){
@@ -70,6 +71,7 @@ pub trait MatchEvent{
             Event::NetworkResponses(e)=>self.handle_network_responses(cx, e),
             Event::KeyDown(e)=>self.handle_key_down(cx, e),
             Event::KeyUp(e)=>self.handle_key_up(cx, e),
+            Event::Command(e)=>self.handle_command(cx, *e),
             Event::BackPressed=>self.handle_back_pressed(cx),
             _=>()
         }
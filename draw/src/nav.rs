@@ -7,7 +7,9 @@ use {
         makepad_platform::Area,
         makepad_platform::DrawListId,
         makepad_platform::Margin,
+        makepad_platform::Rect,
         makepad_platform::Cx,
+        makepad_platform::{AccessRole, AccessProps, AccessNode, AccessTree, access_id},
     }
 };
 
@@ -50,7 +52,10 @@ pub struct NavStop {
     pub role: NavRole,
     pub order: NavOrder,
     pub margin: Margin,
-    pub area: Area
+    pub area: Area,
+    // stops that arent focusable are only there for the accessibility tree
+    pub focusable: bool,
+    pub access: AccessProps,
 }
 
 #[derive(Debug, Clone)]
@@ -58,14 +63,76 @@ pub enum NavItem {
     Child(DrawListId),
     Stop(NavStop),
     BeginScroll(Area),
-    EndScroll(Area)
+    EndScroll(Area),
+    // the stops between begin and end are children of the group in the accessibility tree
+    BeginGroup(NavStop),
+    EndGroup,
 }
 
-#[derive(Debug, Clone)]
-pub enum NavRole {
-    TextInput,
-    DropDown,
-    Slider,
+// the nav stops carry the same roles the accessibility tree uses
+pub type NavRole = AccessRole;
+
+impl CxNavTree {
+    // the bounds of the areas come from the caller, so the tree can be built without drawing
+    fn build_access_tree<F>(&self, root: DrawListId, tree: &mut AccessTree, bounds: F) where F: Fn(Area) -> Rect {
+        fn push_node(tree: &mut AccessTree, bounds: &dyn Fn(Area) -> Rect, parent: usize, role: NavRole, focusable: bool, access: &AccessProps, area: Area) -> usize {
+            tree.push(parent, AccessNode {
+                id: access_id(area),
+                role,
+                props: access.clone(),
+                focusable,
+                focused: false,
+                bounds: bounds(area),
+                area,
+                parent: None,
+                children: Vec::new(),
+            })
+        }
+        fn build_access_tree(nav_tree: &CxNavTree, bounds: &dyn Fn(Area) -> Rect, draw_list_id: DrawListId, tree: &mut AccessTree, parents: &mut Vec<usize>) {
+            if draw_list_id.index() >= nav_tree.nav_lists.len() {
+                return
+            }
+            for nav_item in &nav_tree[draw_list_id].nav_list {
+                let parent = *parents.last().unwrap();
+                match nav_item {
+                    NavItem::Child(draw_list_id) => {
+                        build_access_tree(nav_tree, bounds, *draw_list_id, tree, parents);
+                    }
+                    NavItem::Stop(stop) => if !stop.area.is_empty() {
+                        push_node(tree, bounds, parent, stop.role, stop.focusable, &stop.access, stop.area);
+                    }
+                    NavItem::BeginGroup(stop) => {
+                        let index = push_node(tree, bounds, parent, stop.role, stop.focusable, &stop.access, stop.area);
+                        parents.push(index);
+                    }
+                    NavItem::BeginScroll(area) => {
+                        let index = push_node(tree, bounds, parent, NavRole::Group, false, &AccessProps::default(), *area);
+                        parents.push(index);
+                    }
+                    NavItem::EndGroup | NavItem::EndScroll(_) => if parents.len() > 1 {
+                        parents.pop();
+                    }
+                }
+            }
+        }
+        build_access_tree(self, &bounds, root, tree, &mut vec![0]);
+        // items without a name of their own are read out by the labels inside them
+        for index in 0..tree.nodes.len() {
+            if tree.nodes[index].props.name.is_empty() && tree.nodes[index].role.is_actionable() {
+                let mut names = Vec::new();
+                let mut stack = tree.nodes[index].children.clone();
+                stack.reverse();
+                while let Some(child) = stack.pop() {
+                    let node = &tree.nodes[child];
+                    if node.role == NavRole::Label && !node.props.name.is_empty() {
+                        names.push(node.props.name.clone());
+                    }
+                    stack.extend(node.children.iter().rev());
+                }
+                tree.nodes[index].props.name = names.join(" ");
+            }
+        }
+    }
 }
 
 impl<'a> Cx2d<'a> {
@@ -92,10 +159,13 @@ impl<'a> Cx2d<'a> {
                             return Some(area)
                         }
                     }
-                    NavItem::Stop(stop) => if let Some(area) = callback(cx, stop) {
-                        scroll_stack.push(area);
-                        return Some(area)
+                    NavItem::Stop(stop) | NavItem::BeginGroup(stop) => if stop.focusable && !stop.area.is_empty() {
+                        if let Some(area) = callback(cx, stop) {
+                            scroll_stack.push(area);
+                            return Some(area)
+                        }
                     }
+                    NavItem::EndGroup => (),
                     NavItem::BeginScroll(area)=>{
                         scroll_stack.push(*area);
                     }
@@ -114,6 +184,14 @@ impl<'a> Cx2d<'a> {
         }
     }
     
+    // turns the nav lists under a root draw list into the nodes of an accessibility tree
+    pub fn build_access_tree(cx: &mut Cx, root: DrawListId, tree: &mut AccessTree) {
+        let nav_tree_rc = cx.get_global::<CxNavTreeRc>().clone();
+        let nav_tree = &*nav_tree_rc.0.borrow();
+        let cx = &*cx;
+        nav_tree.build_access_tree(root, tree, | area | if area.is_valid(cx) {area.rect(cx)} else {Rect::default()});
+    }
+    
     pub fn nav_list_clear(&mut self, draw_list_id: DrawListId) {
         let mut nav_tree = self.nav_tree_rc.0.borrow_mut();
         if draw_list_id.index() >= nav_tree.nav_lists.len() {
@@ -128,13 +206,62 @@ impl<'a> Cx2d<'a> {
     }
     
     pub fn add_nav_stop(&mut self, area: Area, role: NavRole, margin: Margin) {
+        self.add_nav_stop_with_access(area, role, margin, AccessProps::default());
+    }
+    
+    pub fn add_nav_stop_with_access(&mut self, area: Area, role: NavRole, margin: Margin, access: AccessProps) {
+        let draw_list_id = *self.draw_list_stack.last().unwrap();
+        self.nav_list_item_push(draw_list_id, NavItem::Stop(NavStop {
+            role,
+            area,
+            order: NavOrder::Default,
+            margin,
+            focusable: true,
+            access,
+        }));
+    }
+    
+    // a node in the accessibility tree that tab doesnt stop at, like a label
+    pub fn add_access_node(&mut self, area: Area, role: NavRole, access: AccessProps) {
         let draw_list_id = *self.draw_list_stack.last().unwrap();
         self.nav_list_item_push(draw_list_id, NavItem::Stop(NavStop {
             role,
             area,
             order: NavOrder::Default,
-            margin
+            margin: Margin::default(),
+            focusable: false,
+            access,
+        }));
+    }
+    
+    pub fn add_begin_group(&mut self) -> NavGroupIndex {
+        let mut nav_tree = self.nav_tree_rc.0.borrow_mut();
+        let draw_list_id = *self.draw_list_stack.last().unwrap();
+        let id = NavGroupIndex(nav_tree[draw_list_id].nav_list.len());
+        nav_tree[draw_list_id].nav_list.push(NavItem::BeginGroup(NavStop {
+            role: NavRole::Group,
+            area: Area::Empty,
+            order: NavOrder::Default,
+            margin: Margin::default(),
+            focusable: false,
+            access: AccessProps::default(),
         }));
+        id
+    }
+    
+    // the area of a group is only known once its children are drawn, so it is filled in at the end
+    pub fn add_end_group(&mut self, index: NavGroupIndex, area: Area, role: NavRole, focusable: bool, access: AccessProps) {
+        let mut nav_tree = self.nav_tree_rc.0.borrow_mut();
+        let draw_list_id = *self.draw_list_stack.last().unwrap();
+        nav_tree[draw_list_id].nav_list[index.0] = NavItem::BeginGroup(NavStop {
+            role,
+            area,
+            order: NavOrder::Default,
+            margin: Margin::default(),
+            focusable,
+            access,
+        });
+        nav_tree[draw_list_id].nav_list.push(NavItem::EndGroup);
     }
     
     pub fn add_begin_scroll(&mut self)->NavScrollIndex{
@@ -153,4 +280,89 @@ impl<'a> Cx2d<'a> {
     }
 }
 
-pub struct NavScrollIndex(usize);
\ No newline at end of file
+pub struct NavScrollIndex(usize);
+
+pub struct NavGroupIndex(usize);
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::makepad_platform::{CxDrawListPool, RectArea, DVec2, dvec2},
+    };
+    
+    fn area(draw_list_id: DrawListId, rect_id: usize) -> Area {
+        Area::Rect(RectArea {draw_list_id, rect_id, redraw_id: 0})
+    }
+    
+    fn stop(role: NavRole, focusable: bool, name: &str, area: Area) -> NavStop {
+        NavStop {
+            role,
+            order: NavOrder::Default,
+            margin: Margin::default(),
+            area,
+            focusable,
+            access: AccessProps::with_name(name),
+        }
+    }
+    
+    // every area is a 10 by 10 square, one next to the other by rect id
+    fn bounds(area: Area) -> Rect {
+        match area {
+            Area::Rect(rect) => Rect {pos: dvec2(rect.rect_id as f64 * 10.0, 0.0), size: dvec2(10.0, 10.0)},
+            _ => Rect::default()
+        }
+    }
+    
+    #[test]
+    fn builds_the_tree_from_the_nav_stops() {
+        let mut draw_lists = CxDrawListPool::default();
+        let main = draw_lists.alloc();
+        let popup = draw_lists.alloc();
+        let (main, popup) = (main.id(), popup.id());
+        let mut nav_tree = CxNavTree::default();
+        nav_tree.nav_lists.resize(2, Default::default());
+        nav_tree[main].nav_list = vec![
+            NavItem::Stop(stop(NavRole::Label, false, "Title", area(main, 1))),
+            NavItem::BeginGroup(stop(NavRole::Button, true, "", area(main, 2))),
+            NavItem::Stop(stop(NavRole::Label, false, "Save", area(main, 3))),
+            NavItem::EndGroup,
+            NavItem::Child(popup),
+            NavItem::BeginScroll(area(main, 4)),
+            NavItem::Stop(stop(NavRole::CheckBox, true, "Wrap", area(main, 5))),
+            NavItem::EndScroll(area(main, 4)),
+            // stops that were not drawn this frame are left out
+            NavItem::Stop(stop(NavRole::Button, true, "Hidden", Area::Empty)),
+        ];
+        nav_tree[popup].nav_list = vec![
+            NavItem::Stop(stop(NavRole::TextInput, true, "Search", area(popup, 6))),
+        ];
+        let mut tree = AccessTree::new(Default::default(), "Window", DVec2::default(), dvec2(100.0, 10.0), 1.0);
+        nav_tree.build_access_tree(main, &mut tree, bounds);
+        
+        let nodes: Vec<(NavRole, &str)> = tree.nodes.iter().map( | node | (node.role, node.props.name.as_str())).collect();
+        assert_eq!(nodes, vec![
+            (NavRole::Window, "Window"),
+            (NavRole::Label, "Title"),
+            // a button without a name of its own is read out by its label
+            (NavRole::Button, "Save"),
+            (NavRole::Label, "Save"),
+            (NavRole::TextInput, "Search"),
+            (NavRole::Group, ""),
+            (NavRole::CheckBox, "Wrap"),
+        ]);
+        let children: Vec<&str> = tree.children(0).map( | node | node.props.name.as_str()).collect();
+        assert_eq!(children, vec!["Title", "Save", "Search", ""]);
+        assert_eq!(tree.nodes[3].parent, Some(2));
+        assert_eq!(tree.nodes[6].parent, Some(5));
+        
+        let search = tree.find_by_name("Search").unwrap();
+        assert_eq!(search.id, access_id(area(popup, 6)));
+        assert_eq!(search.bounds, Rect {pos: dvec2(60.0, 0.0), size: dvec2(10.0, 10.0)});
+        assert_eq!(tree.node_at(dvec2(25.0, 5.0)), Some(2));
+        assert_eq!(tree.node_at(dvec2(65.0, 5.0)), Some(4));
+        assert_eq!(tree.node_at(dvec2(95.0, 5.0)), Some(0));
+        
+        let focus_order: Vec<&str> = tree.focus_order().map( | node | node.props.name.as_str()).collect();
+        assert_eq!(focus_order, vec!["Save", "Search", "Wrap"]);
+    }
+}
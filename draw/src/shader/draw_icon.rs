@@ -18,6 +18,7 @@ live_design!{
         
         uniform u_brightness: float
         uniform u_curve: float
+        uniform u_multicolor: float
         
         texture tex: texture2d
         varying pos: vec2,
@@ -61,6 +62,11 @@ live_design!{
             
             // basic hardcoded mipmapping so it stops 'swimming' in VR
             // mipmaps are stored in red/green/blue channel
+            // multicolor icons are stored with premultiplied alpha, and only take the alpha of color
+            if self.u_multicolor > 0.5 {
+                let c = sample2d_rt(self.tex, self.tex_coord1.xy);
+                return vec4(c.rgb * self.u_brightness, c.a) * self.get_color().a;
+            }
             let s = sample2d_rt(self.tex, self.tex_coord1.xy).x;
             s = pow(s, self.u_curve);
             let col = self.get_color(); //color!(white);//get_color();
@@ -75,6 +81,7 @@ pub struct DrawIcon {
     #[live(1.0)] pub brightness: f32,
     #[live(0.6)] pub curve: f32,
     #[live(0.5)] pub linearize: f32,
+    #[live(false)] pub multicolor: bool,
     
     #[live] pub svg_file: LiveDependency,
     #[live] pub svg_path: Rc<String>,
@@ -177,7 +184,8 @@ impl DrawIcon {
                 size: snapped_size,
                 scale: self.scale * scale * dpi_factor,
                 translate: self.translate - bounds.pos,
-                subpixel: subpixel 
+                subpixel: subpixel,
+                multicolor: self.multicolor,
             }, path_hash);
             
             // lets snap the pos/size to actual pixels
@@ -202,6 +210,7 @@ impl DrawIcon {
         self.draw_vars.texture_slots[0] = Some(atlas.texture.clone());
         self.draw_vars.user_uniforms[0] = self.brightness;
         self.draw_vars.user_uniforms[1] = self.curve;
+        self.draw_vars.user_uniforms[2] = if self.multicolor {1.0} else {0.0};
     }
     
 }
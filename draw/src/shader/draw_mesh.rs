@@ -0,0 +1,156 @@
+use {
+    crate::{
+        makepad_platform::*,
+        geometry::GeometryCube3D,
+        cx_2d::Cx2d,
+        cx_3d::{Mesh3d, Material3d},
+    },
+};
+
+live_design!{
+
+    DrawMesh3d = {{DrawMesh3d}} {
+        texture base_color_texture: texture2d
+
+        varying world_pos: vec3
+        varying world_normal: vec3
+        varying uv: vec2
+
+        fn model_matrix(self) -> mat4 {
+            return mat4(self.model_col0, self.model_col1, self.model_col2, self.model_col3)
+        }
+
+        fn vertex(self) -> vec4 {
+            let model = self.model_matrix();
+            let world = model * vec4(self.geom_pos, 1.0);
+            self.world_pos = world.xyz;
+            // only correct for uniform scaling, which is what scenes use in practice
+            self.world_normal = (model * vec4(self.geom_normal, 0.0)).xyz;
+            self.uv = self.geom_uv;
+            return self.camera_projection * (self.camera_view * (self.view_transform * world))
+        }
+
+        fn get_base_color(self) -> vec4 {
+            return self.base_color * sample2d(self.base_color_texture, self.uv)
+        }
+
+        // lambert diffuse with a normalized blinn-phong highlight, the metallic and roughness
+        // factors only steer the mix and the size of the highlight
+        fn shade(self, base: vec4) -> vec4 {
+            let eye = (self.camera_inv * vec4(0.0, 0.0, 0.0, 1.0)).xyz;
+            let v = normalize(eye - self.world_pos);
+            let n = normalize(self.world_normal);
+            // meshes are drawn double sided
+            if dot(n, v) < 0.0 {
+                n = -n;
+            }
+            let l = normalize(self.light_dir);
+            let h = normalize(l + v);
+            let n_dot_l = max(dot(n, l), 0.0);
+            let r = max(self.roughness, 0.05);
+            let shininess = 2.0 / (r * r * r * r) - 2.0;
+            let specular = pow(max(dot(n, h), 0.0), shininess) * (shininess + 8.0) / 25.13;
+            let f0 = mix(vec3(0.04), base.rgb, self.metallic);
+            let fresnel = f0 + (vec3(1.0) - f0) * pow(1.0 - max(dot(h, v), 0.0), 5.0);
+            let diffuse = base.rgb * (1.0 - self.metallic);
+            let rgb = diffuse * (self.ambient + n_dot_l) + base.rgb * self.metallic * self.ambient + fresnel * specular * n_dot_l;
+            return vec4(rgb * base.a, base.a)
+        }
+
+        fn pixel(self) -> vec4 {
+            let base = self.get_base_color();
+            if self.unlit > 0.5 {
+                return vec4(base.rgb * base.a, base.a)
+            }
+            return self.shade(base)
+        }
+    }
+
+    DrawMesh3dUnlit = <DrawMesh3d> {
+        unlit: 1.0
+    }
+}
+
+#[derive(Live, LiveRegister)]
+#[repr(C)]
+pub struct DrawMesh3d {
+    // supplies the vertex layout, and a unit cube when no mesh is given
+    #[live] pub geometry: GeometryCube3D,
+    #[rust] white_texture: Option<Texture>,
+    #[deref] pub draw_vars: DrawVars,
+    #[calc] pub model_col0: Vec4,
+    #[calc] pub model_col1: Vec4,
+    #[calc] pub model_col2: Vec4,
+    #[calc] pub model_col3: Vec4,
+    #[live(vec4(1.0, 1.0, 1.0, 1.0))] pub base_color: Vec4,
+    #[live(0.0)] pub metallic: f32,
+    #[live(0.5)] pub roughness: f32,
+    #[live(0.0)] pub unlit: f32,
+    #[live(0.25)] pub ambient: f32,
+    #[live(vec3(0.4, 1.0, 0.6))] pub light_dir: Vec3,
+}
+
+impl LiveHook for DrawMesh3d {
+    fn before_apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) {
+        self.draw_vars.before_apply_init_shader(cx, apply, index, nodes, &self.geometry);
+    }
+    fn after_apply(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) {
+        self.draw_vars.after_apply_update_self(cx, apply, index, nodes, &self.geometry);
+    }
+}
+
+impl DrawMesh3d {
+    pub fn set_transform(&mut self, transform: &Mat4) {
+        let v = &transform.v;
+        self.model_col0 = vec4(v[0], v[1], v[2], v[3]);
+        self.model_col1 = vec4(v[4], v[5], v[6], v[7]);
+        self.model_col2 = vec4(v[8], v[9], v[10], v[11]);
+        self.model_col3 = vec4(v[12], v[13], v[14], v[15]);
+    }
+
+    pub fn set_material(&mut self, material: &Material3d) {
+        self.base_color = material.base_color;
+        self.metallic = material.metallic;
+        self.roughness = material.roughness;
+        self.unlit = if material.unlit {1.0} else {0.0};
+        if let Some(texture) = &material.base_color_texture {
+            self.draw_vars.set_texture(0, texture);
+        }
+        else {
+            self.draw_vars.empty_texture(0);
+        }
+    }
+
+    // draws the default cube geometry
+    pub fn draw(&mut self, cx: &mut Cx2d, transform: &Mat4) {
+        if let Some(geometry_ref) = &self.geometry.geometry_ref {
+            self.draw_vars.set_geometry(&geometry_ref.0);
+        }
+        self.draw_with_transform(cx, transform);
+    }
+
+    pub fn draw_mesh(&mut self, cx: &mut Cx2d, mesh: &Mesh3d, transform: &Mat4) {
+        self.draw_vars.set_geometry(&mesh.geometry);
+        self.draw_with_transform(cx, transform);
+    }
+
+    fn draw_with_transform(&mut self, cx: &mut Cx2d, transform: &Mat4) {
+        if !self.draw_vars.can_instance() {
+            return
+        }
+        // an untextured draw would otherwise be batched with a textured one
+        if self.draw_vars.texture_slots[0].is_none() {
+            let white = self.white_texture.get_or_insert_with( || {
+                Texture::new_with_format(cx, TextureFormat::VecBGRAu8_32 {
+                    width: 1,
+                    height: 1,
+                    data: vec![0xffffffff]
+                })
+            });
+            self.draw_vars.texture_slots[0] = Some(white.clone());
+        }
+        self.set_transform(transform);
+        let new_area = cx.add_instance(&self.draw_vars);
+        self.draw_vars.area = cx.update_area_refs(self.draw_vars.area, new_area);
+    }
+}
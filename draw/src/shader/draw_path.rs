@@ -0,0 +1,347 @@
+use {
+    crate::{
+        makepad_platform::*,
+        cx_2d::Cx2d,
+        shader::draw_quad::DrawQuad,
+        makepad_vector::geometry::{AffineTransformation, Point, Trapezoid, Transform, Transformation},
+        makepad_vector::internal_iter::*,
+        makepad_vector::path::{Path, PathIterator, LinePath},
+        makepad_vector::stroker::Stroker,
+        makepad_vector::trapezoidator::Trapezoidator,
+    },
+};
+
+pub use crate::makepad_vector::{
+    stroker::{LineCap, LineJoin, StrokeStyle},
+    trapezoidator::FillRule,
+};
+
+live_design!{
+    DrawPath = {{DrawPath}} {
+        varying v_p0: vec2;
+        varying v_p1: vec2;
+        varying v_p2: vec2;
+        varying v_p3: vec2;
+        varying v_pixel: vec2;
+        varying v_pos: vec2;
+
+        fn intersect_line_segment_with_vertical_line(p0: vec2, p1: vec2, x: float) -> vec2 {
+            return vec2(
+                x,
+                mix(p0.y, p1.y, (x - p0.x) / (p1.x - p0.x))
+            );
+        }
+
+        fn intersect_line_segment_with_horizontal_line(p0: vec2, p1: vec2, y: float) -> vec2 {
+            return vec2(
+                mix(p0.x, p1.x, (y - p0.y) / (p1.y - p0.y)),
+                y
+            );
+        }
+
+        fn compute_clamped_right_trapezoid_area(p0: vec2, p1: vec2, p_min: vec2, p_max: vec2) -> float {
+            let x0 = clamp(p0.x, p_min.x, p_max.x);
+            let x1 = clamp(p1.x, p_min.x, p_max.x);
+            if (p0.x < p_min.x && p_min.x < p1.x) {
+                p0 = intersect_line_segment_with_vertical_line(p0, p1, p_min.x);
+            }
+            if (p0.x < p_max.x && p_max.x < p1.x) {
+                p1 = intersect_line_segment_with_vertical_line(p0, p1, p_max.x);
+            }
+            if (p0.y < p_min.y && p_min.y < p1.y) {
+                p0 = intersect_line_segment_with_horizontal_line(p0, p1, p_min.y);
+            }
+            if (p1.y < p_min.y && p_min.y < p0.y) {
+                p1 = intersect_line_segment_with_horizontal_line(p1, p0, p_min.y);
+            }
+            if (p0.y < p_max.y && p_max.y < p1.y) {
+                p1 = intersect_line_segment_with_horizontal_line(p0, p1, p_max.y);
+            }
+            if (p1.y < p_max.y && p_max.y < p0.y) {
+                p0 = intersect_line_segment_with_horizontal_line(p1, p0, p_max.y);
+            }
+            p0 = clamp(p0, p_min, p_max);
+            p1 = clamp(p1, p_min, p_max);
+            let h0 = p_max.y - p0.y;
+            let h1 = p_max.y - p1.y;
+            let a0 = (p0.x - x0) * h0;
+            let a1 = (p1.x - p0.x) * (h0 + h1) * 0.5;
+            let a2 = (x1 - p1.x) * h1;
+            return a0 + a1 + a2;
+        }
+
+        fn compute_clamped_trapezoid_area(self, p_min: vec2, p_max: vec2) -> float {
+            let a0 = compute_clamped_right_trapezoid_area(self.v_p0, self.v_p1, p_min, p_max);
+            let a1 = compute_clamped_right_trapezoid_area(self.v_p2, self.v_p3, p_min, p_max);
+            return a0 - a1;
+        }
+
+        // the paint of the path at a position relative to rect_pos
+        fn get_color(self, pos: vec2) -> vec4 {
+            if self.gradient_kind > 1.5 {
+                let t = length(pos - self.gradient.xy) / max(self.gradient.z, 0.0001);
+                return mix(self.color, self.gradient_color, clamp(t, 0.0, 1.0));
+            }
+            if self.gradient_kind > 0.5 {
+                let d = self.gradient.zw - self.gradient.xy;
+                let t = dot(pos - self.gradient.xy, d) / max(dot(d, d), 0.0001);
+                return mix(self.color, self.gradient_color, clamp(t, 0.0, 1.0));
+            }
+            return self.color;
+        }
+
+        fn pixel(self) -> vec4 {
+            let coverage = clamp(abs(self.compute_clamped_trapezoid_area(self.v_pixel - 0.5, self.v_pixel + 0.5)), 0.0, 1.0);
+            let color = self.get_color(self.v_pos);
+            return vec4(color.rgb * color.a, color.a) * coverage;
+        }
+
+        fn vertex(self) -> vec4 {
+            // the trapezoids are in device pixels relative to rect_pos, so that they get
+            // antialiased per device pixel and move along when the turtle aligns the path
+            let pos_min = vec2(self.a_xs.x, min(self.a_ys.x, self.a_ys.y));
+            let pos_max = vec2(self.a_xs.y, max(self.a_ys.z, self.a_ys.w));
+            let pixel = mix(pos_min - 1.0, pos_max + 1.0, self.geom_pos);
+            let clipped = clamp(
+                self.rect_pos + pixel / self.dpi_factor,
+                self.draw_clip.xy,
+                self.draw_clip.zw
+            );
+
+            self.v_p0 = vec2(self.a_xs.x, self.a_ys.x);
+            self.v_p1 = vec2(self.a_xs.y, self.a_ys.y);
+            self.v_p2 = vec2(self.a_xs.x, self.a_ys.z);
+            self.v_p3 = vec2(self.a_xs.y, self.a_ys.w);
+            self.v_pixel = (clipped - self.rect_pos) * self.dpi_factor;
+            self.v_pos = clipped - self.rect_pos;
+            return self.camera_projection * (self.camera_view * (self.view_transform * vec4(
+                clipped.x,
+                clipped.y,
+                self.draw_depth + self.draw_zbias,
+                1.
+            )))
+        }
+    }
+}
+
+/// Draws vector paths that are built with `move_to`, `line_to`, `cubic_to`, `arc` and friends,
+/// either filled or stroked, with a solid color or a gradient. Paths are converted into
+/// trapezoids on the CPU, and antialiased on the GPU by the area each trapezoid covers of a pixel.
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawPath {
+    #[rust] path: Path,
+    #[rust] current_point: Option<DVec2>,
+    #[rust(AffineTransformation::identity())] transform: AffineTransformation,
+    #[rust] trapezoidator: Trapezoidator,
+    #[rust] stroker: Stroker,
+    #[deref] pub draw_super: DrawQuad,
+    #[live] pub color: Vec4,
+    #[calc] pub gradient_color: Vec4,
+    #[calc] pub gradient: Vec4,
+    #[calc] pub gradient_kind: f32,
+    #[calc] pub a_xs: Vec2,
+    #[calc] pub a_ys: Vec4,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Gradient {
+    Linear {start: DVec2, end: DVec2},
+    Radial {center: DVec2, radius: f64},
+}
+
+impl DrawPath {
+    /// Starts a new path, discarding the previous one.
+    pub fn begin_path(&mut self) {
+        self.path.clear();
+        self.current_point = None;
+    }
+
+    pub fn move_to(&mut self, p: DVec2) {
+        self.path.move_to(point(p));
+        self.current_point = Some(p);
+    }
+
+    pub fn line_to(&mut self, p: DVec2) {
+        if self.current_point.is_none() {
+            return self.move_to(p)
+        }
+        self.path.line_to(point(p));
+        self.current_point = Some(p);
+    }
+
+    pub fn quadratic_to(&mut self, p1: DVec2, p: DVec2) {
+        if self.current_point.is_none() {
+            self.move_to(p1);
+        }
+        self.path.quadratic_to(point(p1), point(p));
+        self.current_point = Some(p);
+    }
+
+    pub fn cubic_to(&mut self, p1: DVec2, p2: DVec2, p: DVec2) {
+        if self.current_point.is_none() {
+            self.move_to(p1);
+        }
+        self.path.cubic_to(point(p1), point(p2), point(p));
+        self.current_point = Some(p);
+    }
+
+    /// Adds a circular arc around `center` from `start_angle` to `end_angle`, in radians, going
+    /// clockwise on screen if `end_angle` is larger. The arc is connected to the current point
+    /// with a line, if there is one.
+    pub fn arc(&mut self, center: DVec2, radius: f64, start_angle: f64, end_angle: f64) {
+        let at = |angle: f64| center + dvec2(angle.cos(), angle.sin()) * radius;
+        self.line_to(at(start_angle));
+        let sweep = (end_angle - start_angle).clamp(-std::f64::consts::TAU, std::f64::consts::TAU);
+        // every segment spans at most a quarter circle, and is approximated by a cubic curve
+        let segments = (sweep.abs() / std::f64::consts::FRAC_PI_2).ceil().max(1.0) as usize;
+        let step = sweep / segments as f64;
+        let k = 4.0 / 3.0 * (step / 4.0).tan() * radius;
+        for i in 0..segments {
+            let a0 = start_angle + step * i as f64;
+            let a1 = a0 + step;
+            let (p0, p1) = (at(a0), at(a1));
+            self.cubic_to(
+                p0 + dvec2(-a0.sin(), a0.cos()) * k,
+                p1 - dvec2(-a1.sin(), a1.cos()) * k,
+                p1,
+            );
+        }
+    }
+
+    pub fn rect(&mut self, rect: Rect) {
+        self.move_to(rect.pos);
+        self.line_to(dvec2(rect.pos.x + rect.size.x, rect.pos.y));
+        self.line_to(rect.pos + rect.size);
+        self.line_to(dvec2(rect.pos.x, rect.pos.y + rect.size.y));
+        self.close_path();
+    }
+
+    pub fn close_path(&mut self) {
+        if self.current_point.is_some() {
+            self.path.close();
+        }
+    }
+
+    /// Sets the transform that is applied to paths and gradients when they are drawn.
+    pub fn set_transform(&mut self, transform: AffineTransformation) {
+        self.transform = transform;
+    }
+
+    pub fn set_color(&mut self, color: Vec4) {
+        self.color = color;
+        self.gradient_kind = 0.0;
+    }
+
+    /// Paints with a gradient from `color0` at `start` to `color1` at `end`.
+    pub fn set_linear_gradient(&mut self, start: DVec2, end: DVec2, color0: Vec4, color1: Vec4) {
+        self.set_gradient(Gradient::Linear {start, end}, color0, color1);
+    }
+
+    /// Paints with a gradient from `color0` at `center` to `color1` at `radius` away from it.
+    pub fn set_radial_gradient(&mut self, center: DVec2, radius: f64, color0: Vec4, color1: Vec4) {
+        self.set_gradient(Gradient::Radial {center, radius}, color0, color1);
+    }
+
+    fn set_gradient(&mut self, gradient: Gradient, color0: Vec4, color1: Vec4) {
+        // the gradient is kept in path coordinates until the path is drawn, when it gets
+        // transformed along with the path
+        let (kind, values) = match gradient {
+            Gradient::Linear {start, end} => (1.0, vec4(start.x as f32, start.y as f32, end.x as f32, end.y as f32)),
+            Gradient::Radial {center, radius} => (2.0, vec4(center.x as f32, center.y as f32, radius as f32, 0.0)),
+        };
+        self.color = color0;
+        self.gradient_color = color1;
+        self.gradient_kind = kind;
+        self.gradient = values;
+    }
+
+    /// Fills the current path with the given fill rule.
+    pub fn fill(&mut self, cx: &mut Cx2d, fill_rule: FillRule) {
+        let dpi_factor = cx.current_dpi_factor();
+        let epsilon = self.tolerance(dpi_factor);
+        let to_device = self.transform.uniform_scale(dpi_factor);
+        let line_path: LinePath = self.path
+            .commands()
+            .linearize(epsilon)
+            .map(|command| command.transform(&to_device))
+            .collect();
+        self.draw_line_path(cx, &line_path, fill_rule);
+    }
+
+    /// Strokes the current path with the given style. The width of the stroke is in path
+    /// coordinates, so it is scaled by the transform.
+    pub fn stroke(&mut self, cx: &mut Cx2d, style: &StrokeStyle) {
+        let dpi_factor = cx.current_dpi_factor();
+        let epsilon = self.tolerance(dpi_factor);
+        let to_device = self.transform.uniform_scale(dpi_factor);
+        let line_path = self.stroker
+            .stroke(self.path.commands().linearize(epsilon), style, epsilon)
+            .transform(&to_device);
+        self.draw_line_path(cx, &line_path, FillRule::NonZero);
+    }
+
+    // The tolerance for flattening curves in path coordinates, which is a tenth of a device pixel.
+    fn tolerance(&self, dpi_factor: f64) -> f64 {
+        let xy = self.transform.xy;
+        let scale = (xy.x.x * xy.y.y - xy.x.y * xy.y.x).abs().sqrt() * dpi_factor;
+        0.1 / scale.max(0.0001)
+    }
+
+    fn draw_line_path(&mut self, cx: &mut Cx2d, line_path: &LinePath, fill_rule: FillRule) {
+        self.trapezoidator.set_fill_rule(fill_rule);
+        let mut trapezoids: Vec<Trapezoid> = Vec::new();
+        if let Some(trapezoidate) = self.trapezoidator.trapezoidate(line_path.commands()) {
+            trapezoids.extend_from_internal_iter(trapezoidate);
+        }
+        if trapezoids.is_empty() {
+            return
+        }
+        let mut min = vec2(f32::INFINITY, f32::INFINITY);
+        let mut max = vec2(-f32::INFINITY, -f32::INFINITY);
+        for trapezoid in &trapezoids {
+            min.x = min.x.min(trapezoid.xs[0]);
+            max.x = max.x.max(trapezoid.xs[1]);
+            min.y = min.y.min(trapezoid.ys[0].min(trapezoid.ys[1]));
+            max.y = max.y.max(trapezoid.ys[2].max(trapezoid.ys[3]));
+        }
+        let dpi_factor = cx.current_dpi_factor() as f32;
+        let gradient = self.gradient;
+        let rect_pos = min / dpi_factor;
+        self.rect_pos = rect_pos;
+        self.rect_size = (max - min) / dpi_factor;
+        // the shader evaluates gradients relative to rect_pos, in logical pixels
+        if self.gradient_kind > 0.5 {
+            let to_rect = |x: f32, y: f32| {
+                let p = self.transform.transform_point(Point::new(x as f64, y as f64));
+                vec2(p.x as f32, p.y as f32) - rect_pos
+            };
+            let start = to_rect(gradient.x, gradient.y);
+            if self.gradient_kind > 1.5 {
+                let xy = self.transform.xy;
+                let scale = (xy.x.x * xy.y.y - xy.x.y * xy.y.x).abs().sqrt() as f32;
+                self.gradient = vec4(start.x, start.y, gradient.z * scale, 0.0);
+            }
+            else {
+                let end = to_rect(gradient.z, gradient.w);
+                self.gradient = vec4(start.x, start.y, end.x, end.y);
+            }
+        }
+        if let Some(mut many) = cx.begin_many_aligned_instances(&self.draw_vars) {
+            for trapezoid in trapezoids {
+                let [x0, x1] = trapezoid.xs.map(|x| x - min.x);
+                let [y0, y1, y2, y3] = trapezoid.ys.map(|y| y - min.y);
+                self.a_xs = Vec2 {x: x0, y: x1};
+                self.a_ys = Vec4 {x: y0, y: y1, z: y2, w: y3};
+                many.instances.extend_from_slice(self.draw_vars.as_slice());
+            }
+            let new_area = cx.end_many_instances(many);
+            self.draw_vars.area = cx.update_area_refs(self.draw_vars.area, new_area);
+        }
+        self.gradient = gradient;
+    }
+}
+
+fn point(p: DVec2) -> Point {
+    Point::new(p.x, p.y)
+}
@@ -2,7 +2,7 @@ use {
     crate::{
         makepad_platform::*,
         turtle::{Walk, Size, Align},
-        font_atlas::{CxFontsAtlasTodo, CxFont, CxFontsAtlas, Font},
+        font_atlas::{CxFontsAtlasTodo, CxFontsAtlas, Font},
         draw_list_2d::ManyInstances,
         geometry::GeometryQuad2D,
         cx_2d::Cx2d
@@ -22,6 +22,8 @@ live_design!{
         uniform sdf_cutoff: float
         
         texture tex: texture2d
+        texture color_tex: texture2d
+        texture color_mask_tex: texture2d
         
         varying tex_coord1: vec2
         varying tex_coord2: vec2
@@ -75,12 +77,27 @@ live_design!{
             return self.blend_color(vec4(s * col.rgb * self.brightness * col.a, s * col.a));
         }
         
+        fn sample_color_glyph(self, pos:vec2)->vec4{
+            // color glyphs are stored premultiplied, the mask holds how much of each pixel comes
+            // from layers in the color of the text
+            let col = self.get_color();
+            let glyph = sample2d(self.color_tex, pos);
+            let mask = sample2d(self.color_mask_tex, pos).x;
+            return vec4(glyph.rgb + col.rgb * mask, glyph.a) * col.a;
+        }
+        
         fn pixel(self) -> vec4 {
+            // the derivatives are only defined outside of branches that depend on the glyph, so
+            // both kinds are sampled and the right one is picked
             let texel_coords = self.tex_coord1.xy;
             let dxt = length(dFdx(texel_coords));
             let dyt = length(dFdy(texel_coords));
             let scale = (dxt + dyt) * 4096.0 *0.5;
-            return self.sample_color(scale, self.tex_coord1.xy);
+            return mix(
+                self.sample_color(scale, self.tex_coord1.xy),
+                self.sample_color_glyph(self.tex_coord1.xy),
+                self.color_glyph
+            );
             // ok lets take our delta in the x direction
             /*
             //4x AA
@@ -142,6 +159,10 @@ const _: () = assert!(crate::font_atlas::ATLAS_HEIGHT == 4096);
 #[live_ignore]
 pub struct TextStyle {
     #[live()] pub font: Font,
+    // tried in order for the characters that `font` has no glyph for
+    #[live] pub font_fallbacks: Vec<Font>,
+    // after the fallback fonts, try the fonts installed on the system (only found on linux)
+    #[live(false)] pub system_font_fallback: bool,
     #[live(9.0)] pub font_size: f64,
     #[live(1.0)] pub brightness: f32,
     #[live(0.5)] pub curve: f32,
@@ -150,6 +171,13 @@ pub struct TextStyle {
     #[live(1.3)] pub height_factor: f64,
 }
 
+impl TextStyle {
+    // the main font followed by the fallback fonts, in the order that they are tried
+    pub fn font_ids(&self) -> impl Iterator<Item = usize> + '_ {
+        self.font.font_id.into_iter().chain(self.font_fallbacks.iter().filter_map( | font | font.font_id))
+    }
+}
+
 #[derive(Clone, Live, LiveHook)]
 #[live_ignore]
 pub enum TextWrap {
@@ -190,7 +218,7 @@ impl<'a> WordIterator<'a> {
         }
     }
     
-    fn next_word(&mut self, font: &mut CxFont) -> Option<WordIteratorItem> {
+    fn next_word(&mut self, fonts_atlas: &mut CxFontsAtlas, text_style: &TextStyle) -> Option<WordIteratorItem> {
         if let Some(char_iter) = &mut self.char_iter {
             while let Some((i, c)) = char_iter.next() {
                 self.last_index = i;
@@ -202,8 +230,8 @@ impl<'a> WordIterator<'a> {
                     with_newline: false
                 };
                 
-                let adv = if let Some(glyph) = font.get_glyph(c) {
-                    glyph.horizontal_metrics.advance_width * self.font_size_total
+                let adv = if let Some(advance) = fonts_atlas.glyph_advance_em(text_style, c) {
+                    advance * self.font_size_total
                 }else {0.0};
                 
                 if c == '\r' {
@@ -288,6 +316,7 @@ pub struct DrawText {
     #[calc] pub delta: Vec2,
     #[calc] pub shader_font_size: f32,
     #[calc] pub advance: f32,
+    #[calc] pub color_glyph: f32,
 }
 
 impl LiveHook for DrawText {
@@ -347,6 +376,8 @@ impl DrawText {
     
     pub fn update_draw_call_vars(&mut self, font_atlas: &CxFontsAtlas) {
         self.draw_vars.texture_slots[0] = Some(font_atlas.texture.clone());
+        self.draw_vars.texture_slots[1] = Some(font_atlas.color_texture.clone());
+        self.draw_vars.texture_slots[2] = Some(font_atlas.color_mask_texture.clone());
         self.draw_vars.user_uniforms[0] = self.text_style.brightness;
         self.draw_vars.user_uniforms[1] = self.text_style.curve;
         let (sdf_radius, sdf_cutoff) = font_atlas.alloc.sdf.as_ref()
@@ -380,19 +411,8 @@ impl DrawText {
             self.begin_many_instances_internal(cx, fonts_atlas);
         }
         
-        let cxfont = fonts_atlas.fonts[font_id].as_mut().unwrap();
         let dpi_factor = cx.current_dpi_factor();
         
-        let atlas_page_id = cxfont.get_atlas_page_id(dpi_factor, self.text_style.font_size);
-        
-        let font = &mut cxfont.ttf_font;
-        let owned_font_face = &cxfont.owned_font_face;
-        
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * font.units_per_em);
-        let font_size_pixels = font_size_logical * dpi_factor;
-        
-        let atlas_page = &mut cxfont.atlas_pages[atlas_page_id];
-        
         let mi = if let Some(mi) = &mut self.many_instances {mi} else {return};
         let zbias_step = 0.00001;
         let mut char_depth = self.draw_depth;
@@ -418,67 +438,112 @@ impl DrawText {
             for (run_level, run_range) in runs_with_level_and_range {
                 // FIXME(eddyb) UBA/`unicode_bidi` only offers a LTR/RTL distinction,
                 // even if `rustybuzz` has vertical `Direction`s as well.
-                let (glyph_ids, new_rustybuzz_buffer) = cxfont
-                    .shape_cache
-                    .get_or_compute_glyph_ids(
-                    (
-                            if run_level.is_rtl() {
-                                rustybuzz::Direction::RightToLeft
-                            } else {
-                                rustybuzz::Direction::LeftToRight
-                            },
-                            &bidi_info.text[run_range]
-                        ),
-                        rustybuzz_buffer,
-                        owned_font_face
-                    );
-                rustybuzz_buffer = new_rustybuzz_buffer;
-                for &glyph_id in glyph_ids {
-                    let glyph = owned_font_face.with_ref(|face| font.get_glyph_by_id(face, glyph_id).unwrap());
+                let direction = if run_level.is_rtl() {
+                    rustybuzz::Direction::RightToLeft
+                } else {
+                    rustybuzz::Direction::LeftToRight
+                };
+                let run = &bidi_info.text[run_range];
+                
+                // Split the run further into pieces that are each shaped with the first font
+                // of the fallback chain that has glyphs for them. Whitespace stays with the
+                // piece before it, so that it does not split up text in the same font.
+                let mut pieces: Vec<(usize, std::ops::Range<usize>)> = Vec::new();
+                for (index, c) in run.char_indices() {
+                    let end = index + c.len_utf8();
+                    if let Some((_, range)) = pieces.last_mut().filter(|_| c.is_whitespace()) {
+                        range.end = end;
+                        continue
+                    }
+                    let Some(piece_font_id) = fonts_atlas.font_id_for_char(&self.text_style, c) else {
+                        continue
+                    };
+                    match pieces.last_mut() {
+                        Some((last_font_id, range)) if *last_font_id == piece_font_id => range.end = end,
+                        _ => pieces.push((piece_font_id, index..end)),
+                    }
+                }
+                // The pieces of a right to left run are laid out from the right as well.
+                if run_level.is_rtl() {
+                    pieces.reverse();
+                }
+                
+                for (font_id, piece_range) in pieces {
+                    let cxfont = fonts_atlas.fonts[font_id].as_mut().unwrap();
+                    let atlas_page_id = cxfont.get_atlas_page_id(dpi_factor, self.text_style.font_size);
                     
-                    let advance = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                    let font = &mut cxfont.ttf_font;
+                    let owned_font_face = &cxfont.owned_font_face;
                     
-                    // HACK(eddyb) this is a different padding from the SDF padding,
-                    // this allows the glyph rasterization to avoid touching the
-                    // edges of the raster area, while the SDF padding exists for
-                    // e.g. bilinear sampling to have excess texels to sample.
-                    let pad_dpx = 2.0;
-                    let w_dpx = ((glyph.bounds.p_max.x - glyph.bounds.p_min.x) * font_size_pixels).ceil() + pad_dpx * 2.0;
-                    let h_dpx = ((glyph.bounds.p_max.y - glyph.bounds.p_min.y) * font_size_pixels).ceil() + pad_dpx * 2.0;
-                    let (w_dpx, h_dpx) = if w_dpx <= pad_dpx * 2.0{(0.0,0.0)}else { (w_dpx, h_dpx) };
-                                        
-                    let tc = *atlas_page.atlas_glyphs.entry(glyph_id).or_insert_with(|| {
-                        // see if we can fit it
-                        // allocate slot
-                        fonts_atlas.alloc.alloc_atlas_glyph(w_dpx, h_dpx, CxFontsAtlasTodo {
-                            font_id,
-                            atlas_page_id,
-                            glyph_id,
-                        })
-                    });
-
-                    let pad = pad_dpx * self.font_scale / dpi_factor;
-                    let w = w_dpx * self.font_scale / dpi_factor;
-                    let h = h_dpx * self.font_scale / dpi_factor;
+                    let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * font.units_per_em);
+                    let font_size_pixels = font_size_logical * dpi_factor;
+                    
+                    let atlas_page = &mut cxfont.atlas_pages[atlas_page_id];
                     
-                    let delta_x = font_size_logical * self.font_scale * glyph.bounds.p_min.x - pad;
-                    let delta_y = -(font_size_logical * self.font_scale * glyph.bounds.p_min.y - pad)
-                        + self.text_style.font_size * self.font_scale * self.text_style.top_drop;
-                    // give the callback a chance to do things
-                    //et scaled_min_pos_x = walk_x + delta_x;
-                    //let scaled_min_pos_y = pos.y - delta_y;
-                    self.font_t1 = tc.t1;
-                    self.font_t2 = tc.t2;
-                    self.rect_pos = dvec2(walk_x + delta_x, pos.y + delta_y).into();
-                    self.rect_size = dvec2(w, h).into();
-                    self.char_depth = char_depth;
-                    self.delta.x = delta_x as f32;
-                    self.delta.y = delta_y as f32;
-                    self.shader_font_size = self.text_style.font_size as f32;
-                    self.advance = advance as f32; //char_offset as f32;
-                    char_depth += zbias_step;
-                    mi.instances.extend_from_slice(self.draw_vars.as_slice());
-                    walk_x += advance;
+                    let (glyph_ids, new_rustybuzz_buffer) = cxfont
+                        .shape_cache
+                        .get_or_compute_glyph_ids(
+                            (direction, &run[piece_range]),
+                            rustybuzz_buffer,
+                            owned_font_face
+                        );
+                    rustybuzz_buffer = new_rustybuzz_buffer;
+                    for &glyph_id in glyph_ids {
+                        let glyph = owned_font_face.with_ref(|face| font.get_glyph_by_id(face, glyph_id).unwrap());
+                        // Color glyphs (e.g. emoji) have their own bounds, and live in the color atlas.
+                        let color_glyph = cxfont.color_glyphs.get(owned_font_face, glyph_id);
+                        let bounds = color_glyph.as_ref().map_or(glyph.bounds, |color_glyph| color_glyph.bounds);
+                        
+                        let advance = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                        
+                        // HACK(eddyb) this is a different padding from the SDF padding,
+                        // this allows the glyph rasterization to avoid touching the
+                        // edges of the raster area, while the SDF padding exists for
+                        // e.g. bilinear sampling to have excess texels to sample.
+                        let pad_dpx = 2.0;
+                        let w_dpx = ((bounds.p_max.x - bounds.p_min.x) * font_size_pixels).ceil() + pad_dpx * 2.0;
+                        let h_dpx = ((bounds.p_max.y - bounds.p_min.y) * font_size_pixels).ceil() + pad_dpx * 2.0;
+                        let (w_dpx, h_dpx) = if w_dpx <= pad_dpx * 2.0{(0.0,0.0)}else { (w_dpx, h_dpx) };
+                                            
+                        let tc = *atlas_page.atlas_glyphs.entry(glyph_id).or_insert_with(|| {
+                            // see if we can fit it
+                            // allocate slot
+                            let alloc = if color_glyph.is_some() {
+                                &mut fonts_atlas.color_alloc
+                            } else {
+                                &mut fonts_atlas.alloc
+                            };
+                            alloc.alloc_atlas_glyph(w_dpx, h_dpx, CxFontsAtlasTodo {
+                                font_id,
+                                atlas_page_id,
+                                glyph_id,
+                            })
+                        });
+    
+                        let pad = pad_dpx * self.font_scale / dpi_factor;
+                        let w = w_dpx * self.font_scale / dpi_factor;
+                        let h = h_dpx * self.font_scale / dpi_factor;
+                        
+                        let delta_x = font_size_logical * self.font_scale * bounds.p_min.x - pad;
+                        let delta_y = -(font_size_logical * self.font_scale * bounds.p_min.y - pad)
+                            + self.text_style.font_size * self.font_scale * self.text_style.top_drop;
+                        // give the callback a chance to do things
+                        //et scaled_min_pos_x = walk_x + delta_x;
+                        //let scaled_min_pos_y = pos.y - delta_y;
+                        self.font_t1 = tc.t1;
+                        self.font_t2 = tc.t2;
+                        self.rect_pos = dvec2(walk_x + delta_x, pos.y + delta_y).into();
+                        self.rect_size = dvec2(w, h).into();
+                        self.char_depth = char_depth;
+                        self.delta.x = delta_x as f32;
+                        self.delta.y = delta_y as f32;
+                        self.shader_font_size = self.text_style.font_size as f32;
+                        self.advance = advance as f32; //char_offset as f32;
+                        self.color_glyph = if color_glyph.is_some() {1.0} else {0.0};
+                        char_depth += zbias_step;
+                        mi.instances.extend_from_slice(self.draw_vars.as_slice());
+                        walk_x += advance;
+                    }
                 }
             }
         }
@@ -496,15 +561,15 @@ impl DrawText {
             return None
         }
         
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * fonts_atlas.fonts[font_id].as_ref().unwrap().ttf_font.units_per_em);
+        let font_size_em = self.text_style.font_size * 96.0 / 72.0;
         let line_height = self.text_style.font_size * self.text_style.height_factor * self.font_scale;
         let eval_width = cx.turtle().eval_width(walk.width, walk.margin, cx.turtle().layout().flow);
         let eval_height = cx.turtle().eval_height(walk.height, walk.margin, cx.turtle().layout().flow);
         
         match if walk.width.is_fit() {&TextWrap::Line}else {&self.wrap} {
             TextWrap::Ellipsis => {
-                let ellip_width = if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph('.') {
-                    glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale
+                let ellip_width = if let Some(advance) = fonts_atlas.glyph_advance_em(&self.text_style, '.') {
+                    advance * font_size_em * self.font_scale
                 }
                 else {
                     0.0
@@ -517,8 +582,8 @@ impl DrawText {
                     if measured_width + ellip_width * 3.0 < eval_width {
                         ellip_pt = Some((i, measured_width, 3));
                     }
-                    if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph(c) {
-                        let adv = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                    if let Some(advance) = fonts_atlas.glyph_advance_em(&self.text_style, c) {
+                        let adv = advance * font_size_em * self.font_scale;
                         // ok so now what.
                         if measured_width + adv >= eval_width { // we have to drop back to ellip_pt
                             // if we don't have an ellip_pt, set it to 0
@@ -554,8 +619,8 @@ impl DrawText {
                 let mut measured_width = 0.0;
                 let mut measured_height = line_height;
                 
-                let mut iter = WordIterator::new(text.char_indices(), eval_width, font_size_logical * self.font_scale);
-                while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
+                let mut iter = WordIterator::new(text.char_indices(), eval_width, font_size_em * self.font_scale);
+                while let Some(word) = iter.next_word(fonts_atlas, &self.text_style) {
                     if measured_width + word.width >= eval_width {
                         measured_height += line_height * self.text_style.line_spacing;
                         measured_width = word.width;
@@ -587,8 +652,8 @@ impl DrawText {
                     if c == '\n' {
                         measured_height += line_height * self.text_style.line_spacing;
                     }
-                    if let Some(glyph) = fonts_atlas.fonts[font_id].as_mut().unwrap().get_glyph(c) {
-                        let adv = glyph.horizontal_metrics.advance_width * font_size_logical * self.font_scale;
+                    if let Some(advance) = fonts_atlas.glyph_advance_em(&self.text_style, c) {
+                        let adv = advance * font_size_em * self.font_scale;
                         measured_width += adv;
                     }
                     if measured_width > max_width {
@@ -623,7 +688,10 @@ impl DrawText {
         let mut fonts_atlas = fonts_atlas_rc.0.borrow_mut();
         let fonts_atlas = &mut*fonts_atlas;
                 
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * fonts_atlas.fonts[font_id].as_ref().unwrap().ttf_font.units_per_em);
+        if fonts_atlas.fonts[font_id].is_none() {
+            return
+        }
+        let font_size_em = self.text_style.font_size * 96.0 / 72.0;
         let line_drop = self.text_style.font_size * self.text_style.height_factor * self.font_scale * self.text_style.top_drop;
         
         // lets get the width of the current turtle
@@ -633,10 +701,10 @@ impl DrawText {
         let mut iter = WordIterator::new(
             text.char_indices(),
             padded_rect.size.x,
-            font_size_logical * self.font_scale, 
+            font_size_em * self.font_scale, 
         );
         
-        while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
+        while let Some(word) = iter.next_word(fonts_atlas, &self.text_style) {
             let walk_rect = cx.walk_turtle(Walk {
                 abs_pos: None,
                 margin: Margin::default(),
@@ -664,7 +732,10 @@ impl DrawText {
         let mut fonts_atlas = fonts_atlas_rc.0.borrow_mut();
         let fonts_atlas = &mut*fonts_atlas;
         
-        let font_size_logical = self.text_style.font_size * 96.0 / (72.0 * fonts_atlas.fonts[font_id].as_ref().unwrap().ttf_font.units_per_em);
+        if fonts_atlas.fonts[font_id].is_none() {
+            return
+        }
+        let font_size_em = self.text_style.font_size * 96.0 / 72.0;
         let line_height = self.text_style.font_size * self.text_style.height_factor * self.font_scale;
                 
         //let in_many = self.many_instances.is_some();
@@ -733,8 +804,8 @@ impl DrawText {
                     });
                     let mut pos = dvec2(0.0, 0.0);
                     
-                    let mut iter = WordIterator::new(text.char_indices(), geom.eval_width, font_size_logical * self.font_scale);
-                    while let Some(word) = iter.next_word(fonts_atlas.fonts[font_id].as_mut().unwrap()) {
+                    let mut iter = WordIterator::new(text.char_indices(), geom.eval_width, font_size_em * self.font_scale);
+                    while let Some(word) = iter.next_word(fonts_atlas, &self.text_style) {
                         if pos.x + word.width >= geom.eval_width {
                             pos.y += line_height * self.text_style.line_spacing;
                             pos.x = 0.0;
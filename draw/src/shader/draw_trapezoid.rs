@@ -14,6 +14,7 @@ pub use {
         geometry::GeometryQuad2D,
         makepad_vector::font::Glyph,
         makepad_vector::trapezoidator::Trapezoidator,
+        makepad_vector::stroker::Stroker,
         makepad_vector::geometry::{AffineTransformation, Transform, Vector},
         makepad_vector::internal_iter::*,
         makepad_vector::path::PathIterator,
@@ -80,10 +81,32 @@ live_design!{
             return a0 - a1;
         }
         
+        fn get_color(self) -> vec4 {
+            if self.gradient_kind < 0.5 {
+                return self.color;
+            }
+            let d = self.gradient.zw - self.gradient.xy;
+            let t = dot(self.v_pixel - self.gradient.xy, d) / max(dot(d, d), 0.0001);
+            if self.gradient_kind > 1.5 {
+                t = length(self.v_pixel - self.gradient.xy) / max(self.gradient.z, 0.0001);
+            }
+            // the stops are color, gradient_color, gradient_color2 and gradient_color3, every
+            // mix only changes the color past the offset of the stop it starts at
+            let o = self.gradient_offsets;
+            let color = mix(self.color, self.gradient_color, clamp((t - o.x) / max(o.y - o.x, 0.0001), 0.0, 1.0));
+            color = mix(color, self.gradient_color2, clamp((t - o.y) / max(o.z - o.y, 0.0001), 0.0, 1.0));
+            return mix(color, self.gradient_color3, clamp((t - o.z) / max(o.w - o.z, 0.0001), 0.0, 1.0));
+        }
+        
         fn pixel(self) -> vec4 {
             let p_min = self.v_pixel.xy - 0.5;
             let p_max = self.v_pixel.xy + 0.5;
             let t_area = self.compute_clamped_trapezoid_area(p_min, p_max);
+            // painted shapes are blended over each other with premultiplied alpha
+            if self.chan > 3.5 {
+                let color = self.get_color();
+                return vec4(color.rgb * color.a, color.a) * t_area;
+            }
             if self.chan < 0.5 {
                 return vec4(t_area, 0., 0., 0.);
             }
@@ -117,11 +140,19 @@ live_design!{
 #[repr(C)]
 pub struct DrawTrapezoidVector {
     #[rust] pub trapezoidator: Trapezoidator,
+    #[rust] pub stroker: Stroker,
     #[live] pub geometry: GeometryQuad2D,
     #[deref] pub draw_vars: DrawVars,
     #[calc] pub a_xs: Vec2,
     #[calc] pub a_ys: Vec4,
     #[calc] pub chan: f32,
+    #[calc] pub color: Vec4,
+    #[calc] pub gradient_color: Vec4,
+    #[calc] pub gradient_color2: Vec4,
+    #[calc] pub gradient_color3: Vec4,
+    #[calc] pub gradient: Vec4,
+    #[calc] pub gradient_offsets: Vec4,
+    #[calc] pub gradient_kind: f32,
 }
 
 impl LiveHook for DrawTrapezoidVector{
@@ -0,0 +1,1213 @@
+//! Reads the subset of SVG that icons and illustrations use: paths and basic shapes, nested
+//! groups with transforms, per element fill, stroke and opacity, the `viewBox`, and linear and
+//! radial gradients. The result is a list of shapes that the icon atlas rasterizes.
+
+use {
+    std::collections::HashMap,
+    makepad_html::*,
+    crate::{
+        makepad_platform::*,
+        makepad_vector::geometry::{AffineTransformation, LinearTransformation, Point, Transform, Vector},
+        makepad_vector::path::PathCommand,
+        makepad_vector::stroker::{LineCap, LineJoin, StrokeStyle},
+        makepad_vector::trapezoidator::FillRule,
+    }
+};
+
+#[derive(Clone, Debug, Default)]
+pub struct SvgDocument {
+    pub view_box: Option<Rect>,
+    // the shapes in painting order, from bottom to top
+    pub shapes: Vec<SvgShape>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SvgShape {
+    // the outline in the user space of the element
+    pub path: Vec<PathCommand>,
+    // maps the user space of the element to the coordinates of the document
+    pub transform: AffineTransformation,
+    pub fill: Option<SvgPaint>,
+    pub fill_rule: FillRule,
+    pub stroke: Option<(SvgPaint, StrokeStyle)>,
+}
+
+#[derive(Clone, Debug)]
+pub enum SvgPaint {
+    Color(Vec4),
+    Gradient(SvgGradient),
+}
+
+// the stops are sorted by offset, there are at least two of them
+#[derive(Clone, Debug)]
+pub struct SvgGradient {
+    pub kind: SvgGradientKind,
+    pub stops: Vec<(f64, Vec4)>,
+}
+
+// the geometry of a gradient in the user space of the element it paints
+#[derive(Clone, Copy, Debug)]
+pub enum SvgGradientKind {
+    Linear {start: DVec2, end: DVec2},
+    Radial {center: DVec2, radius: f64},
+}
+
+impl SvgGradient {
+    // the color at `offset`, interpolated between the stops around it
+    pub fn color_at(&self, offset: f64) -> Vec4 {
+        let (first, last) = (self.stops[0], self.stops[self.stops.len() - 1]);
+        if offset <= first.0 {
+            return first.1
+        }
+        let Some(index) = self.stops.iter().position(|stop| stop.0 > offset) else {
+            return last.1
+        };
+        let (start, end) = (self.stops[index - 1], self.stops[index]);
+        let t = (offset - start.0) / (end.0 - start.0);
+        start.1 + (end.1 - start.1) * t as f32
+    }
+
+    // exactly `count` stops: padded with copies of the last stop if there are fewer, and
+    // sampled at even offsets between the first and last stop if there are more
+    pub fn fixed_stops(&self, count: usize) -> Vec<(f64, Vec4)> {
+        if self.stops.len() <= count {
+            let last = self.stops[self.stops.len() - 1];
+            return self.stops.iter().copied().chain(std::iter::repeat(last)).take(count).collect()
+        }
+        let (start, end) = (self.stops[0].0, self.stops[self.stops.len() - 1].0);
+        (0..count).map(|index| {
+            let offset = start + (end - start) * index as f64 / (count - 1) as f64;
+            (offset, self.color_at(offset))
+        }).collect()
+    }
+}
+
+impl SvgDocument {
+    // the view box if the document has one, and otherwise the hull of its shapes
+    pub fn bounds(&self) -> Option<Rect> {
+        if self.view_box.is_some() {
+            return self.view_box
+        }
+        self.shapes.iter().filter_map(|shape| shape.bounds()).reduce(|a, b| a.hull(b))
+    }
+}
+
+impl SvgShape {
+    pub fn new_filled(path: Vec<PathCommand>, color: Vec4) -> Self {
+        Self {
+            path,
+            transform: AffineTransformation::identity(),
+            fill: Some(SvgPaint::Color(color)),
+            fill_rule: FillRule::NonZero,
+            stroke: None,
+        }
+    }
+
+    // a conservative bounding box in document coordinates, built from the control points
+    pub fn bounds(&self) -> Option<Rect> {
+        let mut min = dvec2(f64::INFINITY, f64::INFINITY);
+        let mut max = dvec2(-f64::INFINITY, -f64::INFINITY);
+        let mut bound = |p: Point| {
+            let p = p.transform(&self.transform);
+            min = dvec2(min.x.min(p.x), min.y.min(p.y));
+            max = dvec2(max.x.max(p.x), max.y.max(p.y));
+        };
+        for cmd in &self.path {
+            match *cmd {
+                PathCommand::MoveTo(p) | PathCommand::LineTo(p) => bound(p),
+                PathCommand::ArcTo(e, r, _, _, _) => {
+                    // TODO: this is pretty rough
+                    bound(Point::new(e.x + r.x, e.y + r.y));
+                    bound(Point::new(e.x - r.x, e.y - r.y));
+                }
+                PathCommand::QuadraticTo(p1, p) => {
+                    bound(p1);
+                    bound(p);
+                }
+                PathCommand::CubicTo(p1, p2, p) => {
+                    bound(p1);
+                    bound(p2);
+                    bound(p);
+                }
+                PathCommand::Close => ()
+            }
+        }
+        if min.x > max.x {
+            return None
+        }
+        if let Some((_, style)) = &self.stroke {
+            let pad = style.width * 0.5 * transform_scale(&self.transform);
+            min -= dvec2(pad, pad);
+            max += dvec2(pad, pad);
+        }
+        Some(Rect {pos: min, size: max - min})
+    }
+}
+
+// returns the transform that applies `first` and then `second`
+pub fn concat_transforms(first: &AffineTransformation, second: &AffineTransformation) -> AffineTransformation {
+    AffineTransformation::new(
+        second.xy.compose(first.xy),
+        first.z.transform(&second.xy) + second.z
+    )
+}
+
+// the factor by which a transform scales areas, as a length
+pub fn transform_scale(transform: &AffineTransformation) -> f64 {
+    let xy = transform.xy;
+    (xy.x.x * xy.y.y - xy.x.y * xy.y.x).abs().sqrt()
+}
+
+#[derive(Clone, Debug)]
+enum PaintSpec {
+    None,
+    Color(Vec4),
+    CurrentColor,
+    Url(String, Option<Vec4>),
+}
+
+// the inherited properties of an element
+#[derive(Clone, Debug)]
+struct SvgState {
+    fill: PaintSpec,
+    fill_opacity: f64,
+    fill_rule: FillRule,
+    stroke: PaintSpec,
+    stroke_opacity: f64,
+    stroke_style: StrokeStyle,
+    color: Vec4,
+    // opacity is not inherited, but multiplying it down is close enough without layers
+    opacity: f64,
+    transform: AffineTransformation,
+}
+
+impl Default for SvgState {
+    fn default() -> Self {
+        Self {
+            fill: PaintSpec::Color(vec4(0.0, 0.0, 0.0, 1.0)),
+            fill_opacity: 1.0,
+            fill_rule: FillRule::NonZero,
+            stroke: PaintSpec::None,
+            stroke_opacity: 1.0,
+            stroke_style: StrokeStyle::default(),
+            color: vec4(0.0, 0.0, 0.0, 1.0),
+            opacity: 1.0,
+            transform: AffineTransformation::identity(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct GradientDef {
+    linear: bool,
+    coords: HashMap<LiveId, f64>,
+    user_space: Option<bool>,
+    transform: Option<AffineTransformation>,
+    stops: Vec<(f64, Vec4)>,
+    href: Option<String>,
+}
+
+// the attributes of an element, with its `style` attribute split into declarations which win
+// over attributes. HtmlWalker::find_attr_lc looks past the children of an element, so the
+// attributes are collected directly from the nodes that follow the open tag.
+struct SvgElement<'a> {
+    attrs: Vec<(LiveId, &'a str)>,
+    style: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> SvgElement<'a> {
+    fn new(doc: &'a HtmlDoc, node: &HtmlWalker) -> Self {
+        let attrs: Vec<(LiveId, &'a str)> = doc.nodes[node.index() + 1..].iter().map_while(|n| match n {
+            HtmlNode::Attribute {lc, start, end, ..} => Some((*lc, &doc.decoded[*start..*end])),
+            _ => None
+        }).collect();
+        let style = attrs.iter().find(|(lc, _)| *lc == live_id!(style)).map(|(_, style)| {
+            style.split(';').filter_map(|decl| {
+                let (name, value) = decl.split_once(':')?;
+                Some((name.trim(), value.trim()))
+            }).collect()
+        }).unwrap_or_default();
+        Self {attrs, style}
+    }
+
+    fn attr(&self, name: &str) -> Option<&'a str> {
+        let id = LiveId::from_str_lc(name);
+        self.attrs.iter().find(|(lc, _)| *lc == id).map(|(_, v)| v.trim())
+    }
+
+    fn property(&self, name: &str) -> Option<&'a str> {
+        self.style.iter().rev().find(|(n, _)| n.eq_ignore_ascii_case(name)).map(|(_, v)| *v)
+            .or_else(|| self.attr(name))
+            .filter(|v| *v != "inherit")
+    }
+
+    fn length(&self, name: &str, reference: f64) -> Option<f64> {
+        self.attr(name).and_then(|v| parse_length(v, reference))
+    }
+}
+
+pub fn parse_svg(data: &str) -> SvgDocument {
+    let mut errors = Some(Vec::new());
+    let doc = parse_html(data, &mut errors);
+    if !errors.as_ref().unwrap().is_empty() {
+        log!("SVG parser returned errors {:?}", errors)
+    }
+
+    let gradients = collect_gradients(&doc);
+
+    let mut out = SvgDocument::default();
+    let mut stack = vec![SvgState::default()];
+    let mut node = doc.new_walker();
+    while !node.done() {
+        if let Some(tag) = node.open_tag_lc() {
+            match tag {
+                live_id!(defs) | live_id!(clippath) | live_id!(mask) | live_id!(symbol) |
+                live_id!(pattern) | live_id!(marker) | live_id!(lineargradient) |
+                live_id!(radialgradient) | live_id!(style) | live_id!(title) | live_id!(desc) |
+                live_id!(metadata) | live_id!(text) => {
+                    node.jump_to_close();
+                    node.walk();
+                    continue;
+                }
+                _ => ()
+            }
+            let element = SvgElement::new(&doc, &node);
+            let parent = stack.last().unwrap();
+            if element.property("display") == Some("none") || element.property("visibility") == Some("hidden") {
+                node.jump_to_close();
+                node.walk();
+                continue;
+            }
+            let state = apply_properties(parent, &element);
+            if tag == live_id!(svg) && out.view_box.is_none() {
+                out.view_box = parse_view_box(&element);
+            }
+            let view_size = out.view_box.map(|r| r.size).unwrap_or(dvec2(1.0, 1.0));
+            let path = match tag {
+                live_id!(path) => match element.attr("d").map(|d| parse_svg_path(d.as_bytes())) {
+                    Some(Ok(path)) => Some(path),
+                    Some(Err(e)) => {
+                        log!("Error in SVG Path {}", e);
+                        None
+                    }
+                    None => None
+                },
+                live_id!(rect) => rect_path(&element, view_size),
+                live_id!(circle) => {
+                    let r = element.length("r", view_size.length() / 2f64.sqrt()).unwrap_or(0.0);
+                    ellipse_path(&element, view_size, r, r)
+                }
+                live_id!(ellipse) => {
+                    let rx = element.length("rx", view_size.x).unwrap_or(0.0);
+                    let ry = element.length("ry", view_size.y).unwrap_or(0.0);
+                    ellipse_path(&element, view_size, rx, ry)
+                }
+                live_id!(line) => {
+                    let p0 = Point::new(element.length("x1", view_size.x).unwrap_or(0.0), element.length("y1", view_size.y).unwrap_or(0.0));
+                    let p1 = Point::new(element.length("x2", view_size.x).unwrap_or(0.0), element.length("y2", view_size.y).unwrap_or(0.0));
+                    Some(vec![PathCommand::MoveTo(p0), PathCommand::LineTo(p1)])
+                }
+                live_id!(polyline) | live_id!(polygon) => {
+                    let nums = parse_numbers(element.attr("points").unwrap_or(""));
+                    let mut path: Vec<PathCommand> = nums.chunks_exact(2).enumerate().map(|(i, p)| {
+                        let p = Point::new(p[0], p[1]);
+                        if i == 0 {PathCommand::MoveTo(p)} else {PathCommand::LineTo(p)}
+                    }).collect();
+                    if tag == live_id!(polygon) && !path.is_empty() {
+                        path.push(PathCommand::Close);
+                    }
+                    Some(path)
+                }
+                _ => None
+            };
+            if let Some(path) = path.filter(|path| !path.is_empty()) {
+                if let Some(shape) = build_shape(&state, path, &gradients) {
+                    out.shapes.push(shape);
+                }
+            }
+            stack.push(state);
+        }
+        if node.close_tag_lc().is_some() && stack.len() > 1 {
+            stack.pop();
+        }
+        node.walk();
+    }
+    out
+}
+
+fn apply_properties(parent: &SvgState, element: &SvgElement) -> SvgState {
+    let mut state = parent.clone();
+    if let Some(transform) = element.attr("transform") {
+        state.transform = concat_transforms(&parse_transform(transform), &parent.transform);
+    }
+    if let Some(color) = element.property("color").and_then(parse_color) {
+        state.color = color;
+    }
+    if let Some(fill) = element.property("fill").and_then(parse_paint) {
+        state.fill = fill;
+    }
+    if let Some(stroke) = element.property("stroke").and_then(parse_paint) {
+        state.stroke = stroke;
+    }
+    if let Some(v) = element.property("fill-opacity").and_then(parse_opacity) {
+        state.fill_opacity = v;
+    }
+    if let Some(v) = element.property("stroke-opacity").and_then(parse_opacity) {
+        state.stroke_opacity = v;
+    }
+    if let Some(v) = element.property("opacity").and_then(parse_opacity) {
+        state.opacity *= v;
+    }
+    match element.property("fill-rule") {
+        Some("evenodd") => state.fill_rule = FillRule::EvenOdd,
+        Some("nonzero") => state.fill_rule = FillRule::NonZero,
+        _ => ()
+    }
+    let style = &mut state.stroke_style;
+    if let Some(v) = element.property("stroke-width").and_then(|v| parse_length(v, 1.0)) {
+        style.width = v;
+    }
+    match element.property("stroke-linejoin") {
+        Some("miter") => style.join = LineJoin::Miter,
+        Some("round") => style.join = LineJoin::Round,
+        Some("bevel") => style.join = LineJoin::Bevel,
+        _ => ()
+    }
+    match element.property("stroke-linecap") {
+        Some("butt") => style.cap = LineCap::Butt,
+        Some("round") => style.cap = LineCap::Round,
+        Some("square") => style.cap = LineCap::Square,
+        _ => ()
+    }
+    if let Some(v) = element.property("stroke-miterlimit").and_then(|v| v.parse().ok()) {
+        style.miter_limit = v;
+    }
+    if let Some(v) = element.property("stroke-dasharray") {
+        style.dashes = if v == "none" {Vec::new()} else {parse_numbers(v)};
+        if style.dashes.iter().any(|d| *d < 0.0) || style.dashes.iter().all(|d| *d == 0.0) {
+            style.dashes.clear();
+        }
+    }
+    if let Some(v) = element.property("stroke-dashoffset").and_then(|v| parse_length(v, 1.0)) {
+        style.dash_offset = v;
+    }
+    state
+}
+
+fn build_shape(state: &SvgState, path: Vec<PathCommand>, gradients: &HashMap<String, GradientDef>) -> Option<SvgShape> {
+    let mut shape = SvgShape {
+        path,
+        transform: state.transform,
+        fill: None,
+        fill_rule: state.fill_rule,
+        stroke: None,
+    };
+    // gradients in bounding box units are relative to the untransformed shape
+    let bbox = SvgShape {transform: AffineTransformation::identity(), ..shape.clone()}.bounds()?;
+    shape.fill = resolve_paint(&state.fill, state.fill_opacity * state.opacity, state.color, bbox, gradients);
+    if state.stroke_style.width > 0.0 {
+        shape.stroke = resolve_paint(&state.stroke, state.stroke_opacity * state.opacity, state.color, bbox, gradients)
+            .map(|paint| (paint, state.stroke_style.clone()));
+    }
+    if shape.fill.is_none() && shape.stroke.is_none() {
+        return None
+    }
+    Some(shape)
+}
+
+fn resolve_paint(spec: &PaintSpec, opacity: f64, color: Vec4, bbox: Rect, gradients: &HashMap<String, GradientDef>) -> Option<SvgPaint> {
+    let with_opacity = |c: Vec4| vec4(c.x, c.y, c.z, c.w * opacity as f32);
+    match spec {
+        PaintSpec::None => None,
+        PaintSpec::Color(c) => Some(SvgPaint::Color(with_opacity(*c))),
+        PaintSpec::CurrentColor => Some(SvgPaint::Color(with_opacity(color))),
+        PaintSpec::Url(id, fallback) => {
+            let Some(def) = resolve_gradient(id, gradients) else {
+                return fallback.map(|c| SvgPaint::Color(with_opacity(c)))
+            };
+            let first = *def.stops.first()?;
+            if def.stops.len() == 1 {
+                return Some(SvgPaint::Color(with_opacity(first.1)))
+            }
+            let coord = |id: LiveId, default: f64| def.coords.get(&id).copied().unwrap_or(default);
+            let mut transform = def.transform.unwrap_or(AffineTransformation::identity());
+            if !def.user_space.unwrap_or(false) {
+                transform = concat_transforms(
+                    &transform,
+                    &AffineTransformation::identity()
+                        .scale(Vector::new(bbox.size.x, bbox.size.y))
+                        .translate(Vector::new(bbox.pos.x, bbox.pos.y))
+                );
+            }
+            let map = |x: f64, y: f64| {
+                let p = Point::new(x, y).transform(&transform);
+                dvec2(p.x, p.y)
+            };
+            let kind = if def.linear {
+                SvgGradientKind::Linear {
+                    start: map(coord(live_id!(x1), 0.0), coord(live_id!(y1), 0.0)),
+                    end: map(coord(live_id!(x2), 1.0), coord(live_id!(y2), 0.0)),
+                }
+            }
+            else {
+                SvgGradientKind::Radial {
+                    center: map(coord(live_id!(cx), 0.5), coord(live_id!(cy), 0.5)),
+                    radius: coord(live_id!(r), 0.5) * transform_scale(&transform),
+                }
+            };
+            Some(SvgPaint::Gradient(SvgGradient {
+                kind,
+                stops: def.stops.iter().map(|(offset, color)| (*offset, with_opacity(*color))).collect(),
+            }))
+        }
+    }
+}
+
+// merges a gradient with the gradients it references through `href`
+fn resolve_gradient(id: &str, gradients: &HashMap<String, GradientDef>) -> Option<GradientDef> {
+    let mut def = gradients.get(id)?.clone();
+    let mut href = def.href.clone();
+    let mut depth = 0;
+    while let Some(base) = href.and_then(|id| gradients.get(&id)) {
+        if def.stops.is_empty() {
+            def.stops = base.stops.clone();
+        }
+        if base.linear == def.linear {
+            for (id, v) in &base.coords {
+                def.coords.entry(*id).or_insert(*v);
+            }
+        }
+        def.user_space = def.user_space.or(base.user_space);
+        def.transform = def.transform.or(base.transform);
+        href = base.href.clone();
+        depth += 1;
+        if depth > 8 {
+            break;
+        }
+    }
+    Some(def)
+}
+
+fn collect_gradients(doc: &HtmlDoc) -> HashMap<String, GradientDef> {
+    let mut gradients = HashMap::new();
+    let mut current: Option<(String, GradientDef)> = None;
+    let mut node = doc.new_walker();
+    while !node.done() {
+        match node.open_tag_lc() {
+            Some(tag @ (live_id!(lineargradient) | live_id!(radialgradient))) => {
+                let element = SvgElement::new(doc, &node);
+                let linear = tag == live_id!(lineargradient);
+                let mut def = GradientDef {linear, ..Default::default()};
+                let names: &[&str] = if linear {&["x1", "y1", "x2", "y2"]} else {&["cx", "cy", "r"]};
+                for name in names {
+                    if let Some(v) = element.length(name, 1.0) {
+                        def.coords.insert(LiveId::from_str(name), v);
+                    }
+                }
+                def.user_space = element.attr("gradientUnits").map(|v| v == "userSpaceOnUse");
+                def.transform = element.attr("gradientTransform").map(parse_transform);
+                def.href = element.attr("href").or_else(|| element.attr("xlink:href"))
+                    .and_then(|v| v.strip_prefix('#')).map(|v| v.to_string());
+                current = element.attr("id").map(|id| (id.to_string(), def));
+            }
+            Some(live_id!(stop)) => if let Some((_, def)) = &mut current {
+                let element = SvgElement::new(doc, &node);
+                let offset = element.attr("offset").and_then(|v| parse_length(v, 1.0)).unwrap_or(0.0).clamp(0.0, 1.0);
+                // offsets never decrease
+                let offset = offset.max(def.stops.last().map(|s| s.0).unwrap_or(0.0));
+                let mut color = element.property("stop-color").and_then(parse_color).unwrap_or(vec4(0.0, 0.0, 0.0, 1.0));
+                color.w *= element.property("stop-opacity").and_then(parse_opacity).unwrap_or(1.0) as f32;
+                def.stops.push((offset, color));
+            }
+            _ => ()
+        }
+        if let Some(live_id!(lineargradient) | live_id!(radialgradient)) = node.close_tag_lc() {
+            if let Some((id, def)) = current.take() {
+                gradients.insert(id, def);
+            }
+        }
+        node.walk();
+    }
+    gradients
+}
+
+fn parse_view_box(element: &SvgElement) -> Option<Rect> {
+    let nums = parse_numbers(element.attr("viewBox")?);
+    if nums.len() != 4 || nums[2] <= 0.0 || nums[3] <= 0.0 {
+        return None
+    }
+    Some(Rect {pos: dvec2(nums[0], nums[1]), size: dvec2(nums[2], nums[3])})
+}
+
+// the distance of the control points of a cubic curve that approximates a quarter ellipse
+const KAPPA: f64 = 0.5522847498;
+
+fn rect_path(element: &SvgElement, view_size: DVec2) -> Option<Vec<PathCommand>> {
+    let x = element.length("x", view_size.x).unwrap_or(0.0);
+    let y = element.length("y", view_size.y).unwrap_or(0.0);
+    let w = element.length("width", view_size.x)?;
+    let h = element.length("height", view_size.y)?;
+    if w <= 0.0 || h <= 0.0 {
+        return None
+    }
+    let rx = element.length("rx", view_size.x);
+    let ry = element.length("ry", view_size.y);
+    let rx = rx.or(ry).unwrap_or(0.0).clamp(0.0, w * 0.5);
+    let ry = ry.or(Some(rx)).unwrap_or(0.0).clamp(0.0, h * 0.5);
+    let p = |x: f64, y: f64| Point::new(x, y);
+    if rx == 0.0 || ry == 0.0 {
+        return Some(vec![
+            PathCommand::MoveTo(p(x, y)),
+            PathCommand::LineTo(p(x + w, y)),
+            PathCommand::LineTo(p(x + w, y + h)),
+            PathCommand::LineTo(p(x, y + h)),
+            PathCommand::Close,
+        ])
+    }
+    let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+    let (x1, y1) = (x + w, y + h);
+    Some(vec![
+        PathCommand::MoveTo(p(x + rx, y)),
+        PathCommand::LineTo(p(x1 - rx, y)),
+        PathCommand::CubicTo(p(x1 - rx + kx, y), p(x1, y + ry - ky), p(x1, y + ry)),
+        PathCommand::LineTo(p(x1, y1 - ry)),
+        PathCommand::CubicTo(p(x1, y1 - ry + ky), p(x1 - rx + kx, y1), p(x1 - rx, y1)),
+        PathCommand::LineTo(p(x + rx, y1)),
+        PathCommand::CubicTo(p(x + rx - kx, y1), p(x, y1 - ry + ky), p(x, y1 - ry)),
+        PathCommand::LineTo(p(x, y + ry)),
+        PathCommand::CubicTo(p(x, y + ry - ky), p(x + rx - kx, y), p(x + rx, y)),
+        PathCommand::Close,
+    ])
+}
+
+fn ellipse_path(element: &SvgElement, view_size: DVec2, rx: f64, ry: f64) -> Option<Vec<PathCommand>> {
+    if rx <= 0.0 || ry <= 0.0 {
+        return None
+    }
+    let cx = element.length("cx", view_size.x).unwrap_or(0.0);
+    let cy = element.length("cy", view_size.y).unwrap_or(0.0);
+    let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+    let p = |x: f64, y: f64| Point::new(cx + x, cy + y);
+    Some(vec![
+        PathCommand::MoveTo(p(rx, 0.0)),
+        PathCommand::CubicTo(p(rx, ky), p(kx, ry), p(0.0, ry)),
+        PathCommand::CubicTo(p(-kx, ry), p(-rx, ky), p(-rx, 0.0)),
+        PathCommand::CubicTo(p(-rx, -ky), p(-kx, -ry), p(0.0, -ry)),
+        PathCommand::CubicTo(p(kx, -ry), p(rx, -ky), p(rx, 0.0)),
+        PathCommand::Close,
+    ])
+}
+
+fn parse_numbers(value: &str) -> Vec<f64> {
+    value
+        .split(|c: char| c == ',' || c.is_ascii_whitespace())
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| s.parse().ok())
+        .collect()
+}
+
+// parses a number with an optional unit, percentages are relative to `reference`
+fn parse_length(value: &str, reference: f64) -> Option<f64> {
+    let value = value.trim();
+    if let Some(percent) = value.strip_suffix('%') {
+        return percent.trim().parse::<f64>().ok().map(|v| v * 0.01 * reference)
+    }
+    let value = value.strip_suffix("px").unwrap_or(value);
+    value.trim().parse().ok()
+}
+
+fn parse_opacity(value: &str) -> Option<f64> {
+    parse_length(value, 1.0).map(|v| v.clamp(0.0, 1.0))
+}
+
+fn parse_paint(value: &str) -> Option<PaintSpec> {
+    let value = value.trim();
+    if value == "none" {
+        return Some(PaintSpec::None)
+    }
+    if value == "currentColor" {
+        return Some(PaintSpec::CurrentColor)
+    }
+    if let Some(rest) = value.strip_prefix("url(") {
+        let (url, fallback) = rest.split_once(')')?;
+        let id = url.trim().trim_matches(|c| c == '\'' || c == '"').strip_prefix('#')?;
+        return Some(PaintSpec::Url(id.to_string(), parse_color(fallback)))
+    }
+    parse_color(value).map(PaintSpec::Color)
+}
+
+fn parse_color(value: &str) -> Option<Vec4> {
+    let value = value.trim();
+    if value.starts_with('#') {
+        return Vec4::from_hex_str(value).ok()
+    }
+    if let Some(args) = value.strip_prefix("rgba(").or_else(|| value.strip_prefix("rgb(")) {
+        let args: Vec<&str> = args.trim_end_matches(')').split(',').map(|s| s.trim()).collect();
+        if args.len() < 3 {
+            return None
+        }
+        let channel = |s: &str| parse_length(s, 255.0).map(|v| (v / 255.0).clamp(0.0, 1.0) as f32);
+        let alpha = args.get(3).and_then(|s| parse_opacity(s)).unwrap_or(1.0) as f32;
+        return Some(vec4(channel(args[0])?, channel(args[1])?, channel(args[2])?, alpha))
+    }
+    let rgb = match value.to_ascii_lowercase().as_str() {
+        "transparent" => return Some(vec4(0.0, 0.0, 0.0, 0.0)),
+        "black" => 0x000000,
+        "white" => 0xffffff,
+        "red" => 0xff0000,
+        "lime" => 0x00ff00,
+        "green" => 0x008000,
+        "blue" => 0x0000ff,
+        "yellow" => 0xffff00,
+        "cyan" | "aqua" => 0x00ffff,
+        "magenta" | "fuchsia" => 0xff00ff,
+        "gray" | "grey" => 0x808080,
+        "silver" => 0xc0c0c0,
+        "maroon" => 0x800000,
+        "olive" => 0x808000,
+        "navy" => 0x000080,
+        "purple" => 0x800080,
+        "teal" => 0x008080,
+        "orange" => 0xffa500,
+        _ => return None
+    };
+    Some(Vec4::from_u32((rgb << 8) | 0xff))
+}
+
+fn parse_transform(value: &str) -> AffineTransformation {
+    let mut transform = AffineTransformation::identity();
+    let mut rest = value;
+    while let Some((name, tail)) = rest.split_once('(') {
+        let Some((args, tail)) = tail.split_once(')') else {
+            break
+        };
+        rest = tail;
+        let a = parse_numbers(args);
+        let arg = |i: usize, default: f64| a.get(i).copied().unwrap_or(default);
+        let local = match name.trim().trim_start_matches(',').trim() {
+            "matrix" if a.len() == 6 => AffineTransformation::new(
+                LinearTransformation::new(Vector::new(a[0], a[1]), Vector::new(a[2], a[3])),
+                Vector::new(a[4], a[5])
+            ),
+            "translate" => AffineTransformation::translation(Vector::new(arg(0, 0.0), arg(1, 0.0))),
+            "scale" => AffineTransformation::scaling(Vector::new(arg(0, 1.0), arg(1, arg(0, 1.0)))),
+            "rotate" => {
+                let center = Vector::new(arg(1, 0.0), arg(2, 0.0));
+                AffineTransformation::translation(-center)
+                    .rotate(arg(0, 0.0).to_radians())
+                    .translate(center)
+            }
+            "skewX" => AffineTransformation::new(
+                LinearTransformation::new(Vector::new(1.0, 0.0), Vector::new(arg(0, 0.0).to_radians().tan(), 1.0)),
+                Vector::zero()
+            ),
+            "skewY" => AffineTransformation::new(
+                LinearTransformation::new(Vector::new(1.0, arg(0, 0.0).to_radians().tan()), Vector::new(0.0, 1.0)),
+                Vector::zero()
+            ),
+            _ => continue
+        };
+        // the transforms in the list apply from right to left
+        transform = concat_transforms(&local, &transform);
+    }
+    transform
+}
+
+pub fn parse_svg_path(path: &[u8]) -> Result<Vec<PathCommand>, String> {
+    #[derive(Debug)]
+    enum Cmd {
+        Unknown,
+        Move(bool),
+        Hor(bool),
+        Vert(bool),
+        Line(bool),
+        Arc(bool),
+        Cubic(bool),
+        Quadratic(bool),
+        SmoothCubic(bool),
+        SmoothQuadratic(bool),
+        Close
+    }
+    impl Default for Cmd {fn default() -> Self {Self::Unknown}}
+    
+    #[derive(Default)]
+    struct ParseState {
+        cmd: Cmd,
+        expect_nums: usize,
+        chain: bool,
+        nums: [f64; 7],
+        num_count: usize,
+        last_pt: Point,
+        // the last control point of the previous curve, and whether it was cubic
+        last_ctrl: Option<(Point, bool)>,
+        first_pt: Point,
+        out: Vec<PathCommand>,
+        num_state: Option<NumState>
+    }
+    
+    #[derive(Debug)]
+    struct NumState {
+        num: f64,
+        mul: f64,
+        has_dot: bool,
+    }
+    
+    impl NumState {
+        fn new_pos(v: f64) -> Self {Self {num: v, mul: 1.0, has_dot: false}}
+        fn new_min() -> Self {Self {num: 0.0, mul: -1.0, has_dot: false}}
+        fn finalize(self) -> f64 {self.num * self.mul}
+        fn add_digit(&mut self, digit: f64) {
+            self.num *= 10.0;
+            self.num += digit;
+            if self.has_dot {
+                self.mul *= 0.1;
+            }
+        }
+    }
+    
+    impl ParseState {
+        fn next_cmd(&mut self, cmd: Cmd) -> Result<(), String> {
+            self.finalize_cmd() ?;
+            self.chain = false;
+            self.expect_nums = match cmd {
+                Cmd::Unknown => panic!(),
+                Cmd::Move(_) => 2,
+                Cmd::Hor(_) => 1,
+                Cmd::Vert(_) => 1,
+                Cmd::Line(_) => 2,
+                Cmd::Cubic(_) => 6,
+                Cmd::Arc(_) => 7,
+                Cmd::Quadratic(_) => 4,
+                Cmd::SmoothCubic(_) => 4,
+                Cmd::SmoothQuadratic(_) => 2,
+                Cmd::Close => 0
+            };
+            self.cmd = cmd;
+            Ok(())
+        }
+        
+        fn add_min(&mut self) -> Result<(), String> {
+            if self.num_state.is_some() {
+                self.finalize_num();
+            }
+            if self.expect_nums == self.num_count {
+                self.finalize_cmd() ?;
+            }
+            if self.expect_nums == 0 {
+                return Err(format!("Unexpected minus"));
+            }
+            self.num_state = Some(NumState::new_min());
+            Ok(())
+        }
+        
+        fn add_digit(&mut self, digit: f64) -> Result<(), String> {
+            if let Some(num_state) = &mut self.num_state {
+                num_state.add_digit(digit);
+            }
+            else {
+                if self.expect_nums == self.num_count {
+                    self.finalize_cmd() ?;
+                }
+                if self.expect_nums == 0 {
+                    return Err(format!("Unexpected digit"));
+                }
+                self.num_state = Some(NumState::new_pos(digit))
+            }
+            Ok(())
+        }
+        
+        fn add_dot(&mut self) -> Result<(), String> {
+            if let Some(num_state) = &mut self.num_state {
+                if num_state.has_dot {
+                    self.finalize_num();
+                    self.add_digit(0.0) ?;
+                    self.add_dot() ?;
+                    return Ok(());
+                }
+                num_state.has_dot = true;
+            }
+            else {
+                self.add_digit(0.0) ?;
+                self.add_dot() ?;
+            }
+            Ok(())
+        }
+        
+        fn finalize_num(&mut self) {
+            if let Some(num_state) = self.num_state.take() {
+                self.nums[self.num_count] = num_state.finalize();
+                self.num_count += 1;
+            }
+        }
+        
+        fn whitespace(&mut self) -> Result<(), String> {
+            self.finalize_num();
+            if self.expect_nums == self.num_count {
+                self.finalize_cmd() ?;
+            }
+            Ok(())
+        }
+        
+        fn reflected_ctrl(&self, cubic: bool) -> Point {
+            match self.last_ctrl {
+                Some((ctrl, was_cubic)) if was_cubic == cubic => self.last_pt + (self.last_pt - ctrl),
+                _ => self.last_pt
+            }
+        }
+        
+        fn finalize_cmd(&mut self) -> Result<(), String> {
+            self.finalize_num();
+            if self.chain && self.num_count == 0 {
+                return Ok(())
+            }
+            if self.expect_nums != self.num_count {
+                return Err(format!("SVG Path command {:?} expected {} points, got {}", self.cmd, self.expect_nums, self.num_count));
+            }
+            match self.cmd {
+                Cmd::Unknown => (),
+                Cmd::Move(abs) => {
+                    
+                    if abs {
+                        self.last_pt = Point {x: self.nums[0], y: self.nums[1]};
+                    }
+                    else {
+                        self.last_pt += Vector {x: self.nums[0], y: self.nums[1]};
+                    }
+                    self.first_pt = self.last_pt;
+                    self.out.push(PathCommand::MoveTo(self.last_pt));
+                },
+                Cmd::Hor(abs) => {
+                    if abs {
+                        self.last_pt = Point {x: self.nums[0], y: self.last_pt.y};
+                    }
+                    else {
+                        self.last_pt += Vector {x: self.nums[0], y: 0.0};
+                    }
+                    self.out.push(PathCommand::LineTo(self.last_pt));
+                }
+                Cmd::Vert(abs) => {
+                    if abs {
+                        self.last_pt = Point {x: self.last_pt.x, y: self.nums[0]};
+                    }
+                    else {
+                        self.last_pt += Vector {x: 0.0, y: self.nums[0]};
+                    }
+                    self.out.push(PathCommand::LineTo(self.last_pt));
+                }
+                Cmd::Line(abs) => {
+                    if abs {
+                        self.last_pt = Point {x: self.nums[0], y: self.nums[1]};
+                    }
+                    else {
+                        self.last_pt += Vector {x: self.nums[0], y: self.nums[1]};
+                    }
+                    self.out.push(PathCommand::LineTo(self.last_pt));
+                },
+                Cmd::Cubic(abs) => {
+                    if abs {
+                        self.last_pt = Point {x: self.nums[4], y: self.nums[5]};
+                        self.out.push(PathCommand::CubicTo(
+                            Point {x: self.nums[0], y: self.nums[1]},
+                            Point {x: self.nums[2], y: self.nums[3]},
+                            self.last_pt,
+                        ));
+                    } else {
+                        self.out.push(PathCommand::CubicTo(
+                            self.last_pt + Vector {x: self.nums[0], y: self.nums[1]},
+                            self.last_pt + Vector {x: self.nums[2], y: self.nums[3]},
+                            self.last_pt + Vector {x: self.nums[4], y: self.nums[5]},
+                        ));
+                        self.last_pt += Vector {x: self.nums[4], y: self.nums[5]};
+                    }
+                },
+                Cmd::Arc(abs) => {
+                    if abs {
+                        self.last_pt = Point {x: self.nums[5], y: self.nums[6]};
+                        self.out.push(PathCommand::ArcTo(
+                            self.last_pt,
+                            Point {x: self.nums[0], y: self.nums[1]},
+                            self.nums[2],
+                            self.nums[3] != 0.0,
+                            self.nums[4] != 0.0,
+                        ));
+                    }
+                    else {
+                        self.out.push(PathCommand::ArcTo(
+                            self.last_pt + Vector {x: self.nums[5], y: self.nums[6]},
+                            Point {x: self.nums[0], y: self.nums[1]},
+                            self.nums[2],
+                            self.nums[3] != 0.0,
+                            self.nums[4] != 0.0,
+                        ));
+                        self.last_pt += Vector {x: self.nums[5], y: self.nums[6]};
+                    }
+                },
+                Cmd::Quadratic(abs) => {
+                    if abs {
+                        self.last_pt = Point {x: self.nums[2], y: self.nums[3]};
+                        self.out.push(PathCommand::QuadraticTo(
+                            Point {x: self.nums[0], y: self.nums[1]},
+                            self.last_pt
+                        ));
+                    }
+                    else {
+                        self.out.push(PathCommand::QuadraticTo(
+                            self.last_pt + Vector {x: self.nums[0], y: self.nums[1]},
+                            self.last_pt + Vector {x: self.nums[2], y: self.nums[3]},
+                        ));
+                        self.last_pt += Vector {x: self.nums[2], y: self.nums[3]};
+                    }
+                }
+                Cmd::SmoothCubic(abs) => {
+                    let p1 = self.reflected_ctrl(true);
+                    let (p2, p) = if abs {
+                        (Point {x: self.nums[0], y: self.nums[1]}, Point {x: self.nums[2], y: self.nums[3]})
+                    }
+                    else {
+                        (self.last_pt + Vector {x: self.nums[0], y: self.nums[1]}, self.last_pt + Vector {x: self.nums[2], y: self.nums[3]})
+                    };
+                    self.last_pt = p;
+                    self.out.push(PathCommand::CubicTo(p1, p2, p));
+                }
+                Cmd::SmoothQuadratic(abs) => {
+                    let p1 = self.reflected_ctrl(false);
+                    let p = if abs {
+                        Point {x: self.nums[0], y: self.nums[1]}
+                    }
+                    else {
+                        self.last_pt + Vector {x: self.nums[0], y: self.nums[1]}
+                    };
+                    self.last_pt = p;
+                    self.out.push(PathCommand::QuadraticTo(p1, p));
+                }
+                Cmd::Close => {
+                    self.last_pt = self.first_pt;
+                    self.out.push(PathCommand::Close);
+                }
+            }
+            self.last_ctrl = match self.out.last() {
+                Some(PathCommand::CubicTo(_, p2, _)) => Some((*p2, true)),
+                Some(PathCommand::QuadraticTo(p1, _)) => Some((*p1, false)),
+                _ => None
+            };
+            self.num_count = 0;
+            self.chain = true;
+            Ok(())
+        }
+    }
+    
+    let mut state = ParseState::default();
+    
+    for i in 0..path.len() {
+        match path[i] {
+            b'M' => state.next_cmd(Cmd::Move(true)) ?,
+            b'm' => state.next_cmd(Cmd::Move(false)) ?,
+            b'Q' => state.next_cmd(Cmd::Quadratic(true)) ?,
+            b'q' => state.next_cmd(Cmd::Quadratic(false)) ?,
+            b'S' => state.next_cmd(Cmd::SmoothCubic(true)) ?,
+            b's' => state.next_cmd(Cmd::SmoothCubic(false)) ?,
+            b'T' => state.next_cmd(Cmd::SmoothQuadratic(true)) ?,
+            b't' => state.next_cmd(Cmd::SmoothQuadratic(false)) ?,
+            b'C' => state.next_cmd(Cmd::Cubic(true)) ?,
+            b'c' => state.next_cmd(Cmd::Cubic(false)) ?,
+            b'H' => state.next_cmd(Cmd::Hor(true)) ?,
+            b'h' => state.next_cmd(Cmd::Hor(false)) ?,
+            b'V' => state.next_cmd(Cmd::Vert(true)) ?,
+            b'v' => state.next_cmd(Cmd::Vert(false)) ?,
+            b'L' => state.next_cmd(Cmd::Line(true)) ?,
+            b'l' => state.next_cmd(Cmd::Line(false)) ?,
+            b'A' => state.next_cmd(Cmd::Arc(true)) ?,
+            b'a' => state.next_cmd(Cmd::Arc(false)) ?,
+            b'Z' | b'z' => state.next_cmd(Cmd::Close) ?,
+            b'-' => state.add_min() ?,
+            b'0'..=b'9' => state.add_digit((path[i] - b'0') as f64) ?,
+            b'.' => state.add_dot() ?,
+            b',' | b' ' | b'\r' | b'\n' | b'\t' => state.whitespace() ?,
+            x => {
+                return Err(format!("Unexpected character {} - {}", x, x as char))
+            }
+        }
+    }
+    state.finalize_cmd() ?;
+    
+    Ok(state.out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn map(transform: &AffineTransformation, x: f64, y: f64) -> (f64, f64) {
+        let p = Point::new(x, y).transform(transform);
+        ((p.x * 1000.0).round() / 1000.0, (p.y * 1000.0).round() / 1000.0)
+    }
+    
+    fn rect_of(shape: &SvgShape) -> (f64, f64, f64, f64) {
+        let r = shape.bounds().unwrap();
+        (r.pos.x, r.pos.y, r.size.x, r.size.y)
+    }
+    
+    fn fill_color(shape: &SvgShape) -> Vec4 {
+        match shape.fill {
+            Some(SvgPaint::Color(color)) => color,
+            ref fill => panic!("not a color fill: {:?}", fill)
+        }
+    }
+    
+    fn assert_color(color: Vec4, expected: Vec4) {
+        let d = color - expected;
+        let close = d.x.abs().max(d.y.abs()).max(d.z.abs()).max(d.w.abs()) < 1e-3;
+        assert!(close, "{:?} is not {:?}", color, expected);
+    }
+    
+    #[test]
+    fn parses_transform_lists() {
+        assert_eq!(map(&parse_transform("translate(10, 20)"), 1.0, 1.0), (11.0, 21.0));
+        assert_eq!(map(&parse_transform("scale(2) translate(10)"), 1.0, 1.0), (22.0, 2.0));
+        assert_eq!(map(&parse_transform("translate(10) scale(2, 3)"), 1.0, 1.0), (12.0, 3.0));
+        assert_eq!(map(&parse_transform("rotate(90 5 5)"), 10.0, 5.0), (5.0, 10.0));
+        assert_eq!(map(&parse_transform("matrix(1 0 0 1 3 4)"), 1.0, 1.0), (4.0, 5.0));
+        assert_eq!(map(&parse_transform("skewX(45)"), 0.0, 1.0), (1.0, 1.0));
+        assert_eq!(map(&parse_transform("translate(1,1), bogus(3), scale(2)"), 1.0, 1.0), (3.0, 3.0));
+    }
+    
+    #[test]
+    fn applies_nested_transforms_to_shapes() {
+        let doc = parse_svg(r#"<svg><g transform="translate(10 20)"><rect transform="scale(2)" x="1" y="1" width="1" height="1"/></g></svg>"#);
+        assert_eq!(doc.shapes.len(), 1);
+        assert_eq!(map(&doc.shapes[0].transform, 1.0, 1.0), (12.0, 22.0));
+        assert_eq!(rect_of(&doc.shapes[0]), (12.0, 22.0, 2.0, 2.0));
+    }
+    
+    #[test]
+    fn builds_paths_for_the_basic_shapes() {
+        let doc = parse_svg(r#"<svg viewBox="0 0 200 100">
+            <rect x="1" y="2" width="3" height="4"/>
+            <rect width="50%" height="50%" rx="5"/>
+            <rect width="0" height="4"/>
+            <circle cx="10" cy="10" r="5"/>
+            <circle cx="10" cy="10" r="0"/>
+            <ellipse cx="10" cy="10" rx="5" ry="2"/>
+            <line x1="0" y1="0" x2="5" y2="5" stroke="black"/>
+            <polygon points="0,0 10,0 10,10"/>
+            <polyline points="0 0 10 0 10 10" fill="none" stroke="black"/>
+            <path d="M 0 0 L 10 0 L 10 10 Z"/>
+        </svg>"#);
+        assert_eq!(doc.view_box.map(|r| (r.size.x, r.size.y)), Some((200.0, 100.0)));
+        let rects: Vec<_> = doc.shapes.iter().map(|shape| rect_of(&SvgShape {stroke: None, ..shape.clone()})).collect();
+        assert_eq!(rects, [
+            (1.0, 2.0, 3.0, 4.0),
+            (0.0, 0.0, 100.0, 50.0),
+            (5.0, 5.0, 10.0, 10.0),
+            (5.0, 8.0, 10.0, 4.0),
+            (0.0, 0.0, 5.0, 5.0),
+            (0.0, 0.0, 10.0, 10.0),
+            (0.0, 0.0, 10.0, 10.0),
+            (0.0, 0.0, 10.0, 10.0),
+        ]);
+        assert_eq!(doc.shapes[0].path.len(), 5);
+        assert!(doc.shapes[1].path.iter().any(|cmd| matches!(cmd, PathCommand::CubicTo(..))));
+        assert!(matches!(doc.shapes[5].path.last(), Some(PathCommand::Close)));
+        assert!(!matches!(doc.shapes[6].path.last(), Some(PathCommand::Close)));
+        // lines and polylines without a fill only have a stroke
+        assert!(doc.shapes[4].fill.is_some() && doc.shapes[6].fill.is_none());
+        assert!(doc.shapes[6].stroke.is_some());
+    }
+    
+    #[test]
+    fn style_declarations_win_over_attributes_and_inherit() {
+        let doc = parse_svg(r#"<svg>
+            <rect width="1" height="1" fill="red" style="fill: blue"/>
+            <rect width="1" height="1" style="fill: red; fill: lime"/>
+            <g fill="red" style="fill: blue" opacity="0.5">
+                <rect width="1" height="1"/>
+                <rect width="1" height="1" fill="inherit" fill-opacity="0.5"/>
+                <rect width="1" height="1" fill="currentColor" color="lime"/>
+                <rect width="1" height="1" fill="none"/>
+            </g>
+            <rect width="1" height="1"/>
+        </svg>"#);
+        let colors: Vec<_> = doc.shapes.iter().map(fill_color).collect();
+        assert_eq!(colors.len(), 6);
+        assert_color(colors[0], vec4(0.0, 0.0, 1.0, 1.0));
+        assert_color(colors[1], vec4(0.0, 1.0, 0.0, 1.0));
+        assert_color(colors[2], vec4(0.0, 0.0, 1.0, 0.5));
+        assert_color(colors[3], vec4(0.0, 0.0, 1.0, 0.25));
+        assert_color(colors[4], vec4(0.0, 1.0, 0.0, 0.5));
+        // the default fill is black
+        assert_color(colors[5], vec4(0.0, 0.0, 0.0, 1.0));
+    }
+    
+    #[test]
+    fn skips_hidden_elements_and_definitions() {
+        let doc = parse_svg(r#"<svg>
+            <g style="display: none"><rect width="1" height="1"/></g>
+            <rect width="1" height="1" display="none"/>
+            <rect width="1" height="1" visibility="hidden"/>
+            <defs><rect id="r" width="1" height="1"/></defs>
+            <rect x="5" width="1" height="1"/>
+        </svg>"#);
+        assert_eq!(doc.shapes.len(), 1);
+        assert_eq!(rect_of(&doc.shapes[0]), (5.0, 0.0, 1.0, 1.0));
+    }
+    
+    #[test]
+    fn gradients_inherit_from_the_gradient_they_reference() {
+        let doc = parse_svg(r##"<svg>
+            <defs>
+                <linearGradient id="base" x1="0" y1="0" x2="10" y2="0" gradientUnits="userSpaceOnUse">
+                    <stop offset="0" stop-color="red"/>
+                    <stop offset="50%" stop-color="lime"/>
+                    <stop offset="1" stop-color="blue" stop-opacity="0.5"/>
+                </linearGradient>
+                <linearGradient id="derived" href="#base" x2="20"></linearGradient>
+                <linearGradient id="box"><stop offset="0" stop-color="red"/><stop offset="1" stop-color="blue"/></linearGradient>
+                <linearGradient id="single"><stop offset="0" stop-color="lime"/></linearGradient>
+            </defs>
+            <rect width="10" height="10" fill="url(#derived)"/>
+            <rect x="10" y="10" width="20" height="10" fill="url(#box)"/>
+            <rect width="10" height="10" fill="url(#single)"/>
+            <rect width="10" height="10" fill="url(#missing) red"/>
+        </svg>"##);
+        assert_eq!(doc.shapes.len(), 4);
+        let Some(SvgPaint::Gradient(gradient)) = &doc.shapes[0].fill else {
+            panic!("not a gradient")
+        };
+        let SvgGradientKind::Linear {start, end} = gradient.kind else {
+            panic!("not a linear gradient")
+        };
+        assert_eq!(((start.x, start.y), (end.x, end.y)), ((0.0, 0.0), (20.0, 0.0)));
+        // the stops in between are kept
+        assert_eq!(gradient.stops.iter().map(|stop| stop.0).collect::<Vec<_>>(), [0.0, 0.5, 1.0]);
+        assert_color(gradient.color_at(0.25), vec4(0.5, 0.5, 0.0, 1.0));
+        assert_color(gradient.color_at(1.5), vec4(0.0, 0.0, 1.0, 0.5));
+        
+        // gradients default to the bounding box of the shape
+        let Some(SvgPaint::Gradient(SvgGradient {kind: SvgGradientKind::Linear {start, end}, ..})) = &doc.shapes[1].fill else {
+            panic!("not a linear gradient")
+        };
+        assert_eq!(((start.x, start.y), (end.x, end.y)), ((10.0, 10.0), (30.0, 10.0)));
+        assert_color(fill_color(&doc.shapes[2]), vec4(0.0, 1.0, 0.0, 1.0));
+        assert_color(fill_color(&doc.shapes[3]), vec4(1.0, 0.0, 0.0, 1.0));
+    }
+    
+    #[test]
+    fn resamples_gradients_to_a_fixed_number_of_stops() {
+        let red = vec4(1.0, 0.0, 0.0, 1.0);
+        let blue = vec4(0.0, 0.0, 1.0, 1.0);
+        let gradient = |stops: Vec<(f64, Vec4)>| SvgGradient {
+            kind: SvgGradientKind::Radial {center: dvec2(0.0, 0.0), radius: 1.0},
+            stops,
+        };
+        let stops = gradient(vec![(0.2, red), (0.8, blue)]).fixed_stops(4);
+        assert_eq!(stops.iter().map(|stop| stop.0).collect::<Vec<_>>(), [0.2, 0.8, 0.8, 0.8]);
+        assert_color(stops[3].1, blue);
+        
+        let many = gradient((0..=6).map(|i| (i as f64 / 6.0, if i % 2 == 0 {red} else {blue})).collect());
+        let stops = many.fixed_stops(4);
+        assert_eq!(stops.iter().map(|stop| (stop.0 * 3.0).round()).collect::<Vec<_>>(), [0.0, 1.0, 2.0, 3.0]);
+        assert_color(stops[0].1, red);
+        assert_color(stops[1].1, many.color_at(1.0 / 3.0));
+        assert_color(stops[3].1, red);
+    }
+}
@@ -0,0 +1,290 @@
+//! Discovery of the fonts that are installed on the system, so that text can fall back to them
+//! for characters that none of the fonts of its text style have a glyph for.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::mpsc,
+};
+
+// For every character that a system font has a glyph for, the first font in the system font
+// directories that has it. The characters are kept as non-overlapping, inclusive ranges of code
+// points, by their first code point.
+#[derive(Default)]
+struct CoverageIndex {
+    paths: Vec<PathBuf>,
+    ranges: BTreeMap<u32, (u32, usize)>,
+}
+
+impl CoverageIndex {
+    // Adds a font with the given sorted character ranges. It only covers the characters that
+    // none of the fonts that were added before it do.
+    #[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+    fn add_font(&mut self, path: PathBuf, char_ranges: &[(u32, u32)]) {
+        let index = self.paths.len();
+        self.paths.push(path);
+        let mut gaps = Vec::new();
+        for &(start, end) in char_ranges {
+            let mut next = start;
+            let overlapping: Vec<(u32, u32)> = self.ranges
+                .range(..=end)
+                .rev()
+                .take_while(|(_, &(covered_end, _))| covered_end >= start)
+                .map(|(&covered_start, &(covered_end, _))| (covered_start, covered_end))
+                .collect();
+            for (covered_start, covered_end) in overlapping.into_iter().rev() {
+                if covered_start > next {
+                    gaps.push((next, covered_start - 1));
+                }
+                next = next.max(covered_end + 1);
+            }
+            if next <= end {
+                gaps.push((next, end));
+            }
+        }
+        for (start, end) in gaps {
+            self.ranges.insert(start, (end, index));
+        }
+    }
+    
+    fn path_for_char(&self, c: char) -> Option<&Path> {
+        let c = c as u32;
+        let (_, &(end, index)) = self.ranges.range(..=c).next_back()?;
+        (c <= end).then(|| self.paths[index].as_path())
+    }
+}
+
+enum ScanState {
+    NotStarted,
+    Scanning(mpsc::Receiver<CoverageIndex>),
+    Ready(CoverageIndex),
+}
+
+// The fonts installed on the system. Reading every font file takes a while, so the font
+// directories are scanned on a thread of their own, the first time a character is looked up.
+pub struct SystemFonts {
+    state: ScanState,
+}
+
+impl Default for SystemFonts {
+    fn default() -> Self {
+        Self {state: ScanState::NotStarted}
+    }
+}
+
+impl SystemFonts {
+    // Returns the path of the first system font that has a glyph for the given character. Until
+    // the font directories have been scanned, there is no such font.
+    pub fn path_for_char(&mut self, c: char) -> Option<&Path> {
+        match &self.state {
+            ScanState::NotStarted => {
+                self.state = start_scan();
+            }
+            ScanState::Scanning(receiver) => match receiver.try_recv() {
+                Ok(index) => self.state = ScanState::Ready(index),
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    self.state = ScanState::Ready(CoverageIndex::default())
+                }
+            }
+            ScanState::Ready(_) => {}
+        }
+        match &self.state {
+            ScanState::Ready(index) => index.path_for_char(c),
+            _ => None,
+        }
+    }
+    
+    // Whether the font directories are still being scanned, so that characters which have no
+    // system font yet may get one later.
+    pub fn is_scanning(&self) -> bool {
+        matches!(self.state, ScanState::Scanning(_))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn start_scan() -> ScanState {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut index = CoverageIndex::default();
+        for dir in linux::font_dirs() {
+            scan_dir(&dir, &mut index);
+        }
+        let _ = sender.send(index);
+    });
+    ScanState::Scanning(receiver)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn start_scan() -> ScanState {
+    ScanState::Ready(CoverageIndex::default())
+}
+
+#[cfg(target_os = "linux")]
+fn scan_dir(dir: &Path, index: &mut CoverageIndex) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|entry| Some(entry.ok()?.path())).collect();
+    // Sorting keeps the order in which fonts are tried the same between runs.
+    paths.sort();
+    for path in paths {
+        if path.is_dir() {
+            scan_dir(&path, index);
+            continue
+        }
+        let is_font = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map_or(false, |extension| matches!(extension.to_ascii_lowercase().as_str(), "ttf" | "otf" | "ttc"));
+        if !is_font || index.paths.contains(&path) {
+            continue
+        }
+        if let Some(char_ranges) = std::fs::read(&path).ok().and_then(|data| char_ranges(&data)) {
+            index.add_font(path, &char_ranges);
+        }
+    }
+}
+
+// Returns the code points that the font has glyphs for, as ranges. Of a font collection only the
+// first font is used, as that is also the one that gets loaded.
+#[cfg(target_os = "linux")]
+fn char_ranges(data: &[u8]) -> Option<Vec<(u32, u32)>> {
+    let face = rustybuzz::ttf_parser::Face::parse(data, 0).ok()?;
+    let mut code_points = Vec::new();
+    for subtable in face.tables().cmap?.subtables {
+        if subtable.is_unicode() {
+            subtable.codepoints(|code_point| code_points.push(code_point));
+        }
+    }
+    code_points.sort_unstable();
+    code_points.dedup();
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for code_point in code_points {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == code_point => *end = code_point,
+            _ => ranges.push((code_point, code_point)),
+        }
+    }
+    (!ranges.is_empty()).then_some(ranges)
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::path::PathBuf;
+
+    // Returns the font directories from the fontconfig configuration, followed by the usual
+    // ones in case the configuration could not be read.
+    pub fn font_dirs() -> Vec<PathBuf> {
+        let mut dirs = Vec::new();
+        let mut conf_files = vec![PathBuf::from("/etc/fonts/fonts.conf")];
+        if let Ok(entries) = std::fs::read_dir("/etc/fonts/conf.d") {
+            let mut paths: Vec<PathBuf> = entries.filter_map(|entry| Some(entry.ok()?.path())).collect();
+            paths.sort();
+            conf_files.extend(paths.into_iter().filter(|path| path.extension().map_or(false, |extension| extension == "conf")));
+        }
+        for conf_file in conf_files {
+            if let Ok(conf) = std::fs::read_to_string(&conf_file) {
+                parse_dirs(&conf, &mut dirs);
+            }
+        }
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        dirs.push(PathBuf::from("/usr/share/fonts"));
+        dirs.push(PathBuf::from("/usr/local/share/fonts"));
+        if let Some(home) = &home {
+            dirs.push(home.join(".local/share/fonts"));
+            dirs.push(home.join(".fonts"));
+        }
+        let mut unique = Vec::new();
+        for dir in dirs {
+            if !unique.contains(&dir) {
+                unique.push(dir);
+            }
+        }
+        unique
+    }
+
+    // Finds the `<dir>` elements in a fontconfig configuration file. Directories with a `~` or
+    // `prefix="xdg"` are resolved against the home and XDG data directories.
+    fn parse_dirs(conf: &str, dirs: &mut Vec<PathBuf>) {
+        let mut rest = conf;
+        while let Some(start) = rest.find("<dir") {
+            rest = &rest[start + 4..];
+            if !rest.starts_with(|c: char| c == '>' || c.is_whitespace()) {
+                continue
+            }
+            let Some(tag_end) = rest.find('>') else {
+                return
+            };
+            let attributes = &rest[..tag_end];
+            rest = &rest[tag_end + 1..];
+            let Some(end) = rest.find("</dir>") else {
+                return
+            };
+            let dir = rest[..end].trim();
+            rest = &rest[end..];
+            if dir.is_empty() {
+                continue
+            }
+            let home = std::env::var_os("HOME").map(PathBuf::from);
+            if attributes.contains("prefix=\"xdg\"") {
+                let data_home = std::env::var_os("XDG_DATA_HOME")
+                    .map(PathBuf::from)
+                    .or_else(|| Some(home?.join(".local/share")));
+                if let Some(data_home) = data_home {
+                    dirs.push(data_home.join(dir));
+                }
+            }
+            else if let Some(dir) = dir.strip_prefix('~') {
+                if let Some(home) = home {
+                    dirs.push(home.join(dir.trim_start_matches('/')));
+                }
+            }
+            else {
+                dirs.push(PathBuf::from(dir));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn index_prefers_fonts_that_were_added_first() {
+        let mut index = CoverageIndex::default();
+        index.add_font(PathBuf::from("a.ttf"), &[(0x41, 0x5a), (0x100, 0x1ff)]);
+        index.add_font(PathBuf::from("b.ttf"), &[(0x30, 0x60), (0x150, 0x250)]);
+        index.add_font(PathBuf::from("c.ttf"), &[(0x20, 0x300)]);
+        let path_for_code_point = |code_point: u32| {
+            index.path_for_char(char::from_u32(code_point).unwrap()).map(|path| path.to_str().unwrap())
+        };
+        assert_eq!(path_for_code_point(0x41), Some("a.ttf"));
+        assert_eq!(path_for_code_point(0x5a), Some("a.ttf"));
+        assert_eq!(path_for_code_point(0x30), Some("b.ttf"));
+        assert_eq!(path_for_code_point(0x5b), Some("b.ttf"));
+        assert_eq!(path_for_code_point(0x1ff), Some("a.ttf"));
+        assert_eq!(path_for_code_point(0x200), Some("b.ttf"));
+        assert_eq!(path_for_code_point(0x20), Some("c.ttf"));
+        assert_eq!(path_for_code_point(0x61), Some("c.ttf"));
+        assert_eq!(path_for_code_point(0x251), Some("c.ttf"));
+        assert_eq!(path_for_code_point(0x1f), None);
+        assert_eq!(path_for_code_point(0x301), None);
+    }
+    
+    #[test]
+    fn has_no_fonts_until_the_scan_is_done() {
+        let (sender, receiver) = mpsc::channel();
+        let mut system_fonts = SystemFonts {state: ScanState::Scanning(receiver)};
+        assert_eq!(system_fonts.path_for_char('A'), None);
+        assert!(system_fonts.is_scanning());
+        
+        let mut index = CoverageIndex::default();
+        index.add_font(PathBuf::from("a.ttf"), &[(0x41, 0x41)]);
+        sender.send(index).unwrap();
+        assert_eq!(system_fonts.path_for_char('A'), Some(Path::new("a.ttf")));
+        assert_eq!(system_fonts.path_for_char('B'), None);
+        assert!(!system_fonts.is_scanning());
+    }
+}
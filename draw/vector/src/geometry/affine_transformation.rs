@@ -39,6 +39,12 @@ impl AffineTransformation {
     pub fn translate(self, v: Vector) -> AffineTransformation {
         AffineTransformation::new(self.xy, self.z + v)
     }
+
+    /// Rotates by `angle` radians around the origin, after applying `self`.
+    pub fn rotate(self, angle: f64) -> AffineTransformation {
+        let rotation = LinearTransformation::rotation(angle);
+        AffineTransformation::new(rotation.compose(self.xy), self.z.transform(&rotation))
+    }
 }
 
 impl Transformation for AffineTransformation {
@@ -24,6 +24,11 @@ impl LinearTransformation {
         LinearTransformation::scaling(Vector::new(k, k))
     }
 
+    pub fn rotation(angle: f64) -> LinearTransformation {
+        let (sin, cos) = angle.sin_cos();
+        LinearTransformation::new(Vector::new(cos, sin), Vector::new(-sin, cos))
+    }
+
     pub fn scale(self, v: Vector) -> LinearTransformation {
         LinearTransformation::new(self.x * v.x, self.y * v.y)
     }
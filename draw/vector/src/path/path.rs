@@ -66,16 +66,16 @@ impl Path {
         self.points.push(p);
     }
 
-    // Adds a quadratic Bezier curve segment to the current contour, starting at the current point.
+    /// Adds a quadratic Bezier curve segment to the current contour, starting at the current point.
     pub fn quadratic_to(&mut self, p1: Point, p: Point) {
         self.verbs.push(Verb::QuadraticTo);
         self.points.push(p1);
         self.points.push(p);
     }
 
-    // Adds a quadratic Bezier curve segment to the current contour, starting at the current point.
+    /// Adds a cubic Bezier curve segment to the current contour, starting at the current point.
     pub fn cubic_to(&mut self, p1: Point, p2: Point, p: Point) {
-        self.verbs.push(Verb::QuadraticTo);
+        self.verbs.push(Verb::CubicTo);
         self.points.push(p1);
         self.points.push(p2);
         self.points.push(p);
@@ -166,6 +166,11 @@ impl<'a> Iterator for Commands<'a> {
             Verb::QuadraticTo => {
                 PathCommand::QuadraticTo(self.points.next().unwrap(), self.points.next().unwrap())
             }
+            Verb::CubicTo => PathCommand::CubicTo(
+                self.points.next().unwrap(),
+                self.points.next().unwrap(),
+                self.points.next().unwrap(),
+            ),
             Verb::Close => PathCommand::Close,
         })
     }
@@ -177,5 +182,6 @@ enum Verb {
     LineTo,
     ArcTo,
     QuadraticTo,
+    CubicTo,
     Close,
 }
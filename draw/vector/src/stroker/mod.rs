@@ -0,0 +1,407 @@
+use crate::geometry::{Point, Vector};
+use crate::path::{LinePath, LinePathCommand, LinePathIterator};
+use std::f64::consts::PI;
+
+/// The shape that is drawn where two segments of a stroke meet.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum LineJoin {
+    /// The outer edges of the segments are extended until they meet, unless that would exceed the
+    /// miter limit, in which case the join is beveled.
+    #[default]
+    Miter,
+    /// The corner is rounded off with a circle.
+    Round,
+    /// The corner is cut off with a straight line.
+    Bevel,
+}
+
+/// The shape that is drawn at the ends of an open contour of a stroke.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum LineCap {
+    /// The stroke ends exactly at the end of the contour.
+    #[default]
+    Butt,
+    /// The stroke ends with a half circle.
+    Round,
+    /// The stroke ends with a half square.
+    Square,
+}
+
+/// The style of a stroke.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f64,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// The maximum ratio of the length of a miter join to the width of the stroke.
+    pub miter_limit: f64,
+    /// The lengths of the alternating dashes and gaps, or an empty list for a solid stroke. If
+    /// the list has an odd number of lengths, it is repeated to make it even.
+    pub dashes: Vec<f64>,
+    /// The distance into the dash pattern at which the stroke starts.
+    pub dash_offset: f64,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> StrokeStyle {
+        StrokeStyle {
+            width: 1.0,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            miter_limit: 4.0,
+            dashes: Vec::new(),
+            dash_offset: 0.0,
+        }
+    }
+}
+
+/// Converts a sequence of line path commands to the outline of its stroke.
+#[derive(Clone, Debug, Default)]
+pub struct Stroker {
+    contours: Vec<Contour>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Contour {
+    points: Vec<Point>,
+    is_closed: bool,
+}
+
+impl Stroker {
+    /// Creates a new stroker.
+    pub fn new() -> Stroker {
+        Stroker::default()
+    }
+
+    /// Returns a line path that covers the stroke of the given line path with the given style.
+    /// Round joins and caps are approximated with tolerance `epsilon`.
+    ///
+    /// The contours of the returned line path overlap, and all wind in the same direction, so it
+    /// should be filled with the nonzero fill rule.
+    pub fn stroke<P: LinePathIterator>(
+        &mut self,
+        path: P,
+        style: &StrokeStyle,
+        epsilon: f64,
+    ) -> LinePath {
+        self.contours.clear();
+        path.for_each(&mut |command| {
+            match command {
+                LinePathCommand::MoveTo(p) => self.contours.push(Contour {
+                    points: vec![p],
+                    is_closed: false,
+                }),
+                LinePathCommand::LineTo(p) => match self.contours.last_mut() {
+                    Some(contour) if !contour.is_closed => contour.points.push(p),
+                    // A line after a close continues from the start of the closed contour.
+                    Some(contour) => {
+                        let start = contour.points[0];
+                        self.contours.push(Contour {
+                            points: vec![start, p],
+                            is_closed: false,
+                        });
+                    }
+                    None => self.contours.push(Contour {
+                        points: vec![p],
+                        is_closed: false,
+                    }),
+                },
+                LinePathCommand::Close => {
+                    if let Some(contour) = self.contours.last_mut() {
+                        contour.is_closed = true;
+                    }
+                }
+            }
+            true
+        });
+        for contour in &mut self.contours {
+            contour.points.dedup();
+            if contour.is_closed && contour.points.len() > 1 && contour.points.first() == contour.points.last() {
+                contour.points.pop();
+            }
+        }
+        let dash_total: f64 = style.dashes.iter().sum();
+        if dash_total > 0.0 && style.dashes.iter().all(|&dash| dash >= 0.0) {
+            let mut dashes = style.dashes.clone();
+            let mut dash_total = dash_total;
+            if dashes.len() % 2 == 1 {
+                dashes.extend_from_within(..);
+                dash_total *= 2.0;
+            }
+            let contours = std::mem::take(&mut self.contours);
+            for contour in &contours {
+                dash_contour(contour, &dashes, dash_total, style.dash_offset, &mut self.contours);
+            }
+        }
+
+        let mut outline = Outline {
+            path: LinePath::new(),
+            half_width: style.width / 2.0,
+            epsilon,
+        };
+        if outline.half_width <= 0.0 {
+            return outline.path;
+        }
+        for contour in &self.contours {
+            outline.add_contour(contour, style);
+        }
+        outline.path
+    }
+}
+
+// Splits a contour into the open contours of its dashes.
+fn dash_contour(contour: &Contour, dashes: &[f64], total: f64, offset: f64, output: &mut Vec<Contour>) {
+    let mut index = 0;
+    let mut left = dashes[0];
+    let mut offset = offset.rem_euclid(total);
+    while offset > 0.0 {
+        if offset >= left {
+            offset -= left;
+            index = (index + 1) % dashes.len();
+            left = dashes[index];
+        } else {
+            left -= offset;
+            offset = 0.0;
+        }
+    }
+    let points = &contour.points;
+    let mut current = if index % 2 == 0 {
+        Some(vec![points[0]])
+    } else {
+        None
+    };
+    let segment_count = if contour.is_closed { points.len() } else { points.len() - 1 };
+    for i in 0..segment_count {
+        let (a, b) = (points[i], points[(i + 1) % points.len()]);
+        let length = (b - a).length();
+        let mut t = 0.0;
+        while length - t > left {
+            t += left;
+            let p = a.lerp(b, t / length);
+            if index % 2 == 0 {
+                let mut points = current.take().unwrap();
+                points.push(p);
+                output.push(Contour {
+                    points,
+                    is_closed: false,
+                });
+            } else {
+                current = Some(vec![p]);
+            }
+            index = (index + 1) % dashes.len();
+            left = dashes[index];
+        }
+        left -= length - t;
+        if let Some(points) = &mut current {
+            points.push(b);
+        }
+    }
+    if let Some(points) = current {
+        output.push(Contour {
+            points,
+            is_closed: false,
+        });
+    }
+}
+
+// Builds the outline of a stroke out of one polygon for each segment, join, and cap. All polygons
+// are made to wind counterclockwise, so that filling them with the nonzero fill rule draws their
+// union.
+struct Outline {
+    path: LinePath,
+    half_width: f64,
+    epsilon: f64,
+}
+
+impl Outline {
+    fn add_contour(&mut self, contour: &Contour, style: &StrokeStyle) {
+        let mut points = contour.points.clone();
+        points.dedup();
+        let hw = self.half_width;
+        if points.len() == 1 {
+            let p = points[0];
+            match style.cap {
+                LineCap::Butt => {}
+                LineCap::Round => self.add_circle(p),
+                LineCap::Square => self.add_polygon(&[
+                    Point::new(p.x - hw, p.y - hw),
+                    Point::new(p.x + hw, p.y - hw),
+                    Point::new(p.x + hw, p.y + hw),
+                    Point::new(p.x - hw, p.y + hw),
+                ]),
+            }
+            return;
+        }
+        let is_closed = contour.is_closed && points.len() > 2;
+        let segment_count = if is_closed { points.len() } else { points.len() - 1 };
+        let direction = |i: usize| {
+            let (a, b) = (points[i % points.len()], points[(i + 1) % points.len()]);
+            (b - a).normalize().unwrap_or(Vector::zero())
+        };
+        for i in 0..segment_count {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            let n = left_normal(direction(i)) * hw;
+            self.add_polygon(&[a + n, b + n, b - n, a - n]);
+        }
+        let join_range = if is_closed { 0..points.len() } else { 1..points.len() - 1 };
+        for i in join_range {
+            let d0 = direction((i + segment_count - 1) % segment_count);
+            let d1 = direction(i);
+            self.add_join(points[i], d0, d1, style);
+        }
+        if !is_closed {
+            let d = direction(0);
+            self.add_cap(points[0], -d, style.cap);
+            let d = direction(points.len() - 2);
+            self.add_cap(points[points.len() - 1], d, style.cap);
+        }
+    }
+
+    fn add_join(&mut self, p: Point, d0: Vector, d1: Vector, style: &StrokeStyle) {
+        let cross = d0.cross(d1);
+        if cross.abs() < 1e-9 && d0.dot(d1) > 0.0 {
+            return;
+        }
+        let hw = self.half_width;
+        // The join goes on the outside of the turn.
+        let side = if cross > 0.0 { -1.0 } else { 1.0 };
+        let o0 = left_normal(d0) * (hw * side);
+        let o1 = left_normal(d1) * (hw * side);
+        match style.join {
+            LineJoin::Round => self.add_circle(p),
+            LineJoin::Bevel => self.add_polygon(&[p, p + o0, p + o1]),
+            LineJoin::Miter => {
+                let cos_half = match (o0 + o1).normalize() {
+                    Some(m) => m.dot(o0) / hw,
+                    None => 0.0,
+                };
+                if cos_half > 0.0 && 1.0 / cos_half <= style.miter_limit {
+                    let m = (o0 + o1).normalize().unwrap() * (hw / cos_half);
+                    self.add_polygon(&[p, p + o0, p + m, p + o1]);
+                } else {
+                    self.add_polygon(&[p, p + o0, p + o1]);
+                }
+            }
+        }
+    }
+
+    // Adds the cap at the end point `p` of a contour, where `d` points away from the contour.
+    fn add_cap(&mut self, p: Point, d: Vector, cap: LineCap) {
+        let hw = self.half_width;
+        match cap {
+            LineCap::Butt => {}
+            LineCap::Round => self.add_circle(p),
+            LineCap::Square => {
+                let n = left_normal(d) * hw;
+                let e = d * hw;
+                self.add_polygon(&[p + n, p - n, p - n + e, p + n + e]);
+            }
+        }
+    }
+
+    fn add_circle(&mut self, center: Point) {
+        let r = self.half_width;
+        let count = if self.epsilon > 0.0 && self.epsilon < r {
+            (PI / (1.0 - self.epsilon / r).acos()).ceil().clamp(8.0, 256.0) as usize
+        } else {
+            8
+        };
+        let points: Vec<Point> = (0..count)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / count as f64;
+                Point::new(center.x + r * angle.cos(), center.y + r * angle.sin())
+            })
+            .collect();
+        self.add_polygon(&points);
+    }
+
+    fn add_polygon(&mut self, points: &[Point]) {
+        let mut area = 0.0;
+        for i in 0..points.len() {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            area += a.x * b.y - b.x * a.y;
+        }
+        if area.abs() < 1e-12 {
+            return;
+        }
+        let mut points = points.iter().copied();
+        let mut reversed = points.clone().rev();
+        let points: &mut dyn Iterator<Item = Point> = if area > 0.0 { &mut points } else { &mut reversed };
+        self.path.move_to(points.next().unwrap());
+        for p in points {
+            self.path.line_to(p);
+        }
+        self.path.close();
+    }
+}
+
+fn left_normal(d: Vector) -> Vector {
+    Vector::new(-d.y, d.x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trapezoidator::Trapezoidator;
+    use crate::internal_iter::InternalIterator;
+
+    fn area(path: &LinePath) -> f64 {
+        let mut area = 0.0;
+        Trapezoidator::new()
+            .trapezoidate(path.commands())
+            .unwrap()
+            .for_each(&mut |trapezoid| {
+                let width = (trapezoid.xs[1] - trapezoid.xs[0]) as f64;
+                let height0 = (trapezoid.ys[2] - trapezoid.ys[0]) as f64;
+                let height1 = (trapezoid.ys[3] - trapezoid.ys[1]) as f64;
+                area += width * (height0 + height1) / 2.0;
+                true
+            });
+        area
+    }
+
+    fn line(points: &[(f64, f64)]) -> LinePath {
+        let mut path = LinePath::new();
+        path.move_to(Point::new(points[0].0, points[0].1));
+        for &(x, y) in &points[1..] {
+            path.line_to(Point::new(x, y));
+        }
+        path
+    }
+
+    #[test]
+    fn butt_line() {
+        let path = line(&[(0.0, 0.0), (10.0, 0.0)]);
+        let style = StrokeStyle {
+            width: 2.0,
+            ..StrokeStyle::default()
+        };
+        let stroke = Stroker::new().stroke(path.commands(), &style, 0.01);
+        assert!((area(&stroke) - 20.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn square_caps_and_miter_join() {
+        let path = line(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]);
+        let style = StrokeStyle {
+            width: 2.0,
+            cap: LineCap::Square,
+            ..StrokeStyle::default()
+        };
+        let stroke = Stroker::new().stroke(path.commands(), &style, 0.01);
+        // Two 12x2 bars that overlap in a 2x2 square at the corner.
+        assert!((area(&stroke) - 44.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn dashes() {
+        let path = line(&[(0.0, 0.0), (10.0, 0.0)]);
+        let style = StrokeStyle {
+            width: 2.0,
+            dashes: vec![2.0, 3.0],
+            ..StrokeStyle::default()
+        };
+        let stroke = Stroker::new().stroke(path.commands(), &style, 0.01);
+        assert!((area(&stroke) - 8.0).abs() < 1e-3);
+    }
+}
@@ -652,7 +652,31 @@ impl Mat4 {
             0.0
         ]}
     }
-    
+
+    pub fn look_at(eye: Vec3, target: Vec3, up: Vec3) -> Mat4 {
+        let f = (target - eye).normalize();
+        let s = Vec3::cross(f, up).normalize();
+        let u = Vec3::cross(s, f);
+        Mat4 {v: [
+            s.x,
+            u.x,
+            -f.x,
+            0.0,
+            s.y,
+            u.y,
+            -f.y,
+            0.0,
+            s.z,
+            u.z,
+            -f.z,
+            0.0,
+            -s.dot(eye),
+            -u.dot(eye),
+            f.dot(eye),
+            1.0
+        ]}
+    }
+
     pub fn translation(x: f32, y: f32, z: f32) -> Mat4 {
         Mat4 {v: [
             1.0,
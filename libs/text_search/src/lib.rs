@@ -0,0 +1,6 @@
+// The text model and search of the code editor, kept free of any UI dependencies so the file
+// server can search a project with the same code the editor uses for a single file.
+
+pub mod regex;
+pub mod search;
+pub mod text;
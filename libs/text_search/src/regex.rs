@@ -0,0 +1,786 @@
+use std::{fmt, mem, ops::Range};
+
+// A small regex engine for searching within a single line. It supports alternation, groups,
+// the usual quantifiers (greedy and lazy), `.`, character classes, the `\d \w \s` classes
+// and their negations, and the `^ $ \b \B` assertions. Matching runs all alternatives in
+// lockstep (a Pike VM) and finds the same match a backtracking engine would. It takes time
+// proportional to the size of the program times the length of the line, but only keeps
+// state per instruction, so its memory doesn't grow with the line.
+#[derive(Clone, Debug)]
+pub struct Regex {
+    program: Vec<Inst>,
+    capture_count: usize,
+    case_insensitive: bool,
+}
+
+impl Regex {
+    pub fn new(pattern: &str, case_insensitive: bool) -> Result<Self, RegexError> {
+        let mut parser = Parser {
+            chars: pattern.chars().collect(),
+            index: 0,
+            capture_count: 1,
+        };
+        let node = parser.parse_alternation()?;
+        if parser.index < parser.chars.len() {
+            return Err(parser.error("unmatched `)`"));
+        }
+        // Counted repeats are expanded, so nested ones multiply. Check the size up front
+        // rather than compiling a program that doesn't fit in memory.
+        if node.program_size() > MAX_PROGRAM_SIZE {
+            return Err(RegexError {
+                message: "pattern is too large".to_string(),
+                char_index: 0,
+            });
+        }
+        let mut compiler = Compiler {
+            program: Vec::new(),
+        };
+        // An unanchored search is a lazy `.*` in front of the pattern.
+        compiler.push(Inst::Split(3, 1));
+        compiler.push(Inst::Any);
+        compiler.push(Inst::Jump(0));
+        compiler.push(Inst::Save(0));
+        compiler.compile(&node);
+        compiler.push(Inst::Save(1));
+        compiler.push(Inst::Match);
+        Ok(Self {
+            program: compiler.program,
+            capture_count: parser.capture_count,
+            case_insensitive,
+        })
+    }
+
+    // The number of capture groups, including the implicit group 0 for the whole match.
+    pub fn capture_count(&self) -> usize {
+        self.capture_count
+    }
+
+    // Finds the leftmost match that starts at or after the byte index `start`, and returns
+    // the byte ranges of all capture groups.
+    pub fn captures_at(&self, haystack: &str, start: usize) -> Option<Vec<Option<Range<usize>>>> {
+        let mut input = RegexInput::default();
+        input.set_haystack(haystack);
+        self.captures_in(&mut input, start)
+    }
+
+    pub fn find_at(&self, haystack: &str, start: usize) -> Option<Range<usize>> {
+        self.captures_at(haystack, start)
+            .and_then(|mut captures| captures.swap_remove(0))
+    }
+
+    // Same as `captures_at`, but for a haystack that was decoded into `input` before.
+    pub fn captures_in(&self, input: &mut RegexInput, start: usize) -> Option<Vec<Option<Range<usize>>>> {
+        let slots = self.run(input, start, 2 * self.capture_count)?;
+        Some(
+            slots
+                .chunks(2)
+                .map(|slots| match (slots[0], slots[1]) {
+                    (Some(start), Some(end)) => Some(input.byte_indices[start]..input.byte_indices[end]),
+                    _ => None,
+                })
+                .collect(),
+        )
+    }
+
+    // Only keeps track of where the whole match starts and ends, which is all a search needs.
+    pub fn find_in(&self, input: &mut RegexInput, start: usize) -> Option<Range<usize>> {
+        let slots = self.run(input, start, 2)?;
+        Some(input.byte_indices[slots[0]?]..input.byte_indices[slots[1]?])
+    }
+
+    // Returns the first `slot_count` slots of the leftmost match that starts at or after the
+    // byte index `start`. The threads of each list are in priority order, so once a thread
+    // matches, the threads after it can't change the outcome and are dropped.
+    fn run(&self, input: &mut RegexInput, start: usize, slot_count: usize) -> Option<Vec<Option<usize>>> {
+        let start = input.byte_indices.partition_point(|&index| index < start);
+        if start == input.byte_indices.len() {
+            return None;
+        }
+        let RegexInput {
+            chars,
+            threads,
+            next_threads,
+            stack,
+            ..
+        } = input;
+        let mut slots = vec![None; slot_count];
+        let mut matched = None;
+        threads.clear(self.program.len());
+        self.add_threads(threads, stack, &mut slots, chars, 0, start);
+        for position in start..chars.len() {
+            next_threads.clear(self.program.len());
+            for (index, &pc) in threads.pcs.iter().enumerate() {
+                let thread_slots = &threads.slots[index * slot_count..][..slot_count];
+                let is_step = match self.program[pc] {
+                    Inst::Char(expected) => self.char_eq(chars[position], expected),
+                    Inst::Any => true,
+                    Inst::Class(ref class) => class.matches(chars[position], self.case_insensitive),
+                    Inst::Match => {
+                        matched = Some(thread_slots.to_vec());
+                        break;
+                    }
+                    _ => unreachable!(),
+                };
+                if is_step {
+                    slots.copy_from_slice(thread_slots);
+                    self.add_threads(next_threads, stack, &mut slots, chars, pc + 1, position + 1);
+                }
+            }
+            mem::swap(threads, next_threads);
+            if threads.pcs.is_empty() {
+                return matched;
+            }
+        }
+        // At the end of the line only a match can make progress.
+        threads
+            .pcs
+            .iter()
+            .position(|&pc| matches!(self.program[pc], Inst::Match))
+            .map(|index| threads.slots[index * slot_count..][..slot_count].to_vec())
+            .or(matched)
+    }
+
+    // Follows the splits, jumps, saves and assertions from `pc` at `position`, and adds a
+    // thread for every instruction it reaches that consumes a char or matches. `slots` is
+    // restored before returning.
+    fn add_threads(
+        &self,
+        threads: &mut Threads,
+        stack: &mut Vec<Job>,
+        slots: &mut [Option<usize>],
+        chars: &[char],
+        pc: usize,
+        position: usize,
+    ) {
+        stack.push(Job::Follow(pc));
+        while let Some(job) = stack.pop() {
+            let mut pc = match job {
+                Job::Follow(pc) => pc,
+                Job::Restore { slot, value } => {
+                    slots[slot] = value;
+                    continue;
+                }
+            };
+            while threads.visited.insert(pc) {
+                match self.program[pc] {
+                    Inst::Char(_) | Inst::Any | Inst::Class(_) | Inst::Match => {
+                        threads.pcs.push(pc);
+                        threads.slots.extend_from_slice(slots);
+                        break;
+                    }
+                    Inst::Assert(assertion) => {
+                        if !assertion.holds(chars, position) {
+                            break;
+                        }
+                        pc += 1;
+                    }
+                    Inst::Split(first, second) => {
+                        stack.push(Job::Follow(second));
+                        pc = first;
+                    }
+                    Inst::Jump(target) => pc = target,
+                    Inst::Save(slot) => {
+                        if slot < slots.len() {
+                            stack.push(Job::Restore {
+                                slot,
+                                value: slots[slot],
+                            });
+                            slots[slot] = Some(position);
+                        }
+                        pc += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    fn char_eq(&self, char: char, expected: char) -> bool {
+        char == expected || self.case_insensitive && fold_case(char) == fold_case(expected)
+    }
+}
+
+// A haystack decoded into chars, along with the thread lists that matching needs. Searching
+// a line for several matches, or many lines in a row, reuses both instead of allocating
+// them again for every match.
+#[derive(Clone, Debug, Default)]
+pub struct RegexInput {
+    chars: Vec<char>,
+    byte_indices: Vec<usize>,
+    threads: Threads,
+    next_threads: Threads,
+    stack: Vec<Job>,
+}
+
+impl RegexInput {
+    pub fn set_haystack(&mut self, haystack: &str) {
+        self.chars.clear();
+        self.byte_indices.clear();
+        // Don't hold on to the memory of a much longer line that came before.
+        shrink_to_about(&mut self.chars, haystack.len());
+        shrink_to_about(&mut self.byte_indices, haystack.len() + 1);
+        for (index, char) in haystack.char_indices() {
+            self.chars.push(char);
+            self.byte_indices.push(index);
+        }
+        self.byte_indices.push(haystack.len());
+    }
+}
+
+fn shrink_to_about<T>(vec: &mut Vec<T>, len: usize) {
+    if vec.capacity() > 2 * len.max(1024) {
+        vec.shrink_to(len);
+    }
+}
+
+// The threads at one position of the line, in priority order, with `slot_count` slots
+// each. An instruction is followed at most once per position, so there are never more
+// threads than instructions.
+#[derive(Clone, Debug, Default)]
+struct Threads {
+    pcs: Vec<usize>,
+    slots: Vec<Option<usize>>,
+    visited: SparseSet,
+}
+
+impl Threads {
+    fn clear(&mut self, program_len: usize) {
+        self.pcs.clear();
+        self.slots.clear();
+        self.visited.clear(program_len);
+    }
+}
+
+// A set of instructions that can be cleared without touching its memory.
+#[derive(Clone, Debug, Default)]
+struct SparseSet {
+    dense: Vec<usize>,
+    sparse: Vec<usize>,
+}
+
+impl SparseSet {
+    fn clear(&mut self, capacity: usize) {
+        self.dense.clear();
+        self.sparse.resize(capacity, 0);
+    }
+
+    fn insert(&mut self, value: usize) -> bool {
+        let index = self.sparse[value];
+        if index < self.dense.len() && self.dense[index] == value {
+            return false;
+        }
+        self.sparse[value] = self.dense.len();
+        self.dense.push(value);
+        true
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RegexError {
+    pub message: String,
+    pub char_index: usize,
+}
+
+impl fmt::Display for RegexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.char_index)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Job {
+    Follow(usize),
+    Restore { slot: usize, value: Option<usize> },
+}
+
+#[derive(Clone, Debug)]
+enum Inst {
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assertion),
+    // Tries the first target before the second one.
+    Split(usize, usize),
+    Jump(usize),
+    Save(usize),
+    Match,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Assertion {
+    LineStart,
+    LineEnd,
+    WordBoundary,
+    NotWordBoundary,
+}
+
+impl Assertion {
+    fn holds(self, chars: &[char], position: usize) -> bool {
+        let is_word_boundary = || {
+            let before = position > 0 && is_word_char(chars[position - 1]);
+            let after = position < chars.len() && is_word_char(chars[position]);
+            before != after
+        };
+        match self {
+            Self::LineStart => position == 0,
+            Self::LineEnd => position == chars.len(),
+            Self::WordBoundary => is_word_boundary(),
+            Self::NotWordBoundary => !is_word_boundary(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct Class {
+    negated: bool,
+    items: Vec<ClassItem>,
+}
+
+impl Class {
+    fn perl(perl: Perl, negated: bool) -> Self {
+        Self {
+            negated: false,
+            items: vec![ClassItem::Perl(perl, negated)],
+        }
+    }
+
+    fn matches(&self, char: char, case_insensitive: bool) -> bool {
+        let matches = |char| self.items.iter().any(|item| item.matches(char));
+        let is_match = matches(char)
+            || case_insensitive
+                && (char.to_lowercase().any(matches) || char.to_uppercase().any(matches));
+        is_match != self.negated
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum ClassItem {
+    Range(char, char),
+    Perl(Perl, bool),
+}
+
+impl ClassItem {
+    fn matches(self, char: char) -> bool {
+        match self {
+            Self::Range(start, end) => (start..=end).contains(&char),
+            Self::Perl(perl, negated) => perl.matches(char) != negated,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Perl {
+    Digit,
+    Word,
+    Space,
+}
+
+impl Perl {
+    fn matches(self, char: char) -> bool {
+        match self {
+            Self::Digit => char.is_ascii_digit(),
+            Self::Word => is_word_char(char),
+            Self::Space => char.is_whitespace(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Empty,
+    Char(char),
+    Any,
+    Class(Class),
+    Assert(Assertion),
+    Group(Box<Node>, Option<usize>),
+    Concat(Vec<Node>),
+    Alternation(Vec<Node>),
+    Repeat {
+        node: Box<Node>,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+    },
+}
+
+impl Node {
+    // The number of instructions the compiler emits for this node, saturating instead of
+    // overflowing for patterns that are far too large anyway.
+    fn program_size(&self) -> usize {
+        match self {
+            Self::Empty => 0,
+            Self::Char(_) | Self::Any | Self::Class(_) | Self::Assert(_) => 1,
+            Self::Group(node, group) => node.program_size().saturating_add(if group.is_some() { 2 } else { 0 }),
+            Self::Concat(nodes) => nodes
+                .iter()
+                .fold(0, |size, node| size.saturating_add(node.program_size())),
+            Self::Alternation(nodes) => nodes.iter().fold(2 * nodes.len().saturating_sub(1), |size, node| {
+                size.saturating_add(node.program_size())
+            }),
+            Self::Repeat { node, min, max, .. } => {
+                let size = node.program_size();
+                let optional = match max {
+                    None => size.saturating_add(2),
+                    Some(max) => (max - min).saturating_mul(size.saturating_add(1)),
+                };
+                min.saturating_mul(size).saturating_add(optional)
+            }
+        }
+    }
+}
+
+const MAX_REPEAT: usize = 1000;
+
+// The most instructions a compiled pattern may have. Matching keeps at most one thread per
+// instruction, so this also bounds the memory and the time per char a match needs.
+const MAX_PROGRAM_SIZE: usize = 10_000;
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+    capture_count: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    fn skip_if(&mut self, char: char) -> bool {
+        if self.peek() == Some(char) {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: &str) -> RegexError {
+        RegexError {
+            message: message.to_string(),
+            char_index: self.index,
+        }
+    }
+
+    fn parse_alternation(&mut self) -> Result<Node, RegexError> {
+        let mut alternatives = vec![self.parse_concat()?];
+        while self.skip_if('|') {
+            alternatives.push(self.parse_concat()?);
+        }
+        Ok(if alternatives.len() == 1 {
+            alternatives.pop().unwrap()
+        } else {
+            Node::Alternation(alternatives)
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node, RegexError> {
+        let mut nodes = Vec::new();
+        while let Some(char) = self.peek() {
+            if char == '|' || char == ')' {
+                break;
+            }
+            let node = self.parse_atom()?;
+            nodes.push(self.parse_quantifier(node)?);
+        }
+        Ok(match nodes.len() {
+            0 => Node::Empty,
+            1 => nodes.pop().unwrap(),
+            _ => Node::Concat(nodes),
+        })
+    }
+
+    fn parse_quantifier(&mut self, node: Node) -> Result<Node, RegexError> {
+        let start = self.index;
+        let (min, max) = match self.peek() {
+            Some('*') => (0, None),
+            Some('+') => (1, None),
+            Some('?') => (0, Some(1)),
+            Some('{') => match self.parse_counted_repeat()? {
+                Some(range) => range,
+                None => return Ok(node),
+            },
+            _ => return Ok(node),
+        };
+        if self.index == start {
+            self.index += 1;
+        }
+        if let Node::Empty | Node::Assert(_) = node {
+            self.index = start;
+            return Err(self.error("nothing to repeat"));
+        }
+        let greedy = !self.skip_if('?');
+        Ok(Node::Repeat {
+            node: Box::new(node),
+            min,
+            max,
+            greedy,
+        })
+    }
+
+    // Parses `{n}`, `{n,}` or `{n,m}`, a `{` that doesn't start one of those is a literal.
+    fn parse_counted_repeat(&mut self) -> Result<Option<(usize, Option<usize>)>, RegexError> {
+        let start = self.index;
+        self.index += 1;
+        let min = self.parse_decimal();
+        let max = if self.skip_if(',') {
+            self.parse_decimal()
+        } else {
+            min
+        };
+        let min = match min {
+            Some(min) if self.skip_if('}') => min,
+            _ => {
+                self.index = start;
+                return Ok(None);
+            }
+        };
+        if max.is_some_and(|max| max < min) || min.max(max.unwrap_or(0)) > MAX_REPEAT {
+            self.index = start;
+            return Err(self.error("invalid repetition count"));
+        }
+        Ok(Some((min, max)))
+    }
+
+    fn parse_decimal(&mut self) -> Option<usize> {
+        let start = self.index;
+        while self.peek().is_some_and(|char| char.is_ascii_digit()) {
+            self.index += 1;
+        }
+        self.chars[start..self.index]
+            .iter()
+            .collect::<String>()
+            .parse()
+            .ok()
+    }
+
+    fn parse_atom(&mut self) -> Result<Node, RegexError> {
+        let char = self.peek().unwrap();
+        self.index += 1;
+        Ok(match char {
+            '.' => Node::Any,
+            '^' => Node::Assert(Assertion::LineStart),
+            '$' => Node::Assert(Assertion::LineEnd),
+            '(' => {
+                let group = if self.chars[self.index..].starts_with(&['?', ':']) {
+                    self.index += 2;
+                    None
+                } else {
+                    self.capture_count += 1;
+                    Some(self.capture_count - 1)
+                };
+                let node = self.parse_alternation()?;
+                if !self.skip_if(')') {
+                    return Err(self.error("unclosed group"));
+                }
+                Node::Group(Box::new(node), group)
+            }
+            '[' => Node::Class(self.parse_class()?),
+            '\\' => match self.parse_escape()? {
+                Escape::Char(char) => Node::Char(char),
+                Escape::Perl(perl, negated) => Node::Class(Class::perl(perl, negated)),
+                Escape::Assert(assertion) => Node::Assert(assertion),
+            },
+            '*' | '+' | '?' => {
+                self.index -= 1;
+                return Err(self.error("nothing to repeat"));
+            }
+            char => Node::Char(char),
+        })
+    }
+
+    fn parse_class(&mut self) -> Result<Class, RegexError> {
+        let start = self.index - 1;
+        let mut class = Class {
+            negated: self.skip_if('^'),
+            items: Vec::new(),
+        };
+        let mut is_first = true;
+        loop {
+            let char = match self.peek() {
+                Some(']') if !is_first => {
+                    self.index += 1;
+                    break;
+                }
+                Some(char) => char,
+                None => {
+                    self.index = start;
+                    return Err(self.error("unclosed character class"));
+                }
+            };
+            is_first = false;
+            self.index += 1;
+            let first = if char == '\\' {
+                match self.parse_escape()? {
+                    Escape::Char(char) => char,
+                    Escape::Perl(perl, negated) => {
+                        class.items.push(ClassItem::Perl(perl, negated));
+                        continue;
+                    }
+                    Escape::Assert(_) => return Err(self.error("invalid escape in class")),
+                }
+            } else {
+                char
+            };
+            let is_range = self.peek() == Some('-')
+                && self.chars.get(self.index + 1).is_some_and(|&char| char != ']');
+            if !is_range {
+                class.items.push(ClassItem::Range(first, first));
+                continue;
+            }
+            self.index += 1;
+            let char = self.peek().unwrap();
+            self.index += 1;
+            let last = if char == '\\' {
+                match self.parse_escape()? {
+                    Escape::Char(char) => char,
+                    _ => return Err(self.error("invalid range in class")),
+                }
+            } else {
+                char
+            };
+            if last < first {
+                return Err(self.error("invalid range in class"));
+            }
+            class.items.push(ClassItem::Range(first, last));
+        }
+        Ok(class)
+    }
+
+    fn parse_escape(&mut self) -> Result<Escape, RegexError> {
+        let char = match self.peek() {
+            Some(char) => char,
+            None => return Err(self.error("trailing backslash")),
+        };
+        self.index += 1;
+        Ok(match char {
+            'd' => Escape::Perl(Perl::Digit, false),
+            'D' => Escape::Perl(Perl::Digit, true),
+            'w' => Escape::Perl(Perl::Word, false),
+            'W' => Escape::Perl(Perl::Word, true),
+            's' => Escape::Perl(Perl::Space, false),
+            'S' => Escape::Perl(Perl::Space, true),
+            'b' => Escape::Assert(Assertion::WordBoundary),
+            'B' => Escape::Assert(Assertion::NotWordBoundary),
+            't' => Escape::Char('\t'),
+            'n' => Escape::Char('\n'),
+            'r' => Escape::Char('\r'),
+            char if char.is_ascii_alphanumeric() => {
+                self.index -= 1;
+                return Err(self.error("unknown escape"));
+            }
+            char => Escape::Char(char),
+        })
+    }
+}
+
+enum Escape {
+    Char(char),
+    Perl(Perl, bool),
+    Assert(Assertion),
+}
+
+struct Compiler {
+    program: Vec<Inst>,
+}
+
+impl Compiler {
+    fn push(&mut self, inst: Inst) -> usize {
+        self.program.push(inst);
+        self.program.len() - 1
+    }
+
+    fn compile(&mut self, node: &Node) {
+        match node {
+            Node::Empty => {}
+            Node::Char(char) => {
+                self.push(Inst::Char(*char));
+            }
+            Node::Any => {
+                self.push(Inst::Any);
+            }
+            Node::Class(class) => {
+                self.push(Inst::Class(class.clone()));
+            }
+            Node::Assert(assertion) => {
+                self.push(Inst::Assert(*assertion));
+            }
+            Node::Group(node, group) => match group {
+                Some(group) => {
+                    self.push(Inst::Save(2 * group));
+                    self.compile(node);
+                    self.push(Inst::Save(2 * group + 1));
+                }
+                None => self.compile(node),
+            },
+            Node::Concat(nodes) => {
+                for node in nodes {
+                    self.compile(node);
+                }
+            }
+            Node::Alternation(nodes) => {
+                let mut jumps = Vec::new();
+                for (index, node) in nodes.iter().enumerate() {
+                    if index + 1 == nodes.len() {
+                        self.compile(node);
+                    } else {
+                        let split = self.push(Inst::Split(0, 0));
+                        self.compile(node);
+                        jumps.push(self.push(Inst::Jump(0)));
+                        self.program[split] = Inst::Split(split + 1, self.program.len());
+                    }
+                }
+                let end = self.program.len();
+                for jump in jumps {
+                    self.program[jump] = Inst::Jump(end);
+                }
+            }
+            Node::Repeat {
+                node,
+                min,
+                max,
+                greedy,
+            } => {
+                for _ in 0..*min {
+                    self.compile(node);
+                }
+                match max {
+                    None => {
+                        let split = self.push(Inst::Split(0, 0));
+                        self.compile(node);
+                        self.push(Inst::Jump(split));
+                        self.patch_split(split, *greedy);
+                    }
+                    Some(max) => {
+                        let mut splits = Vec::new();
+                        for _ in *min..*max {
+                            splits.push(self.push(Inst::Split(0, 0)));
+                            self.compile(node);
+                        }
+                        for split in splits {
+                            self.patch_split(split, *greedy);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Points a split at the instruction after it and the current end of the program, in
+    // the order that makes the repetition greedy or lazy.
+    fn patch_split(&mut self, split: usize, greedy: bool) {
+        let end = self.program.len();
+        self.program[split] = if greedy {
+            Inst::Split(split + 1, end)
+        } else {
+            Inst::Split(end, split + 1)
+        };
+    }
+}
+
+fn fold_case(char: char) -> char {
+    char.to_lowercase().next().unwrap_or(char)
+}
+
+fn is_word_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
@@ -0,0 +1,209 @@
+use crate::{
+    regex::{Regex, RegexError, RegexInput},
+    text::{Lines, Position},
+};
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub regex: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Search {
+    pattern: String,
+    options: SearchOptions,
+    matcher: Matcher,
+}
+
+impl Search {
+    pub fn new(pattern: &str, options: SearchOptions) -> Result<Self, RegexError> {
+        let matcher = if options.regex {
+            Matcher::Regex(Regex::new(pattern, !options.case_sensitive)?)
+        } else if options.case_sensitive {
+            Matcher::Literal(pattern.to_string())
+        } else {
+            Matcher::Literal(pattern.to_lowercase())
+        };
+        Ok(Self {
+            pattern: pattern.to_string(),
+            options,
+            matcher,
+        })
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn options(&self) -> SearchOptions {
+        self.options
+    }
+
+    // Finds all non-empty, non-overlapping matches. Matches never span lines.
+    pub fn find_all(&self, lines: &Lines, word_separators: &[char]) -> Vec<SearchMatch> {
+        self.find_up_to(lines, word_separators, usize::MAX)
+    }
+
+    // Same as `find_all`, but stops looking once it has found `max_count` matches.
+    pub fn find_up_to(&self, lines: &Lines, word_separators: &[char], max_count: usize) -> Vec<SearchMatch> {
+        let mut matches = Vec::new();
+        let mut input = RegexInput::default();
+        for (line_index, line) in lines.iter().enumerate() {
+            if matches.len() >= max_count {
+                break;
+            }
+            if let Matcher::Regex(_) = self.matcher {
+                input.set_haystack(line);
+            }
+            let mut byte_index = 0;
+            while let Some((start, end)) = self.find_in_line(line, &mut input, byte_index, word_separators) {
+                matches.push(SearchMatch {
+                    start: Position {
+                        line_index,
+                        byte_index: start,
+                    },
+                    end: Position {
+                        line_index,
+                        byte_index: end,
+                    },
+                });
+                byte_index = end;
+                if matches.len() >= max_count {
+                    break;
+                }
+            }
+        }
+        matches
+    }
+
+    // `input` holds the decoded line for regex searches.
+    fn find_in_line(
+        &self,
+        line: &str,
+        input: &mut RegexInput,
+        start: usize,
+        word_separators: &[char],
+    ) -> Option<(usize, usize)> {
+        let mut start = start;
+        loop {
+            let (match_start, match_end) = match &self.matcher {
+                Matcher::Literal(pattern) if pattern.is_empty() => return None,
+                Matcher::Literal(pattern) => find_literal(
+                    line,
+                    start,
+                    pattern,
+                    self.options.case_sensitive,
+                )?,
+                Matcher::Regex(regex) => {
+                    let range = regex.find_in(input, start)?;
+                    (range.start, range.end)
+                }
+            };
+            let is_match = match_start < match_end
+                && (!self.options.whole_word
+                    || is_separator_or_line_boundary(line[..match_start].chars().next_back(), word_separators)
+                        && is_separator_or_line_boundary(line[match_end..].chars().next(), word_separators));
+            if is_match {
+                return Some((match_start, match_end));
+            }
+            start = match_start + line[match_start..].chars().next()?.len_utf8();
+        }
+    }
+
+    // The text that replaces `search_match`. For regex searches, `$0` to `$9` and `${n}`
+    // refer to capture groups and `$$` is a literal `$`.
+    pub fn expand_replacement(&self, lines: &Lines, search_match: SearchMatch, replacement: &str) -> String {
+        let regex = match &self.matcher {
+            Matcher::Regex(regex) => regex,
+            Matcher::Literal(_) => return replacement.to_string(),
+        };
+        let line = &lines[search_match.start.line_index];
+        let captures = regex
+            .captures_at(line, search_match.start.byte_index)
+            .filter(|captures| {
+                captures[0]
+                    .as_ref()
+                    .is_some_and(|range| range.start == search_match.start.byte_index)
+            })
+            .unwrap_or_default();
+        let group = |index: usize| {
+            captures
+                .get(index)
+                .and_then(|range| range.clone())
+                .map_or("", |range| &line[range])
+        };
+        let mut string = String::new();
+        let mut rest = replacement;
+        while let Some(index) = rest.find('$') {
+            string.push_str(&rest[..index]);
+            rest = &rest[index + 1..];
+            if rest.starts_with('$') {
+                string.push('$');
+                rest = &rest[1..];
+            } else if rest.starts_with(|char: char| char.is_ascii_digit()) {
+                string.push_str(group(rest[..1].parse().unwrap()));
+                rest = &rest[1..];
+            } else if let Some((index, len)) = rest.strip_prefix('{').and_then(|rest| {
+                let end = rest.find('}')?;
+                Some((rest[..end].parse::<usize>().ok()?, end + 2))
+            }) {
+                string.push_str(group(index));
+                rest = &rest[len..];
+            } else {
+                string.push('$');
+            }
+        }
+        string.push_str(rest);
+        string
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SearchMatch {
+    pub start: Position,
+    pub end: Position,
+}
+
+#[derive(Clone, Debug)]
+enum Matcher {
+    Literal(String),
+    Regex(Regex),
+}
+
+fn find_literal(
+    line: &str,
+    start: usize,
+    pattern: &str,
+    case_sensitive: bool,
+) -> Option<(usize, usize)> {
+    if case_sensitive {
+        let index = start + line[start..].find(pattern)?;
+        return Some((index, index + pattern.len()));
+    }
+    // Lowercasing can change the length of a char, so compare char by char instead of
+    // searching a lowercased copy of the line.
+    line[start..].char_indices().find_map(|(index, _)| {
+        let match_start = start + index;
+        let mut pattern_chars = pattern.chars();
+        let mut line_chars = line[match_start..].char_indices();
+        loop {
+            let expected = match pattern_chars.next() {
+                Some(expected) => expected,
+                None => {
+                    let match_end = line_chars.next().map_or(line.len(), |(index, _)| match_start + index);
+                    return Some((match_start, match_end));
+                }
+            };
+            match line_chars.next() {
+                Some((_, char)) if char.to_lowercase().eq(Some(expected)) => {}
+                _ => return None,
+            }
+        }
+    })
+}
+
+fn is_separator_or_line_boundary(char: Option<char>, word_separators: &[char]) -> bool {
+    char.is_none_or(|char| char.is_whitespace() || word_separators.contains(&char))
+}
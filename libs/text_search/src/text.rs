@@ -0,0 +1,673 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    io,
+    io::BufRead,
+    iter,
+    ops::{Add, AddAssign, Index, Range, Sub, SubAssign},
+    slice,
+};
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Text {
+    lines: Lines,
+}
+
+impl Text {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn newline() -> Self {
+        Self {
+            lines: Lines::from(vec![String::new(), String::new()]),
+        }
+    }
+
+    pub fn from_buf_reader<R>(reader: R) -> io::Result<Self>
+    where
+        R: BufRead,
+    {
+        let mut lines = reader.lines().collect::<Result<Vec<_>, _>>()?;
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        Ok(Self {
+            lines: Lines::from(lines),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length() == Length::zero()
+    }
+
+    pub fn length(&self) -> Length {
+        Length {
+            line_count: self.lines.len() - 1,
+            byte_count: self.lines.last().unwrap().len(),
+        }
+    }
+
+    pub fn to_single_char(&self) -> Option<char> {
+        if self.lines.len() > 1 {
+            return None;
+        }
+        let mut chars = self.lines[0].chars();
+        match (chars.next(), chars.next()) {
+            (Some(char), None) => Some(char),
+            _ => None,
+        }
+    }
+
+    pub fn as_lines(&self) -> &Lines {
+        &self.lines
+    }
+
+    pub fn slice(&self, start: Position, length: Length) -> Self {
+        let end = start + length;
+        let mut lines = Vec::new();
+        if start.line_index == end.line_index {
+            lines.push(self.lines[start.line_index][start.byte_index..end.byte_index].to_string());
+        } else {
+            lines.reserve(end.line_index - start.line_index + 1);
+            lines.push(self.lines[start.line_index][start.byte_index..].to_string());
+            lines.extend(
+                self.lines
+                    .range(start.line_index + 1..end.line_index)
+                    .cloned(),
+            );
+            lines.push(self.lines[end.line_index][..end.byte_index].to_string());
+        }
+        Text {
+            lines: Lines::from(lines),
+        }
+    }
+
+    pub fn apply_change(&mut self, change: Change) {
+        match change {
+            Change::Insert(position, text) => self.insert(position, text),
+            Change::Delete(start, length) => self.delete(start, length),
+        }
+    }
+
+    pub fn into_lines(self) -> Vec<String> {
+        self.lines.into_vec()
+    }
+
+    fn insert(&mut self, point: Position, text: Self) {
+        if text.length().line_count == 0 {
+            self.lines.get_mut(point.line_index).replace_range(
+                point.byte_index..point.byte_index,
+                text.lines.first().unwrap(),
+            );
+        } else {
+            let mut lines = text.lines.into_vec();
+            lines
+                .first_mut()
+                .unwrap()
+                .replace_range(..0, &self.lines[point.line_index][..point.byte_index]);
+            lines
+                .last_mut()
+                .unwrap()
+                .push_str(&self.lines[point.line_index][point.byte_index..]);
+            self.lines
+                .splice(point.line_index..point.line_index + 1, lines);
+        }
+    }
+
+    fn delete(&mut self, start: Position, length: Length) {
+        let end = start + length;
+        if start.line_index == end.line_index {
+            self.lines
+                .get_mut(start.line_index)
+                .replace_range(start.byte_index..end.byte_index, "");
+        } else {
+            let mut line = self.lines[start.line_index][..start.byte_index].to_string();
+            line.push_str(&self.lines[end.line_index][end.byte_index..]);
+            self.lines
+                .splice(start.line_index..end.line_index + 1, iter::once(line));
+        }
+    }
+}
+
+impl Default for Text {
+    fn default() -> Self {
+        Self {
+            lines: Lines::from(vec![String::new()]),
+        }
+    }
+}
+
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines = self.lines.iter();
+        let last_line = lines.next_back().unwrap();
+        for line in lines {
+            writeln!(f, "{}", line)?;
+        }
+        write!(f, "{}", last_line)
+    }
+}
+
+impl From<char> for Text {
+    fn from(char: char) -> Self {
+        Self {
+            lines: Lines::from(vec![String::from(char)]),
+        }
+    }
+}
+
+impl From<&str> for Text {
+    fn from(string: &str) -> Self {
+        Self {
+            lines: string.split('\n').map(|string| string.to_owned()).collect(),
+        }
+    }
+}
+
+impl From<&String> for Text {
+    fn from(string: &String) -> Self {
+        string.as_str().into()
+    }
+}
+
+impl From<String> for Text {
+    fn from(string: String) -> Self {
+        string.as_str().into()
+    }
+}
+
+impl FromIterator<char> for Text {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = char>,
+    {
+        Text::from(iter.into_iter().collect::<String>())
+    }
+}
+
+// The lines of a `Text`, stored as a sequence of chunks so that inserting or removing lines
+// only moves the lines of the chunks involved instead of all lines after the edit. Chunks
+// are kept between `MIN_CHUNK_LEN` and `MAX_CHUNK_LEN` lines long, except when there is only
+// one of them.
+#[derive(Clone, Default)]
+pub struct Lines {
+    chunks: Vec<Vec<String>>,
+    chunk_starts: Vec<usize>,
+    len: usize,
+}
+
+const CHUNK_LEN: usize = 512;
+const MIN_CHUNK_LEN: usize = CHUNK_LEN / 4;
+const MAX_CHUNK_LEN: usize = CHUNK_LEN * 2;
+
+impl Lines {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<&String> {
+        if index >= self.len {
+            return None;
+        }
+        let (chunk_index, offset) = self.locate(index);
+        Some(&self.chunks[chunk_index][offset])
+    }
+
+    pub fn first(&self) -> Option<&String> {
+        self.chunks.first().and_then(|chunk| chunk.first())
+    }
+
+    pub fn last(&self) -> Option<&String> {
+        self.chunks.last().and_then(|chunk| chunk.last())
+    }
+
+    pub fn iter(&self) -> LinesIter<'_> {
+        self.range(0..self.len)
+    }
+
+    pub fn range(&self, range: Range<usize>) -> LinesIter<'_> {
+        assert!(range.start <= range.end && range.end <= self.len);
+        if range.start == range.end {
+            return LinesIter {
+                front: [].iter(),
+                chunks: [].iter(),
+                back: [].iter(),
+                len: 0,
+            };
+        }
+        let (start_chunk_index, start_offset) = self.locate(range.start);
+        let (end_chunk_index, end_offset) = self.locate(range.end);
+        if start_chunk_index == end_chunk_index {
+            LinesIter {
+                front: self.chunks[start_chunk_index][start_offset..end_offset].iter(),
+                chunks: [].iter(),
+                back: [].iter(),
+                len: range.len(),
+            }
+        } else {
+            LinesIter {
+                front: self.chunks[start_chunk_index][start_offset..].iter(),
+                chunks: self.chunks[start_chunk_index + 1..end_chunk_index].iter(),
+                back: self.chunks[end_chunk_index][..end_offset].iter(),
+                len: range.len(),
+            }
+        }
+    }
+
+    pub fn into_vec(self) -> Vec<String> {
+        self.chunks.into_iter().flatten().collect()
+    }
+
+    fn get_mut(&mut self, index: usize) -> &mut String {
+        assert!(index < self.len);
+        let (chunk_index, offset) = self.locate(index);
+        &mut self.chunks[chunk_index][offset]
+    }
+
+    // Replaces the lines in `range` with `lines`.
+    fn splice(&mut self, range: Range<usize>, lines: impl IntoIterator<Item = String>) {
+        assert!(range.start <= range.end && range.end <= self.len);
+        let lines: Vec<String> = lines.into_iter().collect();
+        let new_len = self.len - range.len() + lines.len();
+        if self.chunks.is_empty() {
+            self.chunks = chunk_lines(lines);
+            self.len = new_len;
+            self.update_chunk_starts(0);
+            return;
+        }
+        let (mut start_chunk_index, start_offset) = self.locate(range.start);
+        let (mut end_chunk_index, end_offset) = self.locate(range.end);
+        let mut chunk_len = self.chunks[start_chunk_index..=end_chunk_index]
+            .iter()
+            .map(|chunk| chunk.len())
+            .sum::<usize>()
+            - range.len()
+            + lines.len();
+        if start_chunk_index == end_chunk_index
+            && (chunk_len >= MIN_CHUNK_LEN || self.chunks.len() == 1)
+            && chunk_len <= MAX_CHUNK_LEN
+        {
+            self.chunks[start_chunk_index].splice(start_offset..end_offset, lines);
+        } else {
+            let mut start = self.chunk_starts[start_chunk_index];
+            // Merge a chunk that would become too short with one of its neighbours.
+            if chunk_len < MIN_CHUNK_LEN {
+                if end_chunk_index + 1 < self.chunks.len() {
+                    end_chunk_index += 1;
+                    chunk_len += self.chunks[end_chunk_index].len();
+                } else if start_chunk_index > 0 {
+                    start_chunk_index -= 1;
+                    chunk_len += self.chunks[start_chunk_index].len();
+                    start = self.chunk_starts[start_chunk_index];
+                }
+            }
+            let mut merged_lines = Vec::with_capacity(chunk_len);
+            let mut old_lines = self
+                .chunks
+                .drain(start_chunk_index..=end_chunk_index)
+                .flatten();
+            merged_lines.extend(old_lines.by_ref().take(range.start - start));
+            merged_lines.extend(lines);
+            merged_lines.extend(old_lines.skip(range.len()));
+            self.chunks
+                .splice(start_chunk_index..start_chunk_index, chunk_lines(merged_lines));
+        }
+        self.len = new_len;
+        self.update_chunk_starts(start_chunk_index);
+    }
+
+    // Returns the chunk that contains the line at `index`, and the offset of the line in
+    // that chunk. An index one past the last line is located at the end of the last chunk.
+    fn locate(&self, index: usize) -> (usize, usize) {
+        let chunk_index = self
+            .chunk_starts
+            .partition_point(|&chunk_start| chunk_start <= index)
+            .saturating_sub(1);
+        (chunk_index, index - self.chunk_starts[chunk_index])
+    }
+
+    fn update_chunk_starts(&mut self, start_chunk_index: usize) {
+        self.chunk_starts.truncate(start_chunk_index);
+        let mut start = match start_chunk_index {
+            0 => 0,
+            _ => {
+                self.chunk_starts[start_chunk_index - 1] + self.chunks[start_chunk_index - 1].len()
+            }
+        };
+        for chunk in &self.chunks[start_chunk_index..] {
+            self.chunk_starts.push(start);
+            start += chunk.len();
+        }
+    }
+}
+
+impl From<Vec<String>> for Lines {
+    fn from(lines: Vec<String>) -> Self {
+        let mut this = Self {
+            len: lines.len(),
+            chunks: chunk_lines(lines),
+            chunk_starts: Vec::new(),
+        };
+        this.update_chunk_starts(0);
+        this
+    }
+}
+
+impl FromIterator<String> for Lines {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = String>,
+    {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl Index<usize> for Lines {
+    type Output = String;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        match self.get(index) {
+            Some(line) => line,
+            None => panic!("line index {} out of range for {} lines", index, self.len),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a Lines {
+    type Item = &'a String;
+    type IntoIter = LinesIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl fmt::Debug for Lines {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// Two `Lines` are equal if their lines are, regardless of how they are chunked.
+impl PartialEq for Lines {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for Lines {}
+
+impl Hash for Lines {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.len.hash(state);
+        for line in self {
+            line.hash(state);
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LinesIter<'a> {
+    front: slice::Iter<'a, String>,
+    chunks: slice::Iter<'a, Vec<String>>,
+    back: slice::Iter<'a, String>,
+    len: usize,
+}
+
+impl<'a> Iterator for LinesIter<'a> {
+    type Item = &'a String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(line) = self.front.next() {
+                self.len -= 1;
+                return Some(line);
+            }
+            match self.chunks.next() {
+                Some(chunk) => self.front = chunk.iter(),
+                None => break,
+            }
+        }
+        let line = self.back.next()?;
+        self.len -= 1;
+        Some(line)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a> DoubleEndedIterator for LinesIter<'a> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(line) = self.back.next_back() {
+                self.len -= 1;
+                return Some(line);
+            }
+            match self.chunks.next_back() {
+                Some(chunk) => self.back = chunk.iter(),
+                None => break,
+            }
+        }
+        let line = self.front.next_back()?;
+        self.len -= 1;
+        Some(line)
+    }
+}
+
+impl<'a> ExactSizeIterator for LinesIter<'a> {}
+
+// Splits `lines` into chunks of at most `CHUNK_LEN` lines that are as equally long as possible.
+fn chunk_lines(lines: Vec<String>) -> Vec<Vec<String>> {
+    let chunk_count = lines.len().div_ceil(CHUNK_LEN);
+    let mut chunks = Vec::with_capacity(chunk_count);
+    let mut lines = lines.into_iter();
+    for index in 0..chunk_count {
+        let len = lines.len() / (chunk_count - index);
+        chunks.push(lines.by_ref().take(len).collect());
+    }
+    chunks
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Edit {
+    pub change: Change,
+    pub drift: Drift,
+}
+
+impl Edit {
+    pub fn invert(self, text: &Text) -> Self {
+        Self {
+            change: self.change.invert(text),
+            drift: self.drift,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Change {
+    Insert(Position, Text),
+    Delete(Position, Length),
+}
+
+impl Change {
+    pub fn invert(self, text: &Text) -> Self {
+        match self {
+            Self::Insert(position, text) => Change::Delete(position, text.length()),
+            Self::Delete(start, length) => Change::Insert(start, text.slice(start, length)),
+        }
+    }
+
+    // Grows `line_range`, a range of lines that need to be recomputed, to also cover the lines
+    // touched by this change, and shifts it past any lines that the change inserts or removes.
+    // An empty range means that no lines need to be recomputed.
+    pub fn extend_dirty_line_range(&self, line_range: &mut Range<usize>) {
+        let (line_index, inserted_line_count, deleted_line_count) = match *self {
+            Self::Insert(position, ref text) => (position.line_index, text.length().line_count, 0),
+            Self::Delete(start, length) => (start.line_index, 0, length.line_count),
+        };
+        let shift = |index: usize| {
+            if index <= line_index {
+                index
+            } else {
+                index.max(line_index + 1 + deleted_line_count) - deleted_line_count
+                    + inserted_line_count
+            }
+        };
+        let start = line_index;
+        let end = line_index + 1 + inserted_line_count;
+        *line_range = if line_range.start >= line_range.end {
+            start..end
+        } else {
+            shift(line_range.start).min(start)..shift(line_range.end).max(end)
+        };
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Position {
+    pub line_index: usize,
+    pub byte_index: usize,
+}
+
+impl Position {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn apply_edit(self, edit: &Edit) -> Self {
+        match edit.change {
+            Change::Insert(point, ref text) => match self.cmp(&point) {
+                Ordering::Less => self,
+                Ordering::Equal => match edit.drift {
+                    Drift::Before => point + text.length() + (self - point),
+                    Drift::After => self,
+                },
+                Ordering::Greater => point + text.length() + (self - point),
+            },
+            Change::Delete(start, length) => {
+                let end = start + length;
+                if self < start {
+                    self
+                } else {
+                    start + (self - end.min(self))
+                }
+            }
+        }
+    }
+}
+
+impl Add<Length> for Position {
+    type Output = Self;
+
+    fn add(self, length: Length) -> Self::Output {
+        if length.line_count == 0 {
+            Self {
+                line_index: self.line_index,
+                byte_index: self.byte_index + length.byte_count,
+            }
+        } else {
+            Self {
+                line_index: self.line_index + length.line_count,
+                byte_index: length.byte_count,
+            }
+        }
+    }
+}
+
+impl AddAssign<Length> for Position {
+    fn add_assign(&mut self, length: Length) {
+        *self = *self + length;
+    }
+}
+
+impl Sub for Position {
+    type Output = Length;
+
+    fn sub(self, other: Self) -> Self::Output {
+        if self.line_index == other.line_index {
+            Length {
+                line_count: 0,
+                byte_count: self.byte_index - other.byte_index,
+            }
+        } else {
+            Length {
+                line_count: self.line_index - other.line_index,
+                byte_count: self.byte_index,
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Length {
+    pub line_count: usize,
+    pub byte_count: usize,
+}
+
+impl Length {
+    pub fn zero() -> Length {
+        Self::default()
+    }
+}
+
+impl Add for Length {
+    type Output = Length;
+
+    fn add(self, other: Self) -> Self::Output {
+        if other.line_count == 0 {
+            Self {
+                line_count: self.line_count,
+                byte_count: self.byte_count + other.byte_count,
+            }
+        } else {
+            Self {
+                line_count: self.line_count + other.line_count,
+                byte_count: other.byte_count,
+            }
+        }
+    }
+}
+
+impl AddAssign for Length {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Length {
+    type Output = Length;
+
+    fn sub(self, other: Self) -> Self::Output {
+        if self.line_count == other.line_count {
+            Self {
+                line_count: 0,
+                byte_count: self.byte_count - other.byte_count,
+            }
+        } else {
+            Self {
+                line_count: self.line_count - other.line_count,
+                byte_count: self.byte_count,
+            }
+        }
+    }
+}
+
+impl SubAssign for Length {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Drift {
+    Before,
+    After,
+}
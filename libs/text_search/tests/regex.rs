@@ -0,0 +1,127 @@
+use makepad_text_search::regex::{Regex, RegexInput};
+
+#[test]
+fn regex_matches_like_a_backtracking_engine() {
+    let cases = [
+        ("abc", "xxabcxx", Some((2, 5))),
+        ("a|ab", "ab", Some((0, 1))),
+        ("ab|a", "ab", Some((0, 2))),
+        ("a*", "bbb", Some((0, 0))),
+        ("a+", "baaab", Some((1, 4))),
+        ("a+?", "baaab", Some((1, 2))),
+        ("<.*>", "<a><b>", Some((0, 6))),
+        ("<.*?>", "<a><b>", Some((0, 3))),
+        ("colou?r", "the color", Some((4, 9))),
+        ("\\d{2,3}", "a1234", Some((1, 4))),
+        ("\\d{2}", "a1b23", Some((3, 5))),
+        ("x{2,}", "xxxxy", Some((0, 4))),
+        ("[a-c]+", "xxbcaz", Some((2, 5))),
+        ("[^a-c ]+", "abc def", Some((4, 7))),
+        ("[]a]+", "x]a]", Some((1, 4))),
+        ("[a-]+", "x-a-", Some((1, 4))),
+        ("\\bfoo\\b", "foobar foo", Some((7, 10))),
+        ("\\Boo", "foo", Some((1, 3))),
+        ("^fn", "fn main", Some((0, 2))),
+        ("^main", "fn main", None),
+        ("main$", "fn main", Some((3, 7))),
+        ("(a|b)*c", "ababc", Some((0, 5))),
+        ("(a*)*b", "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaac", None),
+        ("\\w+\\s*=\\s*\\S+", "let x = 42;", Some((4, 11))),
+        ("a\\.b", "axb a.b", Some((4, 7))),
+        ("{", "a{b", Some((1, 2))),
+        ("é+", "caféé", Some((3, 7))),
+    ];
+    for (pattern, haystack, expected) in cases {
+        let regex = Regex::new(pattern, false).unwrap();
+        assert_eq!(
+            regex.find_at(haystack, 0).map(|range| (range.start, range.end)),
+            expected,
+            "`{}` in `{}`",
+            pattern,
+            haystack
+        );
+    }
+}
+
+#[test]
+fn regex_captures_groups() {
+    let regex = Regex::new("(\\w+)=(?:(\\d+)|(\\w+))", false).unwrap();
+    assert_eq!(regex.capture_count(), 4);
+    assert_eq!(
+        regex.captures_at("  key=value", 0).unwrap(),
+        vec![Some(2..11), Some(2..5), None, Some(6..11)]
+    );
+    assert_eq!(
+        regex.captures_at("a=1 b=2", 1).unwrap(),
+        vec![Some(4..7), Some(4..5), Some(6..7), None]
+    );
+}
+
+#[test]
+fn regex_is_case_insensitive_on_request() {
+    let regex = Regex::new("hello [a-z]+", true).unwrap();
+    assert_eq!(regex.find_at("say HELLO World", 0), Some(4..15));
+    assert_eq!(Regex::new("hello", false).unwrap().find_at("HELLO", 0), None);
+}
+
+#[test]
+fn regex_reports_invalid_patterns() {
+    for (pattern, char_index) in [
+        ("(ab", 3),
+        ("ab)", 2),
+        ("*a", 0),
+        ("a**", 2),
+        ("[ab", 0),
+        ("a\\", 2),
+        ("\\q", 1),
+        ("[z-a]", 4),
+        ("a{3,1}", 1),
+    ] {
+        let error = Regex::new(pattern, false).unwrap_err();
+        assert_eq!(error.char_index, char_index, "`{}`: {}", pattern, error);
+    }
+}
+
+#[test]
+fn regex_rejects_patterns_that_compile_too_large() {
+    for pattern in ["((a{1000}){1000}){1000}", "(a{1,1000}){1000}", "((((a|b){100}){100}){100}){100}"] {
+        let error = Regex::new(pattern, false).unwrap_err();
+        assert_eq!(error.message, "pattern is too large", "`{}`", pattern);
+    }
+    assert!(Regex::new("(a{100}){10}", false).is_ok());
+}
+
+#[test]
+fn regex_reuses_input_between_matches() {
+    let regex = Regex::new("a+b", false).unwrap();
+    let mut input = RegexInput::default();
+    input.set_haystack("aab xab ab");
+    assert_eq!(regex.find_in(&mut input, 0), Some(0..3));
+    assert_eq!(regex.find_in(&mut input, 3), Some(5..7));
+    assert_eq!(regex.find_in(&mut input, 7), Some(8..10));
+    assert_eq!(regex.find_in(&mut input, 10), None);
+    input.set_haystack("b");
+    assert_eq!(regex.find_in(&mut input, 0), None);
+    input.set_haystack("caaaaab");
+    assert_eq!(regex.find_in(&mut input, 0), Some(1..7));
+}
+
+#[test]
+fn regex_matches_counted_repeats_on_long_lines() {
+    let regex = Regex::new("(a{400})b", false).unwrap();
+    let mut input = RegexInput::default();
+    let line = format!("{}b", "a".repeat(5_000));
+    input.set_haystack(&line);
+    assert_eq!(regex.find_in(&mut input, 0), Some(4_600..5_001));
+    assert_eq!(
+        regex.captures_in(&mut input, 0).unwrap(),
+        vec![Some(4_600..5_001), Some(4_600..5_000)]
+    );
+    assert_eq!(regex.find_in(&mut input, 4_601), None);
+    let regex = Regex::new("[a-z]{1000}$", false).unwrap();
+    assert_eq!(regex.find_in(&mut input, 0), Some(4_001..5_001));
+    input.set_haystack(&"a".repeat(5_000));
+    assert_eq!(Regex::new("[a-z]{400}b", false).unwrap().find_in(&mut input, 0), None);
+    input.set_haystack("aab");
+    assert_eq!(Regex::new("a+b", false).unwrap().find_in(&mut input, 0), Some(0..3));
+}
@@ -9,6 +9,8 @@ fn main() {
             match config{
                 "lines"=>println!("cargo:rustc-cfg=lines"), 
                 "linux_direct"=>println!("cargo:rustc-cfg=linux_direct"), 
+                "wasm_bundle"=>println!("cargo:rustc-cfg=wasm_bundle"), 
+                "linux_package"=>println!("cargo:rustc-cfg=linux_package"), 
                 _=>{}
             }
         }
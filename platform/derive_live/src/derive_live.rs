@@ -74,6 +74,10 @@ fn derive_live_impl_inner(parser: &mut TokenParser, tb: &mut TokenBuilder) -> Re
             tb.add("         self.").ident(&animator_field.name).add(".animate_to_live(cx, state);");
             tb.add("         self.animator_apply_state(cx);");
             tb.add("    }");
+            tb.add("    fn animator_play_delayed(&mut self, cx: &mut Cx, state: &[LiveId;2], delay: f64) {");
+            tb.add("         self.").ident(&animator_field.name).add(".animate_to_live_delayed(cx, state, delay);");
+            tb.add("         self.animator_apply_state(cx);");
+            tb.add("    }");
             tb.add("    fn animator_in_state(&self, cx: &Cx, check_state_pair: &[LiveId; 2]) -> bool{");
             tb.add("         self.").ident(&animator_field.name).add(".animator_in_state(cx, check_state_pair)");
             tb.add("    }");
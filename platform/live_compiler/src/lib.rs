@@ -96,6 +96,7 @@ pub use {
         makepad_live_tokenizer::{LiveErrorOrigin, live_error_origin},
         live_error::{
             LiveError,
+            LiveErrorSpan,
             LiveFileError
         },
         live_document::{LiveOriginal, LiveExpanded}
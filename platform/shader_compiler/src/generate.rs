@@ -76,7 +76,46 @@ pub trait BackendWriter {
     
     fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit);
     fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]);
-    
+
+    // the hooks below default to C-style output, backends with a different syntax override them
+
+    // inout params are pointers that need an explicit & at the callsite and * in the body
+    fn inout_params_are_pointers(&self) -> bool {
+        false
+    }
+
+    // params can't be assigned to, so they are copied into locals at the start of the fn
+    fn params_are_immutable(&self) -> bool {
+        false
+    }
+
+    // a ? b : c is written as select(c, b, a)
+    fn use_select_for_cond_expr(&self) -> bool {
+        false
+    }
+
+    // assignments are statements rather than expressions, so they can't be wrapped in parens
+    fn assignments_are_stmts(&self) -> bool {
+        false
+    }
+
+    // called with the output offset before the code for a fn or statement is written,
+    // and with None where generated code without a source starts again
+    fn record_source_span(&self, _offset: usize, _span: Option<TokenSpan>) {
+    }
+
+    fn write_fn_def_head(&self, string: &mut String, ident: &dyn fmt::Display, return_ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, return_ty);
+        write!(string, "(").unwrap();
+    }
+
+    fn write_fn_def_tail(&self, string: &mut String, _return_ty: &Ty) {
+        write!(string, ") ").unwrap();
+    }
+
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        self.write_var_decl(string, "", false, false, ident, ty);
+    }
 }
 
 pub struct BlockGenerator<'a> {
@@ -100,9 +139,9 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
     if !backend_writer.use_cons_fn(&cons_name) {
         return
     }
-    backend_writer.write_ty_lit(string, ty_lit);
-    write!(string, " {}(", cons_name).unwrap();
-    
+    let ty = ty_lit.to_ty();
+    backend_writer.write_fn_def_head(string, &cons_name, &ty);
+
     let mut sep = "";
     if param_tys.len() == 1 {
         backend_writer.write_var_decl(string, sep, false, false, &Ident(live_id!(x)), &param_tys[0]);
@@ -114,11 +153,11 @@ pub fn generate_cons_fn(backend_writer:&dyn BackendWriter, string: &mut String,
         }
     }
     
-    writeln!(string, ") {{").unwrap();
+    backend_writer.write_fn_def_tail(string, &ty);
+    writeln!(string, "{{").unwrap();
     write!(string, "    return ").unwrap();
     backend_writer.write_ty_lit(string, ty_lit);
     write!(string, "(").unwrap();
-    let ty = ty_lit.to_ty();
     if param_tys.len() == 1 {
         let param_ty = &param_tys[0];
         match param_ty {
@@ -199,6 +238,7 @@ impl<'a> BlockGenerator<'a> {
     
     fn generate_stmt(&mut self, stmt: &Stmt) {
         self.write_indent();
+        self.backend_writer.record_source_span(self.string.len(), Some(stmt.span()));
         match *stmt {
             Stmt::Break {span} => self.generate_break_stmt(span),
             Stmt::Continue {span} => self.generate_continue_stmt(span),
@@ -285,9 +325,11 @@ impl<'a> BlockGenerator<'a> {
         } else {
             -1
         };
+        write!(self.string, "for (").unwrap();
+        self.backend_writer.write_let_decl(self.string, &ident, &Ty::Int);
         write!(
             self.string,
-            "for (int {0} = {1}; {0} {2} {3}; {0} {4} {5}) ",
+            " = {1}; {0} {2} {3}; {0} {4} {5}) ",
             ident,
             if from <= to {from} else {from - 1},
             if from <= to {"<"} else {">="},
@@ -358,11 +400,8 @@ impl<'a> BlockGenerator<'a> {
         expr: &Option<Expr>,
         shadow: &Cell<Option<ScopeSymShadow >>
     ) {
-        self.backend_writer.write_var_decl(
+        self.backend_writer.write_let_decl(
             &mut self.string,
-            "",
-            false, 
-            false,
             &DisplayVarName(ident, shadow.get().unwrap()),
             ty.borrow().as_ref().unwrap()
         );
@@ -388,7 +427,33 @@ impl<'a> BlockGenerator<'a> {
     }
     
     fn generate_expr_stmt(&mut self, _span: TokenSpan, expr: &Expr) {
-        self.generate_expr(expr);
+        match expr.kind {
+            ExprKind::Bin {
+                op: op @ (BinOp::Assign | BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign),
+                ref left_expr,
+                ref right_expr,
+                ..
+            } if self.backend_writer.assignments_are_stmts() => {
+                // a chained assignment like a = b = c becomes b = c; a = b;
+                if let ExprKind::Bin {
+                    op: BinOp::Assign | BinOp::AddAssign | BinOp::SubAssign | BinOp::MulAssign | BinOp::DivAssign,
+                    left_expr: ref inner_left_expr,
+                    ..
+                } = right_expr.kind {
+                    self.generate_expr_stmt(right_expr.span, right_expr);
+                    self.write_indent();
+                    self.generate_expr(left_expr);
+                    write!(self.string, " {} ", op).unwrap();
+                    self.generate_expr(inner_left_expr);
+                }
+                else {
+                    self.generate_expr(left_expr);
+                    write!(self.string, " {} ", op).unwrap();
+                    self.generate_expr(right_expr);
+                }
+            }
+            _ => self.generate_expr(expr)
+        }
         writeln!(self.string, ";").unwrap();
     }
     
@@ -566,6 +631,16 @@ impl<'a> ExprGenerator<'a> {
         expr_if_true: &Expr,
         expr_if_false: &Expr,
     ) {
+        if self.backend_writer.use_select_for_cond_expr() {
+            write!(self.string, "select(").unwrap();
+            self.generate_expr(expr_if_false);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr_if_true);
+            write!(self.string, ", ").unwrap();
+            self.generate_expr(expr);
+            write!(self.string, ")").unwrap();
+            return
+        }
         write!(self.string, "(").unwrap();
         self.generate_expr(expr);
         write!(self.string, " ? ").unwrap();
@@ -711,7 +786,7 @@ impl<'a> ExprGenerator<'a> {
             )).unwrap();
             
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for (index, arg_expr) in arg_exprs.iter().enumerate() {
                 // check if the args is a closure, ifso skip it
                 match arg_expr.ty.borrow().as_ref().unwrap(){
                     Ty::ClosureDef(_)=>{
@@ -721,7 +796,7 @@ impl<'a> ExprGenerator<'a> {
                 }
                 
                 write!(self.string, "{}", sep).unwrap();
-                self.generate_expr(arg_expr);
+                self.generate_arg_expr(fn_def, arg_exprs, index);
                 sep = ", ";
             }
             // and now the closed over values
@@ -748,9 +823,9 @@ impl<'a> ExprGenerator<'a> {
         else {
             write!(self.string, "{}_{} (", fn_def.fn_ptr, fn_def.ident).unwrap();
             let mut sep = "";
-            for arg_expr in arg_exprs {
+            for index in 0..arg_exprs.len() {
                 write!(self.string, "{}", sep).unwrap();
-                self.generate_expr(arg_expr);
+                self.generate_arg_expr(fn_def, arg_exprs, index);
                 sep = ", ";
             }
 
@@ -760,6 +835,15 @@ impl<'a> ExprGenerator<'a> {
         }
     }
     
+    fn generate_arg_expr(&mut self, fn_def: &FnDef, arg_exprs: &[Expr], index: usize) {
+        // the args line up with the tail of the params, draw shader methods skip self
+        let param_index = (index + fn_def.params.len()).checked_sub(arg_exprs.len());
+        if self.backend_writer.inout_params_are_pointers() && param_index.is_some_and( | i | fn_def.params[i].is_inout) {
+            write!(self.string, "&").unwrap();
+        }
+        self.generate_expr(&arg_exprs[index]);
+    }
+    
     fn generate_field_expr(&mut self, _span: TokenSpan, expr: &Expr, field_ident: Ident, ty:&Ty) {
         match expr.ty.borrow().as_ref() {
             Some(Ty::DrawShader(_)) => {
@@ -926,7 +1010,15 @@ impl<'a> ExprGenerator<'a> {
                 write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
             }
             VarKind::MutLocal {ident, shadow} => {
-                write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
+                let is_pointer = self.backend_writer.inout_params_are_pointers() && self.fn_def.is_some_and( | fn_def | {
+                    fn_def.params.iter().any( | param | param.is_inout && param.ident == ident && param.shadow.get() == Some(shadow))
+                });
+                if is_pointer {
+                    write!(self.string, "(*{})", DisplayVarName(ident, shadow)).unwrap();
+                }
+                else {
+                    write!(self.string, "{}", DisplayVarName(ident, shadow)).unwrap();
+                }
             }
             VarKind::LiveValue(value_node_ptr) => {
                 // this is a live value.. also prefix needed
@@ -960,34 +1052,23 @@ pub struct FnDefGenerator<'a> {
 impl<'a> FnDefGenerator<'a> {
     pub fn generate_fn_def(&mut self) {
         
-        self.backend_writer.write_var_decl(
+        self.backend_writer.record_source_span(self.string.len(), Some(self.fn_def.span));
+        let return_ty = self.fn_def.return_ty.borrow();
+        self.backend_writer.write_fn_def_head(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnName(self.fn_def.fn_ptr, self.fn_def.ident), // here we must expand IdentPath to something
-            self.fn_def.return_ty.borrow().as_ref().unwrap()
+            return_ty.as_ref().unwrap()
         );
-        write!(self.string, "(").unwrap();
-        let mut sep = "";
-        for param in &self.fn_def.params {
-            if !param.shadow.get().is_none() {
-                if self.backend_writer.write_var_decl(
-                    &mut self.string,
-                    sep,
-                    param.is_inout,
-                    false,
-                    &DisplayVarName(param.ident, param.shadow.get().unwrap()),
-                    param.ty_expr.ty.borrow().as_ref().unwrap(),
-                ) {
-                    sep = ", ";
-                }
-            }
-        }
+        let sep = write_fn_def_params(self.backend_writer, self.string, &self.fn_def.params);
         self.backend_writer.write_fn_def_hidden_params(self.string, self.fn_def.hidden_args.borrow().as_ref().unwrap(), sep);
-        write!(self.string, ") ").unwrap();
+        self.backend_writer.write_fn_def_tail(&mut self.string, return_ty.as_ref().unwrap());
+        let has_param_copies = write_param_copies(self.backend_writer, self.string, &self.fn_def.params);
         self.generate_block(&self.fn_def.block);
+        if has_param_copies {
+            write!(self.string, "\n}}").unwrap();
+        }
         writeln!(self.string).unwrap();
+        self.backend_writer.record_source_span(self.string.len(), None);
         //self.visited.insert(self.decl.ident_path);
     }
     
@@ -1077,34 +1158,18 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
     
     pub fn generate_fn_def_with_closure_args(&mut self) {
         
-        self.backend_writer.write_var_decl(
+        self.backend_writer.record_source_span(self.string.len(), Some(self.fn_def.span));
+        let return_ty = self.fn_def.return_ty.borrow();
+        self.backend_writer.write_fn_def_head(
             &mut self.string,
-            "",
-            false,
-            false,
             &DisplayFnNameWithClosureArgs(
                 self.closure_site_info.site_index,
                 self.call_def.fn_ptr,
                 self.fn_def.ident
             ), // here we must expand IdentPath to something
-            self.fn_def.return_ty.borrow().as_ref().unwrap()
+            return_ty.as_ref().unwrap()
         );
-        write!(self.string, "(").unwrap();
-        let mut sep = "";
-        for param in &self.fn_def.params {
-            if !param.shadow.get().is_none() {
-                if self.backend_writer.write_var_decl(
-                    &mut self.string,
-                    sep,
-                    param.is_inout,
-                    false,
-                    &DisplayVarName(param.ident, param.shadow.get().unwrap()),
-                    param.ty_expr.ty.borrow().as_ref().unwrap(),
-                ) {
-                    sep = ", ";
-                }
-            }
-        }
+        let mut sep = write_fn_def_params(self.backend_writer, self.string, &self.fn_def.params);
         // now we iterate over the closures in our site,
         // and we need to merge the set of closed over args.
         for sym in &self.closure_site_info.closure_site.all_closed_over {
@@ -1125,13 +1190,17 @@ impl<'a> FnDefWithClosureArgsGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        write!(self.string, ") ").unwrap();
+        self.backend_writer.write_fn_def_tail(&mut self.string, return_ty.as_ref().unwrap());
+        let has_param_copies = write_param_copies(self.backend_writer, self.string, &self.fn_def.params);
         // alright so here the block is generated.. however
         // we need to know the names and the closed-over-args passthrough
         self.generate_block(&self.fn_def.block);
-        
+        if has_param_copies {
+            write!(self.string, "\n}}").unwrap();
+        }
         
         writeln!(self.string).unwrap();
+        self.backend_writer.record_source_span(self.string.len(), None);
         //self.visited.insert(self.decl.ident_path);
     }
     
@@ -1164,21 +1233,18 @@ pub struct ClosureDefGenerator<'a> {
 impl<'a> ClosureDefGenerator<'a> {
     pub fn generate_fn_def(&mut self) {
         
+        self.backend_writer.record_source_span(self.string.len(), Some(self.closure_def.span));
         let fn_param = &self.fn_def.params[self.closure_site_arg.param_index];
         
         let mut sep = "";
         
         if let TyExprKind::ClosureDecl {params, return_ty, ..} = &fn_param.ty_expr.kind {
             
-            self.backend_writer.write_var_decl(
+            self.backend_writer.write_fn_def_head(
                 &mut self.string,
-                "",
-                false,
-                false,
                 &DisplayClosureName(self.call_def.fn_ptr, self.closure_site_arg.closure_def_index), // here we must expand IdentPath to something
                 return_ty.borrow().as_ref().unwrap(),
             );
-            write!(self.string, "(").unwrap();
             
             // ok we have now params and names
             for (param_index, param) in params.iter().enumerate() {
@@ -1219,7 +1285,10 @@ impl<'a> ClosureDefGenerator<'a> {
         merged_hidden_args.extend(self.call_def.hidden_args.borrow().as_ref().unwrap().iter().cloned());
         self.backend_writer.write_fn_def_hidden_params(self.string, &merged_hidden_args, sep);
         
-        writeln!(self.string, ") {{").unwrap();
+        if let TyExprKind::ClosureDecl {return_ty, ..} = &fn_param.ty_expr.kind {
+            self.backend_writer.write_fn_def_tail(&mut self.string, return_ty.borrow().as_ref().unwrap());
+        }
+        writeln!(self.string, "{{").unwrap();
         
         match &self.closure_def.kind {
             ClosureDefKind::Expr(expr) => {
@@ -1233,6 +1302,7 @@ impl<'a> ClosureDefGenerator<'a> {
                 writeln!(self.string).unwrap();
             }
         }
+        self.backend_writer.record_source_span(self.string.len(), None);
         //self.visited.insert(self.decl.ident_path);
     }
     
@@ -1265,6 +1335,61 @@ impl<'a> ClosureDefGenerator<'a> {
     }
 }
 
+fn write_fn_def_params(backend_writer: &dyn BackendWriter, string: &mut String, params: &[Param]) -> &'static str {
+    let mut sep = "";
+    for param in params {
+        if let Some(shadow) = param.shadow.get() {
+            let param_name = DisplayParamName(param.ident, shadow);
+            let var_name = DisplayVarName(param.ident, shadow);
+            let ident: &dyn fmt::Display = if param_is_copied(backend_writer, param) {&param_name} else {&var_name};
+            if backend_writer.write_var_decl(
+                string,
+                sep,
+                param.is_inout,
+                false,
+                ident,
+                param.ty_expr.ty.borrow().as_ref().unwrap(),
+            ) {
+                sep = ", ";
+            }
+        }
+    }
+    sep
+}
+
+fn param_is_copied(backend_writer: &dyn BackendWriter, param: &Param) -> bool {
+    if !backend_writer.params_are_immutable() || param.is_inout {
+        return false
+    }
+    match param.ty_expr.ty.borrow().as_ref().unwrap() {
+        Ty::DrawShader(_) | Ty::ClosureDecl | Ty::ClosureDef(_) => false,
+        _ => true
+    }
+}
+
+// opens an extra block that copies the immutable params into mutable locals
+fn write_param_copies(backend_writer: &dyn BackendWriter, string: &mut String, params: &[Param]) -> bool {
+    let mut has_copies = false;
+    for param in params {
+        if let Some(shadow) = param.shadow.get() {
+            if !param_is_copied(backend_writer, param) {
+                continue
+            }
+            if !has_copies {
+                write!(string, "{{\n").unwrap();
+                has_copies = true;
+            }
+            write!(string, "    ").unwrap();
+            backend_writer.write_let_decl(string, &DisplayVarName(param.ident, shadow), param.ty_expr.ty.borrow().as_ref().unwrap());
+            write!(string, " = {};\n", DisplayParamName(param.ident, shadow)).unwrap();
+        }
+    }
+    if has_copies {
+        write!(string, "    ").unwrap();
+    }
+    has_copies
+}
+
 pub struct DisplayDsIdent(pub Ident);
 impl fmt::Display for DisplayDsIdent {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -1321,6 +1446,14 @@ impl fmt::Display for DisplayVarName {
     }
 }
 
+pub struct DisplayParamName(pub Ident, pub ScopeSymShadow);
+impl fmt::Display for DisplayParamName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "param_{}_{}", self.0, self.1.0);
+        fmt::Result::Ok(())
+    }
+}
+
 pub struct DisplayClosedOverArg(pub Ident, pub ScopeSymShadow);
 impl fmt::Display for DisplayClosedOverArg {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -2,9 +2,11 @@ use {
     std::{
         fmt,
         fmt::Write,
+        cell::RefCell,
         collections::BTreeSet,
     },
     crate::{
+        makepad_live_compiler::TokenSpan,
         makepad_live_id::{
             live_id,
             LiveId,
@@ -12,34 +14,72 @@ use {
         generate::*,
         swizzle::Swizzle,
         shader_ast::*,
-        shader_registry::ShaderRegistry
+        shader_registry::ShaderRegistry,
+        source_map::ShaderSourceMap,
     }
 };
 
+// The GLSL dialect to generate
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GlslFlavor {
+    // GLSL ES 1.00 for OpenGL ES and WebGL, the platform prepends the version and sample helpers
+    Es100,
+    // GLSL 4.50 for Vulkan, a complete shader with explicit layouts:
+    // set 0 holds the uniform blocks in fields_as_uniform_blocks order followed by
+    // live_table and const_table, all as std140 vec4 arrays.
+    // set 1 holds a texture2D and sampler pair per texture field, at binding 2*i and 2*i+1.
+    // geometries and instances are vertex inputs at consecutive locations in that order.
+    Vulkan450,
+}
+
 pub fn generate_vertex_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    generate_vertex_shader_with_flavor(draw_shader_def, const_table, shader_registry, GlslFlavor::Es100).glsl
+}
+
+pub fn generate_pixel_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+    generate_pixel_shader_with_flavor(draw_shader_def, const_table, shader_registry, GlslFlavor::Es100).glsl
+}
+
+pub struct GlslGeneratedShader {
+    pub glsl: String,
+    // maps the generated lines back to live_design! for driver errors
+    pub source_map: ShaderSourceMap,
+}
+
+pub fn generate_vertex_shader_with_flavor(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry, flavor: GlslFlavor) -> GlslGeneratedShader {
     let mut string = String::new();
+    let backend_writer = GlslBackendWriter {shader_registry, const_table, flavor, source_map: Default::default()};
     DrawShaderGenerator {
         draw_shader_def,
         const_table,
         shader_registry,
         string: &mut string,
-        backend_writer: &GlslBackendWriter {shader_registry, const_table}
+        flavor,
+        backend_writer: &backend_writer
     }
     .generate_vertex_shader();
-    string
+    GlslGeneratedShader {
+        glsl: string,
+        source_map: backend_writer.source_map.into_inner()
+    }
 }
 
-pub fn generate_pixel_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> String {
+pub fn generate_pixel_shader_with_flavor(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry, flavor: GlslFlavor) -> GlslGeneratedShader {
     let mut string = String::new();
+    let backend_writer = GlslBackendWriter {shader_registry, const_table, flavor, source_map: Default::default()};
     DrawShaderGenerator {
         draw_shader_def,
         const_table,
         shader_registry,
         string: &mut string,
-        backend_writer: &GlslBackendWriter {shader_registry, const_table}
+        flavor,
+        backend_writer: &backend_writer
     }
     .generate_pixel_shader();
-    string
+    GlslGeneratedShader {
+        glsl: string,
+        source_map: backend_writer.source_map.into_inner()
+    }
 }
 
 struct DrawShaderGenerator<'a> {
@@ -47,6 +87,7 @@ struct DrawShaderGenerator<'a> {
     shader_registry: &'a ShaderRegistry,
     string: &'a mut String,
     const_table: &'a DrawShaderConstTable,
+    flavor: GlslFlavor,
     backend_writer: &'a dyn BackendWriter
 }
 
@@ -314,7 +355,11 @@ impl<'a> DrawShaderGenerator<'a> {
         // we need to collect all consts
         let pixel_decl = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(pixel))).unwrap();
         write!(self.string, "\n").unwrap();
-        writeln!(self.string, "    gl_FragColor = {}();", DisplayFnName(pixel_decl.fn_ptr, pixel_decl.ident)).unwrap();
+        let frag_color = match self.flavor {
+            GlslFlavor::Es100 => "gl_FragColor",
+            GlslFlavor::Vulkan450 => "frag_color",
+        };
+        writeln!(self.string, "    {} = {}();", frag_color, DisplayFnName(pixel_decl.fn_ptr, pixel_decl.ident)).unwrap();
         writeln!(self.string, "}}").unwrap();
     }
     
@@ -361,15 +406,32 @@ impl<'a> DrawShaderGenerator<'a> {
         packed_instances_size: Option<usize>,
         packed_varyings_size: usize,
     ) {
+        let uniform_blocks = self.draw_shader_def.fields_as_uniform_blocks();
+        let live_slots = self.calc_live_slots();
+        
+        if self.flavor == GlslFlavor::Vulkan450 {
+            writeln!(self.string, "#version 450").unwrap();
+            for sample_fn in ["sample2d", "sample2d_rt", "sample2dOES"] {
+                writeln!(self.string, "vec4 {}(texture2D tex, sampler samp, vec2 pos){{return textureLod(sampler2D(tex, samp), pos, 0.0);}}", sample_fn).unwrap();
+            }
+        }
         
         if self.const_table.table.len()>0 {
-            writeln!(self.string, "uniform float const_table[{}];", self.const_table.table.len()).unwrap();
+            match self.flavor {
+                GlslFlavor::Es100 => writeln!(self.string, "uniform float const_table[{}];", self.const_table.table.len()).unwrap(),
+                GlslFlavor::Vulkan450 => {
+                    let binding = uniform_blocks.len() + if live_slots > 0 {1} else {0};
+                    self.generate_vulkan_uniform_block(binding, "const", self.const_table.table.len());
+                }
+            }
         }
         write!(self.string, "\n").unwrap();
         
-        let live_slots = self.calc_live_slots();
         if live_slots >0 {
-            writeln!(self.string, "uniform float live_table[{}];", live_slots).unwrap();
+            match self.flavor {
+                GlslFlavor::Es100 => writeln!(self.string, "uniform float live_table[{}];", live_slots).unwrap(),
+                GlslFlavor::Vulkan450 => self.generate_vulkan_uniform_block(uniform_blocks.len(), "live", live_slots),
+            }
         }
         
         for (live_ref, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
@@ -379,7 +441,7 @@ impl<'a> DrawShaderGenerator<'a> {
             writeln!(self.string, ";").unwrap();
         }
         
-        for (ident, vec) in self.draw_shader_def.fields_as_uniform_blocks() {
+        for (binding, (ident, vec)) in uniform_blocks.into_iter().enumerate() {
             let mut slots = 0;
             
             for (index, _item) in &vec {
@@ -387,7 +449,10 @@ impl<'a> DrawShaderGenerator<'a> {
                 slots += field.ty_expr.ty.borrow().as_ref().unwrap().slots();
             }
             
-            writeln!(self.string, "uniform float {}_table[{}];", ident, slots).unwrap();
+            match self.flavor {
+                GlslFlavor::Es100 => writeln!(self.string, "uniform float {}_table[{}];", ident, slots).unwrap(),
+                GlslFlavor::Vulkan450 => self.generate_vulkan_uniform_block(binding, &ident.to_string(), slots),
+            }
             
             for (index, _item) in vec {
                 let field = &self.draw_shader_def.fields[index];
@@ -401,34 +466,60 @@ impl<'a> DrawShaderGenerator<'a> {
             write!(self.string, "\n").unwrap();
         }
         
+        let mut texture_index = 0;
         for decl in &self.draw_shader_def.fields {
             match decl.kind {
-                DrawShaderFieldKind::Texture {..} => self.generate_texture_decl(decl),
+                DrawShaderFieldKind::Texture {..} => {
+                    self.generate_texture_decl(decl, texture_index);
+                    texture_index += 1;
+                }
                 _ => {}
             }
         }
         write!(self.string, "\n").unwrap();
         
+        let (attribute_qualifier, varying_qualifier) = match (self.flavor, packed_attributes_size.is_some()) {
+            (GlslFlavor::Es100, _) => ("attribute", "varying"),
+            (GlslFlavor::Vulkan450, true) => ("in", "out"),
+            (GlslFlavor::Vulkan450, false) => ("in", "in"),
+        };
+        let mut attribute_location = 0;
         if let Some(packed_attributes_size) = packed_attributes_size {
             self.generate_packed_var_decls(
-                "attribute",
+                attribute_qualifier,
                 "packed_geometry",
                 packed_attributes_size,
+                &mut attribute_location,
             );
         }
         write!(self.string, "\n").unwrap();
         if let Some(packed_instances_size) = packed_instances_size {
             self.generate_packed_var_decls(
-                "attribute",
+                attribute_qualifier,
                 "packed_instance",
                 packed_instances_size,
+                &mut attribute_location,
             );
         }
         write!(self.string, "\n").unwrap();
-        self.generate_packed_var_decls("varying", "packed_varying", packed_varyings_size);
+        self.generate_packed_var_decls(varying_qualifier, "packed_varying", packed_varyings_size, &mut 0);
+        if self.flavor == GlslFlavor::Vulkan450 && packed_attributes_size.is_none() {
+            writeln!(self.string, "layout(location = 0) out vec4 frag_color;").unwrap();
+        }
         write!(self.string, "\n").unwrap();
     }
     
+    fn generate_vulkan_uniform_block(&mut self, binding: usize, name: &str, slots: usize) {
+        writeln!(
+            self.string,
+            "layout(set = 0, binding = {}, std140) uniform {}_block {{vec4 {}_table[{}];}};",
+            binding,
+            name,
+            name,
+            (slots + 3) >> 2
+        ).unwrap();
+    }
+    
     fn generate_struct_def(&mut self, struct_ptr: StructPtr, struct_def: &StructDef) {
         write!(self.string, "struct {} {{", struct_ptr).unwrap();
         if !struct_def.fields.is_empty() {
@@ -465,13 +556,19 @@ impl<'a> DrawShaderGenerator<'a> {
         slots
     }
     
-    fn generate_texture_decl(&mut self, decl: &DrawShaderFieldDef) {
+    fn generate_texture_decl(&mut self, decl: &DrawShaderFieldDef, index: usize) {
+        if self.flavor == GlslFlavor::Vulkan450 {
+            write!(self.string, "layout(set = 1, binding = {}) ", index * 2).unwrap();
+        }
         write!(self.string, "uniform ").unwrap();
         self.write_var_decl(
             &DisplayDsIdent(decl.ident),
             decl.ty_expr.ty.borrow().as_ref().unwrap(),
         );
         writeln!(self.string, ";").unwrap();
+        if self.flavor == GlslFlavor::Vulkan450 {
+            writeln!(self.string, "layout(set = 1, binding = {}) uniform sampler {}_sampler;", index * 2 + 1, DisplayDsIdent(decl.ident)).unwrap();
+        }
     }
     
     fn compute_packed_geometries_slots(&self) -> usize {
@@ -518,10 +615,15 @@ impl<'a> DrawShaderGenerator<'a> {
         packed_var_qualifier: &'a str,
         packed_var_name: &'a str,
         mut packed_vars_size: usize,
+        location: &mut usize,
     ) {
         let mut packed_var_index = 0;
         loop {
             let packed_var_size = packed_vars_size.min(4);
+            if packed_var_size > 0 && self.flavor == GlslFlavor::Vulkan450 {
+                write!(self.string, "layout(location = {}) ", location).unwrap();
+                *location += 1;
+            }
             writeln!(
                 self.string,
                 "{} {} {}_{};",
@@ -544,24 +646,29 @@ impl<'a> DrawShaderGenerator<'a> {
     }
     
     fn write_uniform_ty_unpack(&mut self, ty: &Ty, prefix: &str, s: usize) {
+        let is_vec4 = self.flavor == GlslFlavor::Vulkan450;
+        let slot = | i: usize | DisplayUniformSlot {table: prefix, slot: s + i, is_vec4};
         match ty {
-            Ty::Bool => write!(self.string, "{}[{}]>0.5?true:false", prefix, s),
-            Ty::Int => write!(self.string, "int({}[{}])", prefix, s),
-            Ty::Float => write!(self.string, "{}[{}]", prefix, s),
-            Ty::Bvec2 => write!(self.string, "bvec2({0}[{1}]>0.5?true:false, {0}[{2}]>0.5?true:false)", prefix, s, s + 1),
-            Ty::Bvec3 => write!(self.string, "bvec3({0}[{1}]>0.5?true:false, {0}[{2}]>0.5?true:false, {0}[{3}]>0.5?true:false)", prefix, s, s + 1, s + 2),
-            Ty::Bvec4 => write!(self.string, "bvec4({0}[{1}]>0.5?true:false, {0}[{2}]>0.5?true:false, {0}[{3}]>0.5?true:false, {0}[{4}]>0.5?true:false)", prefix, s, s + 1, s + 2, s + 3),
-            Ty::Ivec2 => write!(self.string, "ivec2(int({0}[{1}]), int({0}[{2}]))", prefix, s, s + 1),
-            Ty::Ivec3 => write!(self.string, "ivec3(int({0}[{1}]), int({0}[{2}]), int({0}[{3}]))", prefix, s, s + 1, s + 2),
-            Ty::Ivec4 => write!(self.string, "ivec4(int({0}[{1}]), int({0}[{2}]), int({0}[{3}]), int({0}[{4}]))", prefix, s, s + 1, s + 2, s + 3),
-            Ty::Vec2 => write!(self.string, "vec2({0}[{1}], {0}[{2}])", prefix, s, s + 1),
-            Ty::Vec3 => write!(self.string, "vec3({0}[{1}], {0}[{2}], {0}[{3}])", prefix, s, s + 1, s + 2),
-            Ty::Vec4 => write!(self.string, "vec4({0}[{1}], {0}[{2}], {0}[{3}], {0}[{4}])", prefix, s, s + 1, s + 2, s + 3),
-            Ty::Mat2 => write!(self.string, "mat2({0}[{1}], {0}[{2}], {0}[{3}], {0}[{4}])", prefix, s, s + 1, s + 2, s + 3),
-            Ty::Mat3 => write!(self.string, "mat3({0}[{1}], {0}[{2}], {0}[{3}], {0}[{4}], {0}[{5}], {0}[{6}], {0}[{7}], {0}[{8}], {0}[{9}])", prefix, s, s + 1, s + 2, s + 3, s + 4, s + 5, s + 6, s + 7, s + 8),
-            Ty::Mat4 => write!(self.string, "mat4({0}[{1}], {0}[{2}], {0}[{3}], {0}[{4}], {0}[{5}], {0}[{6}], {0}[{7}], {0}[{8}], {0}[{9}], {0}[{10}], {0}[{11}], {0}[{12}], {0}[{13}], {0}[{14}], {0}[{15}], {0}[{16}])", prefix, s, s + 1, s + 2, s + 3, s + 4, s + 5, s + 6, s + 7, s + 8, s + 9, s + 10, s + 11, s + 12, s + 13, s + 14, s + 15,),
-            //Ty::Mat4 => write!(self.string, "mat4({0}[{1}], {0}[{2}], {0}[{3}], {0}[{4}], {0}[{5}], {0}[{6}], {0}[{7}], {0}[{8}], {0}[{9}], {0}[{10}], {0}[{11}], {0}[{12}], {0}[{13}], {0}[{14}], {0}[{15}], {0}[{16}])", prefix, s, s + 4, s + 8, s + 12, s + 1, s + 5, s + 9, s + 13, s + 2, s + 6, s + 10, s + 14, s + 3, s + 7, s + 11, s + 15,),
-            Ty::Enum {..} => write!(self.string, "{}[{}]", prefix, s),
+            Ty::Bool => write!(self.string, "{}>0.5?true:false", slot(0)),
+            Ty::Int => write!(self.string, "int({})", slot(0)),
+            Ty::Float => write!(self.string, "{}", slot(0)),
+            Ty::Bvec2 => write!(self.string, "bvec2({}>0.5?true:false, {}>0.5?true:false)", slot(0), slot(1)),
+            Ty::Bvec3 => write!(self.string, "bvec3({}>0.5?true:false, {}>0.5?true:false, {}>0.5?true:false)", slot(0), slot(1), slot(2)),
+            Ty::Bvec4 => write!(self.string, "bvec4({}>0.5?true:false, {}>0.5?true:false, {}>0.5?true:false, {}>0.5?true:false)", slot(0), slot(1), slot(2), slot(3)),
+            Ty::Ivec2 => write!(self.string, "ivec2(int({}), int({}))", slot(0), slot(1)),
+            Ty::Ivec3 => write!(self.string, "ivec3(int({}), int({}), int({}))", slot(0), slot(1), slot(2)),
+            Ty::Ivec4 => write!(self.string, "ivec4(int({}), int({}), int({}), int({}))", slot(0), slot(1), slot(2), slot(3)),
+            Ty::Vec2 => write!(self.string, "vec2({}, {})", slot(0), slot(1)),
+            Ty::Vec3 => write!(self.string, "vec3({}, {}, {})", slot(0), slot(1), slot(2)),
+            Ty::Vec4 => write!(self.string, "vec4({}, {}, {}, {})", slot(0), slot(1), slot(2), slot(3)),
+            Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => {
+                write!(self.string, "{}(", ty).unwrap();
+                for i in 0..ty.slots() {
+                    write!(self.string, "{}{}", if i == 0 {""} else {", "}, slot(i)).unwrap();
+                }
+                write!(self.string, ")")
+            }
+            Ty::Enum {..} => write!(self.string, "{}", slot(0)),
             _ => panic!("unexpected as initializeable type {:?}", ty),
         }.unwrap()
     }
@@ -767,9 +874,29 @@ impl<'a> VarUnpacker<'a> {
     }
 }
 
+// a float in a uniform table, which is a vec4 array when the tables are std140 blocks
+struct DisplayUniformSlot<'a> {
+    table: &'a str,
+    slot: usize,
+    is_vec4: bool,
+}
+
+impl<'a> fmt::Display for DisplayUniformSlot<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_vec4 {
+            write!(f, "{}[{}].{}", self.table, self.slot >> 2, ["x", "y", "z", "w"][self.slot & 3])
+        }
+        else {
+            write!(f, "{}[{}]", self.table, self.slot)
+        }
+    }
+}
+
 struct GlslBackendWriter<'a> {
     pub shader_registry: &'a ShaderRegistry,
-    const_table: &'a DrawShaderConstTable
+    const_table: &'a DrawShaderConstTable,
+    flavor: GlslFlavor,
+    source_map: RefCell<ShaderSourceMap>,
 }
 
 impl<'a> BackendWriter for GlslBackendWriter<'a> {
@@ -790,7 +917,11 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
     }
     
     fn const_table_is_vec4(&self) -> bool {
-        false
+        self.flavor == GlslFlavor::Vulkan450
+    }
+
+    fn record_source_span(&self, offset: usize, span: Option<TokenSpan>) {
+        self.source_map.borrow_mut().push(offset, span);
     }
     
     fn use_cons_fn(&self, _what: &str) -> bool {
@@ -936,8 +1067,16 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
     fn generate_live_value_prefix(&self, _string: &mut String) {
     }
     
-    fn generate_draw_shader_field_expr(&self, string: &mut String, field_ident: Ident, _ty: &Ty) {
-        write!(string, "{}", &DisplayDsIdent(field_ident)).unwrap();
+    fn generate_draw_shader_field_expr(&self, string: &mut String, field_ident: Ident, ty: &Ty) {
+        match (self.flavor, ty) {
+            // textures only show up as sample2d args, so the sampler rides along
+            (GlslFlavor::Vulkan450, Ty::Texture2D | Ty::TextureOES) => {
+                write!(string, "{0}, {0}_sampler", &DisplayDsIdent(field_ident)).unwrap();
+            }
+            _ => {
+                write!(string, "{}", &DisplayDsIdent(field_ident)).unwrap();
+            }
+        }
     }
     
     fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit) {
@@ -960,8 +1099,15 @@ impl<'a> BackendWriter for GlslBackendWriter<'a> {
                 TyLit::Mat2 => "mat2",
                 TyLit::Mat3 => "mat3",
                 TyLit::Mat4 => "mat4",
-                TyLit::Texture2D => "sampler2D",
-                TyLit::TextureOES => "samplerExternalOES",
+                TyLit::Texture2D => match self.flavor {
+                    GlslFlavor::Es100 => "sampler2D",
+                    GlslFlavor::Vulkan450 => "texture2D",
+                },
+                TyLit::TextureOES => match self.flavor {
+                    GlslFlavor::Es100 => "samplerExternalOES",
+                    // external textures are imported as regular images on Vulkan
+                    GlslFlavor::Vulkan450 => "texture2D",
+                },
             }
         )
             .unwrap();
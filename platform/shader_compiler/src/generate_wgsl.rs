@@ -0,0 +1,785 @@
+use {
+    std::{
+        fmt::Write,
+        fmt,
+        cell::RefCell,
+        collections::{BTreeMap, BTreeSet},
+    },
+    crate::{
+        makepad_live_id::*,
+        shader_ast::*,
+        generate::*,
+        swizzle::Swizzle,
+        shader_registry::ShaderRegistry,
+    }
+};
+
+// The generated module holds both entrypoints, vertex_main and fragment_main.
+// Bindings are laid out as follows:
+// @group(0) the uniform blocks in fields_as_uniform_blocks order, then live_table and const_table.
+//           all of them are array<vec4<f32>, N> so the CPU side can upload the same
+//           tightly packed float tables it uses for the other backends
+// @group(1) a texture_2d and sampler pair per texture field, at binding 2*i and 2*i+1
+// Geometry and instance fields are packed into vec4 attributes like the GLSL backend,
+// geometries first, then instances.
+
+pub struct WgslGeneratedShader {
+    pub wgsl: String,
+    pub fields_as_uniform_blocks: BTreeMap<Ident, Vec<(usize, Ident) >>
+}
+
+pub fn generate_shader(draw_shader_def: &DrawShaderDef, const_table: &DrawShaderConstTable, shader_registry: &ShaderRegistry) -> WgslGeneratedShader {
+    let mut string = String::new();
+    let fields_as_uniform_blocks = draw_shader_def.fields_as_uniform_blocks();
+    DrawShaderGenerator {
+        draw_shader_def,
+        shader_registry,
+        const_table,
+        string: &mut string,
+        fields_as_uniform_blocks: &fields_as_uniform_blocks,
+        backend_writer: &WgslBackendWriter {
+            shader_registry,
+            builtin_helpers: RefCell::new(BTreeSet::new())
+        }
+    }
+    .generate_shader();
+    WgslGeneratedShader {
+        wgsl: string,
+        fields_as_uniform_blocks
+    }
+}
+
+struct DrawShaderGenerator<'a> {
+    draw_shader_def: &'a DrawShaderDef,
+    shader_registry: &'a ShaderRegistry,
+    string: &'a mut String,
+    fields_as_uniform_blocks: &'a BTreeMap<Ident, Vec<(usize, Ident) >>,
+    backend_writer: &'a WgslBackendWriter<'a>,
+    const_table: &'a DrawShaderConstTable
+}
+
+impl<'a> DrawShaderGenerator<'a> {
+    fn generate_shader(&mut self) {
+        self.generate_uniform_decls();
+        self.generate_texture_decls();
+        self.generate_private_decls();
+        self.generate_struct_defs();
+        self.generate_vertex_input_struct();
+        self.generate_varyings_struct();
+
+        let mut all_constructor_fns = BTreeSet::new();
+        for fn_iter in self.draw_shader_def.all_fns.borrow().iter() {
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            all_constructor_fns.extend(fn_def.constructor_fn_deps.borrow().as_ref().unwrap().iter().cloned());
+        }
+        for (ty_lit, ref param_tys) in all_constructor_fns {
+            generate_cons_fn(self.backend_writer, self.string, ty_lit, param_tys);
+        }
+
+        let all_fns = self.draw_shader_def.all_fns.borrow();
+        for fn_iter in all_fns.iter().rev() {
+            let const_table_offset = self.const_table.offsets.get(fn_iter).cloned();
+            let fn_def = self.shader_registry.all_fns.get(fn_iter).unwrap();
+            if fn_def.has_closure_args() {
+                for call_iter in all_fns.iter().rev() {
+                    // any function that depends on us, will have the closures we need
+                    let call_def = self.shader_registry.all_fns.get(call_iter).unwrap();
+                    if call_def.callees.borrow().as_ref().unwrap().contains(fn_iter) {
+                        FnDefWithClosureArgsGenerator::generate_fn_def_with_all_closures(
+                            self.string,
+                            self.shader_registry,
+                            fn_def,
+                            call_def,
+                            self.backend_writer,
+                            const_table_offset
+                        );
+                    }
+                }
+                continue
+            }
+            FnDefGenerator {
+                fn_def,
+                const_table_offset,
+                shader_registry: self.shader_registry,
+                backend_writer: self.backend_writer,
+                string: self.string,
+            }
+            .generate_fn_def()
+        }
+        // declaration order doesn't matter in WGSL, so the helpers the fns used go last
+        self.generate_builtin_helpers();
+        self.generate_vertex_main();
+        self.generate_fragment_main();
+    }
+
+    fn generate_uniform_decls(&mut self) {
+        let mut binding = 0;
+        for (ident, vec) in self.fields_as_uniform_blocks {
+            let mut slots = 0;
+            for (index, _item) in vec {
+                slots += self.draw_shader_def.fields[*index].ty_expr.ty.borrow().as_ref().unwrap().slots();
+            }
+            writeln!(self.string, "@group(0) @binding({}) var<uniform> {}_table: array<vec4<f32>, {}>;", binding, ident, vec4_count(slots)).unwrap();
+            binding += 1;
+        }
+        let live_slots = self.calc_live_slots();
+        if live_slots > 0 {
+            writeln!(self.string, "@group(0) @binding({}) var<uniform> live_table: array<vec4<f32>, {}>;", binding, vec4_count(live_slots)).unwrap();
+            binding += 1;
+        }
+        if !self.const_table.table.is_empty() {
+            writeln!(self.string, "@group(0) @binding({}) var<uniform> const_table: array<vec4<f32>, {}>;", binding, vec4_count(self.const_table.table.len())).unwrap();
+        }
+    }
+
+    fn generate_texture_decls(&mut self) {
+        let mut index = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Texture {..} = field.kind {
+                writeln!(self.string, "@group(1) @binding({}) var {}: texture_2d<f32>;", index * 2, DisplayDsIdent(field.ident)).unwrap();
+                writeln!(self.string, "@group(1) @binding({}) var {}: sampler;", index * 2 + 1, DisplaySamplerIdent(field.ident)).unwrap();
+                index += 1;
+            }
+        }
+    }
+
+    fn generate_private_decls(&mut self) {
+        for field in &self.draw_shader_def.fields {
+            match field.kind {
+                DrawShaderFieldKind::Geometry {..} |
+                DrawShaderFieldKind::Instance {..} |
+                DrawShaderFieldKind::Varying {..} |
+                DrawShaderFieldKind::Uniform {..} if !field.ident.0.is_empty() => {
+                    write!(self.string, "var<private> ").unwrap();
+                    self.write_var_decl(&DisplayDsIdent(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+                    writeln!(self.string, ";").unwrap();
+                }
+                _ => ()
+            }
+        }
+        for (live_ref, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            write!(self.string, "var<private> ").unwrap();
+            self.write_var_decl(live_ref, ty);
+            writeln!(self.string, ";").unwrap();
+        }
+    }
+
+    fn generate_struct_defs(&mut self) {
+        // we have all the structs already from analyse
+        for struct_ptr in self.draw_shader_def.all_structs.borrow().iter().rev() {
+            let struct_def = self.shader_registry.structs.get(struct_ptr).unwrap();
+            writeln!(self.string, "struct {} {{", struct_ptr).unwrap();
+            for field in &struct_def.fields {
+                write!(self.string, "    ").unwrap();
+                self.write_var_decl(&DisplayStructField(field.ident), field.ty_expr.ty.borrow().as_ref().unwrap());
+                writeln!(self.string, ",").unwrap();
+            }
+            writeln!(self.string, "}}").unwrap();
+        }
+    }
+
+    fn generate_vertex_input_struct(&mut self) {
+        let geometry_slots = self.compute_packed_geometries_slots();
+        let instance_slots = self.compute_packed_instances_slots();
+        if geometry_slots + instance_slots == 0 {
+            return
+        }
+        writeln!(self.string, "struct VertexInput {{").unwrap();
+        let location = self.generate_packed_var_decls("packed_geometry", geometry_slots, 0);
+        self.generate_packed_var_decls("packed_instance", instance_slots, location);
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_varyings_struct(&mut self) {
+        writeln!(self.string, "struct Varyings {{").unwrap();
+        writeln!(self.string, "    @builtin(position) position: vec4<f32>,").unwrap();
+        let varying_slots = self.compute_packed_varyings_slots();
+        self.generate_packed_var_decls("packed_varying", varying_slots, 0);
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_packed_var_decls(&mut self, packed_var_name: &str, mut packed_vars_size: usize, mut location: usize) -> usize {
+        let mut packed_var_index = 0;
+        while packed_vars_size > 0 {
+            let packed_var_size = packed_vars_size.min(4);
+            writeln!(
+                self.string,
+                "    @location({}) {}_{}: {},",
+                location,
+                packed_var_name,
+                packed_var_index,
+                packed_ty_name(packed_var_size)
+            ).unwrap();
+            packed_vars_size -= packed_var_size;
+            packed_var_index += 1;
+            location += 1;
+        }
+        location
+    }
+
+    fn generate_builtin_helpers(&mut self) {
+        for (ident, arg_tys) in self.backend_writer.builtin_helpers.borrow().iter() {
+            match ident.0 {
+                live_id!(sample2d) | live_id!(sample2d_rt) | live_id!(sample2dOES) => {
+                    // sampling with an explicit lod keeps it legal in the vertex shader and in non-uniform control flow
+                    writeln!(
+                        self.string,
+                        "fn {}(tex: texture_2d<f32>, samp: sampler, pos: vec2<f32>) -> vec4<f32> {{\n    return textureSampleLevel(tex, samp, pos, 0.0);\n}}",
+                        ident
+                    ).unwrap();
+                    continue
+                }
+                _ => ()
+            }
+            let return_ty = self.shader_registry.builtins.get(ident).unwrap().return_tys.get(arg_tys).unwrap();
+            write!(self.string, "fn {}(", DisplayBuiltinHelper(*ident, arg_tys)).unwrap();
+            let mut sep = "";
+            for (index, arg_ty) in arg_tys.iter().enumerate() {
+                self.backend_writer.write_var_decl(self.string, sep, false, false, &DisplaConstructorArg(index), arg_ty);
+                sep = ", ";
+            }
+            self.backend_writer.write_fn_def_tail(self.string, return_ty);
+            write!(self.string, "{{\n    return ").unwrap();
+            match ident.0 {
+                live_id!(mod) => write!(self.string, "x0 - x1 * floor(x0 / x1)").unwrap(),
+                live_id!(lessThan) => write!(self.string, "x0 < x1").unwrap(),
+                live_id!(lessThanEqual) => write!(self.string, "x0 <= x1").unwrap(),
+                live_id!(greaterThan) => write!(self.string, "x0 > x1").unwrap(),
+                live_id!(greaterThanEqual) => write!(self.string, "x0 >= x1").unwrap(),
+                live_id!(equal) => write!(self.string, "x0 == x1").unwrap(),
+                live_id!(notEqual) => write!(self.string, "x0 != x1").unwrap(),
+                live_id!(not) => write!(self.string, "!x0").unwrap(),
+                _ => { // splat the scalar args of the mixed scalar/vector overloads
+                    write!(self.string, "{}(", ident).unwrap();
+                    let mut sep = "";
+                    for (index, arg_ty) in arg_tys.iter().enumerate() {
+                        write!(self.string, "{}", sep).unwrap();
+                        if *arg_ty != *return_ty {
+                            self.backend_writer.write_ty(self.string, return_ty);
+                            write!(self.string, "(x{})", index).unwrap();
+                        }
+                        else {
+                            write!(self.string, "x{}", index).unwrap();
+                        }
+                        sep = ", ";
+                    }
+                    write!(self.string, ")").unwrap();
+                }
+            }
+            writeln!(self.string, ";\n}}").unwrap();
+        }
+    }
+
+    fn generate_vertex_main(&mut self) {
+        let geometry_slots = self.compute_packed_geometries_slots();
+        let instance_slots = self.compute_packed_instances_slots();
+        let varying_slots = self.compute_packed_varyings_slots();
+
+        writeln!(self.string, "@vertex").unwrap();
+        if geometry_slots + instance_slots > 0 {
+            writeln!(self.string, "fn vertex_main(vertex_input: VertexInput) -> Varyings {{").unwrap();
+        }
+        else {
+            writeln!(self.string, "fn vertex_main() -> Varyings {{").unwrap();
+        }
+        self.generate_uniform_block_unpack();
+        self.generate_live_unpack();
+
+        let mut geometries = VarPacker::new("vertex_input.packed_geometry", geometry_slots);
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Geometry {..} = field.kind {
+                geometries.unpack_var(self.string, field.ident, field.ty_expr.ty.borrow().as_ref().unwrap());
+            }
+        }
+        let mut instances = VarPacker::new("vertex_input.packed_instance", instance_slots);
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Instance {..} = field.kind {
+                instances.unpack_var(self.string, field.ident, field.ty_expr.ty.borrow().as_ref().unwrap());
+            }
+        }
+
+        let vertex_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(vertex))).unwrap();
+        writeln!(self.string, "    var varyings: Varyings;").unwrap();
+        writeln!(self.string, "    varyings.position = {}();", DisplayFnName(vertex_def.fn_ptr, vertex_def.ident)).unwrap();
+
+        let mut varyings = VarPacker::new("varyings.packed_varying", varying_slots);
+        for field in &self.draw_shader_def.fields {
+            match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} |
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    varyings.pack_var(self.string, field.ident, field.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                DrawShaderFieldKind::Varying {..} => {
+                    varyings.pack_var(self.string, field.ident, field.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                _ => ()
+            }
+        }
+        writeln!(self.string, "    return varyings;").unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_fragment_main(&mut self) {
+        let varying_slots = self.compute_packed_varyings_slots();
+
+        writeln!(self.string, "@fragment").unwrap();
+        writeln!(self.string, "fn fragment_main(varyings: Varyings) -> @location(0) vec4<f32> {{").unwrap();
+        self.generate_uniform_block_unpack();
+        self.generate_live_unpack();
+
+        let mut varying_unpacker = VarPacker::new("varyings.packed_varying", varying_slots);
+        for field in &self.draw_shader_def.fields {
+            match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} |
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    varying_unpacker.unpack_var(self.string, field.ident, field.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                DrawShaderFieldKind::Varying {..} => {
+                    varying_unpacker.unpack_var(self.string, field.ident, field.ty_expr.ty.borrow().as_ref().unwrap());
+                }
+                _ => ()
+            }
+        }
+        let pixel_def = self.shader_registry.draw_shader_method_decl_from_ident(self.draw_shader_def, Ident(live_id!(pixel))).unwrap();
+        writeln!(self.string, "    return {}();", DisplayFnName(pixel_def.fn_ptr, pixel_def.ident)).unwrap();
+        writeln!(self.string, "}}").unwrap();
+    }
+
+    fn generate_uniform_block_unpack(&mut self) {
+        for (ident, vec) in self.fields_as_uniform_blocks {
+            let table = format!("{}_table", ident);
+            let mut slots = 0;
+            for (index, _item) in vec {
+                let field = &self.draw_shader_def.fields[*index];
+                let ty = field.ty_expr.ty.borrow();
+                write!(self.string, "    {} = ", DisplayDsIdent(field.ident)).unwrap();
+                self.write_uniform_ty_unpack(ty.as_ref().unwrap(), &table, slots);
+                writeln!(self.string, ";").unwrap();
+                slots += ty.as_ref().unwrap().slots();
+            }
+        }
+    }
+
+    fn generate_live_unpack(&mut self) {
+        let mut slots = 0;
+        for (live_ref, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            write!(self.string, "    {} = ", live_ref).unwrap();
+            self.write_uniform_ty_unpack(ty, "live_table", slots);
+            writeln!(self.string, ";").unwrap();
+            slots += ty.slots();
+        }
+    }
+
+    fn write_uniform_ty_unpack(&mut self, ty: &Ty, table: &str, s: usize) {
+        let slot = | i: usize | DisplayTableSlot(table, s + i);
+        match ty {
+            Ty::Bool => write!(self.string, "{} > 0.5", slot(0)),
+            Ty::Int => write!(self.string, "i32({})", slot(0)),
+            Ty::Float | Ty::Enum(_) => write!(self.string, "{}", slot(0)),
+            Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 | Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 |
+            Ty::Vec2 | Ty::Vec3 | Ty::Vec4 | Ty::Mat2 | Ty::Mat3 | Ty::Mat4 => {
+                self.backend_writer.write_ty(self.string, ty);
+                write!(self.string, "(").unwrap();
+                for i in 0..ty.slots() {
+                    if i != 0 {
+                        write!(self.string, ", ").unwrap();
+                    }
+                    match ty {
+                        Ty::Bvec2 | Ty::Bvec3 | Ty::Bvec4 => write!(self.string, "{} > 0.5", slot(i)),
+                        Ty::Ivec2 | Ty::Ivec3 | Ty::Ivec4 => write!(self.string, "i32({})", slot(i)),
+                        _ => write!(self.string, "{}", slot(i)),
+                    }.unwrap();
+                }
+                write!(self.string, ")")
+            }
+            _ => panic!("unexpected as uniform type {:?}", ty),
+        }.unwrap()
+    }
+
+    fn compute_packed_geometries_slots(&self) -> usize {
+        let mut slots = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Geometry {..} = field.kind {
+                slots += field.ty_expr.ty.borrow().as_ref().unwrap().slots();
+            }
+        }
+        slots
+    }
+
+    fn compute_packed_instances_slots(&self) -> usize {
+        let mut slots = 0;
+        for field in &self.draw_shader_def.fields {
+            if let DrawShaderFieldKind::Instance {..} = field.kind {
+                slots += field.ty_expr.ty.borrow().as_ref().unwrap().slots();
+            }
+        }
+        slots
+    }
+
+    fn compute_packed_varyings_slots(&self) -> usize {
+        let mut slots = 0;
+        for field in &self.draw_shader_def.fields {
+            slots += match &field.kind {
+                DrawShaderFieldKind::Geometry {is_used_in_pixel_shader, ..} |
+                DrawShaderFieldKind::Instance {is_used_in_pixel_shader, ..} if is_used_in_pixel_shader.get() => {
+                    field.ty_expr.ty.borrow().as_ref().unwrap().slots()
+                }
+                DrawShaderFieldKind::Varying {..} => field.ty_expr.ty.borrow().as_ref().unwrap().slots(),
+                _ => 0,
+            }
+        }
+        slots
+    }
+
+    fn calc_live_slots(&self) -> usize {
+        let mut slots = 0;
+        for (_, ty) in self.draw_shader_def.all_live_refs.borrow().iter() {
+            slots += ty.slots();
+        }
+        slots
+    }
+
+    fn write_var_decl(&mut self, ident: &dyn fmt::Display, ty: &Ty) {
+        self.backend_writer.write_var_decl(self.string, "", false, false, ident, ty);
+    }
+}
+
+fn vec4_count(slots: usize) -> usize {
+    (slots + 3) >> 2
+}
+
+fn packed_ty_name(size: usize) -> &'static str {
+    match size {
+        1 => "f32",
+        2 => "vec2<f32>",
+        3 => "vec3<f32>",
+        4 => "vec4<f32>",
+        _ => panic!(),
+    }
+}
+
+// WGSL can't assign to a multi component swizzle, so whenever a var doesn't
+// line up with a whole packed var it gets moved one component at a time
+struct VarPacker<'a> {
+    packed_var_name: &'a str,
+    packed_vars_size: usize,
+    packed_var_index: usize,
+    packed_var_size: usize,
+    packed_var_offset: usize,
+}
+
+impl<'a> VarPacker<'a> {
+    fn new(packed_var_name: &'a str, packed_vars_size: usize) -> Self {
+        Self {
+            packed_var_name,
+            packed_vars_size,
+            packed_var_index: 0,
+            packed_var_size: packed_vars_size.min(4),
+            packed_var_offset: 0,
+        }
+    }
+
+    fn pack_var(&mut self, string: &mut String, ident: Ident, ty: &Ty) {
+        self.move_var(string, ident, ty, false)
+    }
+
+    fn unpack_var(&mut self, string: &mut String, ident: Ident, ty: &Ty) {
+        self.move_var(string, ident, ty, true)
+    }
+
+    fn move_var(&mut self, string: &mut String, ident: Ident, ty: &Ty, unpack: bool) {
+        let var_slots = ty.slots();
+        let is_padding = ident.0.is_empty();
+        let is_matrix = matches!(ty, Ty::Mat2 | Ty::Mat3 | Ty::Mat4);
+        let fits = var_slots <= self.packed_var_size - self.packed_var_offset;
+        if !is_padding && !is_matrix && fits && (unpack || var_slots == self.packed_var_size) {
+            write!(string, "    ").unwrap();
+            if !unpack {
+                write!(string, "{}_{} = ", self.packed_var_name, self.packed_var_index).unwrap();
+            }
+            write!(string, "{}", DisplayDsIdent(ident)).unwrap();
+            if unpack {
+                write!(string, " = {}_{}", self.packed_var_name, self.packed_var_index).unwrap();
+                if self.packed_var_size != var_slots {
+                    write!(string, ".{}", Swizzle::from_range(self.packed_var_offset, self.packed_var_offset + var_slots)).unwrap();
+                }
+            }
+            writeln!(string, ";").unwrap();
+            self.advance(var_slots);
+            return
+        }
+        for var_offset in 0..var_slots {
+            if !is_padding {
+                let var_component = DisplayVarComponent(ident, ty, var_offset);
+                let packed_component = DisplayPackedComponent(self.packed_var_name, self.packed_var_index, self.packed_var_size, self.packed_var_offset);
+                if unpack {
+                    writeln!(string, "    {} = {};", var_component, packed_component).unwrap();
+                }
+                else {
+                    writeln!(string, "    {} = {};", packed_component, var_component).unwrap();
+                }
+            }
+            self.advance(1);
+        }
+    }
+
+    fn advance(&mut self, slots: usize) {
+        self.packed_var_offset += slots;
+        if self.packed_var_offset == self.packed_var_size {
+            self.packed_vars_size -= self.packed_var_size;
+            self.packed_var_index += 1;
+            self.packed_var_size = self.packed_vars_size.min(4);
+            self.packed_var_offset = 0;
+        }
+    }
+}
+
+const COMPONENTS: [&str; 4] = ["x", "y", "z", "w"];
+
+struct DisplayVarComponent<'a>(Ident, &'a Ty, usize);
+impl<'a> fmt::Display for DisplayVarComponent<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let dim = match self.1 {
+            Ty::Mat2 => Some(2),
+            Ty::Mat3 => Some(3),
+            Ty::Mat4 => Some(4),
+            _ => None
+        };
+        if let Some(dim) = dim {
+            write!(f, "{}[{}][{}]", DisplayDsIdent(self.0), self.2 / dim, self.2 % dim)
+        }
+        else if self.1.slots() > 1 {
+            write!(f, "{}.{}", DisplayDsIdent(self.0), COMPONENTS[self.2])
+        }
+        else {
+            write!(f, "{}", DisplayDsIdent(self.0))
+        }
+    }
+}
+
+struct DisplayPackedComponent<'a>(&'a str, usize, usize, usize);
+impl<'a> fmt::Display for DisplayPackedComponent<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.2 > 1 {
+            write!(f, "{}_{}.{}", self.0, self.1, COMPONENTS[self.3])
+        }
+        else {
+            write!(f, "{}_{}", self.0, self.1)
+        }
+    }
+}
+
+struct DisplayTableSlot<'a>(&'a str, usize);
+impl<'a> fmt::Display for DisplayTableSlot<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}[{}].{}", self.0, self.1 >> 2, COMPONENTS[self.1 & 3])
+    }
+}
+
+struct DisplaySamplerIdent(Ident);
+impl fmt::Display for DisplaySamplerIdent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ds_{}_sampler", self.0)
+    }
+}
+
+struct DisplayBuiltinHelper<'a>(Ident, &'a [Ty]);
+impl<'a> fmt::Display for DisplayBuiltinHelper<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        for ty in self.1 {
+            write!(f, "_{}", ty)?;
+        }
+        Ok(())
+    }
+}
+
+struct WgslBackendWriter<'a> {
+    shader_registry: &'a ShaderRegistry,
+    // builtins that WGSL lacks or that need their args splatted, emitted as helper fns
+    builtin_helpers: RefCell<BTreeSet<(Ident, Vec<Ty>)>>,
+}
+
+impl<'a> WgslBackendWriter<'a> {
+    fn write_ty(&self, string: &mut String, ty: &Ty) {
+        match ty {
+            Ty::Array {elem_ty, len} => {
+                write!(string, "array<").unwrap();
+                self.write_ty(string, elem_ty);
+                write!(string, ", {}>", len).unwrap();
+            }
+            Ty::Struct(ptr) => write!(string, "{}", ptr).unwrap(),
+            Ty::Enum(_) => write!(string, "f32").unwrap(),
+            _ => self.write_ty_lit(string, ty.maybe_ty_lit().unwrap())
+        }
+    }
+}
+
+impl<'a> BackendWriter for WgslBackendWriter<'a> {
+    fn get_struct_cons_type(&self) -> StructConsType {
+        StructConsType::Paren
+    }
+
+    fn enum_is_float(&self) -> bool {
+        true
+    }
+
+    fn needs_mul_fn_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn needs_unpack_for_matrix_multiplication(&self) -> bool {
+        false
+    }
+
+    fn const_table_is_vec4(&self) -> bool {
+        true
+    }
+
+    fn use_cons_fn(&self, what: &str) -> bool {
+        // WGSL has no diagonal matrix from a scalar and no matrix from matrix constructors
+        let mut parts = what.split('_').skip(1);
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(ty), Some(arg), None) if ty.starts_with("mat") => arg == "float" || arg.starts_with("mat"),
+            _ => false
+        }
+    }
+
+    fn inout_params_are_pointers(&self) -> bool {
+        true
+    }
+
+    fn params_are_immutable(&self) -> bool {
+        true
+    }
+
+    fn use_select_for_cond_expr(&self) -> bool {
+        true
+    }
+
+    fn assignments_are_stmts(&self) -> bool {
+        true
+    }
+
+    fn write_var_decl(
+        &self,
+        string: &mut String,
+        sep: &'static str,
+        is_inout: bool,
+        _is_packed: bool,
+        ident: &dyn fmt::Display,
+        ty: &Ty,
+    ) -> bool {
+        match ty {
+            Ty::DrawShader(_) | Ty::ClosureDef {..} | Ty::ClosureDecl => {
+                return false
+            }
+            Ty::Void => {
+                write!(string, "{}{}", sep, ident).unwrap();
+            }
+            _ => {
+                write!(string, "{}{}: ", sep, ident).unwrap();
+                if is_inout {
+                    write!(string, "ptr<function, ").unwrap();
+                    self.write_ty(string, ty);
+                    write!(string, ">").unwrap();
+                }
+                else {
+                    self.write_ty(string, ty);
+                }
+            }
+        }
+        true
+    }
+
+    fn write_fn_def_head(&self, string: &mut String, ident: &dyn fmt::Display, _return_ty: &Ty) {
+        write!(string, "fn {}(", ident).unwrap();
+    }
+
+    fn write_fn_def_tail(&self, string: &mut String, return_ty: &Ty) {
+        if let Ty::Void = return_ty {
+            write!(string, ") ").unwrap();
+        }
+        else {
+            write!(string, ") -> ").unwrap();
+            self.write_ty(string, return_ty);
+            write!(string, " ").unwrap();
+        }
+    }
+
+    fn write_let_decl(&self, string: &mut String, ident: &dyn fmt::Display, ty: &Ty) {
+        write!(string, "var ").unwrap();
+        self.write_var_decl(string, "", false, false, ident, ty);
+    }
+
+    fn write_call_expr_hidden_args(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+    }
+
+    fn write_fn_def_hidden_params(&self, _string: &mut String, _hidden_args: &BTreeSet<HiddenArgKind >, _sep: &str) {
+    }
+
+    fn generate_live_value_prefix(&self, _string: &mut String) {
+    }
+
+    fn generate_draw_shader_field_expr(&self, string: &mut String, field_ident: Ident, ty: &Ty) {
+        match ty {
+            // textures only show up as sample2d args, so the sampler rides along
+            Ty::Texture2D | Ty::TextureOES => {
+                write!(string, "{}, {}", DisplayDsIdent(field_ident), DisplaySamplerIdent(field_ident)).unwrap();
+            }
+            _ => {
+                write!(string, "{}", DisplayDsIdent(field_ident)).unwrap();
+            }
+        }
+    }
+
+    fn write_ty_lit(&self, string: &mut String, ty_lit: TyLit) {
+        write!(
+            string,
+            "{}",
+            match ty_lit {
+                TyLit::Bool => "bool",
+                TyLit::Int => "i32",
+                TyLit::Float => "f32",
+                TyLit::Bvec2 => "vec2<bool>",
+                TyLit::Bvec3 => "vec3<bool>",
+                TyLit::Bvec4 => "vec4<bool>",
+                TyLit::Ivec2 => "vec2<i32>",
+                TyLit::Ivec3 => "vec3<i32>",
+                TyLit::Ivec4 => "vec4<i32>",
+                TyLit::Vec2 => "vec2<f32>",
+                TyLit::Vec3 => "vec3<f32>",
+                TyLit::Vec4 => "vec4<f32>",
+                TyLit::Mat2 => "mat2x2<f32>",
+                TyLit::Mat3 => "mat3x3<f32>",
+                TyLit::Mat4 => "mat4x4<f32>",
+                TyLit::Texture2D | TyLit::TextureOES => "texture_2d<f32>",
+            }
+        ).unwrap();
+    }
+
+    fn write_builtin_call_ident(&self, string: &mut String, ident: Ident, arg_exprs: &[Expr]) {
+        let arg_tys: Vec<Ty> = arg_exprs.iter().map( | arg_expr | arg_expr.ty.borrow().clone().unwrap()).collect();
+        match ident.0 {
+            live_id!(atan) if arg_tys.len() == 2 => write!(string, "atan2").unwrap(),
+            live_id!(inversesqrt) => write!(string, "inverseSqrt").unwrap(),
+            live_id!(dFdx) => write!(string, "dpdx").unwrap(),
+            live_id!(dFdy) => write!(string, "dpdy").unwrap(),
+            live_id!(sample2d) | live_id!(sample2d_rt) | live_id!(sample2dOES) => {
+                write!(string, "{}", ident).unwrap();
+                self.builtin_helpers.borrow_mut().insert((ident, Vec::new()));
+            }
+            live_id!(mod) | live_id!(lessThan) | live_id!(lessThanEqual) | live_id!(greaterThan) |
+            live_id!(greaterThanEqual) | live_id!(equal) | live_id!(notEqual) | live_id!(not) => {
+                write!(string, "{}", DisplayBuiltinHelper(ident, &arg_tys)).unwrap();
+                self.builtin_helpers.borrow_mut().insert((ident, arg_tys));
+            }
+            live_id!(clamp) | live_id!(min) | live_id!(max) | live_id!(step) | live_id!(smoothstep)
+                if arg_tys.iter().any( | ty | *ty != arg_tys[0]) => {
+                write!(string, "{}", DisplayBuiltinHelper(ident, &arg_tys)).unwrap();
+                self.builtin_helpers.borrow_mut().insert((ident, arg_tys));
+            }
+            _ => write!(string, "{}", ident).unwrap()
+        }
+    }
+}
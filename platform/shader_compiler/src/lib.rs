@@ -14,13 +14,14 @@ pub mod lhs_check;
 pub mod swizzle;
 pub mod util;
 pub mod generate;
+pub mod source_map;
 
-#[cfg(any(target_os = "android", target_os = "linux", target_arch = "wasm32"))]
+// the generators only produce source text, so they all build on every host
+// which lets the tests validate each backend's output offline
 pub mod generate_glsl;
-#[cfg(any(target_os = "macos", target_os="ios", target_os="tvos"))]
 pub mod generate_metal;
-#[cfg(any(target_os = "windows"))]
 pub mod generate_hlsl;
+pub mod generate_wgsl;
 
 pub use makepad_live_compiler;
 pub use makepad_live_compiler::makepad_math;
@@ -45,7 +46,8 @@ pub use {
             ShaderEnum,
             ShaderRegistry,
             DrawShaderQuery
-        }
+        },
+        source_map::ShaderSourceMap,
     }
 };
 
@@ -772,6 +772,22 @@ impl FnDef {
     }
 }
 
+impl Stmt {
+    pub fn span(&self) -> TokenSpan {
+        match self {
+            Stmt::Break {span}
+            | Stmt::Continue {span}
+            | Stmt::For {span, ..}
+            | Stmt::If {span, ..}
+            | Stmt::Match {span, ..}
+            | Stmt::Let {span, ..}
+            | Stmt::Return {span, ..}
+            | Stmt::Block {span, ..}
+            | Stmt::Expr {span, ..} => *span
+        }
+    }
+}
+
 impl DrawShaderDef {
     
     pub fn find_field(&self, ident: Ident) -> Option<&DrawShaderFieldDef> {
@@ -0,0 +1,177 @@
+use {
+    crate::{
+        makepad_live_compiler::{
+            LiveError,
+            LiveErrorSpan,
+            live_error_origin,
+            LiveErrorOrigin,
+            TokenSpan
+        },
+    }
+};
+
+// Maps byte offsets in generated shader source back to the live_design! tokens they
+// were generated from, so errors a GPU driver reports against the generated text
+// can point at the original Rust file.
+#[derive(Clone, Debug, Default)]
+pub struct ShaderSourceMap {
+    // (offset in the generated source, span of the ast node written from there)
+    // in increasing offset order. None marks generated code without a source.
+    entries: Vec<(usize, Option<TokenSpan>)>,
+}
+
+impl ShaderSourceMap {
+    pub fn push(&mut self, offset: usize, span: Option<TokenSpan>) {
+        // nested nodes can start at the same offset, the innermost one wins
+        if let Some(last) = self.entries.last_mut() {
+            if last.0 == offset {
+                last.1 = span;
+                return
+            }
+        }
+        self.entries.push((offset, span));
+    }
+
+    // for when the platform puts a prelude in front of the generated source
+    pub fn offset_by(&mut self, bytes: usize) {
+        for entry in &mut self.entries {
+            entry.0 += bytes;
+        }
+    }
+
+    pub fn span_at_offset(&self, offset: usize) -> Option<TokenSpan> {
+        let index = self.entries.partition_point( | (entry_offset, _) | *entry_offset <= offset);
+        if index == 0 {
+            return None
+        }
+        self.entries[index - 1].1
+    }
+
+    // line is 1 based like in driver logs, the last node that starts on or before
+    // the end of the line is the one that generated it
+    pub fn span_at_line(&self, source: &str, line: usize) -> Option<TokenSpan> {
+        let (start, text) = line_in_source(source, line) ?;
+        self.span_at_offset(start + text.len().max(1) - 1)
+    }
+
+    // turns a driver info log into errors against the live_design! source,
+    // errors that don't map to a shader line are reported at fallback_span
+    pub fn driver_log_to_live_errors(&self, source: &str, log: &str, fallback_span: LiveErrorSpan) -> Vec<LiveError> {
+        let mut live_errors = Vec::new();
+        for driver_error in parse_driver_log(log) {
+            let line = driver_error.line.and_then( | line | line_in_source(source, line).map( | (_, text) | (line, text)));
+            let span = line.and_then( | (line, _) | self.span_at_line(source, line));
+            let message = if let Some((line, text)) = line {
+                format!("{} (generated line {}: {})", driver_error.message, line, text.trim())
+            }
+            else {
+                driver_error.message
+            };
+            live_errors.push(LiveError {
+                origin: live_error_origin!(),
+                span: span.map( | span | span.into()).unwrap_or(fallback_span.clone()),
+                message,
+            });
+        }
+        if live_errors.is_empty() {
+            live_errors.push(LiveError {
+                origin: live_error_origin!(),
+                span: fallback_span,
+                message: format!("shader failed to compile: {}", log.trim()),
+            });
+        }
+        live_errors
+    }
+}
+
+fn line_in_source(source: &str, line: usize) -> Option<(usize, &str)> {
+    let mut start = 0;
+    for (index, text) in source.split('\n').enumerate() {
+        if index + 1 == line {
+            return Some((start, text))
+        }
+        start += text.len() + 1;
+    }
+    None
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShaderDriverError {
+    // 1 based line in the source the driver compiled
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+// Parses a shader info log, the drivers don't agree on a format so this accepts
+// ERROR: 0:12: msg (ANGLE, Apple, Adreno, Mali, glslang)
+// 0:12(5): error: msg (Mesa)
+// 0(12) : error C0000: msg (NVIDIA)
+// warnings and the compiler's summary lines are skipped.
+pub fn parse_driver_log(log: &str) -> Vec<ShaderDriverError> {
+    let mut errors = Vec::new();
+    for line in log.lines() {
+        let line = line.trim_matches( | c: char | c.is_whitespace() || c == '\0');
+        if line.is_empty() || starts_with_ignore_case(line, "warning") || line.contains("compilation errors") {
+            continue
+        }
+        let rest = strip_prefix_ignore_case(line, "error:").unwrap_or(line).trim_start();
+        if let Some((line_number, message)) = parse_location(rest) {
+            if starts_with_ignore_case(message, "warning") {
+                continue
+            }
+            errors.push(ShaderDriverError {
+                line: Some(line_number),
+                message: strip_prefix_ignore_case(message, "error:").unwrap_or(message).trim().to_string()
+            });
+        }
+        else {
+            errors.push(ShaderDriverError {
+                line: None,
+                message: rest.to_string()
+            });
+        }
+    }
+    errors
+}
+
+// parses the source string index and line number in front of a message
+fn parse_location(text: &str) -> Option<(usize, &str)> {
+    let digits = | text: &str | text.find( | c: char | !c.is_ascii_digit()).unwrap_or(text.len());
+    let source_len = digits(text);
+    if source_len == 0 {
+        return None
+    }
+    let rest = &text[source_len..];
+    let (line_number, rest) = if let Some(rest) = rest.strip_prefix(':') {
+        let len = digits(rest);
+        (rest[..len].parse().ok() ?, &rest[len..])
+    }
+    else if let Some(rest) = rest.strip_prefix('(') {
+        let len = digits(rest);
+        (rest[..len].parse().ok() ?, rest[len..].strip_prefix(')') ?)
+    }
+    else {
+        return None
+    };
+    // mesa puts the column in parens behind the line
+    let rest = if let Some(column) = rest.strip_prefix('(') {
+        column.split_once(')').map( | (_, rest) | rest).unwrap_or(column)
+    }
+    else {
+        rest
+    };
+    Some((line_number, rest.trim_start_matches( | c: char | c == ':' || c.is_whitespace())))
+}
+
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    strip_prefix_ignore_case(text, prefix).is_some()
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    if text.len() >= prefix.len() && text.is_char_boundary(prefix.len()) && text[..prefix.len()].eq_ignore_ascii_case(prefix) {
+        Some(&text[prefix.len()..])
+    }
+    else {
+        None
+    }
+}
@@ -0,0 +1,585 @@
+// Shared by the shader compiler tests: compiles the draw shaders straight from the
+// live_design! blocks in draw/src and widgets/src, standing in for the type info that
+// the Live derive and DrawVars::init_shader provide at runtime.
+// every test binary uses a different part of this
+#![allow(dead_code)]
+
+use {
+    std::{
+        any::TypeId,
+        path::PathBuf,
+    },
+    makepad_shader_compiler::{
+        makepad_live_compiler::*,
+        makepad_math::{Vec2, Vec3, Vec4},
+        ShaderRegistry,
+        ShaderTy,
+        DrawShaderPtr,
+        DrawShaderQuery,
+        DrawShaderDef,
+        DrawShaderConstTable,
+    }
+};
+
+// stand-ins for the rust side of the draw shaders, only their TypeIds matter
+struct DrawVars;
+struct GeometryQuad2D;
+struct GeometryCube3D;
+pub struct DrawQuad;
+pub struct DrawColor;
+pub struct DrawIcon;
+pub struct DrawText;
+pub struct DrawLine;
+pub struct DrawTrapezoidVector;
+pub struct DrawPath;
+pub struct DrawMesh3d;
+pub struct DrawEffectLayer;
+pub struct DrawColorWheel;
+pub struct DrawRect;
+pub struct DrawMenuBarItem;
+pub struct DrawPaletteItem;
+struct Std;
+struct ViewEffect;
+struct ColorPicker;
+struct DebugView;
+struct WindowMenu;
+struct CommandPalette;
+
+fn type_info<T: 'static>(name: &str, module_id: &str, fields: Vec<LiveTypeField>) -> LiveTypeInfo {
+    LiveTypeInfo {
+        live_type: TypeId::of::<T>(),
+        type_name: LiveId::from_str(name),
+        module_id: LiveModuleId::from_str(module_id).unwrap(),
+        live_ignore: false,
+        fields
+    }
+}
+
+fn field<T: 'static>(name: &str, live_field_kind: LiveFieldKind) -> LiveTypeField {
+    LiveTypeField {
+        id: LiveId::from_str(name),
+        // plain values have no live definition of their own, same as the derived infos
+        live_type_info: LiveTypeInfo {
+            live_ignore: true,
+            ..type_info::<T>(std::any::type_name::<T>(), "makepad_math::math_f32", vec![])
+        },
+        live_field_kind
+    }
+}
+
+fn sub_field(name: &str, live_type_info: LiveTypeInfo, live_field_kind: LiveFieldKind) -> LiveTypeField {
+    LiveTypeField {
+        id: LiveId::from_str(name),
+        live_type_info,
+        live_field_kind
+    }
+}
+
+fn draw_vars_field() -> LiveTypeField {
+    sub_field("draw_vars", type_info::<DrawVars>("DrawVars", "makepad_platform::draw_vars", vec![]), LiveFieldKind::Deref)
+}
+
+fn geometry_quad_2d() -> LiveTypeInfo {
+    type_info::<GeometryQuad2D>("GeometryQuad2D", "makepad_draw::geometry::geometry_gen", vec![
+        field::<f32>("x1", LiveFieldKind::Live),
+        field::<f32>("y1", LiveFieldKind::Live),
+        field::<f32>("x2", LiveFieldKind::Live),
+        field::<f32>("y2", LiveFieldKind::Live),
+    ])
+}
+
+fn geometry_cube_3d() -> LiveTypeInfo {
+    type_info::<GeometryCube3D>("GeometryCube3D", "makepad_draw::geometry::geometry_gen", vec![
+        field::<f32>("width", LiveFieldKind::Live),
+        field::<f32>("height", LiveFieldKind::Live),
+        field::<f32>("depth", LiveFieldKind::Live),
+    ])
+}
+
+fn geometry_field() -> LiveTypeField {
+    sub_field("geometry", geometry_quad_2d(), LiveFieldKind::Live)
+}
+
+fn draw_super_field() -> LiveTypeField {
+    sub_field("draw_super", draw_quad(), LiveFieldKind::Deref)
+}
+
+fn draw_quad() -> LiveTypeInfo {
+    type_info::<DrawQuad>("DrawQuad", "makepad_draw::shader::draw_quad", vec![
+        geometry_field(),
+        draw_vars_field(),
+        field::<Vec2>("rect_pos", LiveFieldKind::Calc),
+        field::<Vec2>("rect_size", LiveFieldKind::Calc),
+        field::<Vec4>("draw_clip", LiveFieldKind::Calc),
+        field::<f32>("draw_depth", LiveFieldKind::Live),
+    ])
+}
+
+// the live files in registration order, with the rust types their live_design! block refers to.
+// the widget types that aren't draw shaders themselves are registered without fields
+fn live_files() -> Vec<(&'static str, &'static str, Vec<LiveTypeInfo>)> {
+    vec![
+        ("draw/src/geometry/geometry_gen.rs", "makepad_draw::geometry::geometry_gen", vec![geometry_quad_2d(), geometry_cube_3d()]),
+        ("draw/src/shader/std.rs", "makepad_draw::shader::std", vec![type_info::<Std>("Std", "makepad_draw::shader::std", vec![])]),
+        ("draw/src/shader/draw_quad.rs", "makepad_draw::shader::draw_quad", vec![draw_quad()]),
+        ("draw/src/shader/draw_color.rs", "makepad_draw::shader::draw_color", vec![
+            type_info::<DrawColor>("DrawColor", "makepad_draw::shader::draw_color", vec![
+                draw_super_field(),
+                field::<Vec4>("color", LiveFieldKind::Live),
+            ])
+        ]),
+        ("draw/src/shader/draw_icon.rs", "makepad_draw::shader::draw_icon", vec![
+            type_info::<DrawIcon>("DrawIcon", "makepad_draw::shader::draw_icon", vec![
+                field::<f32>("brightness", LiveFieldKind::Live),
+                field::<f32>("curve", LiveFieldKind::Live),
+                field::<f32>("linearize", LiveFieldKind::Live),
+                field::<bool>("multicolor", LiveFieldKind::Live),
+                geometry_field(),
+                draw_vars_field(),
+                field::<Vec2>("rect_pos", LiveFieldKind::Calc),
+                field::<Vec2>("rect_size", LiveFieldKind::Calc),
+                field::<Vec4>("draw_clip", LiveFieldKind::Calc),
+                field::<f32>("draw_depth", LiveFieldKind::Live),
+                field::<Vec4>("color", LiveFieldKind::Live),
+                field::<Vec2>("icon_t1", LiveFieldKind::Calc),
+                field::<Vec2>("icon_t2", LiveFieldKind::Calc),
+            ])
+        ]),
+        ("draw/src/shader/draw_text.rs", "makepad_draw::shader::draw_text", vec![
+            type_info::<DrawText>("DrawText", "makepad_draw::shader::draw_text", vec![
+                geometry_field(),
+                field::<f32>("draw_depth", LiveFieldKind::Live),
+                draw_vars_field(),
+                field::<Vec4>("color", LiveFieldKind::Live),
+                field::<Vec2>("font_t1", LiveFieldKind::Calc),
+                field::<Vec2>("font_t2", LiveFieldKind::Calc),
+                field::<Vec2>("rect_pos", LiveFieldKind::Calc),
+                field::<Vec2>("rect_size", LiveFieldKind::Calc),
+                field::<Vec4>("draw_clip", LiveFieldKind::Calc),
+                field::<f32>("char_depth", LiveFieldKind::Calc),
+                field::<Vec2>("delta", LiveFieldKind::Calc),
+                field::<f32>("shader_font_size", LiveFieldKind::Calc),
+                field::<f32>("advance", LiveFieldKind::Calc),
+                field::<f32>("color_glyph", LiveFieldKind::Calc),
+            ])
+        ]),
+        ("draw/src/shader/draw_line.rs", "makepad_draw::shader::draw_line", vec![
+            type_info::<DrawLine>("DrawLine", "makepad_draw::shader::draw_line", vec![
+                draw_super_field(),
+                field::<Vec2>("line_start", LiveFieldKind::Calc),
+                field::<Vec2>("line_end", LiveFieldKind::Calc),
+                field::<f32>("half_line_width", LiveFieldKind::Calc),
+                field::<Vec4>("color", LiveFieldKind::Calc),
+            ])
+        ]),
+        ("draw/src/shader/draw_trapezoid.rs", "makepad_draw::shader::draw_trapezoid", vec![
+            type_info::<DrawTrapezoidVector>("DrawTrapezoidVector", "makepad_draw::shader::draw_trapezoid", vec![
+                geometry_field(),
+                draw_vars_field(),
+                field::<Vec2>("a_xs", LiveFieldKind::Calc),
+                field::<Vec4>("a_ys", LiveFieldKind::Calc),
+                field::<f32>("chan", LiveFieldKind::Calc),
+                field::<Vec4>("color", LiveFieldKind::Calc),
+                field::<Vec4>("gradient_color", LiveFieldKind::Calc),
+                field::<Vec4>("gradient_color2", LiveFieldKind::Calc),
+                field::<Vec4>("gradient_color3", LiveFieldKind::Calc),
+                field::<Vec4>("gradient", LiveFieldKind::Calc),
+                field::<Vec4>("gradient_offsets", LiveFieldKind::Calc),
+                field::<f32>("gradient_kind", LiveFieldKind::Calc),
+            ])
+        ]),
+        ("draw/src/shader/draw_path.rs", "makepad_draw::shader::draw_path", vec![
+            type_info::<DrawPath>("DrawPath", "makepad_draw::shader::draw_path", vec![
+                draw_super_field(),
+                field::<Vec4>("color", LiveFieldKind::Live),
+                field::<Vec4>("gradient_color", LiveFieldKind::Calc),
+                field::<Vec4>("gradient", LiveFieldKind::Calc),
+                field::<f32>("gradient_kind", LiveFieldKind::Calc),
+                field::<Vec2>("a_xs", LiveFieldKind::Calc),
+                field::<Vec4>("a_ys", LiveFieldKind::Calc),
+            ])
+        ]),
+        ("draw/src/shader/draw_mesh.rs", "makepad_draw::shader::draw_mesh", vec![
+            type_info::<DrawMesh3d>("DrawMesh3d", "makepad_draw::shader::draw_mesh", vec![
+                sub_field("geometry", geometry_cube_3d(), LiveFieldKind::Live),
+                draw_vars_field(),
+                field::<Vec4>("model_col0", LiveFieldKind::Calc),
+                field::<Vec4>("model_col1", LiveFieldKind::Calc),
+                field::<Vec4>("model_col2", LiveFieldKind::Calc),
+                field::<Vec4>("model_col3", LiveFieldKind::Calc),
+                field::<Vec4>("base_color", LiveFieldKind::Live),
+                field::<f32>("metallic", LiveFieldKind::Live),
+                field::<f32>("roughness", LiveFieldKind::Live),
+                field::<f32>("unlit", LiveFieldKind::Live),
+                field::<f32>("ambient", LiveFieldKind::Live),
+                field::<Vec3>("light_dir", LiveFieldKind::Live),
+            ])
+        ]),
+        ("widgets/src/view_effect.rs", "makepad_widgets::view_effect", vec![
+            type_info::<DrawEffectLayer>("DrawEffectLayer", "makepad_widgets::view_effect", vec![
+                draw_super_field(),
+                field::<Vec4>("uv_rect", LiveFieldKind::Live),
+                field::<Vec2>("blur_step", LiveFieldKind::Live),
+                field::<Vec4>("color", LiveFieldKind::Live),
+            ]),
+            type_info::<ViewEffect>("ViewEffect", "makepad_widgets::view_effect", vec![]),
+        ]),
+        ("widgets/src/color_picker.rs", "makepad_widgets::color_picker", vec![
+            type_info::<DrawColorWheel>("DrawColorWheel", "makepad_widgets::color_picker", vec![
+                draw_super_field(),
+                field::<f32>("hue", LiveFieldKind::Live),
+                field::<f32>("sat", LiveFieldKind::Live),
+                field::<f32>("val", LiveFieldKind::Live),
+            ]),
+            type_info::<ColorPicker>("ColorPicker", "makepad_widgets::color_picker", vec![]),
+        ]),
+        ("widgets/src/debug_view.rs", "makepad_widgets::debug_view", vec![
+            type_info::<DrawRect>("DrawRect", "makepad_widgets::debug_view", vec![
+                draw_super_field(),
+                field::<Vec4>("color", LiveFieldKind::Live),
+            ]),
+            type_info::<DebugView>("DebugView", "makepad_widgets::debug_view", vec![]),
+        ]),
+        ("widgets/src/window_menu.rs", "makepad_widgets::window_menu", vec![
+            type_info::<DrawMenuBarItem>("DrawMenuBarItem", "makepad_widgets::window_menu", vec![
+                draw_super_field(),
+                field::<f32>("hover", LiveFieldKind::Live),
+                field::<f32>("active", LiveFieldKind::Live),
+            ]),
+            type_info::<WindowMenu>("WindowMenu", "makepad_widgets::window_menu", vec![]),
+        ]),
+        ("widgets/src/command_palette.rs", "makepad_widgets::command_palette", vec![
+            type_info::<DrawPaletteItem>("DrawPaletteItem", "makepad_widgets::command_palette", vec![
+                draw_super_field(),
+                field::<f32>("selected", LiveFieldKind::Live),
+            ]),
+            type_info::<CommandPalette>("CommandPalette", "makepad_widgets::command_palette", vec![]),
+        ]),
+    ]
+}
+
+pub fn repo_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../..")
+}
+
+// returns the body of the live_design!{} block and the position it starts at
+fn extract_live_design(source: &str) -> (String, TextPos) {
+    let macro_start = source.find("live_design!").expect("no live_design! block");
+    let start = macro_start + source[macro_start..].find('{').unwrap() + 1;
+    let mut depth = 1;
+    for (offset, c) in source[start..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    let line = source[0..start].lines().count() as u32 - 1;
+                    let column = source[0..start].lines().last().unwrap().len() as u32;
+                    return (source[start..start + offset].to_string(), TextPos {line, column})
+                }
+            }
+            _ => ()
+        }
+    }
+    panic!("unterminated live_design! block")
+}
+
+// mirrors what DrawVars::init_shader feeds the analyser on the platform side
+fn expand_instances(live_registry: &LiveRegistry, level: usize, after_draw_vars: &mut bool, live_type: LiveType, draw_shader_def: &mut DrawShaderDef, span: TokenSpan) {
+    if let Some(lf) = live_registry.live_type_infos.get(&live_type) {
+        let mut slots = 0;
+        for field in &lf.fields {
+            if let LiveFieldKind::Deref = field.live_field_kind {
+                if field.live_type_info.live_type != TypeId::of::<DrawVars>() {
+                    expand_instances(live_registry, level + 1, after_draw_vars, field.live_type_info.live_type, draw_shader_def, span);
+                }
+                else {
+                    *after_draw_vars = true;
+                }
+                continue
+            }
+            if *after_draw_vars {
+                let live_type = field.live_type_info.live_type;
+                let ty = if live_type == TypeId::of::<f32>() {ShaderTy::Float}
+                else if live_type == TypeId::of::<Vec2>() {ShaderTy::Vec2}
+                else if live_type == TypeId::of::<Vec3>() {ShaderTy::Vec3}
+                else if live_type == TypeId::of::<Vec4>() {ShaderTy::Vec4}
+                else {panic!("unexpected instance type")};
+                slots += ty.slots();
+                draw_shader_def.add_instance(field.id, ty, span, field.live_field_kind);
+            }
+        }
+        if level > 0 && slots % 2 == 1 {
+            draw_shader_def.add_instance(LiveId(0), ShaderTy::Float, span, LiveFieldKind::Calc);
+        }
+    }
+}
+
+// the draw shader classes, as the file that defines them, their name and their stand-in type
+pub fn draw_shaders() -> Vec<(&'static str, &'static str, LiveType)> {
+    vec![
+        ("draw/src/shader/draw_quad.rs", "DrawQuad", TypeId::of::<DrawQuad>()),
+        ("draw/src/shader/draw_color.rs", "DrawColor", TypeId::of::<DrawColor>()),
+        ("draw/src/shader/draw_icon.rs", "DrawIcon", TypeId::of::<DrawIcon>()),
+        ("draw/src/shader/draw_text.rs", "DrawText", TypeId::of::<DrawText>()),
+        ("draw/src/shader/draw_line.rs", "DrawLine", TypeId::of::<DrawLine>()),
+        ("draw/src/shader/draw_trapezoid.rs", "DrawTrapezoidVector", TypeId::of::<DrawTrapezoidVector>()),
+        ("draw/src/shader/draw_path.rs", "DrawPath", TypeId::of::<DrawPath>()),
+        ("draw/src/shader/draw_mesh.rs", "DrawMesh3d", TypeId::of::<DrawMesh3d>()),
+        ("widgets/src/view_effect.rs", "DrawEffectLayer", TypeId::of::<DrawEffectLayer>()),
+        ("widgets/src/color_picker.rs", "DrawColorWheel", TypeId::of::<DrawColorWheel>()),
+        ("widgets/src/debug_view.rs", "DrawRect", TypeId::of::<DrawRect>()),
+        ("widgets/src/window_menu.rs", "DrawMenuBarItem", TypeId::of::<DrawMenuBarItem>()),
+        ("widgets/src/command_palette.rs", "DrawPaletteItem", TypeId::of::<DrawPaletteItem>()),
+    ]
+}
+
+// the classes in live_design! blocks under dir that deref a DrawQuad or DrawVars and bring
+// shader code of their own, as (file, class name). these should all be in draw_shaders
+pub fn find_draw_shader_classes(dir: &str) -> Vec<(String, String)> {
+    let mut classes = Vec::new();
+    let mut dirs = vec![repo_dir().join(dir)];
+    while let Some(path) = dirs.pop() {
+        for entry in std::fs::read_dir(&path).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue
+            }
+            if path.extension().is_none_or( | ext | ext != "rs") {
+                continue
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            if !source.contains("live_design!") {
+                continue
+            }
+            let file = path.strip_prefix(repo_dir()).unwrap().to_string_lossy().replace('\\', "/");
+            let (body, _) = extract_live_design(&source);
+            let mut rest = body.as_str();
+            while let Some(start) = rest.find("{{") {
+                let end = start + rest[start..].find("}}").unwrap();
+                let name = &rest[start + 2..end];
+                rest = &rest[end + 2..];
+                let class_body = block_after(rest);
+                if has_own_fn(class_body) && derefs_draw_shader(&source, name) {
+                    classes.push((file.clone(), name.to_string()));
+                }
+            }
+        }
+    }
+    classes.sort();
+    classes
+}
+
+// the contents of the {} block that text starts with
+fn block_after(text: &str) -> &str {
+    let start = text.find('{').unwrap() + 1;
+    let mut depth = 1;
+    for (offset, c) in text[start..].char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return &text[start..start + offset]
+                }
+            }
+            _ => ()
+        }
+    }
+    panic!("unterminated block")
+}
+
+// whether a class body defines a fn itself rather than only inside nested properties
+fn has_own_fn(class_body: &str) -> bool {
+    let mut depth = 0;
+    for (offset, c) in class_body.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            'f' if depth == 0 && class_body[offset..].starts_with("fn ")
+                && !class_body[..offset].ends_with( | c: char | c.is_alphanumeric() || c == '_') => return true,
+            _ => ()
+        }
+    }
+    false
+}
+
+fn derefs_draw_shader(source: &str, name: &str) -> bool {
+    let Some(start) = source.find(&format!("struct {} {{", name)) else {
+        return false
+    };
+    let fields = block_after(&source[start..]);
+    fields.contains("draw_super: Draw") || fields.contains("draw_vars: DrawVars")
+}
+
+// the fields the Live derive puts in the live_type_info of a struct, as (name, kind, type),
+// read from its definition in the source file
+fn struct_live_fields(source: &str, name: LiveId) -> Option<(String, Vec<(String, LiveFieldKind, String)>)> {
+    let (start, name) = source.match_indices("struct ").find_map( | (start, _) | {
+        let (candidate, _) = source[start + 7..].split_once(" {")?;
+        (LiveId::from_str(candidate) == name).then(|| (start, candidate.to_string()))
+    })?;
+    let body: String = block_after(&source[start..]).lines()
+        .map( | line | line.split("//").next().unwrap())
+        .collect::<Vec<_>>()
+        .join("\n");
+    // fields end at commas outside of generics
+    let mut fields = Vec::new();
+    let mut depth = 0;
+    let mut field_start = 0;
+    for (offset, c) in body.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                fields.push(&body[field_start..offset]);
+                field_start = offset + 1;
+            }
+            _ => ()
+        }
+    }
+    fields.push(&body[field_start..]);
+    let mut live_fields = Vec::new();
+    for field in fields {
+        let mut rest = field.trim();
+        let mut attrs = Vec::new();
+        while let Some(attr) = rest.strip_prefix("#[") {
+            let end = attr.find(']').unwrap();
+            attrs.push(attr[..end].split('(').next().unwrap().trim().to_string());
+            rest = attr[end + 1..].trim_start();
+        }
+        let Some((field_name, ty)) = rest.trim_start_matches("pub ").split_once(':') else {
+            continue
+        };
+        let ty = ty.trim().to_string();
+        let kind = match attrs.iter().find( | attr | ["animator", "live", "calc", "deref"].contains(&attr.as_str())).map( | attr | attr.as_str()) {
+            Some("live") if ty.starts_with("Option<") => LiveFieldKind::LiveOption,
+            Some("live") => LiveFieldKind::Live,
+            Some("calc") => LiveFieldKind::Calc,
+            Some("deref") => LiveFieldKind::Deref,
+            Some("animator") => LiveFieldKind::Animator,
+            _ => continue
+        };
+        live_fields.push((field_name.trim().to_string(), kind, ty));
+    }
+    Some((name, live_fields))
+}
+
+fn copied_type_name(live_type_info: &LiveTypeInfo) -> LiveId {
+    let live_type = live_type_info.live_type;
+    let name = if live_type == TypeId::of::<f32>() {"f32"}
+    else if live_type == TypeId::of::<bool>() {"bool"}
+    else if live_type == TypeId::of::<Vec2>() {"Vec2"}
+    else if live_type == TypeId::of::<Vec3>() {"Vec3"}
+    else if live_type == TypeId::of::<Vec4>() {"Vec4"}
+    else {return live_type_info.type_name};
+    LiveId::from_str(name)
+}
+
+// compares the fields copied into live_files to the structs in the draw and widget sources. a copy
+// can leave out fields that only the rust side uses, but not the geometry, a deref or anything
+// after the first deref, as those make up the instances and uniforms of the shader
+pub fn live_type_info_mismatches() -> Vec<String> {
+    let mut mismatches = Vec::new();
+    for (file, _, live_type_infos) in live_files() {
+        let source = std::fs::read_to_string(repo_dir().join(file)).unwrap();
+        for live_type_info in live_type_infos.iter().filter( | info | !info.fields.is_empty()) {
+            let Some((name, struct_fields)) = struct_live_fields(&source, live_type_info.type_name) else {
+                mismatches.push(format!("{}: no struct for {}", file, live_type_info.type_name));
+                continue
+            };
+            let mut copied = live_type_info.fields.iter().peekable();
+            let mut after_deref = false;
+            for (field_name, kind, ty) in &struct_fields {
+                after_deref |= *kind == LiveFieldKind::Deref;
+                let required = after_deref || field_name == "geometry";
+                match copied.peek() {
+                    Some(field) if field.id == LiveId::from_str(field_name) => {
+                        if field.live_field_kind != *kind || copied_type_name(&field.live_type_info) != LiveId::from_str(ty) {
+                            mismatches.push(format!("{}: {}.{} is {:?} {} in the source", file, name, field_name, kind, ty));
+                        }
+                        copied.next();
+                    }
+                    _ if required => mismatches.push(format!("{}: {}.{} is missing", file, name, field_name)),
+                    _ => ()
+                }
+            }
+            if copied.next().is_some() {
+                mismatches.push(format!("{}: {} has fields that are not in the source, or out of order", file, name));
+            }
+        }
+    }
+    mismatches
+}
+
+pub fn draw_shader_name(file_name: &str) -> &str {
+    file_name.rsplit('/').next().unwrap().trim_end_matches(".rs")
+}
+
+// analyses the draw shader defined in file_name and hands it to the generator in cb
+pub fn with_draw_shader<R>(file_name: &str, class_type: LiveType, cb: impl FnOnce(&DrawShaderDef, &DrawShaderConstTable, &ShaderRegistry) -> R) -> R {
+    with_draw_shader_and_live_registry(file_name, class_type, | draw_shader_def, const_table, shader_registry, _live_registry | {
+        cb(draw_shader_def, const_table, shader_registry)
+    })
+}
+
+// same as with_draw_shader, but also hands over the live registry to resolve spans against
+pub fn with_draw_shader_and_live_registry<R>(file_name: &str, class_type: LiveType, cb: impl FnOnce(&DrawShaderDef, &DrawShaderConstTable, &ShaderRegistry, &LiveRegistry) -> R) -> R {
+    let mut live_registry = LiveRegistry::default();
+    for (file, module_id, live_type_infos) in live_files() {
+        let source = std::fs::read_to_string(repo_dir().join(file)).unwrap();
+        let (body, start_pos) = extract_live_design(&source);
+        if let Err(err) = live_registry.register_live_file(
+            file,
+            env!("CARGO_MANIFEST_DIR"),
+            LiveModuleId::from_str(module_id).unwrap(),
+            body,
+            live_type_infos,
+            start_pos,
+        ) {
+            panic!("{}", err);
+        }
+    }
+    let mut errors = Vec::new();
+    live_registry.expand_all_documents(&mut errors);
+    assert!(errors.is_empty(), "{:?}", errors);
+
+    let file_id = live_registry.file_name_to_file_id(file_name).unwrap();
+    let nodes = &live_registry.file_id_to_file(file_id).expanded.nodes;
+    let index = nodes.iter().position( | node | {
+        matches!(node.value, LiveValue::Class {live_type, ..} if live_type == class_type)
+    }).unwrap();
+    let draw_shader_ptr = DrawShaderPtr(live_registry.file_id_index_to_live_ptr(file_id, index));
+
+    let mut shader_registry = ShaderRegistry::new();
+    let result = shader_registry.analyse_draw_shader(&live_registry, draw_shader_ptr, | live_registry, _shader_registry, span, draw_shader_query, live_type, draw_shader_def | {
+        match draw_shader_query {
+            DrawShaderQuery::DrawShader => {
+                expand_instances(live_registry, 0, &mut false, live_type, draw_shader_def, span);
+            }
+            DrawShaderQuery::Geometry => {
+                // the same fields as the GeometryFields impls in draw/src/geometry/geometry_gen.rs
+                let fields: &[(&str, ShaderTy)] = if live_type == TypeId::of::<GeometryQuad2D>() {
+                    &[("geom_pos", ShaderTy::Vec2)]
+                }
+                else if live_type == TypeId::of::<GeometryCube3D>() {
+                    &[("geom_pos", ShaderTy::Vec3), ("geom_id", ShaderTy::Float), ("geom_normal", ShaderTy::Vec3), ("geom_uv", ShaderTy::Vec2)]
+                }
+                else {
+                    panic!("unexpected geometry type")
+                };
+                for (id, ty) in fields {
+                    draw_shader_def.add_geometry(LiveId::from_str(id), ty.clone(), span);
+                }
+            }
+        }
+    });
+    if let Err(err) = result {
+        panic!("{}", live_registry.live_error_to_live_file_error(err));
+    }
+    let const_table = shader_registry.compute_const_table(draw_shader_ptr);
+    let draw_shader_def = shader_registry.draw_shader_defs.get(&draw_shader_ptr).unwrap();
+    cb(draw_shader_def, &const_table, &shader_registry, &live_registry)
+}
+
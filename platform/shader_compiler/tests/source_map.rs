@@ -0,0 +1,63 @@
+// Checks that GPU driver errors against the generated GLSL end up at the
+// live_design! source they were generated from.
+
+mod common;
+
+use {
+    makepad_shader_compiler::{
+        makepad_live_compiler::{LiveErrorSpan, TokenSpan},
+        generate_glsl::{self, GlslFlavor},
+        source_map::{parse_driver_log, ShaderDriverError},
+    },
+};
+
+#[test]
+fn parses_driver_logs() {
+    let logs = [
+        ("ERROR: 0:12: 'foo' : undeclared identifier\nERROR: 1 compilation errors.  No code generated.", "'foo' : undeclared identifier"),
+        ("0:12(5): error: `foo' undeclared", "`foo' undeclared"),
+        ("0(12) : error C1008: undefined variable \"foo\"", "error C1008: undefined variable \"foo\""),
+    ];
+    for (log, message) in logs {
+        assert_eq!(parse_driver_log(log), vec![ShaderDriverError {line: Some(12), message: message.to_string()}], "{}", log);
+    }
+    assert_eq!(parse_driver_log("WARNING: 0:3: extension not supported\n0:4(1): warning: unused"), vec![]);
+    assert_eq!(
+        parse_driver_log("error: fragment shader varying v_x not written by vertex shader"),
+        vec![ShaderDriverError {line: None, message: "fragment shader varying v_x not written by vertex shader".to_string()}]
+    );
+}
+
+#[test]
+fn driver_errors_map_to_live_design() {
+    for (file_name, _class_name, class_type) in common::draw_shaders() {
+        common::with_draw_shader_and_live_registry(file_name, class_type, | draw_shader_def, const_table, shader_registry, live_registry | {
+            let fallback_span = LiveErrorSpan::Token(TokenSpan::default());
+            let generated = [
+                ("vertex", generate_glsl::generate_vertex_shader_with_flavor(draw_shader_def, const_table, shader_registry, GlslFlavor::Es100)),
+                ("pixel", generate_glsl::generate_pixel_shader_with_flavor(draw_shader_def, const_table, shader_registry, GlslFlavor::Es100)),
+            ];
+            for (stage, generated) in generated {
+                // find the head of the stage entry fn in the output, its name is the fn ptr followed by
+                // the ident so it ends in a digit and _vertex or _pixel. it can be inherited from another file
+                let entry_fn = format!("_{}(", stage);
+                let line = generated.glsl.lines().position( | line | {
+                    line.trim_end().ends_with('{') && line.find(&entry_fn).is_some_and( | index | {
+                        line[..index].ends_with( | c: char | c.is_ascii_digit())
+                    })
+                }).unwrap_or_else( | | panic!("no {} fn in\n{}", stage, generated.glsl)) + 1;
+
+                let log = format!("0:{}(1): error: something the driver didn't like", line);
+                let errors = generated.source_map.driver_log_to_live_errors(&generated.glsl, &log, fallback_span.clone());
+                assert_eq!(errors.len(), 1);
+                let error = live_registry.live_error_to_live_file_error(errors[0].clone());
+                let source = std::fs::read_to_string(common::repo_dir().join(&error.file)).unwrap();
+                let original = source.lines().nth(error.span.start.line as usize).unwrap();
+                assert!(
+                    original.contains(&format!("fn {}", stage)),
+                    "{} {} line {} maps to {}:{} `{}`", file_name, stage, line, error.file, error.span.start.line + 1, original
+                );
+            }
+        });
+    }
+}
@@ -0,0 +1,183 @@
+// Offline validation of every backend's output for the draw shaders, so codegen bugs
+// fail here instead of as a black screen on a driver that rejects the shader.
+// WGSL and Vulkan GLSL are checked with naga, which also lowers the Vulkan GLSL to SPIR-V.
+// GLSL ES, HLSL and Metal need the vendor compilers, those checks are ignored by default
+// and fail when glslangValidator, dxc or xcrun isn't on the PATH, run them with
+// cargo test -- --ignored on a machine that has the tools.
+
+mod common;
+
+use {
+    std::{
+        path::{Path, PathBuf},
+        process::Command,
+        io::ErrorKind,
+    },
+    makepad_shader_compiler::{
+        generate_glsl::{self, GlslFlavor},
+        generate_hlsl,
+        generate_metal,
+        generate_wgsl,
+    },
+    naga::{
+        valid::{Validator, ValidationFlags, Capabilities},
+        ShaderStage,
+    }
+};
+
+// the prelude the OpenGL backend puts in front of the generated GLSL ES
+const GLES_PRELUDE: &str = "#version 100
+precision highp float;
+precision highp int;
+vec4 sample2d(sampler2D sampler, vec2 pos){return texture2D(sampler, vec2(pos.x, pos.y));}
+vec4 sample2d_rt(sampler2D sampler, vec2 pos){return texture2D(sampler, vec2(pos.x, 1.0-pos.y));}
+";
+
+// runs check on every draw shader and reports all the failures at once. the shaders are
+// compiled against the struct layouts copied into tests/common, so those have to match the
+// shipped structs for the check to mean anything
+fn validate_all(backend: &str, check: impl Fn(&str, &str, &str) -> Result<(), String>) {
+    let mismatches = common::live_type_info_mismatches();
+    assert!(mismatches.is_empty(), "update live_files() in tests/common/mod.rs:\n{}", mismatches.join("\n"));
+    let mut failures = Vec::new();
+    for (file_name, _class_name, class_type) in common::draw_shaders() {
+        let name = common::draw_shader_name(file_name);
+        for (stage, source) in common::with_draw_shader(file_name, class_type, | draw_shader_def, const_table, shader_registry | {
+            generate_stages(backend, draw_shader_def, const_table, shader_registry)
+        }) {
+            if let Err(err) = check(name, stage, &source) {
+                failures.push(format!("{} {} {}:\n{}\n{}", backend, name, stage, err, source));
+            }
+        }
+    }
+    if !failures.is_empty() {
+        panic!("{} of the generated {} shaders failed to validate\n{}", failures.len(), backend, failures.join("\n"));
+    }
+}
+
+fn generate_stages(
+    backend: &str,
+    draw_shader_def: &makepad_shader_compiler::DrawShaderDef,
+    const_table: &makepad_shader_compiler::DrawShaderConstTable,
+    shader_registry: &makepad_shader_compiler::ShaderRegistry
+) -> Vec<(&'static str, String)> {
+    match backend {
+        "wgsl" => vec![("module", generate_wgsl::generate_shader(draw_shader_def, const_table, shader_registry).wgsl)],
+        "metal" => vec![("module", generate_metal::generate_shader(draw_shader_def, const_table, shader_registry).mtlsl)],
+        "hlsl" => vec![("module", generate_hlsl::generate_shader(draw_shader_def, const_table, shader_registry))],
+        "gles" | "vulkan" => {
+            let flavor = if backend == "gles" {GlslFlavor::Es100} else {GlslFlavor::Vulkan450};
+            vec![
+                ("vert", generate_glsl::generate_vertex_shader_with_flavor(draw_shader_def, const_table, shader_registry, flavor).glsl),
+                ("frag", generate_glsl::generate_pixel_shader_with_flavor(draw_shader_def, const_table, shader_registry, flavor).glsl),
+            ]
+        }
+        _ => panic!("unknown backend {}", backend)
+    }
+}
+
+fn validate_module(module: &naga::Module) -> Result<naga::valid::ModuleInfo, String> {
+    Validator::new(ValidationFlags::all(), Capabilities::empty())
+        .validate(module)
+        .map_err( | err | format!("{:?}", err.into_inner()))
+}
+
+fn scratch_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join("makepad_shader_validate");
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// runs an external validator on the source
+fn run_tool(tool: &str, args: &[&str], path: &Path, source: &str) -> Result<(), String> {
+    std::fs::write(path, source).unwrap();
+    match Command::new(tool).args(args).arg(path).output() {
+        Err(err) => Err(format!("could not run {}: {}", tool, err)),
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => Err(format!(
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        )),
+    }
+}
+
+// the ignored tests only run on request, so a missing tool is an error there and not a skip
+fn require_tool(tool: &str) {
+    if let Err(err) = Command::new(tool).arg("--version").output() {
+        if err.kind() == ErrorKind::NotFound {
+            panic!("{} is not on the PATH", tool);
+        }
+    }
+}
+
+#[test]
+fn every_draw_shader_is_validated() {
+    let mut classes = common::find_draw_shader_classes("draw/src");
+    classes.extend(common::find_draw_shader_classes("widgets/src"));
+    let mut covered: Vec<(String, String)> = common::draw_shaders().into_iter().map( | (file_name, class_name, _) | {
+        (file_name.to_string(), class_name.to_string())
+    }).collect();
+    covered.sort();
+    classes.sort();
+    assert_eq!(classes, covered, "add the draw shaders with code of their own to draw_shaders() in tests/common/mod.rs");
+}
+
+#[test]
+fn wgsl_validates() {
+    validate_all("wgsl", | _name, _stage, source | {
+        let module = naga::front::wgsl::parse_str(source).map_err( | err | err.emit_to_string(source))?;
+        validate_module(&module)?;
+        Ok(())
+    });
+}
+
+#[test]
+fn vulkan_glsl_validates_and_lowers_to_spirv() {
+    validate_all("vulkan", | _name, stage, source | {
+        let shader_stage = if stage == "vert" {ShaderStage::Vertex} else {ShaderStage::Fragment};
+        let module = naga::front::glsl::Frontend::default()
+            .parse(&naga::front::glsl::Options::from(shader_stage), source)
+            .map_err( | errors | errors.emit_to_string(source))?;
+        let info = validate_module(&module)?;
+        let pipeline_options = naga::back::spv::PipelineOptions {
+            shader_stage,
+            entry_point: "main".to_string(),
+        };
+        naga::back::spv::write_vec(&module, &info, &naga::back::spv::Options::default(), Some(&pipeline_options))
+            .map_err( | err | format!("SPIR-V output failed: {}", err))?;
+        Ok(())
+    });
+}
+
+#[test]
+#[ignore = "needs glslangValidator on the PATH"]
+fn gles_glsl_validates() {
+    require_tool("glslangValidator");
+    validate_all("gles", | name, stage, source | {
+        let path = scratch_dir().join(format!("{}.{}", name, stage));
+        run_tool("glslangValidator", &[], &path, &format!("{}{}", GLES_PRELUDE, source))
+    });
+}
+
+#[test]
+#[ignore = "needs dxc on the PATH"]
+fn hlsl_validates() {
+    require_tool("dxc");
+    validate_all("hlsl", | name, _stage, source | {
+        let path = scratch_dir().join(format!("{}.hlsl", name));
+        run_tool("dxc", &["-T", "vs_6_0", "-E", "vertex_main"], &path, source)?;
+        run_tool("dxc", &["-T", "ps_6_0", "-E", "pixel_main"], &path, source)
+    });
+}
+
+#[test]
+#[ignore = "needs xcrun with the metal toolchain"]
+fn metal_validates() {
+    require_tool("xcrun");
+    validate_all("metal", | name, _stage, source | {
+        let path = scratch_dir().join(format!("{}.metal", name));
+        let output = scratch_dir().join(format!("{}.air", name));
+        run_tool("xcrun", &["-sdk", "macosx", "metal", "-c", "-o", output.to_str().unwrap()], &path, source)
+    });
+}
@@ -0,0 +1,87 @@
+// Golden file tests for the WGSL backend. They compile the draw shaders straight from
+// the live_design! blocks in draw/src/shader/*.rs, so a change to either those shaders
+// or the generator shows up as a diff against tests/wgsl/*.wgsl.
+// Run with MAKEPAD_UPDATE_GOLDEN=1 to rewrite the golden files after an intended change.
+
+mod common;
+
+use {
+    std::{
+        any::TypeId,
+        path::PathBuf,
+    },
+    makepad_shader_compiler::{
+        makepad_live_compiler::LiveType,
+        generate_wgsl,
+    }
+};
+
+fn check_golden(file_name: &str, class_type: LiveType) {
+    let wgsl = common::with_draw_shader(file_name, class_type, | draw_shader_def, const_table, shader_registry | {
+        generate_wgsl::generate_shader(draw_shader_def, const_table, shader_registry).wgsl
+    });
+    let name = common::draw_shader_name(file_name);
+    let golden_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/wgsl").join(format!("{}.wgsl", name));
+    if std::env::var("MAKEPAD_UPDATE_GOLDEN").is_ok() {
+        std::fs::create_dir_all(golden_path.parent().unwrap()).unwrap();
+        std::fs::write(&golden_path, &wgsl).unwrap();
+        return
+    }
+    let golden = std::fs::read_to_string(&golden_path).unwrap_or_else( | _ | {
+        panic!("missing golden file {:?}, run with MAKEPAD_UPDATE_GOLDEN=1 to create it", golden_path)
+    });
+    if golden != wgsl {
+        for (line, (a, b)) in golden.lines().zip(wgsl.lines()).enumerate() {
+            if a != b {
+                panic!("WGSL for {} differs from {:?} at line {}:\nexpected: {}\n     got: {}", name, golden_path, line + 1, a, b);
+            }
+        }
+        panic!("WGSL for {} differs in length from {:?}", name, golden_path);
+    }
+}
+
+#[test]
+fn copied_type_infos_match_the_structs() {
+    let mismatches = common::live_type_info_mismatches();
+    assert!(mismatches.is_empty(), "update live_files() in tests/common/mod.rs:\n{}", mismatches.join("\n"));
+}
+
+#[test]
+fn draw_quad_wgsl() {
+    check_golden("draw/src/shader/draw_quad.rs", TypeId::of::<common::DrawQuad>());
+}
+
+#[test]
+fn draw_color_wgsl() {
+    check_golden("draw/src/shader/draw_color.rs", TypeId::of::<common::DrawColor>());
+}
+
+#[test]
+fn draw_icon_wgsl() {
+    check_golden("draw/src/shader/draw_icon.rs", TypeId::of::<common::DrawIcon>());
+}
+
+#[test]
+fn draw_text_wgsl() {
+    check_golden("draw/src/shader/draw_text.rs", TypeId::of::<common::DrawText>());
+}
+
+#[test]
+fn draw_line_wgsl() {
+    check_golden("draw/src/shader/draw_line.rs", TypeId::of::<common::DrawLine>());
+}
+
+#[test]
+fn draw_trapezoid_wgsl() {
+    check_golden("draw/src/shader/draw_trapezoid.rs", TypeId::of::<common::DrawTrapezoidVector>());
+}
+
+#[test]
+fn draw_path_wgsl() {
+    check_golden("draw/src/shader/draw_path.rs", TypeId::of::<common::DrawPath>());
+}
+
+#[test]
+fn draw_mesh_wgsl() {
+    check_golden("draw/src/shader/draw_mesh.rs", TypeId::of::<common::DrawMesh3d>());
+}
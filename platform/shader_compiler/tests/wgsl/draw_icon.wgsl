@@ -0,0 +1,129 @@
+@group(0) @binding(0) var<uniform> draw_table: array<vec4<f32>, 1>;
+@group(0) @binding(1) var<uniform> pass_table: array<vec4<f32>, 13>;
+@group(0) @binding(2) var<uniform> user_table: array<vec4<f32>, 1>;
+@group(0) @binding(3) var<uniform> view_table: array<vec4<f32>, 4>;
+@group(0) @binding(4) var<uniform> const_table: array<vec4<f32>, 2>;
+@group(1) @binding(0) var ds_tex: texture_2d<f32>;
+@group(1) @binding(1) var ds_tex_sampler: sampler;
+var<private> ds_camera_projection: mat4x4<f32>;
+var<private> ds_camera_view: mat4x4<f32>;
+var<private> ds_camera_inv: mat4x4<f32>;
+var<private> ds_dpi_factor: f32;
+var<private> ds_dpi_dilate: f32;
+var<private> ds_time: f32;
+var<private> ds_view_transform: mat4x4<f32>;
+var<private> ds_draw_zbias: f32;
+var<private> ds_rect_pos: vec2<f32>;
+var<private> ds_rect_size: vec2<f32>;
+var<private> ds_draw_clip: vec4<f32>;
+var<private> ds_draw_depth: f32;
+var<private> ds_color: vec4<f32>;
+var<private> ds_icon_t1: vec2<f32>;
+var<private> ds_icon_t2: vec2<f32>;
+var<private> ds_geom_pos: vec2<f32>;
+var<private> ds_u_brightness: f32;
+var<private> ds_u_curve: f32;
+var<private> ds_u_multicolor: f32;
+var<private> ds_pos: vec2<f32>;
+var<private> ds_tex_coord1: vec2<f32>;
+var<private> ds_clipped: vec2<f32>;
+struct VertexInput {
+    @location(0) packed_geometry_0: vec2<f32>,
+    @location(1) packed_instance_0: vec4<f32>,
+    @location(2) packed_instance_1: vec4<f32>,
+    @location(3) packed_instance_2: vec4<f32>,
+    @location(4) packed_instance_3: vec4<f32>,
+    @location(5) packed_instance_4: f32,
+}
+struct Varyings {
+    @builtin(position) position: vec4<f32>,
+    @location(0) packed_varying_0: vec4<f32>,
+    @location(1) packed_varying_1: vec4<f32>,
+    @location(2) packed_varying_2: vec2<f32>,
+}
+fn fn_4_18_get_color() -> vec4<f32> {
+    return ds_color;
+}
+fn fn_4_19_pixel() -> vec4<f32> {
+    var var_dx_0: f32 = dpdx(vec2<f32>((ds_tex_coord1.x * const_table[0].y), const_table[0].z)).x;
+    var var_dp_0: f32 = (const_table[0].w / const_table[1].x);
+    if(ds_u_multicolor > const_table[1].y) {
+            var var_c_0: vec4<f32> = sample2d_rt(ds_tex, ds_tex_sampler, ds_tex_coord1.xy);
+        return (vec4<f32>((var_c_0.rgb * ds_u_brightness), var_c_0.a) * fn_4_18_get_color ().a);
+    }
+
+    var var_s_0: f32 = sample2d_rt(ds_tex, ds_tex_sampler, ds_tex_coord1.xy).x;
+    var_s_0 = pow(var_s_0, ds_u_curve);
+    var var_col_0: vec4<f32> = fn_4_18_get_color ();
+    return vec4<f32>((((var_s_0 * var_col_0.rgb) * ds_u_brightness) * var_col_0.a), (var_s_0 * var_col_0.a));
+}
+fn fn_4_16_clip_and_transform_vertex(param_rect_pos_0: vec2<f32>, param_rect_size_0: vec2<f32>) -> vec4<f32> {
+    var var_rect_pos_0: vec2<f32> = param_rect_pos_0;
+    var var_rect_size_0: vec2<f32> = param_rect_size_0;
+    {
+    var var_clipped_0: vec2<f32> = clamp(((ds_geom_pos * var_rect_size_0) + var_rect_pos_0), ds_draw_clip.xy, ds_draw_clip.zw);
+    ds_pos = ((var_clipped_0 - var_rect_pos_0) / var_rect_size_0);
+    ds_tex_coord1 = mix(ds_icon_t1.xy, ds_icon_t2.xy, ds_pos.xy);
+    return (ds_camera_projection * (ds_camera_view * (ds_view_transform * vec4<f32>(var_clipped_0.x, var_clipped_0.y, (ds_draw_depth + ds_draw_zbias), const_table[0].x))));
+}
+}
+fn fn_4_17_vertex() -> vec4<f32> {
+    return fn_4_16_clip_and_transform_vertex (ds_rect_pos, ds_rect_size);
+}
+fn sample2d_rt(tex: texture_2d<f32>, samp: sampler, pos: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(tex, samp, pos, 0.0);
+}
+@vertex
+fn vertex_main(vertex_input: VertexInput) -> Varyings {
+    ds_draw_zbias = draw_table[0].x;
+    ds_camera_projection = mat4x4<f32>(pass_table[0].x, pass_table[0].y, pass_table[0].z, pass_table[0].w, pass_table[1].x, pass_table[1].y, pass_table[1].z, pass_table[1].w, pass_table[2].x, pass_table[2].y, pass_table[2].z, pass_table[2].w, pass_table[3].x, pass_table[3].y, pass_table[3].z, pass_table[3].w);
+    ds_camera_view = mat4x4<f32>(pass_table[4].x, pass_table[4].y, pass_table[4].z, pass_table[4].w, pass_table[5].x, pass_table[5].y, pass_table[5].z, pass_table[5].w, pass_table[6].x, pass_table[6].y, pass_table[6].z, pass_table[6].w, pass_table[7].x, pass_table[7].y, pass_table[7].z, pass_table[7].w);
+    ds_camera_inv = mat4x4<f32>(pass_table[8].x, pass_table[8].y, pass_table[8].z, pass_table[8].w, pass_table[9].x, pass_table[9].y, pass_table[9].z, pass_table[9].w, pass_table[10].x, pass_table[10].y, pass_table[10].z, pass_table[10].w, pass_table[11].x, pass_table[11].y, pass_table[11].z, pass_table[11].w);
+    ds_dpi_factor = pass_table[12].x;
+    ds_dpi_dilate = pass_table[12].y;
+    ds_time = pass_table[12].z;
+    ds_u_brightness = user_table[0].x;
+    ds_u_curve = user_table[0].y;
+    ds_u_multicolor = user_table[0].z;
+    ds_view_transform = mat4x4<f32>(view_table[0].x, view_table[0].y, view_table[0].z, view_table[0].w, view_table[1].x, view_table[1].y, view_table[1].z, view_table[1].w, view_table[2].x, view_table[2].y, view_table[2].z, view_table[2].w, view_table[3].x, view_table[3].y, view_table[3].z, view_table[3].w);
+    ds_geom_pos = vertex_input.packed_geometry_0;
+    ds_rect_pos = vertex_input.packed_instance_0.xy;
+    ds_rect_size = vertex_input.packed_instance_0.zw;
+    ds_draw_clip = vertex_input.packed_instance_1;
+    ds_draw_depth = vertex_input.packed_instance_2.x;
+    ds_color.x = vertex_input.packed_instance_2.y;
+    ds_color.y = vertex_input.packed_instance_2.z;
+    ds_color.z = vertex_input.packed_instance_2.w;
+    ds_color.w = vertex_input.packed_instance_3.x;
+    ds_icon_t1 = vertex_input.packed_instance_3.yz;
+    ds_icon_t2.x = vertex_input.packed_instance_3.w;
+    ds_icon_t2.y = vertex_input.packed_instance_4;
+    var varyings: Varyings;
+    varyings.position = fn_4_17_vertex();
+    varyings.packed_varying_0 = ds_color;
+    varyings.packed_varying_1.x = ds_pos.x;
+    varyings.packed_varying_1.y = ds_pos.y;
+    varyings.packed_varying_1.z = ds_tex_coord1.x;
+    varyings.packed_varying_1.w = ds_tex_coord1.y;
+    varyings.packed_varying_2 = ds_clipped;
+    return varyings;
+}
+@fragment
+fn fragment_main(varyings: Varyings) -> @location(0) vec4<f32> {
+    ds_draw_zbias = draw_table[0].x;
+    ds_camera_projection = mat4x4<f32>(pass_table[0].x, pass_table[0].y, pass_table[0].z, pass_table[0].w, pass_table[1].x, pass_table[1].y, pass_table[1].z, pass_table[1].w, pass_table[2].x, pass_table[2].y, pass_table[2].z, pass_table[2].w, pass_table[3].x, pass_table[3].y, pass_table[3].z, pass_table[3].w);
+    ds_camera_view = mat4x4<f32>(pass_table[4].x, pass_table[4].y, pass_table[4].z, pass_table[4].w, pass_table[5].x, pass_table[5].y, pass_table[5].z, pass_table[5].w, pass_table[6].x, pass_table[6].y, pass_table[6].z, pass_table[6].w, pass_table[7].x, pass_table[7].y, pass_table[7].z, pass_table[7].w);
+    ds_camera_inv = mat4x4<f32>(pass_table[8].x, pass_table[8].y, pass_table[8].z, pass_table[8].w, pass_table[9].x, pass_table[9].y, pass_table[9].z, pass_table[9].w, pass_table[10].x, pass_table[10].y, pass_table[10].z, pass_table[10].w, pass_table[11].x, pass_table[11].y, pass_table[11].z, pass_table[11].w);
+    ds_dpi_factor = pass_table[12].x;
+    ds_dpi_dilate = pass_table[12].y;
+    ds_time = pass_table[12].z;
+    ds_u_brightness = user_table[0].x;
+    ds_u_curve = user_table[0].y;
+    ds_u_multicolor = user_table[0].z;
+    ds_view_transform = mat4x4<f32>(view_table[0].x, view_table[0].y, view_table[0].z, view_table[0].w, view_table[1].x, view_table[1].y, view_table[1].z, view_table[1].w, view_table[2].x, view_table[2].y, view_table[2].z, view_table[2].w, view_table[3].x, view_table[3].y, view_table[3].z, view_table[3].w);
+    ds_color = varyings.packed_varying_0;
+    ds_pos = varyings.packed_varying_1.xy;
+    ds_tex_coord1 = varyings.packed_varying_1.zw;
+    ds_clipped = varyings.packed_varying_2;
+    return fn_4_19_pixel();
+}
@@ -0,0 +1,129 @@
+@group(0) @binding(0) var<uniform> draw_table: array<vec4<f32>, 1>;
+@group(0) @binding(1) var<uniform> pass_table: array<vec4<f32>, 13>;
+@group(0) @binding(2) var<uniform> view_table: array<vec4<f32>, 4>;
+@group(0) @binding(3) var<uniform> const_table: array<vec4<f32>, 2>;
+var<private> ds_camera_projection: mat4x4<f32>;
+var<private> ds_camera_view: mat4x4<f32>;
+var<private> ds_camera_inv: mat4x4<f32>;
+var<private> ds_dpi_factor: f32;
+var<private> ds_dpi_dilate: f32;
+var<private> ds_time: f32;
+var<private> ds_view_transform: mat4x4<f32>;
+var<private> ds_draw_zbias: f32;
+var<private> ds_rect_pos: vec2<f32>;
+var<private> ds_rect_size: vec2<f32>;
+var<private> ds_draw_clip: vec4<f32>;
+var<private> ds_draw_depth: f32;
+var<private> ds_line_start: vec2<f32>;
+var<private> ds_line_end: vec2<f32>;
+var<private> ds_half_line_width: f32;
+var<private> ds_color: vec4<f32>;
+var<private> ds_geom_pos: vec2<f32>;
+var<private> ds_pos: vec2<f32>;
+struct VertexInput {
+    @location(0) packed_geometry_0: vec2<f32>,
+    @location(1) packed_instance_0: vec4<f32>,
+    @location(2) packed_instance_1: vec4<f32>,
+    @location(3) packed_instance_2: vec4<f32>,
+    @location(4) packed_instance_3: vec4<f32>,
+    @location(5) packed_instance_4: vec3<f32>,
+}
+struct Varyings {
+    @builtin(position) position: vec4<f32>,
+    @location(0) packed_varying_0: vec4<f32>,
+    @location(1) packed_varying_1: vec4<f32>,
+    @location(2) packed_varying_2: vec4<f32>,
+    @location(3) packed_varying_3: f32,
+}
+fn fn_6_13_stroke(param_side_0: f32, param_progress_0: f32) -> vec4<f32> {
+    var var_side_0: f32 = param_side_0;
+    var var_progress_0: f32 = param_progress_0;
+    {
+    return ds_color;
+}
+}
+fn fn_6_12_pixel() -> vec4<f32> {
+    var var_p_0: vec2<f32> = (ds_pos * ds_rect_size);
+    var var_b_0: vec2<f32> = ds_line_end;
+    var var_a_0: vec2<f32> = ds_line_start;
+    var var_ba_0: vec2<f32> = (var_b_0 - var_a_0);
+    var var_pa_0: vec2<f32> = (var_p_0 - var_a_0);
+    var var_h_0: f32 = clamp((dot(var_pa_0, var_ba_0) / dot(var_ba_0, var_ba_0)), const_table[0].y, const_table[0].z);
+    var var_dist_0: f32 = length((var_pa_0 - (var_h_0 * var_ba_0)));
+    var var_linemult_0: f32 = smoothstep((ds_half_line_width - const_table[0].w), ds_half_line_width, var_dist_0);
+    var var_C_0: vec4<f32> = fn_6_13_stroke (var_dist_0, var_h_0);
+    return vec4<f32>((var_C_0.xyz * (const_table[1].x - var_linemult_0)), ((const_table[1].y - var_linemult_0) * var_C_0.a));
+}
+fn fn_6_9_clip_and_transform_vertex(param_rect_pos_0: vec2<f32>, param_rect_size_0: vec2<f32>) -> vec4<f32> {
+    var var_rect_pos_0: vec2<f32> = param_rect_pos_0;
+    var var_rect_size_0: vec2<f32> = param_rect_size_0;
+    {
+    var var_clipped_0: vec2<f32> = clamp(((ds_geom_pos * var_rect_size_0) + var_rect_pos_0), ds_draw_clip.xy, ds_draw_clip.zw);
+    ds_pos = ((var_clipped_0 - var_rect_pos_0) / var_rect_size_0);
+    return (ds_camera_projection * (ds_camera_view * (ds_view_transform * vec4<f32>(var_clipped_0.x, var_clipped_0.y, (ds_draw_depth + ds_draw_zbias), const_table[0].x))));
+}
+}
+fn fn_6_11_vertex() -> vec4<f32> {
+    return fn_6_9_clip_and_transform_vertex (ds_rect_pos, ds_rect_size);
+}
+@vertex
+fn vertex_main(vertex_input: VertexInput) -> Varyings {
+    ds_draw_zbias = draw_table[0].x;
+    ds_camera_projection = mat4x4<f32>(pass_table[0].x, pass_table[0].y, pass_table[0].z, pass_table[0].w, pass_table[1].x, pass_table[1].y, pass_table[1].z, pass_table[1].w, pass_table[2].x, pass_table[2].y, pass_table[2].z, pass_table[2].w, pass_table[3].x, pass_table[3].y, pass_table[3].z, pass_table[3].w);
+    ds_camera_view = mat4x4<f32>(pass_table[4].x, pass_table[4].y, pass_table[4].z, pass_table[4].w, pass_table[5].x, pass_table[5].y, pass_table[5].z, pass_table[5].w, pass_table[6].x, pass_table[6].y, pass_table[6].z, pass_table[6].w, pass_table[7].x, pass_table[7].y, pass_table[7].z, pass_table[7].w);
+    ds_camera_inv = mat4x4<f32>(pass_table[8].x, pass_table[8].y, pass_table[8].z, pass_table[8].w, pass_table[9].x, pass_table[9].y, pass_table[9].z, pass_table[9].w, pass_table[10].x, pass_table[10].y, pass_table[10].z, pass_table[10].w, pass_table[11].x, pass_table[11].y, pass_table[11].z, pass_table[11].w);
+    ds_dpi_factor = pass_table[12].x;
+    ds_dpi_dilate = pass_table[12].y;
+    ds_time = pass_table[12].z;
+    ds_view_transform = mat4x4<f32>(view_table[0].x, view_table[0].y, view_table[0].z, view_table[0].w, view_table[1].x, view_table[1].y, view_table[1].z, view_table[1].w, view_table[2].x, view_table[2].y, view_table[2].z, view_table[2].w, view_table[3].x, view_table[3].y, view_table[3].z, view_table[3].w);
+    ds_geom_pos = vertex_input.packed_geometry_0;
+    ds_rect_pos = vertex_input.packed_instance_0.xy;
+    ds_rect_size = vertex_input.packed_instance_0.zw;
+    ds_draw_clip = vertex_input.packed_instance_1;
+    ds_draw_depth = vertex_input.packed_instance_2.x;
+    ds_line_start = vertex_input.packed_instance_2.zw;
+    ds_line_end = vertex_input.packed_instance_3.xy;
+    ds_half_line_width = vertex_input.packed_instance_3.z;
+    ds_color.x = vertex_input.packed_instance_3.w;
+    ds_color.y = vertex_input.packed_instance_4.x;
+    ds_color.z = vertex_input.packed_instance_4.y;
+    ds_color.w = vertex_input.packed_instance_4.z;
+    var varyings: Varyings;
+    varyings.position = fn_6_11_vertex();
+    varyings.packed_varying_0.x = ds_rect_size.x;
+    varyings.packed_varying_0.y = ds_rect_size.y;
+    varyings.packed_varying_0.z = ds_line_start.x;
+    varyings.packed_varying_0.w = ds_line_start.y;
+    varyings.packed_varying_1.x = ds_line_end.x;
+    varyings.packed_varying_1.y = ds_line_end.y;
+    varyings.packed_varying_1.z = ds_half_line_width;
+    varyings.packed_varying_1.w = ds_color.x;
+    varyings.packed_varying_2.x = ds_color.y;
+    varyings.packed_varying_2.y = ds_color.z;
+    varyings.packed_varying_2.z = ds_color.w;
+    varyings.packed_varying_2.w = ds_pos.x;
+    varyings.packed_varying_3 = ds_pos.y;
+    return varyings;
+}
+@fragment
+fn fragment_main(varyings: Varyings) -> @location(0) vec4<f32> {
+    ds_draw_zbias = draw_table[0].x;
+    ds_camera_projection = mat4x4<f32>(pass_table[0].x, pass_table[0].y, pass_table[0].z, pass_table[0].w, pass_table[1].x, pass_table[1].y, pass_table[1].z, pass_table[1].w, pass_table[2].x, pass_table[2].y, pass_table[2].z, pass_table[2].w, pass_table[3].x, pass_table[3].y, pass_table[3].z, pass_table[3].w);
+    ds_camera_view = mat4x4<f32>(pass_table[4].x, pass_table[4].y, pass_table[4].z, pass_table[4].w, pass_table[5].x, pass_table[5].y, pass_table[5].z, pass_table[5].w, pass_table[6].x, pass_table[6].y, pass_table[6].z, pass_table[6].w, pass_table[7].x, pass_table[7].y, pass_table[7].z, pass_table[7].w);
+    ds_camera_inv = mat4x4<f32>(pass_table[8].x, pass_table[8].y, pass_table[8].z, pass_table[8].w, pass_table[9].x, pass_table[9].y, pass_table[9].z, pass_table[9].w, pass_table[10].x, pass_table[10].y, pass_table[10].z, pass_table[10].w, pass_table[11].x, pass_table[11].y, pass_table[11].z, pass_table[11].w);
+    ds_dpi_factor = pass_table[12].x;
+    ds_dpi_dilate = pass_table[12].y;
+    ds_time = pass_table[12].z;
+    ds_view_transform = mat4x4<f32>(view_table[0].x, view_table[0].y, view_table[0].z, view_table[0].w, view_table[1].x, view_table[1].y, view_table[1].z, view_table[1].w, view_table[2].x, view_table[2].y, view_table[2].z, view_table[2].w, view_table[3].x, view_table[3].y, view_table[3].z, view_table[3].w);
+    ds_rect_size = varyings.packed_varying_0.xy;
+    ds_line_start = varyings.packed_varying_0.zw;
+    ds_line_end = varyings.packed_varying_1.xy;
+    ds_half_line_width = varyings.packed_varying_1.z;
+    ds_color.x = varyings.packed_varying_1.w;
+    ds_color.y = varyings.packed_varying_2.x;
+    ds_color.z = varyings.packed_varying_2.y;
+    ds_color.w = varyings.packed_varying_2.z;
+    ds_pos.x = varyings.packed_varying_2.w;
+    ds_pos.y = varyings.packed_varying_3;
+    return fn_6_12_pixel();
+}
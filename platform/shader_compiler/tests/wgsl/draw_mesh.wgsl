@@ -0,0 +1,169 @@
+@group(0) @binding(0) var<uniform> draw_table: array<vec4<f32>, 1>;
+@group(0) @binding(1) var<uniform> pass_table: array<vec4<f32>, 13>;
+@group(0) @binding(2) var<uniform> view_table: array<vec4<f32>, 4>;
+@group(0) @binding(3) var<uniform> const_table: array<vec4<f32>, 6>;
+@group(1) @binding(0) var ds_base_color_texture: texture_2d<f32>;
+@group(1) @binding(1) var ds_base_color_texture_sampler: sampler;
+var<private> ds_camera_projection: mat4x4<f32>;
+var<private> ds_camera_view: mat4x4<f32>;
+var<private> ds_camera_inv: mat4x4<f32>;
+var<private> ds_dpi_factor: f32;
+var<private> ds_dpi_dilate: f32;
+var<private> ds_time: f32;
+var<private> ds_view_transform: mat4x4<f32>;
+var<private> ds_draw_zbias: f32;
+var<private> ds_model_col0: vec4<f32>;
+var<private> ds_model_col1: vec4<f32>;
+var<private> ds_model_col2: vec4<f32>;
+var<private> ds_model_col3: vec4<f32>;
+var<private> ds_base_color: vec4<f32>;
+var<private> ds_metallic: f32;
+var<private> ds_roughness: f32;
+var<private> ds_unlit: f32;
+var<private> ds_ambient: f32;
+var<private> ds_light_dir: vec3<f32>;
+var<private> ds_geom_pos: vec3<f32>;
+var<private> ds_99b3cae7d88b5bea: f32;
+var<private> ds_geom_normal: vec3<f32>;
+var<private> ds_geom_uv: vec2<f32>;
+var<private> ds_world_pos: vec3<f32>;
+var<private> ds_world_normal: vec3<f32>;
+var<private> ds_uv: vec2<f32>;
+struct VertexInput {
+    @location(0) packed_geometry_0: vec4<f32>,
+    @location(1) packed_geometry_1: vec4<f32>,
+    @location(2) packed_geometry_2: f32,
+    @location(3) packed_instance_0: vec4<f32>,
+    @location(4) packed_instance_1: vec4<f32>,
+    @location(5) packed_instance_2: vec4<f32>,
+    @location(6) packed_instance_3: vec4<f32>,
+    @location(7) packed_instance_4: vec4<f32>,
+    @location(8) packed_instance_5: vec4<f32>,
+    @location(9) packed_instance_6: vec3<f32>,
+}
+struct Varyings {
+    @builtin(position) position: vec4<f32>,
+    @location(0) packed_varying_0: vec4<f32>,
+    @location(1) packed_varying_1: vec4<f32>,
+    @location(2) packed_varying_2: vec4<f32>,
+    @location(3) packed_varying_3: vec4<f32>,
+    @location(4) packed_varying_4: vec3<f32>,
+}
+fn fn_9_14_shade(param_base_0: vec4<f32>) -> vec4<f32> {
+    var var_base_0: vec4<f32> = param_base_0;
+    {
+    var var_eye_0: vec3<f32> = (ds_camera_inv * vec4<f32>(const_table[0].w, const_table[1].x, const_table[1].y, const_table[1].z)).xyz;
+    var var_v_0: vec3<f32> = normalize((var_eye_0 - ds_world_pos));
+    var var_n_0: vec3<f32> = normalize(ds_world_normal);
+    if(dot(var_n_0, var_v_0) < const_table[1].w) {
+            var_n_0 = -var_n_0;
+    }
+
+    var var_l_0: vec3<f32> = normalize(ds_light_dir);
+    var var_h_0: vec3<f32> = normalize((var_l_0 + var_v_0));
+    var var_n_dot_l_0: f32 = max(dot(var_n_0, var_l_0), const_table[2].x);
+    var var_r_0: f32 = max(ds_roughness, const_table[2].y);
+    var var_shininess_0: f32 = ((const_table[2].z / (((var_r_0 * var_r_0) * var_r_0) * var_r_0)) - const_table[2].w);
+    var var_specular_0: f32 = ((pow(max(dot(var_n_0, var_h_0), const_table[3].x), var_shininess_0) * (var_shininess_0 + const_table[3].y)) / const_table[3].z);
+    var var_f0_0: vec3<f32> = mix(vec3<f32>(const_table[3].w), var_base_0.rgb, ds_metallic);
+    var var_fresnel_0: vec3<f32> = (var_f0_0 + ((vec3<f32>(const_table[4].x) - var_f0_0) * pow((const_table[4].y - max(dot(var_h_0, var_v_0), const_table[4].z)), const_table[4].w)));
+    var var_diffuse_0: vec3<f32> = (var_base_0.rgb * (const_table[5].x - ds_metallic));
+    var var_rgb_0: vec3<f32> = (((var_diffuse_0 * (ds_ambient + var_n_dot_l_0)) + ((var_base_0.rgb * ds_metallic) * ds_ambient)) + ((var_fresnel_0 * var_specular_0) * var_n_dot_l_0));
+    return vec4<f32>((var_rgb_0 * var_base_0.a), var_base_0.a);
+}
+}
+fn fn_9_13_get_base_color() -> vec4<f32> {
+    return (ds_base_color * sample2d(ds_base_color_texture, ds_base_color_texture_sampler, ds_uv));
+}
+fn fn_9_15_pixel() -> vec4<f32> {
+    var var_base_0: vec4<f32> = fn_9_13_get_base_color ();
+    if(ds_unlit > const_table[0].z) {
+            return vec4<f32>((var_base_0.rgb * var_base_0.a), var_base_0.a);
+    }
+
+    return fn_9_14_shade (var_base_0);
+}
+fn fn_9_11_model_matrix() -> mat4x4<f32> {
+    return mat4x4<f32>(ds_model_col0, ds_model_col1, ds_model_col2, ds_model_col3);
+}
+fn fn_9_12_vertex() -> vec4<f32> {
+    var var_model_0: mat4x4<f32> = fn_9_11_model_matrix ();
+    var var_world_0: vec4<f32> = (var_model_0 * vec4<f32>(ds_geom_pos, const_table[0].x));
+    ds_world_pos = var_world_0.xyz;
+    ds_world_normal = (var_model_0 * vec4<f32>(ds_geom_normal, const_table[0].y)).xyz;
+    ds_uv = ds_geom_uv;
+    return (ds_camera_projection * (ds_camera_view * (ds_view_transform * var_world_0)));
+}
+fn sample2d(tex: texture_2d<f32>, samp: sampler, pos: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(tex, samp, pos, 0.0);
+}
+@vertex
+fn vertex_main(vertex_input: VertexInput) -> Varyings {
+    ds_draw_zbias = draw_table[0].x;
+    ds_camera_projection = mat4x4<f32>(pass_table[0].x, pass_table[0].y, pass_table[0].z, pass_table[0].w, pass_table[1].x, pass_table[1].y, pass_table[1].z, pass_table[1].w, pass_table[2].x, pass_table[2].y, pass_table[2].z, pass_table[2].w, pass_table[3].x, pass_table[3].y, pass_table[3].z, pass_table[3].w);
+    ds_camera_view = mat4x4<f32>(pass_table[4].x, pass_table[4].y, pass_table[4].z, pass_table[4].w, pass_table[5].x, pass_table[5].y, pass_table[5].z, pass_table[5].w, pass_table[6].x, pass_table[6].y, pass_table[6].z, pass_table[6].w, pass_table[7].x, pass_table[7].y, pass_table[7].z, pass_table[7].w);
+    ds_camera_inv = mat4x4<f32>(pass_table[8].x, pass_table[8].y, pass_table[8].z, pass_table[8].w, pass_table[9].x, pass_table[9].y, pass_table[9].z, pass_table[9].w, pass_table[10].x, pass_table[10].y, pass_table[10].z, pass_table[10].w, pass_table[11].x, pass_table[11].y, pass_table[11].z, pass_table[11].w);
+    ds_dpi_factor = pass_table[12].x;
+    ds_dpi_dilate = pass_table[12].y;
+    ds_time = pass_table[12].z;
+    ds_view_transform = mat4x4<f32>(view_table[0].x, view_table[0].y, view_table[0].z, view_table[0].w, view_table[1].x, view_table[1].y, view_table[1].z, view_table[1].w, view_table[2].x, view_table[2].y, view_table[2].z, view_table[2].w, view_table[3].x, view_table[3].y, view_table[3].z, view_table[3].w);
+    ds_geom_pos = vertex_input.packed_geometry_0.xyz;
+    ds_99b3cae7d88b5bea = vertex_input.packed_geometry_0.w;
+    ds_geom_normal = vertex_input.packed_geometry_1.xyz;
+    ds_geom_uv.x = vertex_input.packed_geometry_1.w;
+    ds_geom_uv.y = vertex_input.packed_geometry_2;
+    ds_model_col0 = vertex_input.packed_instance_0;
+    ds_model_col1 = vertex_input.packed_instance_1;
+    ds_model_col2 = vertex_input.packed_instance_2;
+    ds_model_col3 = vertex_input.packed_instance_3;
+    ds_base_color = vertex_input.packed_instance_4;
+    ds_metallic = vertex_input.packed_instance_5.x;
+    ds_roughness = vertex_input.packed_instance_5.y;
+    ds_unlit = vertex_input.packed_instance_5.z;
+    ds_ambient = vertex_input.packed_instance_5.w;
+    ds_light_dir = vertex_input.packed_instance_6;
+    var varyings: Varyings;
+    varyings.position = fn_9_12_vertex();
+    varyings.packed_varying_0 = ds_base_color;
+    varyings.packed_varying_1.x = ds_metallic;
+    varyings.packed_varying_1.y = ds_roughness;
+    varyings.packed_varying_1.z = ds_unlit;
+    varyings.packed_varying_1.w = ds_ambient;
+    varyings.packed_varying_2.x = ds_light_dir.x;
+    varyings.packed_varying_2.y = ds_light_dir.y;
+    varyings.packed_varying_2.z = ds_light_dir.z;
+    varyings.packed_varying_2.w = ds_world_pos.x;
+    varyings.packed_varying_3.x = ds_world_pos.y;
+    varyings.packed_varying_3.y = ds_world_pos.z;
+    varyings.packed_varying_3.z = ds_world_normal.x;
+    varyings.packed_varying_3.w = ds_world_normal.y;
+    varyings.packed_varying_4.x = ds_world_normal.z;
+    varyings.packed_varying_4.y = ds_uv.x;
+    varyings.packed_varying_4.z = ds_uv.y;
+    return varyings;
+}
+@fragment
+fn fragment_main(varyings: Varyings) -> @location(0) vec4<f32> {
+    ds_draw_zbias = draw_table[0].x;
+    ds_camera_projection = mat4x4<f32>(pass_table[0].x, pass_table[0].y, pass_table[0].z, pass_table[0].w, pass_table[1].x, pass_table[1].y, pass_table[1].z, pass_table[1].w, pass_table[2].x, pass_table[2].y, pass_table[2].z, pass_table[2].w, pass_table[3].x, pass_table[3].y, pass_table[3].z, pass_table[3].w);
+    ds_camera_view = mat4x4<f32>(pass_table[4].x, pass_table[4].y, pass_table[4].z, pass_table[4].w, pass_table[5].x, pass_table[5].y, pass_table[5].z, pass_table[5].w, pass_table[6].x, pass_table[6].y, pass_table[6].z, pass_table[6].w, pass_table[7].x, pass_table[7].y, pass_table[7].z, pass_table[7].w);
+    ds_camera_inv = mat4x4<f32>(pass_table[8].x, pass_table[8].y, pass_table[8].z, pass_table[8].w, pass_table[9].x, pass_table[9].y, pass_table[9].z, pass_table[9].w, pass_table[10].x, pass_table[10].y, pass_table[10].z, pass_table[10].w, pass_table[11].x, pass_table[11].y, pass_table[11].z, pass_table[11].w);
+    ds_dpi_factor = pass_table[12].x;
+    ds_dpi_dilate = pass_table[12].y;
+    ds_time = pass_table[12].z;
+    ds_view_transform = mat4x4<f32>(view_table[0].x, view_table[0].y, view_table[0].z, view_table[0].w, view_table[1].x, view_table[1].y, view_table[1].z, view_table[1].w, view_table[2].x, view_table[2].y, view_table[2].z, view_table[2].w, view_table[3].x, view_table[3].y, view_table[3].z, view_table[3].w);
+    ds_base_color = varyings.packed_varying_0;
+    ds_metallic = varyings.packed_varying_1.x;
+    ds_roughness = varyings.packed_varying_1.y;
+    ds_unlit = varyings.packed_varying_1.z;
+    ds_ambient = varyings.packed_varying_1.w;
+    ds_light_dir = varyings.packed_varying_2.xyz;
+    ds_world_pos.x = varyings.packed_varying_2.w;
+    ds_world_pos.y = varyings.packed_varying_3.x;
+    ds_world_pos.z = varyings.packed_varying_3.y;
+    ds_world_normal.x = varyings.packed_varying_3.z;
+    ds_world_normal.y = varyings.packed_varying_3.w;
+    ds_world_normal.z = varyings.packed_varying_4.x;
+    ds_uv = varyings.packed_varying_4.yz;
+    return fn_9_15_pixel();
+}
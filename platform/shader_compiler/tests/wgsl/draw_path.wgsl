@@ -0,0 +1,235 @@
+@group(0) @binding(0) var<uniform> draw_table: array<vec4<f32>, 1>;
+@group(0) @binding(1) var<uniform> pass_table: array<vec4<f32>, 13>;
+@group(0) @binding(2) var<uniform> view_table: array<vec4<f32>, 4>;
+@group(0) @binding(3) var<uniform> const_table: array<vec4<f32>, 5>;
+var<private> ds_camera_projection: mat4x4<f32>;
+var<private> ds_camera_view: mat4x4<f32>;
+var<private> ds_camera_inv: mat4x4<f32>;
+var<private> ds_dpi_factor: f32;
+var<private> ds_dpi_dilate: f32;
+var<private> ds_time: f32;
+var<private> ds_view_transform: mat4x4<f32>;
+var<private> ds_draw_zbias: f32;
+var<private> ds_rect_pos: vec2<f32>;
+var<private> ds_rect_size: vec2<f32>;
+var<private> ds_draw_clip: vec4<f32>;
+var<private> ds_draw_depth: f32;
+var<private> ds_color: vec4<f32>;
+var<private> ds_gradient_color: vec4<f32>;
+var<private> ds_gradient: vec4<f32>;
+var<private> ds_gradient_kind: f32;
+var<private> ds_a_xs: vec2<f32>;
+var<private> ds_a_ys: vec4<f32>;
+var<private> ds_geom_pos: vec2<f32>;
+var<private> ds_pos: vec2<f32>;
+var<private> ds_v_p0: vec2<f32>;
+var<private> ds_v_p1: vec2<f32>;
+var<private> ds_v_p2: vec2<f32>;
+var<private> ds_v_p3: vec2<f32>;
+var<private> ds_v_pixel: vec2<f32>;
+var<private> ds_v_pos: vec2<f32>;
+struct VertexInput {
+    @location(0) packed_geometry_0: vec2<f32>,
+    @location(1) packed_instance_0: vec4<f32>,
+    @location(2) packed_instance_1: vec4<f32>,
+    @location(3) packed_instance_2: vec4<f32>,
+    @location(4) packed_instance_3: vec4<f32>,
+    @location(5) packed_instance_4: vec4<f32>,
+    @location(6) packed_instance_5: vec4<f32>,
+    @location(7) packed_instance_6: vec4<f32>,
+    @location(8) packed_instance_7: f32,
+}
+struct Varyings {
+    @builtin(position) position: vec4<f32>,
+    @location(0) packed_varying_0: vec4<f32>,
+    @location(1) packed_varying_1: vec4<f32>,
+    @location(2) packed_varying_2: vec4<f32>,
+    @location(3) packed_varying_3: vec4<f32>,
+    @location(4) packed_varying_4: vec4<f32>,
+    @location(5) packed_varying_5: vec4<f32>,
+    @location(6) packed_varying_6: vec3<f32>,
+}
+fn fn_8_23_get_color(param_pos_0: vec2<f32>) -> vec4<f32> {
+    var var_pos_0: vec2<f32> = param_pos_0;
+    {
+    if(ds_gradient_kind > const_table[2].x) {
+            var var_t_0: f32 = (length((var_pos_0 - ds_gradient.xy)) / max(ds_gradient.z, const_table[2].y));
+        return mix(ds_color, ds_gradient_color, clamp(var_t_0, const_table[2].z, const_table[2].w));
+    }
+
+    if(ds_gradient_kind > const_table[3].x) {
+            var var_d_0: vec2<f32> = (ds_gradient.zw - ds_gradient.xy);
+        var var_t_0: f32 = (dot((var_pos_0 - ds_gradient.xy), var_d_0) / max(dot(var_d_0, var_d_0), const_table[3].y));
+        return mix(ds_color, ds_gradient_color, clamp(var_t_0, const_table[3].z, const_table[3].w));
+    }
+
+    return ds_color;
+}
+}
+fn fn_8_20_intersect_line_segment_with_horizontal_line(param_p0_0: vec2<f32>, param_p1_0: vec2<f32>, param_y_0: f32) -> vec2<f32> {
+    var var_p0_0: vec2<f32> = param_p0_0;
+    var var_p1_0: vec2<f32> = param_p1_0;
+    var var_y_0: f32 = param_y_0;
+    {
+    return vec2<f32>(mix(var_p0_0.x, var_p1_0.x, ((var_y_0 - var_p0_0.y) / (var_p1_0.y - var_p0_0.y))), var_y_0);
+}
+}
+fn fn_8_19_intersect_line_segment_with_vertical_line(param_p0_0: vec2<f32>, param_p1_0: vec2<f32>, param_x_0: f32) -> vec2<f32> {
+    var var_p0_0: vec2<f32> = param_p0_0;
+    var var_p1_0: vec2<f32> = param_p1_0;
+    var var_x_0: f32 = param_x_0;
+    {
+    return vec2<f32>(var_x_0, mix(var_p0_0.y, var_p1_0.y, ((var_x_0 - var_p0_0.x) / (var_p1_0.x - var_p0_0.x))));
+}
+}
+fn fn_8_21_compute_clamped_right_trapezoid_area(param_p0_0: vec2<f32>, param_p1_0: vec2<f32>, param_p_min_0: vec2<f32>, param_p_max_0: vec2<f32>) -> f32 {
+    var var_p0_0: vec2<f32> = param_p0_0;
+    var var_p1_0: vec2<f32> = param_p1_0;
+    var var_p_min_0: vec2<f32> = param_p_min_0;
+    var var_p_max_0: vec2<f32> = param_p_max_0;
+    {
+    var var_x0_0: f32 = clamp(var_p0_0.x, var_p_min_0.x, var_p_max_0.x);
+    var var_x1_0: f32 = clamp(var_p1_0.x, var_p_min_0.x, var_p_max_0.x);
+    if((var_p0_0.x < var_p_min_0.x) && (var_p_min_0.x < var_p1_0.x)) {
+            var_p0_0 = fn_8_19_intersect_line_segment_with_vertical_line (var_p0_0, var_p1_0, var_p_min_0.x);
+    }
+
+    if((var_p0_0.x < var_p_max_0.x) && (var_p_max_0.x < var_p1_0.x)) {
+            var_p1_0 = fn_8_19_intersect_line_segment_with_vertical_line (var_p0_0, var_p1_0, var_p_max_0.x);
+    }
+
+    if((var_p0_0.y < var_p_min_0.y) && (var_p_min_0.y < var_p1_0.y)) {
+            var_p0_0 = fn_8_20_intersect_line_segment_with_horizontal_line (var_p0_0, var_p1_0, var_p_min_0.y);
+    }
+
+    if((var_p1_0.y < var_p_min_0.y) && (var_p_min_0.y < var_p0_0.y)) {
+            var_p1_0 = fn_8_20_intersect_line_segment_with_horizontal_line (var_p1_0, var_p0_0, var_p_min_0.y);
+    }
+
+    if((var_p0_0.y < var_p_max_0.y) && (var_p_max_0.y < var_p1_0.y)) {
+            var_p1_0 = fn_8_20_intersect_line_segment_with_horizontal_line (var_p0_0, var_p1_0, var_p_max_0.y);
+    }
+
+    if((var_p1_0.y < var_p_max_0.y) && (var_p_max_0.y < var_p0_0.y)) {
+            var_p0_0 = fn_8_20_intersect_line_segment_with_horizontal_line (var_p1_0, var_p0_0, var_p_max_0.y);
+    }
+
+    var_p0_0 = clamp(var_p0_0, var_p_min_0, var_p_max_0);
+    var_p1_0 = clamp(var_p1_0, var_p_min_0, var_p_max_0);
+    var var_h0_0: f32 = (var_p_max_0.y - var_p0_0.y);
+    var var_h1_0: f32 = (var_p_max_0.y - var_p1_0.y);
+    var var_a0_0: f32 = ((var_p0_0.x - var_x0_0) * var_h0_0);
+    var var_a1_0: f32 = (((var_p1_0.x - var_p0_0.x) * (var_h0_0 + var_h1_0)) * const_table[1].w);
+    var var_a2_0: f32 = ((var_x1_0 - var_p1_0.x) * var_h1_0);
+    return ((var_a0_0 + var_a1_0) + var_a2_0);
+}
+}
+fn fn_8_22_compute_clamped_trapezoid_area(param_p_min_0: vec2<f32>, param_p_max_0: vec2<f32>) -> f32 {
+    var var_p_min_0: vec2<f32> = param_p_min_0;
+    var var_p_max_0: vec2<f32> = param_p_max_0;
+    {
+    var var_a0_0: f32 = fn_8_21_compute_clamped_right_trapezoid_area (ds_v_p0, ds_v_p1, var_p_min_0, var_p_max_0);
+    var var_a1_0: f32 = fn_8_21_compute_clamped_right_trapezoid_area (ds_v_p2, ds_v_p3, var_p_min_0, var_p_max_0);
+    return (var_a0_0 - var_a1_0);
+}
+}
+fn fn_8_12_pixel() -> vec4<f32> {
+    var var_coverage_0: f32 = clamp(abs(fn_8_22_compute_clamped_trapezoid_area ((ds_v_pixel - const_table[0].w), (ds_v_pixel + const_table[1].x))), const_table[1].y, const_table[1].z);
+    var var_color_0: vec4<f32> = fn_8_23_get_color (ds_v_pos);
+    return (vec4<f32>((var_color_0.rgb * var_color_0.a), var_color_0.a) * var_coverage_0);
+}
+fn fn_8_11_vertex() -> vec4<f32> {
+    var var_pos_min_0: vec2<f32> = vec2<f32>(ds_a_xs.x, min(ds_a_ys.x, ds_a_ys.y));
+    var var_pos_max_0: vec2<f32> = vec2<f32>(ds_a_xs.y, max(ds_a_ys.z, ds_a_ys.w));
+    var var_pixel_0: vec2<f32> = mix((var_pos_min_0 - const_table[0].x), (var_pos_max_0 + const_table[0].y), ds_geom_pos);
+    var var_clipped_0: vec2<f32> = clamp((ds_rect_pos + (var_pixel_0 / ds_dpi_factor)), ds_draw_clip.xy, ds_draw_clip.zw);
+    ds_v_p0 = vec2<f32>(ds_a_xs.x, ds_a_ys.x);
+    ds_v_p1 = vec2<f32>(ds_a_xs.y, ds_a_ys.y);
+    ds_v_p2 = vec2<f32>(ds_a_xs.x, ds_a_ys.z);
+    ds_v_p3 = vec2<f32>(ds_a_xs.y, ds_a_ys.w);
+    ds_v_pixel = ((var_clipped_0 - ds_rect_pos) * ds_dpi_factor);
+    ds_v_pos = (var_clipped_0 - ds_rect_pos);
+    return (ds_camera_projection * (ds_camera_view * (ds_view_transform * vec4<f32>(var_clipped_0.x, var_clipped_0.y, (ds_draw_depth + ds_draw_zbias), const_table[0].z))));
+}
+@vertex
+fn vertex_main(vertex_input: VertexInput) -> Varyings {
+    ds_draw_zbias = draw_table[0].x;
+    ds_camera_projection = mat4x4<f32>(pass_table[0].x, pass_table[0].y, pass_table[0].z, pass_table[0].w, pass_table[1].x, pass_table[1].y, pass_table[1].z, pass_table[1].w, pass_table[2].x, pass_table[2].y, pass_table[2].z, pass_table[2].w, pass_table[3].x, pass_table[3].y, pass_table[3].z, pass_table[3].w);
+    ds_camera_view = mat4x4<f32>(pass_table[4].x, pass_table[4].y, pass_table[4].z, pass_table[4].w, pass_table[5].x, pass_table[5].y, pass_table[5].z, pass_table[5].w, pass_table[6].x, pass_table[6].y, pass_table[6].z, pass_table[6].w, pass_table[7].x, pass_table[7].y, pass_table[7].z, pass_table[7].w);
+    ds_camera_inv = mat4x4<f32>(pass_table[8].x, pass_table[8].y, pass_table[8].z, pass_table[8].w, pass_table[9].x, pass_table[9].y, pass_table[9].z, pass_table[9].w, pass_table[10].x, pass_table[10].y, pass_table[10].z, pass_table[10].w, pass_table[11].x, pass_table[11].y, pass_table[11].z, pass_table[11].w);
+    ds_dpi_factor = pass_table[12].x;
+    ds_dpi_dilate = pass_table[12].y;
+    ds_time = pass_table[12].z;
+    ds_view_transform = mat4x4<f32>(view_table[0].x, view_table[0].y, view_table[0].z, view_table[0].w, view_table[1].x, view_table[1].y, view_table[1].z, view_table[1].w, view_table[2].x, view_table[2].y, view_table[2].z, view_table[2].w, view_table[3].x, view_table[3].y, view_table[3].z, view_table[3].w);
+    ds_geom_pos = vertex_input.packed_geometry_0;
+    ds_rect_pos = vertex_input.packed_instance_0.xy;
+    ds_rect_size = vertex_input.packed_instance_0.zw;
+    ds_draw_clip = vertex_input.packed_instance_1;
+    ds_draw_depth = vertex_input.packed_instance_2.x;
+    ds_color.x = vertex_input.packed_instance_2.z;
+    ds_color.y = vertex_input.packed_instance_2.w;
+    ds_color.z = vertex_input.packed_instance_3.x;
+    ds_color.w = vertex_input.packed_instance_3.y;
+    ds_gradient_color.x = vertex_input.packed_instance_3.z;
+    ds_gradient_color.y = vertex_input.packed_instance_3.w;
+    ds_gradient_color.z = vertex_input.packed_instance_4.x;
+    ds_gradient_color.w = vertex_input.packed_instance_4.y;
+    ds_gradient.x = vertex_input.packed_instance_4.z;
+    ds_gradient.y = vertex_input.packed_instance_4.w;
+    ds_gradient.z = vertex_input.packed_instance_5.x;
+    ds_gradient.w = vertex_input.packed_instance_5.y;
+    ds_gradient_kind = vertex_input.packed_instance_5.z;
+    ds_a_xs.x = vertex_input.packed_instance_5.w;
+    ds_a_xs.y = vertex_input.packed_instance_6.x;
+    ds_a_ys.x = vertex_input.packed_instance_6.y;
+    ds_a_ys.y = vertex_input.packed_instance_6.z;
+    ds_a_ys.z = vertex_input.packed_instance_6.w;
+    ds_a_ys.w = vertex_input.packed_instance_7;
+    var varyings: Varyings;
+    varyings.position = fn_8_11_vertex();
+    varyings.packed_varying_0 = ds_color;
+    varyings.packed_varying_1 = ds_gradient_color;
+    varyings.packed_varying_2 = ds_gradient;
+    varyings.packed_varying_3.x = ds_gradient_kind;
+    varyings.packed_varying_3.y = ds_pos.x;
+    varyings.packed_varying_3.z = ds_pos.y;
+    varyings.packed_varying_3.w = ds_v_p0.x;
+    varyings.packed_varying_4.x = ds_v_p0.y;
+    varyings.packed_varying_4.y = ds_v_p1.x;
+    varyings.packed_varying_4.z = ds_v_p1.y;
+    varyings.packed_varying_4.w = ds_v_p2.x;
+    varyings.packed_varying_5.x = ds_v_p2.y;
+    varyings.packed_varying_5.y = ds_v_p3.x;
+    varyings.packed_varying_5.z = ds_v_p3.y;
+    varyings.packed_varying_5.w = ds_v_pixel.x;
+    varyings.packed_varying_6.x = ds_v_pixel.y;
+    varyings.packed_varying_6.y = ds_v_pos.x;
+    varyings.packed_varying_6.z = ds_v_pos.y;
+    return varyings;
+}
+@fragment
+fn fragment_main(varyings: Varyings) -> @location(0) vec4<f32> {
+    ds_draw_zbias = draw_table[0].x;
+    ds_camera_projection = mat4x4<f32>(pass_table[0].x, pass_table[0].y, pass_table[0].z, pass_table[0].w, pass_table[1].x, pass_table[1].y, pass_table[1].z, pass_table[1].w, pass_table[2].x, pass_table[2].y, pass_table[2].z, pass_table[2].w, pass_table[3].x, pass_table[3].y, pass_table[3].z, pass_table[3].w);
+    ds_camera_view = mat4x4<f32>(pass_table[4].x, pass_table[4].y, pass_table[4].z, pass_table[4].w, pass_table[5].x, pass_table[5].y, pass_table[5].z, pass_table[5].w, pass_table[6].x, pass_table[6].y, pass_table[6].z, pass_table[6].w, pass_table[7].x, pass_table[7].y, pass_table[7].z, pass_table[7].w);
+    ds_camera_inv = mat4x4<f32>(pass_table[8].x, pass_table[8].y, pass_table[8].z, pass_table[8].w, pass_table[9].x, pass_table[9].y, pass_table[9].z, pass_table[9].w, pass_table[10].x, pass_table[10].y, pass_table[10].z, pass_table[10].w, pass_table[11].x, pass_table[11].y, pass_table[11].z, pass_table[11].w);
+    ds_dpi_factor = pass_table[12].x;
+    ds_dpi_dilate = pass_table[12].y;
+    ds_time = pass_table[12].z;
+    ds_view_transform = mat4x4<f32>(view_table[0].x, view_table[0].y, view_table[0].z, view_table[0].w, view_table[1].x, view_table[1].y, view_table[1].z, view_table[1].w, view_table[2].x, view_table[2].y, view_table[2].z, view_table[2].w, view_table[3].x, view_table[3].y, view_table[3].z, view_table[3].w);
+    ds_color = varyings.packed_varying_0;
+    ds_gradient_color = varyings.packed_varying_1;
+    ds_gradient = varyings.packed_varying_2;
+    ds_gradient_kind = varyings.packed_varying_3.x;
+    ds_pos = varyings.packed_varying_3.yz;
+    ds_v_p0.x = varyings.packed_varying_3.w;
+    ds_v_p0.y = varyings.packed_varying_4.x;
+    ds_v_p1 = varyings.packed_varying_4.yz;
+    ds_v_p2.x = varyings.packed_varying_4.w;
+    ds_v_p2.y = varyings.packed_varying_5.x;
+    ds_v_p3 = varyings.packed_varying_5.yz;
+    ds_v_pixel.x = varyings.packed_varying_5.w;
+    ds_v_pixel.y = varyings.packed_varying_6.x;
+    ds_v_pos = varyings.packed_varying_6.yz;
+    return fn_8_12_pixel();
+}
@@ -0,0 +1,293 @@
+@group(0) @binding(0) var<uniform> draw_table: array<vec4<f32>, 1>;
+@group(0) @binding(1) var<uniform> pass_table: array<vec4<f32>, 13>;
+@group(0) @binding(2) var<uniform> view_table: array<vec4<f32>, 4>;
+@group(0) @binding(3) var<uniform> const_table: array<vec4<f32>, 9>;
+var<private> ds_camera_projection: mat4x4<f32>;
+var<private> ds_camera_view: mat4x4<f32>;
+var<private> ds_camera_inv: mat4x4<f32>;
+var<private> ds_dpi_factor: f32;
+var<private> ds_dpi_dilate: f32;
+var<private> ds_time: f32;
+var<private> ds_view_transform: mat4x4<f32>;
+var<private> ds_draw_zbias: f32;
+var<private> ds_a_xs: vec2<f32>;
+var<private> ds_a_ys: vec4<f32>;
+var<private> ds_chan: f32;
+var<private> ds_color: vec4<f32>;
+var<private> ds_gradient_color: vec4<f32>;
+var<private> ds_gradient_color2: vec4<f32>;
+var<private> ds_gradient_color3: vec4<f32>;
+var<private> ds_gradient: vec4<f32>;
+var<private> ds_gradient_offsets: vec4<f32>;
+var<private> ds_gradient_kind: f32;
+var<private> ds_geom_pos: vec2<f32>;
+var<private> ds_v_p0: vec2<f32>;
+var<private> ds_v_p1: vec2<f32>;
+var<private> ds_v_p2: vec2<f32>;
+var<private> ds_v_p3: vec2<f32>;
+var<private> ds_v_pixel: vec2<f32>;
+struct VertexInput {
+    @location(0) packed_geometry_0: vec2<f32>,
+    @location(1) packed_instance_0: vec4<f32>,
+    @location(2) packed_instance_1: vec4<f32>,
+    @location(3) packed_instance_2: vec4<f32>,
+    @location(4) packed_instance_3: vec4<f32>,
+    @location(5) packed_instance_4: vec4<f32>,
+    @location(6) packed_instance_5: vec4<f32>,
+    @location(7) packed_instance_6: vec4<f32>,
+    @location(8) packed_instance_7: vec4<f32>,
+}
+struct Varyings {
+    @builtin(position) position: vec4<f32>,
+    @location(0) packed_varying_0: vec4<f32>,
+    @location(1) packed_varying_1: vec4<f32>,
+    @location(2) packed_varying_2: vec4<f32>,
+    @location(3) packed_varying_3: vec4<f32>,
+    @location(4) packed_varying_4: vec4<f32>,
+    @location(5) packed_varying_5: vec4<f32>,
+    @location(6) packed_varying_6: vec4<f32>,
+    @location(7) packed_varying_7: vec4<f32>,
+    @location(8) packed_varying_8: vec4<f32>,
+}
+fn fn_7_17_get_color() -> vec4<f32> {
+    if(ds_gradient_kind < const_table[5].y) {
+            return ds_color;
+    }
+
+    var var_d_0: vec2<f32> = (ds_gradient.zw - ds_gradient.xy);
+    var var_t_0: f32 = (dot((ds_v_pixel - ds_gradient.xy), var_d_0) / max(dot(var_d_0, var_d_0), const_table[5].z));
+    if(ds_gradient_kind > const_table[5].w) {
+            var_t_0 = (length((ds_v_pixel - ds_gradient.xy)) / max(ds_gradient.z, const_table[6].x));
+    }
+
+    var var_o_0: vec4<f32> = ds_gradient_offsets;
+    var var_color_0: vec4<f32> = mix(ds_color, ds_gradient_color, clamp(((var_t_0 - var_o_0.x) / max((var_o_0.y - var_o_0.x), const_table[6].y)), const_table[6].z, const_table[6].w));
+    var_color_0 = mix(var_color_0, ds_gradient_color2, clamp(((var_t_0 - var_o_0.y) / max((var_o_0.z - var_o_0.y), const_table[7].x)), const_table[7].y, const_table[7].z));
+    return mix(var_color_0, ds_gradient_color3, clamp(((var_t_0 - var_o_0.z) / max((var_o_0.w - var_o_0.z), const_table[7].w)), const_table[8].x, const_table[8].y));
+}
+fn fn_7_14_intersect_line_segment_with_horizontal_line(param_p0_0: vec2<f32>, param_p1_0: vec2<f32>, param_y_0: f32) -> vec2<f32> {
+    var var_p0_0: vec2<f32> = param_p0_0;
+    var var_p1_0: vec2<f32> = param_p1_0;
+    var var_y_0: f32 = param_y_0;
+    {
+    return vec2<f32>(mix(var_p0_0.x, var_p1_0.x, ((var_y_0 - var_p0_0.y) / (var_p1_0.y - var_p0_0.y))), var_y_0);
+}
+}
+fn fn_7_13_intersect_line_segment_with_vertical_line(param_p0_0: vec2<f32>, param_p1_0: vec2<f32>, param_x_0: f32) -> vec2<f32> {
+    var var_p0_0: vec2<f32> = param_p0_0;
+    var var_p1_0: vec2<f32> = param_p1_0;
+    var var_x_0: f32 = param_x_0;
+    {
+    return vec2<f32>(var_x_0, mix(var_p0_0.y, var_p1_0.y, ((var_x_0 - var_p0_0.x) / (var_p1_0.x - var_p0_0.x))));
+}
+}
+fn fn_7_15_compute_clamped_right_trapezoid_area(param_p0_0: vec2<f32>, param_p1_0: vec2<f32>, param_p_min_0: vec2<f32>, param_p_max_0: vec2<f32>) -> f32 {
+    var var_p0_0: vec2<f32> = param_p0_0;
+    var var_p1_0: vec2<f32> = param_p1_0;
+    var var_p_min_0: vec2<f32> = param_p_min_0;
+    var var_p_max_0: vec2<f32> = param_p_max_0;
+    {
+    var var_x0_0: f32 = clamp(var_p0_0.x, var_p_min_0.x, var_p_max_0.x);
+    var var_x1_0: f32 = clamp(var_p1_0.x, var_p_min_0.x, var_p_max_0.x);
+    if((var_p0_0.x < var_p_min_0.x) && (var_p_min_0.x < var_p1_0.x)) {
+            var_p0_0 = fn_7_13_intersect_line_segment_with_vertical_line (var_p0_0, var_p1_0, var_p_min_0.x);
+    }
+
+    if((var_p0_0.x < var_p_max_0.x) && (var_p_max_0.x < var_p1_0.x)) {
+            var_p1_0 = fn_7_13_intersect_line_segment_with_vertical_line (var_p0_0, var_p1_0, var_p_max_0.x);
+    }
+
+    if((var_p0_0.y < var_p_min_0.y) && (var_p_min_0.y < var_p1_0.y)) {
+            var_p0_0 = fn_7_14_intersect_line_segment_with_horizontal_line (var_p0_0, var_p1_0, var_p_min_0.y);
+    }
+
+    if((var_p1_0.y < var_p_min_0.y) && (var_p_min_0.y < var_p0_0.y)) {
+            var_p1_0 = fn_7_14_intersect_line_segment_with_horizontal_line (var_p1_0, var_p0_0, var_p_min_0.y);
+    }
+
+    if((var_p0_0.y < var_p_max_0.y) && (var_p_max_0.y < var_p1_0.y)) {
+            var_p1_0 = fn_7_14_intersect_line_segment_with_horizontal_line (var_p0_0, var_p1_0, var_p_max_0.y);
+    }
+
+    if((var_p1_0.y < var_p_max_0.y) && (var_p_max_0.y < var_p0_0.y)) {
+            var_p0_0 = fn_7_14_intersect_line_segment_with_horizontal_line (var_p1_0, var_p0_0, var_p_max_0.y);
+    }
+
+    var_p0_0 = clamp(var_p0_0, var_p_min_0, var_p_max_0);
+    var_p1_0 = clamp(var_p1_0, var_p_min_0, var_p_max_0);
+    var var_h0_0: f32 = (var_p_max_0.y - var_p0_0.y);
+    var var_h1_0: f32 = (var_p_max_0.y - var_p1_0.y);
+    var var_a0_0: f32 = ((var_p0_0.x - var_x0_0) * var_h0_0);
+    var var_a1_0: f32 = (((var_p1_0.x - var_p0_0.x) * (var_h0_0 + var_h1_0)) * const_table[5].x);
+    var var_a2_0: f32 = ((var_x1_0 - var_p1_0.x) * var_h1_0);
+    return ((var_a0_0 + var_a1_0) + var_a2_0);
+}
+}
+fn fn_7_16_compute_clamped_trapezoid_area(param_p_min_0: vec2<f32>, param_p_max_0: vec2<f32>) -> f32 {
+    var var_p_min_0: vec2<f32> = param_p_min_0;
+    var var_p_max_0: vec2<f32> = param_p_max_0;
+    {
+    var var_a0_0: f32 = fn_7_15_compute_clamped_right_trapezoid_area (ds_v_p0, ds_v_p1, var_p_min_0, var_p_max_0);
+    var var_a1_0: f32 = fn_7_15_compute_clamped_right_trapezoid_area (ds_v_p2, ds_v_p3, var_p_min_0, var_p_max_0);
+    return (var_a0_0 - var_a1_0);
+}
+}
+fn fn_7_18_pixel() -> vec4<f32> {
+    var var_p_min_0: vec2<f32> = (ds_v_pixel.xy - const_table[1].x);
+    var var_p_max_0: vec2<f32> = (ds_v_pixel.xy + const_table[1].y);
+    var var_t_area_0: f32 = fn_7_16_compute_clamped_trapezoid_area (var_p_min_0, var_p_max_0);
+    if(ds_chan > const_table[1].z) {
+            var var_color_0: vec4<f32> = fn_7_17_get_color ();
+        return (vec4<f32>((var_color_0.rgb * var_color_0.a), var_color_0.a) * var_t_area_0);
+    }
+
+    if(ds_chan < const_table[1].w) {
+            return vec4<f32>(var_t_area_0, const_table[2].x, const_table[2].y, const_table[2].z);
+    }
+
+    if(ds_chan < const_table[2].w) {
+            return vec4<f32>(const_table[3].x, var_t_area_0, const_table[3].y, const_table[3].z);
+    }
+
+    if(ds_chan < const_table[3].w) {
+            return vec4<f32>(const_table[4].x, const_table[4].y, var_t_area_0, const_table[4].z);
+    }
+
+    return vec4<f32>(var_t_area_0, var_t_area_0, var_t_area_0, const_table[4].w);
+}
+fn fn_7_19_vertex() -> vec4<f32> {
+    var var_pos_min_0: vec2<f32> = vec2<f32>(ds_a_xs.x, min(ds_a_ys.x, ds_a_ys.y));
+    var var_pos_max_0: vec2<f32> = vec2<f32>(ds_a_xs.y, max(ds_a_ys.z, ds_a_ys.w));
+    var var_pos_0: vec2<f32> = mix((var_pos_min_0 - const_table[0].x), (var_pos_max_0 + const_table[0].y), ds_geom_pos);
+    ds_v_p0 = vec2<f32>(ds_a_xs.x, ds_a_ys.x);
+    ds_v_p1 = vec2<f32>(ds_a_xs.y, ds_a_ys.y);
+    ds_v_p2 = vec2<f32>(ds_a_xs.x, ds_a_ys.z);
+    ds_v_p3 = vec2<f32>(ds_a_xs.y, ds_a_ys.w);
+    ds_v_pixel = var_pos_0;
+    return (ds_camera_projection * vec4<f32>(var_pos_0, const_table[0].z, const_table[0].w));
+}
+@vertex
+fn vertex_main(vertex_input: VertexInput) -> Varyings {
+    ds_draw_zbias = draw_table[0].x;
+    ds_camera_projection = mat4x4<f32>(pass_table[0].x, pass_table[0].y, pass_table[0].z, pass_table[0].w, pass_table[1].x, pass_table[1].y, pass_table[1].z, pass_table[1].w, pass_table[2].x, pass_table[2].y, pass_table[2].z, pass_table[2].w, pass_table[3].x, pass_table[3].y, pass_table[3].z, pass_table[3].w);
+    ds_camera_view = mat4x4<f32>(pass_table[4].x, pass_table[4].y, pass_table[4].z, pass_table[4].w, pass_table[5].x, pass_table[5].y, pass_table[5].z, pass_table[5].w, pass_table[6].x, pass_table[6].y, pass_table[6].z, pass_table[6].w, pass_table[7].x, pass_table[7].y, pass_table[7].z, pass_table[7].w);
+    ds_camera_inv = mat4x4<f32>(pass_table[8].x, pass_table[8].y, pass_table[8].z, pass_table[8].w, pass_table[9].x, pass_table[9].y, pass_table[9].z, pass_table[9].w, pass_table[10].x, pass_table[10].y, pass_table[10].z, pass_table[10].w, pass_table[11].x, pass_table[11].y, pass_table[11].z, pass_table[11].w);
+    ds_dpi_factor = pass_table[12].x;
+    ds_dpi_dilate = pass_table[12].y;
+    ds_time = pass_table[12].z;
+    ds_view_transform = mat4x4<f32>(view_table[0].x, view_table[0].y, view_table[0].z, view_table[0].w, view_table[1].x, view_table[1].y, view_table[1].z, view_table[1].w, view_table[2].x, view_table[2].y, view_table[2].z, view_table[2].w, view_table[3].x, view_table[3].y, view_table[3].z, view_table[3].w);
+    ds_geom_pos = vertex_input.packed_geometry_0;
+    ds_a_xs = vertex_input.packed_instance_0.xy;
+    ds_a_ys.x = vertex_input.packed_instance_0.z;
+    ds_a_ys.y = vertex_input.packed_instance_0.w;
+    ds_a_ys.z = vertex_input.packed_instance_1.x;
+    ds_a_ys.w = vertex_input.packed_instance_1.y;
+    ds_chan = vertex_input.packed_instance_1.z;
+    ds_color.x = vertex_input.packed_instance_1.w;
+    ds_color.y = vertex_input.packed_instance_2.x;
+    ds_color.z = vertex_input.packed_instance_2.y;
+    ds_color.w = vertex_input.packed_instance_2.z;
+    ds_gradient_color.x = vertex_input.packed_instance_2.w;
+    ds_gradient_color.y = vertex_input.packed_instance_3.x;
+    ds_gradient_color.z = vertex_input.packed_instance_3.y;
+    ds_gradient_color.w = vertex_input.packed_instance_3.z;
+    ds_gradient_color2.x = vertex_input.packed_instance_3.w;
+    ds_gradient_color2.y = vertex_input.packed_instance_4.x;
+    ds_gradient_color2.z = vertex_input.packed_instance_4.y;
+    ds_gradient_color2.w = vertex_input.packed_instance_4.z;
+    ds_gradient_color3.x = vertex_input.packed_instance_4.w;
+    ds_gradient_color3.y = vertex_input.packed_instance_5.x;
+    ds_gradient_color3.z = vertex_input.packed_instance_5.y;
+    ds_gradient_color3.w = vertex_input.packed_instance_5.z;
+    ds_gradient.x = vertex_input.packed_instance_5.w;
+    ds_gradient.y = vertex_input.packed_instance_6.x;
+    ds_gradient.z = vertex_input.packed_instance_6.y;
+    ds_gradient.w = vertex_input.packed_instance_6.z;
+    ds_gradient_offsets.x = vertex_input.packed_instance_6.w;
+    ds_gradient_offsets.y = vertex_input.packed_instance_7.x;
+    ds_gradient_offsets.z = vertex_input.packed_instance_7.y;
+    ds_gradient_offsets.w = vertex_input.packed_instance_7.z;
+    ds_gradient_kind = vertex_input.packed_instance_7.w;
+    var varyings: Varyings;
+    varyings.position = fn_7_19_vertex();
+    varyings.packed_varying_0.x = ds_chan;
+    varyings.packed_varying_0.y = ds_color.x;
+    varyings.packed_varying_0.z = ds_color.y;
+    varyings.packed_varying_0.w = ds_color.z;
+    varyings.packed_varying_1.x = ds_color.w;
+    varyings.packed_varying_1.y = ds_gradient_color.x;
+    varyings.packed_varying_1.z = ds_gradient_color.y;
+    varyings.packed_varying_1.w = ds_gradient_color.z;
+    varyings.packed_varying_2.x = ds_gradient_color.w;
+    varyings.packed_varying_2.y = ds_gradient_color2.x;
+    varyings.packed_varying_2.z = ds_gradient_color2.y;
+    varyings.packed_varying_2.w = ds_gradient_color2.z;
+    varyings.packed_varying_3.x = ds_gradient_color2.w;
+    varyings.packed_varying_3.y = ds_gradient_color3.x;
+    varyings.packed_varying_3.z = ds_gradient_color3.y;
+    varyings.packed_varying_3.w = ds_gradient_color3.z;
+    varyings.packed_varying_4.x = ds_gradient_color3.w;
+    varyings.packed_varying_4.y = ds_gradient.x;
+    varyings.packed_varying_4.z = ds_gradient.y;
+    varyings.packed_varying_4.w = ds_gradient.z;
+    varyings.packed_varying_5.x = ds_gradient.w;
+    varyings.packed_varying_5.y = ds_gradient_offsets.x;
+    varyings.packed_varying_5.z = ds_gradient_offsets.y;
+    varyings.packed_varying_5.w = ds_gradient_offsets.z;
+    varyings.packed_varying_6.x = ds_gradient_offsets.w;
+    varyings.packed_varying_6.y = ds_gradient_kind;
+    varyings.packed_varying_6.z = ds_v_p0.x;
+    varyings.packed_varying_6.w = ds_v_p0.y;
+    varyings.packed_varying_7.x = ds_v_p1.x;
+    varyings.packed_varying_7.y = ds_v_p1.y;
+    varyings.packed_varying_7.z = ds_v_p2.x;
+    varyings.packed_varying_7.w = ds_v_p2.y;
+    varyings.packed_varying_8.x = ds_v_p3.x;
+    varyings.packed_varying_8.y = ds_v_p3.y;
+    varyings.packed_varying_8.z = ds_v_pixel.x;
+    varyings.packed_varying_8.w = ds_v_pixel.y;
+    return varyings;
+}
+@fragment
+fn fragment_main(varyings: Varyings) -> @location(0) vec4<f32> {
+    ds_draw_zbias = draw_table[0].x;
+    ds_camera_projection = mat4x4<f32>(pass_table[0].x, pass_table[0].y, pass_table[0].z, pass_table[0].w, pass_table[1].x, pass_table[1].y, pass_table[1].z, pass_table[1].w, pass_table[2].x, pass_table[2].y, pass_table[2].z, pass_table[2].w, pass_table[3].x, pass_table[3].y, pass_table[3].z, pass_table[3].w);
+    ds_camera_view = mat4x4<f32>(pass_table[4].x, pass_table[4].y, pass_table[4].z, pass_table[4].w, pass_table[5].x, pass_table[5].y, pass_table[5].z, pass_table[5].w, pass_table[6].x, pass_table[6].y, pass_table[6].z, pass_table[6].w, pass_table[7].x, pass_table[7].y, pass_table[7].z, pass_table[7].w);
+    ds_camera_inv = mat4x4<f32>(pass_table[8].x, pass_table[8].y, pass_table[8].z, pass_table[8].w, pass_table[9].x, pass_table[9].y, pass_table[9].z, pass_table[9].w, pass_table[10].x, pass_table[10].y, pass_table[10].z, pass_table[10].w, pass_table[11].x, pass_table[11].y, pass_table[11].z, pass_table[11].w);
+    ds_dpi_factor = pass_table[12].x;
+    ds_dpi_dilate = pass_table[12].y;
+    ds_time = pass_table[12].z;
+    ds_view_transform = mat4x4<f32>(view_table[0].x, view_table[0].y, view_table[0].z, view_table[0].w, view_table[1].x, view_table[1].y, view_table[1].z, view_table[1].w, view_table[2].x, view_table[2].y, view_table[2].z, view_table[2].w, view_table[3].x, view_table[3].y, view_table[3].z, view_table[3].w);
+    ds_chan = varyings.packed_varying_0.x;
+    ds_color.x = varyings.packed_varying_0.y;
+    ds_color.y = varyings.packed_varying_0.z;
+    ds_color.z = varyings.packed_varying_0.w;
+    ds_color.w = varyings.packed_varying_1.x;
+    ds_gradient_color.x = varyings.packed_varying_1.y;
+    ds_gradient_color.y = varyings.packed_varying_1.z;
+    ds_gradient_color.z = varyings.packed_varying_1.w;
+    ds_gradient_color.w = varyings.packed_varying_2.x;
+    ds_gradient_color2.x = varyings.packed_varying_2.y;
+    ds_gradient_color2.y = varyings.packed_varying_2.z;
+    ds_gradient_color2.z = varyings.packed_varying_2.w;
+    ds_gradient_color2.w = varyings.packed_varying_3.x;
+    ds_gradient_color3.x = varyings.packed_varying_3.y;
+    ds_gradient_color3.y = varyings.packed_varying_3.z;
+    ds_gradient_color3.z = varyings.packed_varying_3.w;
+    ds_gradient_color3.w = varyings.packed_varying_4.x;
+    ds_gradient.x = varyings.packed_varying_4.y;
+    ds_gradient.y = varyings.packed_varying_4.z;
+    ds_gradient.z = varyings.packed_varying_4.w;
+    ds_gradient.w = varyings.packed_varying_5.x;
+    ds_gradient_offsets.x = varyings.packed_varying_5.y;
+    ds_gradient_offsets.y = varyings.packed_varying_5.z;
+    ds_gradient_offsets.z = varyings.packed_varying_5.w;
+    ds_gradient_offsets.w = varyings.packed_varying_6.x;
+    ds_gradient_kind = varyings.packed_varying_6.y;
+    ds_v_p0 = varyings.packed_varying_6.zw;
+    ds_v_p1 = varyings.packed_varying_7.xy;
+    ds_v_p2 = varyings.packed_varying_7.zw;
+    ds_v_p3 = varyings.packed_varying_8.xy;
+    ds_v_pixel = varyings.packed_varying_8.zw;
+    return fn_7_18_pixel();
+}
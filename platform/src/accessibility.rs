@@ -0,0 +1,411 @@
+use {
+    std::{
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+    },
+    crate::{
+        makepad_math::*,
+        area::Area,
+        cx::Cx,
+        cx_api::CxOsApi,
+        window::WindowId,
+        event::{Event, KeyCode, KeyEvent, KeyModifiers},
+    }
+};
+
+// the accessibility tree the windows publish while they draw, assistive technology bridges like
+// at-spi on linux read it, and because it is plain data tests can query it in process as well
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AccessRole {
+    Window,
+    Group,
+    Label,
+    Button,
+    CheckBox,
+    RadioButton,
+    TextInput,
+    DropDown,
+    Slider,
+    ListItem,
+    Link,
+    Tab,
+    Image,
+}
+
+impl AccessRole {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Window => "frame",
+            Self::Group => "panel",
+            Self::Label => "label",
+            Self::Button => "push button",
+            Self::CheckBox => "check box",
+            Self::RadioButton => "radio button",
+            Self::TextInput => "entry",
+            Self::DropDown => "combo box",
+            Self::Slider => "slider",
+            Self::ListItem => "list item",
+            Self::Link => "link",
+            Self::Tab => "page tab",
+            Self::Image => "image",
+        }
+    }
+
+    // roles that do something when activated with the keyboard
+    pub fn is_actionable(&self) -> bool {
+        match self {
+            Self::Button | Self::CheckBox | Self::RadioButton | Self::ListItem | Self::Link | Self::Tab | Self::DropDown => true,
+            _ => false
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccessRange {
+    pub min: f64,
+    pub max: f64,
+    pub value: f64,
+    pub step: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AccessProps {
+    pub name: String,
+    pub value: Option<String>,
+    pub range: Option<AccessRange>,
+    pub checked: Option<bool>,
+    pub expanded: Option<bool>,
+    pub selected: bool,
+    pub disabled: bool,
+}
+
+impl AccessProps {
+    pub fn with_name(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AccessNode {
+    // stable between frames as long as the widget draws into the same place
+    pub id: u64,
+    pub role: AccessRole,
+    pub props: AccessProps,
+    pub focusable: bool,
+    pub focused: bool,
+    // in window coordinates
+    pub bounds: Rect,
+    pub area: Area,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+#[derive(Clone, Debug)]
+pub struct AccessTree {
+    pub window_id: WindowId,
+    pub position: DVec2,
+    pub dpi_factor: f64,
+    // the first node is the window itself
+    pub nodes: Vec<AccessNode>,
+}
+
+impl AccessTree {
+    pub fn new(window_id: WindowId, name: &str, position: DVec2, size: DVec2, dpi_factor: f64) -> Self {
+        let mut hasher = DefaultHasher::new();
+        window_id.hash(&mut hasher);
+        Self {
+            window_id,
+            position,
+            dpi_factor,
+            nodes: vec![AccessNode {
+                id: hasher.finish(),
+                role: AccessRole::Window,
+                props: AccessProps::with_name(name),
+                focusable: false,
+                focused: false,
+                bounds: Rect {pos: DVec2::default(), size},
+                area: Area::Empty,
+                parent: None,
+                children: Vec::new(),
+            }]
+        }
+    }
+
+    // adds a node under a parent and returns its index
+    pub fn push(&mut self, parent: usize, mut node: AccessNode) -> usize {
+        let index = self.nodes.len();
+        node.parent = Some(parent);
+        self.nodes.push(node);
+        self.nodes[parent].children.push(index);
+        index
+    }
+
+    pub fn root(&self) -> &AccessNode {
+        &self.nodes[0]
+    }
+
+    pub fn index_of(&self, id: u64) -> Option<usize> {
+        self.nodes.iter().position( | node | node.id == id)
+    }
+
+    pub fn node(&self, id: u64) -> Option<&AccessNode> {
+        self.nodes.iter().find( | node | node.id == id)
+    }
+
+    pub fn children(&self, index: usize) -> impl Iterator<Item = &AccessNode> {
+        self.nodes[index].children.iter().map(move | child | &self.nodes[*child])
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<&AccessNode> {
+        self.nodes.iter().find( | node | node.props.name == name)
+    }
+
+    pub fn find_all_by_role(&self, role: AccessRole) -> impl Iterator<Item = &AccessNode> {
+        self.nodes.iter().filter(move | node | node.role == role)
+    }
+
+    pub fn focused(&self) -> Option<&AccessNode> {
+        self.nodes.iter().find( | node | node.focused)
+    }
+
+    // the focusable nodes in tab order
+    pub fn focus_order(&self) -> impl Iterator<Item = &AccessNode> {
+        self.nodes.iter().filter( | node | node.focusable)
+    }
+
+    // the node that tab, or shift-tab when backward, moves the focus to from a node
+    pub fn next_focus(&self, focus: Option<u64>, backward: bool) -> Option<&AccessNode> {
+        let order: Vec<&AccessNode> = self.focus_order().collect();
+        let focus = focus.and_then( | id | order.iter().position( | node | node.id == id));
+        next_tab_stop(order.len(), focus, backward).map( | index | order[index])
+    }
+
+    // the deepest node that contains a point in window coordinates
+    pub fn node_at(&self, pos: DVec2) -> Option<usize> {
+        if !self.nodes[0].bounds.contains(pos) {
+            return None
+        }
+        let mut index = 0;
+        'outer: loop {
+            for child in self.nodes[index].children.iter().rev() {
+                if self.nodes[*child].bounds.contains(pos) {
+                    index = *child;
+                    continue 'outer;
+                }
+            }
+            return Some(index)
+        }
+    }
+}
+
+// tab walks the stops in drawing order and wraps around at the ends, without a focused stop it
+// starts at the first or the last one
+pub fn next_tab_stop(count: usize, focus: Option<usize>, backward: bool) -> Option<usize> {
+    if count == 0 {
+        return None
+    }
+    Some(if backward {
+        match focus {
+            Some(0) | None => count - 1,
+            Some(index) => index - 1
+        }
+    }
+    else {
+        match focus {
+            Some(index) if index + 1 < count => index + 1,
+            _ => 0
+        }
+    })
+}
+
+// a node id for an area, leaving out the redraw id so it doesnt change with every frame
+pub fn access_id(area: Area) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    match area {
+        Area::Empty => return 0,
+        Area::Instance(inst) => {
+            0u8.hash(&mut hasher);
+            inst.draw_list_id.hash(&mut hasher);
+            inst.draw_item_id.hash(&mut hasher);
+            inst.instance_offset.hash(&mut hasher);
+        }
+        Area::Rect(rect) => {
+            1u8.hash(&mut hasher);
+            rect.draw_list_id.hash(&mut hasher);
+            rect.rect_id.hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+// what assistive technology asks the application to do with a node
+#[derive(Clone, Debug)]
+pub enum AccessRequest {
+    Focus(u64),
+    // as if the node was focused and space was pressed
+    Activate(u64),
+}
+
+impl Cx {
+    pub fn accessibility_enabled(&self) -> bool {
+        self.accessibility_enabled
+    }
+
+    // the windows only build their accessibility tree while this is on, platform bridges turn it on
+    // when a screen reader is running
+    pub fn set_accessibility_enabled(&mut self, enabled: bool) {
+        if enabled && !self.accessibility_enabled {
+            self.redraw_all();
+        }
+        if !enabled {
+            self.access_trees.clear();
+        }
+        self.accessibility_enabled = enabled;
+    }
+
+    pub fn update_access_tree(&mut self, mut tree: AccessTree) {
+        let focus = self.keyboard.key_focus;
+        if !focus.is_empty() {
+            for node in &mut tree.nodes {
+                node.focused = node.area == focus;
+            }
+        }
+        if let Some(old) = self.access_trees.iter_mut().find( | old | old.window_id == tree.window_id) {
+            *old = tree;
+        }
+        else {
+            self.access_trees.push(tree);
+        }
+        self.access_trees_changed = true;
+    }
+
+    pub fn access_tree(&self, window_id: WindowId) -> Option<&AccessTree> {
+        self.access_trees.iter().find( | tree | tree.window_id == window_id)
+    }
+
+    pub fn access_trees(&self) -> &[AccessTree] {
+        &self.access_trees
+    }
+
+    // the node that has the keyboard focus
+    pub fn access_focus(&self) -> Option<u64> {
+        let focus = self.keyboard.key_focus;
+        if focus.is_empty() {
+            return None
+        }
+        Some(access_id(focus))
+    }
+
+    // platform bridges poll this to know when to hand the trees over again
+    pub fn take_access_trees_changed(&mut self) -> bool {
+        std::mem::replace(&mut self.access_trees_changed, false)
+    }
+
+    pub fn handle_access_request(&mut self, request: AccessRequest) {
+        let (AccessRequest::Focus(id) | AccessRequest::Activate(id)) = request;
+        let Some(area) = self.access_trees.iter().find_map( | tree | tree.node(id).map( | node | node.area)) else {
+            return
+        };
+        if area.is_empty() {
+            return
+        }
+        self.set_key_focus(area);
+        self.inner_key_focus_change();
+        if let AccessRequest::Activate(_) = request {
+            let key = KeyEvent {
+                key_code: KeyCode::Space,
+                is_repeat: false,
+                modifiers: KeyModifiers::default(),
+                time: self.seconds_since_app_start(),
+            };
+            self.call_event_handler(&Event::KeyDown(key.clone()));
+            self.call_event_handler(&Event::KeyUp(key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn node(id: u64, role: AccessRole, name: &str, focusable: bool, bounds: Rect) -> AccessNode {
+        AccessNode {
+            id,
+            role,
+            props: AccessProps::with_name(name),
+            focusable,
+            focused: false,
+            bounds,
+            area: Area::Empty,
+            parent: None,
+            children: Vec::new(),
+        }
+    }
+    
+    fn form() -> AccessTree {
+        let mut tree = AccessTree::new(WindowId::default(), "Form", DVec2::default(), dvec2(200.0, 100.0), 2.0);
+        let rect = | x: f64, y: f64 | Rect {pos: dvec2(x, y), size: dvec2(50.0, 20.0)};
+        tree.push(0, node(1, AccessRole::Label, "Name", false, rect(0.0, 0.0)));
+        tree.push(0, node(2, AccessRole::TextInput, "Name", true, rect(60.0, 0.0)));
+        let group = tree.push(0, node(3, AccessRole::Group, "", false, Rect {pos: dvec2(0.0, 30.0), size: dvec2(200.0, 70.0)}));
+        tree.push(group, node(4, AccessRole::CheckBox, "Subscribe", true, rect(0.0, 30.0)));
+        tree.push(group, node(5, AccessRole::Button, "Send", true, rect(60.0, 30.0)));
+        tree
+    }
+    
+    fn names<'a>(nodes: impl Iterator<Item = &'a AccessNode>) -> Vec<&'a str> {
+        nodes.map( | node | node.props.name.as_str()).collect()
+    }
+    
+    #[test]
+    fn tab_wraps_around_at_the_ends() {
+        assert_eq!(next_tab_stop(0, None, false), None);
+        assert_eq!(next_tab_stop(0, None, true), None);
+        assert_eq!(next_tab_stop(3, None, false), Some(0));
+        assert_eq!(next_tab_stop(3, None, true), Some(2));
+        assert_eq!(next_tab_stop(3, Some(1), false), Some(2));
+        assert_eq!(next_tab_stop(3, Some(2), false), Some(0));
+        assert_eq!(next_tab_stop(3, Some(1), true), Some(0));
+        assert_eq!(next_tab_stop(3, Some(0), true), Some(2));
+    }
+    
+    #[test]
+    fn walks_the_focusable_nodes_in_tab_order() {
+        let tree = form();
+        assert_eq!(names(tree.focus_order()), vec!["Name", "Subscribe", "Send"]);
+        let tab = | focus: Option<u64>, backward: bool | tree.next_focus(focus, backward).map( | node | node.id);
+        assert_eq!(tab(None, false), Some(2));
+        assert_eq!(tab(Some(2), false), Some(4));
+        assert_eq!(tab(Some(4), false), Some(5));
+        assert_eq!(tab(Some(5), false), Some(2));
+        assert_eq!(tab(None, true), Some(5));
+        assert_eq!(tab(Some(5), true), Some(4));
+        assert_eq!(tab(Some(2), true), Some(5));
+        // from a node that tab doesnt stop at, tab starts over
+        assert_eq!(tab(Some(1), false), Some(2));
+    }
+    
+    #[test]
+    fn finds_nodes_by_name_role_and_position() {
+        let tree = form();
+        assert_eq!(tree.root().role, AccessRole::Window);
+        assert_eq!(tree.root().props.name, "Form");
+        assert_eq!(names(tree.children(0)), vec!["Name", "Name", ""]);
+        assert_eq!(names(tree.children(3)), vec!["Subscribe", "Send"]);
+        assert_eq!(tree.nodes[4].parent, Some(3));
+        assert_eq!(tree.find_by_name("Name").unwrap().role, AccessRole::Label);
+        assert_eq!(names(tree.find_all_by_role(AccessRole::Button)), vec!["Send"]);
+        assert_eq!(tree.node(4).unwrap().bounds, Rect {pos: dvec2(0.0, 30.0), size: dvec2(50.0, 20.0)});
+        assert_eq!(tree.index_of(5), Some(5));
+        assert_eq!(tree.node(6).map( | node | node.id), None);
+        // the deepest node wins, the window when no node is under the point
+        assert_eq!(tree.node_at(dvec2(70.0, 40.0)), Some(5));
+        assert_eq!(tree.node_at(dvec2(150.0, 40.0)), Some(3));
+        assert_eq!(tree.node_at(dvec2(150.0, 10.0)), Some(0));
+        assert_eq!(tree.node_at(dvec2(250.0, 10.0)), None);
+    }
+}
@@ -33,6 +33,10 @@ pub trait AnimatorImpl {
     
     fn animator_cut(&mut self, cx: &mut Cx, state: &[LiveId; 2]);
     fn animator_play(&mut self, cx: &mut Cx, state: &[LiveId; 2]);
+    // implementors without a way to schedule the state play it right away
+    fn animator_play_delayed(&mut self, cx: &mut Cx, state: &[LiveId; 2], _delay: f64) {
+        self.animator_play(cx, state)
+    }
     fn animator_toggle(&mut self, cx: &mut Cx, is_state_1: bool, animate: Animate, state1: &[LiveId; 2], state2: &[LiveId; 2]) {
         if is_state_1 {
             if let Animate::Yes = animate {
@@ -180,12 +184,78 @@ pub enum Ease {
     #[live {d1: 0.82, d2: 0.97, max: 100}] ExpDecay {d1: f64, d2: f64, max: usize},
     
     #[live {begin: 0.0, end: 1.0}] Pow {begin: f64, end: f64},
-    #[live {cp0: 0.0, cp1: 0.0, cp2: 1.0, cp3: 1.0}] Bezier {cp0: f64, cp1: f64, cp2: f64, cp3: f64}
+    #[live {cp0: 0.0, cp1: 0.0, cp2: 1.0, cp3: 1.0}] Bezier {cp0: f64, cp1: f64, cp2: f64, cp3: f64},
+    #[live {stiffness: 100.0, damping: 10.0, mass: 1.0}] Spring {stiffness: f64, damping: f64, mass: f64}
 }
 
 impl Ease {
+    // the seconds a spring takes to come to rest, play it over this duration for real spring motion
+    pub fn spring_settle_time(&self) -> Option<f64> {
+        if let Self::Spring {stiffness, damping, mass} = self {
+            Some(Self::spring_settle(*stiffness, *damping, *mass))
+        }
+        else {
+            None
+        }
+    }
+    
+    fn spring_omega_zeta(stiffness: f64, damping: f64, mass: f64) -> (f64, f64) {
+        let stiffness = stiffness.max(0.001);
+        let mass = mass.max(0.001);
+        let omega = (stiffness / mass).sqrt();
+        // without any damping a spring never comes to rest
+        let zeta = (damping / (2.0 * (stiffness * mass).sqrt())).max(0.01);
+        (omega, zeta)
+    }
+    
+    // the time at which the spring stays within 0.001 of its target
+    fn spring_settle(stiffness: f64, damping: f64, mass: f64) -> f64 {
+        let (omega, zeta) = Self::spring_omega_zeta(stiffness, damping, mass);
+        if zeta < 0.999 {
+            (1000.0 / (1.0 - zeta * zeta).sqrt()).ln() / (zeta * omega)
+        }
+        else if zeta <= 1.001 {
+            9.23 / omega
+        }
+        else {
+            let root = (zeta * zeta - 1.0).sqrt();
+            let r1 = -omega * (zeta - root);
+            let r2 = -omega * (zeta + root);
+            (1000.0 * (r2 / (r1 - r2)).abs()).ln() / -r1
+        }
+    }
+    
+    // the position of a spring released at 0 with its rest position at 1, after time seconds
+    fn spring_position(stiffness: f64, damping: f64, mass: f64, time: f64) -> f64 {
+        let (omega, zeta) = Self::spring_omega_zeta(stiffness, damping, mass);
+        if zeta < 0.999 {
+            let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+            1.0 - (-zeta * omega * time).exp() * ((omega_d * time).cos() + zeta * omega / omega_d * (omega_d * time).sin())
+        }
+        else if zeta <= 1.001 {
+            1.0 - (-omega * time).exp() * (1.0 + omega * time)
+        }
+        else {
+            let root = (zeta * zeta - 1.0).sqrt();
+            let r1 = -omega * (zeta - root);
+            let r2 = -omega * (zeta + root);
+            1.0 + (r2 * (r1 * time).exp() - r1 * (r2 * time).exp()) / (r1 - r2)
+        }
+    }
+    
     pub fn map(&self, t: f64) -> f64 {
         match self {
+            // the keyframe segment is stretched over the time the spring takes to settle
+            Self::Spring {stiffness, damping, mass} => {
+                if t <= 0.0 {
+                    return 0.0;
+                }
+                if t >= 1.0 {
+                    return 1.0;
+                }
+                let settle = Self::spring_settle(*stiffness, *damping, *mass);
+                Self::spring_position(*stiffness, *damping, *mass, t * settle)
+            }
             Self::ExpDecay {d1, d2, max} => { // there must be a closed form for this
                 if t > 0.999 {
                     return 1.0;
@@ -696,7 +766,20 @@ impl Animator {
                     };
                     node_iter = nodes.next_child(id_index);
                     
-                    let (ended, time) = play.get_ended_time(ext_time - start_time);
+                    // a delayed track holds its first keyframe until the delay has passed
+                    let delay = match nodes.child_value_by_path(track_index, &[live_id!(delay).as_field()]) {
+                        Some(LiveValue::Float64(delay)) => *delay,
+                        _ => 0.0
+                    };
+                    let (ended, time) = if let Some(LiveValue::Float64(seek)) = nodes.child_value_by_path(track_index, &[live_id!(seek).as_field()]) {
+                        (true, *seek)
+                    }
+                    else {
+                        play.get_ended_time((ext_time - start_time - delay).max(0.0))
+                    };
+                    if let Some(index) = nodes.child_by_name(track_index, live_id!(progress).as_field()) {
+                        nodes[index].value = LiveValue::Float64(time);
+                    }
                     
                     if ended { // mark ended step 1
                         if let Some(index) = nodes.child_by_name(track_index, live_id!(ended).as_field()) {
@@ -872,6 +955,55 @@ impl Animator {
         false
     }
     
+    // where a track is on its keyframe timeline, 0 to 1 for states that play forward, a track
+    // that was cut to its state or has not animated yet is at its end
+    pub fn track_progress(&self, track_id: LiveId) -> f64 {
+        if let Some(state) = self.state.as_ref() {
+            if let Some(LiveValue::Float64(progress)) = state.child_value_by_path(0, &[live_id!(tracks).as_field(), track_id.as_field(), live_id!(progress).as_field()]) {
+                return *progress
+            }
+        }
+        1.0
+    }
+    
+    // holds a track at a point of the timeline it last played, until it plays another state
+    pub fn seek_track(&mut self, cx: &mut Cx, track_id: LiveId, progress: f64) {
+        let Some(state) = self.state.as_mut() else {
+            return
+        };
+        let path = [live_id!(tracks).as_field(), track_id.as_field()];
+        if state.child_by_path(0, &path).is_none() {
+            return
+        }
+        let mut seek = Vec::new();
+        seek.push_float64(live_id!(seek), progress);
+        state.replace_or_insert_last_node_by_path(0, &[path[0], path[1], live_id!(seek).as_field()], &seek);
+        self.next_frame = cx.new_next_frame();
+    }
+    
+    // the state each track is in, and if it is still animating towards it
+    pub fn track_states(&self, cx: &Cx) -> Vec<(LiveId, LiveId, bool)> {
+        let mut states = Vec::new();
+        let Some(state) = self.state.as_ref() else {
+            return states
+        };
+        let Some(tracks) = state.child_by_name(0, live_id!(tracks).as_field()) else {
+            return states
+        };
+        let mut child = state.first_child(tracks);
+        while let Some(track) = child {
+            if let Some(LiveValue::Id(state_id)) = state.child_value_by_path(track, &[live_id!(state_id).as_field()]) {
+                let playing = matches!(
+                    state.child_value_by_path(track, &[live_id!(ended).as_field()]),
+                    Some(LiveValue::Int64(ended)) if *ended == 0 || *ended == cx.event_id as i64
+                );
+                states.push((state[track].id, *state_id, playing));
+            }
+            child = state.next_child(track);
+        }
+        states
+    }
+    
     pub fn animator_in_state(&self, cx: &Cx, check_state_pair: &[LiveId; 2]) -> bool {
         // if we aren't initialized, look if our state id is a default
         if self.need_init() {
@@ -1013,6 +1145,11 @@ impl Animator {
     }
     
     pub fn animate_to_live(&mut self, cx: &mut Cx, state_pair: &[LiveId; 2]) {
+        self.animate_to_live_delayed(cx, state_pair, 0.0)
+    }
+    
+    // starts the animation after delay seconds, used to sequence states and stagger widgets
+    pub fn animate_to_live_delayed(&mut self, cx: &mut Cx, state_pair: &[LiveId; 2], delay: f64) {
         if let Some(live_ptr) = self.live_ptr {
             let live_registry_rc = cx.live_registry.clone();
             let live_registry = live_registry_rc.borrow();
@@ -1022,7 +1159,7 @@ impl Animator {
                 self.init_as_needed(cx, index, nodes);
                 
                 if let Some(index) = nodes.child_by_path(index, &[state_pair[0].as_instance(), state_pair[1].as_instance()]) {
-                    self.animate_to(cx, state_pair, index, nodes)
+                    self.animate_to_delayed(cx, state_pair, delay, index, nodes)
                 }
                 else if !self.ignore_missing{
                     error!("animate_to_live {}.{} not found", state_pair[0], state_pair[1])
@@ -1035,6 +1172,10 @@ impl Animator {
     }
     
     pub fn animate_to(&mut self, cx: &mut Cx, state_pair: &[LiveId; 2], index: usize, nodes: &[LiveNode]) {
+        self.animate_to_delayed(cx, state_pair, 0.0, index, nodes)
+    }
+    
+    pub fn animate_to_delayed(&mut self, cx: &mut Cx, state_pair: &[LiveId; 2], delay: f64, index: usize, nodes: &[LiveNode]) {
         
         if let Some(index) = nodes.child_by_name(index, live_id!(cursor).as_field()) {
             let cursor = MouseCursor::new_apply(cx, &mut ApplyFrom::New.into(), index, nodes);
@@ -1061,8 +1202,14 @@ impl Animator {
         
         let mut path = Vec::new();
         
+        // a state can delay itself with delay: seconds, which adds to the delay it is played with
+        let delay = delay + match nodes.child_value_by_path(index, &[live_id!(delay).as_field()]) {
+            Some(LiveValue::Float64(v)) => *v,
+            Some(LiveValue::Int64(v)) => *v as f64,
+            _ => 0.0
+        };
         state.replace_or_insert_last_node_by_path(0, &[live_id!(tracks).as_field(), track.as_field()], live_object!{
-            [track]: {state_id: (state_pair[1]), ended: 0, time: void},
+            [track]: {state_id: (state_pair[1]), ended: 0, time: void, delay: (delay), progress: 0.0},
         });
         
         // copy in from track
@@ -1210,3 +1357,34 @@ impl Animator {
     }
     
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn spring(damping: f64) -> Ease {
+        Ease::Spring {stiffness: 100.0, damping, mass: 1.0}
+    }
+    
+    #[test]
+    fn spring_starts_at_0_and_ends_at_1() {
+        for damping in [0.0, 10.0, 20.0, 100.0] {
+            assert_eq!(spring(damping).map(0.0), 0.0);
+            assert_eq!(spring(damping).map(1.0), 1.0);
+        }
+    }
+    
+    #[test]
+    fn spring_stays_finite_and_settles() {
+        // underdamped, critically damped and overdamped
+        for damping in [10.0, 20.0, 100.0] {
+            let ease = spring(damping);
+            let settle = ease.spring_settle_time().unwrap();
+            assert!(settle.is_finite() && settle > 0.0);
+            for i in 0..=100 {
+                assert!(ease.map(i as f64 / 100.0).is_finite());
+            }
+            assert!((ease.map(0.999) - 1.0).abs() < 0.01);
+        }
+    }
+}
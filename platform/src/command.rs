@@ -0,0 +1,554 @@
+use {
+    std::fs,
+    crate::{
+        makepad_live_id::*,
+        cx::{Cx, OsType},
+        event::{KeyCode, KeyEvent, KeyModifiers},
+    }
+};
+
+// commands are named things the application can do, keybindings map key chords onto them.
+// bindings are resolved against the scope the widget with key focus announced, so the same
+// keys can do something else in a code editor than in the rest of the window. a resolved
+// binding is dispatched as Event::Command instead of the keydown it came from
+
+// the keymap source of bindings loaded from a user config file, these replace all other
+// bindings of the same command
+pub const KEYMAP_USER: LiveId = live_id!(user);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct KeyStroke {
+    pub key_code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::Escape, "Escape"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::ReturnKey, "Enter"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::ArrowUp, "Up"),
+    (KeyCode::ArrowDown, "Down"),
+    (KeyCode::ArrowLeft, "Left"),
+    (KeyCode::ArrowRight, "Right"),
+    (KeyCode::F1, "F1"),
+    (KeyCode::F2, "F2"),
+    (KeyCode::F3, "F3"),
+    (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"),
+    (KeyCode::F6, "F6"),
+    (KeyCode::F7, "F7"),
+    (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"),
+    (KeyCode::F10, "F10"),
+    (KeyCode::F11, "F11"),
+    (KeyCode::F12, "F12"),
+    (KeyCode::KeyA, "A"),
+    (KeyCode::KeyB, "B"),
+    (KeyCode::KeyC, "C"),
+    (KeyCode::KeyD, "D"),
+    (KeyCode::KeyE, "E"),
+    (KeyCode::KeyF, "F"),
+    (KeyCode::KeyG, "G"),
+    (KeyCode::KeyH, "H"),
+    (KeyCode::KeyI, "I"),
+    (KeyCode::KeyJ, "J"),
+    (KeyCode::KeyK, "K"),
+    (KeyCode::KeyL, "L"),
+    (KeyCode::KeyM, "M"),
+    (KeyCode::KeyN, "N"),
+    (KeyCode::KeyO, "O"),
+    (KeyCode::KeyP, "P"),
+    (KeyCode::KeyQ, "Q"),
+    (KeyCode::KeyR, "R"),
+    (KeyCode::KeyS, "S"),
+    (KeyCode::KeyT, "T"),
+    (KeyCode::KeyU, "U"),
+    (KeyCode::KeyV, "V"),
+    (KeyCode::KeyW, "W"),
+    (KeyCode::KeyX, "X"),
+    (KeyCode::KeyY, "Y"),
+    (KeyCode::KeyZ, "Z"),
+    (KeyCode::Key0, "0"),
+    (KeyCode::Key1, "1"),
+    (KeyCode::Key2, "2"),
+    (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"),
+    (KeyCode::Key5, "5"),
+    (KeyCode::Key6, "6"),
+    (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"),
+    (KeyCode::Key9, "9"),
+    (KeyCode::Minus, "-"),
+    (KeyCode::Equals, "="),
+    (KeyCode::LBracket, "["),
+    (KeyCode::RBracket, "]"),
+    (KeyCode::Semicolon, ";"),
+    (KeyCode::Quote, "'"),
+    (KeyCode::Backslash, "\\"),
+    (KeyCode::Comma, ","),
+    (KeyCode::Period, "."),
+    (KeyCode::Slash, "/"),
+    (KeyCode::Backtick, "`"),
+];
+
+// names that are easier to write in a config file than the symbols above
+const KEY_ALIASES: &[(KeyCode, &str)] = &[
+    (KeyCode::Escape, "Esc"),
+    (KeyCode::ReturnKey, "Return"),
+    (KeyCode::ArrowUp, "ArrowUp"),
+    (KeyCode::ArrowDown, "ArrowDown"),
+    (KeyCode::ArrowLeft, "ArrowLeft"),
+    (KeyCode::ArrowRight, "ArrowRight"),
+    (KeyCode::Minus, "Minus"),
+    (KeyCode::Equals, "Equals"),
+    (KeyCode::Equals, "Plus"),
+    (KeyCode::LBracket, "LBracket"),
+    (KeyCode::RBracket, "RBracket"),
+    (KeyCode::Semicolon, "Semicolon"),
+    (KeyCode::Quote, "Quote"),
+    (KeyCode::Backslash, "Backslash"),
+    (KeyCode::Comma, "Comma"),
+    (KeyCode::Period, "Period"),
+    (KeyCode::Slash, "Slash"),
+    (KeyCode::Backtick, "Backtick"),
+];
+
+impl KeyStroke {
+    pub fn new(key_code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self {key_code, modifiers}
+    }
+
+    pub fn from_key_event(e: &KeyEvent) -> Self {
+        Self {key_code: e.key_code, modifiers: e.modifiers}
+    }
+
+    // parses strokes like "Ctrl+Shift+P". Primary is the command key on apple platforms
+    // and control everywhere else
+    pub fn parse(text: &str, apple: bool) -> Result<Self, String> {
+        let mut modifiers = KeyModifiers::default();
+        let mut key_code = None;
+        for part in text.split('+').map( | part | part.trim()) {
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => modifiers.control = true,
+                "shift" => modifiers.shift = true,
+                "alt" | "option" => modifiers.alt = true,
+                "cmd" | "command" | "logo" | "super" | "meta" => modifiers.logo = true,
+                "primary" => if apple {modifiers.logo = true} else {modifiers.control = true},
+                _ => {
+                    if key_code.is_some() {
+                        return Err(format!("more than one key in {}", text))
+                    }
+                    let found = KEY_NAMES.iter().chain(KEY_ALIASES.iter())
+                        .find( | (_, name) | name.eq_ignore_ascii_case(part))
+                        .map( | (key_code, _) | *key_code);
+                    let Some(found) = found else {
+                        return Err(format!("unknown key {} in {}", part, text))
+                    };
+                    key_code = Some(found);
+                }
+            }
+        }
+        let Some(key_code) = key_code else {
+            return Err(format!("no key in {}", text))
+        };
+        Ok(Self {key_code, modifiers})
+    }
+
+    // how the stroke is shown in menus and the command palette
+    pub fn label(&self, apple: bool) -> String {
+        let key = KEY_NAMES.iter().find( | (key_code, _) | *key_code == self.key_code)
+            .map( | (_, name) | *name).unwrap_or("?");
+        let m = &self.modifiers;
+        if apple {
+            let mut out = String::new();
+            if m.control {out.push('⌃')}
+            if m.alt {out.push('⌥')}
+            if m.shift {out.push('⇧')}
+            if m.logo {out.push('⌘')}
+            out.push_str(key);
+            out
+        }
+        else {
+            let mut out = String::new();
+            if m.control {out.push_str("Ctrl+")}
+            if m.alt {out.push_str("Alt+")}
+            if m.shift {out.push_str("Shift+")}
+            if m.logo {out.push_str("Super+")}
+            out.push_str(key);
+            out
+        }
+    }
+}
+
+// one or more strokes pressed after each other, like "Ctrl+K Ctrl+C"
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeyChord(pub Vec<KeyStroke>);
+
+impl KeyChord {
+    pub fn parse(text: &str, apple: bool) -> Result<Self, String> {
+        let mut strokes = Vec::new();
+        for stroke in text.split_whitespace() {
+            strokes.push(KeyStroke::parse(stroke, apple)?);
+        }
+        Ok(Self(strokes))
+    }
+
+    pub fn label(&self, apple: bool) -> String {
+        self.0.iter().map( | stroke | stroke.label(apple)).collect::<Vec<_>>().join(" ")
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CommandInfo {
+    pub id: LiveId,
+    pub name: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct KeyBinding {
+    pub command: LiveId,
+    // an empty chord unbinds the command
+    pub chord: KeyChord,
+    // None applies everywhere, otherwise only while the focused widget announced this scope
+    pub scope: Option<LiveId>,
+    // what added the binding, so a keymap can be replaced as a whole
+    pub source: LiveId,
+}
+
+pub(crate) enum KeyBindingMatch {
+    None,
+    // the key started or broke off a chord
+    Consumed,
+    Command(LiveId),
+}
+
+#[derive(Default)]
+pub struct CxCommands {
+    pub(crate) commands: Vec<CommandInfo>,
+    pub(crate) bindings: Vec<KeyBinding>,
+    pub(crate) scope: Option<LiveId>,
+    pub(crate) pending: Vec<KeyStroke>,
+    pub(crate) queued: Vec<LiveId>,
+}
+
+impl CxCommands {
+    fn is_active(&self, binding: &KeyBinding) -> bool {
+        binding.source == KEYMAP_USER || !self.bindings.iter().any( | b | b.source == KEYMAP_USER && b.command == binding.command)
+    }
+
+    pub(crate) fn match_key_down(&mut self, e: &KeyEvent) -> KeyBindingMatch {
+        if let KeyCode::Control | KeyCode::Shift | KeyCode::Alt | KeyCode::Logo = e.key_code {
+            return KeyBindingMatch::None
+        }
+        let mut keys = std::mem::take(&mut self.pending);
+        let was_pending = !keys.is_empty();
+        keys.push(KeyStroke::from_key_event(e));
+        // the focused scope goes first, then the bindings that apply everywhere
+        let mut scopes = vec![None];
+        if self.scope.is_some() {
+            scopes.insert(0, self.scope);
+        }
+        for scope in scopes {
+            let mut is_prefix = false;
+            for binding in self.bindings.iter().filter( | b | b.scope == scope && self.is_active(b)) {
+                if binding.chord.0 == keys {
+                    return KeyBindingMatch::Command(binding.command)
+                }
+                if binding.chord.0.len() > keys.len() && binding.chord.0.starts_with(&keys) {
+                    is_prefix = true;
+                }
+            }
+            if is_prefix {
+                self.pending = keys;
+                return KeyBindingMatch::Consumed
+            }
+        }
+        if was_pending {
+            KeyBindingMatch::Consumed
+        }
+        else {
+            KeyBindingMatch::None
+        }
+    }
+}
+
+impl Cx {
+    fn uses_command_key(&self) -> bool {
+        matches!(self.os_type(), OsType::Macos | OsType::Ios)
+    }
+
+    // the modifiers Primary stands for in a keybinding
+    pub fn primary_key_modifiers(&self) -> KeyModifiers {
+        if self.uses_command_key() {
+            KeyModifiers {logo: true, ..Default::default()}
+        }
+        else {
+            KeyModifiers {control: true, ..Default::default()}
+        }
+    }
+
+    // registering gives a command the name the command palette and menus show
+    pub fn register_command(&mut self, id: LiveId, name: &str) {
+        if let Some(command) = self.commands.commands.iter_mut().find( | command | command.id == id) {
+            command.name = name.to_string();
+        }
+        else {
+            self.commands.commands.push(CommandInfo {id, name: name.to_string()});
+        }
+    }
+
+    pub fn commands(&self) -> &[CommandInfo] {
+        &self.commands.commands
+    }
+
+    pub fn command_name(&self, id: LiveId) -> Option<&str> {
+        self.commands.commands.iter().find( | command | command.id == id).map( | command | command.name.as_str())
+    }
+
+    pub fn parse_key_chord(&self, keys: &str) -> Result<KeyChord, String> {
+        KeyChord::parse(keys, self.uses_command_key())
+    }
+
+    pub fn bind_command_chord(&mut self, source: LiveId, command: LiveId, chord: KeyChord, scope: Option<LiveId>) {
+        self.commands.bindings.push(KeyBinding {command, chord, scope, source});
+    }
+
+    // keys can list alternatives like "Ctrl+S | Cmd+S", menus show the first one
+    pub fn bind_command(&mut self, source: LiveId, command: LiveId, keys: &str, scope: Option<LiveId>) -> Result<(), String> {
+        let chords = keys.split('|').map( | keys | self.parse_key_chord(keys)).collect::<Result<Vec<_>, _>>()?;
+        for chord in chords {
+            self.bind_command_chord(source, command, chord, scope);
+        }
+        Ok(())
+    }
+
+    pub fn clear_key_bindings(&mut self, source: LiveId) {
+        self.commands.bindings.retain( | binding | binding.source != source);
+        self.commands.pending.clear();
+    }
+
+    // the bindings that are in effect, user bindings have hidden the ones they replace
+    pub fn key_bindings(&self) -> impl Iterator<Item = &KeyBinding> {
+        self.commands.bindings.iter().filter(move | binding | self.commands.is_active(binding))
+    }
+
+    pub fn command_key_label(&self, command: LiveId) -> Option<String> {
+        self.key_bindings()
+            .find( | binding | binding.command == command && !binding.chord.0.is_empty())
+            .map( | binding | self.key_chord_label(&binding.chord))
+    }
+
+    // how this platform writes down a chord, like Ctrl+Shift+P or ⇧⌘P
+    pub fn key_chord_label(&self, chord: &KeyChord) -> String {
+        chord.label(self.uses_command_key())
+    }
+
+    // replaces the bindings of a source with ones from a keymap text like
+    //
+    //   # bindings before the first section apply everywhere
+    //   command_palette = Primary+Shift+P
+    //   [code_editor]
+    //   toggle_comment = Primary+K Primary+C
+    //   find = Ctrl+F | Cmd+F
+    //   word_wrap =
+    //
+    // where alternatives are separated by | and an empty right hand side unbinds the command
+    pub fn load_keymap(&mut self, source: LiveId, text: &str) -> Result<(), String> {
+        let mut bindings = Vec::new();
+        let mut scope = None;
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+            if let Some(section) = line.strip_prefix('[').and_then( | line | line.strip_suffix(']')) {
+                scope = Some(LiveId::from_str(section.trim()));
+                continue
+            }
+            let Some((command, keys)) = line.split_once('=') else {
+                return Err(format!("line {}: expected command = keys", index + 1))
+            };
+            for keys in keys.split('|') {
+                let chord = self.parse_key_chord(keys).map_err( | e | format!("line {}: {}", index + 1, e))?;
+                bindings.push(KeyBinding {
+                    command: LiveId::from_str(command.trim()),
+                    chord,
+                    scope,
+                    source
+                });
+            }
+        }
+        self.clear_key_bindings(source);
+        self.commands.bindings.extend(bindings);
+        Ok(())
+    }
+
+    pub fn load_keymap_file(&mut self, path: &str) -> Result<(), String> {
+        let text = fs::read_to_string(path).map_err( | e | format!("cannot read keymap {}: {}", path, e))?;
+        self.load_keymap(KEYMAP_USER, &text).map_err( | e | format!("{}: {}", path, e))
+    }
+
+    // widgets announce their scope when they get key focus, it is reset on every focus change
+    pub fn set_command_scope(&mut self, scope: Option<LiveId>) {
+        self.commands.scope = scope;
+    }
+
+    pub fn command_scope(&self) -> Option<LiveId> {
+        self.commands.scope
+    }
+
+    // runs a command as if its keys were pressed
+    pub fn send_command(&mut self, command: LiveId) {
+        self.commands.queued.push(command);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn stroke(key_code: KeyCode, control: bool, shift: bool, logo: bool) -> KeyStroke {
+        KeyStroke::new(key_code, KeyModifiers {control, shift, logo, ..Default::default()})
+    }
+    
+    fn key_down(cx: &mut Cx, keys: &str) -> Vec<Option<LiveId>> {
+        let chord = cx.parse_key_chord(keys).unwrap();
+        chord.0.iter().map( | stroke | {
+            let e = KeyEvent {key_code: stroke.key_code, is_repeat: false, modifiers: stroke.modifiers, time: 0.0};
+            match cx.commands.match_key_down(&e) {
+                KeyBindingMatch::None => None,
+                KeyBindingMatch::Consumed => Some(LiveId(0)),
+                KeyBindingMatch::Command(command) => Some(command),
+            }
+        }).collect()
+    }
+    
+    fn cx(os_type: OsType) -> Cx {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        cx.os_type = os_type;
+        cx
+    }
+    
+    #[test]
+    fn parses_key_strokes() {
+        assert_eq!(KeyStroke::parse("Ctrl+Shift+P", false), Ok(stroke(KeyCode::KeyP, true, true, false)));
+        assert_eq!(KeyStroke::parse(" shift + ctrl + p ", false), Ok(stroke(KeyCode::KeyP, true, true, false)));
+        assert_eq!(KeyStroke::parse("Primary+S", false), Ok(stroke(KeyCode::KeyS, true, false, false)));
+        assert_eq!(KeyStroke::parse("Primary+S", true), Ok(stroke(KeyCode::KeyS, false, false, true)));
+        assert_eq!(KeyStroke::parse("Cmd+Plus", false), Ok(stroke(KeyCode::Equals, false, false, true)));
+        assert_eq!(KeyStroke::parse("Esc", false), Ok(stroke(KeyCode::Escape, false, false, false)));
+        assert_eq!(KeyStroke::parse("F12", false), Ok(stroke(KeyCode::F12, false, false, false)));
+        assert!(KeyStroke::parse("Ctrl+Shift", false).is_err());
+        assert!(KeyStroke::parse("Ctrl+A+B", false).is_err());
+        assert!(KeyStroke::parse("Ctrl+Hyper", false).is_err());
+        assert_eq!(stroke(KeyCode::KeyP, true, true, false).label(false), "Ctrl+Shift+P");
+        assert_eq!(stroke(KeyCode::KeyP, false, true, true).label(true), "⇧⌘P");
+    }
+    
+    #[test]
+    fn parses_key_chords() {
+        assert_eq!(KeyChord::parse("Ctrl+K Ctrl+C", false), Ok(KeyChord(vec![
+            stroke(KeyCode::KeyK, true, false, false),
+            stroke(KeyCode::KeyC, true, false, false),
+        ])));
+        assert_eq!(KeyChord::parse("  ", false), Ok(KeyChord(vec![])));
+        assert!(KeyChord::parse("Ctrl+K Ctrl+", false).is_err());
+        assert_eq!(KeyChord::parse("Primary+K Primary+C", true).unwrap().label(true), "⌘K ⌘C");
+    }
+    
+    #[test]
+    fn matches_chords_and_their_prefixes() {
+        let mut cx = cx(OsType::LinuxDirect);
+        cx.bind_command(live_id!(app), live_id!(toggle_comment), "Ctrl+K Ctrl+C", None).unwrap();
+        cx.bind_command(live_id!(app), live_id!(save), "Ctrl+S", None).unwrap();
+        assert_eq!(key_down(&mut cx, "Ctrl+S"), vec![Some(live_id!(save))]);
+        // the first stroke of a chord waits for the next one
+        assert_eq!(key_down(&mut cx, "Ctrl+K Ctrl+C"), vec![Some(LiveId(0)), Some(live_id!(toggle_comment))]);
+        // a stroke that doesnt continue the chord breaks it off without doing anything
+        assert_eq!(key_down(&mut cx, "Ctrl+K Ctrl+S Ctrl+S"), vec![Some(LiveId(0)), Some(LiveId(0)), Some(live_id!(save))]);
+        // modifiers on their own dont break off a chord
+        key_down(&mut cx, "Ctrl+K");
+        let e = KeyEvent {key_code: KeyCode::Control, is_repeat: false, modifiers: KeyModifiers {control: true, ..Default::default()}, time: 0.0};
+        assert!(matches!(cx.commands.match_key_down(&e), KeyBindingMatch::None));
+        assert_eq!(key_down(&mut cx, "Ctrl+C"), vec![Some(live_id!(toggle_comment))]);
+        assert_eq!(key_down(&mut cx, "Ctrl+C"), vec![None]);
+        // alternatives are all bound, or none of them when one doesnt parse
+        cx.bind_command(live_id!(app), live_id!(recompile), "Ctrl+Backtick | Cmd+Backtick", None).unwrap();
+        assert_eq!(key_down(&mut cx, "Ctrl+Backtick"), vec![Some(live_id!(recompile))]);
+        assert_eq!(key_down(&mut cx, "Cmd+Backtick"), vec![Some(live_id!(recompile))]);
+        assert!(cx.bind_command(live_id!(app), live_id!(reload), "Ctrl+R | Cmd+Nope", None).is_err());
+        assert_eq!(key_down(&mut cx, "Ctrl+R"), vec![None]);
+    }
+    
+    #[test]
+    fn prefers_bindings_of_the_focused_scope() {
+        let mut cx = cx(OsType::LinuxDirect);
+        cx.bind_command(live_id!(app), live_id!(find), "Ctrl+F", None).unwrap();
+        cx.bind_command(live_id!(app), live_id!(find_in_file), "Ctrl+F", Some(live_id!(code_editor))).unwrap();
+        cx.bind_command(live_id!(app), live_id!(fold), "Ctrl+K Ctrl+0", Some(live_id!(code_editor))).unwrap();
+        cx.bind_command(live_id!(app), live_id!(clear), "Ctrl+K", None).unwrap();
+        assert_eq!(key_down(&mut cx, "Ctrl+F"), vec![Some(live_id!(find))]);
+        assert_eq!(key_down(&mut cx, "Ctrl+K"), vec![Some(live_id!(clear))]);
+        cx.set_command_scope(Some(live_id!(code_editor)));
+        assert_eq!(key_down(&mut cx, "Ctrl+F"), vec![Some(live_id!(find_in_file))]);
+        // a chord of the scope wins over a binding of the same stroke that applies everywhere
+        assert_eq!(key_down(&mut cx, "Ctrl+K Ctrl+0"), vec![Some(LiveId(0)), Some(live_id!(fold))]);
+        cx.set_command_scope(Some(live_id!(log_list)));
+        assert_eq!(key_down(&mut cx, "Ctrl+F"), vec![Some(live_id!(find))]);
+    }
+    
+    #[test]
+    fn user_keymaps_replace_and_unbind_commands() {
+        let mut cx = cx(OsType::Macos);
+        cx.load_keymap(live_id!(app), "
+            # the defaults
+            save = Primary+S
+            close = Primary+W
+            italic = Primary+I
+            [code_editor]
+            toggle_comment = Primary+K Primary+C
+            word_wrap = Alt+Z
+        ").unwrap();
+        assert_eq!(key_down(&mut cx, "Cmd+S"), vec![Some(live_id!(save))]);
+        assert_eq!(cx.command_key_label(live_id!(close)).as_deref(), Some("⌘W"));
+        
+        cx.load_keymap(KEYMAP_USER, "
+            save = Ctrl+S
+            close = Ctrl+W | Cmd+Shift+W
+            [code_editor]
+            word_wrap =
+        ").unwrap();
+        // the user binding replaces the default of its command only
+        assert_eq!(key_down(&mut cx, "Cmd+S"), vec![None]);
+        assert_eq!(key_down(&mut cx, "Ctrl+S"), vec![Some(live_id!(save))]);
+        assert_eq!(key_down(&mut cx, "Cmd+I"), vec![Some(live_id!(italic))]);
+        // every alternative is bound, the first one is shown
+        assert_eq!(key_down(&mut cx, "Cmd+W"), vec![None]);
+        assert_eq!(key_down(&mut cx, "Ctrl+W"), vec![Some(live_id!(close))]);
+        assert_eq!(key_down(&mut cx, "Cmd+Shift+W"), vec![Some(live_id!(close))]);
+        assert_eq!(cx.command_key_label(live_id!(close)).as_deref(), Some("⌃W"));
+        // an empty right hand side unbinds the command
+        cx.set_command_scope(Some(live_id!(code_editor)));
+        assert_eq!(key_down(&mut cx, "Alt+Z"), vec![None]);
+        assert_eq!(cx.command_key_label(live_id!(word_wrap)), None);
+        assert_eq!(key_down(&mut cx, "Cmd+K Cmd+C"), vec![Some(LiveId(0)), Some(live_id!(toggle_comment))]);
+        
+        // loading the user keymap again replaces it as a whole
+        cx.load_keymap(KEYMAP_USER, "").unwrap();
+        assert_eq!(key_down(&mut cx, "Cmd+S"), vec![Some(live_id!(save))]);
+        assert_eq!(key_down(&mut cx, "Alt+Z"), vec![Some(live_id!(word_wrap))]);
+        
+        // a keymap with an error leaves the bindings as they were
+        assert_eq!(cx.load_keymap(KEYMAP_USER, "save = Ctrl+S\nclose\n"), Err("line 2: expected command = keys".to_string()));
+        assert!(cx.load_keymap(KEYMAP_USER, "save = Ctrl+Nope").unwrap_err().starts_with("line 1:"));
+        assert_eq!(key_down(&mut cx, "Cmd+S"), vec![Some(live_id!(save))]);
+    }
+}
@@ -31,6 +31,8 @@ use {
             NextFrame,
         },
         action::ActionsBuf,
+        accessibility::AccessTree,
+        command::CxCommands,
         cx_api::CxOsOp,
         area::Area,
         gpu_info::GpuInfo,
@@ -114,6 +116,18 @@ pub struct Cx {
     pub(crate) spawner: Spawner,
     
     pub(crate) studio_web_socket: Option<WebSocket>,
+    // studio is picking widgets, so clicks are turned into designer picks
+    pub(crate) designer_pick_mode: bool,
+    // studio asked for the widget tree, which the windows send when they draw next
+    pub(crate) widget_tree_requested: bool,
+    // studio asked for draw and memory samples, which are sent after the next draw
+    pub(crate) draw_samples_requested: bool,
+    // a screen reader is listening, so the windows publish their accessibility tree
+    pub(crate) accessibility_enabled: bool,
+    pub(crate) access_trees: Vec<AccessTree>,
+    pub(crate) access_trees_changed: bool,
+    // the command registry with its keybindings
+    pub(crate) commands: CxCommands,
     
     pub performance_stats: PerformanceStats,
 }
@@ -239,6 +253,13 @@ impl Cx {
             ime_area: Default::default(),
             platform_ops: Default::default(),
             studio_web_socket: None,
+            designer_pick_mode: false,
+            widget_tree_requested: false,
+            draw_samples_requested: false,
+            accessibility_enabled: false,
+            access_trees: Vec::new(),
+            access_trees_changed: false,
+            commands: Default::default(),
             
             new_next_frames: Default::default(),
             
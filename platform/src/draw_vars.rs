@@ -20,7 +20,7 @@ use {
         texture::{Texture},
         geometry::GeometryId,
         area::Area,
-        geometry::{GeometryFields},
+        geometry::{Geometry, GeometryFields},
         live_traits::*,
         draw_shader::*
     },
@@ -138,6 +138,10 @@ impl DrawVars {
     pub fn empty_texture(&mut self, slot: usize) {
         self.texture_slots[slot] = None;
     }
+    
+    pub fn set_geometry(&mut self, geometry: &Geometry) {
+        self.geometry_id = Some(geometry.geometry_id());
+    }
 
     pub fn redraw(&self, cx: &mut Cx) {
         self.area.redraw(cx);
@@ -1,10 +1,14 @@
 use {
     std::{
+        cell::Cell,
         collections::{HashSet, HashMap}
     },
     crate::{
         //makepad_live_compiler::LiveEditEvent,
+        makepad_live_compiler::LiveTokenId,
         makepad_live_id::LiveId,
+        makepad_math::DVec2,
+        studio::{AppToStudio, DesignerPick},
         cx::Cx,
         area::Area,
         //midi::{Midi1InputData, MidiInputInfo},
@@ -149,6 +153,8 @@ pub enum Event {
     Signal,
     Trigger(TriggerEvent),
     MacosMenuCommand(LiveId),
+    /// A command from the keybindings, a menu or [`Cx::send_command`](crate::Cx::send_command).
+    Command(LiveId),
     KeyFocus(KeyFocusEvent),
     KeyFocusLost(KeyFocusEvent),
     KeyDown(KeyEvent),
@@ -175,6 +181,11 @@ pub enum Event {
     TextureHandleReady(TextureHandleReadyEvent),
 
     BackPressed,
+    /// A click while studio is picking widgets in the running application.
+    ///
+    /// It is sent instead of `MouseDown`, and the innermost widget under the click that knows
+    /// where it is defined in `live_design!` picks itself with [`DesignerPickEvent::pick`].
+    DesignerPick(DesignerPickEvent),
     #[cfg(target_arch = "wasm32")]
     ToWasmMsg(ToWasmMsgEvent),
 }
@@ -246,6 +257,8 @@ impl Event{
             48=>"MouseLeave",
             49=>"Actions",
             50=>"BackPressed",
+            52=>"DesignerPick",
+            53=>"Command",
 
             #[cfg(target_arch = "wasm32")]
             51=>"ToWasmMsg",
@@ -315,6 +328,8 @@ impl Event{
             Self::MouseLeave(_)=>48,
             Self::Actions(_)=>49,
             Self::BackPressed=>50,
+            Self::DesignerPick(_)=>52,
+            Self::Command(_)=>53,
 
             #[cfg(target_arch = "wasm32")]
             Self::ToWasmMsg(_)=>51,
@@ -361,12 +376,41 @@ impl Event{
             Self::MouseMove(_)|
             Self::MouseUp(_)|
             Self::TouchUpdate(_)|
-            Self::Scroll(_)=>true,
+            Self::Scroll(_)|
+            Self::DesignerPick(_)=>true,
             _=>false
         }
     }
 }
 
+#[derive(Debug)]
+pub struct DesignerPickEvent {
+    pub abs: DVec2,
+    pub picked: Cell<bool>,
+}
+
+impl DesignerPickEvent {
+    /// Tells studio where the widget defined by `token_id` is in the source, unless another
+    /// widget picked itself first. Returns whether this one was picked.
+    pub fn pick(&self, cx: &Cx, token_id: LiveTokenId) -> bool {
+        if self.picked.get() {
+            return false
+        }
+        let Some(file_id) = token_id.file_id() else {
+            return false
+        };
+        let live_registry = cx.live_registry.borrow();
+        let span = live_registry.token_id_to_span(token_id);
+        Cx::send_studio_message(AppToStudio::DesignerPick(DesignerPick {
+            file_name: live_registry.file_id_to_file_name(file_id).to_string(),
+            line: span.start.line,
+            column: span.start.column,
+        }));
+        self.picked.set(true);
+        true
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TriggerEvent {
     pub triggers: HashMap<Area, Vec<Trigger>>
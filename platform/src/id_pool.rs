@@ -99,4 +99,9 @@ impl<T> IdPool<T> where T: Default {
             self.alloc_new(Some(item))
         }
     }
+    
+    // the item was freed and is waiting to be reused
+    pub fn is_free(&self, id: usize) -> bool {
+        self.free.0.borrow().contains(&id)
+    }
 }
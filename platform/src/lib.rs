@@ -43,6 +43,8 @@ mod debug;
 mod component_map;
 mod performance_stats;
 pub mod studio;
+pub mod accessibility;
+pub mod command;
 
 pub mod web_socket;
 
@@ -140,6 +142,23 @@ pub use {
             CxRef,
             OsType
         },
+        accessibility::{
+            AccessRole,
+            AccessRange,
+            AccessProps,
+            AccessNode,
+            AccessTree,
+            AccessRequest,
+            access_id,
+            next_tab_stop,
+        },
+        command::{
+            KeyStroke,
+            KeyChord,
+            KeyBinding,
+            CommandInfo,
+            KEYMAP_USER,
+        },
         area::{
             Area,
             RectArea,
@@ -219,7 +238,8 @@ pub use {
             CxPassRect,
             Pass,
             PassClearColor,
-            PassClearDepth
+            PassClearDepth,
+            PassMatrixMode
         },
         texture::{
             Texture,
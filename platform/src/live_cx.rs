@@ -17,9 +17,12 @@ use {
             LiveFileId,
         },
         makepad_live_compiler::LiveTypeInfo,
+        makepad_micro_serde::*,
         /*makepad_math::*,*/
         cx::Cx,
         cx::CxDependency,
+        studio::{StudioToApp, StudioToAppVec},
+        web_socket::WebSocketMessage,
     },
 };
 
@@ -230,6 +233,38 @@ impl Cx {
         while let Ok(changes) = self.live_file_change_receiver.try_recv(){
             all_changes.extend(changes);
         }
+        // messages from studio, such as live changes for apps that do not run over stdin
+        let mut widget_tree_requested = false;
+        if let Some(web_socket) = &self.studio_web_socket {
+            while let Ok(message) = web_socket.rx_receiver.try_recv(){
+                let WebSocketMessage::Binary(data) = message else {
+                    continue
+                };
+                let Ok(messages) = StudioToAppVec::deserialize_bin(&data) else {
+                    continue
+                };
+                for message in messages.0 {
+                    match message {
+                        StudioToApp::LiveChange{file_name, content} => {
+                            all_changes.push(LiveFileChange{file_name, content});
+                        }
+                        StudioToApp::DesignerPickMode(enabled) => {
+                            self.designer_pick_mode = enabled;
+                        }
+                        StudioToApp::RequestWidgetTree => {
+                            widget_tree_requested = true;
+                        }
+                        StudioToApp::RequestDrawSamples => {
+                            self.draw_samples_requested = true;
+                        }
+                    }
+                }
+            }
+        }
+        if widget_tree_requested {
+            self.widget_tree_requested = true;
+            self.redraw_all();
+        }
         if all_changes.len()>0{
             let mut live_registry = self.live_registry.borrow_mut();
             let mut errs = Vec::new();
@@ -417,7 +417,9 @@ impl Cx {
                 self.call_event_handler(&Event::Timer(e))
             }
             MacosEvent::MacosMenuCommand(e) => {
-                self.call_event_handler(&Event::MacosMenuCommand(e))
+                self.call_event_handler(&Event::MacosMenuCommand(e));
+                // the menu handled the key equivalent, so this is the only place the command arrives
+                self.call_event_handler(&Event::Command(e))
             }
         }
         
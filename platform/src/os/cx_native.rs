@@ -21,6 +21,15 @@ pub enum EventFlow{
 
 impl Cx {
     
+    /// Packaged linux apps keep their resources in ../share/<binary>/makepad next to the binary
+    #[cfg(linux_package)]
+    pub fn native_package_root() -> Option<String> {
+        let exe = std::env::current_exe().ok()?;
+        let bin_dir = exe.parent()?;
+        let name = exe.file_name()?.to_str()?;
+        Some(format!("{}/../share/{}/makepad", bin_dir.display(), name))
+    }
+    
     pub fn native_load_dependencies(&mut self){
         for (path,dep) in &mut self.dependencies{
             if let Ok(mut file_handle) = File::open(path) {
@@ -1,6 +1,9 @@
 
 use {
-    std::collections::{HashSet, HashMap},
+    std::{
+        cell::Cell,
+        collections::{HashSet, HashMap},
+    },
     crate::{
         cx_api::CxOsApi,
         cx::Cx,
@@ -14,8 +17,10 @@ use {
             Event,
             KeyFocusEvent,
             NextFrameEvent,
+            DesignerPickEvent,
         },
         studio::{AppToStudio,EventSample},
+        command::KeyBindingMatch,
     }
 };
 
@@ -130,8 +135,10 @@ impl Cx {
         }
     }
     
-    fn inner_key_focus_change(&mut self) {
+    pub (crate) fn inner_key_focus_change(&mut self) {
         if let Some((prev, focus)) = self.keyboard.cycle_key_focus_changed(){
+            // the newly focused widget announces its command scope again
+            self.commands.scope = None;
             self.inner_call_event_handler(&Event::KeyFocus(KeyFocusEvent {
                 prev,
                 focus
@@ -173,11 +180,52 @@ impl Cx {
         }
     }
     
+    pub fn handle_commands(&mut self) {
+        let mut counter = 0;
+        while self.commands.queued.len() != 0 {
+            counter += 1;
+            let queued = std::mem::take(&mut self.commands.queued);
+            for command in queued {
+                self.inner_call_event_handler(&Event::Command(command));
+                self.inner_key_focus_change();
+                self.handle_triggers();
+                self.handle_actions();
+            }
+            if counter > 100 {
+                crate::error!("Command feedback loop detected");
+                break
+            }
+        }
+    }
+    
     pub (crate) fn call_event_handler(&mut self, event: &Event) {
+        if self.designer_pick_mode {
+            match event {
+                Event::MouseDown(e) => {
+                    let pick = Event::DesignerPick(DesignerPickEvent {
+                        abs: e.abs,
+                        picked: Cell::new(false),
+                    });
+                    return self.call_event_handler(&pick)
+                }
+                Event::MouseUp(_) => return,
+                _ => ()
+            }
+        }
+        if let Event::KeyDown(e) = event {
+            match self.commands.match_key_down(e) {
+                KeyBindingMatch::Command(command) => {
+                    return self.call_event_handler(&Event::Command(command))
+                }
+                KeyBindingMatch::Consumed => return,
+                KeyBindingMatch::None => ()
+            }
+        }
         self.inner_call_event_handler(event);
         self.inner_key_focus_change();
         self.handle_triggers();
         self.handle_actions();
+        self.handle_commands();
     }
 
     // helpers
@@ -194,6 +242,7 @@ impl Cx {
         let mut draw_event = DrawEvent::default();
         std::mem::swap(&mut draw_event, &mut self.new_draw_event);
         self.call_event_handler(&Event::Draw(draw_event));
+        self.widget_tree_requested = false;
     }
 
     pub (crate) fn call_next_frame_event(&mut self, time: f64) {
@@ -0,0 +1,735 @@
+use {
+    std::{
+        io,
+        sync::{Arc, Mutex},
+    },
+    self::super::dbus::{
+        DbusConnection,
+        DbusMessage,
+        DbusSender,
+        DbusValue,
+        SIGNAL,
+    },
+    crate::{
+        makepad_math::*,
+        accessibility::{AccessNode, AccessRequest, AccessRole, AccessTree},
+    }
+};
+
+// bridges the accessibility trees of the windows to at-spi. a thread connects to the accessibility
+// bus, embeds the application under the registry and answers the queries of screen readers from
+// the last trees the ui thread handed over. focus changes are sent as signals from the ui thread
+
+const ROOT_PATH: &str = "/org/a11y/atspi/accessible/root";
+const NODE_PATH: &str = "/org/a11y/atspi/accessible/";
+const NULL_PATH: &str = "/org/a11y/atspi/null";
+
+const ACCESSIBLE: &str = "org.a11y.atspi.Accessible";
+const APPLICATION: &str = "org.a11y.atspi.Application";
+const COMPONENT: &str = "org.a11y.atspi.Component";
+const VALUE: &str = "org.a11y.atspi.Value";
+const ACTION: &str = "org.a11y.atspi.Action";
+const PROPERTIES: &str = "org.freedesktop.DBus.Properties";
+const INTROSPECTABLE: &str = "org.freedesktop.DBus.Introspectable";
+const EVENT_OBJECT: &str = "org.a11y.atspi.Event.Object";
+
+// AtspiStateType
+const STATE_ACTIVE: u32 = 1;
+const STATE_CHECKED: u32 = 4;
+const STATE_EDITABLE: u32 = 7;
+const STATE_ENABLED: u32 = 8;
+const STATE_EXPANDABLE: u32 = 9;
+const STATE_EXPANDED: u32 = 10;
+const STATE_FOCUSABLE: u32 = 11;
+const STATE_FOCUSED: u32 = 12;
+const STATE_SELECTABLE: u32 = 22;
+const STATE_SELECTED: u32 = 23;
+const STATE_SENSITIVE: u32 = 24;
+const STATE_SHOWING: u32 = 25;
+const STATE_SINGLE_LINE: u32 = 26;
+const STATE_VISIBLE: u32 = 30;
+const STATE_CHECKABLE: u32 = 41;
+
+// AtspiRole
+fn atspi_role(role: AccessRole) -> u32 {
+    match role {
+        AccessRole::Window => 23,
+        AccessRole::Group => 39,
+        AccessRole::Label => 29,
+        AccessRole::Button => 43,
+        AccessRole::CheckBox => 7,
+        AccessRole::RadioButton => 44,
+        AccessRole::TextInput => 79,
+        AccessRole::DropDown => 11,
+        AccessRole::Slider => 51,
+        AccessRole::ListItem => 32,
+        AccessRole::Link => 88,
+        AccessRole::Tab => 37,
+        AccessRole::Image => 27,
+    }
+}
+
+const ROLE_APPLICATION: u32 = 75;
+
+#[derive(Default)]
+struct AtspiState {
+    connected: bool,
+    app_name: String,
+    bus_name: String,
+    // the registry object the application is embedded under
+    parent: (String, String),
+    trees: Vec<AccessTree>,
+    focus: Option<u64>,
+    requests: Vec<AccessRequest>,
+}
+
+#[derive(Clone, Copy)]
+enum Target {
+    App,
+    Node(usize, usize),
+}
+
+pub struct AtspiBridge {
+    state: Arc<Mutex<AtspiState>>,
+    sender: Arc<Mutex<Option<Arc<DbusSender>>>>,
+}
+
+impl AtspiBridge {
+    // like gtk and qt the bridge is left out when NO_AT_BRIDGE=1 is set, or when there is no bus
+    pub fn start(app_name: &str) -> Option<Self> {
+        if std::env::var("NO_AT_BRIDGE").map_or(false, | value | value == "1") {
+            return None
+        }
+        if std::env::var_os("AT_SPI_BUS_ADDRESS").is_none() && std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_none() {
+            return None
+        }
+        let state = Arc::new(Mutex::new(AtspiState {
+            app_name: app_name.to_string(),
+            ..Default::default()
+        }));
+        let sender = Arc::new(Mutex::new(None));
+        let bridge = Self {state: state.clone(), sender: sender.clone()};
+        std::thread::spawn(move || {
+            // a screen reader can be started after the application, so the bus is connected to
+            // again every time accessibility is switched on
+            loop {
+                let Ok(mut conn) = connect() else {
+                    return
+                };
+                if let Err(err) = embed(&mut conn, &state) {
+                    crate::warning!("Accessibility: cannot embed into the at-spi registry {}", err);
+                    return
+                }
+                *sender.lock().unwrap() = Some(conn.sender());
+                state.lock().unwrap().connected = true;
+                while let Ok(msg) = conn.read() {
+                    if !msg.expects_reply() {
+                        continue
+                    }
+                    let reply = {
+                        let mut state = state.lock().unwrap();
+                        handle_call(&mut state, &msg)
+                    };
+                    if conn.send(&reply).is_err() {
+                        break
+                    }
+                }
+                *sender.lock().unwrap() = None;
+                state.lock().unwrap().connected = false;
+                // a fixed address doesnt come back once its bus is gone
+                if std::env::var_os("AT_SPI_BUS_ADDRESS").is_some() {
+                    return
+                }
+            }
+        });
+        Some(bridge)
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.state.lock().unwrap().connected
+    }
+
+    pub fn take_requests(&self) -> Vec<AccessRequest> {
+        std::mem::take(&mut self.state.lock().unwrap().requests)
+    }
+
+    pub fn update_trees(&self, trees: &[AccessTree]) {
+        let mut signals = Vec::new();
+        {
+            let mut state = self.state.lock().unwrap();
+            // screen readers read out changes of the focused node without asking again
+            if let Some(focus) = state.focus {
+                let old = find_node(&state.trees, focus).map( | node | node.props.clone());
+                let new = find_node(trees, focus).map( | node | node.props.clone());
+                if let (Some(old), Some(new)) = (old, new) {
+                    let path = node_path(focus);
+                    if old.name != new.name {
+                        signals.push(property_change(&path, "accessible-name", DbusValue::str(&new.name)));
+                    }
+                    if old.value != new.value || old.range != new.range {
+                        signals.push(property_change(&path, "accessible-value", DbusValue::str(new.value.as_deref().unwrap_or(""))));
+                    }
+                    if old.checked != new.checked {
+                        signals.push(state_changed(&path, "checked", new.checked == Some(true)));
+                    }
+                }
+            }
+            let new_windows = trees.len() != state.trees.len();
+            state.trees = trees.to_vec();
+            if new_windows {
+                signals.push(DbusMessage::signal(ROOT_PATH, EVENT_OBJECT, "ChildrenChanged", event_body("add", 0, 0, object_ref(&state, Target::App))));
+            }
+        }
+        self.send_signals(signals);
+    }
+
+    pub fn update_focus(&self, focus: Option<u64>) {
+        let mut signals = Vec::new();
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.focus == focus {
+                return
+            }
+            if let Some(old) = state.focus {
+                signals.push(state_changed(&node_path(old), "focused", false));
+            }
+            if let Some(new) = focus {
+                if find_node(&state.trees, new).is_some() {
+                    signals.push(state_changed(&node_path(new), "focused", true));
+                }
+            }
+            state.focus = focus;
+        }
+        self.send_signals(signals);
+    }
+
+    fn send_signals(&self, signals: Vec<DbusMessage>) {
+        if signals.is_empty() {
+            return
+        }
+        if let Some(sender) = &*self.sender.lock().unwrap() {
+            for signal in &signals {
+                let _ = sender.send(signal);
+            }
+        }
+    }
+}
+
+// waits until accessibility is enabled and connects to the accessibility bus
+fn connect() -> io::Result<DbusConnection> {
+    if let Ok(address) = std::env::var("AT_SPI_BUS_ADDRESS") {
+        return DbusConnection::open(&address)
+    }
+    let mut session = DbusConnection::session()?;
+    // subscribing before asking for the current value, so a change in between isnt missed
+    session.call(DbusMessage::method_call(
+        "org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        "org.freedesktop.DBus",
+        "AddMatch",
+        vec![DbusValue::str("type='signal',interface='org.freedesktop.DBus.Properties',member='PropertiesChanged',path='/org/a11y/bus',arg0='org.a11y.Status'")]
+    ))?;
+    let enabled = session.call(DbusMessage::method_call(
+        "org.a11y.Bus",
+        "/org/a11y/bus",
+        PROPERTIES,
+        "Get",
+        vec![DbusValue::str("org.a11y.Status"), DbusValue::str("IsEnabled")]
+    ))?;
+    if enabled.body.first().and_then( | v | v.as_bool()) != Some(true) {
+        loop {
+            let msg = session.read()?;
+            if is_enabled_change(&msg) == Some(true) {
+                break
+            }
+        }
+    }
+    let reply = session.call(DbusMessage::method_call(
+        "org.a11y.Bus",
+        "/org/a11y/bus",
+        "org.a11y.Bus",
+        "GetAddress",
+        vec![]
+    ))?;
+    let address = reply.body.first().and_then( | v | v.as_str()).unwrap_or("").to_string();
+    DbusConnection::open(&address)
+}
+
+// the new value of IsEnabled when a message is the signal that it changed
+fn is_enabled_change(msg: &DbusMessage) -> Option<bool> {
+    if msg.kind != SIGNAL || msg.interface() != PROPERTIES || msg.member() != "PropertiesChanged" {
+        return None
+    }
+    if msg.body.first()?.as_str()? != "org.a11y.Status" {
+        return None
+    }
+    let DbusValue::Array(_, changed) = msg.body.get(1)? else {
+        return None
+    };
+    changed.iter().find_map( | entry | match entry {
+        DbusValue::DictEntry(key, value) if key.as_str() == Some("IsEnabled") => value.as_bool(),
+        _ => None
+    })
+}
+
+fn embed(conn: &mut DbusConnection, state: &Arc<Mutex<AtspiState>>) -> io::Result<()> {
+    state.lock().unwrap().bus_name = conn.unique_name.clone();
+    let reply = conn.call(DbusMessage::method_call(
+        "org.a11y.atspi.Registry",
+        ROOT_PATH,
+        "org.a11y.atspi.Socket",
+        "Embed",
+        vec![DbusValue::Struct(vec![DbusValue::str(&conn.unique_name), DbusValue::path(ROOT_PATH)])]
+    ))?;
+    if let Some(DbusValue::Struct(parent)) = reply.body.first() {
+        if let (Some(name), Some(path)) = (parent.get(0).and_then( | v | v.as_str()), parent.get(1).and_then( | v | v.as_str())) {
+            state.lock().unwrap().parent = (name.to_string(), path.to_string());
+        }
+    }
+    Ok(())
+}
+
+fn node_path(id: u64) -> String {
+    format!("{}{}", NODE_PATH, id)
+}
+
+fn find_node(trees: &[AccessTree], id: u64) -> Option<&AccessNode> {
+    trees.iter().find_map( | tree | tree.node(id))
+}
+
+fn find_target(state: &AtspiState, path: &str) -> Option<Target> {
+    if path == ROOT_PATH {
+        return Some(Target::App)
+    }
+    let id: u64 = path.strip_prefix(NODE_PATH)?.parse().ok()?;
+    state.trees.iter().enumerate().find_map( | (t, tree) | tree.index_of(id).map( | n | Target::Node(t, n)))
+}
+
+fn object_ref(state: &AtspiState, target: Target) -> DbusValue {
+    let path = match target {
+        Target::App => ROOT_PATH.to_string(),
+        Target::Node(t, n) => node_path(state.trees[t].nodes[n].id),
+    };
+    DbusValue::Struct(vec![DbusValue::str(&state.bus_name), DbusValue::ObjectPath(path)])
+}
+
+fn null_ref() -> DbusValue {
+    DbusValue::Struct(vec![DbusValue::str(""), DbusValue::path(NULL_PATH)])
+}
+
+fn event_body(kind: &str, detail1: i32, detail2: i32, value: DbusValue) -> Vec<DbusValue> {
+    vec![
+        DbusValue::str(kind),
+        DbusValue::Int32(detail1),
+        DbusValue::Int32(detail2),
+        DbusValue::variant(value),
+        DbusValue::Array("{sv}".into(), vec![]),
+    ]
+}
+
+fn state_changed(path: &str, state: &str, on: bool) -> DbusMessage {
+    DbusMessage::signal(path, EVENT_OBJECT, "StateChanged", event_body(state, on as i32, 0, DbusValue::Int32(0)))
+}
+
+fn property_change(path: &str, property: &str, value: DbusValue) -> DbusMessage {
+    DbusMessage::signal(path, EVENT_OBJECT, "PropertyChange", event_body(property, 0, 0, value))
+}
+
+fn children(state: &AtspiState, target: Target) -> Vec<Target> {
+    match target {
+        Target::App => (0..state.trees.len()).map( | t | Target::Node(t, 0)).collect(),
+        Target::Node(t, n) => state.trees[t].nodes[n].children.iter().map( | c | Target::Node(t, *c)).collect(),
+    }
+}
+
+fn parent(state: &AtspiState, target: Target) -> DbusValue {
+    match target {
+        Target::App => {
+            if state.parent.0.is_empty() {
+                return null_ref()
+            }
+            DbusValue::Struct(vec![DbusValue::str(&state.parent.0), DbusValue::path(&state.parent.1)])
+        }
+        Target::Node(t, n) => match state.trees[t].nodes[n].parent {
+            Some(p) => object_ref(state, Target::Node(t, p)),
+            None => object_ref(state, Target::App),
+        }
+    }
+}
+
+fn index_in_parent(state: &AtspiState, target: Target) -> i32 {
+    match target {
+        Target::App => -1,
+        Target::Node(t, n) => match state.trees[t].nodes[n].parent {
+            Some(p) => state.trees[t].nodes[p].children.iter().position( | c | *c == n).map_or(-1, | i | i as i32),
+            None => t as i32,
+        }
+    }
+}
+
+fn states(state: &AtspiState, target: Target) -> DbusValue {
+    let mut bits = 0u64;
+    let mut set = | s: u32 | bits |= 1 << s;
+    match target {
+        Target::App => (),
+        Target::Node(t, n) => {
+            let node = &state.trees[t].nodes[n];
+            if !node.props.disabled {
+                set(STATE_ENABLED);
+                set(STATE_SENSITIVE);
+            }
+            if node.bounds.size.x > 0.0 && node.bounds.size.y > 0.0 {
+                set(STATE_VISIBLE);
+                set(STATE_SHOWING);
+            }
+            if node.role == AccessRole::Window {
+                set(STATE_ACTIVE);
+            }
+            if node.focusable {
+                set(STATE_FOCUSABLE);
+            }
+            if node.focusable && state.focus == Some(node.id) {
+                set(STATE_FOCUSED);
+            }
+            if let Some(checked) = node.props.checked {
+                set(STATE_CHECKABLE);
+                if checked {
+                    set(STATE_CHECKED);
+                }
+            }
+            if let Some(expanded) = node.props.expanded {
+                set(STATE_EXPANDABLE);
+                if expanded {
+                    set(STATE_EXPANDED);
+                }
+            }
+            if node.role == AccessRole::ListItem {
+                set(STATE_SELECTABLE);
+            }
+            if node.props.selected {
+                set(STATE_SELECTED);
+            }
+            if node.role == AccessRole::TextInput {
+                set(STATE_EDITABLE);
+                set(STATE_SINGLE_LINE);
+            }
+        }
+    }
+    DbusValue::Array("u".into(), vec![DbusValue::UInt32(bits as u32), DbusValue::UInt32((bits >> 32) as u32)])
+}
+
+// the bounds of a node in device pixels, coord_type 0 is the screen, 1 the window, 2 the parent
+fn extents(state: &AtspiState, target: Target, coord_type: u32) -> Rect {
+    let Target::Node(t, n) = target else {
+        return Rect::default()
+    };
+    let tree = &state.trees[t];
+    let bounds = tree.nodes[n].bounds;
+    let origin = match coord_type {
+        0 => -tree.position,
+        2 => tree.nodes[n].parent.map_or(DVec2::default(), | p | tree.nodes[p].bounds.pos),
+        _ => DVec2::default(),
+    };
+    Rect {
+        pos: (bounds.pos - origin) * tree.dpi_factor,
+        size: bounds.size * tree.dpi_factor
+    }
+}
+
+fn interfaces(state: &AtspiState, target: Target) -> Vec<&'static str> {
+    let mut interfaces = vec![ACCESSIBLE];
+    match target {
+        Target::App => interfaces.push(APPLICATION),
+        Target::Node(t, n) => {
+            let node = &state.trees[t].nodes[n];
+            interfaces.push(COMPONENT);
+            if node.props.range.is_some() {
+                interfaces.push(VALUE);
+            }
+            if node.role.is_actionable() {
+                interfaces.push(ACTION);
+            }
+        }
+    }
+    interfaces
+}
+
+fn property(state: &AtspiState, target: Target, interface: &str, name: &str) -> Option<DbusValue> {
+    let node = match target {
+        Target::App => None,
+        Target::Node(t, n) => Some(&state.trees[t].nodes[n]),
+    };
+    Some(match (interface, name) {
+        (ACCESSIBLE, "Name") => DbusValue::str(node.map_or(&state.app_name, | node | &node.props.name)),
+        (ACCESSIBLE, "Description") => DbusValue::str(node.and_then( | node | node.props.value.as_deref()).unwrap_or("")),
+        (ACCESSIBLE, "Parent") => parent(state, target),
+        (ACCESSIBLE, "ChildCount") => DbusValue::Int32(children(state, target).len() as i32),
+        (ACCESSIBLE, "Locale") => DbusValue::str(""),
+        (ACCESSIBLE, "AccessibleId") => DbusValue::str(&node.map_or(String::new(), | node | node.id.to_string())),
+        (APPLICATION, "ToolkitName") => DbusValue::str("Makepad"),
+        (APPLICATION, "Version") => DbusValue::str(env!("CARGO_PKG_VERSION")),
+        (APPLICATION, "AtspiVersion") => DbusValue::str("2.1"),
+        (APPLICATION, "Id") => DbusValue::Int32(0),
+        (VALUE, _) => {
+            let range = node?.props.range.clone()?;
+            match name {
+                "MinimumValue" => DbusValue::Double(range.min),
+                "MaximumValue" => DbusValue::Double(range.max),
+                "MinimumIncrement" => DbusValue::Double(range.step),
+                "CurrentValue" => DbusValue::Double(range.value),
+                "Text" => DbusValue::str(node?.props.value.as_deref().unwrap_or("")),
+                _ => return None
+            }
+        }
+        (ACTION, "NActions") => DbusValue::Int32(1),
+        _ => return None
+    })
+}
+
+const PROPERTY_NAMES: &[(&str, &[&str])] = &[
+    (ACCESSIBLE, &["Name", "Description", "Parent", "ChildCount", "Locale", "AccessibleId"]),
+    (APPLICATION, &["ToolkitName", "Version", "AtspiVersion", "Id"]),
+    (VALUE, &["MinimumValue", "MaximumValue", "MinimumIncrement", "CurrentValue", "Text"]),
+    (ACTION, &["NActions"]),
+];
+
+fn handle_call(state: &mut AtspiState, msg: &DbusMessage) -> DbusMessage {
+    let Some(target) = find_target(state, msg.path()) else {
+        return DbusMessage::error(msg, "org.freedesktop.DBus.Error.UnknownObject", "the object is gone")
+    };
+    let arg_i64 = | index: usize | msg.body.get(index).and_then( | v | v.as_i64()).unwrap_or(0);
+    let arg_str = | index: usize | msg.body.get(index).and_then( | v | v.as_str()).unwrap_or("").to_string();
+    let node_id = match target {
+        Target::Node(t, n) => Some(state.trees[t].nodes[n].id),
+        Target::App => None,
+    };
+    let body = match (msg.interface(), msg.member()) {
+        (INTROSPECTABLE, "Introspect") => {
+            let xml: String = interfaces(state, target).iter().map( | i | format!("<interface name=\"{}\"/>", i)).collect();
+            vec![DbusValue::String(format!("<node>{}</node>", xml))]
+        }
+        (PROPERTIES, "Get") => match property(state, target, &arg_str(0), &arg_str(1)) {
+            Some(value) => vec![DbusValue::variant(value)],
+            None => return DbusMessage::error(msg, "org.freedesktop.DBus.Error.UnknownProperty", "unknown property")
+        }
+        (PROPERTIES, "GetAll") => {
+            let interface = arg_str(0);
+            let entries = PROPERTY_NAMES.iter()
+                .filter( | (i, _) | *i == interface)
+                .flat_map( | (_, names) | names.iter())
+                .filter_map( | name | property(state, target, &interface, name).map( | v | {
+                    DbusValue::dict_entry(DbusValue::str(name), DbusValue::variant(v))
+                }))
+                .collect();
+            vec![DbusValue::Array("{sv}".into(), entries)]
+        }
+        // the application id and values are set by the registry and screen readers, which we ignore
+        (PROPERTIES, "Set") => vec![],
+        (ACCESSIBLE, "GetChildAtIndex") => {
+            let children = children(state, target);
+            vec![children.get(arg_i64(0) as usize).map_or_else(null_ref, | c | object_ref(state, *c))]
+        }
+        (ACCESSIBLE, "GetChildren") => {
+            let refs = children(state, target).into_iter().map( | c | object_ref(state, c)).collect();
+            vec![DbusValue::Array("(so)".into(), refs)]
+        }
+        (ACCESSIBLE, "GetIndexInParent") => vec![DbusValue::Int32(index_in_parent(state, target))],
+        (ACCESSIBLE, "GetRelationSet") => vec![DbusValue::Array("(ua(so))".into(), vec![])],
+        (ACCESSIBLE, "GetRole") => vec![DbusValue::UInt32(match target {
+            Target::App => ROLE_APPLICATION,
+            Target::Node(t, n) => atspi_role(state.trees[t].nodes[n].role),
+        })],
+        (ACCESSIBLE, "GetRoleName") | (ACCESSIBLE, "GetLocalizedRoleName") => vec![DbusValue::str(match target {
+            Target::App => "application",
+            Target::Node(t, n) => state.trees[t].nodes[n].role.name(),
+        })],
+        (ACCESSIBLE, "GetState") => vec![states(state, target)],
+        (ACCESSIBLE, "GetAttributes") => vec![DbusValue::Array("{ss}".into(), vec![
+            DbusValue::dict_entry(DbusValue::str("toolkit"), DbusValue::str("Makepad"))
+        ])],
+        (ACCESSIBLE, "GetApplication") => vec![object_ref(state, Target::App)],
+        (ACCESSIBLE, "GetInterfaces") => vec![DbusValue::Array(
+            "s".into(),
+            interfaces(state, target).into_iter().map(DbusValue::str).collect()
+        )],
+        (APPLICATION, "GetLocale") => vec![DbusValue::str("")],
+        (COMPONENT, "Contains") => {
+            let rect = extents(state, target, arg_i64(2) as u32);
+            vec![DbusValue::Bool(rect.contains(dvec2(arg_i64(0) as f64, arg_i64(1) as f64)))]
+        }
+        (COMPONENT, "GetAccessibleAtPoint") => {
+            let found = match target {
+                Target::App => None,
+                Target::Node(t, n) => {
+                    let tree = &state.trees[t];
+                    let rect = extents(state, target, arg_i64(2) as u32);
+                    let pos = (dvec2(arg_i64(0) as f64, arg_i64(1) as f64) - rect.pos) / tree.dpi_factor + tree.nodes[n].bounds.pos;
+                    tree.node_at(pos).filter( | found | *found != n).map( | found | Target::Node(t, found))
+                }
+            };
+            vec![found.map_or_else(null_ref, | found | object_ref(state, found))]
+        }
+        (COMPONENT, "GetExtents") => {
+            let rect = extents(state, target, arg_i64(0) as u32);
+            vec![DbusValue::Struct(vec![
+                DbusValue::Int32(rect.pos.x as i32),
+                DbusValue::Int32(rect.pos.y as i32),
+                DbusValue::Int32(rect.size.x as i32),
+                DbusValue::Int32(rect.size.y as i32),
+            ])]
+        }
+        (COMPONENT, "GetPosition") => {
+            let rect = extents(state, target, arg_i64(0) as u32);
+            vec![DbusValue::Int32(rect.pos.x as i32), DbusValue::Int32(rect.pos.y as i32)]
+        }
+        (COMPONENT, "GetSize") => {
+            let rect = extents(state, target, 1);
+            vec![DbusValue::Int32(rect.size.x as i32), DbusValue::Int32(rect.size.y as i32)]
+        }
+        // 7 is the window layer, 3 the widget layer
+        (COMPONENT, "GetLayer") => vec![DbusValue::UInt32(if matches!(target, Target::Node(_, 0)) {7} else {3})],
+        (COMPONENT, "GetMDIZOrder") => vec![DbusValue::Int16(0)],
+        (COMPONENT, "GetAlpha") => vec![DbusValue::Double(1.0)],
+        (COMPONENT, "GrabFocus") => {
+            if let Some(id) = node_id {
+                state.requests.push(AccessRequest::Focus(id));
+            }
+            vec![DbusValue::Bool(node_id.is_some())]
+        }
+        (ACTION, "GetNActions") => vec![DbusValue::Int32(1)],
+        (ACTION, "GetName") | (ACTION, "GetLocalizedName") => vec![DbusValue::str("click")],
+        (ACTION, "GetDescription") | (ACTION, "GetKeyBinding") => vec![DbusValue::str("")],
+        (ACTION, "GetActions") => vec![DbusValue::Array("(sss)".into(), vec![
+            DbusValue::Struct(vec![DbusValue::str("click"), DbusValue::str(""), DbusValue::str("")])
+        ])],
+        (ACTION, "DoAction") => {
+            if let Some(id) = node_id {
+                state.requests.push(AccessRequest::Activate(id));
+            }
+            vec![DbusValue::Bool(node_id.is_some())]
+        }
+        _ => return DbusMessage::error(msg, "org.freedesktop.DBus.Error.UnknownMethod", "unknown method")
+    };
+    DbusMessage::method_return(msg, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::accessibility::AccessProps,
+        crate::area::Area,
+    };
+    
+    fn node(id: u64, role: AccessRole, props: AccessProps, focusable: bool, bounds: Rect) -> AccessNode {
+        AccessNode {id, role, props, focusable, focused: false, bounds, area: Area::Empty, parent: None, children: Vec::new()}
+    }
+    
+    fn state() -> AtspiState {
+        let mut tree = AccessTree::new(Default::default(), "Settings", dvec2(100.0, 50.0), dvec2(400.0, 300.0), 2.0);
+        let rect = | x: f64, y: f64 | Rect {pos: dvec2(x, y), size: dvec2(80.0, 20.0)};
+        let group = tree.push(0, node(1, AccessRole::Group, AccessProps::default(), false, Rect {pos: dvec2(10.0, 10.0), size: dvec2(300.0, 100.0)}));
+        tree.push(group, node(2, AccessRole::CheckBox, AccessProps {checked: Some(true), ..AccessProps::with_name("Wrap")}, true, rect(20.0, 20.0)));
+        tree.push(group, node(3, AccessRole::Button, AccessProps {disabled: true, ..AccessProps::with_name("Apply")}, true, rect(120.0, 20.0)));
+        tree.push(0, node(4, AccessRole::TextInput, AccessProps::with_name("Search"), true, rect(20.0, 200.0)));
+        AtspiState {
+            app_name: "app".to_string(),
+            bus_name: ":1.5".to_string(),
+            trees: vec![tree],
+            focus: Some(2),
+            ..Default::default()
+        }
+    }
+    
+    fn target(state: &AtspiState, id: u64) -> Target {
+        find_target(state, &node_path(id)).unwrap()
+    }
+    
+    fn has_state(state: &AtspiState, id: u64, bit: u32) -> bool {
+        let DbusValue::Array(_, words) = states(state, target(state, id)) else {
+            panic!()
+        };
+        let bits = words[0].as_i64().unwrap() as u64 | (words[1].as_i64().unwrap() as u64) << 32;
+        bits & 1 << bit != 0
+    }
+    
+    fn call(state: &mut AtspiState, id: u64, interface: &str, member: &str, body: Vec<DbusValue>) -> Vec<DbusValue> {
+        let path = if id == 0 {ROOT_PATH.to_string()} else {node_path(id)};
+        let mut msg = DbusMessage::method_call(":1.5", &path, interface, member, body);
+        msg.serial = 1;
+        handle_call(state, &msg).body
+    }
+    
+    #[test]
+    fn reports_roles_and_names() {
+        let mut state = state();
+        assert_eq!(call(&mut state, 0, ACCESSIBLE, "GetRole", vec![]), vec![DbusValue::UInt32(ROLE_APPLICATION)]);
+        assert_eq!(call(&mut state, 2, ACCESSIBLE, "GetRole", vec![]), vec![DbusValue::UInt32(7)]);
+        assert_eq!(call(&mut state, 4, ACCESSIBLE, "GetRoleName", vec![]), vec![DbusValue::str("entry")]);
+        let name = | state: &mut AtspiState, id: u64 | {
+            call(state, id, PROPERTIES, "Get", vec![DbusValue::str(ACCESSIBLE), DbusValue::str("Name")])[0].as_str().unwrap().to_string()
+        };
+        assert_eq!(name(&mut state, 0), "app");
+        assert_eq!(name(&mut state, 3), "Apply");
+        let children = call(&mut state, 1, ACCESSIBLE, "GetChildren", vec![]);
+        assert_eq!(children, vec![DbusValue::Array("(so)".into(), vec![
+            DbusValue::Struct(vec![DbusValue::str(":1.5"), DbusValue::path(&node_path(2))]),
+            DbusValue::Struct(vec![DbusValue::str(":1.5"), DbusValue::path(&node_path(3))]),
+        ])]);
+        assert_eq!(call(&mut state, 3, ACCESSIBLE, "GetIndexInParent", vec![]), vec![DbusValue::Int32(1)]);
+    }
+    
+    #[test]
+    fn reports_states() {
+        let state = state();
+        assert!(has_state(&state, 2, STATE_FOCUSED));
+        assert!(has_state(&state, 2, STATE_CHECKABLE));
+        assert!(has_state(&state, 2, STATE_CHECKED));
+        assert!(has_state(&state, 2, STATE_ENABLED));
+        assert!(has_state(&state, 2, STATE_SHOWING));
+        assert!(!has_state(&state, 3, STATE_FOCUSED));
+        assert!(has_state(&state, 3, STATE_FOCUSABLE));
+        assert!(!has_state(&state, 3, STATE_ENABLED));
+        assert!(!has_state(&state, 3, STATE_SENSITIVE));
+        assert!(has_state(&state, 4, STATE_EDITABLE));
+        assert!(has_state(&state, 4, STATE_SINGLE_LINE));
+        assert!(!has_state(&state, 4, STATE_CHECKABLE));
+        assert!(!has_state(&state, 1, STATE_FOCUSABLE));
+    }
+    
+    #[test]
+    fn reports_bounds_in_device_pixels() {
+        let mut state = state();
+        let extents = | state: &mut AtspiState, coord_type: u32 | call(state, 3, COMPONENT, "GetExtents", vec![DbusValue::UInt32(coord_type)]);
+        let rect = | x: i32, y: i32 | vec![DbusValue::Struct(vec![DbusValue::Int32(x), DbusValue::Int32(y), DbusValue::Int32(160), DbusValue::Int32(40)])];
+        // the screen, the window and the parent
+        assert_eq!(extents(&mut state, 0), rect(440, 140));
+        assert_eq!(extents(&mut state, 1), rect(240, 40));
+        assert_eq!(extents(&mut state, 2), rect(220, 20));
+        let at_point = call(&mut state, 1, COMPONENT, "GetAccessibleAtPoint", vec![DbusValue::Int32(250), DbusValue::Int32(50), DbusValue::UInt32(1)]);
+        assert_eq!(at_point, vec![DbusValue::Struct(vec![DbusValue::str(":1.5"), DbusValue::path(&node_path(3))])]);
+    }
+    
+    #[test]
+    fn forwards_focus_and_actions() {
+        let mut state = state();
+        call(&mut state, 4, COMPONENT, "GrabFocus", vec![]);
+        call(&mut state, 2, ACTION, "DoAction", vec![DbusValue::Int32(0)]);
+        let requests: Vec<String> = state.requests.iter().map( | request | format!("{:?}", request)).collect();
+        assert_eq!(requests, vec!["Focus(4)", "Activate(2)"]);
+    }
+    
+    #[test]
+    fn reads_is_enabled_changes() {
+        let change = | interface: &str, key: &str, value: bool | DbusMessage::signal("/org/a11y/bus", PROPERTIES, "PropertiesChanged", vec![
+            DbusValue::str(interface),
+            DbusValue::Array("{sv}".into(), vec![DbusValue::dict_entry(DbusValue::str(key), DbusValue::variant(DbusValue::Bool(value)))]),
+            DbusValue::Array("s".into(), vec![]),
+        ]);
+        assert_eq!(is_enabled_change(&change("org.a11y.Status", "IsEnabled", true)), Some(true));
+        assert_eq!(is_enabled_change(&change("org.a11y.Status", "IsEnabled", false)), Some(false));
+        assert_eq!(is_enabled_change(&change("org.a11y.Status", "ScreenReaderEnabled", true)), None);
+        assert_eq!(is_enabled_change(&change("org.a11y.Other", "IsEnabled", true)), None);
+        assert_eq!(is_enabled_change(&DbusMessage::signal("/org/a11y/bus", PROPERTIES, "PropertiesChanged", vec![])), None);
+    }
+}
@@ -0,0 +1,627 @@
+use {
+    std::{
+        io,
+        io::{Read, Write},
+        collections::VecDeque,
+        os::unix::{
+            net::UnixStream,
+            fs::MetadataExt,
+        },
+        sync::{
+            Arc,
+            Mutex,
+            atomic::{AtomicU32, Ordering},
+        },
+    },
+};
+
+// a small d-bus client over a unix socket, just enough to serve objects on the accessibility bus
+// without linking libdbus. it only speaks little endian and doesnt pass file descriptors
+
+pub const METHOD_CALL: u8 = 1;
+pub const METHOD_RETURN: u8 = 2;
+pub const ERROR: u8 = 3;
+pub const SIGNAL: u8 = 4;
+
+const FLAG_NO_REPLY_EXPECTED: u8 = 1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DbusValue {
+    Byte(u8),
+    Bool(bool),
+    Int16(i16),
+    UInt16(u16),
+    Int32(i32),
+    UInt32(u32),
+    Int64(i64),
+    UInt64(u64),
+    Double(f64),
+    String(String),
+    ObjectPath(String),
+    Signature(String),
+    // the signature of the elements is kept so empty arrays can be written
+    Array(String, Vec<DbusValue>),
+    Struct(Vec<DbusValue>),
+    Variant(Box<DbusValue>),
+    DictEntry(Box<DbusValue>, Box<DbusValue>),
+}
+
+impl DbusValue {
+    pub fn str(s: &str) -> Self {
+        Self::String(s.to_string())
+    }
+
+    pub fn path(s: &str) -> Self {
+        Self::ObjectPath(s.to_string())
+    }
+
+    pub fn variant(value: DbusValue) -> Self {
+        Self::Variant(Box::new(value))
+    }
+
+    pub fn dict_entry(key: DbusValue, value: DbusValue) -> Self {
+        Self::DictEntry(Box::new(key), Box::new(value))
+    }
+
+    pub fn signature(&self) -> String {
+        match self {
+            Self::Byte(_) => "y".into(),
+            Self::Bool(_) => "b".into(),
+            Self::Int16(_) => "n".into(),
+            Self::UInt16(_) => "q".into(),
+            Self::Int32(_) => "i".into(),
+            Self::UInt32(_) => "u".into(),
+            Self::Int64(_) => "x".into(),
+            Self::UInt64(_) => "t".into(),
+            Self::Double(_) => "d".into(),
+            Self::String(_) => "s".into(),
+            Self::ObjectPath(_) => "o".into(),
+            Self::Signature(_) => "g".into(),
+            Self::Array(sig, _) => format!("a{}", sig),
+            Self::Struct(fields) => format!("({})", fields.iter().map( | f | f.signature()).collect::<String>()),
+            Self::Variant(_) => "v".into(),
+            Self::DictEntry(key, value) => format!("{{{}{}}}", key.signature(), value.signature()),
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) | Self::ObjectPath(s) | Self::Signature(s) => Some(s),
+            Self::Variant(v) => v.as_str(),
+            _ => None
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Self::Byte(v) => Some(*v as i64),
+            Self::Int16(v) => Some(*v as i64),
+            Self::UInt16(v) => Some(*v as i64),
+            Self::Int32(v) => Some(*v as i64),
+            Self::UInt32(v) => Some(*v as i64),
+            Self::Int64(v) => Some(*v),
+            Self::UInt64(v) => Some(*v as i64),
+            Self::Variant(v) => v.as_i64(),
+            _ => None
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(v) => Some(*v),
+            Self::Variant(v) => v.as_bool(),
+            _ => None
+        }
+    }
+
+    fn marshal(&self, buf: &mut Vec<u8>) {
+        match self {
+            Self::Byte(v) => buf.push(*v),
+            Self::Bool(v) => put_u32(buf, *v as u32),
+            Self::Int16(v) => put_aligned(buf, 2, &v.to_le_bytes()),
+            Self::UInt16(v) => put_aligned(buf, 2, &v.to_le_bytes()),
+            Self::Int32(v) => put_aligned(buf, 4, &v.to_le_bytes()),
+            Self::UInt32(v) => put_u32(buf, *v),
+            Self::Int64(v) => put_aligned(buf, 8, &v.to_le_bytes()),
+            Self::UInt64(v) => put_aligned(buf, 8, &v.to_le_bytes()),
+            Self::Double(v) => put_aligned(buf, 8, &v.to_le_bytes()),
+            Self::String(s) | Self::ObjectPath(s) => {
+                put_u32(buf, s.len() as u32);
+                buf.extend_from_slice(s.as_bytes());
+                buf.push(0);
+            }
+            Self::Signature(s) => put_signature(buf, s),
+            Self::Array(sig, items) => {
+                put_u32(buf, 0);
+                let len_pos = buf.len() - 4;
+                // the padding to the first element isnt part of the length
+                pad(buf, alignment(sig.as_bytes()[0]));
+                let start = buf.len();
+                for item in items {
+                    item.marshal(buf);
+                }
+                let len = (buf.len() - start) as u32;
+                buf[len_pos..len_pos + 4].copy_from_slice(&len.to_le_bytes());
+            }
+            Self::Struct(fields) => {
+                pad(buf, 8);
+                for field in fields {
+                    field.marshal(buf);
+                }
+            }
+            Self::Variant(value) => {
+                put_signature(buf, &value.signature());
+                value.marshal(buf);
+            }
+            Self::DictEntry(key, value) => {
+                pad(buf, 8);
+                key.marshal(buf);
+                value.marshal(buf);
+            }
+        }
+    }
+}
+
+fn alignment(code: u8) -> usize {
+    match code {
+        b'y' | b'g' | b'v' => 1,
+        b'n' | b'q' => 2,
+        b'b' | b'i' | b'u' | b's' | b'o' | b'a' | b'h' => 4,
+        _ => 8
+    }
+}
+
+fn pad(buf: &mut Vec<u8>, align: usize) {
+    while buf.len() % align != 0 {
+        buf.push(0);
+    }
+}
+
+fn put_aligned(buf: &mut Vec<u8>, align: usize, bytes: &[u8]) {
+    pad(buf, align);
+    buf.extend_from_slice(bytes);
+}
+
+fn put_u32(buf: &mut Vec<u8>, v: u32) {
+    put_aligned(buf, 4, &v.to_le_bytes());
+}
+
+fn put_signature(buf: &mut Vec<u8>, s: &str) {
+    buf.push(s.len() as u8);
+    buf.extend_from_slice(s.as_bytes());
+    buf.push(0);
+}
+
+fn invalid(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("dbus: {}", what))
+}
+
+// the length of the first complete type in a signature
+fn single_type_len(sig: &[u8]) -> io::Result<usize> {
+    match sig.first() {
+        None => Err(invalid("empty signature")),
+        Some(b'a') => Ok(1 + single_type_len(&sig[1..])?),
+        Some(open @ (b'(' | b'{')) => {
+            let close = if *open == b'(' {b')'} else {b'}'};
+            let mut pos = 1;
+            while pos < sig.len() && sig[pos] != close {
+                pos += single_type_len(&sig[pos..])?;
+            }
+            if pos >= sig.len() {
+                return Err(invalid("unterminated signature"))
+            }
+            Ok(pos + 1)
+        }
+        Some(_) => Ok(1)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn align(&mut self, align: usize) {
+        self.pos = (self.pos + align - 1) / align * align;
+    }
+
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if self.pos + len > self.data.len() {
+            return Err(invalid("message too short"))
+        }
+        let bytes = &self.data[self.pos..self.pos + len];
+        self.pos += len;
+        Ok(bytes)
+    }
+
+    fn fixed<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        self.align(N);
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.fixed::<4>()?))
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        let len = self.u32()? as usize;
+        let bytes = self.take(len + 1)?;
+        String::from_utf8(bytes[..len].to_vec()).map_err( | _ | invalid("string is not utf8"))
+    }
+
+    fn signature(&mut self) -> io::Result<String> {
+        let len = self.take(1)?[0] as usize;
+        let bytes = self.take(len + 1)?;
+        String::from_utf8(bytes[..len].to_vec()).map_err( | _ | invalid("signature is not utf8"))
+    }
+
+    fn value(&mut self, sig: &[u8]) -> io::Result<DbusValue> {
+        Ok(match sig[0] {
+            b'y' => DbusValue::Byte(self.take(1)?[0]),
+            b'b' => DbusValue::Bool(self.u32()? != 0),
+            b'n' => DbusValue::Int16(i16::from_le_bytes(self.fixed()?)),
+            b'q' => DbusValue::UInt16(u16::from_le_bytes(self.fixed()?)),
+            b'i' => DbusValue::Int32(i32::from_le_bytes(self.fixed()?)),
+            b'u' | b'h' => DbusValue::UInt32(self.u32()?),
+            b'x' => DbusValue::Int64(i64::from_le_bytes(self.fixed()?)),
+            b't' => DbusValue::UInt64(u64::from_le_bytes(self.fixed()?)),
+            b'd' => DbusValue::Double(f64::from_le_bytes(self.fixed()?)),
+            b's' => DbusValue::String(self.string()?),
+            b'o' => DbusValue::ObjectPath(self.string()?),
+            b'g' => DbusValue::Signature(self.signature()?),
+            b'v' => {
+                let inner = self.signature()?;
+                if inner.is_empty() {
+                    return Err(invalid("empty variant"))
+                }
+                DbusValue::variant(self.value(inner.as_bytes())?)
+            }
+            b'a' => {
+                let len = self.u32()? as usize;
+                let elem = &sig[1..1 + single_type_len(&sig[1..])?];
+                self.align(alignment(elem[0]));
+                let end = self.pos + len;
+                let mut items = Vec::new();
+                while self.pos < end {
+                    items.push(self.value(elem)?);
+                }
+                DbusValue::Array(String::from_utf8_lossy(elem).to_string(), items)
+            }
+            b'(' => {
+                self.align(8);
+                let len = single_type_len(sig)?;
+                let mut fields = Vec::new();
+                let mut pos = 1;
+                while pos < len - 1 {
+                    let field_len = single_type_len(&sig[pos..])?;
+                    fields.push(self.value(&sig[pos..pos + field_len])?);
+                    pos += field_len;
+                }
+                DbusValue::Struct(fields)
+            }
+            b'{' => {
+                self.align(8);
+                let key_len = single_type_len(&sig[1..])?;
+                let key = self.value(&sig[1..1 + key_len])?;
+                let value = self.value(&sig[1 + key_len..])?;
+                DbusValue::dict_entry(key, value)
+            }
+            _ => return Err(invalid("unknown type in signature"))
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DbusMessage {
+    pub kind: u8,
+    pub flags: u8,
+    pub serial: u32,
+    pub path: Option<String>,
+    pub interface: Option<String>,
+    pub member: Option<String>,
+    pub error_name: Option<String>,
+    pub reply_serial: Option<u32>,
+    pub destination: Option<String>,
+    pub sender: Option<String>,
+    pub body: Vec<DbusValue>,
+}
+
+impl DbusMessage {
+    pub fn method_call(destination: &str, path: &str, interface: &str, member: &str, body: Vec<DbusValue>) -> Self {
+        Self {
+            kind: METHOD_CALL,
+            path: Some(path.to_string()),
+            interface: Some(interface.to_string()),
+            member: Some(member.to_string()),
+            destination: Some(destination.to_string()),
+            body,
+            ..Default::default()
+        }
+    }
+
+    pub fn signal(path: &str, interface: &str, member: &str, body: Vec<DbusValue>) -> Self {
+        Self {
+            kind: SIGNAL,
+            flags: FLAG_NO_REPLY_EXPECTED,
+            path: Some(path.to_string()),
+            interface: Some(interface.to_string()),
+            member: Some(member.to_string()),
+            body,
+            ..Default::default()
+        }
+    }
+
+    pub fn method_return(call: &DbusMessage, body: Vec<DbusValue>) -> Self {
+        Self {
+            kind: METHOD_RETURN,
+            flags: FLAG_NO_REPLY_EXPECTED,
+            reply_serial: Some(call.serial),
+            destination: call.sender.clone(),
+            body,
+            ..Default::default()
+        }
+    }
+
+    pub fn error(call: &DbusMessage, name: &str, text: &str) -> Self {
+        Self {
+            kind: ERROR,
+            flags: FLAG_NO_REPLY_EXPECTED,
+            error_name: Some(name.to_string()),
+            reply_serial: Some(call.serial),
+            destination: call.sender.clone(),
+            body: vec![DbusValue::str(text)],
+            ..Default::default()
+        }
+    }
+
+    pub fn expects_reply(&self) -> bool {
+        self.kind == METHOD_CALL && self.flags & FLAG_NO_REPLY_EXPECTED == 0
+    }
+
+    pub fn member(&self) -> &str {
+        self.member.as_deref().unwrap_or("")
+    }
+
+    pub fn interface(&self) -> &str {
+        self.interface.as_deref().unwrap_or("")
+    }
+
+    pub fn path(&self) -> &str {
+        self.path.as_deref().unwrap_or("")
+    }
+
+    pub fn encode(&self, serial: u32) -> Vec<u8> {
+        let mut body = Vec::new();
+        for value in &self.body {
+            value.marshal(&mut body);
+        }
+        let mut fields = Vec::new();
+        let mut field = | code: u8, value: DbusValue | {
+            fields.push(DbusValue::Struct(vec![DbusValue::Byte(code), DbusValue::variant(value)]));
+        };
+        if let Some(path) = &self.path {field(1, DbusValue::path(path))}
+        if let Some(interface) = &self.interface {field(2, DbusValue::str(interface))}
+        if let Some(member) = &self.member {field(3, DbusValue::str(member))}
+        if let Some(error_name) = &self.error_name {field(4, DbusValue::str(error_name))}
+        if let Some(reply_serial) = self.reply_serial {field(5, DbusValue::UInt32(reply_serial))}
+        if let Some(destination) = &self.destination {field(6, DbusValue::str(destination))}
+        if !self.body.is_empty() {
+            field(8, DbusValue::Signature(self.body.iter().map( | v | v.signature()).collect()));
+        }
+        let mut buf = vec![b'l', self.kind, self.flags, 1];
+        buf.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&serial.to_le_bytes());
+        DbusValue::Array("(yv)".into(), fields).marshal(&mut buf);
+        pad(&mut buf, 8);
+        buf.extend_from_slice(&body);
+        buf
+    }
+
+    pub fn read_from(stream: &mut impl Read) -> io::Result<DbusMessage> {
+        let mut fixed = [0u8; 16];
+        stream.read_exact(&mut fixed)?;
+        if fixed[0] != b'l' {
+            return Err(invalid("big endian messages are not supported"))
+        }
+        let body_len = u32::from_le_bytes(fixed[4..8].try_into().unwrap()) as usize;
+        let fields_len = u32::from_le_bytes(fixed[12..16].try_into().unwrap()) as usize;
+        let header_len = (16 + fields_len + 7) / 8 * 8;
+        let mut data = fixed.to_vec();
+        data.resize(header_len + body_len, 0);
+        stream.read_exact(&mut data[16..])?;
+
+        let mut msg = DbusMessage {
+            kind: fixed[1],
+            flags: fixed[2],
+            serial: u32::from_le_bytes(fixed[8..12].try_into().unwrap()),
+            ..Default::default()
+        };
+        let mut reader = Reader {data: &data[..16 + fields_len], pos: 12};
+        let mut signature = String::new();
+        if let DbusValue::Array(_, fields) = reader.value(b"a(yv)")? {
+            for field in fields {
+                let DbusValue::Struct(field) = field else {continue};
+                let (DbusValue::Byte(code), value) = (&field[0], &field[1]) else {continue};
+                let text = value.as_str().map( | s | s.to_string());
+                match code {
+                    1 => msg.path = text,
+                    2 => msg.interface = text,
+                    3 => msg.member = text,
+                    4 => msg.error_name = text,
+                    5 => msg.reply_serial = value.as_i64().map( | v | v as u32),
+                    6 => msg.destination = text,
+                    7 => msg.sender = text,
+                    8 => signature = text.unwrap_or_default(),
+                    _ => ()
+                }
+            }
+        }
+        let mut reader = Reader {data: &data[header_len..], pos: 0};
+        let sig = signature.as_bytes();
+        let mut pos = 0;
+        while pos < sig.len() {
+            let len = single_type_len(&sig[pos..])?;
+            msg.body.push(reader.value(&sig[pos..pos + len])?);
+            pos += len;
+        }
+        Ok(msg)
+    }
+}
+
+// the sending half of a connection, it can be shared with other threads to emit signals
+pub struct DbusSender {
+    stream: Mutex<UnixStream>,
+    serial: AtomicU32,
+}
+
+impl DbusSender {
+    pub fn send(&self, msg: &DbusMessage) -> io::Result<u32> {
+        let serial = self.serial.fetch_add(1, Ordering::Relaxed);
+        let data = msg.encode(serial);
+        self.stream.lock().unwrap().write_all(&data)?;
+        Ok(serial)
+    }
+}
+
+pub struct DbusConnection {
+    stream: UnixStream,
+    sender: Arc<DbusSender>,
+    // messages that came in while waiting for a reply
+    queued: VecDeque<DbusMessage>,
+    pub unique_name: String,
+}
+
+impl DbusConnection {
+    pub fn session() -> io::Result<Self> {
+        let address = std::env::var("DBUS_SESSION_BUS_ADDRESS")
+            .map_err( | _ | io::Error::new(io::ErrorKind::NotFound, "dbus: no session bus address"))?;
+        Self::open(&address)
+    }
+
+    // addresses look like unix:path=/run/user/1000/bus or unix:abstract=/tmp/dbus-x,guid=..
+    // with alternatives separated by semicolons
+    pub fn open(address: &str) -> io::Result<Self> {
+        let mut last_err = io::Error::new(io::ErrorKind::NotFound, "dbus: no usable address");
+        for entry in address.split(';') {
+            let Some(params) = entry.strip_prefix("unix:") else {continue};
+            for param in params.split(',') {
+                let stream = if let Some(path) = param.strip_prefix("path=") {
+                    UnixStream::connect(unescape(path))
+                }
+                else if let Some(name) = param.strip_prefix("abstract=") {
+                    connect_abstract(&unescape(name))
+                }
+                else {
+                    continue
+                };
+                match stream.and_then(Self::from_stream) {
+                    Ok(conn) => return Ok(conn),
+                    Err(err) => last_err = err
+                }
+            }
+        }
+        Err(last_err)
+    }
+
+    fn from_stream(mut stream: UnixStream) -> io::Result<Self> {
+        let uid = std::fs::metadata("/proc/self")?.uid();
+        let hex_uid: String = uid.to_string().bytes().map( | b | format!("{:02x}", b)).collect();
+        stream.write_all(format!("\0AUTH EXTERNAL {}\r\n", hex_uid).as_bytes())?;
+        let line = read_line(&mut stream)?;
+        if !line.starts_with("OK") {
+            return Err(io::Error::new(io::ErrorKind::PermissionDenied, format!("dbus: auth failed {}", line)))
+        }
+        stream.write_all(b"BEGIN\r\n")?;
+        let sender = Arc::new(DbusSender {
+            stream: Mutex::new(stream.try_clone()?),
+            serial: AtomicU32::new(1),
+        });
+        let mut conn = Self {
+            stream,
+            sender,
+            queued: VecDeque::new(),
+            unique_name: String::new(),
+        };
+        let reply = conn.call(DbusMessage::method_call(
+            "org.freedesktop.DBus",
+            "/org/freedesktop/DBus",
+            "org.freedesktop.DBus",
+            "Hello",
+            vec![]
+        ))?;
+        conn.unique_name = reply.body.first().and_then( | v | v.as_str()).unwrap_or("").to_string();
+        Ok(conn)
+    }
+
+    pub fn sender(&self) -> Arc<DbusSender> {
+        self.sender.clone()
+    }
+
+    pub fn send(&self, msg: &DbusMessage) -> io::Result<u32> {
+        self.sender.send(msg)
+    }
+
+    // sends a method call and waits for its reply, errors come back as io errors
+    pub fn call(&mut self, msg: DbusMessage) -> io::Result<DbusMessage> {
+        let serial = self.send(&msg)?;
+        loop {
+            let reply = DbusMessage::read_from(&mut self.stream)?;
+            if reply.reply_serial == Some(serial) && (reply.kind == METHOD_RETURN || reply.kind == ERROR) {
+                if reply.kind == ERROR {
+                    let text = reply.body.first().and_then( | v | v.as_str()).unwrap_or("");
+                    return Err(io::Error::new(io::ErrorKind::Other, format!(
+                        "dbus: {} {}",
+                        reply.error_name.as_deref().unwrap_or(""),
+                        text
+                    )))
+                }
+                return Ok(reply)
+            }
+            self.queued.push_back(reply);
+        }
+    }
+
+    pub fn read(&mut self) -> io::Result<DbusMessage> {
+        if let Some(msg) = self.queued.pop_front() {
+            return Ok(msg)
+        }
+        DbusMessage::read_from(&mut self.stream)
+    }
+}
+
+fn read_line(stream: &mut UnixStream) -> io::Result<String> {
+    // byte by byte, so nothing after the line is consumed
+    let mut line = Vec::new();
+    let mut byte = [0u8];
+    while !line.ends_with(b"\r\n") {
+        stream.read_exact(&mut byte)?;
+        line.push(byte[0]);
+    }
+    line.truncate(line.len() - 2);
+    Ok(String::from_utf8_lossy(&line).to_string())
+}
+
+fn unescape(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Ok(b) = u8::from_str_radix(&value[i + 1..i + 3], 16) {
+                out.push(b);
+                i += 3;
+                continue
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+fn connect_abstract(name: &str) -> io::Result<UnixStream> {
+    use std::os::linux::net::SocketAddrExt;
+    let addr = std::os::unix::net::SocketAddr::from_abstract_name(name.as_bytes())?;
+    UnixStream::connect_addr(&addr)
+}
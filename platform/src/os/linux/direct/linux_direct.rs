@@ -306,6 +306,9 @@ impl Cx {
 
 impl CxOsApi for Cx {
     fn init_cx_os(&mut self) {
+        #[cfg(linux_package)]{
+            self.live_registry.borrow_mut().package_root = Self::native_package_root();
+        }
         self.live_expand();
         self.live_scan_dependencies();
         self.native_load_dependencies();
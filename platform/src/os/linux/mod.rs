@@ -33,6 +33,11 @@ pub mod pulse_sys;
 #[cfg(not(target_os="android"))]
 mod web_socket;
 
+#[cfg(not(any(linux_direct, target_os="android")))]
+pub mod dbus;
+#[cfg(not(any(linux_direct, target_os="android")))]
+pub mod atspi;
+
 #[cfg(target_os="android")]
 pub mod android;
 
@@ -9,7 +9,12 @@ use {
     self::super::gl_sys,
     crate::{
         makepad_live_id::*,
-        makepad_shader_compiler::generate_glsl,
+        makepad_live_compiler::{LiveRegistry, LiveErrorSpan, TokenSpan},
+        makepad_shader_compiler::{
+            generate_glsl::{self, GlslFlavor, GlslGeneratedShader},
+            DrawShaderPtr,
+            ShaderSourceMap,
+        },
         cx::{Cx, OsType, OsType::Android},
         texture::{Texture, TextureFormat, TexturePixel, CxTexture},
         makepad_math::{Mat4, DVec2, Vec4},
@@ -61,15 +66,26 @@ impl Cx {
                 }
                 let shp = &mut self.draw_shaders.os_shaders[sh.os_shader_id.unwrap()];
                 
-                if shp.gl_shader.is_none(){
-                    shp.gl_shader = Some(GlShader::new(
+                if shp.gl_shader.is_none() && !shp.has_errors {
+                    match GlShader::new(
                         &shp.vertex,
                         &shp.pixel,
                         &sh.mapping,
                         self.os_type.get_cache_dir().as_ref()
-                    ));
+                    ) {
+                        Ok(gl_shader) => shp.gl_shader = Some(gl_shader),
+                        Err((stage, log)) => {
+                            shp.has_errors = true;
+                            shp.log_driver_errors(&self.live_registry.borrow(), stage, &log);
+                        }
+                    }
                 }
-                let shgl = shp.gl_shader.as_ref().unwrap();
+                let shgl = if let Some(shgl) = shp.gl_shader.as_ref() {
+                    shgl
+                }
+                else { // the driver rejected the shader, the error is logged
+                    continue;
+                };
                 
                 if draw_call.instance_dirty || draw_item.os.inst_vb.gl_buffer.is_none(){
                     draw_call.instance_dirty = false;
@@ -457,31 +473,33 @@ impl Cx {
                 let cx_shader = &mut self.draw_shaders.shaders[item.draw_shader_id];
                 let draw_shader_def = self.shader_registry.draw_shader_defs.get(&draw_shader_ptr);
                 
-                let vertex = generate_glsl::generate_vertex_shader(
+                let vertex = generate_glsl::generate_vertex_shader_with_flavor(
                     draw_shader_def.as_ref().unwrap(),
                     &cx_shader.mapping.const_table,
-                    &self.shader_registry
+                    &self.shader_registry,
+                    GlslFlavor::Es100
                 );
-                let pixel = generate_glsl::generate_pixel_shader(
+                let pixel = generate_glsl::generate_pixel_shader_with_flavor(
                     draw_shader_def.as_ref().unwrap(),
                     &cx_shader.mapping.const_table,
-                    &self.shader_registry
+                    &self.shader_registry,
+                    GlslFlavor::Es100
                 );
                 
                 if cx_shader.mapping.flags.debug {
-                    crate::log!("{}\n{}", vertex, pixel);
+                    crate::log!("{}\n{}", vertex.glsl, pixel.glsl);
                 }
                 
                 // lets see if we have the shader already
                 for (index, ds) in self.draw_shaders.os_shaders.iter().enumerate() {
-                    if ds.vertex == vertex && ds.pixel == pixel {
+                    if ds.vertex == vertex.glsl && ds.pixel == pixel.glsl {
                         cx_shader.os_shader_id = Some(index);
                         break;
                     }
                 }
                 
                 if cx_shader.os_shader_id.is_none() {
-                    let shp = CxOsDrawShader::new(&vertex, &pixel, &self.os_type);
+                    let shp = CxOsDrawShader::new(vertex, pixel, *draw_shader_ptr, &self.os_type);
                     cx_shader.os_shader_id = Some(self.draw_shaders.os_shaders.len());
                     self.draw_shaders.os_shaders.push(shp);
                 }
@@ -507,6 +525,19 @@ pub struct CxOsDrawShader {
     pub gl_shader: Option<GlShader>,
     pub vertex: String,
     pub pixel: String,
+    pub vertex_source_map: ShaderSourceMap,
+    pub pixel_source_map: ShaderSourceMap,
+    pub draw_shader_ptr: DrawShaderPtr,
+    // the driver rejected the shader, so we don't retry every frame
+    pub has_errors: bool,
+}
+
+// where in the pipeline the driver rejected a shader
+#[derive(Clone, Copy, Debug)]
+pub enum GlShaderStage {
+    Vertex,
+    Pixel,
+    Link,
 }
 
 #[derive(Clone)]
@@ -524,7 +555,7 @@ pub struct GlShader {
 }
 
 impl GlShader{
-    pub fn new(vertex: &str, pixel: &str, mapping: &CxDrawShaderMapping, cache_dir: Option<&String>)->Self{
+    pub fn new(vertex: &str, pixel: &str, mapping: &CxDrawShaderMapping, cache_dir: Option<&String>)->Result<Self, (GlShaderStage, String)>{
         unsafe fn read_cache(vertex:&str, pixel:&str, cache_dir:Option<&String>)->Option<gl_sys::GLuint>{ 
             if let Some(cache_dir) = cache_dir {
                 let shader_hash = live_id!(shader).str_append(&vertex).str_append(&pixel);
@@ -553,23 +584,28 @@ impl GlShader{
                 gl_sys::ShaderSource(vs, 1, [vertex.as_ptr() as *const _].as_ptr(), ptr::null());
                 gl_sys::CompileShader(vs);
                 //println!("{}", Self::opengl_get_info_log(true, vs as usize, &vertex));
-                if let Some(error) = Self::opengl_has_shader_error(true, vs as usize, &vertex) {
-                    panic!("ERROR::SHADER::VERTEX::COMPILATION_FAILED\n{}", error);
+                if let Some(error) = Self::opengl_has_shader_error(true, vs as usize) {
+                    gl_sys::DeleteShader(vs);
+                    return Err((GlShaderStage::Vertex, error));
                 }
                 let fs = gl_sys::CreateShader(gl_sys::FRAGMENT_SHADER);
                 gl_sys::ShaderSource(fs, 1, [pixel.as_ptr() as *const _].as_ptr(), ptr::null());
                 gl_sys::CompileShader(fs);
                 //println!("{}", Self::opengl_get_info_log(true, fs as usize, &fragment));
-                if let Some(error) = Self::opengl_has_shader_error(true, fs as usize, &pixel) {
-                    panic!("ERROR::SHADER::FRAGMENT::COMPILATION_FAILED\n{}", error);
+                if let Some(error) = Self::opengl_has_shader_error(true, fs as usize) {
+                    gl_sys::DeleteShader(vs);
+                    gl_sys::DeleteShader(fs);
+                    return Err((GlShaderStage::Pixel, error));
                 }
                 
                 let program = gl_sys::CreateProgram();
                 gl_sys::AttachShader(program, vs);
                 gl_sys::AttachShader(program, fs);
                 gl_sys::LinkProgram(program);
-                if let Some(error) = Self::opengl_has_shader_error(false, program as usize, "") {
-                    panic!("ERROR::SHADER::LINK::COMPILATION_FAILED\n{}", error);
+                if let Some(error) = Self::opengl_has_shader_error(false, program as usize) {
+                    gl_sys::DeleteShader(vs);
+                    gl_sys::DeleteShader(fs);
+                    return Err((GlShaderStage::Link, error));
                 }
                 gl_sys::DeleteShader(vs);
                 gl_sys::DeleteShader(fs);
@@ -597,7 +633,7 @@ impl GlShader{
                 } 
             }
 
-            Self{
+            Ok(Self{
                 program,
                 geometries:Self::opengl_get_attributes(program, "packed_geometry_", mapping.geometries.total_slots),
                 instances: Self::opengl_get_attributes(program, "packed_instance_", mapping.instances.total_slots),
@@ -608,7 +644,7 @@ impl GlShader{
                 user_uniforms: Self::opengl_get_uniform(program, "user_table"),
                 live_uniforms: Self::opengl_get_uniform(program, "live_table"),
                 const_table_uniform: Self::opengl_get_uniform(program, "const_table"),
-            }
+            })
         }
     }
 
@@ -631,7 +667,7 @@ impl GlShader{
         }
     }
     
-    pub fn opengl_get_info_log(compile: bool, shader: usize, source: &str) -> String {
+    pub fn opengl_get_log(compile: bool, shader: usize) -> String {
         unsafe {
             let mut length = 0;
             if compile {
@@ -646,21 +682,27 @@ impl GlShader{
                 gl_sys::GetProgramInfoLog(shader as u32, length, ptr::null_mut(), log.as_mut_ptr());
             }
             log.set_len(length as usize);
-            let mut r = "".to_string();
-            r.push_str(CStr::from_ptr(log.as_ptr()).to_str().unwrap());
-            r.push_str("\n");
-            let split = source.split("\n");
-            for (line, chunk) in split.enumerate() {
-                r.push_str(&(line + 1).to_string());
-                r.push_str(":");
-                r.push_str(chunk);
-                r.push_str("\n");
+            if length == 0 {
+                return String::new()
             }
-            r
+            CStr::from_ptr(log.as_ptr()).to_string_lossy().into_owned()
         }
     }
     
-    pub fn opengl_has_shader_error(compile: bool, shader: usize, source: &str) -> Option<String> {
+    pub fn opengl_get_info_log(compile: bool, shader: usize, source: &str) -> String {
+        let mut r = Self::opengl_get_log(compile, shader);
+        r.push_str("\n");
+        let split = source.split("\n");
+        for (line, chunk) in split.enumerate() {
+            r.push_str(&(line + 1).to_string());
+            r.push_str(":");
+            r.push_str(chunk);
+            r.push_str("\n");
+        }
+        r
+    }
+    
+    pub fn opengl_has_shader_error(compile: bool, shader: usize) -> Option<String> {
         //None
         unsafe {
             
@@ -674,7 +716,7 @@ impl GlShader{
             };
             
             if success != gl_sys::TRUE as i32 {
-                Some(Self::opengl_get_info_log(compile, shader, source))
+                Some(Self::opengl_get_log(compile, shader))
             }
             else {
                 None
@@ -746,7 +788,7 @@ impl GlShader{
 }
 
 impl CxOsDrawShader {
-    pub fn new(vertex: &str, pixel: &str, os_type: &OsType) -> Self {
+    pub fn new(vertex: GlslGeneratedShader, pixel: GlslGeneratedShader, draw_shader_ptr: DrawShaderPtr, os_type: &OsType) -> Self {
         // Check if GL_OES_EGL_image_external extension is available in the current device, otherwise do not attempt to use in the shaders.
         let available_extensions = get_gl_string(gl_sys::EXTENSIONS);
         let is_external_texture_supported = available_extensions.split_whitespace().any(|ext| ext == "GL_OES_EGL_image_external");
@@ -770,7 +812,7 @@ impl CxOsDrawShader {
             maybe_ext_tex_extension_sampler = "vec4 sample2dOES(samplerExternalOES sampler, vec2 pos){{ return texture2D(sampler, vec2(pos.x, pos.y));}}".to_string();
         }
         
-        let vertex = format!("
+        let vertex_prelude = format!("
             #version 100
             {}
             precision highp float;
@@ -780,9 +822,9 @@ impl CxOsDrawShader {
             mat4 transpose(mat4 m){{return mat4(m[0][0],m[1][0],m[2][0],m[3][0],m[0][1],m[1][1],m[2][1],m[3][1],m[0][2],m[1][2],m[2][2],m[3][3], m[3][0], m[3][1], m[3][2], m[3][3]);}}
             mat3 transpose(mat3 m){{return mat3(m[0][0],m[1][0],m[2][0],m[0][1],m[1][1],m[2][1],m[0][2],m[1][2],m[2][2]);}}
             mat2 transpose(mat2 m){{return mat2(m[0][0],m[1][0],m[0][1],m[1][1]);}}
-            {}\0", maybe_ext_tex_extension_import, vertex);
+            ", maybe_ext_tex_extension_import);
 
-        let pixel = format!("
+        let pixel_prelude = format!("
             #version 100
             #extension GL_OES_standard_derivatives : enable
             {}
@@ -794,15 +836,57 @@ impl CxOsDrawShader {
             mat4 transpose(mat4 m){{return mat4(m[0][0],m[1][0],m[2][0],m[3][0],m[0][1],m[1][1],m[2][1],m[3][1],m[0][2],m[1][2],m[2][2],m[3][3], m[3][0], m[3][1], m[3][2], m[3][3]);}}
             mat3 transpose(mat3 m){{return mat3(m[0][0],m[1][0],m[2][0],m[0][1],m[1][1],m[2][1],m[0][2],m[1][2],m[2][2]);}}
             mat2 transpose(mat2 m){{return mat2(m[0][0],m[1][0],m[0][1],m[1][1]);}}
-            {}\0", maybe_ext_tex_extension_import, maybe_ext_tex_extension_sampler, pixel);
+            ", maybe_ext_tex_extension_import, maybe_ext_tex_extension_sampler);
+        
+        // the driver reports lines in the source with the prelude, so shift the maps past it
+        let mut vertex_source_map = vertex.source_map;
+        vertex_source_map.offset_by(vertex_prelude.len());
+        let mut pixel_source_map = pixel.source_map;
+        pixel_source_map.offset_by(pixel_prelude.len());
         
             // lets fetch the uniform positions for our uniforms
         CxOsDrawShader {
-            vertex,
-            pixel,
+            vertex: format!("{}{}\0", vertex_prelude, vertex.glsl),
+            pixel: format!("{}{}\0", pixel_prelude, pixel.glsl),
+            vertex_source_map,
+            pixel_source_map,
+            draw_shader_ptr,
+            has_errors: false,
             gl_shader: None,
         }
     }
+    
+    // reports a driver error at the live_design! source it came from, like a rustc error
+    pub fn log_driver_errors(&self, live_registry: &LiveRegistry, stage: GlShaderStage, log: &str) {
+        let fallback_span = if let Some(token_id) = live_registry.ptr_to_node(self.draw_shader_ptr.0).origin.token_id() {
+            token_id.into()
+        }
+        else {
+            LiveErrorSpan::Token(TokenSpan::default())
+        };
+        let live_errors = match stage {
+            GlShaderStage::Vertex => self.vertex_source_map.driver_log_to_live_errors(&self.vertex, log, fallback_span),
+            GlShaderStage::Pixel => self.pixel_source_map.driver_log_to_live_errors(&self.pixel, log, fallback_span),
+            GlShaderStage::Link => ShaderSourceMap::default().driver_log_to_live_errors("", log, fallback_span),
+        };
+        for live_error in live_errors {
+            let err = live_registry.live_error_to_live_file_error(live_error);
+            if std::env::args().find(|v| v == "--message-format=json").is_some(){
+                crate::log::log_with_level(
+                    &err.file,
+                    err.span.start.line,
+                    err.span.start.column,
+                    err.span.end.line,
+                    err.span.end.column,
+                    err.message,
+                    crate::log::LogLevel::Error
+                );
+            }
+            else{
+                crate::error!("{:?} shader error {}", stage, err);
+            }
+        }
+    }
 
     pub fn free_resources(&mut self){
         if let Some(gl_shader) = self.gl_shader.take(){
@@ -10,7 +10,8 @@ use {
         egl_sys,
         x11::xlib_event::*,
         x11::xlib_app::*,
-        linux_media::CxLinuxMedia
+        linux_media::CxLinuxMedia,
+        atspi::AtspiBridge,
     },
     crate::{
         cx_api::{CxOsOp, CxOsApi}, 
@@ -60,6 +61,11 @@ impl Cx {
             return cx.borrow_mut().stdin_event_loop();
         }
         
+        let app_name = std::env::current_exe().ok()
+            .and_then( | exe | exe.file_stem().map( | name | name.to_string_lossy().to_string()))
+            .unwrap_or_default();
+        cx.borrow_mut().os.atspi = AtspiBridge::start(&app_name);
+        
         cx.borrow_mut().call_event_handler(&Event::Startup);
         cx.borrow_mut().redraw_all();
         get_xlib_app_global().start_timer(0,0.008,true);
@@ -199,6 +205,7 @@ impl Cx {
                         self.handle_media_signals();
                         self.call_event_handler(&Event::Signal);
                     }
+                    self.handle_atspi();
                 }
                 else{
                     self.call_event_handler(&Event::Timer(e))
@@ -217,6 +224,24 @@ impl Cx {
     pub(crate) fn handle_networking_events(&mut self) {
     }
     
+    // keeps the at-spi bridge in sync with the accessibility trees and runs what screen readers asked for
+    fn handle_atspi(&mut self) {
+        let Some(atspi) = self.os.atspi.take() else {
+            return
+        };
+        if atspi.is_connected() != self.accessibility_enabled() {
+            self.set_accessibility_enabled(atspi.is_connected());
+        }
+        if self.take_access_trees_changed() {
+            atspi.update_trees(self.access_trees());
+        }
+        atspi.update_focus(self.access_focus());
+        for request in atspi.take_requests() {
+            self.handle_access_request(request);
+        }
+        self.os.atspi = Some(atspi);
+    }
+    
     pub (crate) fn handle_repaint(&mut self, opengl_windows: &mut Vec<OpenglWindow>) {
         self.os.opengl_cx.as_ref().unwrap().make_current();
         let mut passes_todo = Vec::new();
@@ -349,6 +374,9 @@ impl Cx {
 impl CxOsApi for Cx {
     fn init_cx_os(&mut self) {
         self.os.start_time = Some(Instant::now());
+        #[cfg(linux_package)]{
+            self.live_registry.borrow_mut().package_root = Self::native_package_root();
+        }
         self.live_expand();
         self.live_scan_dependencies();
         self.native_load_dependencies();
@@ -370,5 +398,6 @@ pub struct CxOs {
     pub (crate) start_time: Option<Instant>,
     // HACK(eddyb) generalize this to EGL, properly.
     pub(super) opengl_cx: Option<OpenglCx>,
+    pub(super) atspi: Option<AtspiBridge>,
 }
 
@@ -19,6 +19,7 @@ use crate::{
 
 #[derive(FromWasm)]
 pub struct FromWasmLoadDeps {
+    pub base_path: String,
     pub deps: Vec<String>
 }
 
@@ -122,7 +122,7 @@ export class WasmWebBrowser extends WasmBridge {
     FromWasmLoadDeps(args) {
         let promises = [];
         for (let path of args.deps) {
-            promises.push(fetch_path("/makepad/", path))
+            promises.push(fetch_path(args.base_path, path))
         }
         this.load_deps_promise = Promise.all(promises);
     }
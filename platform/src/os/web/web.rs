@@ -70,8 +70,14 @@ impl Cx {
                         deps.push(path.to_string());
                     }
                     
+                    // bundled builds resolve dependencies relative to the site root
+                    #[cfg(wasm_bundle)]
+                    let base_path = "/".to_string();
+                    #[cfg(not(wasm_bundle))]
+                    let base_path = "/makepad/".to_string();
+                    
                     self.os.from_wasm(
-                        FromWasmLoadDeps {deps}
+                        FromWasmLoadDeps {base_path, deps}
                     );
                 },
                 
@@ -515,6 +521,9 @@ impl Cx {
 
 impl CxOsApi for Cx {
     fn init_cx_os(&mut self) {
+        #[cfg(wasm_bundle)]{
+            self.live_registry.borrow_mut().package_root = Some("makepad".to_string());
+        }
         self.live_expand();
         self.live_scan_dependencies();
         
@@ -337,11 +337,13 @@ impl CxPass {
                 );
                 self.pass_uniforms.camera_projection = ortho;
                 self.pass_uniforms.camera_view = Mat4::identity();
+                self.pass_uniforms.camera_inv = Mat4::identity();
             }
             PassMatrixMode::Projection {fov_y, near, far, cam} => {
                 let proj = Mat4::perspective(fov_y, (size.x / size.y) as f32, near, far);
                 self.pass_uniforms.camera_projection = proj;
                 self.pass_uniforms.camera_view = cam;
+                self.pass_uniforms.camera_inv = cam.invert();
             }
         };
     }
@@ -1,5 +1,8 @@
 use crate::makepad_micro_serde::*;
 use crate::log::LogLevel;
+use crate::cx::Cx;
+use crate::draw_list::{CxDrawKind, DrawListId};
+use crate::texture::Texture;
 
 // communication enums for studio
 
@@ -27,11 +30,67 @@ pub struct StudioLogItem{
     pub level: LogLevel
 }
 
+// the place in live_design! where the widget that was clicked while picking is defined
+#[derive(SerBin, DeBin, Debug, Clone)]
+pub struct DesignerPick{
+    pub file_name: String,
+    pub line: u32,
+    pub column: u32,
+}
+
+// the draw calls that a render pass made in the last frame
+#[derive(SerBin, DeBin, Debug, Clone)]
+pub struct DrawPassSample{
+    pub name: String,
+    pub draw_lists: u64,
+    pub draw_calls: u64,
+    pub instances: u64,
+}
+
+#[derive(SerBin, DeBin, Debug, Clone)]
+pub struct DrawSample{
+    pub passes: Vec<DrawPassSample>,
+}
+
+// the gpu memory held by textures, the font atlas is one of them
+#[derive(SerBin, DeBin, Debug, Clone)]
+pub struct MemorySample{
+    pub texture_count: u64,
+    pub texture_bytes: u64,
+    pub font_atlas_bytes: u64,
+}
+
+#[derive(SerBin, DeBin, Debug, Clone)]
+pub struct AnimatorTrackSample{
+    pub track: String,
+    pub state: String,
+    pub playing: bool,
+    // where the track is on its keyframe timeline
+    pub progress: f64,
+}
+
+// a widget in the widget tree of a window, the nodes are sent in depth first order
+#[derive(SerBin, DeBin, Debug, Clone)]
+pub struct WidgetTreeNode{
+    pub depth: u32,
+    pub name: String,
+    pub class: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub animator: Vec<AnimatorTrackSample>,
+}
+
 #[derive(SerBin, DeBin, Debug)]
 pub enum AppToStudio{
     LogItem(StudioLogItem),
     EventSample(EventSample),
-    GPUSample(GPUSample)
+    GPUSample(GPUSample),
+    DesignerPick(DesignerPick),
+    DrawSample(DrawSample),
+    MemorySample(MemorySample),
+    WidgetTree(Vec<WidgetTreeNode>),
 }
 
 #[derive(SerBin, DeBin)]
@@ -42,8 +101,109 @@ pub enum StudioToApp{
     LiveChange{
         file_name: String,
         content: String
-    }
+    },
+    // while picking, clicks select widgets in the designer instead of going to the app
+    DesignerPickMode(bool),
+    // the windows send their widget tree after the next draw
+    RequestWidgetTree,
+    // the app sends what it drew and its texture memory after the next draw
+    RequestDrawSamples,
 }
 
 #[derive(SerBin, DeBin)]
 pub struct StudioToAppVec(pub Vec<StudioToApp>);
+
+impl Cx{
+    // the windows should send their widget tree to studio while they draw
+    pub fn widget_tree_requested(&self)->bool{
+        self.widget_tree_requested
+    }
+    
+    // sends what the last frame drew and the texture memory to studio, if it asked for them
+    pub fn send_studio_draw_samples(&mut self, font_atlas: &Texture){
+        if !Cx::has_studio_web_socket(){
+            return
+        }
+        if let Some((draw, memory)) = self.take_studio_draw_samples(font_atlas){
+            Cx::send_studio_message(AppToStudio::DrawSample(draw));
+            Cx::send_studio_message(AppToStudio::MemorySample(memory));
+        }
+    }
+    
+    // the samples of the last frame if studio asked for them, every request gets one answer
+    fn take_studio_draw_samples(&mut self, font_atlas: &Texture)->Option<(DrawSample, MemorySample)>{
+        if !std::mem::take(&mut self.draw_samples_requested){
+            return None
+        }
+        let mut passes = Vec::new();
+        for pass_id in self.passes.id_iter(){
+            if self.passes.0.is_free(pass_id.0){
+                continue
+            }
+            let pass = &self.passes[pass_id];
+            let Some(draw_list_id) = pass.main_draw_list_id else{
+                continue
+            };
+            let mut sample = DrawPassSample{
+                name: if pass.debug_name.is_empty(){format!("Pass {}", pass_id.0)} else {pass.debug_name.clone()},
+                draw_lists: 0,
+                draw_calls: 0,
+                instances: 0,
+            };
+            self.count_draw_calls(draw_list_id, &mut sample);
+            passes.push(sample);
+        }
+        let mut texture_count = 0;
+        let mut texture_bytes = 0;
+        for texture in &self.textures.0.pool{
+            let bytes = texture.allocated_bytes();
+            if bytes > 0{
+                texture_count += 1;
+                texture_bytes += bytes as u64;
+            }
+        }
+        Some((DrawSample{passes}, MemorySample{
+            texture_count,
+            texture_bytes,
+            font_atlas_bytes: self.textures[font_atlas.texture_id()].allocated_bytes() as u64,
+        }))
+    }
+    
+    fn count_draw_calls(&self, draw_list_id: DrawListId, sample: &mut DrawPassSample){
+        sample.draw_lists += 1;
+        let draw_items = &self.draw_lists[draw_list_id].draw_items;
+        for index in 0..draw_items.len(){
+            let draw_item = &draw_items[index];
+            match &draw_item.kind{
+                CxDrawKind::SubList(sub_list_id) => self.count_draw_calls(*sub_list_id, sample),
+                CxDrawKind::DrawCall(draw_call) => {
+                    let len = draw_item.instances.as_ref().map_or(0, |instances| instances.len());
+                    if len > 0 && draw_call.total_instance_slots > 0{
+                        sample.draw_calls += 1;
+                        sample.instances += (len / draw_call.total_instance_slots) as u64;
+                    }
+                }
+                CxDrawKind::Empty => ()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn sends_no_draw_samples_until_studio_asks() {
+        let mut cx = Cx::new(Box::new(|_, _| {}));
+        let font_atlas = Texture::new(&mut cx);
+        assert!(cx.take_studio_draw_samples(&font_atlas).is_none());
+        
+        cx.draw_samples_requested = true;
+        let (draw, memory) = cx.take_studio_draw_samples(&font_atlas).unwrap();
+        assert!(draw.passes.is_empty());
+        assert_eq!(memory.font_atlas_bytes, 0);
+        // the next frame sends nothing until studio asks again
+        assert!(cx.take_studio_draw_samples(&font_atlas).is_none());
+    }
+}
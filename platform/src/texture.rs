@@ -140,6 +140,25 @@ pub(crate) enum TexturePixel{
 }
 
 impl CxTexture{
+    // the size of the texture on the gpu, or 0 if it is not allocated there yet
+    pub(crate) fn allocated_bytes(&self)->usize{
+        let Some(alloc) = &self.alloc else {
+            return 0
+        };
+        let pixel_bytes = match alloc.pixel {
+            TexturePixel::BGRAu8 => 4,
+            TexturePixel::RGBAf16 => 8,
+            TexturePixel::RGBAf32 => 16,
+            TexturePixel::Ru8 => 1,
+            TexturePixel::RGu8 => 2,
+            TexturePixel::Rf32 => 4,
+            TexturePixel::D32 => 4,
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            TexturePixel::VideoRGB => 3,
+        };
+        alloc.width * alloc.height * pixel_bytes
+    }
+    
     pub(crate) fn set_updated(&mut self, up:bool){
         if let Some(alloc) = &mut self.alloc{
             if let TextureCategory::Vec{updated} = &mut alloc.category{
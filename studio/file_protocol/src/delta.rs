@@ -0,0 +1,403 @@
+use {
+    crate::makepad_micro_serde::{SerBin, DeBin, DeBinErr},
+    std::{collections::BTreeMap, mem},
+};
+
+/// A type for representing a change to a text.
+///
+/// A delta is a sequence of operations that together span the entire text it applies to, from
+/// start to end. Lengths are in bytes. Deltas are kept normalized: adjacent operations of the same
+/// kind are merged, and an insert directly before or after a delete always comes first. This means
+/// that deltas that make the same change have the same operations.
+#[derive(Clone, Debug, Default, Eq, PartialEq, SerBin, DeBin)]
+pub struct Delta {
+    pub ops: Vec<DeltaOp>,
+}
+
+/// A type for representing a single operation of a delta.
+#[derive(Clone, Debug, Eq, PartialEq, SerBin, DeBin)]
+pub enum DeltaOp {
+    /// Keeps the given number of bytes of the text.
+    Retain(usize),
+    /// Inserts the given text.
+    Insert(String),
+    /// Deletes the given number of bytes of the text.
+    Delete(usize),
+}
+
+impl Delta {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an operation that keeps the given number of bytes.
+    pub fn retain(&mut self, len: usize) {
+        if len == 0 {
+            return;
+        }
+        match self.ops.last_mut() {
+            Some(DeltaOp::Retain(last_len)) => *last_len += len,
+            _ => self.ops.push(DeltaOp::Retain(len)),
+        }
+    }
+
+    /// Appends an operation that inserts the given text.
+    pub fn insert(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        if let Some(DeltaOp::Delete(_)) = self.ops.last() {
+            let delete = self.ops.pop().unwrap();
+            self.insert(text);
+            self.ops.push(delete);
+            return;
+        }
+        match self.ops.last_mut() {
+            Some(DeltaOp::Insert(last_text)) => last_text.push_str(text),
+            _ => self.ops.push(DeltaOp::Insert(text.to_string())),
+        }
+    }
+
+    /// Appends an operation that deletes the given number of bytes.
+    pub fn delete(&mut self, len: usize) {
+        if len == 0 {
+            return;
+        }
+        match self.ops.last_mut() {
+            Some(DeltaOp::Delete(last_len)) => *last_len += len,
+            _ => self.ops.push(DeltaOp::Delete(len)),
+        }
+    }
+
+    /// Returns a delta that changes `old_text` into `new_text`, by replacing everything between
+    /// their common prefix and suffix.
+    pub fn replace(old_text: &str, new_text: &str) -> Self {
+        let mut prefix_len = old_text
+            .bytes()
+            .zip(new_text.bytes())
+            .take_while( | (old_byte, new_byte) | old_byte == new_byte)
+            .count();
+        while !old_text.is_char_boundary(prefix_len) || !new_text.is_char_boundary(prefix_len) {
+            prefix_len -= 1;
+        }
+        let max_suffix_len = old_text.len().min(new_text.len()) - prefix_len;
+        let mut suffix_len = old_text
+            .bytes()
+            .rev()
+            .zip(new_text.bytes().rev())
+            .take(max_suffix_len)
+            .take_while( | (old_byte, new_byte) | old_byte == new_byte)
+            .count();
+        while !old_text.is_char_boundary(old_text.len() - suffix_len)
+            || !new_text.is_char_boundary(new_text.len() - suffix_len)
+        {
+            suffix_len -= 1;
+        }
+        let mut delta = Delta::new();
+        delta.retain(prefix_len);
+        delta.insert(&new_text[prefix_len..new_text.len() - suffix_len]);
+        delta.delete(old_text.len() - suffix_len - prefix_len);
+        delta.retain(suffix_len);
+        delta
+    }
+
+    /// Returns the length of the texts this delta applies to.
+    pub fn base_len(&self) -> usize {
+        self.ops.iter().map( | op | match op {
+            DeltaOp::Retain(len) | DeltaOp::Delete(len) => *len,
+            DeltaOp::Insert(_) => 0,
+        }).sum()
+    }
+
+    /// Returns the length of the texts that result from applying this delta.
+    pub fn target_len(&self) -> usize {
+        self.ops.iter().map( | op | match op {
+            DeltaOp::Retain(len) => *len,
+            DeltaOp::Insert(text) => text.len(),
+            DeltaOp::Delete(_) => 0,
+        }).sum()
+    }
+
+    /// Returns whether this delta leaves every text it applies to unchanged.
+    pub fn is_noop(&self) -> bool {
+        self.ops.iter().all( | op | matches!(op, DeltaOp::Retain(_)))
+    }
+
+    /// Applies this delta to the given text, or returns `None` if it does not apply to it.
+    pub fn apply(&self, text: &str) -> Option<String> {
+        if text.len() != self.base_len() {
+            return None;
+        }
+        let mut new_text = String::with_capacity(self.target_len());
+        let mut index = 0;
+        for op in &self.ops {
+            match op {
+                DeltaOp::Retain(len) => {
+                    new_text.push_str(text.get(index..index + len) ?);
+                    index += len;
+                }
+                DeltaOp::Insert(inserted_text) => new_text.push_str(inserted_text),
+                DeltaOp::Delete(len) => {
+                    text.get(index..index + len) ?;
+                    index += len;
+                }
+            }
+        }
+        Some(new_text)
+    }
+
+    /// Returns a single delta that has the same effect as applying this delta followed by
+    /// `other`, or `None` if `other` does not apply to the result of this delta.
+    pub fn compose(&self, other: &Delta) -> Option<Delta> {
+        if self.target_len() != other.base_len() {
+            return None;
+        }
+        let mut delta = Delta::new();
+        let mut ops_0 = self.ops.iter().cloned();
+        let mut ops_1 = other.ops.iter().cloned();
+        let mut op_0 = ops_0.next();
+        let mut op_1 = ops_1.next();
+        loop {
+            match (op_0.take(), op_1.take()) {
+                (None, None) => break,
+                (Some(DeltaOp::Delete(len)), next_op_1) => {
+                    delta.delete(len);
+                    op_0 = ops_0.next();
+                    op_1 = next_op_1;
+                }
+                (next_op_0, Some(DeltaOp::Insert(text))) => {
+                    delta.insert(&text);
+                    op_0 = next_op_0;
+                    op_1 = ops_1.next();
+                }
+                (None, _) | (_, None) => return None,
+                (Some(DeltaOp::Retain(len_0)), Some(DeltaOp::Retain(len_1))) => {
+                    let len = len_0.min(len_1);
+                    delta.retain(len);
+                    op_0 = rest(DeltaOp::Retain, len_0, len, &mut ops_0);
+                    op_1 = rest(DeltaOp::Retain, len_1, len, &mut ops_1);
+                }
+                (Some(DeltaOp::Retain(len_0)), Some(DeltaOp::Delete(len_1))) => {
+                    let len = len_0.min(len_1);
+                    delta.delete(len);
+                    op_0 = rest(DeltaOp::Retain, len_0, len, &mut ops_0);
+                    op_1 = rest(DeltaOp::Delete, len_1, len, &mut ops_1);
+                }
+                (Some(DeltaOp::Insert(text)), Some(DeltaOp::Retain(len_1))) => {
+                    let len = text.len().min(len_1);
+                    delta.insert(text.get(..len) ?);
+                    op_0 = rest_of_text(text, len, &mut ops_0) ?;
+                    op_1 = rest(DeltaOp::Retain, len_1, len, &mut ops_1);
+                }
+                (Some(DeltaOp::Insert(text)), Some(DeltaOp::Delete(len_1))) => {
+                    let len = text.len().min(len_1);
+                    op_0 = rest_of_text(text, len, &mut ops_0) ?;
+                    op_1 = rest(DeltaOp::Delete, len_1, len, &mut ops_1);
+                }
+            }
+        }
+        Some(delta)
+    }
+
+    /// Transforms this delta and `other`, which both apply to the same text, into a pair of deltas
+    /// `(self_after_other, other_after_self)`, such that applying `other` followed by
+    /// `self_after_other` has the same effect as applying `self` followed by `other_after_self`.
+    /// When both deltas insert at the same position, the text of this delta goes first.
+    ///
+    /// Returns `None` if the deltas do not apply to texts of the same length.
+    pub fn transform(&self, other: &Delta) -> Option<(Delta, Delta)> {
+        if self.base_len() != other.base_len() {
+            return None;
+        }
+        let mut delta_0 = Delta::new();
+        let mut delta_1 = Delta::new();
+        let mut ops_0 = self.ops.iter().cloned();
+        let mut ops_1 = other.ops.iter().cloned();
+        let mut op_0 = ops_0.next();
+        let mut op_1 = ops_1.next();
+        loop {
+            match (op_0.take(), op_1.take()) {
+                (None, None) => break,
+                (Some(DeltaOp::Insert(text)), next_op_1) => {
+                    delta_0.insert(&text);
+                    delta_1.retain(text.len());
+                    op_0 = ops_0.next();
+                    op_1 = next_op_1;
+                }
+                (next_op_0, Some(DeltaOp::Insert(text))) => {
+                    delta_0.retain(text.len());
+                    delta_1.insert(&text);
+                    op_0 = next_op_0;
+                    op_1 = ops_1.next();
+                }
+                (None, _) | (_, None) => return None,
+                (Some(DeltaOp::Retain(len_0)), Some(DeltaOp::Retain(len_1))) => {
+                    let len = len_0.min(len_1);
+                    delta_0.retain(len);
+                    delta_1.retain(len);
+                    op_0 = rest(DeltaOp::Retain, len_0, len, &mut ops_0);
+                    op_1 = rest(DeltaOp::Retain, len_1, len, &mut ops_1);
+                }
+                (Some(DeltaOp::Delete(len_0)), Some(DeltaOp::Delete(len_1))) => {
+                    // Both deltas delete the same text, so neither has to do it anymore.
+                    let len = len_0.min(len_1);
+                    op_0 = rest(DeltaOp::Delete, len_0, len, &mut ops_0);
+                    op_1 = rest(DeltaOp::Delete, len_1, len, &mut ops_1);
+                }
+                (Some(DeltaOp::Delete(len_0)), Some(DeltaOp::Retain(len_1))) => {
+                    let len = len_0.min(len_1);
+                    delta_0.delete(len);
+                    op_0 = rest(DeltaOp::Delete, len_0, len, &mut ops_0);
+                    op_1 = rest(DeltaOp::Retain, len_1, len, &mut ops_1);
+                }
+                (Some(DeltaOp::Retain(len_0)), Some(DeltaOp::Delete(len_1))) => {
+                    let len = len_0.min(len_1);
+                    delta_1.delete(len);
+                    op_0 = rest(DeltaOp::Retain, len_0, len, &mut ops_0);
+                    op_1 = rest(DeltaOp::Delete, len_1, len, &mut ops_1);
+                }
+            }
+        }
+        Some((delta_0, delta_1))
+    }
+
+    /// Returns the offset in the text that results from applying this delta that corresponds
+    /// to the given offset in the text it applies to. Offsets in deleted text move to the start
+    /// of the deletion, and offsets at an insertion move past it.
+    pub fn transform_offset(&self, offset: usize) -> usize {
+        let mut old_offset = 0;
+        let mut new_offset = 0;
+        for op in &self.ops {
+            match op {
+                DeltaOp::Retain(len) => {
+                    if old_offset + len > offset {
+                        break;
+                    }
+                    old_offset += len;
+                    new_offset += len;
+                }
+                DeltaOp::Insert(text) => new_offset += text.len(),
+                DeltaOp::Delete(len) => {
+                    if old_offset + len > offset {
+                        return new_offset;
+                    }
+                    old_offset += len;
+                }
+            }
+        }
+        new_offset + (offset - old_offset)
+    }
+}
+
+// Returns what is left of an operation with the given length after `len` bytes of it have been
+// used, or the next operation if nothing is left.
+fn rest(
+    op: fn(usize) -> DeltaOp,
+    op_len: usize,
+    len: usize,
+    ops: &mut impl Iterator<Item = DeltaOp>
+) -> Option<DeltaOp> {
+    if op_len > len {
+        Some(op(op_len - len))
+    } else {
+        ops.next()
+    }
+}
+
+// Like `rest`, but for inserts. Returns `None` if `len` is not at a char boundary of the text.
+fn rest_of_text(
+    mut text: String,
+    len: usize,
+    ops: &mut impl Iterator<Item = DeltaOp>
+) -> Option<Option<DeltaOp>> {
+    if text.len() > len {
+        if !text.is_char_boundary(len) {
+            return None;
+        }
+        Some(Some(DeltaOp::Insert(text.split_off(len))))
+    } else {
+        Some(ops.next())
+    }
+}
+
+/// A type for keeping track of the deltas that a participant of a file has sent to the collab
+/// server, and for transforming the deltas it receives from the server accordingly.
+///
+/// A participant has at most one delta underway to the server at any time. Local deltas that
+/// are made while waiting for the server to acknowledge it are buffered, and composed into a
+/// single delta that is sent once the server does.
+#[derive(Clone, Debug, Default)]
+pub struct DeltaClient {
+    revision: u64,
+    outstanding: Option<Delta>,
+    buffer: Option<Delta>,
+    remote: BTreeMap<u64, Delta>,
+}
+
+impl DeltaClient {
+    /// Creates a client for the given revision of a file, without any local deltas.
+    pub fn new(revision: u64) -> Self {
+        Self {
+            revision,
+            ..Self::default()
+        }
+    }
+
+    /// Returns the newest revision of the file that the client has seen.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Returns whether the server has acknowledged every local delta.
+    pub fn is_synced(&self) -> bool {
+        self.outstanding.is_none()
+    }
+
+    /// Adds a delta that was applied locally. Returns the delta to send to the server together
+    /// with the revision it applies to, if it can be sent right away.
+    pub fn apply_local(&mut self, delta: Delta) -> Option<(u64, Delta)> {
+        if self.outstanding.is_none() {
+            self.outstanding = Some(delta.clone());
+            return Some((self.revision, delta));
+        }
+        self.buffer = Some(match self.buffer.take() {
+            Some(buffer) => buffer.compose(&delta).expect("local deltas should follow each other"),
+            None => delta,
+        });
+        None
+    }
+
+    /// Handles the server acknowledging the outstanding delta as the given revision. Returns the
+    /// next delta to send together with the revision it applies to, if any.
+    pub fn apply_ack(&mut self, revision: u64) -> Option<(u64, Delta)> {
+        self.revision = revision;
+        self.outstanding = self.buffer.take();
+        self.outstanding.clone().map( | delta | (self.revision, delta))
+    }
+
+    /// Handles a delta that another participant applied, resulting in the given revision.
+    ///
+    /// The notification for a delta can arrive before the acknowledgement of a local delta that
+    /// the server applied earlier, so remote deltas are held back until every revision before
+    /// them is known. Call `next_remote` to get the ones that can be applied.
+    pub fn receive_remote(&mut self, revision: u64, delta: Delta) {
+        if revision > self.revision {
+            self.remote.insert(revision, delta);
+        }
+    }
+
+    /// Returns the next remote delta to apply to the local text, which already contains the
+    /// local deltas, if any.
+    pub fn next_remote(&mut self) -> Option<Delta> {
+        let mut delta = self.remote.remove(&(self.revision + 1)) ?;
+        self.revision += 1;
+        for local_delta in [&mut self.outstanding, &mut self.buffer].into_iter().flatten() {
+            let (new_local_delta, new_delta) = mem::take(local_delta)
+                .transform(&delta)
+                .expect("remote deltas should apply to the same revision as local ones");
+            *local_delta = new_local_delta;
+            delta = new_delta;
+        }
+        Some(delta)
+    }
+}
@@ -2,6 +2,7 @@ use {
     crate::{
         makepad_live_id::*,
         makepad_micro_serde::{SerBin, DeBin, DeBinErr},
+        delta::Delta,
     },
 };
 
@@ -36,10 +37,48 @@ pub enum FileRequest {
     /// If the client is the first participant for the file, this also causes the file to be opened
     /// on the server.
     OpenFile(String, u64),
+    /// Requests the collab server to remove the client as a participant from the file with the
+    /// given path. If the client is the last participant for the file, this also causes the file
+    /// to be closed on the server.
+    CloseFile(String),
     /// Requests the collab server to apply the given delta to the given revision of the file with
-    /// the given id.
-    SaveFile(String, String, u64),
-
+    /// the given path. The server writes the resulting text to disk.
+    ApplyDelta(String, u64, Delta, u64),
+    /// Requests the collab server to tell the other participants of the file with the given path
+    /// where the selections of the client are, as byte ranges from anchor to cursor in the newest
+    /// revision of the file.
+    SetSelections(String, Vec<(usize, usize)>),
+    /// Requests the collab server to create an empty file with the given path.
+    CreateFile(String),
+    /// Requests the collab server to create a directory with the given path.
+    CreateDirectory(String),
+    /// Requests the collab server to rename or move the file or directory with the given path to
+    /// the second path.
+    RenameFile(String, String),
+    /// Requests the collab server to delete the file or directory with the given path, including
+    /// everything in it.
+    DeleteFile(String),
+    /// Requests the collab server to search every file in its file tree for the given pattern.
+    /// Matches are streamed back as `SearchResults` notifications carrying the same id, followed
+    /// by a `SearchFinished` notification. Starting a new search cancels the previous one.
+    Search {
+        id: u64,
+        pattern: String,
+        regex: bool,
+        case: bool,
+        /// Only files matching one of these globs are searched, unless it is empty. Globs that
+        /// start with `!` exclude files instead.
+        globs: Vec<String>,
+    },
+    /// Requests the collab server to return the git status of every changed file in its file
+    /// tree.
+    GitStatus,
+    /// Requests the collab server to return the text of the file with the given path as it was in
+    /// the last git commit.
+    GitHeadText(String),
+    /// Requests the collab server to return the git blame for the line with the given index of
+    /// the file with the given path.
+    GitBlame(String, usize),
 }
 
 /// A type for representing either a response or a notification from the collab server.
@@ -57,11 +96,35 @@ pub enum FileResponse {
     /// The result of requesting the collab server to return its file tree.
     LoadFileTree(Result<FileTreeData, FileError>),
     /// The result of requesting the collab server to add the client as a participant to the file
-    /// with the given id.
-    OpenFile(Result<(String, String, u64), FileError>),
+    /// with the given id. Contains the path, the text and revision of the file, and the id.
+    OpenFile(Result<(String, String, u64, u64), FileError>),
+    /// The result of requesting the collab server to remove the client as a participant from the
+    /// file with the given path.
+    CloseFile(Result<String, FileError>),
     /// The result of requesting the collab server to apply a delta to a revision of the file with
-    /// the given id.
-    SaveFile(Result<(String,String,String, u64), FileError>),
+    /// the given path. Contains the path, the new revision of the file, and the id either way.
+    ApplyDelta(Result<(String, u64, u64), (u64, FileError)>),
+    /// The result of requesting the collab server to set the selections of the client.
+    SetSelections(Result<String, FileError>),
+    /// The result of requesting the collab server to create a file.
+    CreateFile(Result<String, FileError>),
+    /// The result of requesting the collab server to create a directory.
+    CreateDirectory(Result<String, FileError>),
+    /// The result of requesting the collab server to rename a file or directory.
+    RenameFile(Result<(String, String), FileError>),
+    /// The result of requesting the collab server to delete a file or directory.
+    DeleteFile(Result<String, FileError>),
+    /// The result of requesting the collab server to search its file tree. This is sent as soon
+    /// as the search has started, and contains the id of the search either way.
+    Search(Result<u64, (u64, FileError)>),
+    /// The result of requesting the collab server to return the git status of its file tree.
+    GitStatus(Result<Vec<GitFileStatus>, FileError>),
+    /// The result of requesting the collab server to return the text of a file in the last git
+    /// commit. Contains the path, and the text if the file was part of the commit.
+    GitHeadText(Result<(String, Option<String>), FileError>),
+    /// The result of requesting the collab server to return the git blame for a line. Contains
+    /// the path, the line index, and the blame.
+    GitBlame(Result<(String, usize, GitBlameLine), FileError>),
 }
 
 /// A type for representing data about a file tree.
@@ -96,10 +159,77 @@ pub struct DirectoryEntry {
 /// A type for representing a notification from the collab server.
 #[derive(Clone, Debug, SerBin, DeBin)]
 pub enum FileNotification {
-    FileChangedOnDisk,
-    // Notifies the client that another client applied the given delta to the file with the given
-    // id. This is only sent for files for which the client is a participant.
-   // DeltaWasApplied(TextFileId),
+    /// Notifies the client that files in the file tree were changed by something other than the
+    /// collab server.
+    FileChangedOnDisk(Vec<FileChange>),
+    /// The matches that the search with the given id found in a single file.
+    SearchResults { id: u64, results: Vec<SearchResult> },
+    /// The search with the given id has looked at every file, or was cancelled.
+    SearchFinished { id: u64 },
+    /// Notifies the client that another client, or a change on disk, applied the given delta to
+    /// the file with the given path, resulting in the given revision. This is only sent for files
+    /// for which the client is a participant.
+    DeltaWasApplied { path: String, revision: u64, delta: Delta },
+    /// Notifies the client that another participant of the file with the given path changed its
+    /// selections. An empty list means that the participant left.
+    SelectionsChanged { path: String, participant: u64, selections: Vec<(usize, usize)> },
+    /// Notifies the client that the git repository of the file tree changed, for instance
+    /// because of a commit or a checkout. Statuses and texts from the last commit that the client
+    /// has might be out of date.
+    GitChanged,
+}
+
+/// A type for representing a change to a file or directory on disk. Paths are relative to the
+/// root of the file tree. Renames show up as a removal followed by a creation.
+#[derive(Clone, Debug, SerBin, DeBin)]
+pub enum FileChange {
+    Created(String),
+    Modified(String),
+    Removed(String),
+}
+
+/// A type for representing a single match of a search.
+#[derive(Clone, Debug, SerBin, DeBin)]
+pub struct SearchResult {
+    /// The path of the file, relative to the root of the file tree.
+    pub path: String,
+    pub line_index: usize,
+    /// The byte range of the match within its line.
+    pub start_byte: usize,
+    pub end_byte: usize,
+    /// The line that contains the match, cut off if it is very long.
+    pub line: String,
+}
+
+/// A type for representing the git status of a file, compared to the last commit.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, SerBin, DeBin)]
+pub enum GitStatus {
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    Untracked,
+    Conflicted,
+}
+
+/// A type for representing a changed file in the git status of a file tree.
+#[derive(Clone, Debug, SerBin, DeBin)]
+pub struct GitFileStatus {
+    /// The path of the file, relative to the root of the file tree.
+    pub path: String,
+    pub status: GitStatus,
+}
+
+/// A type for representing the commit that last changed a line.
+#[derive(Clone, Debug, SerBin, DeBin)]
+pub struct GitBlameLine {
+    /// The hash of the commit, which is all zeros for lines that are not committed yet.
+    pub commit: String,
+    pub author: String,
+    /// The time of the commit, in seconds since the Unix epoch.
+    pub time: u64,
+    /// The first line of the commit message.
+    pub summary: String,
 }
 
 /// A type for representing errors from the collab server.
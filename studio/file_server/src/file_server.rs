@@ -2,20 +2,31 @@ use {
     crate::{
         makepad_file_protocol::{
             DirectoryEntry,
+            Delta,
+            FileChange,
             FileNodeData,
             FileTreeData,
             FileError,
             FileNotification,
             FileRequest,
             FileResponse,
+            GitBlameLine,
+            GitFileStatus,
         },
+        makepad_text_search::search::{Search, SearchOptions},
+        file_watcher::FileWatcher,
+        search::{FileGlobs, ProjectSearch},
+        git,
     },
     std::{
         cmp::Ordering,
+        collections::HashMap,
         fmt,
         fs,
-        path::{Path, PathBuf},
-        sync::{Arc, RwLock},
+        path::{Component, Path, PathBuf},
+        sync::{atomic::{self, AtomicU64}, Arc, Mutex, RwLock, Weak},
+        thread,
+        time::Duration,
     },
 };
 
@@ -33,6 +44,9 @@ impl FileServer {
             next_connection_id: 0,
             shared: Arc::new(RwLock::new(Shared {
                 root_path: root_path.into(),
+                open_files: Mutex::new(HashMap::new()),
+                file_watcher: Mutex::new(None),
+                watching_connections: Mutex::new(Vec::new()),
             })),
         }
     }
@@ -44,10 +58,54 @@ impl FileServer {
     pub fn connect(&mut self, notification_sender: Box<dyn NotificationSender>) -> FileServerConnection {
         let connection_id = ConnectionId(self.next_connection_id);
         self.next_connection_id += 1;
+        {
+            let shared = self.shared.read().unwrap();
+            shared.watching_connections.lock().unwrap().push((connection_id, notification_sender.clone()));
+            let mut file_watcher = shared.file_watcher.lock().unwrap();
+            if file_watcher.is_none() {
+                *file_watcher = Some(FileWatcher::start(
+                    shared.root_path.clone(),
+                    Box::new(watcher_sender(Arc::downgrade(&self.shared))),
+                ));
+            }
+        }
         FileServerConnection {
-            _connection_id:connection_id,
+            connection_id,
             shared: self.shared.clone(),
-            _notification_sender: notification_sender
+            notification_sender,
+            active_search: Arc::new(AtomicU64::new(0)),
+        }
+    }
+    
+    /// Waits until changes on disk are reported to the connections, and returns whether they are
+    /// before the given `timeout` runs out. The watcher starts with the first connection, and
+    /// reads the file tree before it reports anything.
+    pub fn wait_until_watching(&self, timeout: Duration) -> bool {
+        let shared = self.shared.read().unwrap();
+        let file_watcher = shared.file_watcher.lock().unwrap();
+        file_watcher.as_ref().is_some_and( | file_watcher | file_watcher.wait_until_ready(timeout))
+    }
+}
+
+// Returns the function that the file watcher sends its notifications with. Changes on disk to
+// open files are applied to them before they are passed on, so that every participant sees them
+// as a delta. The watcher only holds on to the shared state weakly, so that it stops once the
+// server and its connections are gone.
+fn watcher_sender(shared: Weak<RwLock<Shared >>) -> impl Fn(FileNotification) + Clone + Send + 'static {
+    move | notification: FileNotification | {
+        let Some(shared) = shared.upgrade() else {
+            return
+        };
+        if let FileNotification::FileChangedOnDisk(changes) = &notification {
+            for change in changes {
+                if let FileChange::Modified(path) = change {
+                    sync_open_file_from_disk(&shared, path);
+                }
+            }
+        }
+        let shared = shared.read().unwrap();
+        for (_, notification_sender) in shared.watching_connections.lock().unwrap().iter() {
+            notification_sender.send_notification(notification.clone());
         }
     }
 }
@@ -55,11 +113,13 @@ impl FileServer {
 /// A connection to a collab server.
 pub struct FileServerConnection {
     // The id for this connection.
-    _connection_id: ConnectionId,
+    connection_id: ConnectionId,
     // State is shared between every connection.
     shared: Arc<RwLock<Shared >>,
     // Used to send notifications for this connection.
-    _notification_sender: Box<dyn NotificationSender>,
+    notification_sender: Box<dyn NotificationSender>,
+    // The id of the search that is running for this connection. Searches stop once this changes.
+    active_search: Arc<AtomicU64>,
 }
 
 impl FileServerConnection {
@@ -73,7 +133,17 @@ impl FileServerConnection {
         match request {
             FileRequest::LoadFileTree {with_data} => FileResponse::LoadFileTree(self.load_file_tree(with_data)),
             FileRequest::OpenFile(path,id) => FileResponse::OpenFile(self.open_file(path, id)),
-            FileRequest::SaveFile(path, delta, id) => FileResponse::SaveFile(self.save_file(path, delta, id)),
+            FileRequest::CloseFile(path) => FileResponse::CloseFile(self.close_file(path)),
+            FileRequest::ApplyDelta(path, revision, delta, id) => FileResponse::ApplyDelta(self.apply_delta(path, revision, delta, id).map_err( | error | (id, error))),
+            FileRequest::SetSelections(path, selections) => FileResponse::SetSelections(self.set_selections(path, selections)),
+            FileRequest::CreateFile(path) => FileResponse::CreateFile(self.create_file(path, false)),
+            FileRequest::CreateDirectory(path) => FileResponse::CreateDirectory(self.create_file(path, true)),
+            FileRequest::RenameFile(old_path, new_path) => FileResponse::RenameFile(self.rename_file(old_path, new_path)),
+            FileRequest::DeleteFile(path) => FileResponse::DeleteFile(self.delete_file(path)),
+            FileRequest::Search {id, pattern, regex, case, globs} => FileResponse::Search(self.search(id, pattern, regex, case, globs)),
+            FileRequest::GitStatus => FileResponse::GitStatus(self.git_status()),
+            FileRequest::GitHeadText(path) => FileResponse::GitHeadText(self.git_head_text(path)),
+            FileRequest::GitBlame(path, line_index) => FileResponse::GitBlame(self.git_blame(path, line_index)),
         }
     }
     
@@ -91,13 +161,7 @@ impl FileServerConnection {
                 // Get the file name for the entry.
                 let name = entry.file_name();
                 if let Ok(name_string) = name.into_string() {
-                    if entry_path.is_dir() && name_string == "target"
-                        || name_string.starts_with('.') {
-                        // Skip over directories called "target". This is sort of a hack. The reason
-                        // it's here is that the "target" directory for Rust projects is huge, and
-                        // our current implementation of the file tree widget is not yet fast enough
-                        // to display vast numbers of nodes. We paper over this by pretending the
-                        // "target" directory does not exist.
+                    if is_hidden_entry(&name_string, entry_path.is_dir()) {
                         continue;
                     }
                 }
@@ -165,45 +229,287 @@ impl FileServerConnection {
     }
     
     // Handles an `OpenFile` request.
-    fn open_file(&self, child_path: String, id:u64) -> Result<(String, String, u64), FileError> {
-        let path = self.make_full_path(&child_path);
-        
-        let bytes = fs::read(&path).map_err(
-            | error | FileError::Unknown(error.to_string())
-        ) ?;
-        // Converts the file contents to a `Text`. This is necessarily a lossy conversion
-        // because `Text` assumes everything is UTF-8 encoded, and this isn't always the
-        // case for files on disk (is this a problem?)
-        /*let text: Text = Text::from_lines(String::from_utf8_lossy(&bytes)
-            .lines()
-            .map( | line | line.chars().collect::<Vec<_ >> ())
-            .collect::<Vec<_ >>());*/
-        
-        let text = String::from_utf8_lossy(&bytes);
-        Ok((child_path, text.to_string(), id))
+    fn open_file(&self, child_path: String, id:u64) -> Result<(String, String, u64, u64), FileError> {
+        let shared = self.shared.read().unwrap();
+        let mut open_files = shared.open_files.lock().unwrap();
+        let open_file = match open_files.get_mut(&child_path) {
+            Some(open_file) => open_file,
+            None => {
+                let path = self.make_full_path(&child_path);
+                let bytes = fs::read(&path).map_err(
+                    | error | FileError::Unknown(error.to_string())
+                ) ?;
+                // This is necessarily a lossy conversion because deltas assume everything is
+                // UTF-8 encoded, and this isn't always the case for files on disk.
+                let text = String::from_utf8_lossy(&bytes).to_string();
+                open_files.entry(child_path.clone()).or_insert(OpenFile {
+                    text,
+                    revision: 0,
+                    history_start: 0,
+                    history: Vec::new(),
+                    participants: HashMap::new(),
+                })
+            }
+        };
+        open_file.participants.insert(self.connection_id, Participant {
+            notification_sender: self.notification_sender.clone(),
+            base_revision: open_file.revision,
+            selections: Vec::new(),
+        });
+        for (connection_id, participant) in &open_file.participants {
+            if *connection_id != self.connection_id && !participant.selections.is_empty() {
+                self.notification_sender.send_notification(FileNotification::SelectionsChanged {
+                    path: child_path.clone(),
+                    participant: connection_id.0 as u64,
+                    selections: participant.selections.clone(),
+                });
+            }
+        }
+        Ok((child_path, open_file.text.clone(), open_file.revision, id))
+    }
+    
+    // Handles a `CloseFile` request.
+    fn close_file(&self, child_path: String) -> Result<String, FileError> {
+        let shared = self.shared.read().unwrap();
+        let mut open_files = shared.open_files.lock().unwrap();
+        let open_file = open_files.get_mut(&child_path).filter(
+            | open_file | open_file.participants.contains_key(&self.connection_id)
+        ).ok_or_else( | | FileError::Unknown(format!("{} is not open", child_path))) ?;
+        open_file.remove_participant(&child_path, self.connection_id);
+        if open_file.participants.is_empty() {
+            open_files.remove(&child_path);
+        }
+        Ok(child_path)
     }
     
     // Handles an `ApplyDelta` request.
-    fn save_file(
+    fn apply_delta(
         &self,
         child_path: String,
-        new_content: String,
+        revision: u64,
+        delta: Delta,
         id: u64
-    ) -> Result<(String, String, String, u64), FileError> {
+    ) -> Result<(String, u64, u64), FileError> {
         let path = self.make_full_path(&child_path);
+        let shared = self.shared.read().unwrap();
+        let mut open_files = shared.open_files.lock().unwrap();
+        let open_file = open_files.get_mut(&child_path).filter(
+            | open_file | open_file.participants.contains_key(&self.connection_id)
+        ).ok_or_else( | | FileError::Unknown(format!("{} is not open", child_path))) ?;
+        if revision < open_file.history_start || revision > open_file.revision {
+            return Err(FileError::Unknown(format!("Unknown revision {} of {}", revision, child_path)))
+        }
         
-        let old_content = String::from_utf8_lossy(&fs::read(&path).map_err(
-            | error | FileError::Unknown(error.to_string())
-        ) ?).to_string();
-
-        fs::write(&path, &new_content).map_err(
+        // The delta was made against an older revision, so transform it against every delta
+        // that was applied since then.
+        let mut delta = delta;
+        for past_delta in &open_file.history[(revision - open_file.history_start) as usize..] {
+            delta = delta.transform(past_delta).map(| (delta, _) | delta).ok_or_else(
+                | | FileError::Unknown(format!("Invalid delta for {}", child_path))
+            ) ?;
+        }
+        let text = delta.apply(&open_file.text).ok_or_else(
+            | | FileError::Unknown(format!("Invalid delta for {}", child_path))
+        ) ?;
+        fs::write(&path, &text).map_err(
             | error | FileError::Unknown(error.to_string())
         ) ?;
         
-        Ok((child_path, old_content, new_content, id))
+        open_file.participants.get_mut(&self.connection_id).unwrap().base_revision = revision;
+        open_file.apply_delta(&child_path, text, delta, Some(self.connection_id));
+        Ok((child_path, open_file.revision, id))
+    }
+    
+    // Handles a `SetSelections` request.
+    fn set_selections(&self, child_path: String, selections: Vec<(usize, usize)>) -> Result<String, FileError> {
+        let shared = self.shared.read().unwrap();
+        let mut open_files = shared.open_files.lock().unwrap();
+        let open_file = open_files.get_mut(&child_path).ok_or_else(
+            | | FileError::Unknown(format!("{} is not open", child_path))
+        ) ?;
+        let participant = open_file.participants.get_mut(&self.connection_id).ok_or_else(
+            | | FileError::Unknown(format!("{} is not open", child_path))
+        ) ?;
+        participant.selections = selections.clone();
+        open_file.notify_others(self.connection_id, FileNotification::SelectionsChanged {
+            path: child_path.clone(),
+            participant: self.connection_id.0 as u64,
+            selections,
+        });
+        Ok(child_path)
+    }
+    
+    // Like `make_full_path`, but only for paths that stay inside the root and are not the root
+    // itself. Used for requests that change the file tree.
+    fn make_checked_full_path(&self, child_path: &String) -> Result<PathBuf, FileError> {
+        let is_valid = !child_path.is_empty() && Path::new(child_path).components().all(
+            | component | matches!(component, Component::Normal(_))
+        );
+        if !is_valid {
+            return Err(FileError::Unknown(format!("Invalid path {}", child_path)))
+        }
+        Ok(self.make_full_path(child_path))
+    }
+    
+    // Handles a `CreateFile` or `CreateDirectory` request.
+    fn create_file(&self, child_path: String, is_dir: bool) -> Result<String, FileError> {
+        let path = self.make_checked_full_path(&child_path) ?;
+        if path.exists() {
+            return Err(FileError::Unknown(format!("{} already exists", child_path)))
+        }
+        let result = if is_dir {
+            fs::create_dir_all(&path)
+        }
+        else {
+            path.parent().map_or(Ok(()), fs::create_dir_all).and_then( | _ | fs::write(&path, ""))
+        };
+        result.map_err( | error | FileError::Unknown(error.to_string())) ?;
+        Ok(child_path)
+    }
+    
+    // Handles a `RenameFile` request.
+    fn rename_file(&self, old_child_path: String, new_child_path: String) -> Result<(String, String), FileError> {
+        let old_path = self.make_checked_full_path(&old_child_path) ?;
+        let new_path = self.make_checked_full_path(&new_child_path) ?;
+        if new_path.exists() {
+            return Err(FileError::Unknown(format!("{} already exists", new_child_path)))
+        }
+        let shared = self.shared.read().unwrap();
+        let mut open_files = shared.open_files.lock().unwrap();
+        new_path.parent().map_or(Ok(()), fs::create_dir_all).and_then( | _ | fs::rename(&old_path, &new_path)).map_err(
+            | error | FileError::Unknown(error.to_string())
+        ) ?;
+        // Open files keep their collaboration session when they are moved.
+        let moved_paths: Vec<String> = open_files.keys().filter(
+            | path | is_same_or_child_path(path, &old_child_path)
+        ).cloned().collect();
+        for path in moved_paths {
+            let open_file = open_files.remove(&path).unwrap();
+            open_files.insert(format!("{}{}", new_child_path, &path[old_child_path.len()..]), open_file);
+        }
+        Ok((old_child_path, new_child_path))
+    }
+    
+    // Handles a `DeleteFile` request.
+    fn delete_file(&self, child_path: String) -> Result<String, FileError> {
+        let path = self.make_checked_full_path(&child_path) ?;
+        let shared = self.shared.read().unwrap();
+        let mut open_files = shared.open_files.lock().unwrap();
+        let result = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        }
+        else {
+            fs::remove_file(&path)
+        };
+        result.map_err( | error | FileError::Unknown(error.to_string())) ?;
+        open_files.retain( | path, _ | !is_same_or_child_path(path, &child_path));
+        Ok(child_path)
+    }
+    
+    // Handles a `Search` request. The search itself runs on its own thread, and streams its
+    // results as notifications.
+    fn search(
+        &self,
+        id: u64,
+        pattern: String,
+        regex: bool,
+        case: bool,
+        globs: Vec<String>
+    ) -> Result<u64, (u64, FileError)> {
+        // Cancel the previous search, even if this one turns out to be invalid.
+        self.active_search.store(id, atomic::Ordering::Relaxed);
+        let search = Search::new(&pattern, SearchOptions {
+            case_sensitive: case,
+            whole_word: false,
+            regex,
+        }).map_err( | error | (id, FileError::Unknown(error.to_string()))) ?;
+        let project_search = ProjectSearch {
+            id,
+            search,
+            globs: FileGlobs::new(&globs),
+            active_search: self.active_search.clone(),
+            notification_sender: self.notification_sender.clone(),
+        };
+        let root_path = self.shared.read().unwrap().root_path.clone();
+        thread::spawn(move || project_search.run(&root_path));
+        Ok(id)
+    }
+    
+    // Handles a `GitStatus` request.
+    fn git_status(&self) -> Result<Vec<GitFileStatus>, FileError> {
+        let root_path = self.shared.read().unwrap().root_path.clone();
+        git::git_status(&root_path)
+    }
+    
+    // Handles a `GitHeadText` request.
+    fn git_head_text(&self, child_path: String) -> Result<(String, Option<String>), FileError> {
+        self.make_checked_full_path(&child_path) ?;
+        let root_path = self.shared.read().unwrap().root_path.clone();
+        let text = git::git_head_text(&root_path, &child_path) ?;
+        Ok((child_path, text))
+    }
+    
+    // Handles a `GitBlame` request. Lines are blamed as they are on disk, which open files are
+    // written to as soon as they change.
+    fn git_blame(&self, child_path: String, line_index: usize) -> Result<(String, usize, GitBlameLine), FileError> {
+        self.make_checked_full_path(&child_path) ?;
+        let root_path = self.shared.read().unwrap().root_path.clone();
+        let blame = git::git_blame(&root_path, &child_path, line_index) ?;
+        Ok((child_path, line_index, blame))
     }
 }
 
+impl Drop for FileServerConnection {
+    fn drop(&mut self) {
+        let shared = self.shared.read().unwrap();
+        let mut open_files = shared.open_files.lock().unwrap();
+        open_files.retain( | path, open_file | {
+            open_file.remove_participant(path, self.connection_id);
+            !open_file.participants.is_empty()
+        });
+        // Nobody is left to tell about changes on disk, so there is no point in watching for them.
+        let mut watching_connections = shared.watching_connections.lock().unwrap();
+        watching_connections.retain( | (connection_id, _) | *connection_id != self.connection_id);
+        if watching_connections.is_empty() {
+            shared.file_watcher.lock().unwrap().take();
+        }
+    }
+}
+
+// Brings the open file with the given path up to date with its contents on disk, if they were
+// changed by something other than the collab server. The change is applied as a delta, which
+// is sent to every participant.
+fn sync_open_file_from_disk(shared: &RwLock<Shared>, child_path: &str) {
+    let shared = shared.read().unwrap();
+    let mut open_files = shared.open_files.lock().unwrap();
+    let Some(open_file) = open_files.get_mut(child_path) else {
+        return
+    };
+    let Ok(bytes) = fs::read(shared.root_path.join(child_path)) else {
+        return
+    };
+    let text = String::from_utf8_lossy(&bytes).to_string();
+    if text == open_file.text {
+        return
+    }
+    let delta = Delta::replace(&open_file.text, &text);
+    open_file.apply_delta(child_path, text, delta, None);
+}
+
+// Returns whether `path` is `parent_path` or a path inside it.
+fn is_same_or_child_path(path: &str, parent_path: &str) -> bool {
+    path.strip_prefix(parent_path).is_some_and( | rest | rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Returns whether entries with the given name are left out of the file tree and searches.
+pub fn is_hidden_entry(name: &str, is_dir: bool) -> bool {
+    // Skip over directories called "target". This is sort of a hack. The reason it's here is
+    // that the "target" directory for Rust projects is huge, and our current implementation of
+    // the file tree widget is not yet fast enough to display vast numbers of nodes. We paper over
+    // this by pretending the "target" directory does not exist.
+    is_dir && name == "target" || name.starts_with('.')
+}
+
 /// A trait for sending notifications over a connection.
 pub trait NotificationSender: Send {
     /// This method is necessary to create clones of boxed trait objects.
@@ -239,6 +545,89 @@ impl fmt::Debug for dyn NotificationSender {
 #[derive(Debug)]
 struct Shared {
     root_path: PathBuf,
+    // The collaboration session for each open file, by path.
+    open_files: Mutex<HashMap<String, OpenFile>>,
+    // Watches the file tree for every connection, while there are any.
+    file_watcher: Mutex<Option<FileWatcher>>,
+    // The connections that changes on disk are sent to.
+    watching_connections: Mutex<Vec<(ConnectionId, Box<dyn NotificationSender>)>>,
+}
+
+// The collaboration session for an open file.
+#[derive(Debug)]
+struct OpenFile {
+    // The text of the newest revision.
+    text: String,
+    // The newest revision.
+    revision: u64,
+    // The revision that the oldest delta in the history applies to.
+    history_start: u64,
+    // The deltas from `history_start` to the newest revision.
+    history: Vec<Delta>,
+    participants: HashMap<ConnectionId, Participant>,
+}
+
+impl OpenFile {
+    // Makes `text` the newest revision, and notifies every participant except `origin` of the
+    // delta that led to it. Deltas that every participant has seen are removed from the history.
+    fn apply_delta(&mut self, path: &str, text: String, delta: Delta, origin: Option<ConnectionId>) {
+        for participant in self.participants.values_mut() {
+            for selection in &mut participant.selections {
+                *selection = (delta.transform_offset(selection.0), delta.transform_offset(selection.1));
+            }
+        }
+        self.text = text;
+        self.revision += 1;
+        self.history.push(delta.clone());
+        let notification = FileNotification::DeltaWasApplied {
+            path: path.to_string(),
+            revision: self.revision,
+            delta,
+        };
+        for (connection_id, participant) in &self.participants {
+            if Some(*connection_id) != origin {
+                participant.notification_sender.send_notification(notification.clone());
+            }
+        }
+        self.prune_history();
+    }
+    
+    fn remove_participant(&mut self, path: &str, connection_id: ConnectionId) {
+        if self.participants.remove(&connection_id).is_some() {
+            self.notify_others(connection_id, FileNotification::SelectionsChanged {
+                path: path.to_string(),
+                participant: connection_id.0 as u64,
+                selections: Vec::new(),
+            });
+            self.prune_history();
+        }
+    }
+    
+    fn notify_others(&self, connection_id: ConnectionId, notification: FileNotification) {
+        for (other_connection_id, participant) in &self.participants {
+            if *other_connection_id != connection_id {
+                participant.notification_sender.send_notification(notification.clone());
+            }
+        }
+    }
+    
+    fn prune_history(&mut self) {
+        let oldest_revision = self.participants.values().map( | participant | participant.base_revision).min().unwrap_or(self.revision);
+        if oldest_revision > self.history_start {
+            self.history.drain(..(oldest_revision - self.history_start) as usize);
+            self.history_start = oldest_revision;
+        }
+    }
+}
+
+// A client that is editing an open file.
+#[derive(Debug)]
+struct Participant {
+    notification_sender: Box<dyn NotificationSender>,
+    // The newest revision that the participant is known to have seen.
+    base_revision: u64,
+    // The selections of the participant in the newest revision.
+    selections: Vec<(usize, usize)>,
 }
 
 /// An identifier for a connection.
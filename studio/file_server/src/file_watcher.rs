@@ -0,0 +1,638 @@
+use {
+    crate::{
+        file_server::{is_hidden_entry, NotificationSender},
+        git,
+        makepad_file_protocol::{FileChange, FileNotification},
+        search::IgnoreFile,
+    },
+    std::{
+        collections::{BTreeMap, HashMap},
+        fs,
+        path::{Path, PathBuf},
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc, Condvar, Mutex,
+        },
+        thread,
+        time::{Duration, SystemTime},
+    },
+};
+
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+// Watches the file tree for changes and sends a `FileChangedOnDisk` notification with everything
+// that changed. The same entries as in the file tree are left out, and so is everything that a
+// .gitignore file ignores. On Linux the tree is watched with inotify, elsewhere it is scanned
+// every `WATCH_INTERVAL`. Commits, checkouts and the like in the git repository of the file tree
+// are sent as a `GitChanged` notification. Watching stops when the watcher is dropped.
+#[derive(Debug)]
+pub struct FileWatcher {
+    is_running: Arc<AtomicBool>,
+    is_ready: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl FileWatcher {
+    pub fn start(root_path: PathBuf, notification_sender: Box<dyn NotificationSender>) -> Self {
+        let is_running = Arc::new(AtomicBool::new(true));
+        let is_ready = Arc::new((Mutex::new(false), Condvar::new()));
+        thread::spawn({
+            let is_running = is_running.clone();
+            let is_ready = is_ready.clone();
+            move || {
+                // The first scan of a large tree takes a while, so it happens here rather than
+                // on the thread that starts the watcher.
+                let mut tree_watcher = TreeWatcher::new(&root_path);
+                let git_dir = git::git_dir(&root_path);
+                let mut git_stamps = scan_git_dir(git_dir.as_deref());
+                *is_ready.0.lock().unwrap() = true;
+                is_ready.1.notify_all();
+                while is_running.load(Ordering::Relaxed) {
+                    let changes = tree_watcher.wait_for_changes(WATCH_INTERVAL);
+                    if !is_running.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    if !changes.is_empty() {
+                        notification_sender
+                            .send_notification(FileNotification::FileChangedOnDisk(changes));
+                    }
+                    let new_git_stamps = scan_git_dir(git_dir.as_deref());
+                    if new_git_stamps != git_stamps {
+                        git_stamps = new_git_stamps;
+                        notification_sender.send_notification(FileNotification::GitChanged);
+                    }
+                }
+            }
+        });
+        Self { is_running, is_ready }
+    }
+
+    // Waits until every change made from now on is reported, returns false on a timeout.
+    pub fn wait_until_ready(&self, timeout: Duration) -> bool {
+        let (is_ready, condvar) = &*self.is_ready;
+        let is_ready = condvar
+            .wait_timeout_while(is_ready.lock().unwrap(), timeout, |is_ready| !*is_ready)
+            .unwrap()
+            .0;
+        *is_ready
+    }
+}
+
+impl Drop for FileWatcher {
+    fn drop(&mut self) {
+        self.is_running.store(false, Ordering::Relaxed);
+    }
+}
+
+enum TreeWatcher {
+    #[cfg(target_os = "linux")]
+    Inotify(inotify::InotifyWatcher),
+    Polling(PollingWatcher),
+}
+
+impl TreeWatcher {
+    fn new(root_path: &Path) -> Self {
+        // inotify can run out of watches on very large trees, scanning still works then
+        #[cfg(target_os = "linux")]
+        if let Some(watcher) = inotify::InotifyWatcher::new(root_path) {
+            return Self::Inotify(watcher);
+        }
+        Self::Polling(PollingWatcher::new(root_path))
+    }
+
+    // Blocks for at most `timeout` and returns the changes made in the meantime.
+    fn wait_for_changes(&mut self, timeout: Duration) -> Vec<FileChange> {
+        match self {
+            #[cfg(target_os = "linux")]
+            Self::Inotify(watcher) => watcher.wait_for_changes(timeout),
+            Self::Polling(watcher) => watcher.wait_for_changes(timeout),
+        }
+    }
+}
+
+// The .gitignore files in the tree, by the unix path of the directory they are in.
+#[derive(Default)]
+struct IgnoreRules {
+    ignore_files: HashMap<String, IgnoreFile>,
+}
+
+impl IgnoreRules {
+    fn load(&mut self, dir_path: &Path, unix_path: &str) {
+        match fs::read_to_string(dir_path.join(".gitignore")) {
+            Ok(gitignore) => {
+                self.ignore_files
+                    .insert(unix_path.to_string(), IgnoreFile::parse(unix_path, &gitignore));
+            }
+            Err(_) => {
+                self.ignore_files.remove(unix_path);
+            }
+        }
+    }
+
+    fn is_ignored(&self, unix_path: &str, is_dir: bool) -> bool {
+        // The rules in deeper .gitignore files take precedence.
+        std::iter::once("")
+            .chain(unix_path.match_indices('/').map(|(index, _)| &unix_path[..index]))
+            .filter_map(|dir| self.ignore_files.get(dir))
+            .fold(false, |ignored, ignore_file| {
+                ignore_file.is_ignored(unix_path, is_dir).unwrap_or(ignored)
+            })
+    }
+}
+
+fn child_unix_path(unix_path: &str, name: &str) -> String {
+    if unix_path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}/{}", unix_path, name)
+    }
+}
+
+// Calls `visit` for every entry in the directory and below it that is neither hidden nor
+// ignored, directories before what is in them. Symlinks are not followed.
+fn walk_tree(
+    dir_path: &Path,
+    unix_path: &str,
+    rules: &mut IgnoreRules,
+    visit: &mut dyn FnMut(&fs::DirEntry, &str, bool),
+) {
+    rules.load(dir_path, unix_path);
+    let Ok(entries) = fs::read_dir(dir_path) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let is_dir = file_type.is_dir();
+        if is_hidden_entry(&name, is_dir) {
+            continue;
+        }
+        let entry_unix_path = child_unix_path(unix_path, &name);
+        if rules.is_ignored(&entry_unix_path, is_dir) {
+            continue;
+        }
+        visit(&entry, &entry_unix_path, is_dir);
+        if is_dir {
+            walk_tree(&entry.path(), &entry_unix_path, rules, visit);
+        }
+    }
+}
+
+// Collects changes and merges the ones to the same path, creating and then writing a file is
+// a single creation for instance.
+#[derive(Default)]
+struct ChangeSet {
+    changes: BTreeMap<String, ChangeKind>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+impl ChangeSet {
+    fn created(&mut self, path: String) {
+        let kind = match self.changes.get(&path) {
+            Some(ChangeKind::Removed) => ChangeKind::Modified,
+            Some(&kind) => kind,
+            None => ChangeKind::Created,
+        };
+        self.changes.insert(path, kind);
+    }
+
+    fn modified(&mut self, path: String) {
+        self.changes.entry(path).or_insert(ChangeKind::Modified);
+    }
+
+    fn removed(&mut self, path: String) {
+        if self.changes.get(&path) == Some(&ChangeKind::Created) {
+            self.changes.remove(&path);
+        } else {
+            self.changes.insert(path, ChangeKind::Removed);
+        }
+    }
+
+    fn into_changes(self) -> Vec<FileChange> {
+        self.changes
+            .into_iter()
+            .map(|(path, kind)| match kind {
+                ChangeKind::Created => FileChange::Created(path),
+                ChangeKind::Modified => FileChange::Modified(path),
+                ChangeKind::Removed => FileChange::Removed(path),
+            })
+            .collect()
+    }
+}
+
+// Finds changes by comparing the size and modification time of every entry with those of
+// the previous scan.
+struct PollingWatcher {
+    root_path: PathBuf,
+    stamps: HashMap<String, FileStamp>,
+}
+
+impl PollingWatcher {
+    fn new(root_path: &Path) -> Self {
+        Self {
+            root_path: root_path.to_path_buf(),
+            stamps: scan_file_tree(root_path),
+        }
+    }
+
+    fn wait_for_changes(&mut self, timeout: Duration) -> Vec<FileChange> {
+        thread::sleep(timeout);
+        let new_stamps = scan_file_tree(&self.root_path);
+        let changes = diff_file_trees(&self.stamps, &new_stamps);
+        self.stamps = new_stamps;
+        changes
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct FileStamp {
+    is_dir: bool,
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+fn scan_file_tree(root_path: &Path) -> HashMap<String, FileStamp> {
+    let mut stamps = HashMap::new();
+    walk_tree(root_path, "", &mut IgnoreRules::default(), &mut |entry, unix_path, is_dir| {
+        let Ok(metadata) = entry.metadata() else {
+            return;
+        };
+        stamps.insert(
+            unix_path.to_string(),
+            FileStamp {
+                is_dir,
+                modified: metadata.modified().ok(),
+                len: metadata.len(),
+            },
+        );
+    });
+    stamps
+}
+
+// The files in the git directory that change whenever the last commit or the index does. The
+// reflog of HEAD is written to by every commit, checkout and reset.
+fn scan_git_dir(git_dir: Option<&Path>) -> Vec<Option<FileStamp>> {
+    let Some(git_dir) = git_dir else {
+        return Vec::new();
+    };
+    ["HEAD", "index", "logs/HEAD"]
+        .iter()
+        .map(|name| {
+            let metadata = fs::metadata(git_dir.join(name)).ok()?;
+            Some(FileStamp {
+                is_dir: false,
+                modified: metadata.modified().ok(),
+                len: metadata.len(),
+            })
+        })
+        .collect()
+}
+
+fn diff_file_trees(
+    old_stamps: &HashMap<String, FileStamp>,
+    new_stamps: &HashMap<String, FileStamp>,
+) -> Vec<FileChange> {
+    let mut changes = Vec::new();
+    for (path, old_stamp) in old_stamps {
+        match new_stamps.get(path) {
+            None => changes.push(FileChange::Removed(path.clone())),
+            Some(new_stamp) if new_stamp.is_dir != old_stamp.is_dir => {
+                changes.push(FileChange::Removed(path.clone()));
+                changes.push(FileChange::Created(path.clone()));
+            }
+            Some(new_stamp) if !new_stamp.is_dir && new_stamp != old_stamp => {
+                changes.push(FileChange::Modified(path.clone()))
+            }
+            Some(_) => {}
+        }
+    }
+    for path in new_stamps.keys() {
+        if !old_stamps.contains_key(path) {
+            changes.push(FileChange::Created(path.clone()));
+        }
+    }
+    changes.sort_by(|change_0, change_1| change_path(change_0).cmp(change_path(change_1)));
+    changes
+}
+
+fn change_path(change: &FileChange) -> &str {
+    match change {
+        FileChange::Created(path) | FileChange::Modified(path) | FileChange::Removed(path) => path,
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod inotify {
+    use {
+        super::{child_unix_path, is_hidden_entry, walk_tree, ChangeSet, IgnoreRules},
+        crate::makepad_file_protocol::FileChange,
+        std::{
+            collections::{BTreeMap, HashMap},
+            ffi::CString,
+            os::{
+                raw::{c_char, c_int, c_void},
+                unix::ffi::OsStrExt,
+            },
+            path::{Path, PathBuf},
+            time::{Duration, Instant},
+        },
+    };
+
+    const IN_MODIFY: u32 = 0x2;
+    const IN_ATTRIB: u32 = 0x4;
+    const IN_CLOSE_WRITE: u32 = 0x8;
+    const IN_MOVED_FROM: u32 = 0x40;
+    const IN_MOVED_TO: u32 = 0x80;
+    const IN_CREATE: u32 = 0x100;
+    const IN_DELETE: u32 = 0x200;
+    const IN_Q_OVERFLOW: u32 = 0x4000;
+    const IN_IGNORED: u32 = 0x8000;
+    const IN_ONLYDIR: u32 = 0x0100_0000;
+    const IN_ISDIR: u32 = 0x4000_0000;
+    const IN_NONBLOCK: c_int = 0o4000;
+    const IN_CLOEXEC: c_int = 0o2000000;
+    const POLLIN: i16 = 0x1;
+
+    const WATCH_MASK: u32 = IN_MODIFY
+        | IN_ATTRIB
+        | IN_CLOSE_WRITE
+        | IN_MOVED_FROM
+        | IN_MOVED_TO
+        | IN_CREATE
+        | IN_DELETE
+        | IN_ONLYDIR;
+
+    // The events of a single save or of removing a directory come in a burst. The watcher waits
+    // this long for more events before it sends what it has, so that they go out together.
+    const SETTLE_TIME: Duration = Duration::from_millis(50);
+
+    #[repr(C)]
+    struct PollFd {
+        fd: c_int,
+        events: i16,
+        revents: i16,
+    }
+
+    extern "C" {
+        fn inotify_init1(flags: c_int) -> c_int;
+        fn inotify_add_watch(fd: c_int, path: *const c_char, mask: u32) -> c_int;
+        fn inotify_rm_watch(fd: c_int, wd: c_int) -> c_int;
+        fn poll(fds: *mut PollFd, nfds: u64, timeout: c_int) -> c_int;
+        fn read(fd: c_int, buf: *mut c_void, count: usize) -> isize;
+        fn close(fd: c_int) -> c_int;
+    }
+
+    pub struct InotifyWatcher {
+        fd: c_int,
+        root_path: PathBuf,
+        rules: IgnoreRules,
+        // The unix path of the directory of each watch.
+        watches: HashMap<c_int, String>,
+        // Every entry in the tree that isn't hidden or ignored, and whether it is a directory.
+        entries: BTreeMap<String, bool>,
+        buffer: Vec<u8>,
+    }
+
+    impl InotifyWatcher {
+        // Returns `None` if inotify is unavailable or runs out of watches for the tree.
+        pub fn new(root_path: &Path) -> Option<Self> {
+            let fd = unsafe { inotify_init1(IN_NONBLOCK | IN_CLOEXEC) };
+            if fd < 0 {
+                return None;
+            }
+            let mut watcher = Self {
+                fd,
+                root_path: root_path.to_path_buf(),
+                rules: IgnoreRules::default(),
+                watches: HashMap::new(),
+                entries: BTreeMap::new(),
+                buffer: vec![0; 64 * 1024],
+            };
+            if !watcher.watch_tree("", &mut ChangeSet::default()) {
+                return None;
+            }
+            Some(watcher)
+        }
+
+        pub fn wait_for_changes(&mut self, timeout: Duration) -> Vec<FileChange> {
+            let mut changes = ChangeSet::default();
+            if !self.poll(timeout) {
+                return Vec::new();
+            }
+            let start = Instant::now();
+            loop {
+                self.read_events(&mut changes);
+                if start.elapsed() > super::WATCH_INTERVAL || !self.poll(SETTLE_TIME) {
+                    break;
+                }
+            }
+            changes.into_changes()
+        }
+
+        fn poll(&self, timeout: Duration) -> bool {
+            let mut poll_fd = PollFd {
+                fd: self.fd,
+                events: POLLIN,
+                revents: 0,
+            };
+            unsafe { poll(&mut poll_fd, 1, timeout.as_millis() as c_int) > 0 }
+        }
+
+        fn read_events(&mut self, changes: &mut ChangeSet) {
+            loop {
+                let len = unsafe {
+                    read(self.fd, self.buffer.as_mut_ptr() as *mut c_void, self.buffer.len())
+                };
+                if len <= 0 {
+                    return;
+                }
+                let mut events = Vec::new();
+                let mut offset = 0;
+                // Each event is a struct inotify_event, followed by the nul padded name.
+                while offset + 16 <= len as usize {
+                    let field = |index: usize| {
+                        let start = offset + 4 * index;
+                        u32::from_ne_bytes(self.buffer[start..start + 4].try_into().unwrap())
+                    };
+                    let (wd, mask, name_len) = (field(0) as c_int, field(1), field(3) as usize);
+                    let name = &self.buffer[offset + 16..offset + 16 + name_len];
+                    let name = &name[..name.iter().position(|&byte| byte == 0).unwrap_or(name.len())];
+                    events.push((wd, mask, String::from_utf8_lossy(name).to_string()));
+                    offset += 16 + name_len;
+                }
+                for (wd, mask, name) in events {
+                    self.handle_event(wd, mask, &name, changes);
+                }
+            }
+        }
+
+        fn handle_event(&mut self, wd: c_int, mask: u32, name: &str, changes: &mut ChangeSet) {
+            if mask & IN_Q_OVERFLOW != 0 {
+                self.rescan("", changes);
+                return;
+            }
+            if mask & IN_IGNORED != 0 {
+                self.watches.remove(&wd);
+                return;
+            }
+            let Some(dir_unix_path) = self.watches.get(&wd).cloned() else {
+                return;
+            };
+            // Events about the watched directory itself are reported by its parent.
+            if name.is_empty() {
+                return;
+            }
+            if name == ".gitignore" {
+                self.rescan(&dir_unix_path, changes);
+                return;
+            }
+            let is_dir = mask & IN_ISDIR != 0;
+            if is_hidden_entry(name, is_dir) {
+                return;
+            }
+            let unix_path = child_unix_path(&dir_unix_path, name);
+            if mask & (IN_DELETE | IN_MOVED_FROM) != 0 {
+                self.forget_tree(&unix_path, changes);
+            }
+            if self.rules.is_ignored(&unix_path, is_dir) {
+                return;
+            }
+            if mask & (IN_CREATE | IN_MOVED_TO) != 0 {
+                if self.entries.insert(unix_path.clone(), is_dir).is_none() {
+                    changes.created(unix_path.clone());
+                } else if !is_dir {
+                    changes.modified(unix_path.clone());
+                }
+                if is_dir {
+                    self.watch_tree(&unix_path, changes);
+                }
+            }
+            if mask & (IN_MODIFY | IN_ATTRIB | IN_CLOSE_WRITE) != 0
+                && !is_dir
+                && self.entries.contains_key(&unix_path)
+            {
+                changes.modified(unix_path);
+            }
+        }
+
+        // Watches the directory and every directory below it, reporting what is in them as
+        // created. Returns false when inotify runs out of watches.
+        fn watch_tree(&mut self, unix_path: &str, changes: &mut ChangeSet) -> bool {
+            let Self {
+                fd,
+                root_path,
+                rules,
+                watches,
+                entries,
+                ..
+            } = self;
+            if !add_watch(*fd, root_path, unix_path, watches) {
+                return false;
+            }
+            let mut is_ok = true;
+            walk_tree(&root_path.join(unix_path), unix_path, rules, &mut |_, entry_unix_path, is_dir| {
+                // The directory is watched before it is read, so nothing created in it is missed.
+                if is_dir && !add_watch(*fd, root_path, entry_unix_path, watches) {
+                    is_ok = false;
+                }
+                if entries.insert(entry_unix_path.to_string(), is_dir).is_none() {
+                    changes.created(entry_unix_path.to_string());
+                }
+            });
+            is_ok
+        }
+
+        // Reports the entry and everything below it as removed, and stops watching them.
+        fn forget_tree(&mut self, unix_path: &str, changes: &mut ChangeSet) {
+            for path in take_tree(&mut self.entries, unix_path, true) {
+                changes.removed(path);
+            }
+            self.unwatch_tree(unix_path, true);
+        }
+
+        // Walks the directory again after its ignore rules changed or events were lost, and
+        // reports the difference with what was there before.
+        fn rescan(&mut self, unix_path: &str, changes: &mut ChangeSet) {
+            let old_entries = take_tree(&mut self.entries, unix_path, false);
+            self.unwatch_tree(unix_path, false);
+            let mut rescan_changes = ChangeSet::default();
+            self.watch_tree(unix_path, &mut rescan_changes);
+            for path in &old_entries {
+                if !self.entries.contains_key(path) {
+                    changes.removed(path.clone());
+                }
+            }
+            for change in rescan_changes.into_changes() {
+                if let FileChange::Created(path) = change {
+                    if old_entries.binary_search(&path).is_err() {
+                        changes.created(path);
+                    }
+                }
+            }
+        }
+
+        fn unwatch_tree(&mut self, unix_path: &str, include_root: bool) {
+            let fd = self.fd;
+            self.watches.retain(|&wd, watch_unix_path| {
+                if !is_in_tree(watch_unix_path, unix_path, include_root) {
+                    return true;
+                }
+                unsafe { inotify_rm_watch(fd, wd) };
+                false
+            });
+            self.rules
+                .ignore_files
+                .retain(|dir, _| !is_in_tree(dir, unix_path, include_root));
+        }
+    }
+
+    impl Drop for InotifyWatcher {
+        fn drop(&mut self) {
+            unsafe { close(self.fd) };
+        }
+    }
+
+    fn add_watch(fd: c_int, root_path: &Path, unix_path: &str, watches: &mut HashMap<c_int, String>) -> bool {
+        let Ok(path) = CString::new(root_path.join(unix_path).as_os_str().as_bytes()) else {
+            return false;
+        };
+        let wd = unsafe { inotify_add_watch(fd, path.as_ptr(), WATCH_MASK) };
+        if wd < 0 {
+            return false;
+        }
+        watches.insert(wd, unix_path.to_string());
+        true
+    }
+
+    // Whether `path` is below the directory `tree`, or is `tree` itself if `include_root`.
+    fn is_in_tree(path: &str, tree: &str, include_root: bool) -> bool {
+        if path == tree {
+            return include_root;
+        }
+        tree.is_empty() || path.strip_prefix(tree).is_some_and(|rest| rest.starts_with('/'))
+    }
+
+    // Removes the entries below the directory from `entries` and returns their paths in order.
+    fn take_tree(entries: &mut BTreeMap<String, bool>, unix_path: &str, include_root: bool) -> Vec<String> {
+        let paths = entries
+            .range(unix_path.to_string()..)
+            .map(|(path, _)| path)
+            .take_while(|path| path.starts_with(unix_path))
+            .filter(|path| is_in_tree(path, unix_path, include_root))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in &paths {
+            entries.remove(path);
+        }
+        paths
+    }
+}
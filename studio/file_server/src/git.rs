@@ -0,0 +1,121 @@
+use {
+    crate::{
+        makepad_file_protocol::{FileError, GitBlameLine, GitFileStatus, GitStatus},
+        makepad_shell::shell_env_cap_split,
+    },
+    std::path::{Path, PathBuf},
+};
+
+// Runs git with the given arguments in the given directory, and returns what it wrote to
+// stdout.
+fn git(cwd: &Path, args: &[&str]) -> Result<String, FileError> {
+    let (stdout, stderr, success) = shell_env_cap_split(&[], cwd, "git", args);
+    if !success {
+        return Err(FileError::Unknown(stderr.trim().to_string()));
+    }
+    Ok(stdout)
+}
+
+/// Returns the git directory of the repository that the given path is in, if any.
+pub fn git_dir(root_path: &Path) -> Option<PathBuf> {
+    let stdout = git(root_path, &["rev-parse", "--absolute-git-dir"]).ok()?;
+    Some(PathBuf::from(stdout.trim()))
+}
+
+/// Returns the git status of every changed file under the given path. Paths are relative to
+/// the given path.
+pub fn git_status(root_path: &Path) -> Result<Vec<GitFileStatus>, FileError> {
+    // The paths in the status are relative to the root of the repository, which does not have
+    // to be the root of the file tree.
+    let prefix = git(root_path, &["rev-parse", "--show-prefix"])?;
+    let stdout = git(
+        root_path,
+        &["status", "--porcelain=v1", "-z", "--untracked-files=all", "--", "."],
+    )?;
+    Ok(parse_status(&stdout, prefix.trim_end_matches('\n')))
+}
+
+/// Parses the output of `git status --porcelain=v1 -z`, and leaves out the given prefix from
+/// every path.
+pub fn parse_status(output: &str, prefix: &str) -> Vec<GitFileStatus> {
+    let mut statuses = Vec::new();
+    let mut entries = output.split('\0');
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        let (code, path) = entry.split_at(3);
+        let code = code.as_bytes();
+        let status = match (code[0], code[1]) {
+            (b'?', b'?') => GitStatus::Untracked,
+            (b'U', _) | (_, b'U') | (b'A', b'A') | (b'D', b'D') => GitStatus::Conflicted,
+            (b'R', _) | (_, b'R') => {
+                // Renames are followed by the path they were renamed from.
+                entries.next();
+                GitStatus::Renamed
+            }
+            (b'C', _) | (_, b'C') => {
+                entries.next();
+                GitStatus::Added
+            }
+            (b'A', _) => GitStatus::Added,
+            (b'D', _) | (_, b'D') => GitStatus::Deleted,
+            _ => GitStatus::Modified,
+        };
+        let Some(path) = path.strip_prefix(prefix) else {
+            continue;
+        };
+        statuses.push(GitFileStatus {
+            path: path.to_string(),
+            status,
+        });
+    }
+    statuses
+}
+
+/// Returns the text of the file with the given path in the last commit, or `None` if it was not
+/// part of it.
+pub fn git_head_text(root_path: &Path, child_path: &str) -> Result<Option<String>, FileError> {
+    if git(root_path, &["rev-parse", "--verify", "--quiet", "HEAD"]).is_err() {
+        return Ok(None);
+    }
+    let object = format!("HEAD:./{}", child_path);
+    if git(root_path, &["cat-file", "-e", &object]).is_err() {
+        return Ok(None);
+    }
+    git(root_path, &["show", &object]).map(Some)
+}
+
+/// Returns the commit that last changed the line with the given index of the file with the
+/// given path, as it is on disk.
+pub fn git_blame(root_path: &Path, child_path: &str, line_index: usize) -> Result<GitBlameLine, FileError> {
+    let range = format!("{},{}", line_index + 1, line_index + 1);
+    let stdout = git(root_path, &["blame", "--porcelain", "-L", &range, "--", child_path])?;
+    parse_blame(&stdout).ok_or_else(|| FileError::Unknown("cannot parse git blame".to_string()))
+}
+
+/// Parses the output of `git blame --porcelain` for a single line.
+pub fn parse_blame(output: &str) -> Option<GitBlameLine> {
+    let mut lines = output.lines();
+    let commit = lines.next()?.split(' ').next()?.to_string();
+    let mut blame = GitBlameLine {
+        commit,
+        author: String::new(),
+        time: 0,
+        summary: String::new(),
+    };
+    for line in lines {
+        // The line itself comes last, after a tab.
+        if line.starts_with('\t') {
+            break;
+        }
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "author" => blame.author = value.to_string(),
+            "author-time" => blame.time = value.parse().unwrap_or(0),
+            "summary" => blame.summary = value.to_string(),
+            _ => {}
+        }
+    }
+    Some(blame)
+}
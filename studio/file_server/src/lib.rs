@@ -3,8 +3,16 @@
 pub mod file_server;
 #[cfg(not(target_arch = "wasm32"))]
 pub use file_server::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod file_watcher;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod search;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod git;
 
 pub use makepad_micro_serde;
 pub use makepad_live_id;
 pub use makepad_file_protocol;
+pub use makepad_text_search;
+pub use makepad_shell;
 pub use makepad_file_protocol::*;
@@ -0,0 +1,330 @@
+use {
+    crate::{
+        file_server::{is_hidden_entry, NotificationSender},
+        makepad_text_search::{search::Search, text::Lines},
+        makepad_file_protocol::{FileNotification, SearchResult},
+    },
+    std::{
+        fs,
+        path::Path,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc,
+        },
+    },
+};
+
+// Stop looking once this many matches have been found, a search for `e` would otherwise send
+// the entire project over.
+const MAX_SEARCH_RESULTS: usize = 10000;
+// Lines longer than this are cut off in the results. Minified files can have very long lines.
+const MAX_LINE_LEN: usize = 256;
+// Only the start of longer lines is searched, a regex takes time proportional to the length
+// of the line for every match it looks for.
+const MAX_SEARCHED_LINE_LEN: usize = 4096;
+// Larger files are skipped, these are hardly ever source files.
+const MAX_FILE_SIZE: u64 = 8 * 1024 * 1024;
+
+// A search that runs on its own thread, until it has looked at every file or until another
+// search replaces it as the active one.
+pub struct ProjectSearch {
+    pub id: u64,
+    pub search: Search,
+    pub globs: FileGlobs,
+    pub active_search: Arc<AtomicU64>,
+    pub notification_sender: Box<dyn NotificationSender>,
+}
+
+impl ProjectSearch {
+    pub fn run(self, root_path: &Path) {
+        let mut state = SearchState {
+            result_count: 0,
+            ignore_files: Vec::new(),
+        };
+        if !self.search.pattern().is_empty() {
+            self.search_directory(&mut state, root_path, "");
+        }
+        self.notification_sender
+            .send_notification(FileNotification::SearchFinished { id: self.id });
+    }
+
+    fn is_cancelled(&self, state: &SearchState) -> bool {
+        state.result_count >= MAX_SEARCH_RESULTS
+            || self.active_search.load(Ordering::Relaxed) != self.id
+    }
+
+    fn search_directory(&self, state: &mut SearchState, path: &Path, unix_path: &str) {
+        let ignore_file_count = state.ignore_files.len();
+        if let Ok(gitignore) = fs::read_to_string(path.join(".gitignore")) {
+            state
+                .ignore_files
+                .push(IgnoreFile::parse(unix_path, &gitignore));
+        }
+        // the file type of a dir entry doesn't follow symlinks, links are skipped so a link
+        // to a parent dir can't make the search loop
+        let mut entries = match fs::read_dir(path) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let file_type = entry.file_type().ok()?;
+                    if file_type.is_symlink() {
+                        return None;
+                    }
+                    Some((entry.file_name().into_string().ok()?, entry.path(), file_type))
+                })
+                .collect::<Vec<_>>(),
+            Err(_) => Vec::new(),
+        };
+        entries.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+        for (name, entry_path, file_type) in entries {
+            if self.is_cancelled(state) {
+                break;
+            }
+            let is_dir = file_type.is_dir();
+            if is_hidden_entry(&name, is_dir) {
+                continue;
+            }
+            let entry_unix_path = if unix_path.is_empty() {
+                name
+            } else {
+                format!("{}/{}", unix_path, name)
+            };
+            if state
+                .ignore_files
+                .iter()
+                .fold(false, |ignored, ignore_file| {
+                    ignore_file.is_ignored(&entry_unix_path, is_dir).unwrap_or(ignored)
+                })
+            {
+                continue;
+            }
+            if is_dir {
+                self.search_directory(state, &entry_path, &entry_unix_path);
+            } else if file_type.is_file() && self.globs.is_match(&entry_unix_path) {
+                self.search_file(state, &entry_path, entry_unix_path);
+            }
+        }
+        state.ignore_files.truncate(ignore_file_count);
+    }
+
+    fn search_file(&self, state: &mut SearchState, path: &Path, unix_path: String) {
+        if fs::metadata(path).map_or(true, |metadata| metadata.len() > MAX_FILE_SIZE) {
+            return;
+        }
+        // Skip over files that are not UTF-8, these are almost always binary files.
+        let Ok(text) = fs::read(path).map(String::from_utf8) else {
+            return;
+        };
+        let Ok(text) = text else {
+            return;
+        };
+        if text.contains('\0') {
+            return;
+        }
+        let lines: Lines = text
+            .lines()
+            .map(|line| truncate(line, MAX_SEARCHED_LINE_LEN).to_string())
+            .collect();
+        let mut results = Vec::new();
+        for search_match in self.search.find_up_to(&lines, &[], MAX_SEARCH_RESULTS - state.result_count) {
+            results.push(SearchResult {
+                path: unix_path.clone(),
+                line_index: search_match.start.line_index,
+                start_byte: search_match.start.byte_index,
+                end_byte: search_match.end.byte_index,
+                line: truncate(&lines[search_match.start.line_index], MAX_LINE_LEN).to_string(),
+            });
+            state.result_count += 1;
+        }
+        if !results.is_empty() {
+            self.notification_sender
+                .send_notification(FileNotification::SearchResults {
+                    id: self.id,
+                    results,
+                });
+        }
+    }
+}
+
+// Cuts a line off at a char boundary at or before `max_len`.
+fn truncate(line: &str, max_len: usize) -> &str {
+    let mut len = line.len().min(max_len);
+    while !line.is_char_boundary(len) {
+        len -= 1;
+    }
+    &line[..len]
+}
+
+struct SearchState {
+    result_count: usize,
+    // The .gitignore files of the directory that is being searched and all its parents.
+    ignore_files: Vec<IgnoreFile>,
+}
+
+// The globs that decide which files are searched. Globs without a `/` are matched against the
+// file name, others against the path relative to the root.
+#[derive(Debug, Default)]
+pub struct FileGlobs {
+    include: Vec<Vec<char>>,
+    exclude: Vec<Vec<char>>,
+}
+
+impl FileGlobs {
+    pub fn new(globs: &[String]) -> Self {
+        let mut file_globs = Self::default();
+        for glob in globs.iter().map(|glob| glob.trim()).filter(|glob| !glob.is_empty()) {
+            match glob.strip_prefix('!') {
+                Some(glob) => file_globs.exclude.push(glob.chars().collect()),
+                None => file_globs.include.push(glob.chars().collect()),
+            }
+        }
+        file_globs
+    }
+
+    pub fn is_match(&self, unix_path: &str) -> bool {
+        let path = unix_path.chars().collect::<Vec<_>>();
+        let name = unix_path
+            .rsplit('/')
+            .next()
+            .unwrap_or(unix_path)
+            .chars()
+            .collect::<Vec<_>>();
+        let matches = |glob: &Vec<char>| {
+            if glob.contains(&'/') {
+                glob_match(glob, &path)
+            } else {
+                glob_match(glob, &name)
+            }
+        };
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+}
+
+#[derive(Debug)]
+struct IgnoreRule {
+    glob: Vec<char>,
+    is_negated: bool,
+    is_dir_only: bool,
+    // Rules with a `/` other than at the end only match relative to their .gitignore file,
+    // others match the name of an entry at any depth.
+    is_anchored: bool,
+}
+
+#[derive(Debug)]
+pub struct IgnoreFile {
+    // The unix path of the directory that contains the .gitignore file.
+    base_path: String,
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreFile {
+    pub fn parse(base_path: &str, gitignore: &str) -> Self {
+        let rules = gitignore
+            .lines()
+            .map(|line| line.trim_end())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| {
+                let (is_negated, line) = match line.strip_prefix('!') {
+                    Some(line) => (true, line),
+                    None => (false, line.strip_prefix('\\').unwrap_or(line)),
+                };
+                let (is_dir_only, line) = match line.strip_suffix('/') {
+                    Some(line) => (true, line),
+                    None => (false, line),
+                };
+                IgnoreRule {
+                    glob: line.trim_start_matches('/').chars().collect(),
+                    is_negated,
+                    is_dir_only,
+                    is_anchored: line.contains('/'),
+                }
+            })
+            .collect();
+        Self {
+            base_path: base_path.to_string(),
+            rules,
+        }
+    }
+
+    // Returns whether the last rule that matches the given path ignores it, or `None` if no rule
+    // matches it.
+    pub fn is_ignored(&self, unix_path: &str, is_dir: bool) -> Option<bool> {
+        let relative_path = if self.base_path.is_empty() {
+            unix_path
+        } else {
+            unix_path
+                .strip_prefix(self.base_path.as_str())?
+                .strip_prefix('/')?
+        };
+        let path = relative_path.chars().collect::<Vec<_>>();
+        let name = relative_path
+            .rsplit('/')
+            .next()
+            .unwrap_or(relative_path)
+            .chars()
+            .collect::<Vec<_>>();
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| {
+                (is_dir || !rule.is_dir_only)
+                    && glob_match(&rule.glob, if rule.is_anchored { &path } else { &name })
+            })
+            .map(|rule| !rule.is_negated)
+    }
+}
+
+// Matches a path against a glob. `*` and `?` do not match a `/`, `**/` matches any number of
+// directories and a trailing `**` matches everything. Character classes such as `[a-z]` and
+// `[!0-9]` are supported as well.
+pub fn glob_match(glob: &[char], path: &[char]) -> bool {
+    match glob {
+        [] => path.is_empty(),
+        ['*', '*'] => true,
+        ['*', '*', '/', glob @ ..] => {
+            glob_match(glob, path)
+                || path
+                    .iter()
+                    .enumerate()
+                    .any(|(index, char)| *char == '/' && glob_match(glob, &path[index + 1..]))
+        }
+        ['*', glob @ ..] => (0..=path.len())
+            .take_while(|&index| index == 0 || path[index - 1] != '/')
+            .any(|index| glob_match(glob, &path[index..])),
+        ['?', glob @ ..] => match path {
+            [char, path @ ..] if *char != '/' => glob_match(glob, path),
+            _ => false,
+        },
+        ['[', class @ ..] => {
+            let Some(end) = class.iter().skip(1).position(|char| *char == ']').map(|end| end + 1) else {
+                return matches!(path, ['[', path @ ..] if glob_match(class, path));
+            };
+            let (is_negated, ranges) = match &class[..end] {
+                ['!' | '^', ranges @ ..] => (true, ranges),
+                ranges => (false, ranges),
+            };
+            match path {
+                [char, path @ ..] if *char != '/' => {
+                    let mut is_match = false;
+                    let mut index = 0;
+                    while index < ranges.len() {
+                        if index + 2 < ranges.len() && ranges[index + 1] == '-' {
+                            is_match |= (ranges[index]..=ranges[index + 2]).contains(char);
+                            index += 3;
+                        } else {
+                            is_match |= ranges[index] == *char;
+                            index += 1;
+                        }
+                    }
+                    is_match != is_negated && glob_match(&class[end + 1..], path)
+                }
+                _ => false,
+            }
+        }
+        ['\\', char, glob @ ..] | [char, glob @ ..] => match path {
+            [path_char, path @ ..] if path_char == char => glob_match(glob, path),
+            _ => false,
+        },
+    }
+}
@@ -0,0 +1,201 @@
+mod common;
+
+use {
+    common::TempDir,
+    makepad_file_server::{
+        Delta, DeltaClient, FileNotification, FileRequest, FileResponse, FileServer,
+    },
+    std::{
+        fs,
+        sync::mpsc::{self, Receiver},
+        time::{Duration, Instant},
+    },
+};
+
+fn create_project(name: &str) -> TempDir {
+    TempDir::with_files(name, &[("main.rs", b"fn main() {}\n")])
+}
+
+fn delta(ops: &[(&str, usize, &str)]) -> Delta {
+    let mut delta = Delta::new();
+    for (kind, len, text) in ops {
+        match *kind {
+            "r" => delta.retain(*len),
+            "i" => delta.insert(text),
+            _ => delta.delete(*len),
+        }
+    }
+    delta
+}
+
+fn open_file(response: FileResponse) -> (String, u64) {
+    match response {
+        FileResponse::OpenFile(Ok((_, text, revision, _))) => (text, revision),
+        response => panic!("{:?}", response),
+    }
+}
+
+fn applied_revision(response: FileResponse) -> u64 {
+    match response {
+        FileResponse::ApplyDelta(Ok((_, revision, _))) => revision,
+        response => panic!("{:?}", response),
+    }
+}
+
+fn recv_delta(receiver: &Receiver<FileNotification>) -> (u64, Delta) {
+    loop {
+        if let FileNotification::DeltaWasApplied {revision, delta, ..} = receiver.recv_timeout(Duration::from_secs(5)).unwrap() {
+            return (revision, delta)
+        }
+    }
+}
+
+#[test]
+fn transforms_concurrent_deltas() {
+    let text = "hello world";
+    let cases = [
+        (delta(&[("r", 5, ""), ("i", 0, ","), ("r", 6, "")]), delta(&[("r", 6, ""), ("d", 5, ""), ("i", 0, "there")])),
+        (delta(&[("r", 2, ""), ("d", 6, ""), ("r", 3, "")]), delta(&[("r", 4, ""), ("d", 5, ""), ("r", 2, "")])),
+        (delta(&[("i", 0, "a"), ("r", 11, "")]), delta(&[("i", 0, "b"), ("r", 11, "")])),
+        (delta(&[("d", 11, "")]), delta(&[("r", 3, ""), ("i", 0, "é"), ("r", 8, "")])),
+    ];
+    for (delta_0, delta_1) in cases {
+        let (delta_0_after_1, delta_1_after_0) = delta_0.transform(&delta_1).unwrap();
+        let text_0 = delta_1_after_0.apply(&delta_0.apply(text).unwrap()).unwrap();
+        let text_1 = delta_0_after_1.apply(&delta_1.apply(text).unwrap()).unwrap();
+        assert_eq!(text_0, text_1);
+        let composed = delta_0.compose(&delta_1_after_0).unwrap();
+        assert_eq!(composed.apply(text).unwrap(), text_0);
+    }
+    // When both insert at the same position, the first delta goes first.
+    let (delta_0, delta_1) = (delta(&[("i", 0, "a"), ("r", 1, "")]), delta(&[("i", 0, "b"), ("r", 1, "")]));
+    let (_, delta_1_after_0) = delta_0.transform(&delta_1).unwrap();
+    assert_eq!(delta_1_after_0.apply(&delta_0.apply("x").unwrap()).unwrap(), "abx");
+    // Deltas that do not fit the text or split a char are refused.
+    assert!(delta(&[("r", 3, "")]).apply("ab").is_none());
+    assert!(delta(&[("r", 1, ""), ("d", 1, "")]).apply("é").is_none());
+}
+
+#[test]
+fn replaces_changed_part_of_text() {
+    for (old_text, new_text) in [("abcdef", "abXYef"), ("aaa", "aaaa"), ("", "new"), ("héllo", "hällo"), ("same", "same")] {
+        let delta = Delta::replace(old_text, new_text);
+        assert_eq!(delta.apply(old_text).unwrap(), new_text);
+    }
+    assert_eq!(Delta::replace("abcdef", "abXYef"), delta(&[("r", 2, ""), ("i", 0, "XY"), ("d", 2, ""), ("r", 2, "")]));
+    assert!(Delta::replace("same", "same").is_noop());
+}
+
+#[test]
+fn converges_concurrent_edits_of_two_participants() {
+    let project = create_project("converge");
+    let root_path = project.path();
+    let mut server = FileServer::new(root_path);
+    let (sender_a, receiver_a) = mpsc::channel();
+    let (sender_b, receiver_b) = mpsc::channel();
+    let connection_a = server.connect(Box::new(move | notification | {let _ = sender_a.send(notification);}));
+    let connection_b = server.connect(Box::new(move | notification | {let _ = sender_b.send(notification);}));
+
+    let (mut text_a, revision) = open_file(connection_a.handle_request(FileRequest::OpenFile("main.rs".to_string(), 0)));
+    let (mut text_b, _) = open_file(connection_b.handle_request(FileRequest::OpenFile("main.rs".to_string(), 0)));
+    let mut client_a = DeltaClient::new(revision);
+    let mut client_b = DeltaClient::new(revision);
+
+    // Both participants edit the same revision at the same time.
+    let delta_a = delta(&[("r", 3, ""), ("d", 4, ""), ("i", 0, "start"), ("r", 6, "")]);
+    text_a = delta_a.apply(&text_a).unwrap();
+    let (revision_a, delta_a) = client_a.apply_local(delta_a).unwrap();
+    let delta_b = delta(&[("r", 11, ""), ("i", 0, " let x = 1; "), ("r", 2, "")]);
+    text_b = delta_b.apply(&text_b).unwrap();
+    let (revision_b, delta_b) = client_b.apply_local(delta_b).unwrap();
+    // B keeps typing while its first delta is underway.
+    let delta_b_2 = delta(&[("i", 0, "// main\n"), ("r", text_b.len(), "")]);
+    text_b = delta_b_2.apply(&text_b).unwrap();
+    assert!(client_b.apply_local(delta_b_2).is_none());
+
+    let revision = applied_revision(connection_a.handle_request(FileRequest::ApplyDelta("main.rs".to_string(), revision_a, delta_a, 0)));
+    assert!(client_a.apply_ack(revision).is_none());
+    let revision = applied_revision(connection_b.handle_request(FileRequest::ApplyDelta("main.rs".to_string(), revision_b, delta_b, 0)));
+
+    // B receives the delta of A, which the server applied before the one of B.
+    let (revision_of_a, delta) = recv_delta(&receiver_b);
+    client_b.receive_remote(revision_of_a, delta);
+    while let Some(delta) = client_b.next_remote() {
+        text_b = delta.apply(&text_b).unwrap();
+    }
+    let (revision_b, delta_b) = client_b.apply_ack(revision).unwrap();
+    let revision = applied_revision(connection_b.handle_request(FileRequest::ApplyDelta("main.rs".to_string(), revision_b, delta_b, 0)));
+    assert!(client_b.apply_ack(revision).is_none());
+
+    for _ in 0..2 {
+        let (revision, delta) = recv_delta(&receiver_a);
+        client_a.receive_remote(revision, delta);
+        while let Some(delta) = client_a.next_remote() {
+            text_a = delta.apply(&text_a).unwrap();
+        }
+    }
+    assert_eq!(text_a, text_b);
+    assert_eq!(text_a, "// main\nfn start() { let x = 1; }\n");
+    assert_eq!(fs::read_to_string(root_path.join("main.rs")).unwrap(), text_a);
+    assert_eq!(client_a.revision(), client_b.revision());
+
+    // A participant that joins later gets the newest revision.
+    let (sender_c, _receiver_c) = mpsc::channel();
+    let connection_c = server.connect(Box::new(move | notification | {let _ = sender_c.send(notification);}));
+    let (text_c, revision_c) = open_file(connection_c.handle_request(FileRequest::OpenFile("main.rs".to_string(), 0)));
+    assert_eq!((text_c, revision_c), (text_a, client_a.revision()));
+}
+
+#[test]
+fn holds_back_deltas_that_arrive_before_an_ack() {
+    let mut client = DeltaClient::new(0);
+    let (_, _) = client.apply_local(delta(&[("i", 0, "a"), ("r", 1, "")])).unwrap();
+    // The server applied the local delta as revision 1, and a remote delta as revision 2, but
+    // the notification of the latter arrives first.
+    client.receive_remote(2, delta(&[("r", 2, ""), ("i", 0, "b")]));
+    assert!(client.next_remote().is_none());
+    assert!(client.apply_ack(1).is_none());
+    let delta = client.next_remote().unwrap();
+    assert_eq!(delta.apply("ax").unwrap(), "axb");
+    assert_eq!(client.revision(), 2);
+}
+
+#[test]
+fn applies_changes_on_disk_to_open_files() {
+    let project = create_project("disk");
+    let root_path = project.path();
+    let mut server = FileServer::new(root_path);
+    let (sender, receiver) = mpsc::channel();
+    let connection = server.connect(Box::new(move | notification | {let _ = sender.send(notification);}));
+    let (text, revision) = open_file(connection.handle_request(FileRequest::OpenFile("main.rs".to_string(), 0)));
+    assert!(server.wait_until_watching(Duration::from_secs(10)));
+
+    // Make sure the modification time changes.
+    std::thread::sleep(Duration::from_millis(20));
+    fs::write(root_path.join("main.rs"), "fn main() { run() }\n").unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let (new_revision, delta) = loop {
+        assert!(Instant::now() < deadline);
+        if let Ok(FileNotification::DeltaWasApplied {revision, delta, ..}) = receiver.recv_timeout(Duration::from_millis(100)) {
+            break (revision, delta)
+        }
+    };
+    assert_eq!(new_revision, revision + 1);
+    assert_eq!(delta.apply(&text).unwrap(), "fn main() { run() }\n");
+
+    // The session moves along with the file.
+    let response = connection.handle_request(FileRequest::RenameFile("main.rs".to_string(), "lib.rs".to_string()));
+    assert!(matches!(response, FileResponse::RenameFile(Ok(_))));
+    let response = connection.handle_request(FileRequest::ApplyDelta(
+        "lib.rs".to_string(),
+        new_revision,
+        Delta::replace("fn main() { run() }\n", "fn lib() {}\n"),
+        0
+    ));
+    assert_eq!(applied_revision(response), new_revision + 1);
+    assert_eq!(fs::read_to_string(root_path.join("lib.rs")).unwrap(), "fn lib() {}\n");
+    let response = connection.handle_request(FileRequest::CloseFile("lib.rs".to_string()));
+    assert!(matches!(response, FileResponse::CloseFile(Ok(_))));
+    let response = connection.handle_request(FileRequest::ApplyDelta("lib.rs".to_string(), 0, Delta::new(), 0));
+    assert!(matches!(response, FileResponse::ApplyDelta(Err(_))));
+}
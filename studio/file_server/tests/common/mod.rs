@@ -0,0 +1,49 @@
+// Shared by the file server tests.
+// every test binary uses a different part of this
+#![allow(dead_code)]
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+// A directory for a test to work in, removed again when the test is done with it, also when
+// the test fails. Declare it before anything that watches it, so that it is dropped last.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    // Creates an empty directory. The name only has to be unique within a test binary.
+    pub fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("makepad_file_server_test_{}_{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self { path }
+    }
+
+    // Creates a directory with the given files in it, along with the directories they are in.
+    pub fn with_files(name: &str, files: &[(&str, &[u8])]) -> Self {
+        let dir = Self::new(name);
+        for (path, data) in files {
+            dir.write(path, data);
+        }
+        dir
+    }
+
+    pub fn write(&self, path: &str, data: impl AsRef<[u8]>) {
+        let path = self.path.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, data).unwrap();
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
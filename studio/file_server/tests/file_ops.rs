@@ -0,0 +1,161 @@
+mod common;
+
+use {
+    common::TempDir,
+    makepad_file_server::{
+        FileChange, FileNotification, FileRequest, FileResponse, FileServer,
+    },
+    std::{
+        fs,
+        sync::mpsc,
+        time::{Duration, Instant},
+    },
+};
+
+fn create_project(name: &str) -> TempDir {
+    TempDir::with_files(name, &[("src/main.rs", b"fn main() {}\n")])
+}
+
+#[test]
+fn creates_renames_and_deletes_files() {
+    let project = create_project("ops");
+    let root_path = project.path();
+    let mut server = FileServer::new(root_path);
+    let connection = server.connect(Box::new(|_| {}));
+
+    let response = connection.handle_request(FileRequest::CreateFile("src/a/b.rs".to_string()));
+    assert!(matches!(response, FileResponse::CreateFile(Ok(path)) if path == "src/a/b.rs"));
+    assert_eq!(fs::read_to_string(root_path.join("src/a/b.rs")).unwrap(), "");
+    let response = connection.handle_request(FileRequest::CreateFile("src/a/b.rs".to_string()));
+    assert!(matches!(response, FileResponse::CreateFile(Err(_))));
+
+    let response = connection.handle_request(FileRequest::CreateDirectory("docs".to_string()));
+    assert!(matches!(response, FileResponse::CreateDirectory(Ok(_))));
+    assert!(root_path.join("docs").is_dir());
+
+    let response = connection.handle_request(FileRequest::RenameFile(
+        "src/main.rs".to_string(),
+        "src/bin/main.rs".to_string(),
+    ));
+    assert!(matches!(response, FileResponse::RenameFile(Ok((old_path, new_path))) if old_path == "src/main.rs" && new_path == "src/bin/main.rs"));
+    assert!(!root_path.join("src/main.rs").exists());
+    assert_eq!(fs::read_to_string(root_path.join("src/bin/main.rs")).unwrap(), "fn main() {}\n");
+
+    let response = connection.handle_request(FileRequest::DeleteFile("src".to_string()));
+    assert!(matches!(response, FileResponse::DeleteFile(Ok(_))));
+    assert!(!root_path.join("src").exists());
+
+    // Paths that leave the root are refused.
+    for path in ["", "../outside", "/tmp/outside", "docs/../.."] {
+        let response = connection.handle_request(FileRequest::DeleteFile(path.to_string()));
+        assert!(matches!(response, FileResponse::DeleteFile(Err(_))), "{:?}", path);
+    }
+}
+
+#[test]
+fn watches_for_changes_on_disk() {
+    let project = create_project("watch");
+    let root_path = project.path();
+    let (sender, receiver) = mpsc::channel();
+    let mut server = FileServer::new(root_path);
+    let connection = server.connect(Box::new(move |notification| {
+        let _ = sender.send(notification);
+    }));
+    assert!(server.wait_until_watching(Duration::from_secs(10)));
+
+    fs::write(root_path.join("src/main.rs"), "fn main() {\n}\n").unwrap();
+    fs::write(root_path.join("src/lib.rs"), "").unwrap();
+    fs::write(root_path.join(".hidden"), "").unwrap();
+    let mut changes = Vec::new();
+    let start = Instant::now();
+    while changes.len() < 2 {
+        assert!(start.elapsed() < Duration::from_secs(10), "timed out");
+        if let Ok(FileNotification::FileChangedOnDisk(new_changes)) = receiver.recv_timeout(Duration::from_millis(100)) {
+            changes.extend(new_changes);
+        }
+    }
+    let changes = changes
+        .iter()
+        .map(|change| format!("{:?}", change))
+        .collect::<Vec<_>>();
+    assert_eq!(changes, [
+        format!("{:?}", FileChange::Created("src/lib.rs".to_string())),
+        format!("{:?}", FileChange::Modified("src/main.rs".to_string())),
+    ]);
+
+    fs::remove_dir_all(root_path.join("src")).unwrap();
+    let start = Instant::now();
+    loop {
+        assert!(start.elapsed() < Duration::from_secs(10), "timed out");
+        if let Ok(FileNotification::FileChangedOnDisk(changes)) = receiver.recv_timeout(Duration::from_millis(100)) {
+            assert_eq!(changes.len(), 3);
+            assert!(changes.iter().all(|change| matches!(change, FileChange::Removed(_))));
+            break;
+        }
+    }
+    drop(connection);
+}
+
+// Collects the changes on disk that are reported until `count` of them came in.
+fn receive_changes(receiver: &mpsc::Receiver<FileNotification>, count: usize) -> Vec<String> {
+    let mut changes = Vec::new();
+    let start = Instant::now();
+    while changes.len() < count {
+        assert!(start.elapsed() < Duration::from_secs(10), "timed out");
+        if let Ok(FileNotification::FileChangedOnDisk(new_changes)) = receiver.recv_timeout(Duration::from_millis(100)) {
+            changes.extend(new_changes.iter().map(|change| format!("{:?}", change)));
+        }
+    }
+    changes
+}
+
+#[test]
+fn does_not_report_changes_to_ignored_files() {
+    let project = create_project("watch_ignored");
+    let root_path = project.path();
+    fs::write(root_path.join(".gitignore"), "*.log\nbuild/\n").unwrap();
+    let (sender, receiver) = mpsc::channel();
+    let mut server = FileServer::new(root_path);
+    let _connection = server.connect(Box::new(move |notification| {
+        let _ = sender.send(notification);
+    }));
+    assert!(server.wait_until_watching(Duration::from_secs(10)));
+
+    fs::write(root_path.join("debug.log"), "").unwrap();
+    fs::create_dir_all(root_path.join("build/out")).unwrap();
+    fs::write(root_path.join("build/out/main.o"), "").unwrap();
+    fs::write(root_path.join("src/lib.rs"), "").unwrap();
+    assert_eq!(receive_changes(&receiver, 1), [
+        format!("{:?}", FileChange::Created("src/lib.rs".to_string())),
+    ]);
+    // Nothing about the ignored files shows up later either.
+    assert!(receiver.recv_timeout(Duration::from_secs(1)).is_err());
+}
+
+#[test]
+fn shares_one_watcher_between_connections() {
+    let project = create_project("watch_shared");
+    let root_path = project.path();
+    let mut server = FileServer::new(root_path);
+    let (sender_a, receiver_a) = mpsc::channel();
+    let connection_a = server.connect(Box::new(move |notification| {
+        let _ = sender_a.send(notification);
+    }));
+    let (sender_b, receiver_b) = mpsc::channel();
+    let _connection_b = server.connect(Box::new(move |notification| {
+        let _ = sender_b.send(notification);
+    }));
+    assert!(server.wait_until_watching(Duration::from_secs(10)));
+
+    fs::write(root_path.join("src/a.rs"), "").unwrap();
+    let expected = [format!("{:?}", FileChange::Created("src/a.rs".to_string()))];
+    assert_eq!(receive_changes(&receiver_a, 1), expected);
+    assert_eq!(receive_changes(&receiver_b, 1), expected);
+
+    // The other connection keeps getting changes once one of them is gone.
+    drop(connection_a);
+    fs::write(root_path.join("src/b.rs"), "").unwrap();
+    assert_eq!(receive_changes(&receiver_b, 1), [
+        format!("{:?}", FileChange::Created("src/b.rs".to_string())),
+    ]);
+}
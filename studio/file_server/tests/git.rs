@@ -0,0 +1,140 @@
+mod common;
+
+use {
+    common::TempDir,
+    makepad_file_server::{
+        git::{parse_blame, parse_status},
+        FileNotification, FileRequest, FileResponse, FileServer, GitStatus,
+    },
+    std::{
+        fs,
+        path::Path,
+        process::Command,
+        sync::mpsc,
+        time::{Duration, Instant},
+    },
+};
+
+fn git(cwd: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+// Creates a repository with a committed file in a subdirectory, which is the root of the file
+// tree, so that paths have to be made relative to it.
+fn create_repository(name: &str) -> TempDir {
+    let repo = TempDir::with_files(name, &[
+        ("project/src/main.rs", b"fn main() {\n}\n"),
+        ("project/old.rs", b"old\n"),
+        ("README", b"readme\n"),
+    ]);
+    git(repo.path(), &["init", "-q"]);
+    git(repo.path(), &["add", "."]);
+    git(repo.path(), &["commit", "-q", "-m", "First commit"]);
+    repo
+}
+
+#[test]
+fn parses_status_and_blame() {
+    let output = " M project/a.rs\0R  project/new.rs\0project/old.rs\0?? project/b.rs\0UU project/c.rs\0D  other/d.rs\0";
+    let statuses: Vec<_> = parse_status(output, "project/").into_iter().map(|status| (status.path, status.status)).collect();
+    assert_eq!(statuses, [
+        ("a.rs".to_string(), GitStatus::Modified),
+        ("new.rs".to_string(), GitStatus::Renamed),
+        ("b.rs".to_string(), GitStatus::Untracked),
+        ("c.rs".to_string(), GitStatus::Conflicted),
+    ]);
+
+    let output = "353680f9112a0d6cfdb0d471d16ee0f3730cab7e 1 1 1\nauthor Jane Doe\nauthor-mail <jane@example.com>\nauthor-time 1700000000\nsummary Fix the thing\nfilename a.rs\n\tauthor 1\n";
+    let blame = parse_blame(output).unwrap();
+    assert_eq!(blame.commit, "353680f9112a0d6cfdb0d471d16ee0f3730cab7e");
+    assert_eq!(blame.author, "Jane Doe");
+    assert_eq!(blame.time, 1700000000);
+    assert_eq!(blame.summary, "Fix the thing");
+    assert!(parse_blame("").is_none());
+}
+
+#[test]
+fn reports_changes_to_temporary_repository() {
+    let repo = create_repository("status");
+    let repo_path = repo.path();
+    let root_path = repo_path.join("project");
+    let mut server = FileServer::new(&root_path);
+    let (sender, receiver) = mpsc::channel();
+    let connection = server.connect(Box::new(move | notification | {let _ = sender.send(notification);}));
+    assert!(server.wait_until_watching(Duration::from_secs(10)));
+
+    fs::write(root_path.join("src/main.rs"), "fn main() {\n    run();\n}\n").unwrap();
+    fs::write(root_path.join("src/new.rs"), "new\n").unwrap();
+    fs::remove_file(root_path.join("old.rs")).unwrap();
+    fs::write(repo_path.join("README"), "changed\n").unwrap();
+    let mut statuses = match connection.handle_request(FileRequest::GitStatus) {
+        FileResponse::GitStatus(Ok(statuses)) => statuses.into_iter().map(|status| (status.path, status.status)).collect::<Vec<_>>(),
+        response => panic!("{:?}", response),
+    };
+    statuses.sort_by(|status_0, status_1| status_0.0.cmp(&status_1.0));
+    // Changes outside of the file tree are left out.
+    assert_eq!(statuses, [
+        ("old.rs".to_string(), GitStatus::Deleted),
+        ("src/main.rs".to_string(), GitStatus::Modified),
+        ("src/new.rs".to_string(), GitStatus::Untracked),
+    ]);
+
+    match connection.handle_request(FileRequest::GitHeadText("src/main.rs".to_string())) {
+        FileResponse::GitHeadText(Ok((path, text))) => {
+            assert_eq!(path, "src/main.rs");
+            assert_eq!(text.as_deref(), Some("fn main() {\n}\n"));
+        }
+        response => panic!("{:?}", response),
+    }
+    assert!(matches!(
+        connection.handle_request(FileRequest::GitHeadText("src/new.rs".to_string())),
+        FileResponse::GitHeadText(Ok((_, None)))
+    ));
+    assert!(matches!(
+        connection.handle_request(FileRequest::GitHeadText("../README".to_string())),
+        FileResponse::GitHeadText(Err(_))
+    ));
+
+    match connection.handle_request(FileRequest::GitBlame("src/main.rs".to_string(), 0)) {
+        FileResponse::GitBlame(Ok((_, 0, blame))) => {
+            assert_eq!(blame.author, "Test");
+            assert_eq!(blame.summary, "First commit");
+        }
+        response => panic!("{:?}", response),
+    }
+    match connection.handle_request(FileRequest::GitBlame("src/main.rs".to_string(), 1)) {
+        FileResponse::GitBlame(Ok((_, 1, blame))) => assert!(blame.commit.bytes().all(|byte| byte == b'0')),
+        response => panic!("{:?}", response),
+    }
+
+    // Committing shows up as a change to the repository.
+    git(repo_path, &["commit", "-q", "-a", "-m", "Second commit"]);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        assert!(Instant::now() < deadline);
+        if let Ok(FileNotification::GitChanged) = receiver.recv_timeout(Duration::from_millis(100)) {
+            break
+        }
+    }
+}
+
+#[test]
+fn reports_errors_outside_of_repository() {
+    let project = TempDir::with_files("no_repo", &[("main.rs", b"fn main() {}\n")]);
+    let mut server = FileServer::new(project.path());
+    let connection = server.connect(Box::new(| _ | {}));
+    // Temporary directories might be inside a repository after all.
+    if matches!(connection.handle_request(FileRequest::GitStatus), FileResponse::GitStatus(Err(_))) {
+        assert!(matches!(
+            connection.handle_request(FileRequest::GitBlame("main.rs".to_string(), 0)),
+            FileResponse::GitBlame(Err(_))
+        ));
+    }
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use {
+    common::TempDir,
+    makepad_file_server::{
+        search::glob_match, FileNotification, FileRequest, FileResponse, FileServer, SearchResult,
+    },
+    std::{
+        path::Path,
+        sync::mpsc,
+        time::Duration,
+    },
+};
+
+fn create_project(name: &str) -> TempDir {
+    TempDir::with_files(name, &[
+        (".gitignore", b"ignored/\n*.log\n"),
+        ("src/main.rs", b"fn main() {\n    println!(\"hello\");\n}\n"),
+        ("src/lib.rs", b"// Hello there\npub fn hello() {}\n"),
+        ("src/sub/.gitignore", b"!keep.log\n"),
+        ("src/sub/keep.log", b"hello\n"),
+        ("src/binary.bin", b"hello\0"),
+        ("build.log", b"hello\n"),
+        ("ignored/a.rs", b"hello\n"),
+        ("target/a.rs", b"hello\n"),
+        (".hidden/a.rs", b"hello\n"),
+    ])
+}
+
+fn search(
+    root_path: &Path,
+    pattern: &str,
+    regex: bool,
+    case: bool,
+    globs: &[&str],
+) -> Result<Vec<SearchResult>, String> {
+    let (sender, receiver) = mpsc::channel();
+    let mut server = FileServer::new(root_path);
+    let connection = server.connect(Box::new(move |notification| {
+        sender.send(notification).unwrap()
+    }));
+    let response = connection.handle_request(FileRequest::Search {
+        id: 3,
+        pattern: pattern.to_string(),
+        regex,
+        case,
+        globs: globs.iter().map(|glob| glob.to_string()).collect(),
+    });
+    match response {
+        FileResponse::Search(Ok(3)) => {}
+        FileResponse::Search(Err((3, error))) => return Err(format!("{:?}", error)),
+        response => panic!("unexpected response {:?}", response),
+    }
+    let mut all_results = Vec::new();
+    loop {
+        match receiver.recv_timeout(Duration::from_secs(10)).unwrap() {
+            FileNotification::SearchResults { id: 3, results } => all_results.extend(results),
+            FileNotification::SearchFinished { id: 3 } => return Ok(all_results),
+            FileNotification::FileChangedOnDisk(_) => {}
+            notification => panic!("unexpected notification {:?}", notification),
+        }
+    }
+}
+
+fn summarize(results: &[SearchResult]) -> Vec<(&str, usize, usize, usize)> {
+    results
+        .iter()
+        .map(|result| (result.path.as_str(), result.line_index, result.start_byte, result.end_byte))
+        .collect()
+}
+
+#[test]
+fn searches_files_that_are_not_ignored() {
+    let project = create_project("ignore");
+    let root_path = project.path();
+    let results = search(root_path, "hello", false, false, &[]).unwrap();
+    assert_eq!(
+        summarize(&results),
+        [
+            ("src/lib.rs", 0, 3, 8),
+            ("src/lib.rs", 1, 7, 12),
+            ("src/main.rs", 1, 14, 19),
+            ("src/sub/keep.log", 0, 0, 5),
+        ]
+    );
+    assert_eq!(results[0].line, "// Hello there");
+
+    let results = search(root_path, "hello", false, true, &[]).unwrap();
+    assert_eq!(results.len(), 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn skips_symlinks() {
+    let project = create_project("symlinks");
+    let root_path = project.path();
+    std::os::unix::fs::symlink(root_path, root_path.join("src/loop")).unwrap();
+    std::os::unix::fs::symlink(root_path.join("src/main.rs"), root_path.join("src/link.rs")).unwrap();
+    let results = search(root_path, "println", false, false, &[]).unwrap();
+    assert_eq!(summarize(&results), [("src/main.rs", 1, 4, 11)]);
+}
+
+#[test]
+fn filters_files_with_globs() {
+    let project = create_project("globs");
+    let root_path = project.path();
+    let results = search(root_path, "hello", false, false, &["*.rs", "!lib.rs"]).unwrap();
+    assert_eq!(summarize(&results), [("src/main.rs", 1, 14, 19)]);
+    let results = search(root_path, "hello", false, false, &["src/sub/**"]).unwrap();
+    assert_eq!(summarize(&results), [("src/sub/keep.log", 0, 0, 5)]);
+}
+
+#[test]
+fn searches_with_a_regex() {
+    let project = create_project("regex");
+    let root_path = project.path();
+    let results = search(root_path, r"h\w+o\(", true, true, &[]).unwrap();
+    assert_eq!(summarize(&results), [("src/lib.rs", 1, 7, 13)]);
+    assert!(search(root_path, "(hello", true, true, &[]).is_err());
+    assert!(search(root_path, "", false, false, &[]).unwrap().is_empty());
+}
+
+#[test]
+fn skips_large_files_and_the_end_of_long_lines() {
+    let project = create_project("limits");
+    let root_path = project.path();
+    project.write("src/large.txt", "hello\n".repeat(2_000_000));
+    let long_line = format!("hello{}hello\n", "x".repeat(3_000_000));
+    project.write("src/minified.js", long_line);
+    let results = search(root_path, "hello", false, true, &["*.js", "*.txt"]).unwrap();
+    assert_eq!(summarize(&results), [("src/minified.js", 0, 0, 5)]);
+    assert_eq!(results[0].line.len(), 256);
+    let results = search(root_path, "[a-z]{400}", true, true, &["*.js"]).unwrap();
+    assert_eq!(results.len(), 10);
+}
+
+#[test]
+fn stops_after_the_maximum_number_of_results() {
+    let project = create_project("max_results");
+    let root_path = project.path();
+    project.write("src/many.txt", "a a a a\n".repeat(5_000));
+    project.write("src/more.txt", "a\n");
+    let results = search(root_path, "a", false, true, &["*.txt"]).unwrap();
+    assert_eq!(results.len(), 10_000);
+    assert!(results.iter().all(|result| result.path == "src/many.txt"));
+}
+
+#[test]
+fn matches_globs() {
+    let matches = |glob: &str, path: &str| {
+        glob_match(&glob.chars().collect::<Vec<_>>(), &path.chars().collect::<Vec<_>>())
+    };
+    assert!(matches("*.rs", "main.rs"));
+    assert!(!matches("*.rs", "src/main.rs"));
+    assert!(matches("**/*.rs", "main.rs"));
+    assert!(matches("**/*.rs", "src/a/main.rs"));
+    assert!(matches("src/**", "src/a/main.rs"));
+    assert!(matches("src/**/b", "src/b"));
+    assert!(matches("*.[oa]", "lib.a"));
+    assert!(!matches("*.[!oa]", "lib.a"));
+    assert!(matches("file?.[0-9]", "file1.7"));
+    assert!(!matches("file?", "file/"));
+}
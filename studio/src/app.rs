@@ -1,8 +1,10 @@
 use crate::{
     makepad_code_editor::code_editor::*,
+    makepad_code_editor::text::Position,
     makepad_widgets::*,
     makepad_micro_serde::*,
     makepad_widgets::file_tree::*,
+    makepad_widgets::designer::*,
     file_system::file_system::*,
     studio_editor::*,
     run_view::*,
@@ -13,8 +15,13 @@ use crate::{
             BuildManager,
             BuildManagerAction
         },
+    },
+    lsp_manager::lsp_manager::{
+        LspManager,
+        LspAction
     }
 }; 
+use std::ops::Range;
 use std::fs::File;
 use std::io::Write;
 use std::env;
@@ -31,6 +38,10 @@ live_design!{
 pub struct App {
     #[live] ui: WidgetRef,
     #[rust] data: AppData,
+    // where to put the cursor once a file that is still loading has opened
+    #[rust] pending_jump: Option<JumpTo>,
+    // the editor tab whose file is shown in the designer
+    #[rust] designer_tab: Option<LiveId>,
 }
 
 impl LiveRegister for App{
@@ -39,7 +50,10 @@ impl LiveRegister for App{
         crate::makepad_code_editor::live_design(cx);
         crate::run_list::live_design(cx);
         crate::log_list::live_design(cx);
+        crate::search_list::live_design(cx);
+        crate::git_diff_view::live_design(cx);
         crate::profiler::live_design(cx);
+        crate::inspector::live_design(cx);
         crate::run_view::live_design(cx);
         crate::studio_editor::live_design(cx);
         crate::studio_file_tree::live_design(cx);
@@ -61,12 +75,88 @@ impl App {
             self.data.file_system.ensure_unique_tab_names(cx, &dock)
         }
     }
+    
+    fn jump_to(&mut self, cx: &mut Cx, jt: JumpTo) {
+        let dock = self.ui.dock(id!(dock));
+        if let Some(file_id) = self.data.file_system.path_to_file_node_id(&jt.file_name) {
+            if let Some(tab_id) = self.data.file_system.file_node_id_to_tab_id(file_id){
+                dock.select_tab(cx, tab_id);
+                // ok lets scroll into view
+                self.set_cursor_in_tab(cx, tab_id, jt.start);
+                self.designer_tab = Some(tab_id);
+            }
+            else{
+                // lets open the editor
+                let tab_id = dock.unique_tab_id(file_id.0.0);
+                self.data.file_system.request_open_file(tab_id, file_id);
+                // lets add a file tab 'somewhere'
+                dock.create_and_select_tab(cx, live_id!(edit_tabs), tab_id, live_id!(StudioEditor), "".to_string(), TabClosable::Yes);
+                // lets scan the entire doc for duplicates
+                self.data.file_system.ensure_unique_tab_names(cx, &dock);
+                self.pending_jump = Some(jt);
+                self.designer_tab = Some(tab_id);
+            }
+            self.update_designer(cx);
+        }
+    }
+    
+    fn designer(&self) -> DesignerRef {
+        self.ui.dock(id!(dock)).item(live_id!(designer)).as_designer()
+    }
+    
+    // shows the file of the designer tab in the designer, once its document has loaded
+    fn update_designer(&mut self, cx: &mut Cx) {
+        let Some(tab_id) = self.designer_tab else {
+            self.designer().set_source(cx, "", "");
+            return
+        };
+        let file_system = &self.data.file_system;
+        let Some(file_id) = file_system.tab_id_to_file_node_id.get(&tab_id) else {
+            return
+        };
+        if let Some(OpenDoc::Document(document)) = file_system.open_documents.get(file_id) {
+            let path = file_system.file_node_path(*file_id);
+            self.designer().set_source(cx, &path, &document.as_text().to_string());
+        }
+    }
+    
+    // applies an edit that was made in the designer to the document, like it was typed in the editor
+    fn apply_designer_edit(&mut self, cx: &mut Cx, range: Range<usize>, text: String) {
+        let Some(tab_id) = self.designer_tab else {
+            return
+        };
+        let Some(session) = self.data.file_system.get_session_mut(tab_id) else {
+            return
+        };
+        let mut source = session.document().as_text().to_string();
+        if source.get(range.clone()).is_none() {
+            return
+        }
+        source.replace_range(range, &text);
+        session.replace_text(source.into());
+        self.data.file_system.send_edits(cx, tab_id);
+        if let Some(file_id) = self.data.file_system.tab_id_to_file_node_id.get(&tab_id).copied() {
+            self.data.file_system.redraw_view_by_file_id(cx, file_id, &self.ui.dock(id!(dock)));
+        }
+        self.update_designer(cx);
+    }
+    
+    fn set_cursor_in_tab(&mut self, cx: &mut Cx, tab_id: LiveId, pos: Position) {
+        let dock = self.ui.dock(id!(dock));
+        if let Some(mut editor) = dock.item(tab_id).as_studio_editor().borrow_mut() {
+            if let Some(session) = self.data.file_system.get_session_mut(tab_id) {
+                editor.editor.set_cursor_and_scroll(cx, pos, session);
+                editor.editor.set_key_focus(cx);
+            }
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct AppData{
     pub build_manager: BuildManager,
     pub file_system: FileSystem,
+    pub lsp_manager: LspManager,
 }
 
 // all global app commands coming in from keybindings, and UI components
@@ -74,11 +164,15 @@ pub struct AppData{
 #[derive(DefaultNone, Debug, Clone)]
 pub enum AppAction{
     JumpTo(JumpTo),
+    // a widget was clicked in a running app while the designer was picking
+    DesignerPicked(JumpTo),
     RedrawLog,
     RedrawProfiler,
+    RedrawInspector,
     RedrawFile(FileNodeId),
     StartRecompile,
     ReloadFileTree,
+    ShowChanges(FileNodeId),
     RecompileStarted,
     ClearLog, 
     None
@@ -97,6 +191,7 @@ impl MatchEvent for App{
                 
         self.data.file_system.init(cx, &root_path);
         self.data.build_manager.init(cx, &root_path);
+        self.data.lsp_manager.init(&root_path);
         self.data.build_manager.discover_external_ip(cx);
         self.data.build_manager.start_http_server();
     }
@@ -107,29 +202,14 @@ impl MatchEvent for App{
         let log_list = self.ui.view(id!(log_list));
         let run_list = self.ui.view(id!(run_list));
         let profiler = self.ui.view(id!(profiler));
+        let inspector = self.ui.view(id!(inspector));
         match action.cast(){
             AppAction::JumpTo(jt)=>{
-                if let Some(file_id) = self.data.file_system.path_to_file_node_id(&jt.file_name) {
-                    if let Some(tab_id) = self.data.file_system.file_node_id_to_tab_id(file_id){
-                        dock.select_tab(cx, tab_id);
-                        // ok lets scroll into view
-                        if let Some(mut editor) = dock.item(tab_id).as_studio_editor().borrow_mut() {
-                            if let Some(session) = self.data.file_system.get_session_mut(tab_id) {
-                                editor.editor.set_cursor_and_scroll(cx, jt.start, session);
-                                editor.editor.set_key_focus(cx);
-                            }
-                        }
-                    }
-                    else{
-                        // lets open the editor
-                        let tab_id = dock.unique_tab_id(file_id.0.0);
-                        self.data.file_system.request_open_file(tab_id, file_id);
-                        // lets add a file tab 'somewhere'
-                        dock.create_and_select_tab(cx, live_id!(edit_tabs), tab_id, live_id!(StudioEditor), "".to_string(), TabClosable::Yes);
-                        // lets scan the entire doc for duplicates
-                        self.data.file_system.ensure_unique_tab_names(cx, &dock)
-                    }
-                }
+                self.jump_to(cx, jt);
+            }
+            AppAction::DesignerPicked(jt)=>{
+                self.designer().select_line(cx, jt.start.line_index);
+                self.jump_to(cx, jt);
             }
             AppAction::RedrawFile(file_id)=>{
                 self.data.file_system.redraw_view_by_file_id(cx, file_id, &dock);
@@ -138,13 +218,23 @@ impl MatchEvent for App{
                 self.data.build_manager.clear_log(cx, &dock, &mut self.data.file_system);
                 log_list.redraw(cx);
                 profiler.redraw(cx);
+                inspector.redraw(cx);
             }
             AppAction::ReloadFileTree=>{
                 self.data.file_system.reload_file_tree();
             }
+            AppAction::ShowChanges(file_id)=>{
+                let tab_id = dock.unique_tab_id(file_id.0.0);
+                self.data.file_system.request_open_diff(tab_id, file_id);
+                let name = format!("{} (changes)", self.data.file_system.file_node_name(file_id));
+                dock.create_and_select_tab(cx, live_id!(edit_tabs), tab_id, live_id!(GitDiffView), name, TabClosable::Yes);
+            }
             AppAction::RedrawProfiler=>{
                 profiler.redraw(cx);
             }
+            AppAction::RedrawInspector=>{
+                inspector.redraw(cx);
+            }
             AppAction::RedrawLog=>{
                 log_list.redraw(cx);
             }
@@ -175,6 +265,12 @@ impl MatchEvent for App{
         match action.cast(){
             FileSystemAction::TreeLoaded => {
                 file_tree.redraw(cx);
+                // close the tabs of files that were deleted or moved away
+                for tab_id in self.data.file_system.remove_deleted_tabs() {
+                    dock.close_tab(cx, tab_id);
+                }
+                self.data.file_system.ensure_unique_tab_names(cx, &dock);
+                self.data.file_system.redraw_all_views(cx, &dock);
                 //self.open_code_file_by_path(cx, "examples/slides/src/app.rs");
             }
             FileSystemAction::RecompileNeeded => {
@@ -185,8 +281,53 @@ impl MatchEvent for App{
                 self.data.build_manager.clear_log(cx, &dock, &mut self.data.file_system);
                 log_list.redraw(cx);
             }
+            FileSystemAction::DocumentOpened(file_id) => {
+                if let Some(OpenDoc::Document(document)) = self.data.file_system.open_documents.get(&file_id) {
+                    let path = self.data.file_system.file_node_path(file_id);
+                    self.data.lsp_manager.open_document(file_id, &path, document);
+                }
+                self.update_designer(cx);
+                if let Some(jt) = self.pending_jump.take() {
+                    if self.data.file_system.path_to_file_node_id(&jt.file_name) == Some(file_id) {
+                        if let Some(tab_id) = self.data.file_system.file_node_id_to_tab_id(file_id) {
+                            self.set_cursor_in_tab(cx, tab_id, jt.start);
+                        }
+                    }
+                    else {
+                        self.pending_jump = Some(jt);
+                    }
+                }
+            }
+            FileSystemAction::DocumentChangedRemotely(file_id) => {
+                self.data.file_system.redraw_view_by_file_id(cx, file_id, &dock);
+            }
+            FileSystemAction::SearchResultsChanged => {
+                self.ui.view(id!(search)).redraw(cx);
+            }
+            FileSystemAction::GitStatusChanged => {
+                file_tree.redraw(cx);
+            }
+            FileSystemAction::GitFileChanged(file_id) => {
+                self.data.file_system.redraw_view_by_file_id(cx, file_id, &dock);
+            }
             FileSystemAction::None=>()
         }
+        
+        match action.cast(){
+            LspAction::Hover{tab_id, position, text} => {
+                if let Some(mut editor) = dock.item(tab_id).as_studio_editor().borrow_mut() {
+                    editor.editor.show_hover(cx, position, text);
+                }
+            }
+            LspAction::Completions{tab_id, items} => {
+                if let Some(mut editor) = dock.item(tab_id).as_studio_editor().borrow_mut() {
+                    if let Some(session) = self.data.file_system.get_session_mut(tab_id) {
+                        editor.editor.show_completions(cx, session, items);
+                    }
+                }
+            }
+            LspAction::None=>()
+        }
                 
         match action.cast(){
             RunListAction::Create(run_view_id, name) => {
@@ -207,14 +348,46 @@ impl MatchEvent for App{
             match action.cast(){
                 CodeEditorAction::TextDidChange => {
                     // lets write the file
-                    self.data.file_system.request_save_file(action.path.get(0))
+                    let tab_id = action.path.get(0);
+                    self.data.file_system.send_edits(cx, tab_id);
+                    // the changes shown in diff views of this file changed as well
+                    if let Some(file_id) = self.data.file_system.tab_id_to_file_node_id.get(&tab_id).copied() {
+                        self.data.file_system.redraw_view_by_file_id(cx, file_id, &dock);
+                    }
+                    self.designer_tab = Some(tab_id);
+                    self.update_designer(cx);
+                }
+                CodeEditorAction::HoverRequested(position) => {
+                    let tab_id = action.path.get(0);
+                    if let Some(file_id) = self.data.file_system.tab_id_to_file_node_id.get(&tab_id) {
+                        self.data.lsp_manager.request_hover(tab_id, *file_id, position);
+                    }
+                }
+                CodeEditorAction::DefinitionRequested(position) => {
+                    if let Some(file_id) = self.data.file_system.tab_id_to_file_node_id.get(&action.path.get(0)) {
+                        self.data.lsp_manager.request_definition(*file_id, position);
+                    }
+                }
+                CodeEditorAction::CompletionRequested(position) => {
+                    let tab_id = action.path.get(0);
+                    if let Some(file_id) = self.data.file_system.tab_id_to_file_node_id.get(&tab_id) {
+                        self.data.lsp_manager.request_completion(tab_id, *file_id, position);
+                    }
                 }
                 CodeEditorAction::None=>{}
             }
             
             match action.cast(){
+                DockAction::TabWasPressed(tab_id) if self.data.file_system.tab_id_to_file_node_id.contains_key(&tab_id)=>{
+                    self.designer_tab = Some(tab_id);
+                    self.update_designer(cx);
+                }
                 DockAction::TabCloseWasPressed(tab_id)=>{
                     dock.close_tab(cx, tab_id);
+                    if self.designer_tab == Some(tab_id) {
+                        self.designer_tab = None;
+                        self.update_designer(cx);
+                    }
                     if self.data.build_manager.handle_tab_close(tab_id) {
                         log_list.redraw(cx);
                         run_list.redraw(cx);
@@ -266,31 +439,39 @@ impl MatchEvent for App{
 
     }        
         
-    fn handle_key_down(&mut self, cx: &mut Cx, event: &KeyEvent){
-        let KeyEvent {
-            key_code,
-            modifiers: KeyModifiers {logo, control, ..},
-            ..
-        } = event;
-        if *control || *logo {
-            if let KeyCode::Backtick = key_code {
-                cx.action(AppAction::StartRecompile)
-            }
-            else if let KeyCode::KeyK = key_code {
-                cx.action(AppAction::ClearLog)
-            }
-            else if let KeyCode::KeyR = key_code{
-                cx.action(AppAction::ReloadFileTree)
-            }
+    fn handle_command(&mut self, cx: &mut Cx, command: LiveId){
+        match command {
+            live_id!(recompile) => cx.action(AppAction::StartRecompile),
+            live_id!(clear_log) => cx.action(AppAction::ClearLog),
+            live_id!(reload_file_tree) => cx.action(AppAction::ReloadFileTree),
+            _ => ()
         }
     }
     
     fn handle_actions(&mut self, cx: &mut Cx, actions:&Actions){
         let file_tree = self.ui.file_tree(id!(file_tree));
         let dock = self.ui.dock(id!(dock));
+        let designer = self.designer();
         for action in actions{
             self.handle_action(cx, action);
         }
+        if let Some((range, text)) = designer.edited(&actions) {
+            self.apply_designer_edit(cx, range, text);
+        }
+        if let Some(line_index) = designer.node_selected(&actions) {
+            if let Some(file_id) = self.designer_tab.and_then(|tab_id| self.data.file_system.tab_id_to_file_node_id.get(&tab_id)) {
+                cx.action(AppAction::JumpTo(JumpTo{
+                    file_name: self.data.file_system.file_node_path(*file_id),
+                    start: Position{
+                        line_index,
+                        byte_index: 0,
+                    },
+                }));
+            }
+        }
+        if let Some(enabled) = designer.pick_mode_changed(&actions) {
+            self.data.build_manager.set_designer_pick_mode(enabled);
+        }
         if let Some(file_id) = file_tree.should_file_start_drag(&actions) {
             let path = self.data.file_system.file_node_path(file_id);
             file_tree.file_start_drag(cx, file_id, DragItem::FilePath {
@@ -307,12 +488,15 @@ impl MatchEvent for App{
             dock.create_and_select_tab(cx, live_id!(edit_tabs), tab_id, live_id!(StudioEditor), "".to_string(), TabClosable::Yes);
                                         
             // lets scan the entire doc for duplicates
-            self.data.file_system.ensure_unique_tab_names(cx, &dock)
+            self.data.file_system.ensure_unique_tab_names(cx, &dock);
+            self.designer_tab = Some(tab_id);
+            self.update_designer(cx);
         }
     }
     
     fn handle_shutdown(&mut self, _cx:&mut Cx){
         self.data.build_manager.clear_active_builds();
+        self.data.lsp_manager.shutdown();
     }
 }
 
@@ -324,6 +508,7 @@ impl AppMain for App {
         
         self.data.file_system.handle_event(cx, event, &self.ui);
         self.data.build_manager.handle_event(cx, event, &mut self.data.file_system); 
+        self.data.lsp_manager.handle_event(cx, event);
 
         // process events on all run_views
         let dock = self.ui.dock(id!(dock));
@@ -338,7 +523,8 @@ impl AppMain for App {
         if let Some(mut dock_items) = dock.needs_save(){
             dock_items.retain(|di| {
                 if let DockItemStore::Tab{kind,..} = di{
-                    if kind.0 == live_id!(RunView){
+                    // diff views are opened from the file tree again, they are not restored
+                    if kind.0 == live_id!(RunView) || kind.0 == live_id!(GitDiffView){
                         return false
                     }
                 }
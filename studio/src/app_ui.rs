@@ -8,8 +8,12 @@ live_design!{
     import makepad_studio::studio_file_tree::StudioFileTree;
     import makepad_studio::run_view::RunView;
     import makepad_studio::log_list::LogList;
+    import makepad_studio::search_list::SearchList;
+    import makepad_studio::git_diff_view::GitDiffView;
     import makepad_studio::run_list::RunList;
     import makepad_studio::profiler::Profiler;
+    import makepad_studio::inspector::Inspector;
+    import makepad_widgets::designer::Designer;
     
     ICO_SEARCH = dep("crate://self/resources/icons/Icon_Search.svg")
 
@@ -35,6 +39,13 @@ live_design!{
     AppUI =  <Window> {
         caption_bar = { margin: {left: -100}, visible: true, caption_label = {label = {text: "Makepad Studio"}} },
         window: {inner_size: vec2(1600, 900)},
+        keymap: {
+            command_palette = {name: "Command Palette", key: "Primary+Shift+P"}
+            // these always took either control or command, whatever the platform
+            recompile = {name: "Recompile", key: "Primary+Backtick | Ctrl+Backtick | Cmd+Backtick"}
+            clear_log = {name: "Clear Log", key: "Primary+K | Ctrl+K | Cmd+K"}
+            reload_file_tree = {name: "Reload File Tree", key: "Primary+R | Ctrl+R | Cmd+R"}
+        }
         window_menu = {
             main = Main {items: [app, file, edit, selection, view, run, window, help]}
                 
@@ -108,7 +119,7 @@ live_design!{
             }
                 
             file_tree_tabs = Tabs {
-                tabs: [file_tree, search, run_list],
+                tabs: [file_tree, search, run_list, designer],
                 selected: 2
             }
                 
@@ -118,7 +129,7 @@ live_design!{
             }
                 
             log_tabs = Tabs {
-                tabs: [log_list, profiler],
+                tabs: [log_list, profiler, inspector],
                 selected: 1
             }
                 
@@ -157,6 +168,12 @@ live_design!{
                 kind: RunList
             }
                 
+            designer = Tab {
+                name: "Design"
+                closable: false,
+                kind: Designer
+            }
+                
             file1 = Tab {
                 name: "app.rs",
                 closable: true,
@@ -174,6 +191,12 @@ live_design!{
                 closable: false,
                 kind: Profiler
             }
+            
+            inspector = Tab {
+                name: "Inspector",
+                closable: false,
+                kind: Inspector
+            }
                 
             StudioEditor = <StudioEditor> {}
             EditFirst = <RectView> {
@@ -222,52 +245,15 @@ live_design!{
             }
             RunList = <RunList> {
             }
-            Search = <RectView> {
-                draw_bg: {color: #x28}
-                //  margin:{left: 0, top: 0}
-                <View> {
-                    margin:10
-                    flow: Down
-                    <View> 
-                    {
-                        flow: Right
-                        height: Fit
-                        <TextInput>{
-                            draw_bg: {
-                                fn pixel(self) -> vec4 {
-                                    return #x00000044
-                                }
-                            }
-                            width: Fill,
-                            empty_message:"Search here"                           
-                        }
-                        //panic = <IconButton> {draw_icon: {svg_file: (ICO_PANIC)} icon_walk: {width: Fit, height: 17.0}, margin: {left: 5.0, right: -10.0}}
-                    // <Button> {
-                    //     text:"Search"
-                            
-                    //     draw_icon: {
-                    //         svg_file: (ICO_SEARCH)
-                    //         fn get_color(self) -> vec4 {
-                    //             return #8;
-                    //         }
-                    //     } 
-                    //     icon_walk:  {
-                    //         margin: {left: 10}
-                    //         width: Fit,
-                    //         // height: 12.0
-                    //     } 
-                    // }
-                }
-                    <Label>
-                    {
-                        text: "this does not work yet."
-                    }
-                }
-            }
+            Search = <SearchList> {}
+            Designer = <Designer> {}
+            GitDiffView = <GitDiffView> {}
             RunView = <RunView> {}
             StudioFileTree = <StudioFileTree> {}
             LogList = <LogList> {}
             Profiler = <Profiler> {}
+            Inspector = <Inspector> {}
         }}
+        command_palette = <CommandPalette> {}
     }
 }
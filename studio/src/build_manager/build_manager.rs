@@ -9,13 +9,14 @@ use {
             HostToStdin,
             StdinToHost,
         },
-        makepad_platform::studio::{AppToStudio,AppToStudioVec,EventSample, GPUSample},
+        makepad_platform::studio::{AppToStudio,AppToStudioVec,EventSample, GPUSample, DrawSample, MemorySample, WidgetTreeNode, StudioToApp, StudioToAppVec},
         build_manager::{
             build_protocol::*,
             build_client::BuildClient
         },
         run_view::*,
         app::AppAction,
+        log_list::JumpTo,
         makepad_shell::*,
     },
     makepad_code_editor::{text, decoration::{Decoration, DecorationType}},
@@ -27,7 +28,7 @@ use {
         path::Path,
         fs::File,
     },
-    std::sync::mpsc,
+    std::sync::{mpsc, Arc, Mutex},
     std::thread,
     std::time,
     std::net::{UdpSocket, SocketAddr},
@@ -74,6 +75,10 @@ impl ActiveBuilds {
 pub struct ProfileSampleStore{
     pub event: Vec<EventSample>,
     pub gpu: Vec<GPUSample>,
+    // the draw calls and memory of the last frame the app drew
+    pub draw: Option<DrawSample>,
+    pub memory: Option<MemorySample>,
+    pub widget_tree: Vec<WidgetTreeNode>,
 }
 
 #[derive(Default)]
@@ -90,7 +95,7 @@ pub struct BuildManager {
     pub studio_http: String,
     pub recv_studio_msg: ToUIReceiver<(LiveId,AppToStudioVec)>,
     pub recv_external_ip: ToUIReceiver<SocketAddr>,
-    pub send_file_change: FromUISender<LiveFileChange>
+    pub send_studio_to_app: FromUISender<(LiveId, StudioToApp)>
 }
 
 pub struct BuildBinary {
@@ -201,14 +206,42 @@ impl BuildManager {
     }
     
     pub fn live_reload_needed(&mut self, live_file_change: LiveFileChange) {
-        // lets send this filechange to all our stdin stuff
-       for item_id in self.active.builds.keys() {
-            self.clients[0].send_cmd_with_id(*item_id, BuildCmd::HostToStdin(HostToStdin::ReloadFile {
-                file: live_file_change.file_name.clone(),
-                contents: live_file_change.content.clone()
-            }.to_json()));
+        // builds that run in studio get the change over stdin, the others over their websocket
+        for (item_id, build) in &self.active.builds {
+            if build.process.target.runs_in_studio() {
+                self.clients[0].send_cmd_with_id(*item_id, BuildCmd::HostToStdin(HostToStdin::ReloadFile {
+                    file: live_file_change.file_name.clone(),
+                    contents: live_file_change.content.clone()
+                }.to_json()));
+            }
+            else {
+                let _ = self.send_studio_to_app.send((*item_id, StudioToApp::LiveChange {
+                    file_name: live_file_change.file_name.clone(),
+                    content: live_file_change.content.clone()
+                }));
+            }
+        }
+    }
+    
+    // while picking, a click in a running app selects the widget under it in the designer
+    pub fn set_designer_pick_mode(&mut self, enabled: bool) {
+        for item_id in self.active.builds.keys() {
+            let _ = self.send_studio_to_app.send((*item_id, StudioToApp::DesignerPickMode(enabled)));
+        }
+    }
+    
+    // the apps send their widget tree when they draw next, which the inspector then shows
+    pub fn request_widget_tree(&mut self) {
+        for item_id in self.active.builds.keys() {
+            let _ = self.send_studio_to_app.send((*item_id, StudioToApp::RequestWidgetTree));
+        }
+    }
+    
+    // the apps only sample their draw calls and memory on request, the inspector asks while it is shown
+    pub fn request_draw_samples(&mut self) {
+        for item_id in self.active.builds.keys() {
+            let _ = self.send_studio_to_app.send((*item_id, StudioToApp::RequestDrawSamples));
         }
-        let _ = self.send_file_change.send(live_file_change);
     }
     
     pub fn handle_event(&mut self, cx: &mut Cx, event: &Event, file_system: &mut FileSystem) {
@@ -279,6 +312,27 @@ impl BuildManager {
                             values.gpu.push(sample);
                             cx.action(AppAction::RedrawProfiler)
                         }
+                        AppToStudio::DrawSample(sample)=>{
+                            self.profile.entry(build_id).or_default().draw = Some(sample);
+                            cx.action(AppAction::RedrawInspector)
+                        }
+                        AppToStudio::MemorySample(sample)=>{
+                            self.profile.entry(build_id).or_default().memory = Some(sample);
+                            cx.action(AppAction::RedrawInspector)
+                        }
+                        AppToStudio::WidgetTree(nodes)=>{
+                            self.profile.entry(build_id).or_default().widget_tree = nodes;
+                            cx.action(AppAction::RedrawInspector)
+                        }
+                        AppToStudio::DesignerPick(pick)=>{
+                            cx.action(AppAction::DesignerPicked(JumpTo{
+                                file_name: pick.file_name,
+                                start: text::Position{
+                                    line_index: pick.line as usize,
+                                    byte_index: pick.column as usize
+                                }
+                            }))
+                        }
                     }
                 }
             }
@@ -367,14 +421,16 @@ impl BuildManager {
             request: tx_request
         });
         
-        let rx_file_change = self.send_file_change.receiver();
-        //let (tx_live_file, rx_live_file) = mpsc::channel::<HttpServerRequest> ();
+        let rx_studio_to_app = self.send_studio_to_app.receiver();
+        // the websockets of the running builds, so that studio can send them messages
+        let web_sockets: Arc<Mutex<HashMap<LiveId, mpsc::Sender<Vec<u8>>>>> = Default::default();
         
         // livecoding observer
+        let observer_web_sockets = web_sockets.clone();
         std::thread::spawn(move || {
-            loop{
-                if let Ok(_change) = rx_file_change.recv() {
-                    // lets send this change to all our websocket connections
+            while let Ok((build_id, msg)) = rx_studio_to_app.recv() {
+                if let Some(sender) = observer_web_sockets.lock().unwrap().get(&build_id) {
+                    let _ = sender.send(StudioToAppVec(vec![msg]).serialize_bin());
                 }
             }
         });
@@ -401,15 +457,18 @@ impl BuildManager {
             while let Ok(message) = rx_request.recv() {
                 // only store last change, fix later
                 match message {
-                    HttpServerRequest::ConnectWebSocket {web_socket_id, response_sender: _,headers} => {
+                    HttpServerRequest::ConnectWebSocket {web_socket_id, response_sender, headers} => {
                         if let Some(id) = headers.path.rsplit("/").next(){
                             if let Ok(id) = id.parse::<u64>(){
                                 socket_id_to_build_id.insert(web_socket_id, LiveId(id));
+                                web_sockets.lock().unwrap().insert(LiveId(id), response_sender);
                             }
                         }
                     },
                     HttpServerRequest::DisconnectWebSocket {web_socket_id} => {
-                        socket_id_to_build_id.remove(&web_socket_id);
+                        if let Some(id) = socket_id_to_build_id.remove(&web_socket_id) {
+                            web_sockets.lock().unwrap().remove(&id);
+                        }
                     },
                     HttpServerRequest::BinaryMessage {web_socket_id, response_sender: _, data} => {
                         if let Some(id) = socket_id_to_build_id.get(&web_socket_id){
@@ -0,0 +1,217 @@
+use {
+    std::{
+        collections::HashMap,
+        sync::mpsc::Receiver,
+    },
+    crate::{
+        makepad_code_editor::{
+            Document,
+            decoration::{Decoration, DecorationType},
+            selection::Selection,
+            text::{Change, Drift, Edit, Length, Position},
+        },
+        makepad_file_protocol::{Delta, DeltaClient, DeltaOp},
+    },
+};
+
+// the id of the decorations that show the selections of other participants
+pub const REMOTE_CURSOR_DECORATION_ID: usize = 2;
+
+// keeps an open document in sync with the collaboration session of its file on the file server
+pub struct CollabDocument {
+    pub path: String,
+    client: DeltaClient,
+    // the text of the document as far as the deltas go, which is behind the document itself
+    // until the edits in edit_receiver are turned into deltas
+    text: String,
+    edit_receiver: Receiver<Vec<Edit>>,
+    selections: Vec<(Position, Position)>,
+    remote_selections: HashMap<u64, Vec<(usize, usize)>>,
+}
+
+impl CollabDocument {
+    pub fn new(path: String, revision: u64, document: &Document) -> Self {
+        Self {
+            path,
+            client: DeltaClient::new(revision),
+            text: document.as_text().to_string(),
+            edit_receiver: document.observe_edits(),
+            selections: Vec::new(),
+            remote_selections: HashMap::new(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    // turns the edits made to the document since the last call into deltas, and returns the ones
+    // to send to the server with the revisions they apply to
+    pub fn flush_edits(&mut self) -> Vec<(u64, Delta)> {
+        let mut deltas = Vec::new();
+        while let Ok(edits) = self.edit_receiver.try_recv() {
+            for edit in edits {
+                let delta = edit_to_delta(&self.text, &edit);
+                if delta.is_noop() {
+                    continue;
+                }
+                self.text = delta.apply(&self.text).unwrap();
+                self.transform_remote_selections(&delta);
+                deltas.extend(self.client.apply_local(delta));
+            }
+        }
+        deltas
+    }
+
+    // handles the server acknowledging the delta that was sent last, and returns the next one
+    // to send, if any. deltas from other participants that were waiting for this are applied
+    pub fn apply_ack(&mut self, document: &Document, revision: u64) -> Option<(u64, Delta)> {
+        let next = self.client.apply_ack(revision);
+        self.apply_remotes(document);
+        next
+    }
+
+    // applies a delta from another participant to the document. returns the deltas for the
+    // local edits that had to be flushed first
+    pub fn apply_remote(&mut self, document: &Document, revision: u64, delta: Delta) -> Vec<(u64, Delta)> {
+        let deltas = self.flush_edits();
+        self.client.receive_remote(revision, delta);
+        self.apply_remotes(document);
+        deltas
+    }
+
+    fn apply_remotes(&mut self, document: &Document) {
+        while let Some(delta) = self.client.next_remote() {
+            let edits = delta_to_edits(&self.text, &delta);
+            self.text = delta.apply(&self.text).unwrap();
+            self.transform_remote_selections(&delta);
+            document.apply_remote_edits(edits);
+            // these are the edits we just made, they should not go back to the server
+            while self.edit_receiver.try_recv().is_ok() {}
+        }
+    }
+
+    // returns the selections to send to the server if they changed since the last call
+    pub fn selections_changed(&mut self, document: &Document, selections: &[Selection]) -> Option<Vec<(usize, usize)>> {
+        let positions: Vec<_> = selections.iter().map(|selection| (selection.anchor, selection.cursor.position)).collect();
+        if positions == self.selections {
+            return None
+        }
+        self.selections = positions;
+        // edits that are not sent yet are taken into account here, they will be soon
+        let text = document.as_text();
+        let mut line_starts = vec![0];
+        for line in text.as_lines().iter() {
+            line_starts.push(line_starts.last().unwrap() + line.len() + 1);
+        }
+        Some(self.selections.iter().map(|(anchor, cursor)| {
+            (line_starts[anchor.line_index] + anchor.byte_index, line_starts[cursor.line_index] + cursor.byte_index)
+        }).collect())
+    }
+
+    // the decorations move along with the text by themselves, but the offsets they are made
+    // from have to be kept up to date as well
+    fn transform_remote_selections(&mut self, delta: &Delta) {
+        for (anchor, cursor) in self.remote_selections.values_mut().flatten() {
+            *anchor = delta.transform_offset(*anchor);
+            *cursor = delta.transform_offset(*cursor);
+        }
+    }
+
+    // shows the selections of the other participants as decorations on the document
+    pub fn set_remote_selections(&mut self, document: &Document, participant: u64, selections: Vec<(usize, usize)>) {
+        if selections.is_empty() {
+            self.remote_selections.remove(&participant);
+        }
+        else {
+            self.remote_selections.insert(participant, selections);
+        }
+        let mut decorations: Vec<_> = self.remote_selections.values().flatten().map(|(anchor, cursor)| {
+            Decoration::new(
+                REMOTE_CURSOR_DECORATION_ID,
+                offset_to_position(&self.text, *anchor),
+                offset_to_position(&self.text, *cursor),
+                DecorationType::RemoteCursor
+            )
+        }).collect();
+        decorations.sort_by_key(|decoration| decoration.start());
+        document.replace_decorations_with_id(REMOTE_CURSOR_DECORATION_ID, decorations);
+    }
+}
+
+fn position_to_offset(text: &str, position: Position) -> usize {
+    let line_start = text.split('\n').take(position.line_index).map(|line| line.len() + 1).sum::<usize>();
+    (line_start + position.byte_index).min(text.len())
+}
+
+// offsets from other participants are not always up to date, so these are clamped to the text
+fn offset_to_position(text: &str, offset: usize) -> Position {
+    let mut offset = offset.min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    Position::zero() + length(&text[..offset])
+}
+
+fn length(text: &str) -> Length {
+    match text.rfind('\n') {
+        Some(index) => Length {
+            line_count: text.matches('\n').count(),
+            byte_count: text.len() - index - 1,
+        },
+        None => Length {
+            line_count: 0,
+            byte_count: text.len(),
+        }
+    }
+}
+
+fn edit_to_delta(text: &str, edit: &Edit) -> Delta {
+    let mut delta = Delta::new();
+    match &edit.change {
+        Change::Insert(position, inserted_text) => {
+            let offset = position_to_offset(text, *position);
+            delta.retain(offset);
+            delta.insert(&inserted_text.to_string());
+            delta.retain(text.len() - offset);
+        }
+        Change::Delete(start, length) => {
+            let start_offset = position_to_offset(text, *start);
+            let end_offset = position_to_offset(text, *start + *length);
+            delta.retain(start_offset);
+            delta.delete(end_offset - start_offset);
+            delta.retain(text.len() - end_offset);
+        }
+    }
+    delta
+}
+
+// returns the edits that have the same effect on the document as the delta has on its text
+pub fn delta_to_edits(text: &str, delta: &Delta) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    let mut position = Position::zero();
+    let mut offset = 0;
+    for op in &delta.ops {
+        match op {
+            DeltaOp::Retain(len) => {
+                position += length(&text[offset..offset + len]);
+                offset += len;
+            }
+            DeltaOp::Insert(inserted_text) => {
+                edits.push(Edit {
+                    change: Change::Insert(position, inserted_text.as_str().into()),
+                    drift: Drift::Before,
+                });
+                position += length(inserted_text);
+            }
+            DeltaOp::Delete(len) => {
+                edits.push(Edit {
+                    change: Change::Delete(position, length(&text[offset..offset + len])),
+                    drift: Drift::Before,
+                });
+                offset += len;
+            }
+        }
+    }
+    edits
+}
@@ -8,7 +8,7 @@ use {
     std::{
         //env,
         io::{Read, Write},
-        net::{ TcpStream},
+        net::{TcpListener, TcpStream},
         sync::mpsc::{self, Receiver, Sender, TryRecvError},
         thread,
         path::Path,
@@ -32,7 +32,13 @@ pub struct FileClientInner {
 impl FileClient {
     pub fn init(&mut self, _cx:&mut Cx, path:&Path){
         if self.inner.is_none() {
-            self.inner = Some(FileClientInner::new_with_local_server(path))
+            // --collab-connect=host:port edits the files of another studio together with it, which
+            // makes them available with --collab-listen=host:port
+            let arg = |prefix: &str| std::env::args().find_map(|arg| arg.strip_prefix(prefix).map(|addr| addr.to_string()));
+            self.inner = Some(match arg("--collab-connect=") {
+                Some(addr) => FileClientInner::new_connect_remote(&addr),
+                None => FileClientInner::new_with_local_server(path, arg("--collab-listen=").as_deref())
+            })
         }
     }
     
@@ -60,7 +66,7 @@ impl FileClient {
 }
 
 impl FileClientInner {
-    pub fn new_with_local_server(path:&Path) -> Self {
+    pub fn new_with_local_server(path:&Path, listen_addr: Option<&str>) -> Self {
         let (request_sender, request_receiver) = mpsc::channel();
         let message_signal = SignalToUI::new();
         let (message_sender, message_receiver) = mpsc::channel();
@@ -90,7 +96,12 @@ impl FileClientInner {
             message_signal.clone(),
             message_sender,
         );
-        //spawn_connection_listener(TcpListener::bind("127.0.0.1:0").unwrap(), server);
+        if let Some(listen_addr) = listen_addr {
+            match TcpListener::bind(listen_addr) {
+                Ok(listener) => spawn_connection_listener(listener, server),
+                Err(err) => error!("Cannot listen on {} {}", listen_addr, err)
+            }
+        }
         
         Self {
             request_sender,
@@ -116,61 +127,73 @@ impl FileClientInner {
     }
     
 }
-/*
-fn _spawn_connection_listener(listener: TcpListener, mut server: FileServer) {
+fn spawn_connection_listener(listener: TcpListener, mut server: FileServer) {
     thread::spawn(move || {
         log!("Server listening on {}", listener.local_addr().unwrap());
         for stream in listener.incoming() {
-            let stream = stream.unwrap();
+            let Ok(stream) = stream else {
+                continue
+            };
             log!("Incoming connection from {}", stream.peer_addr().unwrap());
-            let (action_sender, action_receiver) = mpsc::channel();
-            let _connection = server.connect(Box::new({
-                let action_sender = action_sender.clone();
+            let (message_sender, message_receiver) = mpsc::channel();
+            let connection = server.connect(Box::new({
+                let message_sender = message_sender.clone();
                 move | notification | {
-                    action_sender.send(FileClientAction::Notification(notification)).unwrap();
+                    let _ = message_sender.send(FileClientMessage::Notification(notification));
                 }
             }));
             spawn_remote_request_handler(
                 connection,
                 stream.try_clone().unwrap(),
-                action_sender,
+                message_sender,
             );
-            spawn_response_or_notification_sender(action_receiver, stream);
+            spawn_response_or_notification_sender(message_receiver, stream);
         }
     });
-}*/
+}
 
-fn _spawn_remote_request_handler(
+// handles the requests of a remote client until it disconnects, which drops its connection
+fn spawn_remote_request_handler(
     connection: FileServerConnection,
     mut stream: TcpStream,
     message_sender: Sender<FileClientMessage>,
 ) {
     thread::spawn(move || loop {
         let mut len_bytes = [0; 4];
-        stream.read_exact(&mut len_bytes).unwrap();
+        if stream.read_exact(&mut len_bytes).is_err() {
+            break;
+        }
         let len = u32::from_be_bytes(len_bytes);
         let mut request_bytes = vec![0; len as usize];
-        stream.read_exact(&mut request_bytes).unwrap();
+        if stream.read_exact(&mut request_bytes).is_err() {
+            break;
+        }
         
         let request = DeBin::deserialize_bin(request_bytes.as_slice()).unwrap();
         let response = connection.handle_request(request);
-        message_sender.send(FileClientMessage::Response(response)).unwrap();
+        if message_sender.send(FileClientMessage::Response(response)).is_err() {
+            break;
+        }
     });
 }
 
-fn _spawn_response_or_notification_sender(
+fn spawn_response_or_notification_sender(
     message_receiver: Receiver<FileClientMessage>,
     mut stream: TcpStream,
 ) {
     thread::spawn(move || loop {
-        let message = message_receiver.recv().unwrap();
+        let Ok(message) = message_receiver.recv() else {
+            break
+        };
         let mut message_bytes = Vec::new();
         
         message.ser_bin(&mut message_bytes);
         
-        let len_bytes = message_bytes.len().to_be_bytes();
-        stream.write_all(&len_bytes).unwrap();
-        stream.write_all(&message_bytes).unwrap();
+        // messages are prefixed with their length as 4 bytes, which is what the receiver reads
+        let len_bytes = (message_bytes.len() as u32).to_be_bytes();
+        if stream.write_all(&len_bytes).and_then(|_| stream.write_all(&message_bytes)).is_err() {
+            break;
+        }
     });
 }
 
@@ -179,7 +202,7 @@ fn spawn_request_sender(request_receiver: Receiver<FileRequest>, mut stream: Tcp
         let request = request_receiver.recv().unwrap();
         let mut request_bytes = Vec::new();
         request.ser_bin(&mut request_bytes);
-        let len_bytes = request_bytes.len().to_be_bytes();
+        let len_bytes = (request_bytes.len() as u32).to_be_bytes();
         stream.write_all(&len_bytes).unwrap();
         stream.write_all(&request_bytes).unwrap();
     });
@@ -6,14 +6,20 @@ use {
         makepad_platform::makepad_live_compiler::LiveFileChange,
         makepad_widgets::*,
         makepad_widgets::file_tree::*,
-        file_system::FileClient,
+        file_system::{FileClient, collab::{self, CollabDocument}, git},
         makepad_file_protocol::{
+            Delta,
             FileRequest,
             FileError,
             FileResponse,
             FileClientMessage,
+            FileNotification,
             FileNodeData,
             FileTreeData,
+            SearchResult,
+            FileChange,
+            GitBlameLine,
+            GitStatus,
         },
     },
 };
@@ -26,7 +32,36 @@ pub struct FileSystem {
     pub path_to_file_node_id: HashMap<String, FileNodeId>,
     pub tab_id_to_file_node_id: HashMap<LiveId, FileNodeId>,
     pub tab_id_to_session: HashMap<LiveId, Session>,
-    pub open_documents: HashMap<FileNodeId, OpenDoc>
+    pub open_documents: HashMap<FileNodeId, OpenDoc>,
+    pub search_id: u64,
+    pub search_results: Vec<SearchResult>,
+    pub search_status: SearchStatus,
+    // the collaboration sessions of the open documents on the file server
+    pub collab_documents: HashMap<FileNodeId, CollabDocument>,
+    // notifications for documents that are still being opened
+    pub early_notifications: Vec<FileNotification>,
+    // renames that are applied to the open documents once the file tree is reloaded
+    pub pending_renames: Vec<(String, String)>,
+    // the tabs that show the changes to a file since the last commit
+    pub tab_id_to_diff_file_node_id: HashMap<LiveId, FileNodeId>,
+    // the git status of every changed file, by path
+    pub git_statuses: HashMap<String, GitStatus>,
+    // a git status request is underway, and another one is needed once it is done
+    pub git_status_requested: bool,
+    pub git_status_outdated: bool,
+    // the line that the cursor of each tab was on when it was blamed last
+    pub git_blame_lines: HashMap<LiveId, usize>,
+    // the blame for the last line that was blamed in each file, by path
+    pub git_blames: HashMap<String, (usize, GitBlameLine)>,
+}
+
+#[derive(Default)]
+pub enum SearchStatus {
+    #[default]
+    Idle,
+    Running,
+    Finished,
+    Error(String)
 }
 
 pub enum OpenDoc {
@@ -59,6 +94,11 @@ pub enum FileSystemAction {
     TreeLoaded,
     RecompileNeeded,
     LiveReloadNeeded(LiveFileChange),
+    DocumentOpened(FileNodeId),
+    SearchResultsChanged,
+    DocumentChangedRemotely(FileNodeId),
+    GitStatusChanged,
+    GitFileChanged(FileNodeId),
     None
 }
 
@@ -72,9 +112,74 @@ impl FileSystem {
         self.file_client.send_request(FileRequest::LoadFileTree {with_data: false});
     }
     
+    pub fn search(&mut self, pattern: String, regex: bool, case: bool, globs: Vec<String>) {
+        // results of the previous search that are still underway are dropped by their id
+        self.search_id += 1;
+        self.search_results.clear();
+        self.search_status = if pattern.is_empty() {SearchStatus::Idle} else {SearchStatus::Running};
+        self.file_client.send_request(FileRequest::Search {id: self.search_id, pattern, regex, case, globs});
+    }
+    
+    pub fn request_create_file(&mut self, path: String, is_dir: bool) {
+        if is_dir {
+            self.file_client.send_request(FileRequest::CreateDirectory(path));
+        }
+        else {
+            self.file_client.send_request(FileRequest::CreateFile(path));
+        }
+    }
+    
+    pub fn request_rename_file(&mut self, old_path: String, new_path: String) {
+        self.file_client.send_request(FileRequest::RenameFile(old_path, new_path));
+    }
+    
+    pub fn request_delete_file(&mut self, path: String) {
+        self.file_client.send_request(FileRequest::DeleteFile(path));
+    }
+    
     pub fn remove_tab(&mut self, tab_id: LiveId) {
         self.tab_id_to_file_node_id.remove(&tab_id);
         self.tab_id_to_session.remove(&tab_id);
+        self.tab_id_to_diff_file_node_id.remove(&tab_id);
+        self.git_blame_lines.remove(&tab_id);
+    }
+    
+    pub fn request_git_status(&mut self) {
+        // the status is asked for after every change, so only one request is underway at a time
+        if self.git_status_requested {
+            self.git_status_outdated = true;
+            return
+        }
+        self.git_status_requested = true;
+        self.file_client.send_request(FileRequest::GitStatus);
+    }
+    
+    fn request_git_head_texts(&mut self) {
+        for collab in self.collab_documents.values() {
+            self.file_client.send_request(FileRequest::GitHeadText(collab.path.clone()));
+        }
+    }
+    
+    fn collab_path_to_file_node_id(&self, path: &str) -> Option<FileNodeId> {
+        self.collab_documents.iter().find(|(_, collab)| collab.path == path).map(|(file_id, _)| *file_id)
+    }
+    
+    // the blame for the line the cursor of the given tab is on, to show after that line
+    pub fn inline_blame(&self, tab_id: LiveId) -> Option<(usize, String)> {
+        let session = self.tab_id_to_session.get(&tab_id)?;
+        let file_id = self.tab_id_to_file_node_id.get(&tab_id)?;
+        let collab = self.collab_documents.get(file_id)?;
+        let line_index = session.selections()[session.last_added_selection_index()?].cursor.position.line_index;
+        // changed lines are not blamed until they are on disk, but we already know the answer
+        let is_changed = session.document().diff_hunks().iter().any(|hunk| hunk.new.contains(&line_index));
+        if is_changed {
+            return Some((line_index, "Uncommitted changes".to_string()))
+        }
+        let (blame_line_index, blame) = self.git_blames.get(&collab.path)?;
+        if *blame_line_index != line_index {
+            return None
+        }
+        Some((line_index, git::format_blame(blame)))
     }
     
     pub fn path_to_file_node_id(&self, path: &str) -> Option<FileNodeId> {
@@ -110,14 +215,21 @@ impl FileSystem {
                 match message {
                     FileClientMessage::Response(response) => match response {
                         FileResponse::LoadFileTree(response) => {
+                            let renames = self.take_pending_renames();
                             self.load_file_tree(response.unwrap());
+                            self.apply_renames(renames);
+                            self.request_git_status();
                             cx.action(FileSystemAction::TreeLoaded)
                             // dock.select_tab(cx, dock, state, live_id!(file_tree).into(), live_id!(file_tree).into(), Animate::No);
                         }
                         FileResponse::OpenFile(result) => {
                             match result {
-                                Ok((_unix_path, data, id)) => {
+                                Ok((unix_path, data, revision, id)) => {
                                     let file_id = FileNodeId(LiveId(id));
+                                    if let Some(OpenDoc::Document(_)) = self.open_documents.get(&file_id) {
+                                        self.reset_document(cx, file_id, unix_path, data, revision);
+                                        continue;
+                                    }
                                     let dock = ui.dock(id!(dock));
                                     for (tab_id, file_id) in &self.tab_id_to_file_node_id {
                                         if id == file_id.0.0 {
@@ -126,8 +238,16 @@ impl FileSystem {
                                     }
                                     if let Some(OpenDoc::Decorations(dec)) = self.open_documents.get(&file_id) {
                                         let dec = dec.clone();
-                                        self.open_documents.insert(file_id, OpenDoc::Document(Document::new(data.into(), dec)));
-                                    }else {panic!()}
+                                        let extension = Path::new(&unix_path).extension().and_then(|ext| ext.to_str()).unwrap_or("");
+                                        let document = Document::new(data.into(), dec, extension);
+                                        self.file_client.send_request(FileRequest::GitHeadText(unix_path.clone()));
+                                        self.collab_documents.insert(file_id, CollabDocument::new(unix_path, revision, &document));
+                                        self.open_documents.insert(file_id, OpenDoc::Document(document));
+                                        cx.action(FileSystemAction::DocumentOpened(file_id));
+                                        for notification in std::mem::take(&mut self.early_notifications) {
+                                            self.handle_collab_notification(cx, notification);
+                                        }
+                                    }
                                     ui.redraw(cx);
                                 }
                                 Err(FileError::CannotOpen(_unix_path)) => {
@@ -138,62 +258,301 @@ impl FileSystem {
                                 }
                             }
                         }
-                        FileResponse::SaveFile(result) => match result {
-                            Ok((path, old, new, _id)) => {
-                                // alright file has been saved
-                                // now we need to check if a live_design!{} changed or something outside it
-                                if old != new {
-                                    let mut old_neg = Vec::new();
-                                    let mut new_neg = Vec::new();
-                                    match LiveRegistry::tokenize_from_str_live_design(&old, Default::default(), Default::default(), Some(&mut old_neg)) {
-                                        Err(e) => {
-                                            log!("Cannot tokenize old file {}", e)
-                                        }
-                                        Ok(old_tokens) => match LiveRegistry::tokenize_from_str_live_design(&new, Default::default(), Default::default(), Some(&mut new_neg)) {
-                                            Err(e) => {
-                                                log!("Cannot tokenize new file {}", e);
-                                            }
-                                            Ok(new_tokens) => {
-                                                // we need the space 'outside' of these tokens
-                                                if old_neg != new_neg {
-                                                    cx.action(FileSystemAction::RecompileNeeded)
-                                                }
-                                                if old_tokens != new_tokens {
-                                                    // design code changed, hotreload it
-                                                    cx.action( FileSystemAction::LiveReloadNeeded(LiveFileChange {
-                                                        file_name: path,
-                                                        content: new
-                                                    }));
-                                                }
-                                            }
-                                        }
+                        FileResponse::ApplyDelta(result) => match result {
+                            Ok((_path, revision, id)) => {
+                                let file_id = FileNodeId(LiveId(id));
+                                let (Some(collab), Some(OpenDoc::Document(document))) = (self.collab_documents.get_mut(&file_id), self.open_documents.get(&file_id)) else {
+                                    continue
+                                };
+                                let old_text = collab.text().to_string();
+                                let next = collab.apply_ack(document, revision);
+                                let path = collab.path.clone();
+                                let new_text = collab.text().to_string();
+                                if let Some((revision, delta)) = next {
+                                    self.file_client.send_request(FileRequest::ApplyDelta(path.clone(), revision, delta, id));
+                                }
+                                // the file on disk changed, which can change its status
+                                self.request_git_status();
+                                if old_text != new_text {
+                                    self.check_live_reload(cx, path, &old_text, new_text);
+                                    cx.action(FileSystemAction::DocumentChangedRemotely(file_id));
+                                }
+                            }
+                            Err((id, err)) => {
+                                // the server no longer agrees with us, start over from its text
+                                log!("Cannot apply edits {:?}", err);
+                                if let Some(collab) = self.collab_documents.get(&FileNodeId(LiveId(id))) {
+                                    self.file_client.send_request(FileRequest::OpenFile(collab.path.clone(), id));
+                                }
+                            }
+                        }
+                        FileResponse::CloseFile(_) | FileResponse::SetSelections(_) => (),
+                        FileResponse::Search(result) => match result {
+                            Err((id, FileError::Unknown(err) | FileError::CannotOpen(err))) if id == self.search_id => {
+                                self.search_status = SearchStatus::Error(err);
+                                cx.action(FileSystemAction::SearchResultsChanged)
+                            }
+                            _ => ()
+                        }
+                        FileResponse::CreateFile(result) | FileResponse::CreateDirectory(result) | FileResponse::DeleteFile(result) => match result {
+                            Ok(_) => self.reload_file_tree(),
+                            Err(err) => log!("File operation failed {:?}", err)
+                        }
+                        FileResponse::GitStatus(result) => {
+                            self.git_status_requested = false;
+                            // outside of a repository there is nothing to show
+                            self.git_statuses = result.map(|statuses| {
+                                statuses.into_iter().map(|status| (status.path, status.status)).collect()
+                            }).unwrap_or_default();
+                            if self.git_status_outdated {
+                                self.git_status_outdated = false;
+                                self.request_git_status();
+                            }
+                            cx.action(FileSystemAction::GitStatusChanged)
+                        }
+                        FileResponse::GitHeadText(result) => if let Ok((path, text)) = result {
+                            if let Some(file_id) = self.collab_path_to_file_node_id(&path) {
+                                if let Some(OpenDoc::Document(document)) = self.open_documents.get(&file_id) {
+                                    document.set_diff_base(text.map(|text| text.into()));
+                                    cx.action(FileSystemAction::GitFileChanged(file_id))
+                                }
+                            }
+                        }
+                        FileResponse::GitBlame(result) => if let Ok((path, line_index, blame)) = result {
+                            if let Some(file_id) = self.collab_path_to_file_node_id(&path) {
+                                self.git_blames.insert(path, (line_index, blame));
+                                cx.action(FileSystemAction::GitFileChanged(file_id))
+                            }
+                        }
+                        FileResponse::RenameFile(result) => match result {
+                            Ok((old_path, new_path)) => {
+                                // the server moves the collaboration sessions right away
+                                for collab in self.collab_documents.values_mut() {
+                                    if collab.path == old_path {
+                                        collab.path = new_path.clone();
+                                    }
+                                    else if let Some(rest) = collab.path.strip_prefix(&old_path).and_then(|rest| rest.strip_prefix('/')) {
+                                        collab.path = format!("{}/{}", new_path, rest);
                                     }
                                 }
+                                self.pending_renames.push((old_path, new_path));
+                                self.reload_file_tree();
                             }
-                            Err(_) => {}
-                            // ok we saved a file, we should check however what changed
-                            // to see if we need a recompile
-                            
+                            Err(err) => log!("Cannot rename file {:?}", err)
                         }
                     },
-                    FileClientMessage::Notification(_notification) => {
-                        //self.editors.handle_collab_notification(cx, &mut state.editor_state, notification)
+                    FileClientMessage::Notification(notification) => match notification {
+                        FileNotification::SearchResults {id, results} => if id == self.search_id {
+                            self.search_results.extend(results);
+                            cx.action(FileSystemAction::SearchResultsChanged)
+                        }
+                        FileNotification::SearchFinished {id} => if id == self.search_id {
+                            if let SearchStatus::Running = self.search_status {
+                                self.search_status = SearchStatus::Finished;
+                            }
+                            cx.action(FileSystemAction::SearchResultsChanged)
+                        }
+                        FileNotification::FileChangedOnDisk(changes) => {
+                            // the server turns changes to open files into deltas itself
+                            if changes.iter().any(|change| !matches!(change, FileChange::Modified(_))) {
+                                self.reload_file_tree();
+                            }
+                            else {
+                                self.request_git_status();
+                            }
+                        }
+                        FileNotification::GitChanged => {
+                            // a commit or checkout changes what the files are compared to
+                            self.git_blames.clear();
+                            self.git_blame_lines.clear();
+                            self.request_git_head_texts();
+                            self.request_git_status();
+                        }
+                        notification @ (FileNotification::DeltaWasApplied {..} | FileNotification::SelectionsChanged {..}) => {
+                            self.handle_collab_notification(cx, notification);
+                        }
                     }
                 }
             }
         }
     }
 
+    fn handle_collab_notification(&mut self, cx: &mut Cx, notification: FileNotification) {
+        let path = match &notification {
+            FileNotification::DeltaWasApplied {path, ..} | FileNotification::SelectionsChanged {path, ..} => path,
+            _ => return
+        };
+        let Some(file_id) = self.collab_path_to_file_node_id(path) else {
+            // the response to opening the document might still be underway
+            if self.path_to_file_node_id(path).is_some_and(|file_id| matches!(self.open_documents.get(&file_id), Some(OpenDoc::Decorations(_)))) {
+                self.early_notifications.push(notification);
+            }
+            return
+        };
+        let Some(OpenDoc::Document(document)) = self.open_documents.get(&file_id) else {
+            return
+        };
+        let collab = self.collab_documents.get_mut(&file_id).unwrap();
+        match notification {
+            FileNotification::DeltaWasApplied {revision, delta, ..} => {
+                let old_text = collab.text().to_string();
+                let deltas = collab.apply_remote(document, revision, delta);
+                let path = collab.path.clone();
+                let new_text = collab.text().to_string();
+                for (revision, delta) in deltas {
+                    self.file_client.send_request(FileRequest::ApplyDelta(path.clone(), revision, delta, file_id.0.0));
+                }
+                self.check_live_reload(cx, path, &old_text, new_text);
+            }
+            FileNotification::SelectionsChanged {participant, selections, ..} => {
+                collab.set_remote_selections(document, participant, selections);
+            }
+            _ => ()
+        }
+        cx.action(FileSystemAction::DocumentChangedRemotely(file_id));
+    }
+    
+    // replaces the text of an open document with the text of the server after the collaboration
+    // session was lost
+    fn reset_document(&mut self, cx: &mut Cx, file_id: FileNodeId, path: String, text: String, revision: u64) {
+        let Some(OpenDoc::Document(document)) = self.open_documents.get(&file_id) else {
+            return
+        };
+        let old_text = document.as_text().to_string();
+        let delta = Delta::replace(&old_text, &text);
+        document.apply_remote_edits(collab::delta_to_edits(&old_text, &delta));
+        document.replace_decorations_with_id(collab::REMOTE_CURSOR_DECORATION_ID, []);
+        self.collab_documents.insert(file_id, CollabDocument::new(path, revision, document));
+        cx.action(FileSystemAction::DocumentChangedRemotely(file_id));
+    }
+    
+    // checks whether a changed file needs to be recompiled, or if live_design! can be reloaded
+    fn check_live_reload(&mut self, cx: &mut Cx, path: String, old: &str, new: String) {
+        if old == new {
+            return
+        }
+        let mut old_neg = Vec::new();
+        let mut new_neg = Vec::new();
+        match LiveRegistry::tokenize_from_str_live_design(old, Default::default(), Default::default(), Some(&mut old_neg)) {
+            Err(e) => {
+                log!("Cannot tokenize old file {}", e)
+            }
+            Ok(old_tokens) => match LiveRegistry::tokenize_from_str_live_design(&new, Default::default(), Default::default(), Some(&mut new_neg)) {
+                Err(e) => {
+                    log!("Cannot tokenize new file {}", e);
+                }
+                Ok(new_tokens) => {
+                    // we need the space 'outside' of these tokens
+                    if old_neg != new_neg {
+                        cx.action(FileSystemAction::RecompileNeeded)
+                    }
+                    if old_tokens != new_tokens {
+                        // design code changed, hotreload it
+                        cx.action( FileSystemAction::LiveReloadNeeded(LiveFileChange {
+                            file_name: path,
+                            content: new
+                        }));
+                    }
+                }
+            }
+        }
+    }
+    
+    fn take_pending_renames(&mut self) -> Vec<(FileNodeId, String)> {
+        // find the new paths of open files while the old tree is still around
+        let mut renames = Vec::new();
+        for (old_path, new_path) in std::mem::take(&mut self.pending_renames) {
+            let file_ids: Vec<FileNodeId> = self.open_documents.keys().chain(self.tab_id_to_file_node_id.values()).cloned().collect();
+            for file_id in file_ids {
+                if !self.file_nodes.contains_key(&file_id) || renames.iter().any(|(id, _)| *id == file_id) {
+                    continue;
+                }
+                let path = self.file_node_path(file_id);
+                if path == old_path {
+                    renames.push((file_id, new_path.clone()));
+                }
+                else if let Some(rest) = path.strip_prefix(&old_path).and_then(|rest| rest.strip_prefix('/')) {
+                    renames.push((file_id, format!("{}/{}", new_path, rest)));
+                }
+            }
+        }
+        renames
+    }
+    
+    fn apply_renames(&mut self, renames: Vec<(FileNodeId, String)>) {
+        for (old_id, new_path) in renames {
+            let Some(new_id) = self.path_to_file_node_id(&new_path) else {
+                continue
+            };
+            if let Some(doc) = self.open_documents.remove(&old_id) {
+                self.open_documents.insert(new_id, doc);
+            }
+            if let Some(collab) = self.collab_documents.remove(&old_id) {
+                self.collab_documents.insert(new_id, collab);
+            }
+            for file_id in self.tab_id_to_file_node_id.values_mut().chain(self.tab_id_to_diff_file_node_id.values_mut()) {
+                if *file_id == old_id {
+                    *file_id = new_id;
+                }
+            }
+        }
+    }
+    
+    // closes the tabs of files that no longer exist, and returns their ids
+    pub fn remove_deleted_tabs(&mut self) -> Vec<LiveId> {
+        let tab_ids: Vec<LiveId> = self.tab_id_to_file_node_id.iter()
+            .chain(self.tab_id_to_diff_file_node_id.iter())
+            .filter(|(_, file_id)| !self.file_nodes.contains_key(file_id))
+            .map(|(tab_id, _)| *tab_id)
+            .collect();
+        for tab_id in &tab_ids {
+            if let Some(file_id) = self.tab_id_to_file_node_id.get(tab_id).or(self.tab_id_to_diff_file_node_id.get(tab_id)).cloned() {
+                self.open_documents.remove(&file_id);
+                self.collab_documents.remove(&file_id);
+            }
+            self.remove_tab(*tab_id);
+        }
+        tab_ids
+    }
+    
     pub fn handle_sessions(&mut self) {
-        for session in self.tab_id_to_session.values_mut() {
+        for (tab_id, session) in &mut self.tab_id_to_session {
             session.handle_changes();
+            // let the other participants know where our cursors are
+            let Some(file_id) = self.tab_id_to_file_node_id.get(tab_id) else {
+                continue
+            };
+            if let Some(collab) = self.collab_documents.get_mut(file_id) {
+                if let Some(selections) = collab.selections_changed(session.document(), &session.selections()) {
+                    self.file_client.send_request(FileRequest::SetSelections(collab.path.clone(), selections));
+                }
+                // blame the line the cursor moved to, for files that are in the last commit
+                let Some(index) = session.last_added_selection_index() else {
+                    continue
+                };
+                let line_index = session.selections()[index].cursor.position.line_index;
+                if self.git_blame_lines.get(tab_id) != Some(&line_index) && session.document().diff_base().is_some() {
+                    self.git_blame_lines.insert(*tab_id, line_index);
+                    self.file_client.send_request(FileRequest::GitBlame(collab.path.clone(), line_index));
+                }
+            }
         }
     }
     
     pub fn request_open_file(&mut self, tab_id: LiveId, file_id: FileNodeId) {
+        self.tab_id_to_file_node_id.insert(tab_id, file_id);
+        self.open_document(file_id);
+    }
+    
+    // opens a tab with the changes to the given file since the last commit
+    pub fn request_open_diff(&mut self, tab_id: LiveId, file_id: FileNodeId) {
+        self.tab_id_to_diff_file_node_id.insert(tab_id, file_id);
+        self.open_document(file_id);
+    }
+    
+    fn open_document(&mut self, file_id: FileNodeId) {
         // ok lets see if we have a document
         // ifnot, we create a new one
-        self.tab_id_to_file_node_id.insert(tab_id, file_id);
         // move decorations to doc
         let dec = match self.open_documents.get(&file_id){
             Some(OpenDoc::Decorations(_))=> if let Some(OpenDoc::Decorations(dec)) = self.open_documents.remove(&file_id){
@@ -213,16 +572,22 @@ impl FileSystem {
     }
     
     
-    pub fn request_save_file(&mut self, tab_id: LiveId) {
-        // ok lets see if we have a document
-        // ifnot, we create a new one
-        if let Some(file_id) = self.tab_id_to_file_node_id.get(&tab_id) {
-            if let Some(OpenDoc::Document(doc)) = self.open_documents.get(&file_id) {
-                let text = doc.as_text().to_string();
-                let path = self.file_node_path(*file_id);
-                self.file_client.send_request(FileRequest::SaveFile(path.clone(), text, file_id.0.0));
-            }
+    // sends the edits made to the document in the given tab to the file server, which saves them
+    pub fn send_edits(&mut self, cx: &mut Cx, tab_id: LiveId) {
+        let Some(file_id) = self.tab_id_to_file_node_id.get(&tab_id).cloned() else {
+            return
         };
+        let Some(collab) = self.collab_documents.get_mut(&file_id) else {
+            return
+        };
+        let old_text = collab.text().to_string();
+        let deltas = collab.flush_edits();
+        let path = collab.path.clone();
+        let new_text = collab.text().to_string();
+        for (revision, delta) in deltas {
+            self.file_client.send_request(FileRequest::ApplyDelta(path.clone(), revision, delta, file_id.0.0));
+        }
+        self.check_live_reload(cx, path, &old_text, new_text);
     }
     
     pub fn clear_decorations(&mut self, file_node_id: &FileNodeId) {
@@ -230,7 +595,8 @@ impl FileSystem {
         // ifnot, we create a new one
         match self.open_documents.get_mut(file_node_id) {
             Some(OpenDoc::Decorations(dec)) => dec.clear(),
-            Some(OpenDoc::Document(doc)) => doc.clear_decorations(),
+            // keep the diagnostics of the language server
+            Some(OpenDoc::Document(doc)) => doc.replace_decorations_with_id(0, []),
             None => ()
         };
     }
@@ -241,13 +607,13 @@ impl FileSystem {
         for document in self.open_documents.values_mut() {
             match document {
                 OpenDoc::Decorations(dec) => dec.clear(),
-                OpenDoc::Document(doc) => doc.clear_decorations(),
+                OpenDoc::Document(doc) => doc.replace_decorations_with_id(0, []),
             }
         }
     }
     
     pub fn redraw_view_by_file_id(&mut self, cx: &mut Cx, id: FileNodeId, dock: &DockRef) {
-        for (tab_id, file_id) in &self.tab_id_to_file_node_id {
+        for (tab_id, file_id) in self.tab_id_to_file_node_id.iter().chain(self.tab_id_to_diff_file_node_id.iter()) {
             if id == *file_id {
                 dock.item(*tab_id).redraw(cx)
             }
@@ -278,18 +644,24 @@ impl FileSystem {
     
     
     pub fn draw_file_node(&self, cx: &mut Cx2d, file_node_id: FileNodeId, file_tree: &mut FileTree) {
+        self.draw_file_node_with_path(cx, file_node_id, "", file_tree);
+    }
+    
+    fn draw_file_node_with_path(&self, cx: &mut Cx2d, file_node_id: FileNodeId, path: &str, file_tree: &mut FileTree) {
         if let Some(file_node) = self.file_nodes.get(&file_node_id) {
             match &file_node.child_edges {
                 Some(child_edges) => {
                     if file_tree.begin_folder(cx, file_node_id, &file_node.name).is_ok() {
                         for child_edge in child_edges {
-                            self.draw_file_node(cx, child_edge.file_node_id, file_tree);
+                            let child_path = if path.is_empty() {child_edge.name.clone()} else {format!("{}/{}", path, child_edge.name)};
+                            self.draw_file_node_with_path(cx, child_edge.file_node_id, &child_path, file_tree);
                         }
                         file_tree.end_folder();
                     }
                 }
                 None => {
-                    file_tree.file(cx, file_node_id, &file_node.name);
+                    let badge = self.git_statuses.get(path).map(|status| git::status_badge(*status));
+                    file_tree.file_with_badge(cx, file_node_id, &file_node.name, badge);
                 }
             }
         }
@@ -432,6 +804,7 @@ impl FileSystem {
         
         
         self.file_nodes.clear();
+        self.path_to_file_node_id.clear();
         
         create_file_node(
             Some(live_id!(root).into()),
@@ -0,0 +1,40 @@
+use crate::{
+    makepad_file_protocol::{GitBlameLine, GitStatus},
+    makepad_widgets::*,
+};
+
+// the letter and color that files with the given status are marked with in the file tree
+pub fn status_badge(status: GitStatus) -> (&'static str, Vec4) {
+    match status {
+        GitStatus::Modified => ("M", Vec4::from_u32(0xe2c08dff)),
+        GitStatus::Added => ("A", Vec4::from_u32(0x81b88bff)),
+        GitStatus::Deleted => ("D", Vec4::from_u32(0xc74e39ff)),
+        GitStatus::Renamed => ("R", Vec4::from_u32(0x73c991ff)),
+        GitStatus::Untracked => ("U", Vec4::from_u32(0x73c991ff)),
+        GitStatus::Conflicted => ("C", Vec4::from_u32(0xe4676bff)),
+    }
+}
+
+// the text that is shown after a line to tell who changed it last, and why
+pub fn format_blame(blame: &GitBlameLine) -> String {
+    if blame.commit.bytes().all(|byte| byte == b'0') {
+        return "Uncommitted changes".to_string()
+    }
+    let (year, month, day) = date_from_unix_time(blame.time);
+    format!("{}, {:04}-{:02}-{:02} • {}", blame.author, year, month, day, blame.summary)
+}
+
+// turns seconds since the unix epoch into a date in utc. this is the days_from_civil algorithm
+// by howard hinnant, the other way around
+fn date_from_unix_time(time: u64) -> (u64, u64, u64) {
+    let days = time / 86400 + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {shifted_month + 3} else {shifted_month - 9};
+    let year = year_of_era + era * 400 + if month <= 2 {1} else {0};
+    (year, month, day)
+}
@@ -8,4 +8,6 @@ pub mod file_client_wasm;
 #[cfg(target_arch = "wasm32")]
 pub use file_client_wasm::*;
 
+pub mod collab;
 pub mod file_system;
+pub mod git;
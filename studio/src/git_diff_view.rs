@@ -0,0 +1,220 @@
+use {
+    crate::{
+        app::{AppAction, AppData},
+        file_system::file_system::{FileSystem, OpenDoc},
+        log_list::JumpTo,
+        makepad_widgets::*,
+        makepad_code_editor::{diff::Hunk, text::{Position, Text}, Document},
+    },
+};
+
+live_design!{
+    import makepad_draw::shader::std::*;
+    import makepad_widgets::base::*;
+    import makepad_widgets::theme_desktop_dark::*;
+
+    DiffSide = <View> {
+        height: Fit,
+        width: Fill
+        flow: Right
+        show_bg: true
+        draw_bg: {
+            // 0 is unchanged, 1 is removed, 2 is added, 3 is a gap across from changed lines
+            instance kind: 0.0
+            fn pixel(self) -> vec4 {
+                if self.kind > 2.5 {
+                    return #x222
+                }
+                if self.kind > 1.5 {
+                    return #x2c3d25
+                }
+                if self.kind > 0.5 {
+                    return #x4b2327
+                }
+                return #x28
+            }
+        }
+        number = <Label> {
+            width: 50,
+            margin: 0,
+            padding: {left: 5, top: 1, bottom: 1}
+            draw_text: {color: #6, text_style: <THEME_FONT_CODE> {}}
+        }
+        text = <Label> {
+            width: Fill,
+            margin: 0,
+            padding: {top: 1, bottom: 1}
+            draw_text: {wrap: Ellipsis, text_style: <THEME_FONT_CODE> {}}
+        }
+    }
+
+    GitDiffView = {{GitDiffView}}{
+        height: Fill,
+        width: Fill
+        flow: Down
+        show_bg: true
+        draw_bg: {color: #x28}
+        <View> {
+            height: Fit,
+            width: Fill
+            padding: 10
+            title = <Label> {draw_text: {color: #a}, text: ""}
+        }
+        list = <PortalList> {
+            drag_scrolling: false
+            height: Fill,
+            width: Fill
+            flow: Down
+            Row = <View> {
+                height: Fit,
+                width: Fill
+                flow: Right
+                spacing: 2
+                cursor: Hand
+                left = <DiffSide> {}
+                right = <DiffSide> {}
+            }
+        }
+    }
+}
+
+// A row of the diff, with a line of the last commit on the left and a line of the current text
+// on the right. Changed lines that have nothing across from them leave a gap on the other side.
+struct DiffRow {
+    old_line: Option<usize>,
+    new_line: Option<usize>,
+    is_changed: bool,
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct GitDiffView{
+    #[deref] view: View,
+    #[rust] rows: Vec<DiffRow>,
+}
+
+impl GitDiffView{
+    fn update_rows(&mut self, hunks: &[Hunk], old_line_count: usize, new_line_count: usize) {
+        self.rows.clear();
+        let mut old_line = 0;
+        let mut new_line = 0;
+        for hunk in hunks.iter().chain([&Hunk {old: old_line_count..old_line_count, new: new_line_count..new_line_count}]) {
+            while old_line < hunk.old.start {
+                self.rows.push(DiffRow {old_line: Some(old_line), new_line: Some(new_line), is_changed: false});
+                old_line += 1;
+                new_line += 1;
+            }
+            for index in 0..hunk.old.len().max(hunk.new.len()) {
+                self.rows.push(DiffRow {
+                    old_line: (index < hunk.old.len()).then_some(hunk.old.start + index),
+                    new_line: (index < hunk.new.len()).then_some(hunk.new.start + index),
+                    is_changed: true,
+                });
+            }
+            old_line = hunk.old.end;
+            new_line = hunk.new.end;
+        }
+    }
+
+    fn draw_rows(&mut self, cx: &mut Cx2d, list: &mut PortalList, document: &Document) {
+        let diff_base = document.diff_base();
+        let text = document.as_text();
+        list.set_item_range(cx, 0, self.rows.len());
+        while let Some(item_id) = list.next_visible_item(cx) {
+            let Some(row) = self.rows.get(item_id) else {
+                continue
+            };
+            let side = |line: Option<usize>, text: Option<&Text>, changed_kind: f64| match (line, text) {
+                (Some(line), Some(text)) => (format!("{}", line + 1), text.as_lines()[line].clone(), if row.is_changed {changed_kind} else {0.0}),
+                _ => (String::new(), String::new(), 3.0),
+            };
+            let (old_number, old_text, old_kind) = side(row.old_line, diff_base.as_ref(), 1.0);
+            let (new_number, new_text, new_kind) = side(row.new_line, Some(&text), 2.0);
+            let item = list.item(cx, item_id, live_id!(Row)).unwrap().as_view();
+            item.apply_over(cx, live!{
+                left = {
+                    draw_bg: {kind: (old_kind)}
+                    number = {text: (old_number)}
+                    text = {text: (old_text)}
+                }
+                right = {
+                    draw_bg: {kind: (new_kind)}
+                    number = {text: (new_number)}
+                    text = {text: (new_text)}
+                }
+            });
+            item.draw_all(cx, &mut Scope::empty());
+        }
+    }
+}
+
+impl Widget for GitDiffView {
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk:Walk)->DrawStep{
+        let tab_id = scope.path.get(0);
+        let file_system = &scope.data.get::<AppData>().file_system;
+        let Some(document) = diff_document(file_system, tab_id) else {
+            self.rows.clear();
+            self.view.widget(id!(title)).set_text("Loading...");
+            return self.view.draw_walk(cx, scope, walk);
+        };
+        let line_count = document.as_text().as_lines().len();
+        // files that are not in the last commit are all new
+        let (hunks, old_line_count) = match &*document.diff_base() {
+            Some(diff_base) => (document.diff_hunks().to_vec(), diff_base.as_lines().len()),
+            None => (vec![Hunk {old: 0..0, new: 0..line_count}], 0),
+        };
+        self.update_rows(&hunks, old_line_count, line_count);
+        let path = file_system.file_node_path(file_system.tab_id_to_diff_file_node_id[&tab_id]);
+        let title = match hunks.len() {
+            0 => format!("{} has no changes since the last commit", path),
+            1 => format!("{}: 1 change since the last commit", path),
+            count => format!("{}: {} changes since the last commit", path, count),
+        };
+        self.view.widget(id!(title)).set_text(&title);
+        while let Some(step) = self.view.draw_walk(cx, scope, walk).step(){
+            if let Some(mut list) = step.as_portal_list().borrow_mut(){
+                if let Some(document) = diff_document(&scope.data.get::<AppData>().file_system, tab_id) {
+                    self.draw_rows(cx, &mut list, document);
+                }
+            }
+        }
+        DrawStep::done()
+    }
+
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
+        let list = self.view.portal_list(id!(list));
+        self.view.handle_event(cx, event, scope);
+        let tab_id = scope.path.get(0);
+        let file_system = &scope.data.get::<AppData>().file_system;
+        if let Event::Actions(actions) = event{
+            // clicking a line opens the file at that line
+            for (item_id, item) in list.items_with_actions(actions) {
+                if item.as_view().finger_down(actions).is_none() {
+                    continue
+                }
+                let Some(file_id) = file_system.tab_id_to_diff_file_node_id.get(&tab_id) else {
+                    continue
+                };
+                let line_index = self.rows[..(item_id + 1).min(self.rows.len())]
+                    .iter()
+                    .rev()
+                    .find_map(|row| row.new_line)
+                    .unwrap_or(0);
+                cx.action(AppAction::JumpTo(JumpTo{
+                    file_name: file_system.file_node_path(*file_id),
+                    start: Position{
+                        line_index,
+                        byte_index: 0,
+                    },
+                }));
+            }
+        }
+    }
+}
+
+fn diff_document(file_system: &FileSystem, tab_id: LiveId) -> Option<&Document> {
+    let file_id = file_system.tab_id_to_diff_file_node_id.get(&tab_id)?;
+    match file_system.open_documents.get(file_id)? {
+        OpenDoc::Document(document) => Some(document),
+        OpenDoc::Decorations(_) => None,
+    }
+}
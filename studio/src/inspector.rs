@@ -0,0 +1,163 @@
+use {
+    crate::{
+        app::AppData,
+        makepad_widgets::*,
+        makepad_platform::studio::{DrawSample, MemorySample, WidgetTreeNode},
+    },
+    std::fmt::Write,
+};
+
+live_design!{
+    import makepad_draw::shader::std::*;
+    import makepad_widgets::base::*;
+    import makepad_widgets::theme_desktop_dark::*;
+
+    Inspector = {{Inspector}}{
+        height: Fill,
+        width: Fill
+        flow: Down
+        show_bg: true
+        draw_bg: {color: #x28}
+        <View> {
+            height: Fit,
+            width: Fill
+            flow: Right
+            padding: 10
+            spacing: 20
+            refresh = <Button> {text: "Refresh widget tree"}
+            draw_stats = <Label> {draw_text: {color: #a, text_style: <THEME_FONT_CODE> {}}, text: ""}
+            memory_stats = <Label> {draw_text: {color: #a, text_style: <THEME_FONT_CODE> {}}, text: ""}
+        }
+        tree = <PortalList> {
+            drag_scrolling: false
+            height: Fill,
+            width: Fill
+            flow: Down
+            Node = <View> {
+                height: Fit,
+                width: Fill
+                flow: Right
+                spacing: 10
+                padding: {top: 1, bottom: 1}
+                name = <Label> {draw_text: {color: #c, text_style: <THEME_FONT_CODE> {}}}
+                class = <Label> {draw_text: {color: #8a8, text_style: <THEME_FONT_CODE> {}}}
+                rect = <Label> {draw_text: {color: #8, text_style: <THEME_FONT_CODE> {}}}
+                animator = <Label> {draw_text: {color: #aa8, text_style: <THEME_FONT_CODE> {}}}
+            }
+        }
+    }
+}
+
+// shows what the running app reported about its last frame: the draw calls per pass, the
+// memory held by textures, and its widget tree once that has been requested
+#[derive(Live, LiveHook, Widget)]
+struct Inspector{
+    #[deref] view:View,
+    // asks for new draw samples a while after the inspector was drawn, so that the app only
+    // samples its frames while the inspector is shown, and not every frame
+    #[rust] sample_timer: Timer,
+}
+
+impl Inspector {
+    fn draw_tree(&mut self, cx: &mut Cx2d, tree: &mut PortalList, nodes: &[WidgetTreeNode]) {
+        tree.set_item_range(cx, 0, nodes.len());
+        while let Some(item_id) = tree.next_visible_item(cx) {
+            let Some(node) = nodes.get(item_id) else {
+                continue
+            };
+            let rect = if node.width > 0.0 || node.height > 0.0 {
+                format!("{:.0},{:.0} {:.0}x{:.0}", node.x, node.y, node.width, node.height)
+            }
+            else {
+                String::new()
+            };
+            let mut animator = String::new();
+            for track in &node.animator {
+                if track.playing {
+                    let _ = write!(animator, "{}: {} ({:.0}%) ", track.track, track.state, track.progress * 100.0);
+                }
+                else {
+                    let _ = write!(animator, "{}: {} ", track.track, track.state);
+                }
+            }
+            let item = tree.item(cx, item_id, live_id!(Node)).unwrap().as_view();
+            item.apply_over(cx, live!{
+                padding: {left: (10.0 + node.depth as f64 * 16.0)}
+                name = {text: (&node.name)}
+                class = {text: (&node.class)}
+                rect = {text: (rect)}
+                animator = {text: (animator)}
+            });
+            item.draw_all(cx, &mut Scope::empty());
+        }
+    }
+}
+
+impl Widget for Inspector {
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk:Walk)->DrawStep{
+        let bm = &scope.data.get::<AppData>().build_manager;
+        let pss = bm.profile.values().next();
+        let draw_stats = match pss.and_then(|pss| pss.draw.as_ref()) {
+            Some(draw) => format_draw_sample(draw),
+            None => "No draw calls reported yet".to_string(),
+        };
+        let memory_stats = match pss.and_then(|pss| pss.memory.as_ref()) {
+            Some(memory) => format_memory_sample(memory),
+            None => String::new(),
+        };
+        self.view.widget(id!(draw_stats)).set_text(&draw_stats);
+        self.view.widget(id!(memory_stats)).set_text(&memory_stats);
+        if self.sample_timer.is_empty() {
+            self.sample_timer = cx.start_timeout(SAMPLE_INTERVAL);
+        }
+        while let Some(step) = self.view.draw_walk(cx, scope, walk).step(){
+            if let Some(mut tree) = step.as_portal_list().borrow_mut(){
+                let bm = &scope.data.get::<AppData>().build_manager;
+                let nodes = bm.profile.values().next().map(|pss| pss.widget_tree.as_slice()).unwrap_or(&[]);
+                self.draw_tree(cx, &mut tree, nodes);
+            }
+        }
+        DrawStep::done()
+    }
+
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
+        self.view.handle_event(cx, event, scope);
+        if self.sample_timer.is_event(event).is_some() {
+            self.sample_timer = Timer::empty();
+            scope.data.get_mut::<AppData>().build_manager.request_draw_samples();
+        }
+        if let Event::Actions(actions) = event{
+            if self.view.button(id!(refresh)).clicked(actions){
+                scope.data.get_mut::<AppData>().build_manager.request_widget_tree();
+            }
+        }
+    }
+}
+
+const SAMPLE_INTERVAL: f64 = 1.0;
+
+fn format_draw_sample(draw: &DrawSample) -> String {
+    let mut text = String::new();
+    for pass in &draw.passes {
+        let _ = writeln!(text, "{}: {} draw calls, {} instances in {} draw lists", pass.name, pass.draw_calls, pass.instances, pass.draw_lists);
+    }
+    text.trim_end().to_string()
+}
+
+fn format_memory_sample(memory: &MemorySample) -> String {
+    format!(
+        "{} textures: {}\nfont atlas: {}",
+        memory.texture_count,
+        format_bytes(memory.texture_bytes),
+        format_bytes(memory.font_atlas_bytes)
+    )
+}
+
+fn format_bytes(bytes: u64) -> String {
+    if bytes >= 1024 * 1024 {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+    else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
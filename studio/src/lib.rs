@@ -4,12 +4,16 @@ pub mod app;
 pub mod app_ui;
 pub mod build_manager;
 pub mod file_system;
+pub mod lsp_manager;
 pub mod studio_editor;
 pub mod studio_file_tree;
 pub mod log_list;
+pub mod search_list;
+pub mod git_diff_view;
 pub mod run_list;
 pub mod run_view;
 pub mod profiler;
+pub mod inspector;
 //pub use makepad_code_editor;
 
 pub use makepad_widgets::makepad_draw;
@@ -0,0 +1,87 @@
+use {
+    crate::{
+        makepad_platform::*,
+        lsp_manager::lsp_protocol::{encode_message, read_message, LspMessage},
+    },
+    std::{
+        io::{BufReader, Write},
+        path::Path,
+        process::{Child, Command, Stdio},
+        sync::mpsc::{self, Sender},
+        thread,
+    },
+};
+
+pub enum LspClientMessage {
+    Message(LspMessage),
+    Exited,
+}
+
+// A language server process that we talk to over its stdin and stdout. Messages coming from the
+// server are sent to the UI thread tagged with `server_id`.
+pub struct LspClient {
+    child: Child,
+    body_sender: Sender<String>,
+}
+
+impl LspClient {
+    pub fn start(
+        command: &str,
+        args: &[String],
+        current_dir: &Path,
+        server_id: usize,
+        msg_sender: ToUISender<(usize, LspClientMessage)>,
+    ) -> Result<Self, std::io::Error> {
+        let mut child = Command::new(command)
+            .args(args)
+            .current_dir(current_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let mut stdin = child.stdin.take().expect("stdin cannot be taken!");
+        let stdout = child.stdout.take().expect("stdout cannot be taken!");
+        let (body_sender, body_receiver) = mpsc::channel::<String>();
+
+        let _stdin_thread = thread::spawn(move || {
+            while let Ok(body) = body_receiver.recv() {
+                if stdin.write_all(encode_message(&body).as_bytes()).is_err()
+                    || stdin.flush().is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        let _stdout_thread = thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Ok(Some(body)) = read_message(&mut reader) {
+                match LspMessage::parse(&body) {
+                    Ok(message) => {
+                        if msg_sender.send((server_id, LspClientMessage::Message(message))).is_err() {
+                            return;
+                        }
+                    }
+                    Err(err) => {
+                        log!("Cannot parse language server message: {}", err);
+                    }
+                }
+            }
+            let _ = msg_sender.send((server_id, LspClientMessage::Exited));
+        });
+
+        Ok(Self { child, body_sender })
+    }
+
+    pub fn send(&self, body: String) {
+        let _ = self.body_sender.send(body);
+    }
+}
+
+impl Drop for LspClient {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,599 @@
+use {
+    crate::{
+        app::AppAction,
+        log_list::JumpTo,
+        lsp_manager::{
+            lsp_client::{LspClient, LspClientMessage},
+            lsp_protocol::*,
+        },
+        makepad_code_editor::{
+            code_editor::CompletionItem,
+            decoration::{Decoration, DecorationType},
+            inlays::InlineInlay,
+            text::{Edit, Position, Text},
+            Document,
+        },
+        makepad_micro_serde::*,
+        makepad_widgets::*,
+        makepad_widgets::file_tree::FileNodeId,
+    },
+    std::{
+        collections::HashMap,
+        fs,
+        path::{Path, PathBuf},
+        sync::mpsc::Receiver,
+    },
+};
+
+// The decoration id used for diagnostics, so that they can be replaced without touching the
+// decorations that come from the build.
+pub const LSP_DECORATION_ID: usize = 1;
+
+#[derive(Clone, Debug)]
+pub struct LspServerConfig {
+    pub extensions: Vec<String>,
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl LspServerConfig {
+    pub fn rust_analyzer() -> Self {
+        Self {
+            extensions: vec!["rs".to_string()],
+            command: "rust-analyzer".to_string(),
+            args: Vec::new(),
+        }
+    }
+
+    // Parses `ext,ext:command arg arg`, as given to studio with `--lsp=`.
+    pub fn parse(config: &str) -> Option<Self> {
+        let (extensions, command) = config.split_once(':')?;
+        let mut command = command.split_whitespace().map(|arg| arg.to_string());
+        Some(Self {
+            extensions: extensions
+                .split(',')
+                .map(|extension| extension.trim().to_string())
+                .collect(),
+            command: command.next()?,
+            args: command.collect(),
+        })
+    }
+}
+
+#[derive(DefaultNone, Debug, Clone)]
+pub enum LspAction {
+    Hover {
+        tab_id: LiveId,
+        position: Position,
+        text: String,
+    },
+    Completions {
+        tab_id: LiveId,
+        items: Vec<CompletionItem>,
+    },
+    None,
+}
+
+enum PendingRequest {
+    Initialize,
+    Hover {
+        tab_id: LiveId,
+        position: Position,
+    },
+    Definition,
+    Completion {
+        tab_id: LiveId,
+    },
+    InlayHints {
+        file_id: FileNodeId,
+        version: u64,
+    },
+}
+
+struct LspServer {
+    client: Option<LspClient>,
+    is_initialized: bool,
+    // Messages wait here until the server has answered the initialize request.
+    queued_bodies: Vec<String>,
+    position_encoding: PositionEncoding,
+    next_request_id: u64,
+    pending_requests: HashMap<u64, PendingRequest>,
+}
+
+struct SyncedDocument {
+    server_id: usize,
+    uri: String,
+    version: u64,
+    // The text as the server knows it, which edits are converted against.
+    text: Text,
+    document: Document,
+    edit_receiver: Receiver<Vec<Edit>>,
+}
+
+#[derive(Default)]
+pub struct LspManager {
+    root_path: PathBuf,
+    configs: Vec<LspServerConfig>,
+    servers: HashMap<usize, LspServer>,
+    documents: HashMap<FileNodeId, SyncedDocument>,
+    diagnostics: HashMap<String, Vec<LspDiagnostic>>,
+    recv_client_msg: ToUIReceiver<(usize, LspClientMessage)>,
+}
+
+impl LspManager {
+    // Language servers are opt-in, `--lsp` starts rust-analyzer for Rust files and each
+    // `--lsp=ext,ext:command arg arg` adds a server of your own. Without either, studio
+    // doesn't spawn any.
+    pub fn init(&mut self, root_path: &Path) {
+        self.root_path = root_path.canonicalize().unwrap_or(root_path.to_path_buf());
+        self.configs = std::env::args()
+            .filter_map(|arg| match arg.as_str() {
+                "--lsp" => Some(LspServerConfig::rust_analyzer()),
+                arg => arg.strip_prefix("--lsp=").and_then(LspServerConfig::parse),
+            })
+            .collect();
+    }
+
+    pub fn open_document(&mut self, file_id: FileNodeId, path: &str, document: &Document) {
+        let extension = Path::new(path).extension().and_then(|ext| ext.to_str()).unwrap_or("");
+        let Some(server_id) = self
+            .configs
+            .iter()
+            .position(|config| config.extensions.iter().any(|ext| ext == extension))
+        else {
+            return;
+        };
+        if !self.start_server(server_id) {
+            return;
+        }
+        let uri = path_to_uri(&self.root_path.join(path));
+        let text = document.as_text().clone();
+        self.send_notification(
+            server_id,
+            "textDocument/didOpen",
+            DidOpenTextDocumentParams {
+                textDocument: TextDocumentItem {
+                    uri: uri.clone(),
+                    languageId: language_id(extension).to_string(),
+                    version: 0,
+                    text: text.to_string(),
+                },
+            },
+        );
+        self.documents.insert(
+            file_id,
+            SyncedDocument {
+                server_id,
+                uri,
+                version: 0,
+                text,
+                document: document.clone(),
+                edit_receiver: document.observe_edits(),
+            },
+        );
+        self.apply_diagnostics(file_id);
+        self.request_inlay_hints(file_id);
+    }
+
+    pub fn request_hover(&mut self, tab_id: LiveId, file_id: FileNodeId, position: Position) {
+        self.send_position_request(
+            file_id,
+            "textDocument/hover",
+            position,
+            PendingRequest::Hover { tab_id, position },
+        );
+    }
+
+    pub fn request_definition(&mut self, file_id: FileNodeId, position: Position) {
+        self.send_position_request(
+            file_id,
+            "textDocument/definition",
+            position,
+            PendingRequest::Definition,
+        );
+    }
+
+    pub fn request_completion(&mut self, tab_id: LiveId, file_id: FileNodeId, position: Position) {
+        self.send_position_request(
+            file_id,
+            "textDocument/completion",
+            position,
+            PendingRequest::Completion { tab_id },
+        );
+    }
+
+    pub fn handle_event(&mut self, cx: &mut Cx, event: &Event) {
+        self.flush_edits();
+        if let Event::Signal = event {
+            while let Ok((server_id, message)) = self.recv_client_msg.try_recv() {
+                match message {
+                    LspClientMessage::Message(message) => {
+                        self.handle_message(cx, server_id, message)
+                    }
+                    LspClientMessage::Exited => {
+                        log!("Language server {} exited", self.configs[server_id].command);
+                        if let Some(server) = self.servers.get_mut(&server_id) {
+                            server.client = None;
+                        }
+                        self.documents.retain(|_, document| document.server_id != server_id);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn shutdown(&mut self) {
+        for server_id in self.servers.keys().copied().collect::<Vec<_>>() {
+            self.send_notification(server_id, "exit", NoParams);
+        }
+        self.servers.clear();
+    }
+
+    // Servers are started once, when the first document they handle is opened. A server that
+    // failed to start or exited stays around without a client, so that we don't retry forever.
+    fn start_server(&mut self, server_id: usize) -> bool {
+        if let Some(server) = self.servers.get(&server_id) {
+            return server.client.is_some();
+        }
+        let config = &self.configs[server_id];
+        let client = match LspClient::start(
+            &config.command,
+            &config.args,
+            &self.root_path,
+            server_id,
+            self.recv_client_msg.sender(),
+        ) {
+            Ok(client) => Some(client),
+            Err(err) => {
+                log!("Cannot start language server {}: {}", config.command, err);
+                None
+            }
+        };
+        let is_started = client.is_some();
+        let mut server = LspServer {
+            client,
+            is_initialized: false,
+            queued_bodies: Vec::new(),
+            position_encoding: PositionEncoding::default(),
+            next_request_id: 0,
+            pending_requests: HashMap::new(),
+        };
+        server.pending_requests.insert(0, PendingRequest::Initialize);
+        server.next_request_id = 1;
+        if let Some(client) = &server.client {
+            client.send(
+                LspRequest::new(0, "initialize", InitializeParams::new(&self.root_path))
+                    .serialize_json(),
+            );
+        }
+        self.servers.insert(server_id, server);
+        is_started
+    }
+
+    fn send_body(&mut self, server_id: usize, body: String) {
+        let Some(server) = self.servers.get_mut(&server_id) else {
+            return;
+        };
+        if let Some(client) = &server.client {
+            if server.is_initialized {
+                client.send(body);
+            } else {
+                server.queued_bodies.push(body);
+            }
+        }
+    }
+
+    fn send_notification<T: SerJson>(&mut self, server_id: usize, method: &str, params: T) {
+        self.send_body(server_id, LspNotification::new(method, params).serialize_json());
+    }
+
+    fn send_request<T: SerJson>(
+        &mut self,
+        server_id: usize,
+        method: &str,
+        params: T,
+        pending_request: PendingRequest,
+    ) {
+        let Some(server) = self.servers.get_mut(&server_id) else {
+            return;
+        };
+        let id = server.next_request_id;
+        server.next_request_id += 1;
+        server.pending_requests.insert(id, pending_request);
+        self.send_body(server_id, LspRequest::new(id, method, params).serialize_json());
+    }
+
+    fn send_position_request(
+        &mut self,
+        file_id: FileNodeId,
+        method: &str,
+        position: Position,
+        pending_request: PendingRequest,
+    ) {
+        // The server has to see the latest text before it can answer questions about it.
+        self.flush_edits();
+        let Some(document) = self.documents.get(&file_id) else {
+            return;
+        };
+        let Some(server) = self.servers.get(&document.server_id) else {
+            return;
+        };
+        let params = TextDocumentPositionParams {
+            textDocument: TextDocumentIdentifier {
+                uri: document.uri.clone(),
+            },
+            position: server
+                .position_encoding
+                .to_lsp_position(&document.text, position),
+        };
+        let server_id = document.server_id;
+        self.send_request(server_id, method, params, pending_request);
+    }
+
+    fn request_inlay_hints(&mut self, file_id: FileNodeId) {
+        let Some(document) = self.documents.get(&file_id) else {
+            return;
+        };
+        let Some(server) = self.servers.get(&document.server_id) else {
+            return;
+        };
+        let lines = document.text.as_lines();
+        let end = Position {
+            line_index: lines.len() - 1,
+            byte_index: lines[lines.len() - 1].len(),
+        };
+        let params = InlayHintParams {
+            textDocument: TextDocumentIdentifier {
+                uri: document.uri.clone(),
+            },
+            range: LspRange {
+                start: LspPosition::default(),
+                end: server.position_encoding.to_lsp_position(&document.text, end),
+            },
+        };
+        let pending_request = PendingRequest::InlayHints {
+            file_id,
+            version: document.version,
+        };
+        let server_id = document.server_id;
+        self.send_request(server_id, "textDocument/inlayHint", params, pending_request);
+    }
+
+    // Sends the edits made to each document since the last flush as one incremental change.
+    fn flush_edits(&mut self) {
+        let mut changed_file_ids = Vec::new();
+        let mut notifications = Vec::new();
+        for (file_id, document) in &mut self.documents {
+            let Some(server) = self.servers.get(&document.server_id) else {
+                continue;
+            };
+            let mut content_changes = Vec::new();
+            while let Ok(edits) = document.edit_receiver.try_recv() {
+                for edit in edits {
+                    content_changes.push(
+                        server
+                            .position_encoding
+                            .to_lsp_change(&document.text, &edit.change),
+                    );
+                    document.text.apply_change(edit.change);
+                }
+            }
+            if content_changes.is_empty() {
+                continue;
+            }
+            document.version += 1;
+            changed_file_ids.push(*file_id);
+            notifications.push((
+                document.server_id,
+                DidChangeTextDocumentParams {
+                    textDocument: VersionedTextDocumentIdentifier {
+                        uri: document.uri.clone(),
+                        version: document.version,
+                    },
+                    contentChanges: content_changes,
+                },
+            ));
+        }
+        for (server_id, params) in notifications {
+            self.send_notification(server_id, "textDocument/didChange", params);
+        }
+        for file_id in changed_file_ids {
+            self.request_inlay_hints(file_id);
+        }
+    }
+
+    fn handle_message(&mut self, cx: &mut Cx, server_id: usize, message: LspMessage) {
+        match message {
+            LspMessage::Response { id, result } => {
+                let Some(pending_request) = self
+                    .servers
+                    .get_mut(&server_id)
+                    .and_then(|server| server.pending_requests.remove(&id))
+                else {
+                    return;
+                };
+                match result {
+                    Ok(result) => self.handle_response(cx, server_id, pending_request, &result),
+                    Err(err) => log!("Language server error: {}", err),
+                }
+            }
+            LspMessage::Notification { method, params } => {
+                if method == "textDocument/publishDiagnostics" {
+                    if let Some((uri, diagnostics)) = parse_diagnostics(&params) {
+                        self.diagnostics.insert(uri.clone(), diagnostics);
+                        let file_ids = self
+                            .documents
+                            .iter()
+                            .filter(|(_, document)| document.uri == uri)
+                            .map(|(file_id, _)| *file_id)
+                            .collect::<Vec<_>>();
+                        for file_id in file_ids {
+                            self.apply_diagnostics(file_id);
+                            cx.action(AppAction::RedrawFile(file_id));
+                        }
+                    }
+                }
+            }
+            LspMessage::Request { id, method, params } => {
+                // We don't provide anything the server may ask for, so we answer every request
+                // with an empty result.
+                let result = if method == "workspace/configuration" {
+                    let item_count = match json_get(&params, "items") {
+                        Some(JsonValue::Array(items)) => items.len(),
+                        _ => 0,
+                    };
+                    format!("[{}]", vec!["null"; item_count].join(","))
+                } else {
+                    "null".to_string()
+                };
+                if let Some(client) = self.servers.get(&server_id).and_then(|server| server.client.as_ref()) {
+                    client.send(LspResponse::new(id, RawJson(result)).serialize_json());
+                }
+            }
+        }
+    }
+
+    fn handle_response(
+        &mut self,
+        cx: &mut Cx,
+        server_id: usize,
+        pending_request: PendingRequest,
+        result: &JsonValue,
+    ) {
+        let Some(server) = self.servers.get_mut(&server_id) else {
+            return;
+        };
+        let position_encoding = server.position_encoding;
+        match pending_request {
+            PendingRequest::Initialize => {
+                server.position_encoding = json_get(result, "capabilities")
+                    .map(PositionEncoding::from_capabilities)
+                    .unwrap_or_default();
+                server.is_initialized = true;
+                let queued_bodies = std::mem::take(&mut server.queued_bodies);
+                if let Some(client) = &server.client {
+                    client.send(LspNotification::new("initialized", NoParams).serialize_json());
+                    for body in queued_bodies {
+                        client.send(body);
+                    }
+                }
+            }
+            PendingRequest::Hover { tab_id, position } => {
+                cx.action(LspAction::Hover {
+                    tab_id,
+                    position,
+                    text: parse_hover(result),
+                });
+            }
+            PendingRequest::Definition => {
+                if let Some(jump_to) = parse_locations(result)
+                    .into_iter()
+                    .find_map(|location| self.location_to_jump(position_encoding, &location))
+                {
+                    cx.action(AppAction::JumpTo(jump_to));
+                }
+            }
+            PendingRequest::Completion { tab_id } => {
+                cx.action(LspAction::Completions {
+                    tab_id,
+                    items: parse_completions(result)
+                        .into_iter()
+                        .map(|item| CompletionItem {
+                            label: item.label,
+                            detail: item.detail,
+                            insert_text: item.insert_text,
+                        })
+                        .collect(),
+                });
+            }
+            PendingRequest::InlayHints { file_id, version } => {
+                let Some(document) = self.documents.get(&file_id) else {
+                    return;
+                };
+                // Hints for an older version of the text would end up in the wrong places.
+                if document.version != version {
+                    return;
+                }
+                let mut inlays = parse_inlay_hints(result)
+                    .into_iter()
+                    .map(|hint| {
+                        (
+                            position_encoding.to_position(&document.text, hint.position),
+                            InlineInlay::Text(hint.label),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                inlays.sort_by_key(|(position, _)| *position);
+                document.document.set_inline_inlays(inlays);
+                cx.action(AppAction::RedrawFile(file_id));
+            }
+        }
+    }
+
+    // Definitions inside the project become a jump to a file the file tree knows about.
+    fn location_to_jump(
+        &self,
+        position_encoding: PositionEncoding,
+        location: &LspLocation,
+    ) -> Option<JumpTo> {
+        let path = uri_to_path(&location.uri)?;
+        let file_name = path
+            .strip_prefix(&self.root_path)
+            .ok()?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let start = match self.documents.values().find(|document| document.uri == location.uri) {
+            Some(document) => position_encoding.to_position(&document.text, location.range.start),
+            None => position_encoding.to_position(
+                &Text::from(fs::read_to_string(&path).ok()?),
+                location.range.start,
+            ),
+        };
+        Some(JumpTo { file_name, start })
+    }
+
+    fn apply_diagnostics(&mut self, file_id: FileNodeId) {
+        let Some(document) = self.documents.get(&file_id) else {
+            return;
+        };
+        let Some(server) = self.servers.get(&document.server_id) else {
+            return;
+        };
+        let text = document.document.as_text();
+        let mut decorations = self
+            .diagnostics
+            .get(&document.uri)
+            .into_iter()
+            .flatten()
+            .filter_map(|diagnostic| {
+                let ty = match diagnostic.severity {
+                    1 => DecorationType::Error,
+                    2 | 3 => DecorationType::Warning,
+                    _ => return None,
+                };
+                let start = server
+                    .position_encoding
+                    .to_position(&text, diagnostic.range.start);
+                let end = server
+                    .position_encoding
+                    .to_position(&text, diagnostic.range.end);
+                Some(Decoration::new(LSP_DECORATION_ID, start, end, ty))
+            })
+            .collect::<Vec<_>>();
+        drop(text);
+        decorations.sort_by_key(|decoration| decoration.start());
+        document
+            .document
+            .replace_decorations_with_id(LSP_DECORATION_ID, decorations);
+    }
+}
+
+fn language_id(extension: &str) -> &str {
+    match extension {
+        "rs" => "rust",
+        "js" => "javascript",
+        "ts" => "typescript",
+        "py" => "python",
+        extension => extension,
+    }
+}
@@ -0,0 +1,623 @@
+use {
+    crate::{
+        makepad_micro_serde::*,
+        makepad_code_editor::text::{Change, Position, Text},
+    },
+    std::{
+        io::{self, BufRead},
+        path::{Path, PathBuf},
+    },
+};
+
+// Language Server Protocol messages, as far as studio uses them. Outgoing messages are plain
+// SerJson structs, incoming messages are parsed as `JsonValue` because servers send many fields
+// we don't care about.
+
+pub fn encode_message(body: &str) -> String {
+    format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+// Reads one `Content-Length` framed message, or returns `None` once the stream is closed.
+pub fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let Some(content_length) = content_length else {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header"));
+    };
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+// A piece of JSON that is written out as is.
+pub struct RawJson(pub String);
+
+impl SerJson for RawJson {
+    fn ser_json(&self, _d: usize, s: &mut SerJsonState) {
+        s.out.push_str(&self.0);
+    }
+}
+
+#[derive(SerJson)]
+pub struct LspRequest<T> where T: SerJson {
+    pub jsonrpc: String,
+    pub id: u64,
+    pub method: String,
+    pub params: T,
+}
+
+impl<T: SerJson> LspRequest<T> {
+    pub fn new(id: u64, method: &str, params: T) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            method: method.to_string(),
+            params,
+        }
+    }
+}
+
+#[derive(SerJson)]
+pub struct LspNotification<T> where T: SerJson {
+    pub jsonrpc: String,
+    pub method: String,
+    pub params: T,
+}
+
+impl<T: SerJson> LspNotification<T> {
+    pub fn new(method: &str, params: T) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+        }
+    }
+}
+
+#[derive(SerJson)]
+pub struct LspResponse {
+    pub jsonrpc: String,
+    pub id: RawJson,
+    pub result: RawJson,
+}
+
+impl LspResponse {
+    pub fn new(id: RawJson, result: RawJson) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result,
+        }
+    }
+}
+
+pub struct NoParams;
+
+impl SerJson for NoParams {
+    fn ser_json(&self, _d: usize, s: &mut SerJsonState) {
+        s.out.push_str("{}");
+    }
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct InitializeParams {
+    pub processId: u32,
+    pub rootUri: String,
+    pub capabilities: ClientCapabilities,
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct ClientCapabilities {
+    pub general: GeneralClientCapabilities,
+    pub textDocument: TextDocumentClientCapabilities,
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct GeneralClientCapabilities {
+    pub positionEncodings: Vec<String>,
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct TextDocumentClientCapabilities {
+    pub hover: HoverClientCapabilities,
+    pub completion: CompletionClientCapabilities,
+    pub publishDiagnostics: PublishDiagnosticsClientCapabilities,
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct HoverClientCapabilities {
+    pub contentFormat: Vec<String>,
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct CompletionClientCapabilities {
+    pub completionItem: CompletionItemClientCapabilities,
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct CompletionItemClientCapabilities {
+    pub snippetSupport: bool,
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct PublishDiagnosticsClientCapabilities {
+    pub relatedInformation: bool,
+}
+
+impl InitializeParams {
+    pub fn new(root_path: &Path) -> Self {
+        Self {
+            processId: std::process::id(),
+            rootUri: path_to_uri(root_path),
+            capabilities: ClientCapabilities {
+                general: GeneralClientCapabilities {
+                    positionEncodings: vec!["utf-8".to_string(), "utf-16".to_string()],
+                },
+                textDocument: TextDocumentClientCapabilities {
+                    hover: HoverClientCapabilities {
+                        contentFormat: vec!["plaintext".to_string()],
+                    },
+                    completion: CompletionClientCapabilities {
+                        completionItem: CompletionItemClientCapabilities {
+                            snippetSupport: false,
+                        },
+                    },
+                    publishDiagnostics: PublishDiagnosticsClientCapabilities {
+                        relatedInformation: false,
+                    },
+                },
+            },
+        }
+    }
+}
+
+#[derive(SerJson)]
+pub struct TextDocumentIdentifier {
+    pub uri: String,
+}
+
+#[derive(SerJson)]
+pub struct VersionedTextDocumentIdentifier {
+    pub uri: String,
+    pub version: u64,
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct TextDocumentItem {
+    pub uri: String,
+    pub languageId: String,
+    pub version: u64,
+    pub text: String,
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct DidOpenTextDocumentParams {
+    pub textDocument: TextDocumentItem,
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct DidChangeTextDocumentParams {
+    pub textDocument: VersionedTextDocumentIdentifier,
+    pub contentChanges: Vec<TextDocumentContentChangeEvent>,
+}
+
+#[derive(SerJson)]
+pub struct TextDocumentContentChangeEvent {
+    pub range: LspRange,
+    pub text: String,
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct DidCloseTextDocumentParams {
+    pub textDocument: TextDocumentIdentifier,
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct TextDocumentPositionParams {
+    pub textDocument: TextDocumentIdentifier,
+    pub position: LspPosition,
+}
+
+#[allow(non_snake_case)]
+#[derive(SerJson)]
+pub struct InlayHintParams {
+    pub textDocument: TextDocumentIdentifier,
+    pub range: LspRange,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, SerJson)]
+pub struct LspPosition {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, SerJson)]
+pub struct LspRange {
+    pub start: LspPosition,
+    pub end: LspPosition,
+}
+
+impl LspPosition {
+    fn from_json(value: &JsonValue) -> Option<Self> {
+        Some(Self {
+            line: json_u64(json_get(value, "line")?)? as u32,
+            character: json_u64(json_get(value, "character")?)? as u32,
+        })
+    }
+}
+
+impl LspRange {
+    fn from_json(value: &JsonValue) -> Option<Self> {
+        Some(Self {
+            start: LspPosition::from_json(json_get(value, "start")?)?,
+            end: LspPosition::from_json(json_get(value, "end")?)?,
+        })
+    }
+}
+
+// The unit in which a server counts characters within a line.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum PositionEncoding {
+    Utf8,
+    #[default]
+    Utf16,
+}
+
+impl PositionEncoding {
+    pub fn from_capabilities(capabilities: &JsonValue) -> Self {
+        match json_get(capabilities, "positionEncoding").and_then(json_str) {
+            Some("utf-8") => Self::Utf8,
+            _ => Self::Utf16,
+        }
+    }
+
+    pub fn to_lsp_position(self, text: &Text, position: Position) -> LspPosition {
+        let lines = text.as_lines();
+        let line_index = position.line_index.min(lines.len() - 1);
+        let line = &lines[line_index];
+        let byte_index = position.byte_index.min(line.len());
+        let character = match self {
+            Self::Utf8 => byte_index,
+            Self::Utf16 => line[..byte_index].encode_utf16().count(),
+        };
+        LspPosition {
+            line: line_index as u32,
+            character: character as u32,
+        }
+    }
+
+    // Positions past the end of a line or the text are clamped, since a server may still be
+    // looking at an older version of the text.
+    pub fn to_position(self, text: &Text, position: LspPosition) -> Position {
+        let lines = text.as_lines();
+        let line_index = (position.line as usize).min(lines.len() - 1);
+        let line = &lines[line_index];
+        let character = position.character as usize;
+        let byte_index = match self {
+            Self::Utf8 => {
+                let mut byte_index = character.min(line.len());
+                while !line.is_char_boundary(byte_index) {
+                    byte_index -= 1;
+                }
+                byte_index
+            }
+            Self::Utf16 => {
+                let mut utf16_index = 0;
+                line.char_indices()
+                    .find(|(_, char)| {
+                        utf16_index += char.len_utf16();
+                        utf16_index > character
+                    })
+                    .map_or(line.len(), |(byte_index, _)| byte_index)
+            }
+        };
+        Position {
+            line_index,
+            byte_index,
+        }
+    }
+
+    pub fn to_lsp_change(self, text: &Text, change: &Change) -> TextDocumentContentChangeEvent {
+        match change {
+            Change::Insert(position, inserted) => {
+                let position = self.to_lsp_position(text, *position);
+                TextDocumentContentChangeEvent {
+                    range: LspRange {
+                        start: position,
+                        end: position,
+                    },
+                    text: inserted.to_string(),
+                }
+            }
+            Change::Delete(start, length) => TextDocumentContentChangeEvent {
+                range: LspRange {
+                    start: self.to_lsp_position(text, *start),
+                    end: self.to_lsp_position(text, *start + *length),
+                },
+                text: String::new(),
+            },
+        }
+    }
+}
+
+pub enum LspMessage {
+    Response {
+        id: u64,
+        result: Result<JsonValue, String>,
+    },
+    Request {
+        id: RawJson,
+        method: String,
+        params: JsonValue,
+    },
+    Notification {
+        method: String,
+        params: JsonValue,
+    },
+}
+
+impl LspMessage {
+    pub fn parse(body: &str) -> Result<Self, String> {
+        let value = JsonValue::deserialize_json(body).map_err(|err| format!("{:?}", err))?;
+        let method = json_get(&value, "method").and_then(json_str);
+        let id = json_get(&value, "id");
+        let params = json_get(&value, "params").cloned().unwrap_or(JsonValue::Null);
+        match (method, id) {
+            (Some(method), Some(id)) => Ok(Self::Request {
+                id: RawJson(match id {
+                    JsonValue::String(id) => id.serialize_json(),
+                    id => json_u64(id).unwrap_or(0).to_string(),
+                }),
+                method: method.to_string(),
+                params,
+            }),
+            (Some(method), None) => Ok(Self::Notification {
+                method: method.to_string(),
+                params,
+            }),
+            (None, Some(id)) => Ok(Self::Response {
+                id: json_u64(id).ok_or_else(|| "response with a non numeric id".to_string())?,
+                result: match json_get(&value, "error") {
+                    Some(error) => Err(json_get(error, "message")
+                        .and_then(json_str)
+                        .unwrap_or("unknown error")
+                        .to_string()),
+                    None => Ok(json_get(&value, "result").cloned().unwrap_or(JsonValue::Null)),
+                },
+            }),
+            (None, None) => Err("message without method or id".to_string()),
+        }
+    }
+}
+
+pub fn json_get<'a>(value: &'a JsonValue, key: &str) -> Option<&'a JsonValue> {
+    match value {
+        JsonValue::Object(object) => object.get(key),
+        _ => None,
+    }
+}
+
+pub fn json_str(value: &JsonValue) -> Option<&str> {
+    match value {
+        JsonValue::String(string) => Some(string),
+        _ => None,
+    }
+}
+
+pub fn json_u64(value: &JsonValue) -> Option<u64> {
+    match value {
+        JsonValue::U64(value) => Some(*value),
+        JsonValue::I64(value) => u64::try_from(*value).ok(),
+        _ => None,
+    }
+}
+
+fn json_array(value: &JsonValue) -> &[JsonValue] {
+    match value {
+        JsonValue::Array(array) => array,
+        _ => &[],
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LspDiagnostic {
+    pub range: LspRange,
+    pub severity: u64,
+    pub message: String,
+}
+
+pub fn parse_diagnostics(params: &JsonValue) -> Option<(String, Vec<LspDiagnostic>)> {
+    let uri = json_str(json_get(params, "uri")?)?.to_string();
+    let diagnostics = json_array(json_get(params, "diagnostics")?)
+        .iter()
+        .filter_map(|diagnostic| {
+            Some(LspDiagnostic {
+                range: LspRange::from_json(json_get(diagnostic, "range")?)?,
+                // A missing severity is up to the client to interpret, we treat it as an error.
+                severity: json_get(diagnostic, "severity").and_then(json_u64).unwrap_or(1),
+                message: json_get(diagnostic, "message")
+                    .and_then(json_str)
+                    .unwrap_or("")
+                    .to_string(),
+            })
+        })
+        .collect();
+    Some((uri, diagnostics))
+}
+
+// Hover contents are either markup content, a marked string, or a list of marked strings.
+pub fn parse_hover(result: &JsonValue) -> String {
+    fn marked_string(value: &JsonValue) -> Option<&str> {
+        json_str(value).or_else(|| json_get(value, "value").and_then(json_str))
+    }
+
+    let Some(contents) = json_get(result, "contents") else {
+        return String::new();
+    };
+    match contents {
+        JsonValue::Array(array) => array
+            .iter()
+            .filter_map(marked_string)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        contents => marked_string(contents).unwrap_or("").to_string(),
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LspLocation {
+    pub uri: String,
+    pub range: LspRange,
+}
+
+// A definition result is a location, a list of locations, or a list of location links.
+pub fn parse_locations(result: &JsonValue) -> Vec<LspLocation> {
+    fn location(value: &JsonValue) -> Option<LspLocation> {
+        if let Some(uri) = json_get(value, "targetUri") {
+            return Some(LspLocation {
+                uri: json_str(uri)?.to_string(),
+                range: LspRange::from_json(json_get(value, "targetSelectionRange")?)?,
+            });
+        }
+        Some(LspLocation {
+            uri: json_str(json_get(value, "uri")?)?.to_string(),
+            range: LspRange::from_json(json_get(value, "range")?)?,
+        })
+    }
+
+    match result {
+        JsonValue::Array(array) => array.iter().filter_map(location).collect(),
+        result => location(result).into_iter().collect(),
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LspCompletionItem {
+    pub label: String,
+    pub detail: String,
+    pub insert_text: String,
+}
+
+// A completion result is either a list of items or a completion list that contains them.
+pub fn parse_completions(result: &JsonValue) -> Vec<LspCompletionItem> {
+    let items = json_get(result, "items").unwrap_or(result);
+    json_array(items)
+        .iter()
+        .filter_map(|item| {
+            let label = json_str(json_get(item, "label")?)?.to_string();
+            let insert_text = json_get(item, "textEdit")
+                .and_then(|text_edit| json_get(text_edit, "newText"))
+                .or_else(|| json_get(item, "insertText"))
+                .and_then(json_str)
+                .unwrap_or(&label)
+                .to_string();
+            Some(LspCompletionItem {
+                detail: json_get(item, "detail")
+                    .and_then(json_str)
+                    .unwrap_or("")
+                    .to_string(),
+                label,
+                insert_text,
+            })
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct LspInlayHint {
+    pub position: LspPosition,
+    pub label: String,
+}
+
+pub fn parse_inlay_hints(result: &JsonValue) -> Vec<LspInlayHint> {
+    json_array(result)
+        .iter()
+        .filter_map(|hint| {
+            let mut label = match json_get(hint, "label")? {
+                JsonValue::Array(parts) => parts
+                    .iter()
+                    .filter_map(|part| json_get(part, "value").and_then(json_str))
+                    .collect::<String>(),
+                label => json_str(label)?.to_string(),
+            };
+            if matches!(json_get(hint, "paddingLeft"), Some(JsonValue::Bool(true))) {
+                label.insert(0, ' ');
+            }
+            if matches!(json_get(hint, "paddingRight"), Some(JsonValue::Bool(true))) {
+                label.push(' ');
+            }
+            Some(LspInlayHint {
+                position: LspPosition::from_json(json_get(hint, "position")?)?,
+                label,
+            })
+        })
+        .collect()
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            // Drive letters keep their colon.
+            b':' if uri.len() == "file:///".len() + 1 => uri.push(':'),
+            byte => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::new();
+    let mut iter = path.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+    // `/C:/dir` on Windows.
+    if path.as_bytes().get(2) == Some(&b':') {
+        return Some(PathBuf::from(&path[1..]));
+    }
+    Some(PathBuf::from(path))
+}
@@ -0,0 +1,242 @@
+use {
+    crate::{
+        app::{AppAction, AppData},
+        file_system::file_system::{FileSystem, SearchStatus},
+        log_list::JumpTo,
+        makepad_widgets::*,
+        makepad_code_editor::text::{Position},
+    },
+};
+
+live_design!{
+    import makepad_draw::shader::std::*;
+    import makepad_widgets::base::*;
+    import makepad_widgets::theme_desktop_dark::*;
+
+    SearchItem = <RectView> {
+        height: Fit,
+        width: Fill
+        padding: {top: 4, bottom: 4, left: 10, right: 10}
+        cursor: Hand
+
+        draw_bg: {
+            instance is_even: 0.0
+            instance hover: 0.0
+            fn pixel(self) -> vec4 {
+                return mix(
+                    mix(
+                        THEME_COLOR_BG_EDITOR,
+                        THEME_COLOR_BG_ODD,
+                        self.is_even
+                    ),
+                    THEME_COLOR_BG_SELECTED,
+                    self.hover * 0.5
+                );
+            }
+        }
+        animator: {
+            hover = {
+                default: off
+                off = {
+                    from: {all: Forward {duration: 0.1}}
+                    apply: {
+                        draw_bg: {hover: 0.0}
+                    }
+                }
+                on = {
+                    from: {all: Snap}
+                    apply: {
+                        draw_bg: {hover: 1.0}
+                    },
+                }
+            }
+        }
+    }
+
+    SearchList = {{SearchList}}{
+        height: Fill,
+        width: Fill
+        flow: Down
+        show_bg: true
+        draw_bg: {color: #x28}
+        <View> {
+            height: Fit,
+            width: Fill
+            flow: Down
+            padding: 10
+            spacing: 5
+            pattern = <TextInput> {
+                draw_bg: {
+                    fn pixel(self) -> vec4 {
+                        return #x00000044
+                    }
+                }
+                width: Fill,
+                empty_message: "Search"
+            }
+            globs = <TextInput> {
+                draw_bg: {
+                    fn pixel(self) -> vec4 {
+                        return #x00000044
+                    }
+                }
+                width: Fill,
+                empty_message: "Files to search, e.g. *.rs, !tests/**"
+            }
+            <View> {
+                height: Fit,
+                width: Fill
+                flow: Right
+                spacing: 10
+                case = <CheckBox> {text: "Match case"}
+                regex = <CheckBox> {text: "Regex"}
+            }
+            status = <Label> {draw_text: {color: #8}, text: ""}
+        }
+        list = <PortalList> {
+            drag_scrolling: false
+            height: Fill,
+            width: Fill
+            flow: Down
+            File = <SearchItem> {
+                path = <Label> {width: Fill, margin: 0, padding: 0}
+            }
+            Match = <SearchItem> {
+                padding: {left: 20}
+                line_number = <Label> {draw_text: {color: #8}, width: 40, margin: 0, padding: 0}
+                line = <Label> {width: Fill, margin: 0, padding: 0, draw_text: {text_style: <THEME_FONT_CODE> {}}}
+            }
+        }
+    }
+}
+
+// A row of the list, which is either the path of a file or one of its matches. Both refer to
+// a result, for files this is their first match.
+enum SearchRow {
+    File(usize),
+    Match(usize),
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct SearchList{
+    #[deref] view: View,
+    #[rust] case: bool,
+    #[rust] regex: bool,
+    #[rust] rows: Vec<SearchRow>,
+}
+
+impl SearchList{
+    fn start_search(&mut self, file_system: &mut FileSystem) {
+        let pattern = self.view.text_input(id!(pattern)).text();
+        let globs = self.view.text_input(id!(globs)).text()
+            .split(',')
+            .map(|glob| glob.trim().to_string())
+            .filter(|glob| !glob.is_empty())
+            .collect();
+        file_system.search(pattern, self.regex, self.case, globs);
+    }
+
+    fn update_rows(&mut self, file_system: &FileSystem) {
+        self.rows.clear();
+        let mut last_path = None;
+        for (index, result) in file_system.search_results.iter().enumerate() {
+            if last_path != Some(&result.path) {
+                self.rows.push(SearchRow::File(index));
+                last_path = Some(&result.path);
+            }
+            self.rows.push(SearchRow::Match(index));
+        }
+        let file_count = self.rows.len() - file_system.search_results.len();
+        let status = match &file_system.search_status {
+            SearchStatus::Idle => String::new(),
+            SearchStatus::Running => format!("Searching... {} results", file_system.search_results.len()),
+            SearchStatus::Finished => format!("{} results in {} files", file_system.search_results.len(), file_count),
+            SearchStatus::Error(err) => err.clone(),
+        };
+        self.view.widget(id!(status)).set_text(&status);
+    }
+
+    fn draw_results(&mut self, cx: &mut Cx2d, list: &mut PortalList, file_system: &FileSystem) {
+        list.set_item_range(cx, 0, self.rows.len());
+        while let Some(item_id) = list.next_visible_item(cx) {
+            let is_even = if item_id & 1 == 0 {1.0} else {0.0};
+            match self.rows.get(item_id) {
+                Some(SearchRow::File(index)) => {
+                    let result = &file_system.search_results[*index];
+                    let item = list.item(cx, item_id, live_id!(File)).unwrap().as_view();
+                    item.apply_over(cx, live!{
+                        path = {text: (&result.path)}
+                        draw_bg: {is_even: (is_even)}
+                    });
+                    item.draw_all(cx, &mut Scope::empty());
+                }
+                Some(SearchRow::Match(index)) => {
+                    let result = &file_system.search_results[*index];
+                    let item = list.item(cx, item_id, live_id!(Match)).unwrap().as_view();
+                    item.apply_over(cx, live!{
+                        line_number = {text: (format!("{}", result.line_index + 1))}
+                        line = {text: (result.line.trim_start())}
+                        draw_bg: {is_even: (is_even)}
+                    });
+                    item.draw_all(cx, &mut Scope::empty());
+                }
+                None => ()
+            }
+        }
+    }
+}
+
+impl Widget for SearchList {
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk:Walk)->DrawStep{
+        let file_system = &scope.data.get::<AppData>().file_system;
+        self.update_rows(file_system);
+        while let Some(step) = self.view.draw_walk(cx, scope, walk).step(){
+            if let Some(mut list) = step.as_portal_list().borrow_mut(){
+                self.draw_results(cx, &mut *list, &scope.data.get::<AppData>().file_system)
+            }
+        }
+        DrawStep::done()
+    }
+
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
+        let search_list = self.view.portal_list(id!(list));
+        self.view.handle_event(cx, event, scope);
+        let file_system = &mut scope.data.get_mut::<AppData>().file_system;
+        if let Event::Actions(actions) = event{
+            let mut search_changed = false;
+            if self.view.text_input(id!(pattern)).changed(&actions).is_some()
+                || self.view.text_input(id!(globs)).changed(&actions).is_some() {
+                search_changed = true;
+            }
+            if let Some(case) = self.view.check_box(id!(case)).changed(&actions) {
+                self.case = case;
+                search_changed = true;
+            }
+            if let Some(regex) = self.view.check_box(id!(regex)).changed(&actions) {
+                self.regex = regex;
+                search_changed = true;
+            }
+            if search_changed {
+                self.start_search(file_system);
+                self.view.redraw(cx);
+            }
+            for (item_id, item) in search_list.items_with_actions(&actions) {
+                if item.as_view().finger_down(&actions).is_some() {
+                    let (SearchRow::File(index) | SearchRow::Match(index)) = match self.rows.get(item_id) {
+                        Some(row) => row,
+                        None => continue
+                    };
+                    if let Some(result) = file_system.search_results.get(*index) {
+                        cx.action(AppAction::JumpTo(JumpTo{
+                            file_name: result.path.clone(),
+                            start: Position{
+                                line_index: result.line_index,
+                                byte_index: result.start_byte,
+                            },
+                        }));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -29,6 +29,8 @@ impl Widget for StudioEditor {
         // alright we have a scope, and an id, so now we can properly draw the editor.
         let session_id = scope.path.get(0);
         let app_scope = scope.data.get_mut::<AppData>();
+        let blame = app_scope.file_system.inline_blame(session_id);
+        self.editor.set_line_annotation(cx, blame);
         if let Some(session) = app_scope.file_system.get_session_mut(session_id){
             self.editor.draw_walk_editor(cx, session, walk);
         }
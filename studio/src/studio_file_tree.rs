@@ -1,28 +1,149 @@
 
 use {
     crate::{
-        app::{AppData},
+        app::{AppAction, AppData},
         makepad_widgets::*,
-        makepad_widgets::file_tree::FileTree,
+        makepad_widgets::file_tree::{FileTree, FileTreeAction, FileNodeId},
+        makepad_widgets::popup_menu::{PopupMenu, PopupMenuAction, PopupMenuItemId},
     },
 };
 
 live_design!{
+    import makepad_widgets::base::*;
     import makepad_widgets::theme_desktop_dark::*;
-        
+
     StudioFileTree = {{StudioFileTree}}{
         file_tree: <FileTree>{}
+        context_menu: <PopupMenu>{width: 120}
+        name_bar: <View>{
+            visible: false
+            height: Fit,
+            width: Fill
+            flow: Down
+            padding: 5
+            spacing: 5
+            show_bg: true
+            draw_bg: {color: #x33}
+            prompt = <Label> {draw_text: {color: #a}, text: ""}
+            name_input = <View> {
+                height: Fit,
+                width: Fill
+                name = <TextInput> {
+                    draw_bg: {
+                        fn pixel(self) -> vec4 {
+                            return #x00000044
+                        }
+                    }
+                    width: Fill,
+                    empty_message: "Name"
+                }
+            }
+            <View> {
+                height: Fit,
+                width: Fill
+                flow: Right
+                spacing: 5
+                ok = <Button> {text: "OK"}
+                cancel = <Button> {text: "Cancel"}
+            }
+        }
     }
-} 
- 
-#[derive(Live, LiveHook, Widget)] 
+}
+
+// The file operation that the name bar is asking about. Files are moved by renaming them to a
+// path in another directory.
+enum FileOperation {
+    Create {dir_path: String, is_dir: bool},
+    Rename {path: String},
+    Delete {path: String},
+}
+
+#[derive(Live, LiveHook, Widget)]
 pub struct StudioFileTree{
-    #[wrap] #[live] pub file_tree: FileTree
+    #[wrap] #[live] pub file_tree: FileTree,
+    #[live] context_menu: PopupMenu,
+    #[live] name_bar: View,
+    #[rust] context_menu_node: Option<(FileNodeId, DVec2)>,
+    #[rust] operation: Option<FileOperation>,
+    #[rust] focus_name: bool,
+    #[rust] area: Area,
+}
+
+impl StudioFileTree {
+    fn draw_context_menu(&mut self, cx: &mut Cx2d, file_node_id: FileNodeId, abs: DVec2, is_file: bool) {
+        self.context_menu.begin(cx);
+        if !is_file {
+            self.context_menu.draw_item(cx, PopupMenuItemId(live_id!(new_file)), "New File");
+            self.context_menu.draw_item(cx, PopupMenuItemId(live_id!(new_folder)), "New Folder");
+        }
+        if is_file {
+            self.context_menu.draw_item(cx, PopupMenuItemId(live_id!(show_changes)), "Show Changes");
+        }
+        if file_node_id != live_id!(root).into() {
+            self.context_menu.draw_item(cx, PopupMenuItemId(live_id!(rename)), "Rename");
+            self.context_menu.draw_item(cx, PopupMenuItemId(live_id!(delete)), "Delete");
+        }
+        self.context_menu.end(cx, Area::Empty, abs);
+    }
+
+    fn close_context_menu(&mut self, cx: &mut Cx) {
+        self.context_menu_node = None;
+        self.context_menu.redraw(cx);
+        self.file_tree.redraw(cx);
+    }
+
+    fn start_operation(&mut self, cx: &mut Cx, operation: FileOperation) {
+        let (prompt, name) = match &operation {
+            FileOperation::Create {dir_path, is_dir: false} => (format!("New file in /{}", dir_path), ""),
+            FileOperation::Create {dir_path, is_dir: true} => (format!("New folder in /{}", dir_path), ""),
+            FileOperation::Rename {path} => (format!("Rename or move {}", path), path.as_str()),
+            FileOperation::Delete {path} => (format!("Delete {}?", path), ""),
+        };
+        self.name_bar.label(id!(prompt)).set_text(&prompt);
+        self.name_bar.text_input(id!(name)).set_text(name);
+        self.name_bar.view(id!(name_input)).set_visible(!matches!(operation, FileOperation::Delete {..}));
+        self.name_bar.apply_over(cx, live!{visible: true});
+        self.focus_name = true;
+        self.operation = Some(operation);
+        self.area.redraw(cx);
+    }
+
+    fn finish_operation(&mut self, cx: &mut Cx, scope: &mut Scope, confirmed: bool) {
+        let Some(operation) = self.operation.take() else {
+            return
+        };
+        self.name_bar.apply_over(cx, live!{visible: false});
+        self.area.redraw(cx);
+        if !confirmed {
+            return
+        }
+        let name = self.name_bar.text_input(id!(name)).text();
+        let name = name.trim().trim_matches('/');
+        let file_system = &mut scope.data.get_mut::<AppData>().file_system;
+        match operation {
+            FileOperation::Create {dir_path, is_dir} => if !name.is_empty() {
+                let path = if dir_path.is_empty() {name.to_string()} else {format!("{}/{}", dir_path, name)};
+                file_system.request_create_file(path, is_dir);
+            }
+            FileOperation::Rename {path} => if !name.is_empty() && name != path {
+                file_system.request_rename_file(path, name.to_string());
+            }
+            FileOperation::Delete {path} => file_system.request_delete_file(path),
+        }
+    }
 }
 
 impl Widget for StudioFileTree {
     fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, walk:Walk)->DrawStep{
-        while self.file_tree.draw_walk(cx, scope, walk).is_step() {
+        cx.begin_turtle(walk, Layout::flow_down());
+        self.name_bar.draw_all(cx, scope);
+        if self.focus_name {
+            self.focus_name = false;
+            if let Some(name) = self.name_bar.text_input(id!(name)).borrow_mut() {
+                name.set_key_focus(cx);
+            }
+        }
+        while self.file_tree.draw_walk(cx, scope, Walk::fill()).is_step() {
             self.file_tree.set_folder_is_open(cx, live_id!(root).into(), true, Animate::No);
             scope.data.get_mut::<AppData>().file_system.draw_file_node(
                 cx,
@@ -30,10 +151,75 @@ impl Widget for StudioFileTree {
                 &mut self.file_tree
             );
         }
+        if let Some((file_node_id, abs)) = self.context_menu_node {
+            let file_system = &scope.data.get::<AppData>().file_system;
+            let is_file = file_system.file_nodes.get(&file_node_id).map_or(true, |node| node.is_file());
+            self.draw_context_menu(cx, file_node_id, abs, is_file);
+        }
+        cx.end_turtle_with_area(&mut self.area);
         DrawStep::done()
     }
-    
+
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
-        self.file_tree.handle_event(cx, event, scope);
+        if let Some((file_node_id, _)) = self.context_menu_node {
+            let mut selected = None;
+            self.context_menu.handle_event_with(cx, event, Area::Empty, &mut |_, action| {
+                if let PopupMenuAction::WasSelected(item_id) = action {
+                    selected = Some(item_id.0);
+                }
+            });
+            if let Some(item_id) = selected {
+                self.close_context_menu(cx);
+                let file_system = &scope.data.get::<AppData>().file_system;
+                if !file_system.file_nodes.contains_key(&file_node_id) {
+                    return
+                }
+                if item_id == live_id!(show_changes) {
+                    cx.action(AppAction::ShowChanges(file_node_id));
+                    return
+                }
+                let path = file_system.file_node_path(file_node_id);
+                let operation = match item_id {
+                    live_id!(new_file) => FileOperation::Create {dir_path: path, is_dir: false},
+                    live_id!(new_folder) => FileOperation::Create {dir_path: path, is_dir: true},
+                    live_id!(rename) => FileOperation::Rename {path},
+                    _ => FileOperation::Delete {path},
+                };
+                self.start_operation(cx, operation);
+                return
+            }
+            if let Event::MouseDown(e) = event {
+                if !self.context_menu.menu_contains_pos(cx, e.abs) {
+                    self.close_context_menu(cx);
+                }
+            }
+            // the menu is on top of the tree, so keep mouse events away from it
+            if self.context_menu_node.is_some() && event.requires_visibility() {
+                return
+            }
+        }
+
+        let actions = cx.capture_actions(|cx| self.name_bar.handle_event(cx, event, scope));
+        if self.operation.is_some() {
+            let name = self.name_bar.text_input(id!(name));
+            if self.name_bar.button(id!(ok)).clicked(&actions) || name.returned(&actions).is_some() {
+                self.finish_operation(cx, scope, true);
+            }
+            else if self.name_bar.button(id!(cancel)).clicked(&actions) || name.escaped(&actions) {
+                self.finish_operation(cx, scope, false);
+            }
+        }
+
+        let actions = cx.capture_actions(|cx| self.file_tree.handle_event(cx, event, scope));
+        for action in &actions {
+            if let Some(action) = action.as_widget_action() {
+                if let FileTreeAction::ContextMenu(file_node_id, abs) = action.cast() {
+                    self.context_menu_node = Some((file_node_id, abs));
+                    self.context_menu.redraw(cx);
+                    self.file_tree.redraw(cx);
+                }
+            }
+        }
+        cx.extend_actions(actions);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,103 @@
+// A language server that answers from a script, for testing the language server client.
+// The test binary runs it when started with --fake-lsp-server <script> <log>.
+//
+// Every message that is received is appended to the log, one per line. Each line of the script
+// says what to do when a message with a given method comes in:
+//
+//     <method> result <json>                 answer the request with <json>
+//     <method> notify <method> <json>        send a notification with <json> as its params
+//
+// `$uri` in the json is replaced with the uri of the last document the client mentioned.
+// Requests without a scripted result are answered with null.
+
+use {
+    makepad_studio::{
+        lsp_manager::lsp_protocol::{encode_message, json_get, json_str, read_message, LspMessage},
+        makepad_micro_serde::*,
+    },
+    std::{
+        fs::{self, OpenOptions},
+        io::{self, BufReader, Write},
+    },
+};
+
+enum Reply {
+    Result(String),
+    Notify(String, String),
+}
+
+fn parse_script(script: &str) -> Vec<(String, Reply)> {
+    script
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let (method, rest) = line.split_once(' ')?;
+            let (kind, rest) = rest.split_once(' ')?;
+            let reply = match kind {
+                "result" => Reply::Result(rest.to_string()),
+                "notify" => {
+                    let (method, params) = rest.split_once(' ')?;
+                    Reply::Notify(method.to_string(), params.to_string())
+                }
+                _ => return None,
+            };
+            Some((method.to_string(), reply))
+        })
+        .collect()
+}
+
+pub fn run(script_path: &str, log_path: &str) {
+    let script = parse_script(&fs::read_to_string(script_path).expect("cannot read script"));
+    let mut log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path)
+        .expect("cannot open log");
+
+    let mut reader = BufReader::new(io::stdin());
+    let mut stdout = io::stdout();
+    let mut uri = String::new();
+    while let Ok(Some(body)) = read_message(&mut reader) {
+        writeln!(log, "{}", body).unwrap();
+        let Ok(message) = LspMessage::parse(&body) else {
+            continue;
+        };
+        let (id, method, params) = match message {
+            LspMessage::Request { id, method, params } => (Some(id), method, params),
+            LspMessage::Notification { method, params } => (None, method, params),
+            LspMessage::Response { .. } => continue,
+        };
+        if let Some(document_uri) = json_get(&params, "textDocument")
+            .and_then(|text_document| json_get(text_document, "uri"))
+            .and_then(json_str)
+        {
+            uri = document_uri.to_string();
+        }
+        let mut result = None;
+        for (_, reply) in script.iter().filter(|(trigger, _)| *trigger == method) {
+            match reply {
+                Reply::Result(json) => result = Some(json.replace("$uri", &uri)),
+                Reply::Notify(method, params) => {
+                    let notification = format!(
+                        "{{\"jsonrpc\":\"2.0\",\"method\":{},\"params\":{}}}",
+                        method.serialize_json(),
+                        params.replace("$uri", &uri)
+                    );
+                    stdout.write_all(encode_message(&notification).as_bytes()).unwrap();
+                }
+            }
+        }
+        if let Some(id) = id {
+            let result = result.unwrap_or_else(|| "null".to_string());
+            let response = format!(
+                "{{\"jsonrpc\":\"2.0\",\"id\":{},\"result\":{}}}",
+                id.0, result
+            );
+            stdout.write_all(encode_message(&response).as_bytes()).unwrap();
+        }
+        stdout.flush().unwrap();
+        if method == "exit" {
+            break;
+        }
+    }
+}
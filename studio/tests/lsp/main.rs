@@ -0,0 +1,249 @@
+// Runs without the libtest harness, so the test binary can double as the language server the
+// client talks to. That keeps the scripted server out of the binaries studio builds.
+
+mod fake_server;
+
+use {
+    makepad_studio::{
+        lsp_manager::{
+            lsp_client::{LspClient, LspClientMessage},
+            lsp_protocol::*,
+        },
+        makepad_code_editor::text::{Change, Position, Text},
+        makepad_micro_serde::*,
+        makepad_platform::ToUIReceiver,
+    },
+    std::{
+        env, fs,
+        path::PathBuf,
+        thread,
+        time::{Duration, Instant},
+    },
+};
+
+const SCRIPT: &str = r#"
+initialize result {"capabilities":{"positionEncoding":"utf-8"}}
+textDocument/didOpen notify textDocument/publishDiagnostics {"uri":"$uri","diagnostics":[{"range":{"start":{"line":0,"character":3},"end":{"line":0,"character":7}},"severity":1,"message":"unknown"}]}
+textDocument/hover result {"contents":{"kind":"plaintext","value":"fn main()"}}
+textDocument/definition result [{"uri":"$uri","range":{"start":{"line":1,"character":4},"end":{"line":1,"character":8}}}]
+textDocument/completion result {"isIncomplete":false,"items":[{"label":"len","detail":"fn(&self) -> usize"},{"label":"push","textEdit":{"newText":"push_str"}}]}
+"#;
+
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("makepad_lsp_test_{}_{}", std::process::id(), name))
+}
+
+fn next_message(receiver: &ToUIReceiver<(usize, LspClientMessage)>) -> LspMessage {
+    let start = Instant::now();
+    loop {
+        match receiver.try_recv() {
+            Ok((_, LspClientMessage::Message(message))) => return message,
+            Ok((_, LspClientMessage::Exited)) => panic!("server exited"),
+            Err(_) => {
+                assert!(start.elapsed() < Duration::from_secs(10), "timed out");
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+    }
+}
+
+fn next_result(receiver: &ToUIReceiver<(usize, LspClientMessage)>, expected_id: u64) -> JsonValue {
+    match next_message(receiver) {
+        LspMessage::Response { id, result } => {
+            assert_eq!(id, expected_id);
+            result.unwrap()
+        }
+        _ => panic!("expected a response"),
+    }
+}
+
+// the first argument that makes the test binary run as the fake server
+const FAKE_SERVER_ARG: &str = "--fake-lsp-server";
+
+fn main() {
+    let args = env::args().collect::<Vec<_>>();
+    if args.get(1).is_some_and(|arg| arg == FAKE_SERVER_ARG) {
+        fake_server::run(&args[2], &args[3]);
+        return;
+    }
+    let filter = args.iter().skip(1).find(|arg| !arg.starts_with('-'));
+    let tests: [(&str, fn()); 2] = [
+        ("talks_to_a_scripted_server", talks_to_a_scripted_server),
+        ("converts_utf16_positions", converts_utf16_positions),
+    ];
+    for (name, test) in tests {
+        if filter.is_some_and(|filter| !name.contains(filter.as_str())) {
+            continue;
+        }
+        test();
+        println!("test {} ... ok", name);
+    }
+}
+
+fn talks_to_a_scripted_server() {
+    let script_path = temp_path("script");
+    let log_path = temp_path("log");
+    fs::write(&script_path, SCRIPT).unwrap();
+    let _ = fs::remove_file(&log_path);
+
+    let receiver = ToUIReceiver::default();
+    let client = LspClient::start(
+        env::current_exe().unwrap().to_str().unwrap(),
+        &[
+            FAKE_SERVER_ARG.to_string(),
+            script_path.to_string_lossy().to_string(),
+            log_path.to_string_lossy().to_string(),
+        ],
+        &env::temp_dir(),
+        7,
+        receiver.sender(),
+    )
+    .unwrap();
+
+    let root_path = env::temp_dir();
+    client.send(LspRequest::new(0, "initialize", InitializeParams::new(&root_path)).serialize_json());
+    let result = next_result(&receiver, 0);
+    let encoding = PositionEncoding::from_capabilities(json_get(&result, "capabilities").unwrap());
+    assert_eq!(encoding, PositionEncoding::Utf8);
+    client.send(LspNotification::new("initialized", NoParams).serialize_json());
+
+    let uri = path_to_uri(&root_path.join("main.rs"));
+    let mut text = Text::from("fn main() {\n    let x = 1;\n}");
+    client.send(
+        LspNotification::new(
+            "textDocument/didOpen",
+            DidOpenTextDocumentParams {
+                textDocument: TextDocumentItem {
+                    uri: uri.clone(),
+                    languageId: "rust".to_string(),
+                    version: 0,
+                    text: text.to_string(),
+                },
+            },
+        )
+        .serialize_json(),
+    );
+    let LspMessage::Notification { method, params } = next_message(&receiver) else {
+        panic!("expected a notification");
+    };
+    assert_eq!(method, "textDocument/publishDiagnostics");
+    let (diagnostics_uri, diagnostics) = parse_diagnostics(&params).unwrap();
+    assert_eq!(diagnostics_uri, uri);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, 1);
+    assert_eq!(diagnostics[0].message, "unknown");
+    assert_eq!(
+        encoding.to_position(&text, diagnostics[0].range.start),
+        Position {
+            line_index: 0,
+            byte_index: 3
+        }
+    );
+
+    let change = Change::Insert(
+        Position {
+            line_index: 1,
+            byte_index: 4,
+        },
+        Text::from("\"é\";\n    "),
+    );
+    let content_change = encoding.to_lsp_change(&text, &change);
+    text.apply_change(change);
+    client.send(
+        LspNotification::new(
+            "textDocument/didChange",
+            DidChangeTextDocumentParams {
+                textDocument: VersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: 1,
+                },
+                contentChanges: vec![content_change],
+            },
+        )
+        .serialize_json(),
+    );
+
+    let position_params = |line, character| TextDocumentPositionParams {
+        textDocument: TextDocumentIdentifier { uri: uri.clone() },
+        position: LspPosition { line, character },
+    };
+    client.send(LspRequest::new(1, "textDocument/hover", position_params(0, 4)).serialize_json());
+    assert_eq!(parse_hover(&next_result(&receiver, 1)), "fn main()");
+
+    client.send(LspRequest::new(2, "textDocument/definition", position_params(0, 4)).serialize_json());
+    let locations = parse_locations(&next_result(&receiver, 2));
+    assert_eq!(locations.len(), 1);
+    assert_eq!(uri_to_path(&locations[0].uri), Some(root_path.join("main.rs")));
+    assert_eq!(locations[0].range.start, LspPosition { line: 1, character: 4 });
+
+    client.send(LspRequest::new(3, "textDocument/completion", position_params(0, 4)).serialize_json());
+    let items = parse_completions(&next_result(&receiver, 3));
+    assert_eq!(items.len(), 2);
+    assert_eq!((items[0].label.as_str(), items[0].detail.as_str(), items[0].insert_text.as_str()), ("len", "fn(&self) -> usize", "len"));
+    assert_eq!((items[1].label.as_str(), items[1].insert_text.as_str()), ("push", "push_str"));
+
+    // Unscripted requests get a null result.
+    client.send(LspRequest::new(4, "shutdown", NoParams).serialize_json());
+    assert!(matches!(next_result(&receiver, 4), JsonValue::Null));
+    client.send(LspNotification::new("exit", NoParams).serialize_json());
+    let start = Instant::now();
+    loop {
+        if let Ok((server_id, LspClientMessage::Exited)) = receiver.try_recv() {
+            assert_eq!(server_id, 7);
+            break;
+        }
+        assert!(start.elapsed() < Duration::from_secs(10), "timed out");
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    let log = fs::read_to_string(&log_path).unwrap();
+    let methods = log
+        .lines()
+        .map(|line| {
+            let value = JsonValue::deserialize_json(line).unwrap();
+            json_str(json_get(&value, "method").unwrap()).unwrap().to_string()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        methods,
+        [
+            "initialize",
+            "initialized",
+            "textDocument/didOpen",
+            "textDocument/didChange",
+            "textDocument/hover",
+            "textDocument/definition",
+            "textDocument/completion",
+            "shutdown",
+            "exit"
+        ]
+    );
+    let did_change = log.lines().nth(3).unwrap();
+    assert!(did_change.contains(
+        r#""contentChanges":[{"range":{"start":{"line":1,"character":4},"end":{"line":1,"character":4}},"text":"\"é\";\n    "}]"#
+    ));
+
+    drop(client);
+    let _ = fs::remove_file(&script_path);
+    let _ = fs::remove_file(&log_path);
+}
+
+fn converts_utf16_positions() {
+    let text = Text::from("let é = \"😀\";\nx");
+    let encoding = PositionEncoding::Utf16;
+    // The emoji takes two utf-16 code units but four bytes.
+    let position = Position {
+        line_index: 0,
+        byte_index: 14,
+    };
+    let lsp_position = encoding.to_lsp_position(&text, position);
+    assert_eq!(lsp_position, LspPosition { line: 0, character: 11 });
+    assert_eq!(encoding.to_position(&text, lsp_position), position);
+    assert_eq!(
+        encoding.to_position(&text, LspPosition { line: 5, character: 100 }),
+        Position {
+            line_index: 1,
+            byte_index: 1
+        }
+    );
+}
@@ -0,0 +1,237 @@
+use std::path::{Path, PathBuf};
+use crate::utils::*;
+use crate::makepad_shell::*;
+use makepad_toml_parser::*;
+use super::PackageFormat;
+
+struct PackageInfo {
+    binary: String,
+    app_name: String,
+    version: String,
+    description: String,
+    maintainer: String,
+    homepage: Option<String>,
+    categories: String,
+    icon: Option<PathBuf>,
+}
+
+impl PackageInfo {
+    fn from_cargo_toml(build_crate: &str, crate_dir: &Path) -> Result<Self, String> {
+        let cargo_str = std::fs::read_to_string(crate_dir.join("Cargo.toml"))
+            .map_err( | e | format!("Cannot read Cargo.toml for {} {:?}", build_crate, e)) ?;
+        let toml = parse_toml(&cargo_str).map_err( | e | format!("Cannot parse Cargo.toml {:?}", e)) ?;
+        let get_str = | key: &str | if let Some(Toml::Str(v, _)) = toml.get(key) {Some(v.to_string())} else {None};
+        
+        let maintainer = if let Some(Toml::Array(authors)) = toml.get("package.authors") {
+            authors.first().cloned().and_then( | a | a.into_str())
+        }
+        else {
+            None
+        };
+        Ok(Self {
+            binary: build_crate.to_string(),
+            app_name: get_str("package.metadata.makepad-linux-app-name").unwrap_or_else( || build_crate.to_string()),
+            version: get_str("package.version").unwrap_or_else( || "0.0.0".to_string()),
+            description: get_str("package.description").unwrap_or_else( || build_crate.to_string()),
+            maintainer: maintainer.unwrap_or_else( || "Unknown <unknown@unknown>".to_string()),
+            homepage: get_str("package.homepage"),
+            categories: get_str("package.metadata.makepad-linux-categories").unwrap_or_else( || "Utility;".to_string()),
+            icon: get_str("package.metadata.makepad-linux-icon").map( | icon | crate_dir.join(icon)),
+        })
+    }
+    
+    fn icon_ext(&self) -> &'static str {
+        match self.icon.as_ref().and_then( | icon | icon.extension()).and_then( | e | e.to_str()) {
+            Some("png") => "png",
+            _ => "svg"
+        }
+    }
+    
+    fn desktop_file(&self, exec: &str) -> String {
+        format!("[Desktop Entry]\n\
+            Type=Application\n\
+            Name={}\n\
+            Comment={}\n\
+            Exec={}\n\
+            Icon={}\n\
+            Terminal=false\n\
+            Categories={}\n",
+            self.app_name,
+            self.description,
+            exec,
+            self.binary,
+            self.categories
+        )
+    }
+    
+    fn debian_control(&self, installed_size_kb: u64) -> String {
+        let arch = match std::env::consts::ARCH {
+            "x86_64" => "amd64",
+            "aarch64" => "arm64",
+            "x86" => "i386",
+            "arm" => "armhf",
+            x => x
+        };
+        let mut control = format!("Package: {}\n\
+            Version: {}\n\
+            Architecture: {}\n\
+            Maintainer: {}\n\
+            Installed-Size: {}\n\
+            Depends: libx11-6, libxcursor1, libgl1, libegl1, libasound2 | libasound2t64, libpulse0\n\
+            Section: misc\n\
+            Priority: optional\n",
+            self.binary.to_lowercase().replace('_', "-"),
+            self.version,
+            arch,
+            self.maintainer,
+            installed_size_kb
+        );
+        if let Some(homepage) = &self.homepage {
+            control.push_str(&format!("Homepage: {}\n", homepage));
+        }
+        control.push_str(&format!("Description: {}\n", self.description));
+        control
+    }
+    
+    fn default_icon_svg(&self) -> String {
+        let letter = self.app_name.chars().next().unwrap_or('M').to_uppercase();
+        format!(r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 256 256">
+    <rect x="8" y="8" width="240" height="240" rx="48" fill="#3f3f5f"/>
+    <text x="128" y="172" font-family="sans-serif" font-size="140" text-anchor="middle" fill="#ffffff">{letter}</text>
+</svg>
+"##)
+    }
+    
+    fn write_icon(&self, dst: &Path) -> Result<(), String> {
+        if let Some(icon) = &self.icon {
+            cp(icon, dst, false)
+        }
+        else {
+            write_text(dst, &self.default_icon_svg())
+        }
+    }
+}
+
+fn rust_build(args: &[String]) -> Result<(), String> {
+    let cwd = std::env::current_dir().unwrap();
+    let mut args_out = vec!["build"];
+    for arg in args {
+        args_out.push(arg);
+    }
+    shell_env(&[("MAKEPAD", "lines+linux_package")], &cwd, "cargo", &args_out)
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    ls(dir).unwrap_or_default().iter()
+        .filter_map( | path | std::fs::metadata(dir.join(path)).ok())
+        .map( | meta | meta.len())
+        .sum()
+}
+
+/// Lays out the binary, the resources of its crates, the .desktop file and the icon
+/// in a /usr style prefix. The platform layer finds the resources at ../share/<binary>/makepad
+fn install_prefix(info: &PackageInfo, build_crate: &str, src_binary: &Path, prefix: &Path, exec: &str) -> Result<(), String> {
+    cp(src_binary, &prefix.join(format!("bin/{}", info.binary)), true) ?;
+    
+    let share_dir = prefix.join(format!("share/{}/makepad", info.binary));
+    let crates = copy_crate_resources(build_crate, &share_dir) ?;
+    println!("Copied the resources of {} crates", crates);
+    
+    write_text(&prefix.join(format!("share/applications/{}.desktop", info.binary)), &info.desktop_file(exec)) ?;
+    let icon_dir = if info.icon_ext() == "png" {"256x256"} else {"scalable"};
+    info.write_icon(&prefix.join(format!("share/icons/hicolor/{}/apps/{}.{}", icon_dir, info.binary, info.icon_ext()))) ?;
+    Ok(())
+}
+
+fn package_tar(info: &PackageInfo, build_crate: &str, src_binary: &Path, out_dir: &Path) -> Result<PathBuf, String> {
+    let name = format!("{}-{}-{}", info.binary, info.version, std::env::consts::ARCH);
+    install_prefix(info, build_crate, src_binary, &out_dir.join(&name), &info.binary) ?;
+    let tar_file = format!("{}.tar.gz", name);
+    shell_env_cap(&[], out_dir, "tar", &["-czf", &tar_file, &name]) ?;
+    Ok(out_dir.join(tar_file))
+}
+
+fn package_deb(info: &PackageInfo, build_crate: &str, src_binary: &Path, out_dir: &Path) -> Result<PathBuf, String> {
+    let root = out_dir.join("deb");
+    let prefix = root.join("usr");
+    install_prefix(info, build_crate, src_binary, &prefix, &format!("/usr/bin/{}", info.binary)) ?;
+    write_text(&root.join("DEBIAN/control"), &info.debian_control(dir_size(&prefix) >> 10)) ?;
+    let deb_file = out_dir.join(format!("{}_{}.deb", info.binary, info.version));
+    shell_env_cap(&[], out_dir, "dpkg-deb", &[
+        "--build",
+        "--root-owner-group",
+        root.to_str().unwrap(),
+        deb_file.to_str().unwrap()
+    ]) ?;
+    Ok(deb_file)
+}
+
+fn package_appdir(info: &PackageInfo, build_crate: &str, src_binary: &Path, out_dir: &Path) -> Result<PathBuf, String> {
+    let app_dir = out_dir.join(format!("{}.AppDir", info.binary));
+    install_prefix(info, build_crate, src_binary, &app_dir.join("usr"), &info.binary) ?;
+    
+    // the AppDir spec wants the desktop file, icon and AppRun at the root
+    write_text(&app_dir.join(format!("{}.desktop", info.binary)), &info.desktop_file(&info.binary)) ?;
+    info.write_icon(&app_dir.join(format!("{}.{}", info.binary, info.icon_ext()))) ?;
+    let app_run = app_dir.join("AppRun");
+    write_text(&app_run, &format!("#!/bin/sh\nHERE=\"$(dirname \"$(readlink -f \"$0\")\")\"\nexec \"$HERE/usr/bin/{}\" \"$@\"\n", info.binary)) ?;
+    #[cfg(unix)]{
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&app_run, PermissionsExt::from_mode(0o755))
+            .map_err( | _e | format!("Cant set exec permissions on {:?}", app_run)) ?;
+    }
+    
+    // turn it into an AppImage if appimagetool is available
+    let app_image = out_dir.join(format!("{}-{}.AppImage", info.binary, std::env::consts::ARCH));
+    match shell_env_cap(&[], out_dir, "appimagetool", &[app_dir.to_str().unwrap(), app_image.to_str().unwrap()]) {
+        Ok(_) => Ok(app_image),
+        Err(_) => {
+            println!("appimagetool not found, leaving the AppDir unpacked");
+            Ok(app_dir)
+        }
+    }
+}
+
+pub fn package(format: PackageFormat, args: &[String]) -> Result<PathBuf, String> {
+    let build_crate = get_build_crate_from_args(args) ?;
+    let underscore_build_crate = build_crate.replace('-', "_");
+    let profile = get_profile_from_args(args);
+    let cwd = std::env::current_dir().unwrap();
+    
+    if profile == "debug" {
+        println!("WARNING - packaging a DEBUG build of the application, this creates a very slow and big app. Try adding --release for a fast, or --profile=small for a small build.");
+    }
+    rust_build(args) ?;
+    
+    let src_binary = cwd.join(format!("target/{profile}/{build_crate}"));
+    if !src_binary.is_file() {
+        return Err(format!("Cannot find binary {:?}", src_binary));
+    }
+    let crate_dir = get_crate_dir(build_crate) ?;
+    let info = PackageInfo::from_cargo_toml(build_crate, &crate_dir) ?;
+    
+    let out_dir = cwd.join(format!("target/makepad-linux-package/{profile}/{underscore_build_crate}"));
+    let _ = rmdir(&out_dir);
+    mkdir(&out_dir) ?;
+    
+    let artifact = match format {
+        PackageFormat::Tar => package_tar(&info, build_crate, &src_binary, &out_dir),
+        PackageFormat::Deb => package_deb(&info, build_crate, &src_binary, &out_dir),
+        PackageFormat::AppDir => package_appdir(&info, build_crate, &src_binary, &out_dir),
+    } ?;
+    println!("Linux package completed: {:?}", artifact);
+    Ok(artifact)
+}
+
+pub fn apt_get_install_makepad_deps() -> Result<(), String> {
+    let cwd = std::env::current_dir().unwrap();
+    shell_env(&[], &cwd, "sudo", &[
+        "apt-get",
+        "install",
+        "libxcursor-dev",
+        "libx11-dev",
+        "libgl1-mesa-dev",
+        "libasound2-dev",
+        "libpulse-dev",
+    ])
+}
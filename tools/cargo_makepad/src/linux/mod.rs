@@ -0,0 +1,49 @@
+mod compile;
+use compile::*;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum PackageFormat {
+    Deb,
+    Tar,
+    AppDir
+}
+
+impl PackageFormat {
+    fn from_str(opt: &str) -> Result<Self, String> {
+        match opt {
+            "deb" => Ok(Self::Deb),
+            "tar" => Ok(Self::Tar),
+            "appdir" => Ok(Self::AppDir),
+            x => Err(format!("{:?} please provide a valid package format: deb, tar, appdir", x))
+        }
+    }
+}
+
+pub fn handle_linux(mut args: &[String]) -> Result<(), String> {
+    let mut format = PackageFormat::Tar;
+    // pull out options
+    for i in 0..args.len() {
+        let v = &args[i];
+        if let Some(opt) = v.strip_prefix("--format=") {
+            format = PackageFormat::from_str(opt) ?;
+        }
+        else {
+            args = &args[i..];
+            break
+        }
+    }
+    if args.is_empty() {
+        return Err("Not enough arguments".into());
+    }
+    
+    match args[0].as_ref() {
+        "apt-get-install-makepad-deps" => {
+            apt_get_install_makepad_deps()
+        }
+        "package" => {
+            package(format, &args[1..]) ?;
+            Ok(())
+        }
+        _ => Err(format!("{} is not a valid command or option", args[0]))
+    }
+}
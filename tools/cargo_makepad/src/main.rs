@@ -3,10 +3,12 @@ mod wasm;
 mod utils;
 mod apple;
 mod check;
+mod linux;
 use android::*;
 use wasm::*;
 use apple::*;
 use check::*;
+use linux::*;
 pub use makepad_shell;
 
 fn show_help(err: &str){
@@ -21,12 +23,15 @@ fn show_help(err: &str){
     println!("Wasm Commands:");
     println!();
     println!("    wasm install-toolchain                       Install the toolchain needed for wasm32 with rustup");
-    println!("    wasm build <cargo args>                      Build a wasm project");
+    println!("    wasm [options] build <cargo args>            Build a wasm project into target/makepad-wasm-app/<profile>/<crate>");
     println!("    wasm [options] run <cargo args>              Build and run a wasm project, starts a webserver at port 8080 by default");
     println!();
     println!("    [options] with its default value:");
     println!();
     println!("       --port=8080                               The port to run the wasm webserver");
+    println!("       --no-strip                                Keep the debug sections in the wasm file");
+    println!("       --gzip                                    Write gzip precompressed (.gz) files next to the bundle files");
+    println!("       --brotli                                  Write brotli precompressed (.br) files, needs the brotli commandline tool");
     println!();
     println!("Apple iOS/TVOs Commands:");
     println!();
@@ -71,6 +76,16 @@ fn show_help(err: &str){
     println!("Linux commands:");
     println!();
     println!("    linux apt-get-install-makepad-deps           Call apt-get install with all dependencies needed for makepad.");
+    println!("    linux [options] package <cargo args>         Build and package a desktop application into target/makepad-linux-package");
+    println!();
+    println!("    [options] with its default value:");
+    println!();
+    println!("       --format=tar,deb,appdir                   The package format (default is tar). appdir also builds an AppImage if appimagetool is installed");
+    println!();
+    println!("    The package metadata is read from Cargo.toml [package] and these optional keys:");
+    println!("       metadata.makepad-linux-app-name           The application name in the .desktop file");
+    println!("       metadata.makepad-linux-icon               Path to a .png or .svg icon relative to the crate");
+    println!("       metadata.makepad-linux-categories         The .desktop Categories (default is Utility;)");
     println!();
     println!();
     }
@@ -106,6 +121,9 @@ fn main() {
         "check" => if let Err(e) = handle_check(&args[1..]){
             println!("Got error: {}", e);
         }
+        "linux" => if let Err(e) = handle_linux(&args[1..]){
+            println!("Got error: {}", e);
+        }
         _=> show_help("not implemented yet")
     }
 }
\ No newline at end of file
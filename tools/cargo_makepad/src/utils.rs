@@ -59,6 +59,20 @@ pub fn get_crate_resources(build_crate: &str) -> HashSet<(String, PathBuf)> {
     dependencies
 }
 
+// copies the resources dir of the build crate and of every crate it depends on to
+// <dst_dir>/<crate_name>/resources, the same files the android and apple packages carry
+pub fn copy_crate_resources(build_crate: &str, dst_dir: &Path) -> Result<usize, String> {
+    let mut resources = get_crate_resources(build_crate);
+    let local_resources_path = get_crate_dir(build_crate) ?.join("resources");
+    if local_resources_path.is_dir() {
+        resources.insert((build_crate.replace('-', "_"), local_resources_path));
+    }
+    for (name, resources_path) in &resources {
+        cp_all(resources_path, &dst_dir.join(name).join("resources"), false) ?;
+    }
+    Ok(resources.len())
+}
+
 pub fn get_build_crate_from_args(args: &[String]) -> Result<&str, String> {
     if args.is_empty() {
         return Err("Not enough arguments to build".into());
@@ -85,4 +99,3 @@ pub fn get_profile_from_args(args: &[String]) -> String {
     }
     return "debug".to_string()
 }
-
@@ -1,7 +1,33 @@
+use std::path::{Path, PathBuf};
+use crate::utils::*;
 use crate::makepad_shell::*;
+use super::wasm_strip::*;
+use super::server::*;
+use super::compress::*;
 
-pub fn build(args: &[String]) -> Result<(), String> {
-    
+pub struct WasmConfig {
+    pub strip: bool,
+    pub gzip: bool,
+    pub brotli: bool,
+    pub port: u16,
+}
+
+impl Default for WasmConfig {
+    fn default() -> Self {
+        Self {
+            strip: true,
+            gzip: false,
+            brotli: false,
+            port: 8080,
+        }
+    }
+}
+
+pub struct WasmBuildResult {
+    pub app_dir: PathBuf,
+}
+
+fn rust_build(args: &[String]) -> Result<(), String> {
     let base_args = &[ 
         "run",
         "nightly",
@@ -23,14 +49,118 @@ pub fn build(args: &[String]) -> Result<(), String> {
       
     shell_env(&[
         ("RUSTFLAGS", "-C codegen-units=1 -C target-feature=+atomics,+bulk-memory,+mutable-globals -C link-arg=--export=__stack_pointer -C opt-level=z"),
-        ("MAKEPAD", "lines"),
+        ("MAKEPAD", "lines+wasm_bundle"),
     ], &cwd, "rustup", &args_out) ?;
     
-    println!("WebAssembly build completed");
+    Ok(())
+}
+
+fn index_html(title: &str, wasm_file: &str) -> String {
+    format!(r#"<!DOCTYPE html>
+<html>
+    <head>
+        <meta charset="utf-8">
+        <meta name="viewport" content="width=device-width, initial-scale=1.0, user-scalable=no">
+        <title>{title}</title>
+        <script type='module'>
+            import {{WasmWebGL}} from "/makepad/platform/src/os/web/web_gl.js"
+            
+            const wasm = await WasmWebGL.fetch_and_instantiate_wasm(
+                "/{wasm_file}"
+            );
+            
+            class MyWasmApp {{
+                constructor(wasm) {{
+                    let canvas = document.getElementsByClassName('full_canvas')[0];
+                    this.webgl = new WasmWebGL (wasm, this, canvas);
+                }}
+            }}
+            
+            let app = new MyWasmApp(wasm);
+        </script>
+        <link rel='stylesheet' type='text/css' href='/makepad/platform/src/os/web/full_canvas.css'>
+    </head>
+    <body>
+        <canvas class='full_canvas'></canvas>
+        <div class='canvas_loader' >
+            <div style=''>
+                Loading..
+            </div>
+        </div>
+    </body>
+</html>
+"#)
+}
+
+fn copy_wasm(cwd: &Path, build_crate: &str, profile: &str, app_dir: &Path, config: &WasmConfig) -> Result<String, String> {
+    // binaries keep the dashes of the package name, libraries get underscores
+    let target_dir = cwd.join(format!("target/wasm32-unknown-unknown/{profile}"));
+    let underscore_build_crate = build_crate.replace('-', "_");
+    let src_wasm = [build_crate, &underscore_build_crate].iter()
+        .map( | name | target_dir.join(format!("{name}.wasm")))
+        .find( | path | path.is_file())
+        .ok_or_else( || format!("Cannot find wasm output for {} in {:?}", build_crate, target_dir)) ?;
     
+    let wasm_file = format!("{underscore_build_crate}.wasm");
+    let dst_wasm = app_dir.join(&wasm_file);
+    if config.strip {
+        let data = std::fs::read(&src_wasm).map_err( | e | format!("Cannot read wasm file {:?} {:?}", src_wasm, e)) ?;
+        let stripped = wasm_strip_debug(&data).map_err( | _ | format!("Cannot parse wasm file {:?}", src_wasm)) ?;
+        println!("Wasm file stripped size: {} kb", stripped.len() >> 10);
+        std::fs::write(&dst_wasm, stripped).map_err( | e | format!("Cannot write wasm file {:?} {:?}", dst_wasm, e)) ?;
+    }
+    else {
+        cp(&src_wasm, &dst_wasm, false) ?;
+    }
+    Ok(wasm_file)
+}
+
+fn copy_platform_js(app_dir: &Path) -> Result<(), String> {
+    let platform_dir = get_crate_dir("makepad-platform") ?;
+    let web_dir = platform_dir.join("src/os/web");
+    let dst_dir = app_dir.join("makepad/platform/src/os/web");
+    for file in ["web.js", "web_gl.js", "web_worker.js", "audio_worklet.js", "full_canvas.css"] {
+        cp(&web_dir.join(file), &dst_dir.join(file), false) ?;
+    }
+    let bridge_dir = get_crate_dir("makepad-wasm-bridge") ?;
+    cp(
+        &bridge_dir.join("src/wasm_bridge.js"),
+        &app_dir.join("makepad/libs/wasm_bridge/src/wasm_bridge.js"),
+        false
+    ) ?;
     Ok(())
 }
 
-pub fn run(_args: &[String]) -> Result<(), String> {
-    return Err("Run is not implemented yet".into());
+
+pub fn build(config: &WasmConfig, args: &[String]) -> Result<WasmBuildResult, String> {
+    let build_crate = get_build_crate_from_args(args) ?;
+    let underscore_build_crate = build_crate.replace('-', "_");
+    let profile = get_profile_from_args(args);
+    let cwd = std::env::current_dir().unwrap();
+    
+    rust_build(args) ?;
+    
+    let app_dir = cwd.join(format!("target/makepad-wasm-app/{profile}/{underscore_build_crate}"));
+    let _ = rmdir(&app_dir);
+    mkdir(&app_dir) ?;
+    
+    let wasm_file = copy_wasm(&cwd, build_crate, &profile, &app_dir, config) ?;
+    write_text(&app_dir.join("index.html"), &index_html(&underscore_build_crate, &wasm_file)) ?;
+    copy_platform_js(&app_dir) ?;
+    let crates = copy_crate_resources(build_crate, &app_dir.join("makepad")) ?;
+    println!("Copied the resources of {} crates", crates);
+    
+    if config.gzip || config.brotli {
+        compress_dir(&app_dir, config.gzip, config.brotli) ?;
+    }
+    
+    println!("WebAssembly build completed: {:?}", app_dir);
+    Ok(WasmBuildResult {
+        app_dir
+    })
+}
+
+pub fn run(config: &WasmConfig, args: &[String]) -> Result<(), String> {
+    let result = build(config, args) ?;
+    serve_dir(&result.app_dir, config.port)
 }
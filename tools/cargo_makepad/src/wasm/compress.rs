@@ -0,0 +1,54 @@
+use std::path::Path;
+use crate::makepad_shell::*;
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (i, entry) in table.iter_mut().enumerate() {
+        let mut c = i as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 {0xEDB88320 ^ (c >> 1)} else {c >> 1};
+        }
+        *entry = c;
+    }
+    let mut crc = 0xFFFFFFFFu32;
+    for byte in data {
+        crc = table[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+    }
+    crc ^ 0xFFFFFFFF
+}
+
+pub fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 2, 0xff];
+    out.extend_from_slice(&makepad_miniz::compress_to_vec(data, 10));
+    out.extend_from_slice(&crc32(data).to_le_bytes());
+    out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    out
+}
+
+fn is_precompressed(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then( | e | e.to_str()),
+        Some("png") | Some("jpg") | Some("jpeg") | Some("gif") | Some("webp") | Some("gz") | Some("br")
+    )
+}
+
+pub fn compress_dir(dir: &Path, gzip_files: bool, brotli_files: bool) -> Result<(), String> {
+    let files = ls(dir) ?;
+    for file in files {
+        let path = dir.join(&file);
+        if is_precompressed(&path) {
+            continue
+        }
+        if gzip_files {
+            let data = std::fs::read(&path).map_err( | e | format!("Cannot read {:?} {:?}", path, e)) ?;
+            let gz_path = path.with_file_name(format!("{}.gz", path.file_name().unwrap().to_str().unwrap()));
+            std::fs::write(&gz_path, gzip(&data)).map_err( | e | format!("Cannot write {:?} {:?}", gz_path, e)) ?;
+        }
+        if brotli_files {
+            // there is no brotli encoder in-tree, so we use the commandline tool if its there
+            shell_env_cap(&[], dir, "brotli", &["-f", "-q", "11", file.to_str().unwrap()])
+                .map_err( | e | format!("Brotli compression needs the brotli commandline tool installed: {}", e)) ?;
+        }
+    }
+    Ok(())
+}
@@ -1,7 +1,38 @@
 mod compile;
+mod compress;
 mod sdk;
+mod server;
+mod wasm_strip;
+use compile::WasmConfig;
+
+// pulls the options off the front of the arguments and returns the rest, which start with the command
+fn parse_options<'a>(args: &'a [String], config: &mut WasmConfig) -> Result<&'a [String], String> {
+    for (i, v) in args.iter().enumerate() {
+        if let Some(opt) = v.strip_prefix("--port=") {
+            config.port = opt.parse().map_err( | _ | format!("{} is not a valid port", opt)) ?;
+        }
+        else if v.trim() == "--no-strip" {
+            config.strip = false;
+        }
+        else if v.trim() == "--gzip" {
+            config.gzip = true;
+        }
+        else if v.trim() == "--brotli" {
+            config.brotli = true;
+        }
+        else {
+            return Ok(&args[i..])
+        }
+    }
+    Ok(&[])
+}
 
 pub fn handle_wasm(args: &[String]) -> Result<(), String> {
+    let mut config = WasmConfig::default();
+    let args = parse_options(args, &mut config)?;
+    if args.is_empty() {
+        return Err("Not enough arguments".into());
+    }
     
     match args[0].as_ref() {
         "rustup-install-toolchain"=>{
@@ -11,12 +42,39 @@ pub fn handle_wasm(args: &[String]) -> Result<(), String> {
             sdk::rustup_toolchain_install()
         }
         "build" =>{
-            compile::build(&args[1..])?;
+            compile::build(&config, &args[1..])?;
             Ok(())
         }
         "run" =>{
-            compile::run(&args[1..])
+            compile::run(&config, &args[1..])
         }
         _ => Err(format!("{} is not a valid command or option", args[0]))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map( | arg | arg.to_string()).collect()
+    }
+    
+    #[test]
+    fn parses_the_options_before_the_command() {
+        let mut config = WasmConfig::default();
+        let args = args(&["--port=8080", "--no-strip", "--brotli", "run", "-p", "makepad-example-simple"]);
+        assert_eq!(parse_options(&args, &mut config).unwrap(), &args[3..]);
+        assert_eq!(config.port, 8080);
+        assert!(!config.strip && config.brotli && !config.gzip);
+    }
+    
+    #[test]
+    fn leaves_no_command_when_there_are_only_options() {
+        let mut config = WasmConfig::default();
+        assert!(parse_options(&args(&["--gzip", "--no-strip"]), &mut config).unwrap().is_empty());
+        assert!(config.gzip);
+        assert_eq!(handle_wasm(&args(&["--gzip"])), Err("Not enough arguments".to_string()));
+        assert!(parse_options(&args(&["--port=http"]), &mut config).is_err());
+    }
+}
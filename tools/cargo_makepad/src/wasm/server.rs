@@ -0,0 +1,100 @@
+use makepad_http::server::*;
+use std::{
+    net::SocketAddr,
+    path::Path,
+    sync::mpsc,
+};
+
+fn mime_type(path: &str) -> Option<&'static str> {
+    let ext = path.rsplit('.').next()?;
+    Some(match ext {
+        "html" => "text/html",
+        "wasm" => "application/wasm",
+        "css" => "text/css",
+        "js" => "text/javascript",
+        "json" => "application/json",
+        "ttf" => "application/ttf",
+        "otf" => "font/otf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpg",
+        "svg" => "image/svg+xml",
+        "txt" | "md" => "text/plain",
+        _ => "application/octet-stream"
+    })
+}
+
+fn not_found() -> HttpServerResponse {
+    HttpServerResponse {
+        header: "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+        body: vec![]
+    }
+}
+
+fn serve_file(dir: &Path, path: &str, accept_encoding: Option<&str>) -> HttpServerResponse {
+    let path = path.split('?').next().unwrap_or("");
+    let path = if path.ends_with('/') {format!("{}index.html", path)} else {path.to_string()};
+    if path.contains("..") || path.contains('\\') {
+        return not_found()
+    }
+    let mime_type = if let Some(mime_type) = mime_type(&path) {mime_type} else {return not_found()};
+    let file_path = dir.join(path.trim_start_matches('/'));
+    
+    // prefer the precompressed variants the bundler wrote out
+    let accept_encoding = accept_encoding.unwrap_or("");
+    let mut candidates = Vec::new();
+    if accept_encoding.contains("br") {
+        candidates.push(("br", format!("{}.br", file_path.display())));
+    }
+    if accept_encoding.contains("gzip") {
+        candidates.push(("gzip", format!("{}.gz", file_path.display())));
+    }
+    candidates.push(("none", file_path.display().to_string()));
+    
+    for (encoding, candidate) in candidates {
+        if let Ok(body) = std::fs::read(&candidate) {
+            let header = format!(
+                "HTTP/1.1 200 OK\r\n\
+                Content-Type: {}\r\n\
+                Cross-Origin-Embedder-Policy: require-corp\r\n\
+                Cross-Origin-Opener-Policy: same-origin\r\n\
+                Content-encoding: {}\r\n\
+                Cache-Control: max-age:0\r\n\
+                Content-Length: {}\r\n\
+                Connection: close\r\n\r\n",
+                mime_type,
+                encoding,
+                body.len()
+            );
+            return HttpServerResponse {header, body}
+        }
+    }
+    not_found()
+}
+
+pub fn serve_dir(dir: &Path, port: u16) -> Result<(), String> {
+    let (tx_request, rx_request) = mpsc::channel::<HttpServerRequest> ();
+    let addr = SocketAddr::from(([127, 0, 0, 1], port));
+    
+    if start_http_server(HttpServer {
+        listen_address: addr,
+        post_max_size: 1024 * 1024,
+        request: tx_request
+    }).is_none() {
+        return Err(format!("Cannot start webserver on {}", addr))
+    }
+    println!("Serving {:?} on http://{}", dir, addr);
+    
+    while let Ok(message) = rx_request.recv() {
+        match message {
+            HttpServerRequest::Get {headers, response_sender} => {
+                let response = serve_file(dir, &headers.path, headers.accept_encoding.as_deref());
+                let _ = response_sender.send(response);
+            }
+            HttpServerRequest::Post {response, ..} => {
+                let _ = response.send(not_found());
+            }
+            _ => ()
+        }
+    }
+    Ok(())
+}
@@ -36,8 +36,10 @@ pub fn derive_widget_node_impl(input: TokenStream) ->  TokenStream {
         let mut walk_field = None;
         let mut deref_field = None;
         let mut wrap_field = None;
+        let mut animator_field = None;
         let mut find_fields = Vec::new();
         let mut redraw_fields = Vec::new();
+        let mut area_field = None;
         for field in &mut fields {
             if field.attrs.iter().find(|v| v.name == "walk").is_some(){
                 walk_field = Some(field.name.clone());
@@ -46,8 +48,15 @@ pub fn derive_widget_node_impl(input: TokenStream) ->  TokenStream {
                 deref_field = Some(field.name.clone());
             }
             if field.attrs.iter().find(|v| v.name == "redraw").is_some(){
+                let ty = field.ty.to_string();
+                if area_field.is_none() && ty != "WidgetRef"{
+                    area_field = Some((field.name.clone(), ty == "Area"));
+                }
                 redraw_fields.push(field.name.clone());
             }
+            if field.attrs.iter().find(|v| v.name == "animator").is_some(){
+                animator_field = Some(field.name.clone());
+            }
             if field.attrs.iter().find(|v| v.name == "find").is_some(){
                 find_fields.push(field.name.clone());
             }
@@ -87,8 +96,8 @@ pub fn derive_widget_node_impl(input: TokenStream) ->  TokenStream {
             }
             if find_fields.len()>0{
                 tb.add("    fn find_widgets(&mut self, path: &[LiveId], cached: WidgetCache, results: &mut WidgetSet){");
-                for find_field in find_fields{
-                    tb.add("    self.").ident(&find_field).add(".find_widgets(path, cached, results);");
+                for find_field in &find_fields{
+                    tb.add("    self.").ident(find_field).add(".find_widgets(path, cached, results);");
                 }
                 tb.add("    }");
             }
@@ -98,7 +107,35 @@ pub fn derive_widget_node_impl(input: TokenStream) ->  TokenStream {
             else{
                 tb.add("    fn find_widgets(&mut self, path: &[LiveId], cached: WidgetCache, results: &mut WidgetSet){}");
             }
-            
+            if find_fields.len()>0{
+                tb.add("    fn child_widgets(&self, children: &mut Vec<(LiveId, WidgetRef)>){");
+                for find_field in &find_fields{
+                    tb.add("    children.push((LiveId::from_str(").string(find_field).add("), self.").ident(find_field).add(".clone()));");
+                }
+                tb.add("    }");
+            }
+            else if let Some(deref_field) = &deref_field{
+                tb.add("    fn child_widgets(&self, children: &mut Vec<(LiveId, WidgetRef)>){self.").ident(&deref_field).add(".child_widgets(children)}");
+            }
+            if let Some(deref_field) = &deref_field{
+                tb.add("    fn area(&self) -> Area { WidgetNode::area(&self.").ident(&deref_field).add(")}");
+            }
+            else if let Some((area_field, is_area)) = &area_field{
+                if *is_area{
+                    tb.add("    fn area(&self) -> Area { self.").ident(&area_field).add("}");
+                }
+                else{
+                    tb.add("    fn area(&self) -> Area { self.").ident(&area_field).add(".area()}");
+                }
+            }
+            if let Some(animator_field) = &animator_field{
+                tb.add("    fn animator(&self) -> Option<&Animator> { Some(&self.").ident(&animator_field).add(")}");
+                tb.add("    fn animator_play_delayed(&mut self, cx: &mut Cx, state: &[LiveId;2], delay: f64) { AnimatorImpl::animator_play_delayed(self, cx, state, delay)}");
+            }
+            else if let Some(deref_field) = &deref_field{
+                tb.add("    fn animator(&self) -> Option<&Animator> { self.").ident(&deref_field).add(".animator()}");
+                tb.add("    fn animator_play_delayed(&mut self, cx: &mut Cx, state: &[LiveId;2], delay: f64) { WidgetNode::animator_play_delayed(&mut self.").ident(&deref_field).add(", cx, state, delay)}");
+            }
         }
         tb.add("}");
         return tb.end();
@@ -24,6 +24,8 @@ live_design!{
     import crate::flat_list::FlatListBase;
     import crate::scroll_bars::ScrollBarsBase;
     import crate::view::ViewBase;
+    import crate::view_3d::View3DBase;
+    import crate::view_effect::ViewEffectBase;
     import crate::nav_control::NavControlBase;
     import crate::popup_menu::PopupMenuItemBase;
     import crate::popup_menu::PopupMenuBase;
@@ -123,6 +125,28 @@ live_design!{
         }
     }
     
+    View3D = <View3DBase> {
+        width: Fill
+        height: Fill
+        
+        draw_bg: {
+            texture image: texture2d
+            varying scale: vec2
+            varying shift: vec2
+            fn vertex(self) -> vec4 {
+                let dpi = self.dpi_factor;
+                let ceil_size = ceil(self.rect_size * dpi) / dpi
+                let floor_pos = floor(self.rect_pos * dpi) / dpi
+                self.scale = self.rect_size / ceil_size;
+                self.shift = (self.rect_pos - floor_pos) / ceil_size;
+                return self.clip_and_transform_vertex(self.rect_pos, self.rect_size)
+            }
+            fn pixel(self) -> vec4 {
+                return sample2d_rt(self.image, self.pos * self.scale + self.shift);
+            }
+        }
+    }
+    
     RotatedImage = <RotatedImageBase> {
         width: Fit
         height: Fit
@@ -691,6 +715,8 @@ live_design!{
     KeyboardViewBase = <KeyboardViewBase>{}
     PageFlipBase = <PageFlipBase>{}
     ViewBase = <ViewBase>{}
+    View3DBase = <View3DBase>{}
+    ViewEffectBase = <ViewEffectBase>{}
     ButtonBase = <ButtonBase>{}
     CheckBoxBase = <CheckBoxBase>{}
     DockBase = <DockBase>{}
@@ -38,6 +38,16 @@ pub struct Button {
 
     #[live]
     pub text: RcStringMut,
+
+    // what the button is to assistive technology, widgets built on it can change it
+    #[rust(NavRole::Button)]
+    access_role: NavRole,
+}
+
+impl Button {
+    pub(crate) fn set_access_role(&mut self, role: NavRole) {
+        self.access_role = role;
+    }
 }
 
 impl Widget for Button {
@@ -61,6 +71,22 @@ impl Widget for Button {
             Hit::FingerHoverOut(_) => {
                 self.animator_play(cx, id!(hover.off));
             }
+            Hit::KeyFocus(_) => {
+                self.animator_play(cx, id!(hover.on));
+            }
+            Hit::KeyFocusLost(_) => {
+                self.animator_play(cx, id!(hover.off));
+            }
+            // space and return press a focused button like a click does
+            Hit::KeyDown(ke) if !ke.is_repeat && matches!(ke.key_code, KeyCode::Space | KeyCode::ReturnKey) => {
+                cx.widget_action(uid, &scope.path, ButtonAction::Pressed);
+                self.animator_play(cx, id!(hover.pressed));
+            }
+            Hit::KeyUp(ke) if matches!(ke.key_code, KeyCode::Space | KeyCode::ReturnKey) => {
+                cx.widget_action(uid, &scope.path, ButtonAction::Clicked);
+                cx.widget_action(uid, &scope.path, ButtonAction::Released);
+                self.animator_play(cx, id!(hover.on));
+            }
             Hit::FingerUp(fe) => {
                 if fe.is_over {
                     cx.widget_action(uid, &scope.path, ButtonAction::Clicked);
@@ -85,6 +111,12 @@ impl Widget for Button {
             .draw_walk(cx, self.label_walk, Align::default(), self.text.as_ref());
         self.draw_icon.draw_walk(cx, self.icon_walk);
         self.draw_bg.end(cx);
+        cx.add_nav_stop_with_access(
+            self.draw_bg.area(),
+            self.access_role,
+            Margin::default(),
+            AccessProps::with_name(self.text.as_ref())
+        );
         DrawStep::done()
     }
 
@@ -71,6 +71,23 @@ impl CheckBox {
         self.draw_text.draw_walk(cx, self.label_walk, self.label_align, self.text.as_ref());
         self.draw_icon.draw_walk(cx, self.icon_walk);
         self.draw_check.end(cx);
+        let checked = self.animator_in_state(cx, id!(selected.on));
+        cx.add_nav_stop_with_access(self.draw_check.area(), NavRole::CheckBox, Margin::default(), AccessProps {
+            checked: Some(checked),
+            ..AccessProps::with_name(self.text.as_ref())
+        });
+    }
+    
+    fn toggle(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        let uid = self.widget_uid();
+        if self.animator_in_state(cx, id!(selected.on)) {
+            self.animator_play(cx, id!(selected.off));
+            cx.widget_action(uid, &scope.path, CheckBoxAction::Change(false));
+        }
+        else {
+            self.animator_play(cx, id!(selected.on));
+            cx.widget_action(uid, &scope.path, CheckBoxAction::Change(true));
+        }
     }
 }
 
@@ -95,7 +112,6 @@ impl Widget for CheckBox {
     }
     
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
-        let uid = self.widget_uid();
         self.animator_handle_event(cx, event);
                 
         match event.hits(cx, self.draw_check.area()) {
@@ -106,16 +122,18 @@ impl Widget for CheckBox {
             Hit::FingerHoverOut(_) => {
                 self.animator_play(cx, id!(hover.off));
             },
+            Hit::KeyFocus(_) => {
+                self.animator_play(cx, id!(hover.on));
+            }
+            Hit::KeyFocusLost(_) => {
+                self.animator_play(cx, id!(hover.off));
+            }
             Hit::FingerDown(_fe) => {
-                if self.animator_in_state(cx, id!(selected.on)) {
-                    self.animator_play(cx, id!(selected.off));
-                    cx.widget_action(uid, &scope.path, CheckBoxAction::Change(false));
-                }
-                else {
-                    self.animator_play(cx, id!(selected.on));
-                    cx.widget_action(uid, &scope.path, CheckBoxAction::Change(true));
-                }
+                self.toggle(cx, scope);
             },
+            Hit::KeyDown(ke) if !ke.is_repeat && ke.key_code == KeyCode::Space => {
+                self.toggle(cx, scope);
+            }
             Hit::FingerUp(_fe) => {
                                 
             }
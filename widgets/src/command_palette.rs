@@ -0,0 +1,215 @@
+use {
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw::*,
+        widget::*,
+        text_input::{TextInput, TextInputAction},
+    }
+};
+
+live_design!{
+    DrawPaletteItem = {{DrawPaletteItem}} {
+        instance color_selected: #0000
+
+        fn pixel(self) -> vec4 {
+            return mix(#0000, self.color_selected, self.selected)
+        }
+    }
+
+    CommandPaletteBase = {{CommandPalette}} {}
+}
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawPaletteItem {
+    #[deref] draw_super: DrawQuad,
+    #[live] selected: f32,
+}
+
+// lists the registered commands with their keys, filtered by what is typed, and runs the
+// chosen one. it opens on its own command and draws itself as an overlay
+#[derive(Live, LiveHook, Widget)]
+pub struct CommandPalette {
+    #[redraw] #[rust] area: Area,
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+    #[live] draw_list: DrawList2d,
+    #[live] draw_bg: DrawQuad,
+    #[live] draw_item: DrawPaletteItem,
+    #[live] draw_name: DrawText,
+    #[live] draw_key: DrawText,
+    #[live] item_walk: Walk,
+    #[live] item_layout: Layout,
+    #[live] query: TextInput,
+    // the command that opens the palette
+    #[live] command: LiveId,
+    #[live] top: f64,
+    #[live] max_items: usize,
+    #[rust] is_open: bool,
+    #[rust] matches: Vec<LiveId>,
+    #[rust] selected: usize,
+    #[rust] item_areas: Vec<Area>,
+}
+
+impl CommandPalette {
+    pub fn open(&mut self, cx: &mut Cx) {
+        self.is_open = true;
+        self.query.text.clear();
+        self.update_matches(cx);
+        self.query.set_key_focus(cx);
+        self.redraw(cx);
+    }
+
+    pub fn close(&mut self, cx: &mut Cx) {
+        if !self.is_open {
+            return
+        }
+        self.is_open = false;
+        // give the keys back to whoever had them before the palette opened
+        if cx.has_key_focus(self.query.area()) {
+            cx.revert_key_focus();
+        }
+        self.draw_list.redraw(cx);
+        self.redraw(cx);
+    }
+
+    fn run(&mut self, cx: &mut Cx, command: LiveId) {
+        self.close(cx);
+        cx.send_command(command);
+    }
+
+    fn update_matches(&mut self, cx: &mut Cx) {
+        let query = self.query.text.to_lowercase();
+        self.matches = cx.commands().iter().filter( | info | {
+            let name = info.name.to_lowercase();
+            info.id != self.command && query.split_whitespace().all( | word | name.contains(word))
+        }).map( | info | info.id).take(self.max_items).collect();
+        self.selected = 0;
+    }
+}
+
+impl Widget for CommandPalette {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if let Event::Command(command) = event {
+            if !self.command.is_empty() && *command == self.command {
+                if self.is_open {
+                    self.close(cx);
+                }
+                else {
+                    self.open(cx);
+                }
+            }
+            return
+        }
+        if !self.is_open {
+            return
+        }
+        match event {
+            Event::KeyDown(ke) if cx.has_key_focus(self.query.area()) => match ke.key_code {
+                KeyCode::ArrowUp => {
+                    self.selected = self.selected.saturating_sub(1);
+                    self.draw_list.redraw(cx);
+                    return
+                }
+                KeyCode::ArrowDown => {
+                    if self.selected + 1 < self.matches.len() {
+                        self.selected += 1;
+                    }
+                    self.draw_list.redraw(cx);
+                    return
+                }
+                _ => ()
+            }
+            Event::MouseDown(e) if !self.draw_bg.area().clipped_rect(cx).contains(e.abs) => {
+                self.close(cx);
+                return
+            }
+            _ => ()
+        }
+        for action in cx.capture_actions( | cx | self.query.handle_event(cx, event, scope)) {
+            match action.as_widget_action().cast() {
+                TextInputAction::Change(_) => {
+                    self.update_matches(cx);
+                    self.draw_list.redraw(cx);
+                }
+                TextInputAction::Return(_) => {
+                    if let Some(command) = self.matches.get(self.selected).cloned() {
+                        self.run(cx, command);
+                    }
+                    else {
+                        self.close(cx);
+                    }
+                    return
+                }
+                TextInputAction::Escape | TextInputAction::KeyFocusLost => {
+                    self.close(cx);
+                    return
+                }
+                _ => ()
+            }
+        }
+        for index in 0..self.item_areas.len() {
+            match event.hits(cx, self.item_areas[index]) {
+                Hit::FingerHoverIn(_) => {
+                    self.selected = index;
+                    self.draw_list.redraw(cx);
+                }
+                Hit::FingerDown(_) => {
+                    if let Some(command) = self.matches.get(index).cloned() {
+                        self.run(cx, command);
+                    }
+                    return
+                }
+                _ => ()
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, _walk: Walk) -> DrawStep {
+        // takes no room in its parent, the area only serves to get redrawn
+        cx.walk_turtle_with_area(&mut self.area, Walk::size(Size::Fixed(0.0), Size::Fixed(0.0)));
+        self.item_areas.clear();
+        if !self.is_open {
+            return DrawStep::done()
+        }
+        self.draw_list.begin_overlay_reuse(cx);
+        let pass_size = cx.current_pass_size();
+        let width = self.walk.width.fixed_or_zero();
+        cx.begin_pass_sized_turtle(Layout {
+            padding: Padding {left: ((pass_size.x - width) * 0.5).max(0.0), top: self.top, ..Default::default()},
+            ..Layout::flow_down()
+        });
+        self.draw_bg.begin(cx, self.walk, self.layout);
+        let query_walk = self.query.walk(cx);
+        self.query.draw_walk_text_input(cx, query_walk);
+        for (index, command) in self.matches.iter().enumerate() {
+            self.draw_item.selected = if index == self.selected {1.0} else {0.0};
+            self.draw_item.begin(cx, self.item_walk, self.item_layout);
+            let name = cx.command_name(*command).unwrap_or("").to_string();
+            self.draw_name.draw_walk(cx, Walk::fit(), Align::default(), &name);
+            if let Some(label) = cx.command_key_label(*command) {
+                self.draw_key.draw_walk(cx, Walk::fill_fit(), Align {x: 1.0, y: 0.0}, &label);
+            }
+            self.draw_item.end(cx);
+            self.item_areas.push(self.draw_item.area());
+        }
+        self.draw_bg.end(cx);
+        cx.end_pass_sized_turtle();
+        self.draw_list.end(cx);
+        DrawStep::done()
+    }
+}
+
+impl CommandPaletteRef {
+    pub fn open(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.open(cx);
+        }
+    }
+
+    pub fn close(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.close(cx);
+        }
+    }
+}
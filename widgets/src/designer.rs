@@ -1,192 +1,339 @@
-use crate::{
-    makepad_derive_widget::*,
-    makepad_draw::*,
-    file_tree::*,
-    view::View,
-    widget::*,
+use {
+    std::ops::Range,
+    crate::{
+        makepad_derive_widget::*,
+        makepad_draw::*,
+        designer_outline::{parse_outline, OutlineNode},
+        file_tree::*,
+        check_box::*,
+        portal_list::*,
+        text_input::*,
+        view::*,
+        widget::*,
+    },
 };
 
 live_design!{
     import makepad_widgets::base::*
     import makepad_widgets::theme_desktop_dark::*
     import makepad_draw::shader::std::*
-    
+
     Designer = {{Designer}} {
-        has_view: true,
-        flow: Right
-        container: <RoundedView> {
-            draw_bg: {color: #3}
-            width: Fill, height: 400
-            flow: Down, spacing: 10, padding:10
-            <RoundedView>{
-                width: Fill, height: Fit
-                padding:5
-                draw_bg:{color:#5}
-                label = <Label> {text: "HI", draw_text:{color:#f}}
-            }
+        width: Fill, height: Fill
+        flow: Down
+        show_bg: true
+        draw_bg: {color: #x28}
+        <View> {
+            width: Fill, height: Fit
+            flow: Right, padding: 10, spacing: 10
+            align: {y: 0.5}
+            title = <Label> {width: Fill, draw_text: {color: #a}, text: "Open a file with a live_design! block to design it"}
+            pick = <CheckBox> {text: "Pick in app"}
         }
         <Splitter> {
-            align: FromStart(300),
+            axis: Vertical
+            align: FromStart(300)
             a: <View> {
-                outline = <FileTree> {
-                }
-            },
-            b: <CachedScrollXY> {
-                dpi_factor: 1.5
-                draw_bg: {color: #4}
-                width: Fill, height: Fill
+                outline = <FileTree> {}
+            }
+            b: <View> {
                 flow: Down
-            },
+                <View> {
+                    width: Fill, height: Fit
+                    padding: 10
+                    node = <Label> {draw_text: {color: #a}, text: ""}
+                }
+                inspector = <PortalList> {
+                    drag_scrolling: false
+                    width: Fill, height: Fill
+                    flow: Down
+                    Property = <View> {
+                        width: Fill, height: Fit
+                        flow: Right, spacing: 5
+                        padding: {left: 10, right: 10, top: 2, bottom: 2}
+                        align: {y: 0.5}
+                        name = <Label> {width: 100, draw_text: {color: #a}}
+                        swatch = <RoundedView> {width: 14, height: 14, draw_bg: {radius: 2.0}}
+                        value = <TextInput> {
+                            width: Fill
+                            draw_bg: {
+                                fn pixel(self) -> vec4 {
+                                    return #x00000044
+                                }
+                            }
+                            empty_message: "Not set"
+                        }
+                    }
+                }
+            }
         }
     }
 }
 
-#[allow(dead_code)]
-enum OutlineNode {
-    Global {
-        uid: FileNodeId,
-        name: LiveId,
-        ptr: LivePtr
-    },
-    Component {
-        uid: FileNodeId,
-        name: LiveId,
-        class: LiveId,
-        prop_type: LivePropType,
-        ptr: LivePtr,
-        children: Vec<OutlineNode>
-    }
+// the layout properties that widgets offer in the inspector, also when they are not set yet
+const WIDGET_PROPERTIES: [&str; 7] = ["width", "height", "margin", "padding", "flow", "spacing", "align"];
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum DesignerAction {
+    // replace this byte range of the source with the text
+    Edit(Range<usize>, String),
+    // a node was selected in the outline, this is the line it starts on
+    NodeSelected(usize),
+    PickModeChanged(bool),
+    None
 }
 
-#[derive(Live, Widget)]
+// a row of the inspector, which is a property of the selected node
+struct InspectorRow {
+    name: String,
+    value: String,
+    color: Option<Vec4>,
+    // the text input still shows an older value
+    needs_text: bool,
+}
+
+// an outline of the live_design! blocks in a source file, with an inspector for the properties
+// of the selected node. edits are not applied to the source here, they are sent as actions
+// so that whoever owns the source can apply them and hand back the new source
+#[derive(Live, LiveHook, Widget)]
 pub struct Designer {
-    #[live] container: Option<LivePtr>,
-    #[rust] outline_nodes: Vec<OutlineNode>,
-    #[rust] components: ComponentMap<LivePtr, (WidgetRef, WidgetRef)>,
-    #[deref] ui: View,
+    #[deref] view: View,
+    #[rust] title: String,
+    #[rust] source: String,
+    #[rust] outline: Vec<OutlineNode>,
+    #[rust] selected: Option<Vec<usize>>,
+    // a line to select once the source it belongs to has been set
+    #[rust] pending_line: Option<usize>,
+    #[rust] rows: Vec<InspectorRow>,
 }
 
-impl LiveHook for Designer {
-    
-    fn after_new_from_doc(&mut self, cx: &mut Cx) {
-        // lets take the doc we need (app_mobile for instance)
-        let live_registry_rc = cx.live_registry.clone();
-        let live_registry = &*live_registry_rc.borrow();
-        let file_id = live_registry.file_name_to_file_id("examples/ironfish/src/app_desktop.rs").unwrap();
-        // now we fetch the unexpanded nodes
-        // and build a list
-        let file = live_registry.file_id_to_file(file_id);
-        let nodes = &file.expanded.nodes;
-        // lets run over the file
-        fn recur_walk(live_registry: &LiveRegistry, base_ptr: LivePtr, mut index: usize, nodes: &[LiveNode], out: &mut Vec<OutlineNode>) -> usize {
-            while index < nodes.len() - 1 {
-                if let LiveValue::Class {class_parent, ..} = &nodes[index].value {
-                    // lets emit a class at our level
-                    let mut children = Vec::new();
-                    let name = nodes[index].id;
-                    let class = live_registry.ptr_to_node(class_parent.unwrap()).id;
-                    let ptr = base_ptr.with_index(index);
-                    index = recur_walk(live_registry, base_ptr, index + 1, nodes, &mut children);
-                    out.insert(0, OutlineNode::Component {
-                        uid: LiveId::unique().into(),
-                        name,
-                        prop_type: nodes[index].origin.prop_type(),
-                        class,
-                        ptr,
-                        children
-                    });
-                }
-                else if nodes[index].value.is_close() {
-                    return index + 1;
+impl Designer {
+    pub fn set_source(&mut self, cx: &mut Cx, title: &str, source: &str) {
+        if self.title == title && self.source == source && self.pending_line.is_none() {
+            return
+        }
+        if self.title != title {
+            self.selected = None;
+        }
+        self.title = title.to_string();
+        self.source = source.to_string();
+        self.outline = parse_outline(source);
+        if let Some(line) = self.pending_line.take() {
+            self.selected = OutlineNode::path_of_line(&self.outline, line);
+            self.open_selected(cx);
+        }
+        if self.selected.as_ref().is_some_and( | path | OutlineNode::find(&self.outline, path).is_none()) {
+            self.selected = None;
+        }
+        self.update_rows();
+        self.view.redraw(cx);
+    }
+
+    pub fn select_line(&mut self, cx: &mut Cx, line: usize) {
+        self.pending_line = Some(line);
+        self.view.redraw(cx);
+    }
+
+    fn node_id(&self, path: &[usize]) -> FileNodeId {
+        let mut id = LiveId::from_str(&self.title);
+        for index in path {
+            id = id.bytes_append(&index.to_be_bytes());
+        }
+        FileNodeId(id)
+    }
+
+    fn path_of_node_id(&self, node_id: FileNodeId) -> Option<Vec<usize>> {
+        fn find(designer: &Designer, nodes: &[OutlineNode], path: &mut Vec<usize>, node_id: FileNodeId) -> bool {
+            for (index, node) in nodes.iter().enumerate() {
+                path.push(index);
+                if designer.node_id(path) == node_id || find(designer, &node.children, path, node_id) {
+                    return true
                 }
-                else {
-                    index = nodes.skip_node(index);
+                path.pop();
+            }
+            false
+        }
+        let mut path = Vec::new();
+        find(self, &self.outline, &mut path, node_id).then_some(path)
+    }
+
+    // opens the folders in the outline that the selected node is in
+    fn open_selected(&mut self, cx: &mut Cx) {
+        let Some(path) = self.selected.clone() else {
+            return
+        };
+        let outline = self.view.file_tree(id!(outline));
+        if let Some(mut outline) = outline.borrow_mut() {
+            for len in 1..=path.len() {
+                outline.set_folder_is_open(cx, self.node_id(&path[..len]), true, Animate::No);
+            }
+        };
+    }
+
+    fn selected_node(&self) -> Option<&OutlineNode> {
+        OutlineNode::find(&self.outline, self.selected.as_ref()?)
+    }
+
+    fn update_rows(&mut self) {
+        let Some(node) = self.selected_node() else {
+            self.rows.clear();
+            return
+        };
+        let mut rows: Vec<InspectorRow> = node.properties.iter().map( | property | InspectorRow {
+            name: property.name.clone(),
+            value: property.value.clone(),
+            color: property.color,
+            needs_text: true,
+        }).collect();
+        if node.class.is_some() {
+            for name in WIDGET_PROPERTIES {
+                if node.property(name).is_none() {
+                    rows.push(InspectorRow {name: name.to_string(), value: String::new(), color: None, needs_text: true});
                 }
             }
-            index
         }
-        let base_ptr = live_registry.file_id_index_to_live_ptr(file_id, 0);
-        recur_walk(live_registry, base_ptr, 1, nodes, &mut self.outline_nodes);
+        else if node.name.as_ref().is_some_and( | name | name.starts_with("draw_")) && node.property("color").is_none() {
+            rows.push(InspectorRow {name: "color".to_string(), value: String::new(), color: None, needs_text: true});
+        }
+        self.rows = rows;
     }
-    // ok now we can iterate our top level components
-    // and instance them
-}
 
-impl Designer {
-    
-    fn draw_design(&mut self, cx: &mut Cx2d) {
-        // alrigh so. lets draw the designs
-        let mut count = 0;
-        for node in &self.outline_nodes {
-            if let OutlineNode::Component {ptr, name, class, ..} = node {
-                count += 1;
-                if count > 5{
-                    break;
+    fn draw_outline(&self, cx: &mut Cx2d, outline: &mut FileTree) {
+        fn draw_nodes(designer: &Designer, cx: &mut Cx2d, outline: &mut FileTree, nodes: &[OutlineNode], path: &mut Vec<usize>) {
+            for (index, node) in nodes.iter().enumerate() {
+                path.push(index);
+                let node_id = designer.node_id(path);
+                if node.children.is_empty() {
+                    outline.file(cx, node_id, &node.label());
                 }
-                let container_ptr = self.container;
-                let (widget, container) = self.components.get_or_insert(cx, *ptr, | cx | {
-                    (
-                        WidgetRef::new_from_ptr(cx, Some(*ptr)),
-                        WidgetRef::new_from_ptr(cx, container_ptr),
-                    )
-                });
-                container.widget(id!(label)).set_text(&format!("{}=<{}>", name, class));
-                // lets draw this thing in a neat little container box with a title bar
-                while let Some(_) = container.draw(cx, &mut Scope::empty()).step() {
-                    widget.draw_all(cx, &mut Scope::empty());
+                else if outline.begin_folder(cx, node_id, &node.label()).is_ok() {
+                    draw_nodes(designer, cx, outline, &node.children, path);
+                    outline.end_folder();
                 }
+                path.pop();
             }
         }
-        
-    }
-    
-    fn draw_outline(&mut self, cx: &mut Cx2d, outline: &mut FileTree) {
-        fn recur_walk(cx: &mut Cx2d, outline: &mut FileTree, children: &[OutlineNode]) {
-            for child in children {
-                match child {
-                    OutlineNode::Global {..} => {}
-                    OutlineNode::Component {name, children, uid, class, prop_type, ..} => {
-                        if outline.begin_folder(cx, *uid, &if !name.is_unique(){
-                            if let LivePropType::Field = prop_type {
-                                format!("{}: <{}>", name, class)
-                            }
-                            else {
-                                format!("{}=<{}>", name, class)
-                            }
-                        }else {
-                            format!("<{}>", class)
-                        }).is_ok() {
-                            recur_walk(cx, outline, children);
-                            outline.end_folder();
-                        }
-                    }
-                }
+        draw_nodes(self, cx, outline, &self.outline, &mut Vec::new());
+    }
+
+    fn draw_inspector(&mut self, cx: &mut Cx2d, inspector: &mut PortalList) {
+        inspector.set_item_range(cx, 0, self.rows.len());
+        while let Some(item_id) = inspector.next_visible_item(cx) {
+            let Some(row) = self.rows.get_mut(item_id) else {
+                continue
+            };
+            let Some((item, existed)) = inspector.item_with_existed(cx, item_id, live_id!(Property)) else {
+                continue
+            };
+            let item = item.as_view();
+            item.apply_over(cx, live!{
+                name = {text: (&row.name)}
+                swatch = {visible: (row.color.is_some()), draw_bg: {color: (row.color.unwrap_or_default())}}
+            });
+            // the text is only set when the value changed, so that it does not replace what is being typed
+            if row.needs_text || !existed {
+                item.text_input(id!(value)).set_text(&row.value);
+                row.needs_text = false;
             }
+            item.draw_all(cx, &mut Scope::empty());
         }
-        recur_walk(cx, outline, &self.outline_nodes);
     }
-    
 }
 
 impl Widget for Designer {
-    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope){
-        self.ui.handle_event(cx, event, scope);
-        for (component, container) in self.components.values_mut() {
-            component.handle_event(cx, event, scope);
-            container.handle_event(cx, event, scope);
-        }
-    }
-    
-    fn draw_walk(&mut self, cx: &mut Cx2d, scope:&mut Scope, _walk: Walk) -> DrawStep {
-        let outline = self.ui.file_tree(id!(outline));
-        while let Some(next) = self.ui.draw(cx, scope).step() {
-            if let Some(mut outline) = outline.has_widget(&next).borrow_mut() {
-                self.draw_outline(cx, &mut *outline);
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        let title = if self.title.is_empty() {
+            "Open a file with a live_design! block to design it".to_string()
+        }
+        else if self.outline.is_empty() {
+            format!("{} has no live_design! block", self.title)
+        }
+        else {
+            self.title.clone()
+        };
+        self.view.widget(id!(title)).set_text(&title);
+        let node = match self.selected_node() {
+            Some(node) => format!("{}, line {}", node.label(), node.line + 1),
+            None => String::new(),
+        };
+        self.view.widget(id!(node)).set_text(&node);
+        while let Some(step) = self.view.draw_walk(cx, scope, walk).step() {
+            if let Some(mut outline) = step.as_file_tree().borrow_mut() {
+                self.draw_outline(cx, &mut outline);
             }
-            else if next == self.ui.widget(id!(design)) {
-                self.draw_design(cx);
+            else if let Some(mut inspector) = step.as_portal_list().borrow_mut() {
+                self.draw_inspector(cx, &mut inspector);
             }
         }
         DrawStep::done()
     }
+
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        let uid = self.widget_uid();
+        let outline = self.view.file_tree(id!(outline));
+        let inspector = self.view.portal_list(id!(inspector));
+        self.view.handle_event(cx, event, scope);
+        let Event::Actions(actions) = event else {
+            return
+        };
+        if let Some(enabled) = self.view.check_box(id!(pick)).changed(actions) {
+            cx.widget_action(uid, &scope.path, DesignerAction::PickModeChanged(enabled));
+        }
+        if let Some(item) = actions.find_widget_action(outline.widget_uid()) {
+            if let FileTreeAction::FileClicked(node_id) | FileTreeAction::FolderClicked(node_id) = item.cast() {
+                self.selected = self.path_of_node_id(node_id);
+                self.update_rows();
+                if let Some(node) = self.selected_node() {
+                    cx.widget_action(uid, &scope.path, DesignerAction::NodeSelected(node.line));
+                }
+                self.view.redraw(cx);
+            }
+        }
+        for (item_id, item) in inspector.items_with_actions(actions) {
+            let Some(value) = item.text_input(id!(value)).returned(actions) else {
+                continue
+            };
+            let (Some(node), Some(row)) = (self.selected_node(), self.rows.get(item_id)) else {
+                continue
+            };
+            if let Some((range, text)) = node.set_property_edit(&self.source, &row.name, &value) {
+                cx.widget_action(uid, &scope.path, DesignerAction::Edit(range, text));
+            }
+        }
+    }
+}
+
+impl DesignerRef {
+    pub fn set_source(&self, cx: &mut Cx, title: &str, source: &str) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_source(cx, title, source);
+        }
+    }
+
+    pub fn select_line(&self, cx: &mut Cx, line: usize) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.select_line(cx, line);
+        }
+    }
+
+    pub fn edited(&self, actions: &Actions) -> Option<(Range<usize>, String)> {
+        if let DesignerAction::Edit(range, text) = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some((range, text))
+        }
+        None
+    }
+
+    pub fn node_selected(&self, actions: &Actions) -> Option<usize> {
+        if let DesignerAction::NodeSelected(line) = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some(line)
+        }
+        None
+    }
+
+    pub fn pick_mode_changed(&self, actions: &Actions) -> Option<bool> {
+        if let DesignerAction::PickModeChanged(enabled) = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some(enabled)
+        }
+        None
+    }
 }
@@ -0,0 +1,520 @@
+use {
+    std::ops::Range,
+    crate::{
+        makepad_draw::*,
+        makepad_platform::makepad_live_tokenizer::{Cursor, FullToken, State},
+    },
+};
+
+// a node in the live_design! blocks of a source file, such as `name = <View> {...}`,
+// `<Button> {...}` or `draw_bg: {...}`, with the properties that are set on it directly.
+// ranges are byte ranges into the source, lines start at 0
+#[derive(Clone, Debug, Default)]
+pub struct OutlineNode {
+    pub name: Option<String>,
+    // `<View>`, `{{App}}` or an enum variant like `Splitter`
+    pub class: Option<String>,
+    // the node is written as `name: ...` instead of `name = ...`
+    pub is_field: bool,
+    pub line: usize,
+    pub end_line: usize,
+    // the text between the braces
+    pub body: Range<usize>,
+    pub properties: Vec<OutlineProperty>,
+    pub children: Vec<OutlineNode>,
+}
+
+#[derive(Clone, Debug)]
+pub struct OutlineProperty {
+    pub name: String,
+    pub value: String,
+    pub color: Option<Vec4>,
+    pub line: usize,
+    // all of `name: value`
+    pub range: Range<usize>,
+    pub value_range: Range<usize>,
+}
+
+impl OutlineNode {
+    pub fn label(&self) -> String {
+        let assign = if self.is_field {":"} else {" ="};
+        match (&self.name, &self.class) {
+            (Some(name), Some(class)) => format!("{}{} {}", name, assign, class),
+            (Some(name), None) => format!("{}{} {{}}", name, assign),
+            (None, Some(class)) => class.clone(),
+            (None, None) => "{}".to_string(),
+        }
+    }
+
+    pub fn property(&self, name: &str) -> Option<&OutlineProperty> {
+        self.properties.iter().find( | property | property.name == name)
+    }
+
+    // the node at the given path of child indices
+    pub fn find<'a>(nodes: &'a [OutlineNode], path: &[usize]) -> Option<&'a OutlineNode> {
+        let (first, rest) = path.split_first()?;
+        let node = nodes.get(*first)?;
+        if rest.is_empty() {
+            Some(node)
+        }
+        else {
+            Self::find(&node.children, rest)
+        }
+    }
+
+    // the path of the innermost node that the given line is part of
+    pub fn path_of_line(nodes: &[OutlineNode], line: usize) -> Option<Vec<usize>> {
+        let index = nodes.iter().position( | node | node.line <= line && line <= node.end_line)?;
+        let mut path = vec![index];
+        if let Some(rest) = Self::path_of_line(&nodes[index].children, line) {
+            path.extend(rest);
+        }
+        Some(path)
+    }
+
+    // the edit that sets the property `name` to `value`, which inserts the property if it is not
+    // set yet, or removes it if `value` is empty
+    pub fn set_property_edit(&self, source: &str, name: &str, value: &str) -> Option<(Range<usize>, String)> {
+        let value = value.trim();
+        if let Some(property) = self.property(name) {
+            if value.is_empty() {
+                return Some((removal_range(source, property.range.clone()), String::new()))
+            }
+            return Some((property.value_range.clone(), value.to_string()))
+        }
+        if value.is_empty() {
+            return None
+        }
+        let body = &source[self.body.clone()];
+        if !body.contains('\n') {
+            let trimmed = body.trim_end();
+            if trimmed.trim_start().is_empty() {
+                return Some((self.body.clone(), format!("{}: {}", name, value)))
+            }
+            let separator = if trimmed.ends_with(',') {" "} else {", "};
+            let end = self.body.start + trimmed.len();
+            return Some((end..end, format!("{}{}: {}", separator, name, value)))
+        }
+        // multiline bodies get the property on a line of its own, after the other properties
+        let first_item = self.body.start + body.len() - body.trim_start().len();
+        let indent = if source[self.body.start..first_item].contains('\n') {
+            line_indent(source, first_item).to_string()
+        }
+        else {
+            format!("{}    ", line_indent(source, self.body.start))
+        };
+        let at = self.properties.last().map( | property | property.range.end).unwrap_or(self.body.start);
+        Some((at..at, format!("\n{}{}: {}", indent, name, value)))
+    }
+}
+
+// the whitespace at the start of the line that `index` is on
+fn line_indent(source: &str, index: usize) -> &str {
+    let line_start = source[..index].rfind('\n').map( | at | at + 1).unwrap_or(0);
+    let line = &source[line_start..];
+    &line[..line.len() - line.trim_start().len()]
+}
+
+// removes a property with the comma after it, or the one before it if it is the last one on
+// its line, and its whole line if nothing else is on it
+fn removal_range(source: &str, range: Range<usize>) -> Range<usize> {
+    let skip_spaces = | at: usize | at + source[at..].len() - source[at..].trim_start_matches([' ', '\t']).len();
+    let mut start = range.start;
+    let mut end = skip_spaces(range.end);
+    if source[end..].starts_with(',') {
+        end = skip_spaces(end + 1);
+    }
+    else if source[..start].trim_end_matches([' ', '\t']).ends_with(',') {
+        start = source[..start].trim_end_matches([' ', '\t']).len() - 1;
+    }
+    let line_start = source[..range.start].rfind('\n').map( | at | at + 1).unwrap_or(0);
+    let line_end = source[end..].find('\n').map( | at | end + at + 1).unwrap_or(source.len());
+    if source[line_start..range.start].trim().is_empty() && source[end..line_end].trim().is_empty() {
+        line_start..line_end
+    }
+    else {
+        start..end
+    }
+}
+
+struct Token {
+    token: FullToken,
+    range: Range<usize>,
+    line: usize,
+    // a line ends between this token and the one before it
+    after_newline: bool,
+}
+
+// finds the nodes in all live_design! blocks of the source. only the structure is parsed,
+// shader functions and declarations like `instance hover: 0.0` are skipped
+pub fn parse_outline(source: &str) -> Vec<OutlineNode> {
+    let mut parser = Parser {
+        source,
+        tokens: tokenize(source),
+        index: 0,
+    };
+    let mut nodes = Vec::new();
+    while parser.index + 2 < parser.tokens.len() {
+        if parser.text(parser.index) == "live_design"
+            && parser.is_punct(parser.index + 1, "!")
+            && parser.is_open_brace(parser.index + 2) {
+            let mut root = OutlineNode::default();
+            parser.index += 3;
+            parser.parse_body(&mut root);
+            nodes.extend(root.children);
+        }
+        else {
+            parser.index += 1;
+        }
+    }
+    nodes
+}
+
+fn tokenize(source: &str) -> Vec<Token> {
+    let chars: Vec<char> = source.chars().collect();
+    let byte_offsets: Vec<usize> = source.char_indices().map( | (at, _) | at).chain([source.len()]).collect();
+    let mut scratch = String::new();
+    let mut cursor = Cursor::new(&chars, &mut scratch);
+    let mut state = State::default();
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut line = 0;
+    let mut after_newline = false;
+    loop {
+        let (next_state, token) = state.next(&mut cursor);
+        let Some(token) = token else {
+            break
+        };
+        let range = byte_offsets[start]..byte_offsets[start + token.len];
+        let newlines = source[range.clone()].matches('\n').count();
+        if token.is_ws_or_comment() {
+            after_newline |= newlines > 0;
+        }
+        else {
+            tokens.push(Token {token: token.token, range, line, after_newline});
+            after_newline = false;
+        }
+        line += newlines;
+        start += token.len;
+        state = next_state;
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn text(&self, index: usize) -> &'a str {
+        self.tokens.get(index).map( | token | &self.source[token.range.clone()]).unwrap_or("")
+    }
+
+    fn is_punct(&self, index: usize, punct: &str) -> bool {
+        matches!(self.tokens.get(index), Some(Token {token: FullToken::Punct(_), ..})) && self.text(index) == punct
+    }
+
+    fn is_ident(&self, index: usize) -> bool {
+        matches!(self.tokens.get(index), Some(Token {token: FullToken::Ident(_), ..}))
+    }
+
+    fn is_open_brace(&self, index: usize) -> bool {
+        matches!(self.tokens.get(index), Some(token) if token.token.is_open() && self.text(index) == "{")
+    }
+
+    fn is_close(&self, index: usize) -> bool {
+        matches!(self.tokens.get(index), Some(token) if token.token.is_close())
+    }
+
+    // parses the items of a body up to and including its closing brace
+    fn parse_body(&mut self, node: &mut OutlineNode) {
+        node.body.start = self.tokens.get(self.index - 1).map( | token | token.range.end).unwrap_or(0);
+        while let Some(token) = self.tokens.get(self.index) {
+            let line = token.line;
+            if token.token.is_close() {
+                node.body.end = token.range.start;
+                node.end_line = line;
+                self.index += 1;
+                return
+            }
+            if token.token.is_open() {
+                self.skip_group();
+            }
+            else if self.is_punct(self.index, "<") {
+                self.parse_value(node, None, false, line, token.range.start);
+            }
+            else if self.is_ident(self.index) {
+                let name = self.text(self.index);
+                let start = token.range.start;
+                if name == "fn" {
+                    self.skip_fn();
+                }
+                else if name == "import" {
+                    self.skip_line();
+                }
+                else if self.is_punct(self.index + 1, ":") || self.is_punct(self.index + 1, "=") {
+                    let is_field = self.is_punct(self.index + 1, ":");
+                    self.index += 2;
+                    self.parse_value(node, Some(name.to_string()), is_field, line, start);
+                }
+                else if self.is_ident(self.index + 1) {
+                    // declarations like `instance hover: 0.0` or `uniform color: #f`
+                    self.skip_line();
+                }
+                else {
+                    self.index += 1;
+                }
+            }
+            else {
+                self.index += 1;
+            }
+        }
+        node.body.end = self.source.len();
+    }
+
+    fn parse_value(&mut self, node: &mut OutlineNode, name: Option<String>, is_field: bool, line: usize, start: usize) {
+        let mut child = OutlineNode {
+            name,
+            is_field,
+            line,
+            ..Default::default()
+        };
+        if self.is_punct(self.index, "<") {
+            let class_start = self.index;
+            while self.index < self.tokens.len() && !self.is_punct(self.index, ">") {
+                self.index += 1;
+            }
+            let class = (class_start + 1..self.index).map( | index | self.text(index)).collect::<String>();
+            child.class = Some(format!("<{}>", class));
+            self.index += 1;
+        }
+        else if self.is_open_brace(self.index) && self.is_open_brace(self.index + 1) {
+            child.class = Some(format!("{{{{{}}}}}", self.text(self.index + 2)));
+            self.index += 5;
+        }
+        else if self.is_ident(self.index) && self.is_open_brace(self.index + 1) && !self.tokens[self.index + 1].after_newline {
+            child.class = Some(self.text(self.index).to_string());
+            self.index += 1;
+        }
+        if self.is_open_brace(self.index) {
+            self.index += 1;
+            self.parse_body(&mut child);
+            node.children.push(child);
+            return
+        }
+        let Some(name) = child.name else {
+            return
+        };
+        // a plain value ends at a comma or at the end of the line, outside of any parentheses
+        let value_start = self.index;
+        while self.index < self.tokens.len() {
+            if self.index > value_start && self.tokens[self.index].after_newline
+                || self.is_punct(self.index, ",")
+                || self.is_punct(self.index, ";")
+                || self.is_close(self.index) {
+                break
+            }
+            if self.tokens[self.index].token.is_open() {
+                self.skip_group();
+            }
+            else {
+                self.index += 1;
+            }
+        }
+        if self.index == value_start {
+            return
+        }
+        let value_range = self.tokens[value_start].range.start..self.tokens[self.index - 1].range.end;
+        let color = match self.tokens[value_start].token {
+            FullToken::Color(color) if self.index == value_start + 1 => Some(Vec4::from_u32(color)),
+            _ => None
+        };
+        node.properties.push(OutlineProperty {
+            name,
+            value: self.source[value_range.clone()].to_string(),
+            color,
+            line,
+            range: start..value_range.end,
+            value_range,
+        });
+    }
+
+    // skips from an opening delimiter to after the one that closes it
+    fn skip_group(&mut self) {
+        let mut depth = 0;
+        while let Some(token) = self.tokens.get(self.index) {
+            self.index += 1;
+            if token.token.is_open() {
+                depth += 1;
+            }
+            else if token.token.is_close() {
+                depth -= 1;
+                if depth == 0 {
+                    return
+                }
+            }
+        }
+    }
+
+    fn skip_fn(&mut self) {
+        while self.index < self.tokens.len() && !self.is_open_brace(self.index) {
+            if self.is_close(self.index) {
+                return
+            }
+            if self.tokens[self.index].token.is_open() {
+                self.skip_group();
+            }
+            else {
+                self.index += 1;
+            }
+        }
+        self.skip_group();
+    }
+
+    fn skip_line(&mut self) {
+        self.index += 1;
+        while self.index < self.tokens.len() && !self.tokens[self.index].after_newline && !self.is_close(self.index) {
+            if self.is_punct(self.index, ";") || self.is_punct(self.index, ",") {
+                self.index += 1;
+                return
+            }
+            self.index += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const SOURCE: &str = r#"use makepad_widgets::*;
+
+live_design!{
+    import makepad_widgets::base::*;
+    
+    App = {{App}} {
+        ui: <Window> {
+            body = <View> {
+                flow: Down, spacing: 10
+                draw_bg: {
+                    instance hover: 0.0
+                    color: #f00
+                    fn pixel(self) -> vec4 {
+                        return self.color
+                    }
+                }
+                button = <Button> {text: "Hi"}
+                empty = <View> {}
+                <Label> {
+                    text: "Label"
+                    margin: {top: 5}
+                }
+            }
+        }
+    }
+}
+"#;
+    
+    fn node(path: &[usize]) -> OutlineNode {
+        OutlineNode::find(&parse_outline(SOURCE), path).unwrap().clone()
+    }
+    
+    fn set_property(path: &[usize], name: &str, value: &str) -> String {
+        let (range, text) = node(path).set_property_edit(SOURCE, name, value).unwrap();
+        let mut source = SOURCE.to_string();
+        source.replace_range(range, &text);
+        source
+    }
+    
+    // the line of the source that contains `text`
+    fn line_of(text: &str) -> usize {
+        SOURCE.lines().position( | line | line.contains(text)).unwrap()
+    }
+    
+    #[test]
+    fn parses_the_nodes_and_properties() {
+        let outline = parse_outline(SOURCE);
+        assert_eq!(outline.len(), 1);
+        assert_eq!(outline[0].label(), "App = {{App}}");
+        assert_eq!(node(&[0, 0]).label(), "ui: <Window>");
+        let body = node(&[0, 0, 0]);
+        assert_eq!(body.label(), "body = <View>");
+        assert_eq!((body.line, body.end_line), (line_of("body ="), line_of("body =") + 15));
+        let labels: Vec<_> = body.children.iter().map( | child | child.label()).collect();
+        assert_eq!(labels, ["draw_bg: {}", "button = <Button>", "empty = <View>", "<Label>"]);
+        let properties: Vec<_> = body.properties.iter().map( | property | (property.name.as_str(), property.value.as_str())).collect();
+        assert_eq!(properties, [("flow", "Down"), ("spacing", "10")]);
+        assert_eq!(&SOURCE[body.property("spacing").unwrap().range.clone()], "spacing: 10");
+        
+        // declarations and shader functions are not properties
+        let draw_bg = node(&[0, 0, 0, 0]);
+        assert_eq!(draw_bg.properties.len(), 1);
+        assert_eq!(draw_bg.property("color").unwrap().color, Some(Vec4::from_u32(0xff0000ff)));
+        assert!(draw_bg.children.is_empty());
+        assert_eq!(node(&[0, 0, 0, 1]).property("text").unwrap().value, "\"Hi\"");
+        assert_eq!(node(&[0, 0, 0, 3]).children[0].label(), "margin: {}");
+    }
+    
+    #[test]
+    fn finds_the_node_of_a_line() {
+        let outline = parse_outline(SOURCE);
+        assert_eq!(OutlineNode::path_of_line(&outline, line_of("text: \"Hi\"")), Some(vec![0, 0, 0, 1]));
+        assert_eq!(OutlineNode::path_of_line(&outline, line_of("return self.color")), Some(vec![0, 0, 0, 0]));
+        assert_eq!(OutlineNode::path_of_line(&outline, line_of("top: 5")), Some(vec![0, 0, 0, 3, 0]));
+        assert_eq!(OutlineNode::path_of_line(&outline, line_of("spacing")), Some(vec![0, 0, 0]));
+        assert_eq!(OutlineNode::path_of_line(&outline, line_of("import")), None);
+        assert!(OutlineNode::find(&outline, &[0, 0, 0, 9]).is_none());
+    }
+    
+    #[test]
+    fn changes_properties_in_place() {
+        let source = set_property(&[0, 0, 0], "spacing", " 20 ");
+        assert_eq!(source, SOURCE.replace("spacing: 10", "spacing: 20"));
+        let source = set_property(&[0, 0, 0, 0], "color", "#0f0");
+        assert_eq!(source, SOURCE.replace("color: #f00", "color: #0f0"));
+    }
+    
+    #[test]
+    fn inserts_properties_into_single_line_bodies() {
+        let source = set_property(&[0, 0, 0, 1], "margin", "5");
+        assert_eq!(source, SOURCE.replace("{text: \"Hi\"}", "{text: \"Hi\", margin: 5}"));
+        let source = set_property(&[0, 0, 0, 2], "width", "Fill");
+        assert_eq!(source, SOURCE.replace("<View> {}", "<View> {width: Fill}"));
+    }
+    
+    #[test]
+    fn inserts_properties_on_a_line_of_their_own_into_multiline_bodies() {
+        // after the last property, before the child nodes
+        let source = set_property(&[0, 0, 0, 3], "width", "Fill");
+        assert_eq!(source, SOURCE.replace(
+            "text: \"Label\"\n",
+            "text: \"Label\"\n                    width: Fill\n"
+        ));
+        let source = set_property(&[0, 0, 0], "height", "Fit");
+        assert_eq!(source, SOURCE.replace(
+            "spacing: 10\n",
+            "spacing: 10\n                height: Fit\n"
+        ));
+        // a body without properties gets the indent of its first child
+        let source = set_property(&[0, 0], "width", "Fill");
+        assert_eq!(source, SOURCE.replace(
+            "ui: <Window> {\n",
+            "ui: <Window> {\n            width: Fill\n"
+        ));
+    }
+    
+    #[test]
+    fn removes_properties() {
+        let source = set_property(&[0, 0, 0, 3], "text", "");
+        assert_eq!(source, SOURCE.replace("                    text: \"Label\"\n", ""));
+        let source = set_property(&[0, 0, 0], "flow", "");
+        assert_eq!(source, SOURCE.replace("flow: Down, spacing", "spacing"));
+        let source = set_property(&[0, 0, 0], "spacing", "");
+        assert_eq!(source, SOURCE.replace("flow: Down, spacing: 10", "flow: Down"));
+        let source = set_property(&[0, 0, 0, 1], "text", "");
+        assert_eq!(source, SOURCE.replace("{text: \"Hi\"}", "{}"));
+        assert!(node(&[0, 0, 0]).set_property_edit(SOURCE, "width", "").is_none());
+    }
+}
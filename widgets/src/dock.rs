@@ -75,6 +75,16 @@ impl WidgetNode for Dock{
         self.area.redraw(cx)
     }
     
+    fn child_widgets(&self, children: &mut Vec<(LiveId, WidgetRef)>){
+        for (id, (_, widget)) in self.items.iter() {
+            children.push((*id, widget.clone()));
+        }
+    }
+    
+    fn area(&self) -> Area{
+        self.area
+    }
+    
     fn find_widgets(&mut self, path: &[LiveId], cached: WidgetCache, results: &mut WidgetSet) {
         if let Some((_, widget)) = self.items.get_mut(&path[0]) {
             if path.len()>1 {
@@ -124,7 +124,11 @@ impl DropDown {
         }
         self.draw_bg.end(cx);
         
-        cx.add_nav_stop(self.draw_bg.area(), NavRole::DropDown, Margin::default());
+        cx.add_nav_stop_with_access(self.draw_bg.area(), NavRole::DropDown, Margin::default(), AccessProps {
+            value: self.labels.get(self.selected_item).cloned(),
+            expanded: Some(self.is_open),
+            ..Default::default()
+        });
         
         if self.is_open && self.popup_menu.is_some() {
             //cx.set_sweep_lock(self.draw_bg.area());
@@ -62,6 +62,7 @@ pub struct FileTreeNode {
     #[live] draw_bg: DrawBgQuad,
     #[live] draw_icon: DrawIconQuad,
     #[live] draw_name: DrawNameText,
+    #[live] draw_badge: DrawText,
     #[live] check_box: CheckBox,
     #[layout] layout: Layout,
     
@@ -71,6 +72,7 @@ pub struct FileTreeNode {
     #[live] indent_shift: f64,
     
     #[live] icon_walk: Walk,
+    #[live] badge_walk: Walk,
     
     #[live] is_folder: bool,
     #[live] min_drag_distance: f64,
@@ -123,10 +125,12 @@ pub enum FileTreeAction {
     FileClicked(FileNodeId),
     FolderClicked(FileNodeId),
     ShouldFileStartDrag(FileNodeId),
+    ContextMenu(FileNodeId, DVec2),
 }
 
 pub enum FileTreeNodeAction {
     WasClicked,
+    WasRightClicked(DVec2),
     Opening,
     Closing,
     ShouldStartDrag
@@ -141,6 +145,7 @@ impl FileTreeNode {
         self.draw_icon.scale = scale as f32;
         self.draw_icon.is_even = is_even;
         self.draw_name.font_scale = scale;
+        self.draw_badge.font_scale = scale;
     }
     
     pub fn draw_folder(&mut self, cx: &mut Cx2d, name: &str, is_even: f32, node_height: f64, depth: usize, scale: f64) {
@@ -156,7 +161,7 @@ impl FileTreeNode {
         self.draw_bg.end(cx);
     }
     
-    pub fn draw_file(&mut self, cx: &mut Cx2d, name: &str, is_even: f32, node_height: f64, depth: usize, scale: f64) {
+    pub fn draw_file(&mut self, cx: &mut Cx2d, name: &str, badge: Option<(&str, Vec4)>, is_even: f32, node_height: f64, depth: usize, scale: f64) {
         self.set_draw_state(is_even, scale);
         
         self.draw_bg.begin(cx, Walk::size(Size::Fill, Size::Fixed(scale * node_height)), self.layout);
@@ -164,6 +169,12 @@ impl FileTreeNode {
         cx.walk_turtle(self.indent_walk(depth));
         
         self.draw_name.draw_walk(cx, Walk::fit(), Align::default(), name);
+        if let Some((badge, color)) = badge {
+            // push the badge to the right edge of the node
+            cx.walk_turtle(Walk::size(Size::Fill, Size::Fixed(0.0)));
+            self.draw_badge.color = color;
+            self.draw_badge.draw_walk(cx, self.badge_walk, Align::default(), badge);
+        }
         self.draw_bg.end(cx);
     }
     
@@ -215,6 +226,10 @@ impl FileTreeNode {
                     actions.push((node_id, FileTreeNodeAction::ShouldStartDrag));
                 }
             }
+            Hit::FingerDown(fe) if fe.device.mouse_button() == Some(1) => {
+                self.animator_play(cx, id!(select.on));
+                actions.push((node_id, FileTreeNodeAction::WasRightClicked(fe.abs)));
+            }
             Hit::FingerDown(_) => {
                 self.animator_play(cx, id!(select.on));
                 if self.is_folder {
@@ -323,6 +338,12 @@ impl FileTree {
     }
     
     pub fn file(&mut self, cx: &mut Cx2d, node_id: FileNodeId, name: &str) {
+        self.file_with_badge(cx, node_id, name, None);
+    }
+    
+    // like `file`, but with a short text in the given color at the end of the node, such as the
+    // state of the file in version control.
+    pub fn file_with_badge(&mut self, cx: &mut Cx2d, node_id: FileNodeId, name: &str, badge: Option<(&str, Vec4)>) {
         let scale = self.stack.last().cloned().unwrap_or(1.0);
         
         if scale > 0.2 {
@@ -333,7 +354,7 @@ impl FileTree {
             let (tree_node, _) = self.tree_nodes.get_or_insert(cx, node_id, | cx | {
                 (FileTreeNode::new_from_ptr(cx, file_node), live_id!(file_node))
             });
-            tree_node.draw_file(cx, name, Self::is_even(self.count), self.node_height, self.stack.len(), scale);
+            tree_node.draw_file(cx, name, badge, Self::is_even(self.count), self.node_height, self.stack.len(), scale);
         }
     }
     
@@ -345,6 +366,18 @@ impl FileTree {
         self.tree_nodes.remove(&file_node_id);
     }
     
+    fn select_node(&mut self, cx: &mut Cx, node_id: FileNodeId) {
+        cx.set_key_focus(self.scroll_bars.area());
+        if let Some(last_selected) = self.selected_node_id {
+            if last_selected != node_id {
+                if let Some((node, _)) = self.tree_nodes.get_mut(&last_selected) {
+                    node.set_is_selected(cx, false, Animate::Yes);
+                }
+            }
+        }
+        self.selected_node_id = Some(node_id);
+    }
+    
     pub fn is_folder(&mut self, file_node_id: FileNodeId)->bool {
         if let Some((node,_)) = self.tree_nodes.get(&file_node_id){
             node.is_folder
@@ -416,13 +449,7 @@ impl Widget for FileTree {
                     self.open_nodes.remove(&node_id);
                 }
                 FileTreeNodeAction::WasClicked => {
-                    cx.set_key_focus(self.scroll_bars.area());
-                    if let Some(last_selected) = self.selected_node_id {
-                        if last_selected != node_id {
-                            self.tree_nodes.get_mut(&last_selected).unwrap().0.set_is_selected(cx, false, Animate::Yes);
-                        }
-                    }
-                    self.selected_node_id = Some(node_id);
+                    self.select_node(cx, node_id);
                     if self.is_folder(node_id){
                         cx.widget_action(uid, &scope.path, FileTreeAction::FolderClicked(node_id));
                     }
@@ -430,6 +457,10 @@ impl Widget for FileTree {
                         cx.widget_action(uid, &scope.path, FileTreeAction::FileClicked(node_id));
                     }
                 }
+                FileTreeNodeAction::WasRightClicked(abs) => {
+                    self.select_node(cx, node_id);
+                    cx.widget_action(uid, &scope.path, FileTreeAction::ContextMenu(node_id, abs));
+                }
                 FileTreeNodeAction::ShouldStartDrag => {
                     if self.dragging_node_id.is_none() {
                         cx.widget_action(uid, &scope.path, FileTreeAction::ShouldFileStartDrag(node_id));
@@ -486,6 +517,15 @@ impl FileTreeRef{
     }
     
     
+    pub fn context_menu(&self, actions: &Actions) -> Option<(FileNodeId, DVec2)> {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            if let FileTreeAction::ContextMenu(file_id, abs) = item.cast() {
+                return Some((file_id, abs))
+            }
+        }
+        None
+    }
+    
     pub fn file_start_drag(&self, cx: &mut Cx, _file_id: FileNodeId, item: DragItem) {
         cx.start_dragging(vec![item]);
     }
@@ -3,7 +3,8 @@ use crate::{
     widget::*,
     makepad_derive_widget::*,
     makepad_draw::*,
-    scroll_bars::{ScrollBars}
+    scroll_bars::{ScrollBars},
+    view::View,
 };
 
 live_design!{
@@ -117,7 +118,11 @@ impl FlatList {
     pub fn item(&mut self, cx: &mut Cx, id: LiveId, template: LiveId) -> Option<WidgetRef> {
         if let Some(ptr) = self.templates.get(&template) {
             let (_, entry) = self.items.get_or_insert(cx, id, | cx | {
-                (template, WidgetRef::new_from_ptr(cx, Some(*ptr)))
+                let item = WidgetRef::new_from_ptr(cx, Some(*ptr));
+                if let Some(mut view) = item.borrow_mut::<View>() {
+                    view.set_access_role(Some(NavRole::ListItem));
+                }
+                (template, item)
             });
             return Some(entry.clone())
         }
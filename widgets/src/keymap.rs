@@ -0,0 +1,76 @@
+use crate::makepad_draw::*;
+
+live_design!{
+    KeymapBase = {{Keymap}} {}
+}
+
+// one command in a keymap, like
+// save = {name: "Save", key: "Primary+S"}
+// comment = {name: "Toggle Comment", key: "Primary+K Primary+C", scope: code_editor}
+// find = {name: "Find", key: "Ctrl+F | Cmd+F"}
+#[derive(Clone, Debug, Live, LiveHook, LiveRegister)]
+pub struct KeymapEntry {
+    #[live] name: String,
+    #[live] key: String,
+    #[live] scope: LiveId,
+}
+
+// registers the commands and keybindings defined in live_design with the command registry
+// in Cx, a user keymap file overrides them per command
+#[derive(Live, LiveRegister)]
+pub struct Keymap {
+    #[live] user_file: String,
+    #[rust] entries: Vec<(LiveId, KeymapEntry)>,
+}
+
+impl LiveHook for Keymap {
+    fn before_apply(&mut self, _cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        if apply.from.is_from_doc() {
+            self.entries.clear();
+        }
+    }
+
+    fn apply_value_instance(&mut self, cx: &mut Cx, apply: &mut Apply, index: usize, nodes: &[LiveNode]) -> usize {
+        let id = nodes[index].id;
+        match apply.from {
+            ApplyFrom::NewFromDoc {..} | ApplyFrom::UpdateFromDoc {..} => {
+                if nodes[index].origin.has_prop_type(LivePropType::Instance) && nodes[index].value.is_object() {
+                    let mut entry = KeymapEntry::new(cx);
+                    let index = entry.apply(cx, apply, index, nodes);
+                    self.entries.retain( | (other, _) | *other != id);
+                    self.entries.push((id, entry));
+                    return index;
+                }
+                else {
+                    cx.apply_error_no_matching_field(live_error_origin!(), index, nodes);
+                }
+            }
+            _ => ()
+        }
+        nodes.skip_node(index)
+    }
+
+    fn after_apply(&mut self, cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        if !apply.from.is_from_doc() {
+            return
+        }
+        cx.clear_key_bindings(live_id!(keymap));
+        for (command, entry) in &self.entries {
+            if !entry.name.is_empty() {
+                cx.register_command(*command, &entry.name);
+            }
+            if entry.key.is_empty() {
+                continue
+            }
+            let scope = if entry.scope.is_empty() {None} else {Some(entry.scope)};
+            if let Err(e) = cx.bind_command(live_id!(keymap), *command, &entry.key, scope) {
+                error!("Keymap {}: {}", command, e);
+            }
+        }
+        if !self.user_file.is_empty() && std::path::Path::new(&self.user_file).exists() {
+            if let Err(e) = cx.load_keymap_file(&self.user_file) {
+                error!("{}", e);
+            }
+        }
+    }
+}
@@ -25,6 +25,7 @@ pub mod fold_header;
 pub mod fold_button;
 pub mod multi_window;
 pub mod designer;
+pub mod designer_outline;
 pub mod dock;
 pub mod tab;
 pub mod tab_bar;
@@ -36,6 +37,8 @@ pub mod desktop_button;
 pub mod window;
 pub mod scroll_shadow;
 pub mod window_menu;
+pub mod keymap;
+pub mod command_palette;
 pub mod html;
 pub mod markdown;
 pub mod text_flow;
@@ -56,6 +59,8 @@ pub mod performance_view;
 pub mod nav_control;
 
 pub mod view;
+pub mod view_3d;
+pub mod view_effect;
 pub mod widget;
 pub mod widget_match_event;
 
@@ -72,6 +77,7 @@ pub use crate::{
     data_binding::{DataBindingStore, DataBindingMap},
     button::*,
     view::*,
+    view_3d::*,
     image::*,
     icon::*,
     label::*,
@@ -93,8 +99,11 @@ pub use crate::{
     stack_navigation::*,
     expandable_panel::*,
     window::*,
+    keymap::Keymap,
+    command_palette::*,
     tab::TabClosable,
     scroll_bars::{ScrollBars},
+    view_effect::{ViewEffect, ViewEffectKind, CxBackdropSources},
     scroll_shadow::{DrawScrollShadow},
     scroll_bar::{ScrollBar},
     slides_view::{SlidesView},
@@ -140,6 +149,8 @@ pub fn live_design(cx: &mut Cx) {
     crate::rotated_image::live_design(cx);
     crate::video::live_design(cx);
     crate::view::live_design(cx);
+    crate::view_3d::live_design(cx);
+    crate::view_effect::live_design(cx);
     crate::fold_button::live_design(cx);
     crate::text_input::live_design(cx);
     crate::link_label::live_design(cx);
@@ -148,6 +159,8 @@ pub fn live_design(cx: &mut Cx) {
     crate::desktop_button::live_design(cx);
     crate::window::live_design(cx);
     crate::window_menu::live_design(cx);
+    crate::keymap::live_design(cx);
+    crate::command_palette::live_design(cx);
     crate::scroll_bar::live_design(cx);
     crate::scroll_bars::live_design(cx);
     crate::check_box::live_design(cx);
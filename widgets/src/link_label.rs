@@ -11,11 +11,17 @@ live_design!{
     LinkLabelBase = {{LinkLabel}} {}
 }
 
-#[derive(Live, LiveHook, Widget)]
+#[derive(Live, Widget)]
 pub struct LinkLabel {
     #[deref] button: Button
 }
 
+impl LiveHook for LinkLabel {
+    fn after_new_before_apply(&mut self, _cx: &mut Cx) {
+        self.button.set_access_role(NavRole::Link);
+    }
+}
+
 impl Widget for LinkLabel {
     fn handle_event(
         &mut self,
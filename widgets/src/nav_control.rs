@@ -34,35 +34,28 @@ impl NavControl {
         match event {
             Event::KeyDown(ke) => match ke.key_code {
                 KeyCode::Tab => {
-                    if ke.modifiers.shift {
-                        let mut prev_area = Area::Empty;
-                        if let Some((prev_area, scroll_stack)) = Cx2d::iterate_nav_stops(cx, root, | cx, stop | {
-                            if cx.has_key_focus(stop.area) {
-                                return Some(prev_area);
-                            }
-                            prev_area = stop.area;
-                            None
-                        }) {
-                            if !prev_area.is_empty() {
-                                Self::send_trigger_to_scroll_stack(cx, scroll_stack);
-                                cx.set_key_focus(prev_area);
-                            }
-                        }
+                    let mut stops = Vec::new();
+                    Cx2d::iterate_nav_stops(cx, root, | _, stop | {
+                        stops.push(stop.area);
+                        None
+                    });
+                    let focus = stops.iter().position( | area | cx.has_key_focus(*area));
+                    // a widget outside of the tab order, like the code editor, keeps tab for itself
+                    if focus.is_none() && !cx.has_key_focus(Area::Empty) {
+                        return
                     }
-                    else {
-                        let mut next_stop = false;
-                        if let Some((next_area, scroll_stack)) = Cx2d::iterate_nav_stops(cx, root, | cx, stop | {
-                            if next_stop {
-                                return Some(stop.area)
-                            }
-                            if cx.has_key_focus(stop.area) {
-                                next_stop = true;
-                            }
-                            None
-                        }) {
-                            Self::send_trigger_to_scroll_stack(cx, scroll_stack);
-                            cx.set_key_focus(next_area);
+                    let Some(next) = next_tab_stop(stops.len(), focus, ke.modifiers.shift) else {
+                        return
+                    };
+                    let next_area = stops[next];
+                    if let Some((next_area, scroll_stack)) = Cx2d::iterate_nav_stops(cx, root, | _, stop | {
+                        if stop.area == next_area {
+                            return Some(stop.area)
                         }
+                        None
+                    }) {
+                        Self::send_trigger_to_scroll_stack(cx, scroll_stack);
+                        cx.set_key_focus(next_area);
                     }
                 }
                 _ => ()
@@ -16,6 +16,7 @@ pub struct PopupMenuItem {
     
     #[live] draw_bg: DrawQuad,
     #[live] draw_name: DrawText,
+    #[live] draw_key: DrawText,
     
     #[layout] layout: Layout,
     #[animator] animator: Animator,
@@ -27,6 +28,7 @@ pub struct PopupMenuItem {
     #[live] opened: f32,
     #[live] hover: f32,
     #[live] selected: f32,
+    #[rust] disabled: bool,
 }
 
 #[derive(Live, LiveRegister)]
@@ -35,6 +37,8 @@ pub struct PopupMenu {
     #[live] menu_item: Option<LivePtr>,
     
     #[live] draw_bg: DrawQuad,
+    #[live] draw_separator: DrawQuad,
+    #[live] separator_walk: Walk,
     #[layout] layout: Layout,
     #[walk] walk: Walk,
     #[live] items: Vec<String>,
@@ -80,8 +84,27 @@ impl PopupMenuItem {
         cx: &mut Cx2d,
         label: &str,
     ) {
+        self.draw_item_with_key(cx, label, None, true);
+    }
+    
+    // the key label is drawn right aligned, disabled items are dimmed and ignore the pointer
+    pub fn draw_item_with_key(
+        &mut self,
+        cx: &mut Cx2d,
+        label: &str,
+        key: Option<&str>,
+        enabled: bool,
+    ) {
+        if self.disabled == enabled {
+            self.disabled = !enabled;
+            let disabled = if enabled {0.0} else {1.0};
+            self.draw_name.apply_over(cx, live!{disabled: (disabled)});
+        }
         self.draw_bg.begin(cx, self.walk, self.layout);
         self.draw_name.draw_walk(cx, Walk::fit(), Align::default(), label);
+        if let Some(key) = key {
+            self.draw_key.draw_walk(cx, Walk::fill_fit(), Align {x: 1.0, y: 0.0}, key);
+        }
         self.draw_bg.end(cx);
     }
     
@@ -95,6 +118,9 @@ impl PopupMenuItem {
         if self.animator_handle_event(cx, event).must_redraw() {
             self.draw_bg.area().redraw(cx);
         }
+        if self.disabled {
+            return
+        }
         
         match event.hits_with_options(
             cx,
@@ -187,6 +213,44 @@ impl PopupMenu {
         menu_item.draw_item(cx, label);
     }
     
+    pub fn draw_item_with_key(
+        &mut self,
+        cx: &mut Cx2d,
+        item_id: PopupMenuItemId,
+        label: &str,
+        key: Option<&str>,
+        enabled: bool,
+    ) {
+        self.count += 1;
+        
+        let menu_item = self.menu_item;
+        let menu_item = self.menu_items.get_or_insert(cx, item_id, | cx | {
+            PopupMenuItem::new_from_ptr(cx, menu_item)
+        });
+        menu_item.draw_item_with_key(cx, label, key, enabled);
+    }
+    
+    pub fn draw_separator(&mut self, cx: &mut Cx2d) {
+        self.draw_separator.draw_walk(cx, self.separator_walk);
+    }
+    
+    pub fn item_area(&self, item_id: PopupMenuItemId) -> Area {
+        self.menu_items.get(&item_id).map( | item | item.draw_bg.area()).unwrap_or_default()
+    }
+    
+    // highlights an item like the pointer hovering it, for keyboard navigation.
+    // an id that is not in the menu clears the highlight
+    pub fn hover_item(&mut self, cx: &mut Cx, which_id: PopupMenuItemId) {
+        for (id, item) in &mut *self.menu_items {
+            if *id == which_id {
+                item.animator_cut(cx, id!(hover.on));
+            }
+            else {
+                item.animator_cut(cx, id!(hover.off));
+            }
+        }
+    }
+    
     pub fn init_select_item(&mut self, which_id: PopupMenuItemId) {
         self.init_select_item = Some(which_id);
         self.first_tap = true;
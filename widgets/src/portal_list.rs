@@ -3,7 +3,8 @@ use crate::{
     widget::*,
     makepad_derive_widget::*,
     makepad_draw::*,
-    scroll_bar::{ScrollBar, ScrollAxis, ScrollBarAction}
+    scroll_bar::{ScrollBar, ScrollAxis, ScrollBarAction},
+    view::View,
 };
 
 live_design!{
@@ -506,7 +507,11 @@ impl PortalList {
             let mut already_existed = true;
             let entry = self.items.get_or_insert(cx, (entry_id, template), | cx | {
                 already_existed = false;
-                WidgetRef::new_from_ptr(cx, Some(*ptr))
+                let item = WidgetRef::new_from_ptr(cx, Some(*ptr));
+                if let Some(mut view) = item.borrow_mut::<View>() {
+                    view.set_access_role(Some(NavRole::ListItem));
+                }
+                item
             });
             Some((entry.clone(), already_existed))
         } else {
@@ -813,6 +818,19 @@ impl PortalListRef {
         }
     }
     
+    // plays a state on the drawn items from the top of the list down, each one interval seconds
+    // after the previous
+    pub fn animator_play_staggered(&self, cx: &mut Cx, state: &[LiveId; 2], interval: f64) {
+        let mut items = Vec::new();
+        if let Some(inner) = self.borrow() {
+            items.extend(inner.items.iter().map(|((entry_id, _), item)| (*entry_id, item.clone())));
+        }
+        items.sort_by_key(|(entry_id, _)| *entry_id);
+        for (i, (_, item)) in items.iter().enumerate() {
+            item.animator_play_delayed(cx, state, i as f64 * interval);
+        }
+    }
+    
     pub fn set_tail_range(&self, tail_range: bool) {
         if let Some(mut inner) = self.borrow_mut() {
             inner.tail_range = tail_range
@@ -97,6 +97,18 @@ impl RadioButton {
         }
         self.draw_text.draw_walk(cx, self.label_walk, self.label_align, &self.label);
         self.draw_radio.end(cx);
+        let checked = self.animator_in_state(cx, id!(selected.on));
+        cx.add_nav_stop_with_access(self.draw_radio.area(), NavRole::RadioButton, Margin::default(), AccessProps {
+            checked: Some(checked),
+            ..AccessProps::with_name(&self.label)
+        });
+    }
+    
+    fn select(&mut self, cx: &mut Cx, scope: &mut Scope) {
+        if self.animator_in_state(cx, id!(selected.off)) {
+            self.animator_play(cx, id!(selected.on));
+            cx.widget_action(self.widget_uid(), &scope.path, RadioButtonAction::Clicked);
+        }
     }
 
 }
@@ -118,7 +130,6 @@ impl Widget for RadioButtonGroup {
 impl Widget for RadioButton {
     
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
-        let uid = self.widget_uid();
         self.animator_handle_event(cx, event);
                 
         match event.hits(cx, self.draw_radio.area()) {
@@ -130,12 +141,18 @@ impl Widget for RadioButton {
                 cx.set_cursor(MouseCursor::Arrow);
                 self.animator_play(cx, id!(hover.off));
             },
+            Hit::KeyFocus(_) => {
+                self.animator_play(cx, id!(hover.on));
+            }
+            Hit::KeyFocusLost(_) => {
+                self.animator_play(cx, id!(hover.off));
+            }
             Hit::FingerDown(_fe) => {
-                if self.animator_in_state(cx, id!(selected.off)) {
-                    self.animator_play(cx, id!(selected.on));
-                    cx.widget_action(uid, &scope.path, RadioButtonAction::Clicked);
-                }
+                self.select(cx, scope);
             },
+            Hit::KeyDown(ke) if !ke.is_repeat && ke.key_code == KeyCode::Space => {
+                self.select(cx, scope);
+            }
             Hit::FingerUp(_fe) => {
                                 
             }
@@ -107,6 +107,7 @@ impl Slider {
     
     pub fn draw_walk_slider(&mut self, cx: &mut Cx2d, walk: Walk) {
         self.draw_slider.slide_pos = self.value as f32;
+        let nav_group = cx.add_begin_group();
         self.draw_slider.begin(cx, walk, self.layout);
         
         if let Some(mut dw) = cx.defer_walk(self.label_walk) {
@@ -117,6 +118,16 @@ impl Slider {
         }
         
         self.draw_slider.end(cx);
+        cx.add_end_group(nav_group, self.draw_slider.area(), NavRole::Slider, false, AccessProps {
+            value: Some(self.text_input.text.clone()),
+            range: Some(AccessRange {
+                min: self.min,
+                max: self.max,
+                value: self.to_external(),
+                step: self.step,
+            }),
+            ..AccessProps::with_name(&self.text)
+        });
     }
 }
 
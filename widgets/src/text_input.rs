@@ -624,7 +624,11 @@ impl TextInput {
             }
         }
         
-        cx.add_nav_stop(self.draw_bg.area(), NavRole::TextInput, Margin::default())
+        cx.add_nav_stop_with_access(self.draw_bg.area(), NavRole::TextInput, Margin::default(), AccessProps {
+            // secret text is never read out
+            value: if self.secret {None} else {Some(self.text.clone())},
+            ..AccessProps::with_name(&self.empty_message)
+        })
     }
 }
 
@@ -636,4 +640,14 @@ impl TextInputRef {
         None
     }
     
+    pub fn returned(&self, actions: &Actions) -> Option<String> {
+        if let TextInputAction::Return(val) = actions.find_widget_action_cast(self.widget_uid()) {
+            return Some(val);
+        }
+        None
+    }
+    
+    pub fn escaped(&self, actions: &Actions) -> bool {
+        matches!(actions.find_widget_action_cast(self.widget_uid()), TextInputAction::Escape)
+    }
 }
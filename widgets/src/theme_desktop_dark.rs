@@ -915,14 +915,43 @@ live_design! {
     }
 
     WindowMenu = <WindowMenuBase>{
-        height: 0,
-        width: 0
+        width: Fill,
+        height: Fit,
+        flow: Right,
+        padding: {left: 5}
+
+        draw_bg: {
+            fn pixel(self) -> vec4 {
+                return THEME_COLOR_BG_APP
+            }
+        }
+
+        item_walk: {width: Fit, height: Fit}
+        item_layout: {padding: {left: 8, right: 8, top: 5, bottom: 5}}
+
+        draw_item: {
+            color_hover: (THEME_COLOR_UP_4),
+            color_active: (THEME_COLOR_UP_10)
+        }
+
+        draw_text: {
+            text_style: <THEME_FONT_LABEL> {}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+
+        popup_menu: <PopupMenu> {
+            width: 260,
+            menu_item: {padding: {right: 15}}
+        }
     }
 
+    Keymap = <KeymapBase> {}
+
     Window = <WindowBase> {
         pass: {clear_color: (THEME_COLOR_CLEAR)}
         flow: Down
         nav_control: <NavControl> {}
+        keymap: <Keymap> {}
         caption_bar = <SolidView> {
             visible: false,
 
@@ -1320,19 +1349,29 @@ live_design! {
             text_style: <THEME_FONT_LABEL> {}
             instance selected: 0.0
             instance hover: 0.0
+            instance disabled: 0.0
             fn get_color(self) -> vec4 {
                 return mix(
                     mix(
-                        THEME_COLOR_TEXT_DEFAULT,
-                        THEME_COLOR_TEXT_SELECTED,
-                        self.selected
+                        mix(
+                            THEME_COLOR_TEXT_DEFAULT,
+                            THEME_COLOR_TEXT_SELECTED,
+                            self.selected
+                        ),
+                        THEME_COLOR_TEXT_HOVER,
+                        self.hover
                     ),
-                    THEME_COLOR_TEXT_HOVER,
-                    self.hover
+                    THEME_COLOR_TEXT_META,
+                    self.disabled
                 )
             }
         }
 
+        draw_key: {
+            text_style: <THEME_FONT_LABEL> {}
+            color: (THEME_COLOR_TEXT_META)
+        }
+
         draw_bg: {
             instance selected: 0.0
             instance hover: 0.0
@@ -1408,6 +1447,13 @@ live_design! {
     PopupMenu = <PopupMenuBase> {
         menu_item: <PopupMenuItem> {}
 
+        separator_walk: {width: Fill, height: 1, margin: {top: 3, bottom: 3}}
+        draw_separator: {
+            fn pixel(self) -> vec4 {
+                return THEME_COLOR_UP_10
+            }
+        }
+
         flow: Down,
         padding: 5
 
@@ -1628,9 +1674,21 @@ live_design! {
             }
         }
 
+        draw_badge: {
+            text_style: <THEME_FONT_DATA> {
+                top_drop: 1.2,
+            }
+        }
+
         align: {y: 0.5}
         padding: {left: 5.0, bottom: 0,},
 
+        badge_walk: {
+            width: Fit,
+            height: Fit,
+            margin: {right: 10}
+        }
+
         icon_walk: {
             width: Fixed((THEME_DATA_ICON_WIDTH - 2)),
             height: Fixed((THEME_DATA_ICON_HEIGHT)),
@@ -2171,6 +2229,19 @@ live_design! {
         scroll_bars: <ScrollBars> {show_scroll_x: false, show_scroll_y: true}
     }
 
+    BlurEffect = <ViewEffectBase> {kind: Blur, radius: 8.0}
+    DropShadowEffect = <ViewEffectBase> {kind: DropShadow, radius: 12.0, offset: vec2(0.0, 4.0), color: #0008}
+    BackdropEffect = <ViewEffectBase> {kind: Backdrop, radius: 16.0}
+
+    BlurView = <ViewBase> {effect: <BlurEffect> {}}
+    DropShadowView = <ViewBase> {effect: <DropShadowEffect> {}}
+    // blurs what a texture cached view (CachedView and friends) behind it draws
+    FrostedGlassView = <ViewBase> {
+        effect: <BackdropEffect> {}
+        show_bg: true
+        draw_bg: {color: #fff2}
+    }
+
     ScrollXYView = <ViewBase> {scroll_bars: <ScrollBars> {show_scroll_x: true, show_scroll_y: true}}
     ScrollXView = <ViewBase> {scroll_bars: <ScrollBars> {show_scroll_x: true, show_scroll_y: false}}
     ScrollYView = <ViewBase> {scroll_bars: <ScrollBars> {show_scroll_x: false, show_scroll_y: true}}
@@ -2642,4 +2713,56 @@ live_design! {
     }
 
     // StackView DSL end
+
+    CommandPalette = <CommandPaletteBase> {
+        command: command_palette
+        top: 60.0
+        max_items: 12
+        width: 500,
+        height: Fit
+        flow: Down,
+        padding: 6,
+        spacing: 4
+
+        draw_bg: {
+            instance color: #2
+            instance border_color: #5
+            instance radius: 4.0
+
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                sdf.box(1.0, 1.0, self.rect_size.x - 2.0, self.rect_size.y - 2.0, self.radius)
+                sdf.fill_keep(self.color)
+                sdf.stroke(self.border_color, 1.0)
+                return sdf.result;
+            }
+        }
+
+        query: <TextInput> {
+            width: Fill,
+            height: Fit,
+            padding: 6,
+            empty_message: "Type a command"
+            draw_bg: {
+                color: #1
+            }
+        }
+
+        item_walk: {width: Fill, height: Fit}
+        item_layout: {padding: {left: 6, right: 6, top: 4, bottom: 4}, flow: Right, spacing: 20}
+
+        draw_item: {
+            color_selected: (THEME_COLOR_BG_SELECTED)
+        }
+
+        draw_name: {
+            text_style: <THEME_FONT_LABEL> {}
+            color: (THEME_COLOR_TEXT_DEFAULT)
+        }
+
+        draw_key: {
+            text_style: <THEME_FONT_LABEL> {}
+            color: (THEME_COLOR_TEXT_META)
+        }
+    }
 }
@@ -1,5 +1,9 @@
 use {
-    crate::{makepad_derive_widget::*, makepad_draw::*, scroll_bars::ScrollBars, widget::*},
+    crate::{
+        makepad_derive_widget::*, makepad_draw::*,
+        makepad_platform::makepad_live_compiler::LiveTokenId, scroll_bars::ScrollBars,
+        view_effect::{CxBackdropSources, ViewEffect}, widget::*,
+    },
     std::collections::hash_map::HashMap,
 };
 
@@ -149,6 +153,8 @@ pub struct View {
     cursor: Option<MouseCursor>,
     #[live]
     scroll_bars: Option<LivePtr>,
+    #[live]
+    effect: Option<LivePtr>,
     #[live(false)]
     design_mode: bool,
 
@@ -158,10 +164,20 @@ pub struct View {
     #[rust]
     scroll_bars_obj: Option<Box<ScrollBars>>,
     #[rust]
+    effect_obj: Option<Box<ViewEffect>>,
+    // lists make their items focusable nodes of the accessibility tree
+    #[rust]
+    access_role: Option<NavRole>,
+    #[rust]
+    nav_group: Option<NavGroupIndex>,
+    #[rust]
     view_size: Option<DVec2>,
 
     #[rust]
     area: Area,
+    // where this view is defined in live_design!, so that studio can pick it
+    #[rust]
+    design_token: Option<LiveTokenId>,
     #[rust]
     draw_list: Option<DrawList2d>,
 
@@ -204,10 +220,13 @@ impl LiveHook for View {
     fn after_apply(
         &mut self,
         cx: &mut Cx,
-        _applyl: &mut Apply,
-        _index: usize,
-        _nodes: &[LiveNode],
+        apply: &mut Apply,
+        index: usize,
+        nodes: &[LiveNode],
     ) {
+        if apply.from.is_from_doc() {
+            self.design_token = nodes[index].origin.token_id();
+        }
         if self.optimize.needs_draw_list() && self.draw_list.is_none() {
             self.draw_list = Some(DrawList2d::new(cx));
         }
@@ -217,6 +236,11 @@ impl LiveHook for View {
                     Some(Box::new(ScrollBars::new_from_ptr(cx, self.scroll_bars)));
             }
         }
+        if self.effect.is_some() {
+            if self.effect_obj.is_none() {
+                self.effect_obj = Some(Box::new(ViewEffect::new_from_ptr(cx, self.effect)));
+            }
+        }
         /*
         if let Some(image_texture) = &mut self.image_texture {
             if self.image_scale != 0.0 {
@@ -507,6 +531,26 @@ impl WidgetNode for View {
         self.walk
     }
 
+    fn child_widgets(&self, children: &mut Vec<(LiveId, WidgetRef)>) {
+        for id in &self.draw_order {
+            if let Some(child) = self.children.get(id) {
+                children.push((*id, child.clone()));
+            }
+        }
+    }
+
+    fn area(&self) -> Area {
+        self.area
+    }
+
+    fn animator(&self) -> Option<&Animator> {
+        Some(&self.animator)
+    }
+
+    fn animator_play_delayed(&mut self, cx: &mut Cx, state: &[LiveId; 2], delay: f64) {
+        AnimatorImpl::animator_play_delayed(self, cx, state, delay)
+    }
+
     fn redraw(&mut self, cx: &mut Cx) {
         self.area.redraw(cx);
         for child in self.children.values_mut() {
@@ -616,7 +660,17 @@ impl Widget for View {
             }
         }
 
-        if self.visible && self.cursor.is_some() || self.animator.live_ptr.is_some() {
+        // the children go first, so the innermost view under the click picks itself
+        if let Event::DesignerPick(e) = event {
+            if let Some(token_id) = self.design_token {
+                if self.visible && self.area.rect(cx).contains(e.abs) {
+                    e.pick(cx, token_id);
+                }
+            }
+            return;
+        }
+
+        if self.visible && self.cursor.is_some() || self.animator.live_ptr.is_some() || self.access_role.is_some() {
             match event.hits(cx, self.area()) {
                 Hit::FingerDown(e) => {
                     if self.grab_key_focus {
@@ -647,6 +701,16 @@ impl Widget for View {
                         self.animator_play(cx, id!(hover.off));
                     }
                 }
+                Hit::KeyFocus(_) => {
+                    if self.animator.live_ptr.is_some() {
+                        self.animator_play(cx, id!(hover.on));
+                    }
+                }
+                Hit::KeyFocusLost(_) => {
+                    if self.animator.live_ptr.is_some() {
+                        self.animator_play(cx, id!(hover.off));
+                    }
+                }
                 Hit::KeyDown(e) => cx.widget_action(uid, &scope.path, ViewAction::KeyDown(e)),
                 Hit::KeyUp(e) => cx.widget_action(uid, &scope.path, ViewAction::KeyUp(e)),
                 _ => (),
@@ -704,6 +768,12 @@ impl Widget for View {
                                     self.area,
                                 );
                             }
+                            CxBackdropSources::register(
+                                cx,
+                                &texture_cache.pass,
+                                &texture_cache.color_texture,
+                                self.area,
+                            );
                         }
                         return DrawStep::done();
                     }
@@ -730,6 +800,7 @@ impl Widget for View {
                     }
                     let texture_cache = self.texture_cache.as_mut().unwrap();
                     cx.make_child_pass(&texture_cache.pass);
+                    CxBackdropSources::relink(cx, &texture_cache.pass);
                     cx.begin_pass(&texture_cache.pass, self.dpi_factor);
                     self.draw_list.as_mut().unwrap().begin_always(cx)
                 }
@@ -749,6 +820,14 @@ impl Widget for View {
                 _ => (),
             }
 
+            if self.access_role.is_some() {
+                self.nav_group = Some(cx.add_begin_group());
+            }
+
+            if let Some(effect) = &mut self.effect_obj {
+                effect.begin(cx);
+            }
+
             // ok so.. we have to keep calling draw till we return LiveId(0)
             let scroll = if let Some(scroll_bars) = &mut self.scroll_bars_obj {
                 scroll_bars.begin_nav_area(cx);
@@ -818,6 +897,15 @@ impl Widget for View {
                     scroll_bars.end_nav_area(cx);
                 };
 
+                if let (Some(role), Some(nav_group)) = (self.access_role, self.nav_group.take()) {
+                    cx.add_end_group(nav_group, self.area, role, true, AccessProps::default());
+                }
+
+                if let Some(effect) = &mut self.effect_obj {
+                    let rect = self.area.rect(cx);
+                    effect.end(cx, rect);
+                }
+
                 if self.optimize.needs_draw_list() {
                     let rect = self.area.rect(cx);
                     self.view_size = Some(rect.size);
@@ -854,6 +942,12 @@ impl Widget for View {
                                 area,
                             );
                         }
+                        CxBackdropSources::register(
+                            cx,
+                            &texture_cache.pass,
+                            &texture_cache.color_texture,
+                            area,
+                        );
                     }
                 }
                 self.draw_state.end();
@@ -918,6 +1012,11 @@ impl View {
         self.area
     }
 
+    // turns the view into a focusable node of the accessibility tree
+    pub fn set_access_role(&mut self, role: Option<NavRole>) {
+        self.access_role = role;
+    }
+
     pub fn walk_from_previous_size(&self, walk: Walk) -> Walk {
         let view_size = self.view_size.unwrap_or(DVec2::default());
         Walk {
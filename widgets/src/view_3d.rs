@@ -0,0 +1,171 @@
+use crate::{
+    makepad_derive_widget::*,
+    makepad_draw::*,
+    makepad_draw::gltf::GltfError,
+    widget::*
+};
+
+live_design!{
+    View3DBase = {{View3D}} {}
+}
+
+// renders a 3D scene into a texture and draws that into the 2D layout, the scene is either the
+// glb file in source or a unit cube, and can be orbited by dragging and zoomed by scrolling
+#[derive(Live, Widget)]
+pub struct View3D {
+    #[walk] walk: Walk,
+    #[redraw] #[live] draw_bg: DrawQuad,
+    #[live] draw_mesh: DrawMesh3d,
+    #[live] source: LiveDependency,
+    #[live(true)] orbit: bool,
+    #[live(30.0)] yaw: f64,
+    #[live(20.0)] pitch: f64,
+    #[live(45.0)] fov_y: f64,
+    #[live] clear_color: Vec4,
+    #[rust] cx3d: Option<Cx3d>,
+    #[rust] model: Option<Model3d>,
+    #[rust] frame_model: bool,
+    #[rust] drag_last: Option<DVec2>,
+}
+
+impl LiveHook for View3D {
+    fn after_apply(&mut self, cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        if self.cx3d.is_none() {
+            self.cx3d = Some(Cx3d::new(cx));
+        }
+        let cx3d = self.cx3d.as_mut().unwrap();
+        cx3d.clear_color = self.clear_color;
+        if apply.from.is_from_doc() {
+            cx3d.camera.yaw = self.yaw as f32;
+            cx3d.camera.pitch = self.pitch as f32;
+            cx3d.camera.fov_y = self.fov_y as f32;
+            let source = self.source.clone();
+            if !source.as_str().is_empty() {
+                match cx.get_dependency(source.as_str()) {
+                    Ok(data) => {
+                        let _ = self.load_glb_from_data(cx, &data);
+                    }
+                    Err(err) => {
+                        error!("View3D: Resource not found {} {}", source.as_str(), err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Widget for View3D {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, _scope: &mut Scope) {
+        if !self.orbit {
+            return
+        }
+        match event.hits(cx, self.draw_bg.area()) {
+            Hit::FingerDown(fe) => {
+                self.drag_last = Some(fe.abs);
+            }
+            Hit::FingerMove(fe) => {
+                if let (Some(last), Some(cx3d)) = (self.drag_last, &mut self.cx3d) {
+                    let delta = fe.abs - last;
+                    cx3d.camera.yaw -= (delta.x * 0.5) as f32;
+                    cx3d.camera.pitch = (cx3d.camera.pitch + (delta.y * 0.5) as f32).clamp(-89.0, 89.0);
+                    self.drag_last = Some(fe.abs);
+                    self.draw_bg.redraw(cx);
+                }
+            }
+            Hit::FingerUp(_) => {
+                self.drag_last = None;
+            }
+            Hit::FingerScroll(fs) => {
+                if let Some(cx3d) = &mut self.cx3d {
+                    let zoom = (1.0 + fs.scroll.y * 0.002).clamp(0.5, 2.0) as f32;
+                    cx3d.camera.distance = (cx3d.camera.distance * zoom).max(cx3d.camera.near * 2.0);
+                    self.draw_bg.redraw(cx);
+                }
+            }
+            _ => ()
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope: &mut Scope, walk: Walk) -> DrawStep {
+        let rect = cx.walk_turtle(walk);
+        let Some(cx3d) = &mut self.cx3d else {
+            return DrawStep::done()
+        };
+        if self.frame_model {
+            self.frame_model = false;
+            if let Some((min, max)) = self.model.as_ref().and_then( | model | model.bounds()) {
+                cx3d.camera.frame_bounds(min, max);
+            }
+        }
+        cx3d.begin(cx);
+        if let Some(model) = &self.model {
+            model.draw(cx, &mut self.draw_mesh, &Mat4::identity());
+        }
+        else {
+            self.draw_mesh.draw(cx, &Mat4::identity());
+        }
+        cx3d.end(cx);
+        self.draw_bg.draw_vars.set_texture(0, cx3d.color_texture());
+        self.draw_bg.draw_abs(cx, rect);
+        cx3d.set_area(cx, self.draw_bg.area());
+        DrawStep::done()
+    }
+}
+
+impl View3D {
+    pub fn load_glb_from_data(&mut self, cx: &mut Cx, data: &[u8]) -> Result<(), GltfError> {
+        match Model3d::from_glb(cx, data) {
+            Ok(model) => {
+                self.model = Some(model);
+                self.frame_model = true;
+                Ok(())
+            }
+            Err(err) => {
+                error!("View3D: Cannot load glb model: {}", err);
+                Err(err)
+            }
+        }
+    }
+
+    pub fn set_model(&mut self, model: Option<Model3d>) {
+        self.model = model;
+        self.frame_model = true;
+    }
+
+    pub fn camera(&self) -> Option<&Camera3d> {
+        self.cx3d.as_ref().map( | cx3d | &cx3d.camera)
+    }
+
+    pub fn camera_mut(&mut self) -> Option<&mut Camera3d> {
+        self.cx3d.as_mut().map( | cx3d | &mut cx3d.camera)
+    }
+}
+
+impl View3DRef {
+    pub fn load_glb_from_data(&self, cx: &mut Cx, data: &[u8]) -> Result<(), GltfError> {
+        if let Some(mut inner) = self.borrow_mut() {
+            let result = inner.load_glb_from_data(cx, data);
+            inner.redraw(cx);
+            result
+        }
+        else {
+            Ok(())
+        }
+    }
+
+    pub fn set_model(&self, cx: &mut Cx, model: Option<Model3d>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_model(model);
+            inner.redraw(cx);
+        }
+    }
+
+    pub fn set_camera(&self, cx: &mut Cx, camera: Camera3d) {
+        if let Some(mut inner) = self.borrow_mut() {
+            if let Some(current) = inner.camera_mut() {
+                *current = camera;
+            }
+            inner.redraw(cx);
+        }
+    }
+}
@@ -0,0 +1,385 @@
+use crate::makepad_draw::*;
+
+live_design!{
+    DrawEffectLayer = {{DrawEffectLayer}} {
+        texture image: texture2d
+
+        fn sample_layer(self, uv: vec2) -> vec4 {
+            return sample2d_rt(self.image, uv)
+        }
+
+        fn layer_uv(self) -> vec2 {
+            return self.uv_rect.xy + self.pos * self.uv_rect.zw
+        }
+
+        // a 13 tap binomial kernel folded into 7 bilinear fetches along blur_step
+        fn blur(self) -> vec4 {
+            let uv = self.layer_uv();
+            let s = self.blur_step;
+            return self.sample_layer(uv) * 0.19648255
+                + (self.sample_layer(uv + s * 1.41176470) + self.sample_layer(uv - s * 1.41176470)) * 0.29690696
+                + (self.sample_layer(uv + s * 3.29411764) + self.sample_layer(uv - s * 3.29411764)) * 0.09447039
+                + (self.sample_layer(uv + s * 5.17647058) + self.sample_layer(uv - s * 5.17647058)) * 0.01038136
+        }
+
+        fn pixel(self) -> vec4 {
+            return self.sample_layer(self.layer_uv())
+        }
+    }
+
+    ViewEffectBase = {{ViewEffect}} {
+        draw_blur: {
+            fn pixel(self) -> vec4 {
+                return self.blur()
+            }
+        }
+        draw_shadow: {
+            fn pixel(self) -> vec4 {
+                let alpha = self.sample_layer(self.layer_uv()).w;
+                return vec4(self.color.rgb * self.color.a, self.color.a) * alpha
+            }
+        }
+    }
+}
+
+// the binomial kernel in the blur shader has a standard deviation of sqrt(3) texels
+const KERNEL_SIGMA: f64 = 1.7320508;
+
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawEffectLayer {
+    #[deref] draw_super: DrawQuad,
+    // maps the quad onto the part of the source texture it samples, as offset and scale
+    #[live(vec4(0.0, 0.0, 1.0, 1.0))] uv_rect: Vec4,
+    #[live] blur_step: Vec2,
+    #[live] color: Vec4,
+}
+
+#[derive(Live, LiveHook)]
+#[live_ignore]
+pub enum ViewEffectKind {
+    #[pick]
+    None,
+    Blur,
+    DropShadow,
+    Backdrop,
+}
+
+// post-processing for a view: blur and drop shadow render the view into an offscreen pass and
+// draw the result, backdrop blurs what a texture cached view behind it rendered
+#[derive(Live, LiveHook, LiveRegister)]
+pub struct ViewEffect {
+    #[live] pub kind: ViewEffectKind,
+    // the blur radius in logical pixels, like the css blur() the standard deviation is half of it
+    #[live(8.0)] pub radius: f64,
+    #[live] pub offset: DVec2,
+    #[live] pub spread: f64,
+    #[live(vec4(0.0, 0.0, 0.0, 0.5))] pub color: Vec4,
+    #[live] draw_blur: DrawEffectLayer,
+    #[live] draw_layer: DrawEffectLayer,
+    #[live] draw_shadow: DrawEffectLayer,
+    #[rust] layer: Option<EffectLayer>,
+    #[rust] warned_no_backdrop: bool,
+}
+
+struct EffectPass {
+    pass: Pass,
+    texture: Texture,
+    draw_list: DrawList2d,
+}
+
+impl EffectPass {
+    fn new(cx: &mut Cx, name: &str) -> Self {
+        let pass = Pass::new_with_name(cx, name);
+        let texture = Texture::new_with_format(cx, TextureFormat::RenderBGRAu8 {
+            size: TextureSize::Auto
+        });
+        pass.add_color_texture(cx, &texture, PassClearColor::ClearWith(vec4(0.0, 0.0, 0.0, 0.0)));
+        Self {
+            pass,
+            texture,
+            draw_list: DrawList2d::new(cx),
+        }
+    }
+}
+
+// content is what the view draws, blur_x and blur_y are the two halves of the separable blur,
+// they are chained as content -> blur_x -> blur_y -> the pass the view is drawn in
+struct EffectLayer {
+    content: EffectPass,
+    blur_x: EffectPass,
+    blur_y: EffectPass,
+}
+
+// the texture cached views that a backdrop effect can blur, most recently drawn last
+#[derive(Default)]
+pub struct CxBackdropSources {
+    sources: Vec<BackdropSource>,
+    // source pass -> the blur pass that has to render after it
+    links: Vec<(PassId, PassId)>,
+}
+
+struct BackdropSource {
+    pass_id: PassId,
+    texture: Texture,
+    area: Area,
+}
+
+impl CxBackdropSources {
+    // called by texture cached views each time they draw their texture
+    pub fn register(cx: &mut Cx, pass: &Pass, texture: &Texture, area: Area) {
+        let sources = &mut cx.global::<CxBackdropSources>().sources;
+        sources.retain( | source | source.pass_id != pass.pass_id());
+        sources.push(BackdropSource {
+            pass_id: pass.pass_id(),
+            texture: texture.clone(),
+            area,
+        });
+    }
+
+    // making the cached view a child pass again would break the order the backdrop needs
+    pub fn relink(cx: &mut Cx, pass: &Pass) {
+        let blur_pass = cx.global::<CxBackdropSources>().links.iter()
+            .find( | (source, _) | *source == pass.pass_id())
+            .map( | (_, blur_pass) | *blur_pass);
+        if let Some(blur_pass) = blur_pass {
+            cx.passes[pass.pass_id()].parent = CxPassParent::Pass(blur_pass);
+        }
+    }
+
+    fn link(cx: &mut Cx, source: PassId, blur_pass: PassId) {
+        cx.passes[source].parent = CxPassParent::Pass(blur_pass);
+        let links = &mut cx.global::<CxBackdropSources>().links;
+        links.retain( | (s, _) | *s != source);
+        links.push((source, blur_pass));
+    }
+}
+
+// grows the rect outwards to whole device pixels
+fn snap_rect(rect: Rect, dpi: f64) -> Rect {
+    let pos = (rect.pos * dpi).floor() / dpi;
+    let end = ((rect.pos + rect.size) * dpi).ceil() / dpi;
+    Rect {pos, size: end - pos}
+}
+
+// 3 standard deviations catch all of the blur that spills out of the view
+fn blur_layer_rect(rect: Rect, radius: f64, dpi: f64) -> Rect {
+    let sigma = radius * 0.5;
+    snap_rect(rect.add_margin(dvec2(sigma * 3.0, sigma * 3.0)), dpi)
+}
+
+// the step between the blur taps in the uv space of a pass of the given size,
+// axis picks the direction and scales the step to the uv space of the source
+fn blur_step(size: DVec2, axis: DVec2, radius: f64) -> Vec2 {
+    let sigma = radius * 0.5;
+    let step = dvec2(axis.x / size.x.max(1.0), axis.y / size.y.max(1.0)) * (sigma / KERNEL_SIGMA);
+    vec2(step.x as f32, step.y as f32)
+}
+
+impl ViewEffect {
+    pub fn is_none(&self) -> bool {
+        matches!(self.kind, ViewEffectKind::None)
+    }
+
+    fn layer(&mut self, cx: &mut Cx) -> &mut EffectLayer {
+        self.layer.get_or_insert_with( || EffectLayer {
+            content: EffectPass::new(cx, "ViewEffect content"),
+            blur_x: EffectPass::new(cx, "ViewEffect blur x"),
+            blur_y: EffectPass::new(cx, "ViewEffect blur y"),
+        })
+    }
+
+    // call before the view begins its turtle
+    pub fn begin(&mut self, cx: &mut Cx2d) {
+        match self.kind {
+            ViewEffectKind::Blur | ViewEffectKind::DropShadow => {
+                let layer = self.layer(cx);
+                layer.content.pass.set_pass_parent(cx, &layer.blur_x.pass);
+                layer.blur_x.pass.set_pass_parent(cx, &layer.blur_y.pass);
+                cx.make_child_pass(&layer.blur_y.pass);
+                cx.begin_pass(&layer.content.pass, None);
+                layer.content.draw_list.begin_always(cx);
+            }
+            ViewEffectKind::Backdrop => {
+                // the backdrop goes below everything the view draws, its rect is set in end
+                let texture = self.layer(cx).blur_y.texture.clone();
+                self.draw_layer.draw_vars.set_texture(0, &texture);
+                self.draw_layer.uv_rect = vec4(0.0, 0.0, 1.0, 1.0);
+                self.draw_layer.draw_abs(cx, Rect::default());
+            }
+            ViewEffectKind::None => ()
+        }
+    }
+
+    // call after the view ended its turtle, with the rect the view ended up at
+    pub fn end(&mut self, cx: &mut Cx2d, rect: Rect) {
+        match self.kind {
+            ViewEffectKind::Blur | ViewEffectKind::DropShadow => self.end_layer(cx, rect),
+            ViewEffectKind::Backdrop => self.end_backdrop(cx, rect),
+            ViewEffectKind::None => ()
+        }
+    }
+
+    fn end_layer(&mut self, cx: &mut Cx2d, rect: Rect) {
+        let Some(layer) = &mut self.layer else {return};
+        layer.content.draw_list.end(cx);
+        cx.end_pass(&layer.content.pass);
+
+        let layer_rect = blur_layer_rect(rect, self.radius, cx.current_dpi_factor());
+        let full_uv = vec4(0.0, 0.0, 1.0, 1.0);
+        Self::draw_blur_pass(cx, &mut self.draw_blur, &mut layer.blur_x, &layer.content.texture, layer_rect, full_uv, dvec2(1.0, 0.0), self.radius);
+        Self::draw_blur_pass(cx, &mut self.draw_blur, &mut layer.blur_y, &layer.blur_x.texture, layer_rect, full_uv, dvec2(0.0, 1.0), self.radius);
+
+        if let ViewEffectKind::DropShadow = self.kind {
+            let shadow_rect = layer_rect.translate(self.offset).add_margin(dvec2(self.spread, self.spread));
+            self.draw_shadow.color = self.color;
+            self.draw_shadow.draw_vars.set_texture(0, &layer.blur_y.texture);
+            self.draw_shadow.draw_abs(cx, shadow_rect);
+            self.draw_layer.draw_vars.set_texture(0, &layer.content.texture);
+        }
+        else {
+            self.draw_layer.draw_vars.set_texture(0, &layer.blur_y.texture);
+        }
+        self.draw_layer.uv_rect = full_uv;
+        self.draw_layer.draw_abs(cx, layer_rect);
+        let area = self.draw_layer.area();
+        cx.set_pass_area(&layer.content.pass, area);
+        cx.set_pass_area(&layer.blur_x.pass, area);
+        cx.set_pass_area(&layer.blur_y.pass, area);
+    }
+
+    fn end_backdrop(&mut self, cx: &mut Cx2d, rect: Rect) {
+        let dpi = cx.current_dpi_factor();
+        let rect = snap_rect(rect, dpi);
+        // the topmost cached view under the center of this one, that is not one we are drawn into
+        let center = rect.center();
+        let sources: Vec<(PassId, Texture, Area)> = cx.global::<CxBackdropSources>().sources.iter().rev()
+            .map( | source | (source.pass_id, source.texture.clone(), source.area))
+            .collect();
+        let source = sources.into_iter().find( | (pass_id, _, area) | {
+            !cx.is_pass_on_stack(*pass_id) && area.is_valid(cx) && area.rect(cx).contains(center)
+        });
+        let Some((source_pass, source_texture, _)) = source else {
+            if !self.warned_no_backdrop {
+                self.warned_no_backdrop = true;
+                error!("ViewEffect: backdrop needs a texture cached view (optimize: Texture) behind it");
+            }
+            self.draw_layer.update_abs(cx, Rect::default());
+            return
+        };
+        let Some(source_rect) = cx.get_pass_rect(source_pass, dpi) else {return};
+        if source_rect.size.x <= 0.0 || source_rect.size.y <= 0.0 {
+            return
+        }
+        let Some(layer) = &mut self.layer else {return};
+        CxBackdropSources::link(cx, source_pass, layer.blur_x.pass.pass_id());
+        layer.blur_x.pass.set_pass_parent(cx, &layer.blur_y.pass);
+        cx.make_child_pass(&layer.blur_y.pass);
+
+        let source_uv = vec4(
+            ((rect.pos.x - source_rect.pos.x) / source_rect.size.x) as f32,
+            ((rect.pos.y - source_rect.pos.y) / source_rect.size.y) as f32,
+            (rect.size.x / source_rect.size.x) as f32,
+            (rect.size.y / source_rect.size.y) as f32,
+        );
+        let source_step = dvec2(rect.size.x / source_rect.size.x, rect.size.y / source_rect.size.y);
+        Self::draw_blur_pass(cx, &mut self.draw_blur, &mut layer.blur_x, &source_texture, rect, source_uv, dvec2(source_step.x, 0.0), self.radius);
+        Self::draw_blur_pass(cx, &mut self.draw_blur, &mut layer.blur_y, &layer.blur_x.texture, rect, vec4(0.0, 0.0, 1.0, 1.0), dvec2(0.0, 1.0), self.radius);
+
+        self.draw_layer.update_abs(cx, rect);
+        let area = self.draw_layer.area();
+        cx.set_pass_area(&layer.blur_x.pass, area);
+        cx.set_pass_area(&layer.blur_y.pass, area);
+    }
+
+    // one direction of the blur
+    #[allow(clippy::too_many_arguments)]
+    fn draw_blur_pass(cx: &mut Cx2d, draw_blur: &mut DrawEffectLayer, target: &mut EffectPass, source: &Texture, rect: Rect, uv_rect: Vec4, axis: DVec2, radius: f64) {
+        cx.begin_pass(&target.pass, None);
+        target.draw_list.begin_always(cx);
+        draw_blur.draw_vars.set_texture(0, source);
+        draw_blur.uv_rect = uv_rect;
+        draw_blur.blur_step = blur_step(rect.size, axis, radius);
+        draw_blur.draw_abs(cx, rect);
+        target.draw_list.end(cx);
+        cx.end_pass(&target.pass);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn parent(cx: &Cx, pass: &Pass) -> Option<PassId> {
+        match cx.passes[pass.pass_id()].parent {
+            CxPassParent::Pass(pass_id) => Some(pass_id),
+            _ => None
+        }
+    }
+    
+    #[test]
+    fn blur_step_follows_the_radius() {
+        let size = dvec2(100.0, 50.0);
+        // a radius of 2 sigma makes the kernel sigma one texel
+        let step = blur_step(size, dvec2(1.0, 0.0), 2.0 * KERNEL_SIGMA);
+        assert!((step.x - 0.01).abs() < 1e-6 && step.y == 0.0);
+        let step = blur_step(size, dvec2(0.0, 1.0), 4.0 * KERNEL_SIGMA);
+        assert!(step.x == 0.0 && (step.y - 0.04).abs() < 1e-6);
+        // the axis scales the step into the uv space of a larger source
+        let step = blur_step(size, dvec2(0.5, 0.0), 2.0 * KERNEL_SIGMA);
+        assert!((step.x - 0.005).abs() < 1e-6);
+        assert_eq!(blur_step(size, dvec2(1.0, 0.0), 0.0), vec2(0.0, 0.0));
+        // an empty pass doesn't divide by zero
+        assert_eq!(blur_step(DVec2::default(), dvec2(1.0, 1.0), 8.0), blur_step(dvec2(1.0, 1.0), dvec2(1.0, 1.0), 8.0));
+    }
+    
+    #[test]
+    fn blur_layer_pads_three_sigma_and_snaps_outwards() {
+        let rect = Rect {pos: dvec2(10.0, 20.0), size: dvec2(30.0, 40.0)};
+        let padded = blur_layer_rect(rect, 8.0, 1.0);
+        assert_eq!((padded.pos, padded.size), (dvec2(-2.0, 8.0), dvec2(54.0, 64.0)));
+        assert_eq!(blur_layer_rect(rect, 0.0, 1.0), rect);
+        
+        let rect = Rect {pos: dvec2(0.3, 0.6), size: dvec2(1.0, 1.0)};
+        let snapped = snap_rect(rect, 2.0);
+        assert_eq!((snapped.pos, snapped.size), (dvec2(0.0, 0.5), dvec2(1.5, 1.5)));
+        let snapped = snap_rect(rect, 1.0);
+        assert_eq!((snapped.pos, snapped.size), (dvec2(0.0, 0.0), dvec2(2.0, 2.0)));
+    }
+    
+    #[test]
+    fn backdrop_sources_are_kept_in_draw_order() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        let texture = Texture::new(&mut cx);
+        let passes: Vec<_> = (0..3).map( | _ | Pass::new_with_name(&mut cx, "source")).collect();
+        for pass in passes.iter().chain([&passes[0]]) {
+            CxBackdropSources::register(&mut cx, pass, &texture, Area::Empty);
+        }
+        let order: Vec<_> = cx.global::<CxBackdropSources>().sources.iter().map( | source | source.pass_id).collect();
+        assert_eq!(order, [passes[1].pass_id(), passes[2].pass_id(), passes[0].pass_id()]);
+    }
+    
+    #[test]
+    fn relink_restores_the_blur_pass_as_parent() {
+        let mut cx = Cx::new(Box::new( | _, _ | {}));
+        let source = Pass::new_with_name(&mut cx, "source");
+        let other = Pass::new_with_name(&mut cx, "other");
+        let blur_a = Pass::new_with_name(&mut cx, "blur a");
+        let blur_b = Pass::new_with_name(&mut cx, "blur b");
+        let window = Pass::new_with_name(&mut cx, "window");
+        
+        CxBackdropSources::link(&mut cx, source.pass_id(), blur_a.pass_id());
+        assert_eq!(parent(&cx, &source), Some(blur_a.pass_id()));
+        // a later link of the same source replaces the earlier one
+        CxBackdropSources::link(&mut cx, source.pass_id(), blur_b.pass_id());
+        assert_eq!(cx.global::<CxBackdropSources>().links, [(source.pass_id(), blur_b.pass_id())]);
+        
+        source.set_pass_parent(&mut cx, &window);
+        CxBackdropSources::relink(&mut cx, &source);
+        assert_eq!(parent(&cx, &source), Some(blur_b.pass_id()));
+        
+        other.set_pass_parent(&mut cx, &window);
+        CxBackdropSources::relink(&mut cx, &other);
+        assert_eq!(parent(&cx, &other), Some(window.pass_id()));
+    }
+}
@@ -1,5 +1,6 @@
 use {
     crate::makepad_draw::*,
+    crate::makepad_platform::studio::{AnimatorTrackSample, WidgetTreeNode},
     std::fmt::{Formatter, Debug, Error},
     std::collections::BTreeMap,
     std::any::TypeId,
@@ -26,6 +27,11 @@ pub trait WidgetNode: LiveApply{
     fn find_widgets(&mut self, _path: &[LiveId], _cached: WidgetCache, _results: &mut WidgetSet);
     fn walk(&mut self, _cx:&mut Cx) -> Walk;
     fn redraw(&mut self, _cx: &mut Cx);
+    // the widget tree that the studio inspector shows is walked with these
+    fn child_widgets(&self, _children: &mut Vec<(LiveId, WidgetRef)>) {}
+    fn area(&self) -> Area {Area::Empty}
+    fn animator(&self) -> Option<&Animator> {None}
+    fn animator_play_delayed(&mut self, _cx: &mut Cx, _state: &[LiveId; 2], _delay: f64) {}
 }
 
 pub trait Widget: WidgetNode {
@@ -497,6 +503,58 @@ impl WidgetRef {
         }
     }
     
+    // plays an animator state after delay seconds, widgets without an animator ignore it
+    pub fn animator_play_delayed(&self, cx: &mut Cx, state: &[LiveId; 2], delay: f64) {
+        if let Some(inner) = self.0.borrow_mut().as_mut() {
+            inner.widget.animator_play_delayed(cx, state, delay);
+        }
+    }
+    
+    // plays a state on the child widgets in order, each one interval seconds after the previous
+    pub fn animator_play_staggered(&self, cx: &mut Cx, state: &[LiveId; 2], interval: f64) {
+        let mut children = Vec::new();
+        if let Some(inner) = self.0.borrow().as_ref() {
+            inner.widget.child_widgets(&mut children);
+        }
+        for (i, (_, child)) in children.iter().enumerate() {
+            child.animator_play_delayed(cx, state, i as f64 * interval);
+        }
+    }
+    
+    // appends this widget and the widgets in it to the tree that is sent to the studio inspector
+    pub fn widget_tree(&self, cx: &Cx, name: LiveId, depth: u32, nodes: &mut Vec<WidgetTreeNode>) {
+        let mut children = Vec::new();
+        if let Some(inner) = self.0.borrow().as_ref() {
+            let class = cx.live_registry.borrow().components.get::<WidgetRegistry>().map
+                .get(&inner.widget.ref_cast_type_id())
+                .map(|(info, _)| info.name.to_string())
+                .unwrap_or_default();
+            let rect = inner.widget.area().rect(cx);
+            let animator = inner.widget.animator().map(|animator| {
+                animator.track_states(cx).into_iter().map(|(track, state, playing)| AnimatorTrackSample {
+                    track: track.to_string(),
+                    state: state.to_string(),
+                    playing,
+                    progress: animator.track_progress(track),
+                }).collect()
+            }).unwrap_or_default();
+            nodes.push(WidgetTreeNode {
+                depth,
+                name: name.to_string(),
+                class,
+                x: rect.pos.x,
+                y: rect.pos.y,
+                width: rect.size.x,
+                height: rect.size.y,
+                animator,
+            });
+            inner.widget.child_widgets(&mut children);
+        }
+        for (name, child) in children {
+            child.widget_tree(cx, name, depth + 1, nodes);
+        }
+    }
+    
     pub fn borrow_mut<T: 'static + Widget>(&self) -> Option<std::cell::RefMut<'_, T >> {
         if let Ok(ret) = std::cell::RefMut::filter_map(self.0.borrow_mut(), | inner | {
             if let Some(inner) = inner.as_mut() {
@@ -3,7 +3,9 @@ use crate::{
     debug_view::DebugView,
     performance_view::PerformanceView,
     makepad_draw::*,
+    makepad_platform::studio::{AppToStudio, WidgetTreeNode},
     nav_control::NavControl,
+    keymap::Keymap,
     button::*,
     view::*,
     widget::*,
@@ -25,6 +27,7 @@ pub struct Window {
     #[live] debug_view: DebugView,
     #[live] performance_view: PerformanceView,
     #[live] nav_control: NavControl,
+    #[live] keymap: Keymap,
     #[live] window: WindowHandle,
     #[live] stdin_size: DrawColor,
     #[live] overlay: Overlay,
@@ -132,6 +135,37 @@ impl Window {
         Redrawing::yes()
     }
     
+    // publishes the accessibility tree of the window, built from the nav stops its widgets added
+    fn update_access_tree(&self, cx: &mut Cx) {
+        let window_id = self.window.window_id();
+        let window = &cx.windows[window_id];
+        let geom = &window.window_geom;
+        let mut tree = AccessTree::new(window_id, &window.create_title, geom.position, geom.inner_size, geom.dpi_factor);
+        Cx2d::build_access_tree(cx, self.main_draw_list.draw_list_id(), &mut tree);
+        cx.update_access_tree(tree);
+    }
+    
+    // the window is the root of the tree, with the widgets in its view below it
+    fn send_widget_tree(&self, cx: &Cx) {
+        let rect = self.view.area().rect(cx);
+        let mut nodes = vec![WidgetTreeNode {
+            depth: 0,
+            name: "window".to_string(),
+            class: "Window".to_string(),
+            x: rect.pos.x,
+            y: rect.pos.y,
+            width: rect.size.x,
+            height: rect.size.y,
+            animator: Vec::new(),
+        }];
+        let mut children = Vec::new();
+        self.view.child_widgets(&mut children);
+        for (name, child) in children {
+            child.widget_tree(cx, name, 1, &mut nodes);
+        }
+        Cx::send_studio_message(AppToStudio::WidgetTree(nodes));
+    }
+    
     pub fn end(&mut self, cx: &mut Cx2d) {
         //while self.frame.draw_widget_continue(cx).is_not_done() {}
         self.debug_view.draw(cx);
@@ -300,6 +334,10 @@ impl Widget for Window {
         if self.draw_state.begin(cx, DrawState::Drawing) {
             if self.begin(cx).is_not_redrawing() {
                 self.draw_state.end();
+                // a draw list inside the window may still have redrawn
+                if cx.accessibility_enabled() {
+                    self.update_access_tree(cx);
+                }
                 return DrawStep::done();
             }
         }
@@ -308,6 +346,12 @@ impl Widget for Window {
             self.view.draw_walk(cx, scope, walk)?;
             self.draw_state.end();
             self.end(cx);
+            if cx.widget_tree_requested() {
+                self.send_widget_tree(cx);
+            }
+            if cx.accessibility_enabled() {
+                self.update_access_tree(cx);
+            }
         }
         
         DrawStep::done()
@@ -2,11 +2,30 @@
 use crate::{
     makepad_derive_widget::*,
     makepad_draw::*,
+    popup_menu::{PopupMenu, PopupMenuAction, PopupMenuItemId},
     widget::*,
 };
 use std::collections::HashMap;
 
 live_design!{
+    import makepad_draw::shader::std::*;
+
+    DrawMenuBarItem = {{DrawMenuBarItem}} {
+        instance color_hover: #0000
+        instance color_active: #0000
+
+        fn pixel(self) -> vec4 {
+            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+            sdf.box(1., 1., self.rect_size.x - 2., self.rect_size.y - 2., 2.);
+            sdf.fill(mix(
+                mix(#0000, self.color_hover, self.hover),
+                self.color_active,
+                self.active
+            ));
+            return sdf.result;
+        }
+    }
+
     WindowMenuBase = {{WindowMenu}}{
     }
 }
@@ -32,15 +51,40 @@ pub enum WindowMenuItem {
     Line
 }
 
+#[derive(Live, LiveHook, LiveRegister)]
+#[repr(C)]
+pub struct DrawMenuBarItem {
+    #[deref] draw_super: DrawQuad,
+    #[live] hover: f32,
+    #[live] active: f32,
+}
+
+// on macos the items go into the system menu bar, everywhere else the widget draws a menu bar
+// of its own with the submenus as popup menus
 #[derive(Live, Widget)]
 pub struct WindowMenu{
     #[walk] walk: Walk,
-    #[redraw] #[rust] area: Area,
     #[layout] layout: Layout,
+    #[redraw] #[live] draw_bg: DrawQuad,
+    #[live] draw_item: DrawMenuBarItem,
+    #[live] draw_text: DrawText,
+    #[live] item_walk: Walk,
+    #[live] item_layout: Layout,
+    #[live] popup_menu: Option<LivePtr>,
     #[rust] menu_items: HashMap<LiveId, WindowMenuItem>,
+    #[rust] bar_items: Vec<(LiveId, Area)>,
+    // one popup per level of open submenus
+    #[rust] popups: Vec<PopupMenu>,
+    // the open submenus, starting at a menu in the bar, with the keyboard selection in each
+    #[rust] open: Vec<(LiveId, Option<usize>)>,
+    #[rust] bar_focus: Option<usize>,
+    #[rust] bar_hover: Option<usize>,
+    #[rust] hover_dirty: bool,
+    // alt went down without another key, letting go of it moves the keys to the bar
+    #[rust] alt_alone: bool,
 }
 
-#[derive(Clone, DefaultNone)]
+#[derive(Clone, Debug, DefaultNone)]
 pub enum WindowMenuAction {
     Command(LiveId),
     None
@@ -69,6 +113,27 @@ impl LiveHook for WindowMenu {
         nodes.skip_node(index)
     }
     
+    fn after_apply(&mut self, cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        if !apply.from.is_from_doc() {
+            return
+        }
+        self.popups.clear();
+        self.open.clear();
+        self.bar_focus = None;
+        // the menu items become commands, so their keys work on every platform
+        cx.clear_key_bindings(live_id!(window_menu));
+        for (command, item) in &self.menu_items {
+            if let WindowMenuItem::Item{name, shift, key, enabled: true} = item {
+                cx.register_command(*command, name);
+                if !key.is_unknown() {
+                    let modifiers = KeyModifiers {shift: *shift, ..cx.primary_key_modifiers()};
+                    let chord = KeyChord(vec![KeyStroke::new(*key, modifiers)]);
+                    cx.bind_command_chord(live_id!(window_menu), *command, chord, None);
+                }
+            }
+        }
+    }
+    
     fn after_new_from_doc(&mut self, _cx: &mut Cx) {
         // lets translate the menu into a macos menu
         #[cfg(target_os="macos")]{
@@ -87,7 +152,7 @@ impl LiveHook for WindowMenu {
                         WindowMenuItem::Item{name, shift, key, enabled}=>{
                             return MacosMenu::Item{
                                 command,
-                                name,
+                                name: menu_label(&name),
                                 shift,
                                 key,
                                 enabled
@@ -98,7 +163,7 @@ impl LiveHook for WindowMenu {
                             for item in items{
                                 out.push(recur_menu(item, menu_items));
                             }
-                            return MacosMenu::Sub{name, items:out}
+                            return MacosMenu::Sub{name: menu_label(&name), items:out}
                         }
                         WindowMenuItem::Line=>{
                             return MacosMenu::Line
@@ -118,20 +183,415 @@ impl LiveHook for WindowMenu {
 }
 
 
+// the name a menu shows, an & marks the mnemonic and && stands for an &
+fn menu_label(name: &str) -> String {
+    let mut out = String::new();
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c == '&' {
+            if let Some(next) = chars.next() {
+                out.push(next);
+            }
+        }
+        else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+// the letter that picks a menu from the keyboard, the one after an & or else the first letter
+fn menu_mnemonic(name: &str) -> Option<char> {
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        if c == '&' {
+            match chars.next() {
+                Some('&') => (),
+                Some(c) => return Some(c.to_ascii_lowercase()),
+                None => break
+            }
+        }
+    }
+    name.chars().find( | c | c.is_alphanumeric()).map( | c | c.to_ascii_lowercase())
+}
+
+// steps from the index in dir until an index passes the check, starting before the first or
+// after the last one without an index, and gives up after going around once
+fn wrapping_next(len: usize, from: Option<usize>, dir: isize, check: impl Fn(usize) -> bool) -> Option<usize> {
+    let len = len as isize;
+    let mut index = match from {
+        Some(index) => index as isize,
+        None => if dir > 0 {-1} else {len}
+    };
+    for _ in 0..len {
+        index = (index + dir).rem_euclid(len);
+        if check(index as usize) {
+            return Some(index as usize)
+        }
+    }
+    None
+}
+
+impl WindowMenu {
+    fn bar(&self) -> Vec<LiveId> {
+        if let Some(WindowMenuItem::Main{items}) = self.menu_items.get(&live_id!(main)) {
+            items.clone()
+        }
+        else {
+            Vec::new()
+        }
+    }
+    
+    fn sub_items(&self, sub: LiveId) -> Vec<LiveId> {
+        if let Some(WindowMenuItem::Sub{items, ..}) = self.menu_items.get(&sub) {
+            items.clone()
+        }
+        else {
+            Vec::new()
+        }
+    }
+    
+    fn item_name(&self, item: LiveId) -> &str {
+        match self.menu_items.get(&item) {
+            Some(WindowMenuItem::Item{name, ..}) | Some(WindowMenuItem::Sub{name, ..}) => name,
+            _ => ""
+        }
+    }
+    
+    fn is_selectable(&self, item: LiveId) -> bool {
+        matches!(self.menu_items.get(&item), Some(WindowMenuItem::Item{enabled: true, ..}) | Some(WindowMenuItem::Sub{..}))
+    }
+    
+    // the next item of a submenu the keyboard can land on, wrapping around
+    fn next_selectable(&self, sub: LiveId, from: Option<usize>, dir: isize) -> Option<usize> {
+        let items = self.sub_items(sub);
+        wrapping_next(items.len(), from, dir, | index | self.is_selectable(items[index]))
+    }
+    
+    fn redraw_menus(&mut self, cx: &mut Cx) {
+        self.hover_dirty = true;
+        self.draw_bg.redraw(cx);
+        for popup in &mut self.popups {
+            popup.redraw(cx);
+        }
+    }
+    
+    fn open_menu(&mut self, cx: &mut Cx, index: usize, keyboard: bool) {
+        let Some(sub) = self.bar().get(index).cloned() else {return};
+        let selected = if keyboard {self.next_selectable(sub, None, 1)} else {None};
+        self.open = vec![(sub, selected)];
+        self.bar_focus = Some(index);
+        if !cx.has_key_focus(self.draw_bg.area()) {
+            cx.set_key_focus(self.draw_bg.area());
+        }
+        // keeps the pointer on the bar and the popups while a menu is open
+        cx.sweep_lock(self.draw_bg.area());
+        self.redraw_menus(cx);
+    }
+    
+    fn close_menus(&mut self, cx: &mut Cx) {
+        if !self.open.is_empty() {
+            self.open.clear();
+            cx.sweep_unlock(self.draw_bg.area());
+            self.redraw_menus(cx);
+        }
+    }
+    
+    fn close(&mut self, cx: &mut Cx) {
+        self.close_menus(cx);
+        if self.bar_focus.take().is_some() {
+            self.redraw_menus(cx);
+        }
+        if cx.has_key_focus(self.draw_bg.area()) {
+            cx.revert_key_focus();
+        }
+    }
+    
+    // runs an item of an open submenu, or opens the next level when it is a submenu itself
+    fn activate(&mut self, cx: &mut Cx, level: usize, index: usize) {
+        let Some(item) = self.sub_items(self.open[level].0).get(index).cloned() else {return};
+        match self.menu_items.get(&item) {
+            Some(WindowMenuItem::Item{enabled: true, ..}) => {
+                self.close(cx);
+                cx.send_command(item);
+            }
+            Some(WindowMenuItem::Sub{..}) => {
+                self.open.truncate(level + 1);
+                self.open[level].1 = Some(index);
+                let selected = self.next_selectable(item, None, 1);
+                self.open.push((item, selected));
+                self.redraw_menus(cx);
+            }
+            _ => ()
+        }
+    }
+    
+    fn activate_mnemonic(&mut self, cx: &mut Cx, c: char) -> bool {
+        if let Some((sub, _)) = self.open.last().cloned() {
+            let level = self.open.len() - 1;
+            let index = self.sub_items(sub).iter().position( | item | {
+                self.is_selectable(*item) && menu_mnemonic(self.item_name(*item)) == Some(c)
+            });
+            if let Some(index) = index {
+                self.activate(cx, level, index);
+                return true
+            }
+            return false
+        }
+        if let Some(index) = self.bar().iter().position( | item | menu_mnemonic(self.item_name(*item)) == Some(c)) {
+            self.open_menu(cx, index, true);
+            return true
+        }
+        false
+    }
+    
+    fn handle_key_down(&mut self, cx: &mut Cx, ke: &KeyEvent) {
+        let bar_len = self.bar().len();
+        if bar_len == 0 {
+            return
+        }
+        let current = self.bar_focus.unwrap_or(0);
+        match ke.key_code {
+            KeyCode::Escape => {
+                if self.open.len() > 1 {
+                    self.open.pop();
+                    self.redraw_menus(cx);
+                }
+                else if !self.open.is_empty() {
+                    self.close_menus(cx);
+                }
+                else {
+                    self.close(cx);
+                }
+            }
+            KeyCode::ArrowLeft | KeyCode::ArrowRight => {
+                let right = ke.key_code == KeyCode::ArrowRight;
+                if let Some((sub, Some(index))) = self.open.last().cloned() {
+                    let item = self.sub_items(sub)[index];
+                    if right && matches!(self.menu_items.get(&item), Some(WindowMenuItem::Sub{..})) {
+                        self.activate(cx, self.open.len() - 1, index);
+                        return
+                    }
+                }
+                if !right && self.open.len() > 1 {
+                    self.open.pop();
+                    self.redraw_menus(cx);
+                    return
+                }
+                let next = if right {(current + 1) % bar_len} else {(current + bar_len - 1) % bar_len};
+                if self.open.is_empty() {
+                    self.bar_focus = Some(next);
+                    self.redraw_menus(cx);
+                }
+                else {
+                    self.open_menu(cx, next, true);
+                }
+            }
+            KeyCode::ArrowUp | KeyCode::ArrowDown => {
+                let Some((sub, selected)) = self.open.last().cloned() else {
+                    self.open_menu(cx, current, true);
+                    return
+                };
+                let dir = if ke.key_code == KeyCode::ArrowDown {1} else {-1};
+                let level = self.open.len() - 1;
+                self.open[level].1 = self.next_selectable(sub, selected, dir);
+                self.redraw_menus(cx);
+            }
+            KeyCode::ReturnKey | KeyCode::Space => {
+                match self.open.last().cloned() {
+                    Some((_, Some(index))) => self.activate(cx, self.open.len() - 1, index),
+                    Some((_, None)) => (),
+                    None => self.open_menu(cx, current, true)
+                }
+            }
+            key_code => if let Some(c) = key_code.to_char(false) {
+                self.activate_mnemonic(cx, c);
+            }
+        }
+    }
+    
+    fn draw_menus(&mut self, cx: &mut Cx2d) {
+        while self.popups.len() < self.open.len() {
+            self.popups.push(PopupMenu::new_from_ptr(cx, self.popup_menu));
+        }
+        let hover_dirty = std::mem::take(&mut self.hover_dirty);
+        for level in 0..self.open.len() {
+            let (sub, selected) = self.open[level];
+            let items = self.sub_items(sub);
+            // a menu hangs below its item in the bar, a submenu beside its item in the parent menu
+            let (area, shift) = if level == 0 {
+                let area = self.bar_items.iter().find( | (id, _) | *id == sub).map( | (_, area) | *area).unwrap_or_default();
+                (area, dvec2(0.0, area.rect(cx).size.y))
+            }
+            else {
+                let area = self.popups[level - 1].item_area(PopupMenuItemId(sub));
+                (area, dvec2(area.rect(cx).size.x, 0.0))
+            };
+            let popup = &mut self.popups[level];
+            popup.begin(cx);
+            for item in &items {
+                match self.menu_items.get(item) {
+                    Some(WindowMenuItem::Item{name, shift, key, enabled}) => {
+                        // disabled items have no binding, they still show the key they would have
+                        let key_label = cx.command_key_label(*item).or_else( || {
+                            (!key.is_unknown()).then( || {
+                                let modifiers = KeyModifiers {shift: *shift, ..cx.primary_key_modifiers()};
+                                cx.key_chord_label(&KeyChord(vec![KeyStroke::new(*key, modifiers)]))
+                            })
+                        });
+                        popup.draw_item_with_key(cx, PopupMenuItemId(*item), &menu_label(name), key_label.as_deref(), *enabled);
+                    }
+                    Some(WindowMenuItem::Sub{name, ..}) => {
+                        popup.draw_item_with_key(cx, PopupMenuItemId(*item), &menu_label(name), Some(">"), true);
+                    }
+                    Some(WindowMenuItem::Line) => popup.draw_separator(cx),
+                    _ => ()
+                }
+            }
+            popup.end(cx, area, shift);
+            if hover_dirty {
+                let hover = selected.map( | index | items[index]).unwrap_or_default();
+                popup.hover_item(cx, PopupMenuItemId(hover));
+            }
+        }
+    }
+}
+
 impl Widget for WindowMenu {
     
-    fn handle_event(&mut self, cx: &mut Cx, event: &Event, _scope:&mut Scope) {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope:&mut Scope) {
         match event{
-            Event::MacosMenuCommand(item)=>{
+            Event::Command(item) if matches!(self.menu_items.get(item), Some(WindowMenuItem::Item{enabled: true, ..}))=>{
                 if *item == live_id!(quit){
                     cx.quit();
                 }
+                cx.widget_action(self.widget_uid(), &scope.path, WindowMenuAction::Command(*item));
             }
             _=>()
         }
+        if cfg!(target_os = "macos") {
+            return
+        }
+        
+        let bar_area = self.draw_bg.area();
+        if !cx.has_key_focus(bar_area) {
+            match event {
+                Event::KeyDown(ke) => {
+                    self.alt_alone = ke.key_code == KeyCode::Alt;
+                    let KeyModifiers {alt, control, logo, ..} = ke.modifiers;
+                    if alt && !control && !logo {
+                        if let Some(c) = ke.key_code.to_char(false).filter( | c | c.is_alphanumeric()) {
+                            self.activate_mnemonic(cx, c);
+                        }
+                    }
+                }
+                Event::KeyUp(ke) if ke.key_code == KeyCode::Alt && self.alt_alone && !self.bar().is_empty() => {
+                    self.alt_alone = false;
+                    self.bar_focus = Some(0);
+                    cx.set_key_focus(bar_area);
+                    self.redraw_menus(cx);
+                }
+                _ => ()
+            }
+        }
+        
+        if !self.open.is_empty() {
+            let mut selected = None;
+            for level in 0..self.open.len().min(self.popups.len()) {
+                self.popups[level].handle_event_with(cx, event, bar_area, &mut | _, action | {
+                    if let PopupMenuAction::WasSelected(id) = action {
+                        selected = Some((level, id.0));
+                    }
+                });
+            }
+            if let Some((level, item)) = selected {
+                if let Some(index) = self.sub_items(self.open[level].0).iter().position( | other | *other == item) {
+                    self.activate(cx, level, index);
+                }
+                return
+            }
+            if let Event::MouseDown(e) = event {
+                let inside = bar_area.clipped_rect(cx).contains(e.abs) || self.popups.iter()
+                    .take(self.open.len())
+                    .any( | popup | popup.menu_contains_pos(cx, e.abs));
+                if !inside {
+                    self.close(cx);
+                    return
+                }
+            }
+        }
+        
+        for index in 0..self.bar_items.len() {
+            let (id, area) = self.bar_items[index];
+            match event.hits_with_sweep_area(cx, area, bar_area) {
+                Hit::FingerDown(_) => {
+                    if self.open.first().map( | (sub, _) | *sub) == Some(id) {
+                        self.close(cx);
+                    }
+                    else {
+                        self.open_menu(cx, index, false);
+                    }
+                    return
+                }
+                Hit::FingerHoverIn(_) => {
+                    self.bar_hover = Some(index);
+                    if self.open.first().is_some_and( | (sub, _) | *sub != id) {
+                        self.open_menu(cx, index, false);
+                    }
+                    self.draw_bg.redraw(cx);
+                }
+                Hit::FingerHoverOut(_) if self.bar_hover == Some(index) => {
+                    self.bar_hover = None;
+                    self.draw_bg.redraw(cx);
+                }
+                _ => ()
+            }
+        }
+        
+        match event.hits_with_sweep_area(cx, bar_area, bar_area) {
+            Hit::KeyDown(ke) => {
+                self.alt_alone = ke.key_code == KeyCode::Alt;
+                if !self.alt_alone {
+                    self.handle_key_down(cx, &ke);
+                }
+            }
+            Hit::KeyUp(ke) if ke.key_code == KeyCode::Alt && self.alt_alone => {
+                self.alt_alone = false;
+                self.close(cx);
+            }
+            Hit::KeyFocusLost(_) => {
+                self.close_menus(cx);
+                if self.bar_focus.take().is_some() {
+                    self.redraw_menus(cx);
+                }
+            }
+            _ => ()
+        }
     }
     
-    fn draw_walk(&mut self, _cx: &mut Cx2d, _scope:&mut Scope, _walk: Walk) -> DrawStep {
+    fn draw_walk(&mut self, cx: &mut Cx2d, _scope:&mut Scope, walk: Walk) -> DrawStep {
+        self.bar_items.clear();
+        // the macos menu bar shows these items already
+        if cfg!(target_os = "macos") {
+            return DrawStep::done()
+        }
+        let bar = self.bar();
+        if bar.is_empty() {
+            return DrawStep::done()
+        }
+        self.draw_bg.begin(cx, walk, self.layout);
+        for (index, id) in bar.iter().enumerate() {
+            let active = self.bar_focus == Some(index) || self.open.first().map( | (sub, _) | *sub) == Some(*id);
+            self.draw_item.active = if active {1.0} else {0.0};
+            self.draw_item.hover = if self.bar_hover == Some(index) {1.0} else {0.0};
+            self.draw_item.begin(cx, self.item_walk, self.item_layout);
+            let label = menu_label(self.item_name(*id));
+            self.draw_text.draw_walk(cx, Walk::fit(), Align::default(), &label);
+            self.draw_item.end(cx);
+            self.bar_items.push((*id, self.draw_item.area()));
+        }
+        self.draw_bg.end(cx);
+        self.draw_menus(cx);
         DrawStep::done()
     }
 }
@@ -145,3 +605,51 @@ impl WindowMenuRef {
     }
 }
     
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn next(enabled: &[bool], from: Option<usize>, dir: isize) -> Option<usize> {
+        wrapping_next(enabled.len(), from, dir, | index | enabled[index])
+    }
+    
+    #[test]
+    fn menu_labels_drop_the_mnemonic_marker() {
+        assert_eq!(menu_label("&File"), "File");
+        assert_eq!(menu_label("Save &As"), "Save As");
+        assert_eq!(menu_label("Find && Replace"), "Find & Replace");
+        assert_eq!(menu_label("&&&Edit"), "&Edit");
+        assert_eq!(menu_label("Trailing&"), "Trailing");
+        assert_eq!(menu_label(""), "");
+    }
+    
+    #[test]
+    fn menu_mnemonics_follow_the_marker_or_the_first_letter() {
+        assert_eq!(menu_mnemonic("&File"), Some('f'));
+        assert_eq!(menu_mnemonic("Save &As"), Some('a'));
+        // && is a literal & and not a marker
+        assert_eq!(menu_mnemonic("Find && &Replace"), Some('r'));
+        assert_eq!(menu_mnemonic("Find && Replace"), Some('f'));
+        assert_eq!(menu_mnemonic("&&Edit"), Some('e'));
+        assert_eq!(menu_mnemonic("Trailing&"), Some('t'));
+        assert_eq!(menu_mnemonic("...2D View"), Some('2'));
+        assert_eq!(menu_mnemonic("&"), None);
+        assert_eq!(menu_mnemonic(""), None);
+    }
+    
+    #[test]
+    fn keyboard_selection_skips_disabled_items_and_wraps() {
+        let enabled = [false, true, false, true];
+        assert_eq!(next(&enabled, None, 1), Some(1));
+        assert_eq!(next(&enabled, None, -1), Some(3));
+        assert_eq!(next(&enabled, Some(1), 1), Some(3));
+        assert_eq!(next(&enabled, Some(3), 1), Some(1));
+        assert_eq!(next(&enabled, Some(1), -1), Some(3));
+        // with one selectable item the selection stays on it
+        assert_eq!(next(&[false, true, false], Some(1), 1), Some(1));
+        assert_eq!(next(&[false, false, false], None, 1), None);
+        assert_eq!(next(&[false, false, false], Some(1), -1), None);
+        assert_eq!(next(&[], None, 1), None);
+    }
+}